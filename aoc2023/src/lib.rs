@@ -0,0 +1,348 @@
+//! The solutions of all days as a single library, for use from notebooks, REPLs such as evcxr and
+//! other crates. Each day is a module re-exporting the public API of its crate together with
+//! `solve_part1` and `solve_part2`, which solve the parts with their default options without any
+//! I/O, and `validate`. `part1` and `part2` are shorter names for them.
+//!
+//! Days can be left out by disabling default features and enabling only the `dayN` features
+//! needed:
+//!
+//! ```ignore
+//! let input = std::fs::read_to_string("input17.txt")?;
+//! println!("{}", aoc2023::day17::shortest_path(&input, 1, 3)?);
+//! println!("{}", aoc2023::day17::part2(&input)?);
+//! ```
+
+pub use aoc_core::answer::Answer;
+pub use aoc_core::validate::Issue;
+
+/// Adds `solve_part1`, `solve_part2`, `part1`, `part2` and `validate` of a day to its module
+#[allow(unused_macros)] // Unused if no days are enabled
+macro_rules! parts {
+    ($day:ident) => {
+        /// Solves part 1 with the default options.
+        pub fn part1(input: &str) -> Result<$crate::Answer, Box<dyn std::error::Error>> {
+            ::$day::solve_part1(input)
+        }
+
+        /// Solves part 2 with the default options.
+        pub fn part2(input: &str) -> Result<$crate::Answer, Box<dyn std::error::Error>> {
+            ::$day::solve_part2(input)
+        }
+
+        pub use ::$day::{solve_part1, solve_part2, validate};
+    };
+}
+
+#[cfg(feature = "day1")]
+pub mod day1 {
+    //! Calibration values of the trebuchet document
+
+    pub use ::day1::{
+        Matcher, sum_parallel, calibrate_with, DigitPolicy, digit_matcher, calibration_values,
+        to_csv, CalibrationValue, Part, Stats,
+    };
+
+    parts!(day1);
+}
+
+#[cfg(feature = "day2")]
+pub mod day2 {
+    //! Games of drawing cubes from a bag
+
+    pub use ::day2::{
+        CubeNumbers, IdAnomaly, IdCheck, IdReport, ParseOptions, simulate, simulate_input, Rng,
+        SeededRng, check_ids, possible_with_bags, bags_possible_for_all, draw_stats, ColorStats,
+        DrawStats, LargestDraw,
+    };
+
+    parts!(day2);
+}
+
+#[cfg(feature = "day3")]
+pub mod day3 {
+    //! Part numbers and gears of the engine schematic
+
+    pub use ::day3::{
+        Position, Schematic, SchematicRules, Item, Change, sum_part_numbers_from_reader,
+        sum_part_numbers_by_columns,
+    };
+
+    parts!(day3);
+}
+
+#[cfg(feature = "day4")]
+pub mod day4 {
+    //! Scratchcards and their copies
+
+    pub use ::day4::{
+        IdPolicy, Card, Pile, WinningSets, Copying, CardInstance, Outcome, play, expected_num_cards,
+    };
+
+    parts!(day4);
+}
+
+#[cfg(feature = "day5")]
+pub mod day5 {
+    //! Seed locations according to the almanac
+
+    pub use ::day5::{
+        diff_almanacs, AlmanacDiff, LocationChange, MappingChange, MinResult, min_location,
+        min_location_where, RangeNode, seed_range_splits, splits_to_json,
+    };
+
+    parts!(day5);
+
+    /// An almanac with its seeds and maps
+    pub type Almanac<'a> = ::day5::Puzzle<'a>;
+}
+
+#[cfg(feature = "day6")]
+pub mod day6 {
+    //! Ways to win the boat races
+
+    pub use ::day6::{
+        StrategyTable, BulkEvaluation, ways_to_win_per_race, evaluate_races, strategy_tables,
+    };
+
+    parts!(day6);
+}
+
+#[cfg(feature = "day7")]
+pub mod day7 {
+    //! Camel Cards hands and their winnings
+
+    pub use ::day7::{
+        HandType, TieBreak, Rules, ScoredHand, score_hands, Scorer, Score, Standing, tournament,
+        HeadToHead, head_to_head, DecidedBy, ComparisonReport, compare_hands,
+    };
+
+    parts!(day7);
+}
+
+#[cfg(feature = "day8")]
+pub mod day8 {
+    //! Walks through the desert network
+
+    pub use ::day8::{
+        Arena, Node, DanglingReference, NetworkHealth, Network, Cycle, Solver, Timeline,
+        timelines_to_json, timelines_to_csv, Puzzle, ghost_timelines, network_health,
+    };
+
+    parts!(day8);
+}
+
+#[cfg(feature = "day9")]
+pub mod day9 {
+    //! Extrapolating the oasis sequences
+
+    pub use ::day9::{
+        Method, SequenceProfile, Report, sum_extrapolated_ahead, sum_extrapolated_both, Element,
+        Sequence, sum_extrapolated_both_as,
+    };
+
+    parts!(day9);
+}
+
+#[cfg(feature = "day10")]
+pub mod day10 {
+    //! The loop of pipes and the tiles it encloses
+
+    pub use ::day10::{
+        PIPE_CHARS, PipeCounts, ParseOptions, Puzzle, Loop, find_loops, largest_loop,
+        TraversalFrames,
+    };
+
+    parts!(day10);
+}
+
+#[cfg(feature = "day11")]
+pub mod day11 {
+    //! Distances between galaxies in the expanding universe
+
+    pub use ::day11::{
+        Galaxy, PairDistance, pair_distances, galaxies, galaxies_to_json, Format, SkippedLine,
+        split_image,
+    };
+
+    parts!(day11);
+}
+
+#[cfg(feature = "day13")]
+pub mod day13 {
+    //! Lines of reflection in the patterns of ash and rocks
+
+    pub use ::day13::{
+        generate_pattern, GeneratedPattern, Axis, Assignment, WildcardReflection, SmudgeFix,
+        pattern_summaries, smudge_fixes, wildcard_reflections, canonical_forms, equivalent,
+        duplicate_patterns,
+    };
+
+    parts!(day13);
+}
+
+#[cfg(feature = "day14")]
+pub mod day14 {
+    //! Tilting the platform of rounded rocks
+
+    pub use ::day14::{
+        Puzzle, Rock, track_rocks, Platform, WallPlacement, duplicate_platforms, LoadHistory,
+        TiltFrames, TiltDirection, load_after_tilt, DEFAULT_NUM_CYCLES,
+    };
+
+    parts!(day14);
+}
+
+#[cfg(feature = "day15")]
+pub mod day15 {
+    //! The HASH algorithm and the lens boxes
+
+    pub use ::day15::{HashMap, Format, hash_sum};
+
+    parts!(day15);
+}
+
+#[cfg(feature = "day16")]
+pub mod day16 {
+    //! Beams of light through the contraption
+
+    pub use ::day16::{
+        DEFAULT_MEMORY_LIMIT, Direction, Puzzle, Snapshot, Beam, estimate, Strategy, Entry,
+        EntrySearch, EntryClass, crossings, energized_counts, matrix_to_csv, entry_classes,
+        search_entry, best_subgrid, SubgridSearch, EntryResult, top_entries,
+    };
+
+    parts!(day16);
+}
+
+#[cfg(feature = "day17")]
+pub mod day17 {
+    //! Routes of the crucibles with the least heat loss
+
+    use std::error::Error;
+
+    pub use ::day17::{
+        Replay, ReplayFrames, SettledState, Puzzle, Route, Comparison, compare_crucibles, HeatLosses,
+    };
+
+    parts!(day17);
+
+    /// Least heat loss from the top-left to the bottom-right block of the city in the input, for a
+    /// crucible moving at least `min_move` and at most `max_move` blocks in a straight line
+    pub fn shortest_path(input: &str, min_move: usize, max_move: usize) -> Result<usize, Box<dyn Error>> {
+        Ok(Puzzle::from_input(input, min_move, max_move)?.shortest_path()?)
+    }
+}
+
+#[cfg(feature = "day18")]
+pub mod day18 {
+    //! Area of the lagoon dug by the dig plan
+
+    pub use ::day18::{
+        generate_plan, GeneratedPlan, LagoonStats, DecoderComparison, compare_decoders,
+        lagoon_stats, crosscheck, Dialect, plan_area,
+    };
+
+    parts!(day18);
+}
+
+#[cfg(feature = "day19")]
+pub mod day19 {
+    //! Workflows sorting the machine parts
+
+    pub use ::day19::{
+        Part, Distribution, TraceStep, RuleProfile, WorkflowProfile, Profile, Puzzle,
+        WorkflowComparison, compare_workflows,
+    };
+
+    parts!(day19);
+}
+
+#[cfg(feature = "day20")]
+pub mod day20 {
+    //! Pulses between the modules
+
+    pub use ::day20::{
+        MAX_PULSES_PER_PRESS, Pulse, PulseStats, ModuleState, Machine, ModuleTimeline, Waveform,
+        waveform_csv,
+    };
+
+    parts!(day20);
+}
+
+#[cfg(feature = "day21")]
+pub mod day21 {
+    //! Garden plots reachable in a number of steps
+
+    pub use ::day21::{
+        DEFAULT_FRAME_STEPS, Garden, Parity, ParityMap, ReachFrames, Method, reachable,
+        reachable_after,
+    };
+
+    parts!(day21);
+}
+
+#[cfg(feature = "day22")]
+pub mod day22 {
+    //! Falling bricks of sand
+
+    pub use ::day22::{
+        generate_bricks, BrickOptions, Heights, max_safe_removal, SafeRemoval, DEFAULT_NODE_BUDGET,
+        label, BrickStability, StabilityReport, SettledBrick, settled_bricks, stability_report,
+        SettleFrames,
+    };
+
+    parts!(day22);
+}
+
+#[cfg(feature = "day23")]
+pub mod day23 {
+    //! The longest hike through the forest
+
+    pub use ::day23::{
+        Slopes, Graph, PathSearch, DepthFirst, graph, LongestPath, longest_path, longest_path_with,
+        PathFrames,
+    };
+
+    parts!(day23);
+}
+
+#[cfg(feature = "day24")]
+pub mod day24 {
+    //! Crossing paths of the hailstones
+
+    pub use ::day24::{DEFAULT_AREA, Hailstone, Approach, closest_approach, count_crossings, throw_rock};
+
+    parts!(day24);
+}
+
+#[cfg(feature = "day25")]
+pub mod day25 {
+    //! Cutting the wiring diagram in two
+
+    pub use ::day25::{Format, Cut, find_cut, verify_cut};
+
+    parts!(day25);
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "day17")]
+    #[test]
+    fn day17_shortest_path() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "1911\n1911\n1111";
+        assert_eq!(crate::day17::shortest_path(input, 1, 3)?, 5);
+        assert_eq!(crate::day17::part1(input)?.to_string(), "5");
+        assert_eq!(crate::day17::solve_part1(input)?, crate::day17::part1(input)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "day5")]
+    #[test]
+    fn day5_almanac() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "seeds: 1 4\n\nseed-to-location map:\n10 0 3";
+        let almanac = crate::day5::Almanac::from_input(input)?;
+        assert_eq!(almanac.to_input_string().lines().next(), Some("seeds: 1 4"));
+        assert_eq!(crate::day5::part1(input)?.to_string(), "4");
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! The answer to a part of a puzzle.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+/// An answer, which is a number for almost all puzzles. Numbers are wide enough for answers that
+/// exceed 64 bits, such as the areas of day 18 for gigantic inputs.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Answer {
+    Unsigned(u128),
+    Signed(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(n) => write!(f, "{n}"),
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// A function solving one part of a day from its input
+pub type PartSolver = fn(&str) -> Result<Answer, Box<dyn Error>>;
+
+/// A day whose input is parsed once into a representation that both parts are solved from
+pub trait Solution {
+    /// The parsed input shared by both parts
+    type Parsed;
+
+    fn parse(input: &str) -> Result<Self::Parsed, Box<dyn Error>>;
+
+    fn part1(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>>;
+
+    fn part2(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>>;
+}
+
+/// Parses the input and solves part 1, as a `PartSolver` for the solution
+pub fn part1<S: Solution>(input: &str) -> Result<Answer, Box<dyn Error>> {
+    S::part1(&S::parse(input)?)
+}
+
+/// Parses the input and solves part 2, as a `PartSolver` for the solution
+pub fn part2<S: Solution>(input: &str) -> Result<Answer, Box<dyn Error>> {
+    S::part2(&S::parse(input)?)
+}
+
+/// Solves both parts of the solution from the given files and prints the results. If both files
+/// are the same, the input is read and parsed only once.
+pub fn run_parts<S: Solution>(file_path1: &str, file_path2: &str) -> Result<(), Box<dyn Error>> {
+    println!("Part 1: Reading file {file_path1}");
+    let parsed = S::parse(&fs::read_to_string(file_path1)?)?;
+    println!("Result of part 1: {}", S::part1(&parsed)?);
+
+    let parsed = parse_part2(parsed, file_path1, file_path2, S::parse)?;
+    println!("Result of part 2: {}", S::part2(&parsed)?);
+    Ok(())
+}
+
+/// Returns the parsed input of part 2 for days that print more than the answers: `parsed1`, the
+/// parsed input of part 1, if both parts read the same file, and the parsed file `file_path2`
+/// otherwise.
+pub fn parse_part2<T>(
+    parsed1: T,
+    file_path1: &str,
+    file_path2: &str,
+    parse: impl FnOnce(&str) -> Result<T, Box<dyn Error>>,
+) -> Result<T, Box<dyn Error>> {
+    if file_path2 == file_path1 {
+        println!("Part 2: Reusing the input of part 1");
+        Ok(parsed1)
+    } else {
+        println!("Part 2: Reading file {file_path2}");
+        parse(&fs::read_to_string(file_path2)?)
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Unsigned(n as u128)
+            }
+        }
+
+        impl PartialEq<$t> for Answer {
+            fn eq(&self, other: &$t) -> bool {
+                match *self {
+                    Answer::Unsigned(n) => n == *other as u128,
+                    Answer::Signed(n) => n >= 0 && n as u128 == *other as u128,
+                    Answer::Text(_) => false,
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! from_signed {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Signed(n as i128)
+            }
+        }
+
+        impl PartialEq<$t> for Answer {
+            fn eq(&self, other: &$t) -> bool {
+                match *self {
+                    Answer::Unsigned(n) => *other >= 0 && n == *other as u128,
+                    Answer::Signed(n) => n == *other as i128,
+                    Answer::Text(_) => false,
+                }
+            }
+        }
+    )*};
+}
+
+from_unsigned!(u8, u16, u32, u64, u128, usize);
+from_signed!(i8, i16, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_with_numbers() {
+        assert_eq!(Answer::from(42usize), 42);
+        assert_eq!(Answer::from(-3i32), -3);
+        assert_eq!(Answer::from(7i64), 7u8);
+        assert_ne!(Answer::from(u128::MAX), -1);
+        assert_ne!(Answer::from(-1i8), u128::MAX);
+        assert_ne!(Answer::from("42"), 42);
+    }
+
+    /// Counts how often it parses, so that sharing the parsed input can be checked
+    struct Lines;
+
+    static PARSES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    impl Solution for Lines {
+        type Parsed = Vec<String>;
+
+        fn parse(input: &str) -> Result<Self::Parsed, Box<dyn Error>> {
+            PARSES.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(input.lines().map(str::to_owned).collect())
+        }
+
+        fn part1(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+            Ok(parsed.len().into())
+        }
+
+        fn part2(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+            Ok(parsed.concat().into())
+        }
+    }
+
+    #[test]
+    fn solution_parses_once() -> Result<(), Box<dyn Error>> {
+        const PARTS: [PartSolver; 2] = [part1::<Lines>, part2::<Lines>];
+        assert_eq!(PARTS[0]("a\nb")?, 2);
+        assert_eq!(PARTS[1]("a\nb")?, Answer::from("ab"));
+        let path = std::env::temp_dir().join(format!("aoc_core_solution_{}.txt", std::process::id()));
+        fs::write(&path, "x\ny\nz")?;
+        let path = path.to_str().ok_or("Invalid path")?;
+        let before = PARSES.load(std::sync::atomic::Ordering::SeqCst);
+        run_parts::<Lines>(path, path)?;
+        assert_eq!(PARSES.load(std::sync::atomic::Ordering::SeqCst) - before, 1);
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::from(u128::MAX).to_string(), "340282366920938463463374607431768211455");
+        assert_eq!(Answer::from(-12i128).to_string(), "-12");
+        assert_eq!(Answer::from("ABC").to_string(), "ABC");
+    }
+}
@@ -0,0 +1,290 @@
+//! Signed integers of arbitrary size, with just the operations the solutions need.
+
+use crate::answer::Answer;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An integer stored as its sign and the base 2^32 digits of its magnitude, least significant
+/// first and without leading zeros, so that zero has no digits
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        if self.digits.is_empty() {
+            self.negative = false;
+        }
+        self
+    }
+
+    /// Multiplies by `factor`.
+    pub fn mul_small(mut self, factor: i64) -> Self {
+        let mut carry = 0u128;
+        for digit in &mut self.digits {
+            carry += *digit as u128 * factor.unsigned_abs() as u128;
+            *digit = carry as u32;
+            carry >>= 32;
+        }
+        while carry > 0 {
+            self.digits.push(carry as u32);
+            carry >>= 32;
+        }
+        self.negative ^= factor < 0;
+        self.normalize()
+    }
+
+    /// Divides by `divisor`, rounding towards zero, and returns the quotient and the remainder,
+    /// which has the sign of `self`. Panics if `divisor` is zero.
+    pub fn div_rem_small(mut self, divisor: u64) -> (Self, i128) {
+        assert!(divisor != 0, "Division by zero");
+        let mut remainder = 0u128;
+        for digit in self.digits.iter_mut().rev() {
+            remainder = remainder << 32 | *digit as u128;
+            *digit = (remainder / divisor as u128) as u32;
+            remainder %= divisor as u128;
+        }
+        let remainder = if self.negative { -(remainder as i128) } else { remainder as i128 };
+        (self.normalize(), remainder)
+    }
+
+    /// The value as an `i128`, or `None` if it does not fit
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.digits.len() > 4 {
+            return None;
+        }
+        let magnitude = self.digits.iter().rev().fold(0u128, |acc, &digit| acc << 32 | digit as u128);
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        self.digits.len().cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+
+    fn add_magnitude(mut self, other: &Self) -> Self {
+        let mut carry = 0u64;
+        for i in 0..self.digits.len().max(other.digits.len()) {
+            if i == self.digits.len() {
+                self.digits.push(0);
+            }
+            carry += self.digits[i] as u64 + other.digits.get(i).copied().unwrap_or(0) as u64;
+            self.digits[i] = carry as u32;
+            carry >>= 32;
+        }
+        if carry > 0 {
+            self.digits.push(carry as u32);
+        }
+        self
+    }
+
+    /// Subtracts the magnitude of `other`, which must not be greater than that of `self`.
+    fn sub_magnitude(mut self, other: &Self) -> Self {
+        let mut borrow = 0i64;
+        for i in 0..self.digits.len() {
+            let mut difference = self.digits[i] as i64 - other.digits.get(i).copied().unwrap_or(0) as i64 - borrow;
+            borrow = (difference < 0) as i64;
+            difference += borrow << 32;
+            self.digits[i] = difference as u32;
+        }
+        self.normalize()
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(n: i128) -> Self {
+        let mut magnitude = n.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        Self { negative: n < 0, digits }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        Self::from(n as i128)
+    }
+}
+
+impl Neg for BigInt {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        self.negative = !self.negative;
+        self.normalize()
+    }
+}
+
+impl Add for BigInt {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.negative == other.negative {
+            return self.add_magnitude(&other);
+        }
+        match self.cmp_magnitude(&other) {
+            Ordering::Less => other.sub_magnitude(&self),
+            _ => self.sub_magnitude(&other),
+        }
+    }
+}
+
+impl Sub for BigInt {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.digits.iter().enumerate() {
+                carry += a as u64 * b as u64 + digits[i + j] as u64;
+                digits[i + j] = carry as u32;
+                carry >>= 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt { negative: self.negative != other.negative, digits }.normalize()
+    }
+}
+
+/// Reads a decimal number with an optional leading minus sign.
+impl FromStr for BigInt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("Invalid integer");
+        }
+        let magnitude = digits.bytes().fold(Self::zero(), |n, b| n.mul_small(10) + Self::from((b - b'0') as i64));
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+}
+
+impl std::iter::Sum for BigInt {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 1_000_000_000;
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Groups of nine decimal digits, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = Self { negative: false, digits: self.digits.clone() };
+        while !rest.is_zero() {
+            let (quotient, remainder) = rest.div_rem_small(CHUNK);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap_or(0))?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A number if it fits in 128 bits and its decimal representation otherwise
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        match n.to_i128() {
+            Some(n) => n.into(),
+            None => n.to_string().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        let values = [0, 1, -1, 7, -12345, u32::MAX as i128, -(1 << 40) - 3, i64::MAX as i128, i64::MIN as i128];
+        for &a in &values {
+            for &b in &values {
+                assert_eq!((BigInt::from(a) + BigInt::from(b)).to_i128(), Some(a + b), "{a} + {b}");
+                assert_eq!((BigInt::from(a) - BigInt::from(b)).to_i128(), Some(a - b), "{a} - {b}");
+                assert_eq!(BigInt::from(a).mul_small(b as i64).to_i128(), Some(a * b as i64 as i128), "{a} * {b}");
+                assert_eq!((&BigInt::from(a) * &BigInt::from(b)).to_i128(), Some(a * b), "{a} * {b}");
+            }
+            for divisor in [1, 3, 1 << 33] {
+                let (quotient, remainder) = BigInt::from(a).div_rem_small(divisor);
+                assert_eq!((quotient.to_i128(), remainder), (Some(a / divisor as i128), a % divisor as i128));
+            }
+            assert_eq!(BigInt::from(a).to_string(), a.to_string());
+        }
+    }
+
+    #[test]
+    fn beyond_128_bits() {
+        // 2^200 = (2^50)^4
+        let n = (0..4).fold(BigInt::from(1i64), |n, _| n.mul_small(1 << 50));
+        assert_eq!(n.to_i128(), None);
+        assert_eq!(n.to_string(), "1606938044258990275541962092341162602522202993782792835301376");
+        assert_eq!((-n.clone()).to_string(), format!("-{n}"));
+        assert_eq!((n.clone() - n.clone()).to_i128(), Some(0));
+        assert_eq!(&n * &BigInt::from(-1i64), -n.clone());
+        assert_eq!((&n * &n).to_string(), (0..8).fold(BigInt::from(1i64), |n, _| n.mul_small(1 << 50)).to_string());
+        assert_eq!(Answer::from(n.clone()), Answer::Text(n.to_string()));
+        assert_eq!(Answer::from(BigInt::from(-5i64)), -5);
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(n.to_string().parse::<BigInt>(), Ok(n.clone()));
+        assert_eq!(format!("-{n}").parse::<BigInt>(), Ok(-n));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("0".parse::<BigInt>().map(|n| n.to_i128()), Ok(Some(0)));
+        assert_eq!("-0".parse::<BigInt>().map(|n| n.is_negative()), Ok(false));
+        assert_eq!("-170141183460469231731687303715884105728".parse::<BigInt>().map(|n| n.to_i128()), Ok(Some(i128::MIN)));
+        for invalid in ["", "-", "+1", "1-2", "12a", " 1"] {
+            assert_eq!(invalid.parse::<BigInt>(), Err("Invalid integer"), "{invalid:?}");
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! A fixed-length vector of bits.
+
+use std::fmt;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A vector of `len` bits, all initially unset
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    pub fn new(len: usize) -> Self {
+        Self { words: vec![0; len.div_ceil(WORD_BITS)], len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns bit `i`. Panics if `i` is out of bounds.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "Bit index {i} out of bounds for length {}", self.len);
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+
+    /// Sets bit `i` to `value`. Panics if `i` is out of bounds.
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "Bit index {i} out of bounds for length {}", self.len);
+        let mask = 1 << (i % WORD_BITS);
+        if value {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Indices of the set bits in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        // Skip unset words and jump from one set bit to the next within a word
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    i * WORD_BITS + bit
+                })
+            })
+        })
+    }
+
+    /// Unsets every bit that is not also set in `other`. Panics if the lengths differ.
+    pub fn and_assign(&mut self, other: &Self) {
+        assert_eq!(self.len, other.len, "Bit vectors have different lengths");
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word &= other_word;
+        }
+    }
+
+    /// Counts the bits set in both `self` and `other` without building their intersection. Panics
+    /// if the lengths differ.
+    pub fn count_common_ones(&self, other: &Self) -> usize {
+        assert_eq!(self.len, other.len, "Bit vectors have different lengths");
+        self.words.iter().zip(&other.words).map(|(word, other_word)| (word & other_word).count_ones() as usize).sum()
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let bits: Vec<bool> = iter.into_iter().collect();
+        let mut result = Self::new(bits.len());
+        for (i, bit) in bits.into_iter().enumerate() {
+            result.set(i, bit);
+        }
+        result
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len {
+            write!(f, "{}", if self.get(i) { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_across_words() {
+        let mut bits = BitVec::new(130);
+        bits.set(0, true);
+        bits.set(64, true);
+        bits.set(129, true);
+        bits.set(64, false);
+        assert!(bits.get(0) && !bits.get(64) && bits.get(129));
+        assert_eq!(bits.count_ones(), 2);
+        assert_eq!(bits.ones().collect::<Vec<_>>(), vec![0, 129]);
+    }
+
+    #[test]
+    fn and_assign() {
+        let mut a: BitVec = [true, true, false, true].into_iter().collect();
+        let b: BitVec = [false, true, true, true].into_iter().collect();
+        assert_eq!(a.count_common_ones(&b), 2);
+        a.and_assign(&b);
+        assert_eq!(format!("{a:?}"), "0101");
+    }
+}
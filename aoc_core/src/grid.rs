@@ -0,0 +1,87 @@
+//! Helpers for puzzles whose input is a grid of characters.
+
+use std::error::Error;
+use std::fmt;
+
+/// Returns the width of the first line and the number of lines of a grid input without parsing
+/// it.
+pub fn dimensions(input: &str) -> (usize, usize) {
+    let width = input.lines().next().map_or(0, |line| line.len());
+    (width, input.lines().count())
+}
+
+/// Estimated resources needed for solving a grid puzzle
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Estimate {
+    /// Number of search states
+    pub states: usize,
+    /// Number of bytes of memory
+    pub bytes: usize,
+}
+
+impl Estimate {
+    /// Estimates the resources for a grid of the given size, where each cell gives rise to
+    /// `states_per_cell` search states and occupies `bytes_per_cell` bytes. Returns `None` if the
+    /// numbers do not fit in a `usize`.
+    pub fn for_grid(width: usize, height: usize, states_per_cell: usize, bytes_per_cell: usize) -> Option<Self> {
+        let cells = width.checked_mul(height)?;
+        Some(Self {
+            states: cells.checked_mul(states_per_cell)?,
+            bytes: cells.checked_mul(bytes_per_cell)?,
+        })
+    }
+}
+
+/// Error that is returned instead of attempting to solve a puzzle that would need too much memory
+#[derive(Debug, Eq, PartialEq)]
+pub struct MemoryLimitExceeded {
+    /// Estimated memory in bytes, or `None` if it is too large to be represented
+    pub bytes: Option<usize>,
+    /// Maximum memory in bytes
+    pub limit: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bytes {
+            Some(bytes) => write!(f, "Estimated memory of {bytes} bytes exceeds the limit of {} bytes", self.limit),
+            None => write!(f, "Estimated memory exceeds the limit of {} bytes", self.limit),
+        }
+    }
+}
+
+impl Error for MemoryLimitExceeded {}
+
+/// Estimates the resources for a grid like [`Estimate::for_grid`] and returns an error if the
+/// memory would exceed `limit` bytes.
+pub fn check_grid(
+    width: usize, height: usize, states_per_cell: usize, bytes_per_cell: usize, limit: usize
+) -> Result<Estimate, MemoryLimitExceeded> {
+    match Estimate::for_grid(width, height, states_per_cell, bytes_per_cell) {
+        Some(estimate) if estimate.bytes <= limit => Ok(estimate),
+        estimate => Err(MemoryLimitExceeded { bytes: estimate.map(|e| e.bytes), limit }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimensions_of_input() {
+        assert_eq!(dimensions("abc\ndef\n"), (3, 2));
+        assert_eq!(dimensions(""), (0, 0));
+    }
+
+    #[test]
+    fn check_within_limit() {
+        let estimate = check_grid(10, 20, 4, 8, 1600).unwrap();
+        assert_eq!(estimate, Estimate { states: 800, bytes: 1600 });
+    }
+
+    #[test]
+    fn check_exceeding_limit() {
+        assert_eq!(check_grid(10, 20, 4, 8, 1599), Err(MemoryLimitExceeded { bytes: Some(1600), limit: 1599 }));
+        assert_eq!(check_grid(usize::MAX, 2, 1, 1, 1000), Err(MemoryLimitExceeded { bytes: None, limit: 1000 }));
+    }
+}
@@ -0,0 +1,14 @@
+//! Utilities shared between the solutions of the individual days.
+
+pub mod answer;
+pub mod bigint;
+pub mod bitvec;
+pub mod grid;
+pub mod math;
+pub mod parallel;
+pub mod parse;
+pub mod render;
+pub mod rng;
+pub mod search;
+pub mod union_find;
+pub mod validate;
@@ -0,0 +1,157 @@
+//! Integer arithmetic helpers.
+
+/// Greatest common divisor of `a` and `b`; `gcd(0, 0)` is 0.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple of `a` and `b`; `lcm(0, x)` is 0.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 { return 0 }
+    a / gcd(a, b) * b
+}
+
+/// Returns `(g, x, y)` such that `g` is the (non-negative) greatest common divisor of `a` and `b`
+/// and `a * x + b * y == g`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 { (-old_r, -old_x, -old_y) } else { (old_r, old_x, old_y) }
+}
+
+/// Solves a system of congruences `x ≡ residue (mod modulus)`, given as `(residue, modulus)` pairs
+/// with positive moduli that need not be pairwise coprime.
+///
+/// Returns `(x, m)` where `m` is the least common multiple of the moduli and `x` is the unique
+/// solution in `0..m`, or `None` if the system has no solution or `m` does not fit in an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 { return None }
+        let modulus = modulus as i128;
+        let residue = (residue as i128).rem_euclid(modulus);
+        // We need x + m * k ≡ residue (mod modulus), i.e., m * k ≡ residue - x (mod modulus)
+        let (g, inv, _) = extended_gcd(m.rem_euclid(modulus) as i64, modulus as i64);
+        let (g, inv) = (g as i128, inv as i128);
+        let diff = residue - x;
+        if diff % g != 0 { return None }
+        let step = modulus / g;
+        let k = (diff / g % step * inv).rem_euclid(step);
+        x += m * k;
+        m *= step;
+        if m > i64::MAX as i128 { return None }
+        x = x.rem_euclid(m);
+    }
+    Some((x as i64, m as i64))
+}
+
+/// Largest integer whose square is at most `n`.
+pub fn isqrt(n: u64) -> u64 {
+    if n < 2 { return n }
+    // Start from the floating-point estimate and correct the rounding errors
+    let mut r = (n as f64).sqrt() as u64;
+    while r.checked_mul(r).is_none_or(|sq| sq > n) {
+        r -= 1;
+    }
+    while (r + 1).checked_mul(r + 1).is_some_and(|sq| sq <= n) {
+        r += 1;
+    }
+    r
+}
+
+/// Computes `base` to the power of `exp` modulo `modulus`.
+pub fn modpow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    assert!(modulus > 0, "Modulus must be positive");
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(17, 5), 1);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        assert_eq!(lcm(21883, 19667), 21883 / gcd(21883, 19667) * 19667);
+    }
+
+    #[test]
+    fn extended_gcd_satisfies_bezout() {
+        for (a, b) in [(240, 46), (46, 240), (-240, 46), (240, -46), (0, 5), (5, 0), (0, 0), (1, 1)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i64);
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn crt_coprime_moduli() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 3), (-2, 5)]), Some((8, 15)));
+    }
+
+    #[test]
+    fn crt_non_coprime_moduli() {
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(0, 6), (0, 10), (0, 15)]), Some((0, 30)));
+    }
+
+    #[test]
+    fn crt_edge_cases() {
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(5, 1)]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(1, i64::MAX), (0, 2)]), None);
+    }
+
+    #[test]
+    fn isqrt_is_exact() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(17), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        let r: u64 = 3_037_000_499;
+        assert_eq!(isqrt(r * r), r);
+        assert_eq!(isqrt(r * r - 1), r - 1);
+    }
+
+    #[test]
+    fn modpow_matches_naive() {
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(3, 0, 7), 1);
+        assert_eq!(modpow(3, 0, 1), 0);
+        assert_eq!(modpow(u64::MAX, 2, u64::MAX - 1), 1);
+        let naive = (0..13).fold(1u64, |acc, _| acc * 7 % 13);
+        assert_eq!(modpow(7, 13, 13), naive);
+    }
+}
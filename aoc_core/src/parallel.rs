@@ -0,0 +1,36 @@
+//! Data parallelism on slices using rayon's thread pool.
+
+use rayon::prelude::*;
+
+/// Applies `f` to each item, letting the threads of rayon's pool steal items from each other so
+/// that a few expensive items do not hold up the rest. The results are in the same order as the
+/// items. A panic in `f` is resumed on the calling thread.
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic;
+
+    #[test]
+    fn map_preserves_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(map(&items, |x| x * x), items.iter().map(|x| x * x).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn map_empty() {
+        let items: Vec<u64> = Vec::new();
+        assert!(map(&items, |x| x + 1).is_empty());
+    }
+
+    #[test]
+    fn map_resumes_panic() {
+        let items: Vec<u64> = (0..100).collect();
+        let payload = panic::catch_unwind(|| map(&items, |&x| if x == 42 { panic!("Item 42") } else { x })).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"Item 42"));
+    }
+}
@@ -0,0 +1,202 @@
+//! A tiny parser for single lines of puzzle input, used instead of regular expressions.
+//!
+//! A `Parser` walks through a string, and each method consumes a piece of it or fails with a
+//! `ParseError` pointing at the column where the input deviates from what was expected.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parse failure at a 1-based column, counted in characters
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: impl ToString) -> Self {
+        Self { column, message: message.to_string() }
+    }
+
+    /// Describes the error together with the 1-based number of the line it occurred in.
+    pub fn on_line(&self, line_nr: usize) -> String {
+        format!("Line {line_nr}, {self}")
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Position in a string being parsed
+pub struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// 1-based column of the next character
+    pub fn column(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    /// An error at the current position
+    pub fn error(&self, message: impl ToString) -> ParseError {
+        ParseError::new(self.column(), message)
+    }
+
+    /// The input that has not been consumed yet
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Consumes `tag` if the input continues with it. Returns whether it did.
+    pub fn eat(&mut self, tag: &str) -> bool {
+        let found = self.rest().starts_with(tag);
+        if found {
+            self.pos += tag.len();
+        }
+        found
+    }
+
+    /// Consumes `tag`, failing if the input does not continue with it.
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if self.eat(tag) { Ok(()) } else { Err(self.error(format!("Expected '{tag}'"))) }
+    }
+
+    /// Consumes the longest prefix whose characters satisfy `pred`, which may be empty.
+    pub fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Skips spaces and returns how many there were.
+    pub fn spaces(&mut self) -> usize {
+        self.take_while(|c| c == ' ').len()
+    }
+
+    /// Consumes a nonempty prefix whose characters satisfy `pred`, failing with "Expected `what`"
+    /// if there is none.
+    pub fn word(&mut self, what: &str, pred: impl Fn(char) -> bool) -> Result<&'a str, ParseError> {
+        let column = self.column();
+        match self.take_while(pred) {
+            "" => Err(ParseError::new(column, format!("Expected {what}"))),
+            word => Ok(word),
+        }
+    }
+
+    /// Consumes a decimal number with an optional minus sign.
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let (start, column) = (self.pos, self.column());
+        self.eat("-");
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            self.pos = start;
+            return Err(self.error("Expected number"));
+        }
+        let number = &self.input[start..self.pos];
+        number.parse().map_err(|_| ParseError::new(column, format!("Invalid number {number}")))
+    }
+
+    /// Consumes one or more items separated by `separator`.
+    pub fn separated<T>(
+        &mut self, separator: &str, mut item: impl FnMut(&mut Self) -> Result<T, ParseError>
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Consumes one or more fields of the form `<name><assign><value>` separated by `separator`,
+    /// where names consist of ASCII letters and digits.
+    pub fn tagged_fields<T>(
+        &mut self, separator: &str, assign: &str, mut value: impl FnMut(&mut Self) -> Result<T, ParseError>
+    ) -> Result<Vec<(&'a str, T)>, ParseError> {
+        self.separated(separator, |parser| {
+            let name = parser.word("field name", |c| c.is_ascii_alphanumeric())?;
+            parser.tag(assign)?;
+            Ok((name, value(parser)?))
+        })
+    }
+
+    /// Fails if any input is left.
+    pub fn end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("Unexpected {c:?}"))),
+        }
+    }
+}
+
+/// Parses all of `input` with `parse`, failing if it does not consume everything.
+pub fn parse_all<'a, T>(
+    input: &'a str, parse: impl FnOnce(&mut Parser<'a>) -> Result<T, ParseError>
+) -> Result<T, ParseError> {
+    let mut parser = Parser::new(input);
+    let result = parse(&mut parser)?;
+    parser.end()?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_and_tags() -> Result<(), ParseError> {
+        let (id, numbers) = parse_all("Game 12: 1, -2, 3", |p| {
+            p.tag("Game ")?;
+            let id: u32 = p.number()?;
+            p.tag(": ")?;
+            Ok((id, p.separated(", ", |p| p.number::<i32>())?))
+        })?;
+        assert_eq!(id, 12);
+        assert_eq!(numbers, vec![1, -2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn tagged_fields() -> Result<(), ParseError> {
+        let fields = parse_all("x=1,mm=22", |p| p.tagged_fields(",", "=", |p| p.number::<u8>()))?;
+        assert_eq!(fields, vec![("x", 1), ("mm", 22)]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_columns() {
+        let error = parse_all("Game x", |p| { p.tag("Game ")?; p.number::<u32>() }).unwrap_err();
+        assert_eq!(error, ParseError::new(6, "Expected number"));
+        let error = parse_all("Gäme 1", |p| p.tag("Game")).unwrap_err();
+        assert_eq!(error.to_string(), "column 1: Expected 'Game'");
+        let error = parse_all("1 2", |p| p.number::<u32>()).unwrap_err();
+        assert_eq!(error.on_line(4), "Line 4, column 2: Unexpected ' '");
+        let error = parse_all("ä-300", |p| { p.tag("ä")?; p.number::<i8>() }).unwrap_err();
+        assert_eq!(error, ParseError::new(2, "Invalid number -300"));
+    }
+
+    #[test]
+    fn words() -> Result<(), ParseError> {
+        let mut parser = Parser::new("abc  12");
+        assert_eq!(parser.word("letters", |c| c.is_ascii_lowercase())?, "abc");
+        assert_eq!(parser.spaces(), 2);
+        assert!(parser.word("letters", |c| c.is_ascii_lowercase()).is_err());
+        assert_eq!(parser.rest(), "12");
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+//! Rendering sequences of text frames, e.g. the states of a simulation, as animations.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// A picture made of lines of text, with a caption describing it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    pub caption: String,
+    pub lines: Vec<String>,
+}
+
+impl Frame {
+    pub fn new(caption: impl ToString, lines: Vec<String>) -> Self {
+        Self { caption: caption.to_string(), lines }
+    }
+
+    /// Creates a frame from the output of a `Display` implementation that prints a grid.
+    pub fn from_text(caption: impl ToString, text: &str) -> Self {
+        Self::new(caption, text.lines().map(str::to_owned).collect())
+    }
+}
+
+/// Something that produces frames one after another
+pub trait FrameSource {
+    /// Returns the next frame, or `None` if there are no more frames.
+    fn next_frame(&mut self) -> Option<Frame>;
+}
+
+/// Writes every frame of the source as plain text, each preceded by its caption and followed by
+/// an empty line. Returns the number of frames written.
+pub fn render_text(source: &mut dyn FrameSource, out: &mut dyn Write) -> io::Result<usize> {
+    let mut num_frames = 0;
+    while let Some(frame) = source.next_frame() {
+        writeln!(out, "{}", frame.caption)?;
+        for line in &frame.lines {
+            writeln!(out, "{line}")?;
+        }
+        writeln!(out)?;
+        num_frames += 1;
+    }
+    Ok(num_frames)
+}
+
+/// Plays the frames of the source in a terminal, clearing the screen with ANSI escape codes before
+/// each frame and waiting for `delay` after it. Returns the number of frames shown.
+pub fn play_ansi(source: &mut dyn FrameSource, out: &mut dyn Write, delay: Duration) -> io::Result<usize> {
+    let mut num_frames = 0;
+    while let Some(frame) = source.next_frame() {
+        writeln!(out, "\x1b[2J\x1b[H{}", frame.caption)?;
+        for line in &frame.lines {
+            writeln!(out, "{line}")?;
+        }
+        out.flush()?;
+        num_frames += 1;
+        thread::sleep(delay);
+    }
+    Ok(num_frames)
+}
+
+/// Runs through the source and returns its last frame, or `None` if it has none.
+pub fn last_frame(source: &mut dyn FrameSource) -> Option<Frame> {
+    let mut last = None;
+    while let Some(frame) = source.next_frame() {
+        last = Some(frame);
+    }
+    last
+}
+
+/// Escapes the characters that have a special meaning in XML and HTML text.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Draws the frame as an SVG image with one line of monospaced text per line of the frame and the
+/// caption as its title.
+pub fn frame_to_svg(frame: &Frame) -> String {
+    const CHAR_WIDTH: usize = 6;
+    const LINE_HEIGHT: usize = 10;
+    let width = frame.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) * CHAR_WIDTH;
+    let height = frame.lines.len() * LINE_HEIGHT;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        font-family=\"monospace\" font-size=\"{LINE_HEIGHT}\" xml:space=\"preserve\">\n"
+    );
+    svg.push_str(&format!("<title>{}</title>\n", escape_xml(&frame.caption)));
+    for (i, line) in frame.lines.iter().enumerate() {
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{}\" textLength=\"{}\">{}</text>\n",
+            (i + 1) * LINE_HEIGHT - 2, line.chars().count() * CHAR_WIDTH, escape_xml(line)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Countdown(usize);
+
+    impl FrameSource for Countdown {
+        fn next_frame(&mut self) -> Option<Frame> {
+            if self.0 == 0 {
+                return None;
+            }
+            self.0 -= 1;
+            Some(Frame::from_text(format!("{} left", self.0), &"#".repeat(self.0 + 1)))
+        }
+    }
+
+    #[test]
+    fn text_rendering() -> io::Result<()> {
+        let mut out = Vec::new();
+        assert_eq!(render_text(&mut Countdown(2), &mut out)?, 2);
+        assert_eq!(String::from_utf8(out).unwrap(), "1 left\n##\n\n0 left\n#\n\n");
+        Ok(())
+    }
+
+    #[test]
+    fn ansi_playback() -> io::Result<()> {
+        let mut out = Vec::new();
+        assert_eq!(play_ansi(&mut Countdown(1), &mut out, Duration::ZERO)?, 1);
+        assert_eq!(String::from_utf8(out).unwrap(), "\x1b[2J\x1b[H0 left\n#\n");
+        Ok(())
+    }
+
+    #[test]
+    fn last_frame_and_svg() {
+        let frame = last_frame(&mut Countdown(3)).unwrap();
+        assert_eq!(frame, Frame::from_text("0 left", "#"));
+        assert!(last_frame(&mut Countdown(0)).is_none());
+        let svg = frame_to_svg(&Frame::from_text("a < b", "#.\n&O"));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"20\" "));
+        assert!(svg.contains("<title>a &lt; b</title>\n"));
+        assert!(svg.contains("<text x=\"0\" y=\"18\" textLength=\"12\">&amp;O</text>\n"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
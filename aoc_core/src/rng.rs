@@ -0,0 +1,106 @@
+//! Seeded pseudo-random numbers for generators, heuristics and tests, which give the same numbers
+//! for the same seed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of random numbers
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a number between 0 (inclusive) and `n` (exclusive), which must be positive. Every
+    /// number is equally likely: draws from the incomplete range of multiples of `n` at the top
+    /// are rejected.
+    fn below(&mut self, n: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < limit {
+                return x % n;
+            }
+        }
+    }
+
+    /// Returns an index into a collection of length `len`, which must be positive.
+    fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// Shuffles the items so that every order is about equally likely.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// A linear congruential generator with scrambled output, which is good enough for puzzle inputs
+/// and tests but not for anything that needs statistical quality
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeds the generator with the current time.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    fn step(&mut self) -> u64 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.state
+    }
+}
+
+impl Rng for SeededRng {
+    /// Returns 64 random bits. The low bits of the state have short periods, so the state is mixed
+    /// with the finalizer of MurmurHash3, which spreads every bit over the whole number.
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.step();
+        x = (x ^ (x >> 33)).wrapping_mul(0xff51afd7ed558ccd);
+        x = (x ^ (x >> 33)).wrapping_mul(0xc4ceb9fe1a85ec53);
+        x ^ (x >> 33)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (SeededRng::new(42), SeededRng::new(42));
+        let numbers: Vec<u64> = (0..100).map(|_| a.next_u64()).collect();
+        assert!(numbers.iter().all(|&n| n == b.next_u64()));
+        assert_ne!(SeededRng::new(43).next_u64(), numbers[0]);
+        assert!((0..100).all(|_| a.below(7) < 7 && a.index(3) < 3 && a.below(1 << 40) < 1 << 40));
+        // Both the high and the low bits vary
+        assert!(numbers.iter().any(|&n| n >= 1 << 63) && numbers.iter().any(|&n| n < 1 << 63));
+        assert!(numbers.iter().any(|&n| n % 2 == 0) && numbers.iter().any(|&n| n % 2 == 1));
+        assert_eq!(a.below(1), 0);
+        assert!(a.below(u64::MAX) < u64::MAX);
+    }
+
+    #[test]
+    fn below_is_uniform() {
+        // With n = 3 · 2^62, the modulo of a 64-bit number would give the lower third of the range
+        // twice as often as the rest
+        let mut rng = SeededRng::new(7);
+        let n = 3 << 62;
+        let lower = (0..3000).filter(|_| rng.below(n) < 1 << 62).count();
+        assert!((800..1200).contains(&lower), "{lower} of 3000 numbers in the lower third");
+    }
+
+    #[test]
+    fn shuffle_permutes() {
+        let mut items: Vec<usize> = (0..50).collect();
+        SeededRng::new(1).shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+    }
+}
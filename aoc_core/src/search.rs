@@ -0,0 +1,304 @@
+//! Shortest paths in graphs given by a function returning the successors of a state.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Path costs that can be added without silently wrapping around. Costs must not be negative.
+pub trait Cost: Copy + Ord {
+    const ZERO: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty),*) => {
+        $(
+            impl Cost for $t {
+                const ZERO: Self = 0;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_cost!(u8, u16, u32, u64, u128, usize);
+
+/// The costs found by a search and the goal it stopped at, if any
+#[derive(Clone, Debug)]
+pub struct Search<S, C> {
+    costs: HashMap<S, C>,
+    parents: HashMap<S, S>,
+    pub goal: Option<(S, C)>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Search<S, C> {
+    /// Cost of the cheapest path found to the state. It is the minimum for every state the search
+    /// has finished, which are all reachable states unless it stopped at a goal.
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.costs.get(state).copied()
+    }
+
+    /// All states reached, with the costs of the cheapest paths found to them
+    pub fn costs(&self) -> impl Iterator<Item = (&S, C)> {
+        self.costs.iter().map(|(state, &cost)| (state, cost))
+    }
+
+    /// The states on the cheapest path found to `state`, from a start state to `state`
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        if !self.costs.contains_key(state) {
+            return None;
+        }
+        let mut path = vec![state.clone()];
+        while let Some(parent) = self.parents.get(path.last()?) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs Dijkstra's algorithm from the given start states with their initial costs. `successors`
+/// returns the states reachable in one step from a state, each with the cost of that step.
+/// `is_goal` is called once for every state when its cost is final, and the search stops as soon
+/// as it returns true. Fails if `successors` fails or a cost overflows.
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = (S, C)>,
+    mut successors: impl FnMut(&S) -> Result<I, &'static str>,
+    mut is_goal: impl FnMut(&S, C) -> bool,
+) -> Result<Search<S, C>, &'static str>
+where
+    S: Clone + Eq + Hash + Ord,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut search = Search { costs: HashMap::new(), parents: HashMap::new(), goal: None };
+    let mut heap = BinaryHeap::new();
+    for (state, cost) in starts {
+        if search.costs.get(&state).is_none_or(|&old| cost < old) {
+            search.costs.insert(state.clone(), cost);
+            heap.push(Reverse((cost, state)));
+        }
+    }
+    while let Some(Reverse((cost, state))) = heap.pop() {
+        // A cheaper path to the state has been found after this entry was pushed
+        if search.costs.get(&state).is_some_and(|&best| best < cost) {
+            continue;
+        }
+        if is_goal(&state, cost) {
+            search.goal = Some((state, cost));
+            break;
+        }
+        for (next, step) in successors(&state)? {
+            let next_cost = cost.checked_add(step).ok_or("Cost overflow")?;
+            if search.costs.get(&next).is_none_or(|&old| next_cost < old) {
+                search.costs.insert(next.clone(), next_cost);
+                search.parents.insert(next.clone(), state.clone());
+                heap.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+    Ok(search)
+}
+
+/// A state reached by a search on dense indices, with the cost of the cheapest path found to it and
+/// the index of the previous state on that path
+#[derive(Clone, Debug)]
+struct Reached<S, C> {
+    state: S,
+    cost: C,
+    parent: Option<usize>,
+}
+
+/// The costs found by a search on states with dense indices and the goal it stopped at, if any.
+/// States are looked up by their index.
+#[derive(Clone, Debug)]
+pub struct DenseSearch<S, C> {
+    reached: Vec<Option<Reached<S, C>>>,
+    pub goal: Option<(S, C)>,
+}
+
+impl<S: Clone, C: Copy> DenseSearch<S, C> {
+    /// Cost of the cheapest path found to the state with the index, like `Search::cost`
+    pub fn cost(&self, index: usize) -> Option<C> {
+        Some(self.reached.get(index)?.as_ref()?.cost)
+    }
+
+    /// All states reached, with the costs of the cheapest paths found to them, ordered by index
+    pub fn costs(&self) -> impl Iterator<Item = (&S, C)> {
+        self.reached.iter().flatten().map(|reached| (&reached.state, reached.cost))
+    }
+
+    /// The states on the cheapest path found to the state with the index, from a start state to
+    /// that state
+    pub fn path_to(&self, index: usize) -> Option<Vec<S>> {
+        let mut reached = self.reached.get(index)?.as_ref()?;
+        let mut path = vec![reached.state.clone()];
+        while let Some(parent) = reached.parent {
+            reached = self.reached[parent].as_ref()?;
+            path.push(reached.state.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs Dijkstra's algorithm like `dijkstra`, but keeps the costs in a vector instead of hash
+/// maps. `index` maps each state to a distinct index below `num_states`, so the states need
+/// neither hashing nor an order. Fails if `successors` fails or a cost overflows.
+pub fn dijkstra_dense<S, C, I>(
+    num_states: usize,
+    index: impl Fn(&S) -> usize,
+    starts: impl IntoIterator<Item = (S, C)>,
+    mut successors: impl FnMut(&S) -> Result<I, &'static str>,
+    mut is_goal: impl FnMut(&S, C) -> bool,
+) -> Result<DenseSearch<S, C>, &'static str>
+where
+    S: Clone,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut reached: Vec<Option<Reached<S, C>>> = vec![None; num_states];
+    let mut heap = BinaryHeap::new();
+    for (state, cost) in starts {
+        let i = index(&state);
+        if reached[i].as_ref().is_none_or(|old| cost < old.cost) {
+            reached[i] = Some(Reached { state, cost, parent: None });
+            heap.push(Reverse((cost, i)));
+        }
+    }
+    let mut goal = None;
+    while let Some(Reverse((cost, i))) = heap.pop() {
+        let Some(current) = reached[i].as_ref().filter(|current| current.cost == cost) else {
+            // A cheaper path to the state has been found after this entry was pushed
+            continue;
+        };
+        let state = current.state.clone();
+        if is_goal(&state, cost) {
+            goal = Some((state, cost));
+            break;
+        }
+        for (next, step) in successors(&state)? {
+            let next_cost = cost.checked_add(step).ok_or("Cost overflow")?;
+            let j = index(&next);
+            if reached[j].as_ref().is_none_or(|old| next_cost < old.cost) {
+                reached[j] = Some(Reached { state: next, cost: next_cost, parent: Some(i) });
+                heap.push(Reverse((next_cost, j)));
+            }
+        }
+    }
+    Ok(DenseSearch { reached, goal })
+}
+
+/// Runs a breadth-first search from the given start states, where each step costs 1.
+/// `successors` returns the states reachable in one step from a state. `is_goal` is called once
+/// for every state in the order of their distances, and the search stops as soon as it returns
+/// true.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S, usize) -> bool,
+) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search { costs: HashMap::new(), parents: HashMap::new(), goal: None };
+    let mut queue = VecDeque::new();
+    for state in starts {
+        if !search.costs.contains_key(&state) {
+            search.costs.insert(state.clone(), 0);
+            queue.push_back((state, 0));
+        }
+    }
+    while let Some((state, distance)) = queue.pop_front() {
+        if is_goal(&state, distance) {
+            search.goal = Some((state, distance));
+            break;
+        }
+        for next in successors(&state) {
+            if !search.costs.contains_key(&next) {
+                search.costs.insert(next.clone(), distance + 1);
+                search.parents.insert(next.clone(), state.clone());
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edges of a small weighted graph where the direct edge from 0 to 3 is more expensive than
+    /// the detour over 1 and 2
+    fn edges(node: &u8) -> Result<Vec<(u8, u32)>, &'static str> {
+        Ok(match node {
+            0 => vec![(1, 1), (3, 10)],
+            1 => vec![(2, 2)],
+            2 => vec![(3, 3)],
+            _ => vec![],
+        })
+    }
+
+    #[test]
+    fn dijkstra_finds_cheapest_path() -> Result<(), &'static str> {
+        let search = dijkstra([(0, 0)], edges, |&node, _| node == 3)?;
+        assert_eq!(search.goal, Some((3, 6)));
+        assert_eq!(search.path_to(&3), Some(vec![0, 1, 2, 3]));
+        let search = dijkstra([(0, 0)], edges, |_, _| false)?;
+        assert_eq!(search.costs().count(), 4);
+        assert_eq!(search.cost(&2), Some(3));
+        assert_eq!(search.goal, None);
+        Ok(())
+    }
+
+    #[test]
+    fn dijkstra_multiple_starts() -> Result<(), &'static str> {
+        let search = dijkstra([(0, 5), (2, 1)], edges, |_, _| false)?;
+        assert_eq!(search.cost(&3), Some(4));
+        assert_eq!(search.path_to(&3), Some(vec![2, 3]));
+        assert_eq!(search.cost(&1), Some(6));
+        Ok(())
+    }
+
+    #[test]
+    fn dijkstra_overflow() {
+        let result = dijkstra([(0, u32::MAX - 1)], edges, |_, _| false);
+        assert_eq!(result.err(), Some("Cost overflow"));
+    }
+
+    #[test]
+    fn dense_dijkstra_matches_hashed() -> Result<(), &'static str> {
+        let index = |&node: &u8| usize::from(node);
+        let search = dijkstra_dense(4, index, [(0, 0)], edges, |&node, _| node == 3)?;
+        assert_eq!(search.goal, Some((3, 6)));
+        assert_eq!(search.path_to(3), Some(vec![0, 1, 2, 3]));
+        let search = dijkstra_dense(4, index, [(0, 5), (2, 1)], edges, |_, _| false)?;
+        let hashed = dijkstra([(0, 5), (2, 1)], edges, |_, _| false)?;
+        assert!((0..4).all(|node| search.cost(usize::from(node)) == hashed.cost(&node)));
+        assert_eq!(search.costs().collect::<Vec<_>>(), [(&0, 5), (&1, 6), (&2, 1), (&3, 4)]);
+        assert_eq!(search.path_to(3), Some(vec![2, 3]));
+        assert_eq!(search.path_to(4), None);
+        let result = dijkstra_dense(4, index, [(0, u32::MAX - 1)], edges, |_, _| false);
+        assert_eq!(result.err(), Some("Cost overflow"));
+        Ok(())
+    }
+
+    #[test]
+    fn bfs_on_grid() {
+        // Moving right or down on a 5 × 5 grid
+        let successors = |&(x, y): &(u32, u32)| [(x + 1, y), (x, y + 1)].into_iter().filter(|&(x, y)| x < 5 && y < 5);
+        let search = bfs([(0, 0)], successors, |&state, _| state == (4, 4));
+        assert_eq!(search.goal, Some(((4, 4), 8)));
+        assert_eq!(search.path_to(&(4, 4)).map(|path| path.len()), Some(9));
+        let search = bfs([(0, 0)], successors, |_, _| false);
+        assert_eq!(search.costs().count(), 25);
+        assert_eq!(search.cost(&(2, 3)), Some(5));
+        assert_eq!(search.path_to(&(5, 5)), None);
+    }
+}
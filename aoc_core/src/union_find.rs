@@ -0,0 +1,187 @@
+//! A disjoint-set forest for tracking which elements are connected.
+
+/// Partition of the elements `0..len` into disjoint sets, initially all singletons
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    sizes: Vec<usize>,
+    num_sets: usize,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> Self {
+        Self { parents: (0..len).collect(), ranks: vec![0; len], sizes: vec![1; len], num_sets: len }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Returns the representative of the set containing `i`, compressing the path to it. Panics if
+    /// `i` is out of bounds.
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut i = i;
+        while self.parents[i] != root {
+            i = std::mem::replace(&mut self.parents[i], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `i` and `j`. Returns whether they were different sets before.
+    pub fn union(&mut self, i: usize, j: usize) -> bool {
+        let (mut a, mut b) = (self.find(i), self.find(j));
+        if a == b {
+            return false;
+        }
+        if self.ranks[a] < self.ranks[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if self.ranks[a] == self.ranks[b] {
+            self.ranks[a] += 1;
+        }
+        self.num_sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, i: usize, j: usize) -> bool {
+        self.find(i) == self.find(j)
+    }
+
+    /// Number of elements in the set containing `i`
+    pub fn set_size(&mut self, i: usize) -> usize {
+        let root = self.find(i);
+        self.sizes[root]
+    }
+
+    /// The sets as lists of their elements in increasing order, ordered by their smallest element
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = vec![usize::MAX; self.len()];
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.len() {
+            let root = self.find(i);
+            if index_of_root[root] == usize::MAX {
+                index_of_root[root] = sets.len();
+                sets.push(Vec::new());
+            }
+            sets[index_of_root[root]].push(i);
+        }
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Rng, SeededRng};
+    use std::time::Instant;
+
+    /// Pairs of random elements below `len`
+    fn random_pairs(len: usize, num_pairs: usize, seed: u64) -> Vec<(usize, usize)> {
+        let mut rng = SeededRng::new(seed);
+        let mut next = || rng.index(len);
+        (0..num_pairs).map(|_| (next(), next())).collect()
+    }
+
+    /// Labels every element with the smallest element connected to it, by relabeling until nothing
+    /// changes
+    fn naive_components(len: usize, pairs: &[(usize, usize)]) -> Vec<usize> {
+        let mut labels: Vec<usize> = (0..len).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(i, j) in pairs {
+                let label = labels[i].min(labels[j]);
+                for k in [i, j] {
+                    if labels[k] != label {
+                        labels[k] = label;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    #[test]
+    fn small() {
+        let mut sets = UnionFind::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(4, 3));
+        assert!(sets.union(1, 4));
+        assert!(!sets.union(3, 0));
+        assert!(sets.connected(0, 3) && !sets.connected(2, 5));
+        assert_eq!(sets.num_sets(), 3);
+        assert_eq!(sets.set_size(4), 4);
+        assert_eq!(sets.sets(), vec![vec![0, 1, 3, 4], vec![2], vec![5]]);
+    }
+
+    #[test]
+    fn matches_naive_components() {
+        for seed in 0..50 {
+            let len = 1 + seed as usize % 40;
+            let pairs = random_pairs(len, seed as usize % 30, seed);
+            let labels = naive_components(len, &pairs);
+            let mut sets = UnionFind::new(len);
+            for &(i, j) in &pairs {
+                sets.union(i, j);
+            }
+            for i in 0..len {
+                for j in 0..len {
+                    assert_eq!(sets.connected(i, j), labels[i] == labels[j], "seed {seed}, {i} and {j}");
+                }
+                assert_eq!(sets.set_size(i), labels.iter().filter(|&&label| label == labels[i]).count());
+            }
+            let mut distinct = labels.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(sets.num_sets(), distinct.len());
+            assert_eq!(sets.sets().iter().map(Vec::len).sum::<usize>(), len);
+        }
+    }
+
+    #[test]
+    fn union_order_does_not_matter() {
+        let pairs = random_pairs(100, 80, 7);
+        let mut forward = UnionFind::new(100);
+        let mut backward = UnionFind::new(100);
+        for &(i, j) in &pairs {
+            forward.union(i, j);
+        }
+        for &(i, j) in pairs.iter().rev() {
+            backward.union(j, i);
+        }
+        assert_eq!(forward.sets(), backward.sets());
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_union_find() {
+        let len = 1_000_000;
+        let pairs = random_pairs(len, 2 * len, 42);
+        let start = Instant::now();
+        let mut sets = UnionFind::new(len);
+        for &(i, j) in &pairs {
+            sets.union(i, j);
+        }
+        let num_connected = pairs.iter().filter(|&&(i, j)| sets.connected(i, j)).count();
+        println!("Union-find with {len} elements, {} unions: {:?}", pairs.len(), start.elapsed());
+        assert_eq!(num_connected, pairs.len());
+    }
+}
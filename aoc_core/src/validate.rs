@@ -0,0 +1,137 @@
+//! Reporting problems in puzzle inputs without solving the puzzles.
+
+use std::fmt;
+
+/// A problem found in an input, with 1-based line and column numbers where known
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Issue {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Issue {
+    /// An issue concerning the input as a whole.
+    pub fn new(message: impl ToString) -> Self {
+        Self { line: None, column: None, message: message.to_string() }
+    }
+
+    /// An issue concerning the given line.
+    pub fn at_line(line: usize, message: impl ToString) -> Self {
+        Self { line: Some(line), column: None, message: message.to_string() }
+    }
+
+    /// An issue concerning the given position.
+    pub fn at(line: usize, column: usize, message: impl ToString) -> Self {
+        Self { line: Some(line), column: Some(column), message: message.to_string() }
+    }
+
+    /// Moves the issue down by `num_lines` lines, for issues found in a part of the input.
+    pub fn shifted(mut self, num_lines: usize) -> Self {
+        self.line = self.line.map(|line| line + num_lines);
+        self
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "Line {line}, column {column}: {}", self.message),
+            (Some(line), None) => write!(f, "Line {line}: {}", self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Runs `parse` on every line of the input and reports each failure at its line.
+pub fn check_lines<T, E: fmt::Display>(input: &str, mut parse: impl FnMut(&str) -> Result<T, E>) -> Vec<Issue> {
+    input.lines().enumerate()
+        .filter_map(|(i, line)| parse(line).err().map(|e| Issue::at_line(i + 1, e)))
+        .collect()
+}
+
+/// Runs a parser on the whole input and reports its failure, if any.
+pub fn check_all<T, E: fmt::Display>(result: Result<T, E>) -> Vec<Issue> {
+    result.err().map(Issue::new).into_iter().collect()
+}
+
+/// Checks that the input is a non-empty rectangular grid and reports every line whose length
+/// differs from the first one and every character for which `allowed` returns false.
+pub fn check_grid(input: &str, allowed: impl Fn(char) -> bool) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut lines = input.lines().enumerate().peekable();
+    let width = match lines.peek() {
+        Some((_, line)) => line.chars().count(),
+        None => return vec![Issue::new("Empty input")],
+    };
+    for (i, line) in lines {
+        let len = line.chars().count();
+        if len != width {
+            issues.push(Issue::at_line(i + 1, format!("Line has length {len} but the first line has length {width}")));
+        }
+        for (j, c) in line.chars().enumerate() {
+            if !allowed(c) {
+                issues.push(Issue::at(i + 1, j + 1, format!("Invalid character {c:?}")));
+            }
+        }
+    }
+    issues
+}
+
+/// Splits the input into blocks separated by empty lines and returns each block together with the
+/// number of lines preceding it.
+pub fn blocks(input: &str) -> Vec<(usize, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            blocks.extend(current.take());
+        } else {
+            let (_, block) = current.get_or_insert_with(|| (i, String::new()));
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    blocks.extend(current);
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_display() {
+        assert_eq!(Issue::new("Empty input").to_string(), "Empty input");
+        assert_eq!(Issue::at_line(3, "Bad line").to_string(), "Line 3: Bad line");
+        assert_eq!(Issue::at(3, 5, "Bad char").shifted(2).to_string(), "Line 5, column 5: Bad char");
+    }
+
+    #[test]
+    fn lines_reported_with_numbers() {
+        let issues = check_lines("1\nx\n3\ny", |line| line.parse::<u32>());
+        let lines: Vec<_> = issues.iter().map(|issue| issue.line).collect();
+        assert_eq!(lines, vec![Some(2), Some(4)]);
+    }
+
+    #[test]
+    fn grid_issues() {
+        let issues = check_grid("..#\n.x\n#..", |c| c == '.' || c == '#');
+        assert_eq!(issues, vec![
+            Issue::at_line(2, "Line has length 2 but the first line has length 3"),
+            Issue::at(2, 2, "Invalid character 'x'"),
+        ]);
+        assert_eq!(check_grid("", |_| true), vec![Issue::new("Empty input")]);
+        assert!(check_grid("ab\ncd", |c| c.is_ascii_lowercase()).is_empty());
+    }
+
+    #[test]
+    fn blocks_with_offsets() {
+        let blocks = blocks("a\nb\n\nc\n\n\nd\n");
+        assert_eq!(blocks, vec![
+            (0, "a\nb\n".to_string()),
+            (3, "c\n".to_string()),
+            (6, "d\n".to_string()),
+        ]);
+    }
+}
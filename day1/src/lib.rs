@@ -1,34 +1,129 @@
 use std::error::Error;
 use std::fs;
 
+use aoc_core::answer::{Answer, PartSolver};
+use aoc_core::validate::Issue;
+
+mod matcher;
+mod parallel;
 mod part1;
 mod part2;
+mod policy;
+mod stats;
+
+pub use matcher::Matcher;
+pub use parallel::{calibrate_with, sum_parallel};
+pub use part2::digit_matcher;
+pub use policy::DigitPolicy;
+pub use stats::{calibration_values, to_csv, CalibrationValue, Part, Stats};
 
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print statistics of the calibration values instead of only their sums
+    pub stats: bool,
+    /// File to which the calibration values are written as CSV, one file per part with the
+    /// suffixes `-part1` and `-part2`
+    pub csv_path: Option<String>,
+    /// How the calibration values are formed from the digits when solving
+    pub policy: DigitPolicy,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day1 <file for part 1> <file for part 2> [--stats] [--csv <file>] [--policy first-last|first-two|sum|last]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut stats = false;
+        let mut csv_path = None;
+        let mut policy = DigitPolicy::default();
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--stats" => stats = true,
+                "--csv" => csv_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--policy" => policy = DigitPolicy::try_from(iter.next().ok_or(USAGE)?.as_str()).map_err(|_| USAGE)?,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            stats,
+            csv_path,
+            policy,
         })
     }
 }
 
+/// Inserts `suffix` before the extension of `path`, if any.
+fn csv_file_name(path: &str, suffix: &str) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !extension.contains('/') => format!("{stem}{suffix}.{extension}"),
+        _ => format!("{path}{suffix}"),
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    part1::run(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    part2::run(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines from which no calibration value can be read in either part.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if let Err(e) = part1::find_first_and_last_digits(line, i + 1) {
+            issues.push(Issue::at_line(i + 1, format!("Part 1: {e}")));
+        }
+        if let Err(e) = part2::find_first_and_last_digits(line, i + 1) {
+            issues.push(Issue::at_line(i + 1, format!("Part 2: {e}")));
+        }
+    }
+    issues
+}
+
+/// Prints statistics of the calibration values of a document and writes them to a CSV file if
+/// requested.
+fn analyze(contents: &str, part: Part, csv_path: Option<String>) -> Result<(), Box<dyn Error>> {
+    let values = calibration_values(contents, part).collect::<Result<Vec<_>, _>>()?;
+    print!("{}", Stats::from_values(values.iter().copied()));
+    if let Some(csv_path) = csv_path {
+        println!("Writing calibration values to {csv_path}");
+        fs::write(csv_path, to_csv(values))?;
+    }
+    Ok(())
+}
+
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    if config.stats || config.csv_path.is_some() {
+        for (part, file_path, n) in [(Part::One, &config.file_path1, 1), (Part::Two, &config.file_path2, 2)] {
+            println!("Part {n}: Reading file {file_path}");
+            let contents = fs::read_to_string(file_path)?;
+            let csv_path = config.csv_path.as_ref().map(|path| csv_file_name(path, &format!("-part{n}")));
+            analyze(&contents, part, csv_path)?;
+        }
+        return Ok(());
+    }
+
     println!("Part 1: Reading file {}", config.file_path1);
     let contents = fs::read_to_string(config.file_path1)?;
-    part1::run(&contents)?;
+    let result: Answer = calibrate_with(&contents, Part::One, config.policy)?.into();
+    println!("Result of part 1: {result}");
 
     println!("Part 2: Reading file {}", config.file_path2);
     let contents = fs::read_to_string(config.file_path2)?;
-    part2::run(&contents)?;
+    let result: Answer = calibrate_with(&contents, Part::Two, config.policy)?.into();
+    println!("Result of part 2: {result}");
 
     Ok(())
 }
@@ -0,0 +1,178 @@
+//! Finding several byte patterns in one pass with an Aho-Corasick automaton
+
+/// Deterministic automaton that reports the occurrences of a set of byte patterns, each with a
+/// value, in a single pass over the input
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    /// Class of each byte; bytes not occurring in any pattern share class 0
+    classes: [u8; 256],
+    num_classes: usize,
+    /// Next state for each state and class, state by state
+    transitions: Vec<u32>,
+    /// Length and value of each pattern ending in each state, longest first
+    outputs: Vec<Vec<(usize, u32)>>,
+}
+
+impl Matcher {
+    /// Builds the automaton for the given patterns and their values. Fails if a pattern is empty or
+    /// the patterns use more than 255 distinct bytes.
+    pub fn new(patterns: &[(&[u8], u32)]) -> Result<Self, &'static str> {
+        let mut classes = [0u8; 256];
+        let mut num_classes = 1;
+        for &(pattern, _) in patterns {
+            if pattern.is_empty() {
+                return Err("Empty pattern");
+            }
+            for &b in pattern {
+                if classes[b as usize] == 0 {
+                    classes[b as usize] = u8::try_from(num_classes).map_err(|_| "Too many distinct bytes")?;
+                    num_classes += 1;
+                }
+            }
+        }
+
+        // Trie of the patterns, where a missing edge is 0, which is never the target of an edge
+        let mut trie: Vec<Vec<u32>> = vec![vec![0; num_classes]];
+        let mut outputs: Vec<Vec<(usize, u32)>> = vec![Vec::new()];
+        for &(pattern, value) in patterns {
+            let mut state = 0;
+            for &b in pattern {
+                let class = classes[b as usize] as usize;
+                if trie[state][class] == 0 {
+                    trie[state][class] = trie.len() as u32;
+                    trie.push(vec![0; num_classes]);
+                    outputs.push(Vec::new());
+                }
+                state = trie[state][class] as usize;
+            }
+            outputs[state].push((pattern.len(), value));
+        }
+
+        // Complete the trie to a DFA breadth-first, following the failure links for missing edges.
+        // A state inherits the outputs of its failure state, which are shorter.
+        let mut transitions = vec![0u32; trie.len() * num_classes];
+        let mut failure = vec![0usize; trie.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for class in 0..num_classes {
+            let child = trie[0][class] as usize;
+            transitions[class] = child as u32;
+            if child != 0 {
+                queue.push_back(child);
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[failure[state]].clone();
+            outputs[state].extend(inherited);
+            for class in 0..num_classes {
+                let child = trie[state][class] as usize;
+                let fallback = transitions[failure[state] * num_classes + class];
+                if child == 0 {
+                    transitions[state * num_classes + class] = fallback;
+                } else {
+                    transitions[state * num_classes + class] = child as u32;
+                    failure[child] = fallback as usize;
+                    queue.push_back(child);
+                }
+            }
+        }
+        Ok(Self { classes, num_classes, transitions, outputs })
+    }
+
+    /// Calls `found` with the start and the value of each occurrence of a pattern, ordered by their
+    /// end and, for equal ends, from the longest pattern to the shortest.
+    pub fn for_each_match(&self, haystack: &[u8], mut found: impl FnMut(usize, u32)) {
+        let mut state = 0;
+        for (i, &b) in haystack.iter().enumerate() {
+            state = self.transitions[state * self.num_classes + self.classes[b as usize] as usize] as usize;
+            for &(len, value) in &self.outputs[state] {
+                found(i + 1 - len, value);
+            }
+        }
+    }
+
+    /// The values of the occurrences that start first and last, or `None` if no pattern occurs.
+    /// Of several occurrences starting at the same position, the longest pattern counts.
+    pub fn first_and_last(&self, haystack: &[u8]) -> Option<(u32, u32)> {
+        let mut first: Option<(usize, u32)> = None;
+        let mut last: Option<(usize, u32)> = None;
+        self.for_each_match(haystack, |start, value| {
+            // An occurrence reported later with the same start is longer
+            if first.is_none_or(|(first_start, _)| start <= first_start) {
+                first = Some((start, value));
+            }
+            if last.is_none_or(|(last_start, _)| start >= last_start) {
+                last = Some((start, value));
+            }
+        });
+        Some((first?.1, last?.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc_core::rng::{Rng, SeededRng};
+
+    #[test]
+    fn overlapping_patterns() -> Result<(), &'static str> {
+        let matcher = Matcher::new(&[(b"he", 1), (b"she", 2), (b"his", 3), (b"hers", 4)])?;
+        let mut matches = Vec::new();
+        matcher.for_each_match(b"ushers", |start, value| matches.push((start, value)));
+        assert_eq!(matches, [(1, 2), (2, 1), (2, 4)]);
+        assert_eq!(matcher.first_and_last(b"ushers"), Some((2, 4)));
+        assert_eq!(matcher.first_and_last(b"xyz"), None);
+        assert_eq!(matcher.first_and_last(b""), None);
+        assert!(Matcher::new(&[(b"", 0)]).is_err());
+        Ok(())
+    }
+
+    /// The first and last digit by searching for each pattern separately
+    fn naive_first_and_last(line: &str) -> Option<(u32, u32)> {
+        let words = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+        let patterns = (0..10).map(|d| (d.to_string(), d)).chain(words.iter().zip(0..).map(|(w, d)| (w.to_string(), d)));
+        let mut first: Option<(usize, u32)> = None;
+        let mut last: Option<(usize, u32)> = None;
+        for (pattern, digit) in patterns {
+            if let Some(i) = line.find(&pattern).filter(|&i| first.is_none_or(|(j, _)| i < j)) {
+                first = Some((i, digit));
+            }
+            if let Some(i) = line.rfind(&pattern).filter(|&i| last.is_none_or(|(j, _)| i > j)) {
+                last = Some((i, digit));
+            }
+        }
+        Some((first?.1, last?.1))
+    }
+
+    /// Random lines of lowercase letters and digits, often containing spelled-out digits
+    fn random_lines(count: usize, seed: u64) -> Vec<String> {
+        let mut rng = SeededRng::new(seed);
+        let mut random = move |max: usize| rng.index(max);
+        let pieces = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "zero", "x", "e", "n", "t", "7", "é"];
+        (0..count).map(|_| (0..random(12)).map(|_| pieces[random(pieces.len())]).collect()).collect()
+    }
+
+    #[test]
+    fn digits_match_naive_search() {
+        let matcher = crate::digit_matcher();
+        for line in random_lines(10_000, 1) {
+            assert_eq!(matcher.first_and_last(line.as_bytes()), naive_first_and_last(&line), "{line}");
+        }
+    }
+
+    /// Compares the matcher with searching for each pattern separately on 1M lines. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_digit_matcher() {
+        let lines = random_lines(1_000_000, 2);
+        let matcher = crate::digit_matcher();
+        let start = std::time::Instant::now();
+        let naive: Vec<_> = lines.iter().map(|line| naive_first_and_last(line)).collect();
+        println!("Separate searches: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let matched: Vec<_> = lines.iter().map(|line| matcher.first_and_last(line.as_bytes())).collect();
+        println!("Matcher: {:?}", start.elapsed());
+        assert_eq!(matched, naive);
+    }
+}
@@ -0,0 +1,122 @@
+//! Summing the calibration values of long documents in parallel
+
+use std::error::Error;
+
+use aoc_core::parallel;
+
+use crate::{DigitPolicy, Part};
+
+/// Approximate length in bytes of the pieces of the document that are processed in parallel
+const CHUNK_LEN: usize = 1 << 20;
+
+/// Splits the document into pieces of about `chunk_len` bytes, each ending after a line break, such
+/// that no line is split. Each piece comes with the 1-based number of its first line.
+fn chunks(input: &str, chunk_len: usize) -> Vec<(usize, &str)> {
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    let mut line_nr = 1;
+    while rest.len() > chunk_len {
+        let Some(i) = rest.bytes().skip(chunk_len).position(|b| b == b'\n') else {
+            break;
+        };
+        let (chunk, tail) = rest.split_at(chunk_len + i + 1);
+        chunks.push((line_nr, chunk));
+        line_nr += chunk.bytes().filter(|&b| b == b'\n').count();
+        rest = tail;
+    }
+    chunks.push((line_nr, rest));
+    chunks
+}
+
+/// Sums the calibration values of all lines, processing pieces of long documents in parallel.
+/// Fails with the errors of all lines without a digit, in the order of the lines.
+pub fn sum_parallel(input: &str, part: Part) -> Result<u64, Box<dyn Error>> {
+    calibrate_with(input, part, DigitPolicy::default())
+}
+
+/// Sums the calibration values of all lines like `sum_parallel`, forming the values as specified
+/// by `policy`. Fails with the errors of all lines lacking the digits needed.
+pub fn calibrate_with(input: &str, part: Part, policy: DigitPolicy) -> Result<u64, Box<dyn Error>> {
+    let results = parallel::map(&chunks(input, CHUNK_LEN), |&(first_line_nr, chunk)| {
+        let mut sum = 0;
+        let mut errors = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            match policy.value(line, first_line_nr + i, part) {
+                Ok(value) => sum += value as u64,
+                Err(e) => errors.push(e),
+            }
+        }
+        (sum, errors)
+    });
+    let mut sum = 0;
+    let mut errors = Vec::new();
+    for (chunk_sum, chunk_errors) in results {
+        sum += chunk_sum;
+        errors.extend(chunk_errors);
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n").into());
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::calibration_values;
+
+    #[test]
+    fn chunks_end_at_line_breaks() {
+        assert_eq!(chunks("a1\nb2\nc3\nd4", 4), [(1, "a1\nb2\n"), (3, "c3\nd4")]);
+        assert_eq!(chunks("a1\nb2", 100), [(1, "a1\nb2")]);
+        assert_eq!(chunks("", 4), [(1, "")]);
+    }
+
+    #[test]
+    fn policies() -> Result<(), Box<dyn Error>> {
+        let input = "a1b2c3
+xtwone4
+7";
+        assert_eq!(calibrate_with(input, Part::One, DigitPolicy::FirstAndLast)?, 13 + 44 + 77);
+        assert_eq!(calibrate_with(input, Part::One, DigitPolicy::Sum)?, 6 + 4 + 7);
+        assert_eq!(calibrate_with(input, Part::One, DigitPolicy::LastOnly)?, 3 + 4 + 7);
+        assert_eq!(calibrate_with("a1b2c3\nxtwone4", Part::Two, DigitPolicy::FirstTwo)?, 12 + 21);
+        assert_eq!(calibrate_with(input, Part::Two, DigitPolicy::Sum)?, 6 + 7 + 7);
+        assert_eq!(calibrate_with(input, Part::Two, DigitPolicy::LastOnly)?, 3 + 4 + 7);
+        let err = calibrate_with(input, Part::One, DigitPolicy::FirstTwo).unwrap_err().to_string();
+        assert_eq!(err, "Line 2 does not contain two digits\nLine 3 does not contain two digits");
+        assert!(calibrate_with("abc", Part::Two, DigitPolicy::Sum).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn errors_of_all_lines() {
+        let input = "a1\nxyz\nb2\n".repeat(100_000);
+        let err = sum_parallel(&input, Part::One).unwrap_err().to_string();
+        let lines: Vec<&str> = err.lines().collect();
+        assert_eq!(lines.len(), 100_000);
+        assert_eq!(lines[0], "Line 2 does not contain a digit");
+        assert_eq!(lines[99_999], "Line 299999 does not contain a digit");
+    }
+
+    /// Sums the calibration values of a document with 10M lines. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_long_document() -> Result<(), Box<dyn Error>> {
+        let lines = ["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet", "two1nine", "eigh7wothree",
+            "abcone2threexyz", "xtwone3four", "4nineeightseven2", "zoneight234"];
+        let document = (lines.join("\n") + "\n").repeat(1_000_000);
+        for part in [Part::One, Part::Two] {
+            let start = std::time::Instant::now();
+            let sequential = calibration_values(&document, part).map(|value| value.map(|v| v.value as u64)).sum::<Result<u64, _>>()?;
+            println!("{part:?} sequential: {sequential} in {:?}", start.elapsed());
+            let start = std::time::Instant::now();
+            let parallel = sum_parallel(&document, part)?;
+            println!("{part:?} parallel: {parallel} in {:?}", start.elapsed());
+            assert_eq!(parallel, sequential);
+        }
+        Ok(())
+    }
+}
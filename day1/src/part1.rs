@@ -1,24 +1,22 @@
 use std::error::Error;
 
-fn nth_char_to_digit(s: &str, n: usize) -> u32 {
-    s.chars().nth(n).unwrap().to_digit(10).unwrap()
-}
+use aoc_core::answer::Answer;
 
-fn find_first_and_last_digits(line: &str, line_nr: usize) -> Result<(u32, u32), String> {
-    let first_digit = line.find(char::is_numeric).map(|i| nth_char_to_digit(line, i));
-    let last_digit = line.rfind(char::is_numeric).map(|i| nth_char_to_digit(line, i));
+pub(crate) fn find_first_and_last_digits(line: &str, line_nr: usize) -> Result<(u32, u32), String> {
+    let bytes = line.as_bytes();
+    let first_digit = bytes.iter().find(|b| b.is_ascii_digit()).map(|b| (b - b'0') as u32);
+    let last_digit = bytes.iter().rfind(|b| b.is_ascii_digit()).map(|b| (b - b'0') as u32);
     match (first_digit, last_digit) {
         (Some(fd), Some(ld)) => Ok((fd, ld)),
         _ => Err(format!("Line {line_nr} does not contain a digit")),
     }
 }
 
-pub fn run(contents: &String) -> Result<(), Box<dyn Error>> {
-    let mut sum = 0;
-    for (i, line) in contents.lines().enumerate() {
-        let (fd, ld) = find_first_and_last_digits(line, i+1)?;
-        sum += 10 * fd + ld;
-    }
-    println!("Sum for part 1: {sum}");
-    Ok(())
+/// The digits of a line in order
+pub(crate) fn find_digits(line: &str) -> Vec<u32> {
+    line.bytes().filter(u8::is_ascii_digit).map(|b| (b - b'0') as u32).collect()
+}
+
+pub fn run(contents: &str) -> Result<Answer, Box<dyn Error>> {
+    Ok(crate::sum_parallel(contents, crate::Part::One)?.into())
 }
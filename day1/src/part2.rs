@@ -1,68 +1,56 @@
 use std::error::Error;
 
-const DIGITS: [(&str, u32); 20] = [
-    ("0", 0),
-    ("1", 1),
-    ("2", 2),
-    ("3", 3),
-    ("4", 4),
-    ("5", 5),
-    ("6", 6),
-    ("7", 7),
-    ("8", 8),
-    ("9", 9),
-    ("zero", 0),
-    ("one", 1),
-    ("two", 2),
-    ("three", 3),
-    ("four", 4),
-    ("five", 5),
-    ("six", 6),
-    ("seven", 7),
-    ("eight", 8),
-    ("nine", 9),
+use std::sync::LazyLock;
+
+use aoc_core::answer::Answer;
+
+use crate::Matcher;
+
+const DIGITS: [(&[u8], u32); 20] = [
+    (b"0", 0),
+    (b"1", 1),
+    (b"2", 2),
+    (b"3", 3),
+    (b"4", 4),
+    (b"5", 5),
+    (b"6", 6),
+    (b"7", 7),
+    (b"8", 8),
+    (b"9", 9),
+    (b"zero", 0),
+    (b"one", 1),
+    (b"two", 2),
+    (b"three", 3),
+    (b"four", 4),
+    (b"five", 5),
+    (b"six", 6),
+    (b"seven", 7),
+    (b"eight", 8),
+    (b"nine", 9),
 ];
 
-fn find_first_and_last_digits(line: &str, line_nr: usize) -> Result<(u32, u32), String> {
-    let mut first_digit_index: Option<usize> = None;
-    let mut last_digit_index: Option<usize> = None;
-    let mut first_digit: Option<u32> = None;
-    let mut last_digit: Option<u32> = None;
-    for (pattern, digit) in DIGITS {
-        // Find first occurrence of this digit
-        match line.find(pattern) {
-            Some(i) => {
-                if first_digit_index.map_or(true, |old_i| old_i > i) {
-                    first_digit_index = Some(i);
-                    first_digit = Some(digit);
-                }
-            },
-            None => (),
-        }
+/// Finds the digits and spelled-out digits of a line in one pass
+static MATCHER: LazyLock<Matcher> = LazyLock::new(digit_matcher);
+
+/// A matcher for the digits, written as digits or spelled out, with their values
+pub fn digit_matcher() -> Matcher {
+    Matcher::new(&DIGITS).expect("The digit patterns are not empty")
+}
+
+pub(crate) fn find_first_and_last_digits(line: &str, line_nr: usize) -> Result<(u32, u32), String> {
+    MATCHER.first_and_last(line.as_bytes())
+        .ok_or_else(|| format!("Line {} does not contain a digit (spelled out or not)", line_nr))
+}
 
-        // Find last occurrence of this digit
-        match line.rfind(pattern) {
-            Some(i) => {
-                if last_digit_index.map_or(true, |old_i| old_i < i) {
-                    last_digit_index = Some(i);
-                    last_digit = Some(digit);
-                }
-            },
-            None => (),
-        }
-    }
-    match (first_digit, last_digit) {
-        (Some(fd), Some(ld)) => Ok((fd, ld)),
-        _ => Err(format!("Line {} does not contain a digit (spelled out or not)", line_nr)),
-    }
+/// The digits of a line in the order they start, spelled out or not. Spelled-out digits may
+/// overlap, as in "twone".
+pub(crate) fn find_digits(line: &str) -> Vec<u32> {
+    let mut digits = Vec::new();
+    MATCHER.for_each_match(line.as_bytes(), |start, value| digits.push((start, value)));
+    digits.sort_by_key(|&(start, _)| start);
+    digits.into_iter().map(|(_, value)| value).collect()
 }
 
-pub fn run(contents: &String) -> Result<(), Box<dyn Error>> {
-    let mut sum = 0;
-    for (i, line) in contents.lines().enumerate() {
-        let (fd, ld) = find_first_and_last_digits(line, i+1)?;
-        sum += 10 * fd + ld;
-    }
-    println!("Sum for part 2: {sum}");
-    Ok(())
+pub fn run(contents: &str) -> Result<Answer, Box<dyn Error>> {
+    Ok(crate::sum_parallel(contents, crate::Part::Two)?.into())
 }
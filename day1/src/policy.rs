@@ -0,0 +1,54 @@
+use crate::Part;
+
+/// How the calibration value of a line is formed from the digits in it
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DigitPolicy {
+    /// The first digit followed by the last one, as in the puzzle
+    #[default]
+    FirstAndLast,
+    /// The first digit followed by the second one
+    FirstTwo,
+    /// The sum of all digits
+    Sum,
+    /// Only the last digit
+    LastOnly,
+}
+
+impl TryFrom<&str> for DigitPolicy {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "first-last" => Ok(DigitPolicy::FirstAndLast),
+            "first-two" => Ok(DigitPolicy::FirstTwo),
+            "sum" => Ok(DigitPolicy::Sum),
+            "last" => Ok(DigitPolicy::LastOnly),
+            _ => Err("Expected first-last, first-two, sum or last"),
+        }
+    }
+}
+
+impl DigitPolicy {
+    /// Returns the calibration value of a line, or an error if the line does not have the digits
+    /// the policy needs.
+    pub(crate) fn value(self, line: &str, line_nr: usize, part: Part) -> Result<u32, String> {
+        if self == DigitPolicy::FirstAndLast {
+            let (fd, ld) = match part {
+                Part::One => crate::part1::find_first_and_last_digits(line, line_nr)?,
+                Part::Two => crate::part2::find_first_and_last_digits(line, line_nr)?,
+            };
+            return Ok(10 * fd + ld);
+        }
+        let (digits, kind) = match part {
+            Part::One => (crate::part1::find_digits(line), ""),
+            Part::Two => (crate::part2::find_digits(line), " (spelled out or not)"),
+        };
+        match (self, digits.as_slice()) {
+            (DigitPolicy::FirstTwo, [first, second, ..]) => Ok(10 * first + second),
+            (DigitPolicy::FirstTwo, _) => Err(format!("Line {line_nr} does not contain two digits{kind}")),
+            (DigitPolicy::Sum, [_, ..]) => Ok(digits.iter().sum()),
+            (DigitPolicy::LastOnly, [.., last]) => Ok(*last),
+            _ => Err(format!("Line {line_nr} does not contain a digit{kind}")),
+        }
+    }
+}
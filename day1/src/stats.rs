@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Which reading of the calibration document to use
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Part {
+    /// Only digits count
+    One,
+    /// Spelled-out digits count as well
+    Two,
+}
+
+/// A calibration value together with the 1-based number of the line it was read from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CalibrationValue {
+    pub line_nr: usize,
+    pub value: u32,
+}
+
+/// Returns the calibration value of each line, or an error for lines without a digit.
+pub fn calibration_values(input: &str, part: Part) -> impl Iterator<Item = Result<CalibrationValue, String>> + '_ {
+    let find = match part {
+        Part::One => crate::part1::find_first_and_last_digits,
+        Part::Two => crate::part2::find_first_and_last_digits,
+    };
+    input.lines().enumerate().map(move |(i, line)| {
+        let (fd, ld) = find(line, i + 1)?;
+        Ok(CalibrationValue { line_nr: i + 1, value: 10 * fd + ld })
+    })
+}
+
+/// Summary statistics of the calibration values of a document
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub count: usize,
+    pub sum: u64,
+    /// First line with the smallest value
+    pub min: Option<CalibrationValue>,
+    /// First line with the greatest value
+    pub max: Option<CalibrationValue>,
+    /// Number of lines for each value that occurs
+    pub histogram: BTreeMap<u32, usize>,
+}
+
+impl Stats {
+    pub fn from_values(values: impl IntoIterator<Item = CalibrationValue>) -> Self {
+        let mut stats = Self::default();
+        for value in values {
+            stats.count += 1;
+            stats.sum += value.value as u64;
+            if stats.min.is_none_or(|min| value.value < min.value) {
+                stats.min = Some(value);
+            }
+            if stats.max.is_none_or(|max| value.value > max.value) {
+                stats.max = Some(value);
+            }
+            *stats.histogram.entry(value.value).or_default() += 1;
+        }
+        stats
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Lines: {}", self.count)?;
+        writeln!(f, "Sum: {}", self.sum)?;
+        let (Some(min), Some(max), Some(mean)) = (self.min, self.max, self.mean()) else {
+            return Ok(());
+        };
+        writeln!(f, "Mean: {mean:.2}")?;
+        writeln!(f, "Min: {} (line {})", min.value, min.line_nr)?;
+        writeln!(f, "Max: {} (line {})", max.value, max.line_nr)?;
+        writeln!(f, "Histogram:")?;
+        let most_frequent = self.histogram.values().copied().max().unwrap_or(1);
+        for (value, &n) in &self.histogram {
+            let bar = "#".repeat((n * 50).div_ceil(most_frequent));
+            writeln!(f, "{value:>3} {n:>6} {bar}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the calibration values as CSV with the columns `line` and `value`.
+pub fn to_csv(values: impl IntoIterator<Item = CalibrationValue>) -> String {
+    let mut csv = String::from("line,value\n");
+    for value in values {
+        csv.push_str(&format!("{},{}\n", value.line_nr, value.value));
+    }
+    csv
+}
@@ -1,17 +1,51 @@
+use crate::ParseOptions;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// File to which the loop and the interior of part 2 are written as JSON
+    pub json_path: Option<String>,
+    pub options: ParseOptions,
+    /// File to which the maze of part 2 is written with all pipes off the loop removed
+    pub prune_path: Option<String>,
+    /// Whether to list all loops in the maze of part 2
+    pub loops: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day10 <file for part 1> <file for part 2> [--json <file>] [--start <tile>] [--partial] [--prune <file>] [--loops]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut json_path = None;
+        let mut options = ParseOptions::default();
+        let mut prune_path = None;
+        let mut loops = false;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--json" => json_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--start" => {
+                    let mut chars = iter.next().ok_or(USAGE)?.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => options.start_tile = Some(c),
+                        _ => return Err(USAGE),
+                    }
+                },
+                "--partial" => options.partial = true,
+                "--prune" => prune_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--loops" => loops = true,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            json_path,
+            options,
+            prune_path,
+            loops,
         })
     }
 }
-
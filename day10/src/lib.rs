@@ -1,9 +1,18 @@
+#![cfg_attr(all(feature = "no-panic-strict", not(test)), deny(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
 pub mod config;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parallel;
+use aoc_core::render::{Frame, FrameSource};
+use aoc_core::validate::{self, Issue};
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
 
+const BROKEN_LOOP: &str = "The pipes do not form a loop through the starting position";
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Tile {
     NS, EW, NE, NW, SW, SE, Ground, Start
@@ -44,6 +53,39 @@ impl Display for Tile {
 }
 
 impl Tile {
+    /// Character denoting the tile in the input
+    fn to_char(self) -> char {
+        match self {
+            Tile::NS => '|',
+            Tile::EW => '-',
+            Tile::NE => 'L',
+            Tile::NW => 'J',
+            Tile::SW => '7',
+            Tile::SE => 'F',
+            Tile::Ground => '.',
+            Tile::Start => 'S',
+        }
+    }
+
+    /// Heavy box-drawing character used for the tile once it is known to be part of the cycle
+    fn heavy_char(&self) -> char {
+        match self {
+            Tile::NS => '┃',
+            Tile::EW => '━',
+            Tile::NE => '┗',
+            Tile::NW => '┛',
+            Tile::SW => '┓',
+            Tile::SE => '┏',
+            Tile::Ground => ' ',
+            Tile::Start => 'S',
+        }
+    }
+
+    /// Position of the pipe in `PIPE_CHARS`, or `None` if the tile is not a pipe
+    fn pipe_index(self) -> Option<usize> {
+        PIPE_CHARS.iter().position(|&c| c == self.to_char())
+    }
+
     fn connects_up(&self) -> bool {
         *self == Tile::NS || *self == Tile::NE || *self == Tile::NW
     }
@@ -60,103 +102,220 @@ impl Tile {
         *self == Tile::EW || *self == Tile::SE || *self == Tile::NE
     }
 
-    fn neighbors(&self, (x, y): (usize, usize)) -> [(usize, usize); 2] {
+    /// The coordinates the pipe connects to, or `None` if the tile is not a pipe or connects to
+    /// negative coordinates
+    fn neighbors(&self, (x, y): (usize, usize)) -> Option<[(usize, usize); 2]> {
         // The two resulting coordinates are ordered depending on whether they are N, E, S or W of
         // the given coordinates, so that N before E before S before W.
+        let (n, e, s, w) = (y.checked_sub(1).map(|y| (x, y)), Some((x + 1, y)), Some((x, y + 1)), x.checked_sub(1).map(|x| (x, y)));
         match self {
-            Tile::NS => [(x, y-1), (x, y+1)],
-            Tile::EW => [(x+1, y), (x-1, y)],
-            Tile::NE => [(x, y-1), (x+1, y)],
-            Tile::NW => [(x, y-1), (x-1, y)],
-            Tile::SW => [(x, y+1), (x-1, y)],
-            Tile::SE => [(x+1, y), (x, y+1)],
-            _ => panic!("Unexpected tile"),
+            Tile::NS => Some([n?, s?]),
+            Tile::EW => Some([e?, w?]),
+            Tile::NE => Some([n?, e?]),
+            Tile::NW => Some([n?, w?]),
+            Tile::SW => Some([s?, w?]),
+            Tile::SE => Some([e?, s?]),
+            Tile::Ground | Tile::Start => None,
+        }
+    }
+}
+
+/// Characters of the pipes in the input, in the order in which `PipeCounts` counts them
+pub const PIPE_CHARS: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+/// Number of pipe tiles of each type, in the order of `PIPE_CHARS`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PipeCounts(pub [usize; 6]);
+
+impl PipeCounts {
+    /// Number of pipes denoted by the given character in the input, 0 for other characters
+    pub fn get(&self, c: char) -> usize {
+        PIPE_CHARS.iter().position(|&pipe| pipe == c).map_or(0, |i| self.0[i])
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+impl Display for PipeCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = PIPE_CHARS.iter().zip(self.0).map(|(c, count)| format!("{c} {count}")).collect();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+/// Options for reading a maze
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// The pipe at the starting position, given as its character in the input, instead of
+    /// inferring it from the neighboring tiles
+    pub start_tile: Option<char>,
+    /// Whether the maze may be cut out of a larger one, such that pipes at the border lead out of
+    /// it. If more than two neighbors connect to the starting position, the starting tile is then
+    /// the one for which the loop closes.
+    pub partial: bool,
+}
+
+/// Bit set of tiles of a loop. Each row starts at a new word, so that rows can be scanned
+/// independently of each other.
+struct CycleRows {
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl CycleRows {
+    fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self { words_per_row, bits: vec![0; words_per_row * height] }
+    }
+
+    fn insert(&mut self, x: usize, y: usize) {
+        self.bits[y * self.words_per_row + x / 64] |= 1 << (x % 64);
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        self.row(y)[x / 64] & (1 << (x % 64)) != 0
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.bits[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+}
+
+/// A ray cast from left to right along a row, similar to the crossing number algorithm
+/// (https://en.wikipedia.org/wiki/Point_in_polygon). It switches between outside and inside every
+/// time it traverses | (NS tile), L-*7 (NE, EW*, SW) and F-*J (SE, EW*, NW).
+#[derive(Default)]
+struct Ray {
+    inside: bool,
+    last_angle_read: Option<Tile>,
+}
+
+impl Ray {
+    /// Traverses a tile of the cycle.
+    fn cross(&mut self, tile: Tile) {
+        match tile {
+            Tile::NS => {
+                self.inside = !self.inside;
+            },
+            Tile::EW => (),
+            Tile::NE => {
+                self.last_angle_read = Some(tile);
+            },
+            Tile::NW => {
+                if self.last_angle_read == Some(Tile::SE) {
+                    self.inside = !self.inside;
+                }
+                self.last_angle_read = Some(tile);
+            },
+            Tile::SW => {
+                if self.last_angle_read == Some(Tile::NE) {
+                    self.inside = !self.inside;
+                }
+                self.last_angle_read = Some(tile);
+            },
+            Tile::SE => {
+                self.last_angle_read = Some(tile);
+            },
+            // The starting tile has been replaced by a pipe
+            Tile::Ground | Tile::Start => (),
         }
     }
 }
 
 #[derive(Debug)]
-struct Puzzle {
+pub struct Puzzle {
     width: usize,
     height: usize,
-    rows: Vec<Vec<Tile>>,
+    tiles: Vec<Tile>, // row by row
     start_row: usize,
     start_col: usize,
 }
 
 impl Display for Puzzle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.rows {
+        for row in self.tiles.chunks(self.width) {
             for tile in row {
                 write!(f, "{}", tile)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
 impl Puzzle {
-    fn read_row(line: &str, expected_length: usize) -> Result<Vec<Tile>, &str> {
-        let mut tiles = Vec::with_capacity(expected_length);
+    fn read_row(line: &str, expected_length: usize, tiles: &mut Vec<Tile>) -> Result<(), &'static str> {
         if line.len() != expected_length {
             return Err("Not all lines have equal length");
         }
-        for maybe_tile in line.chars().map(|c| Tile::try_from(c)) {
-            match maybe_tile {
-                Ok(tile) => tiles.push(tile),
-                Err(err) => return Err(err),
-            }
+        for tile in line.chars().map(Tile::try_from) {
+            tiles.push(tile?);
         }
-        Ok(tiles)
+        Ok(())
     }
 
-    fn update_start_position(row: &Vec<Tile>, row_index: usize, start_position: &mut Option<(usize, usize)>) -> Result<(), &'static str> {
-        match row.iter().position(|t| *t == Tile::Start) {
-            Some(i) => {
-                if start_position.is_some() {
-                    return Err("Multiple starting positions found");
-                }
-                *start_position = Some((i, row_index));
-            },
-            None => (),
-        }
-        Ok(())
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_input_with_options(input, &ParseOptions::default())
     }
 
-    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
-        let mut iter = input.lines();
-        let first_line = iter.next().ok_or("Input empty")?;
-        let width = first_line.len();
-        let mut height = 1;
-        let mut rows = Vec::new();
-        let mut start_position: Option<(usize, usize)> = None;
-        let row = Self::read_row(first_line, width)?;
-        Self::update_start_position(&row, 0, &mut start_position)?;
-        rows.push(row);
-        for line in iter {
-            let row = Self::read_row(line, width)?;
-            Self::update_start_position(&row, height, &mut start_position)?;
-            rows.push(row);
+    /// Reads the width, height and tiles of a maze, leaving the starting position as it is
+    fn read_tiles(input: &str) -> Result<(usize, usize, Vec<Tile>), Box<dyn Error>> {
+        let width = input.lines().next().ok_or("Input empty")?.len();
+        let mut height = 0;
+        let mut tiles = Vec::new();
+        for line in input.lines() {
+            Self::read_row(line, width, &mut tiles)?;
             height += 1;
         }
-        let start_position = start_position.ok_or("No starting position found")?;
-        let mut puzzle = Self { width, height, rows, start_row: start_position.1, start_col: start_position.0 };
-        puzzle.rows[puzzle.start_row][puzzle.start_col] = puzzle.infer_starting_tile()?;
+        Ok((width, height, tiles))
+    }
+
+    pub fn from_input_with_options(input: &str, options: &ParseOptions) -> Result<Self, Box<dyn Error>> {
+        let (width, height, tiles) = Self::read_tiles(input)?;
+        let mut start_positions = tiles.iter().enumerate().filter(|(_, &t)| t == Tile::Start).map(|(i, _)| i);
+        let start = start_positions.next().ok_or("No starting position found")?;
+        if start_positions.next().is_some() {
+            return Err("Multiple starting positions found".into());
+        }
+        let mut puzzle = Self { width, height, tiles, start_row: start / width, start_col: start % width };
+        let mut candidates = match options.start_tile {
+            Some(c) => match Tile::try_from(c)? {
+                Tile::Ground | Tile::Start => return Err("The starting tile must be a pipe".into()),
+                tile => vec![tile],
+            },
+            None => puzzle.starting_tile_candidates(),
+        };
+        if options.partial && candidates.len() > 1 {
+            candidates.retain(|&tile| {
+                puzzle.tiles[start] = tile;
+                puzzle.walk_cycle(|_, _| ()).is_ok()
+            });
+        }
+        if candidates.len() != 1 {
+            return Err(format!("There are {} possibilities for the starting tile", candidates.len()).into());
+        }
+        puzzle.tiles[start] = candidates[0];
         Ok(puzzle)
     }
 
-    fn infer_starting_tile(&self) -> Result<Tile, String> {
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        self.tiles[y * self.width + x]
+    }
+
+    /// The pipes the starting tile could be such that both its neighbors connect to it
+    fn starting_tile_candidates(&self) -> Vec<Tile> {
         let y = self.start_row;
         let x = self.start_col;
-        let above = if y > 0 { Some(&self.rows[y - 1][x]) } else { None };
-        let below = if y + 1 < self.height { Some(&self.rows[y + 1][x]) } else { None };
-        let left = if x > 0 { Some(&self.rows[y][x - 1]) } else { None };
-        let right = if x + 1 < self.width { Some(&self.rows[y][x + 1]) } else { None };
-        let connects_up = above.map_or_else(||false, |t| t.connects_down());
-        let connects_down = below.map_or_else(||false, |t| t.connects_up());
-        let connects_left = left.map_or_else(||false, |t| t.connects_right());
-        let connects_right = right.map_or_else(||false, |t| t.connects_left());
+        let above = if y > 0 { Some(self.tile(x, y - 1)) } else { None };
+        let below = if y + 1 < self.height { Some(self.tile(x, y + 1)) } else { None };
+        let left = if x > 0 { Some(self.tile(x - 1, y)) } else { None };
+        let right = if x + 1 < self.width { Some(self.tile(x + 1, y)) } else { None };
+        let connects_up = above.is_some_and(|t| t.connects_down());
+        let connects_down = below.is_some_and(|t| t.connects_up());
+        let connects_left = left.is_some_and(|t| t.connects_right());
+        let connects_right = right.is_some_and(|t| t.connects_left());
         let mut candidates = Vec::new();
         if connects_up {
             if connects_down {
@@ -180,118 +339,366 @@ impl Puzzle {
         if connects_left && connects_right {
             candidates.push(Tile::EW);
         }
-        if candidates.len() != 1 {
-            return Err(format!("There are {} possibilities for the starting tile", candidates.len()));
-        }
-        Ok(candidates.into_iter().next().unwrap())
+        candidates
     }
 
-    fn get_cycle(&self) -> Vec<(usize, usize)> {
-        let mut cycle = Vec::new();
-        let mut x = self.start_col;
-        let mut y = self.start_row;
-        cycle.push((x, y));
-        let mut prev_x = x;
-        let mut prev_y = y;
+    /// Calls `visit` with the coordinates of each tile of the cycle through the starting position,
+    /// beginning with the starting position. Fails if the pipes do not lead back to the start.
+    fn walk_cycle(&self, mut visit: impl FnMut(usize, usize)) -> Result<(), &'static str> {
+        let start = (self.start_col, self.start_row);
+        let (mut x, mut y) = start;
+        let (mut prev_x, mut prev_y) = start;
         loop {
-            let neighbors = self.rows[y][x].neighbors((x, y));
+            visit(x, y);
+            let neighbors = self.tile(x, y).neighbors((x, y)).ok_or(BROKEN_LOOP)?;
+            if (x, y) != start && !neighbors.contains(&(prev_x, prev_y)) {
+                return Err(BROKEN_LOOP);
+            }
             let next = if neighbors[0] == (prev_x, prev_y) { neighbors[1] } else { neighbors[0] };
+            if next.0 >= self.width || next.1 >= self.height {
+                return Err(BROKEN_LOOP);
+            }
             (prev_x, prev_y) = (x, y);
             (x, y) = next;
-            cycle.push((x, y));
-            if x == self.start_col && y == self.start_row {
+            if (x, y) == start {
+                // The starting tile must connect back to the last tile of the loop
+                let start_neighbors = self.tile(x, y).neighbors(start).ok_or(BROKEN_LOOP)?;
+                if !start_neighbors.contains(&(prev_x, prev_y)) {
+                    return Err(BROKEN_LOOP);
+                }
                 break;
             }
         }
-        cycle
+        Ok(())
     }
 
-    fn cycle_length(&self) -> usize {
-        self.get_cycle().len() - 1
+    fn cycle_length(&self) -> Result<usize, &'static str> {
+        let mut length = 0;
+        self.walk_cycle(|_, _| length += 1)?;
+        Ok(length)
     }
 
-    fn area_in_cycle(&self) -> usize {
-        let mut cycle = self.get_cycle();
-        // Remove the last element of the cycle because it's the same as the first and the
-        // duplicate would mess up our algorithm
-        assert_eq!(&cycle.pop().unwrap(), cycle.first().unwrap());
-        cycle.sort_by(|(x1, y1), (x2, y2)| y1.cmp(y2).then(x1.cmp(x2)));
-        // Similar to crossing number algorithm
-        // https://en.wikipedia.org/wiki/Point_in_polygon
-        // We cast a ray from left to right along each row. We switch between outside and inside
-        // every time we traverse | (NS tile), L-*7 (SE, EW*, SW) and F-*J (NE, EW*, NW).
-        let mut area = 0;
-        let mut inside;
-        let mut iter = cycle.iter();
-        let mut next_cycle_pos = iter.next().expect("No cycle");
+    /// Bit set of the tiles in the cycle
+    fn cycle_rows(&self) -> Result<CycleRows, &'static str> {
+        let mut in_cycle = CycleRows::new(self.width, self.height);
+        self.walk_cycle(|x, y| in_cycle.insert(x, y))?;
+        Ok(in_cycle)
+    }
+
+    /// Calls `visit` with the column of each tile of row `y` not in the cycle and whether it is
+    /// enclosed by the cycle.
+    fn classify_row(&self, y: usize, in_cycle: &[u64], mut visit: impl FnMut(usize, bool)) {
+        let mut ray = Ray::default();
+        for x in 0..self.width {
+            if in_cycle[x / 64] & (1 << (x % 64)) != 0 {
+                ray.cross(self.tiles[y * self.width + x]);
+            } else {
+                visit(x, ray.inside);
+            }
+        }
+    }
+
+    /// Calls `visit` with the coordinates of each tile not in the cycle, row by row, and whether
+    /// it is enclosed by the cycle.
+    fn classify(&self, in_cycle: &CycleRows, mut visit: impl FnMut(usize, usize, bool)) {
         for y in 0..self.height {
-            inside = false;
-            let mut last_angle_read = None;
-            for x in 0..self.width {
-                // let tile = &self.rows[y][x];
-                let tile = &self.rows[y][x];
-                if (x, y) == *next_cycle_pos {
-                    match tile {
-                        Tile::NS => {
-                            inside = !inside;
-                        },
-                        Tile::EW => (),
-                        Tile::NE => {
-                            last_angle_read = Some(*tile);
-                        },
-                        Tile::NW => {
-                            if last_angle_read == Some(Tile::SE) {
-                                inside = !inside;
-                            }
-                            last_angle_read = Some(*tile);
-                        },
-                        Tile::SW => {
-                            if last_angle_read == Some(Tile::NE) {
-                                inside = !inside;
-                            }
-                            last_angle_read = Some(*tile);
-                        },
-                        Tile::SE => {
-                            last_angle_read = Some(*tile);
-                        },
-                        _ => panic!("Unexpected tile in cycle"),
-                    }
-                    if let Some(next) = iter.next() {
-                        next_cycle_pos = next;
-                    } else {
-                        return area;
-                    }
-                } else if inside {
+            self.classify_row(y, in_cycle.row(y), |x, inside| visit(x, y, inside));
+        }
+    }
+
+    /// Number of tiles of row `y` enclosed by the cycle. Words of the bit set without tiles of the
+    /// cycle are counted at once.
+    fn row_area(&self, y: usize, in_cycle: &[u64]) -> usize {
+        let mut ray = Ray::default();
+        let mut area = 0;
+        for (i, &word) in in_cycle.iter().enumerate() {
+            let columns = i * 64..self.width.min(i * 64 + 64);
+            if word == 0 {
+                if ray.inside {
+                    area += columns.len();
+                }
+                continue;
+            }
+            for x in columns {
+                if word & (1 << (x % 64)) != 0 {
+                    ray.cross(self.tiles[y * self.width + x]);
+                } else if ray.inside {
                     area += 1;
                 }
             }
         }
         area
     }
+
+    /// Number of tiles enclosed by the cycle, scanning the rows in parallel
+    fn enclosed_area(&self, in_cycle: &CycleRows) -> usize {
+        let rows: Vec<usize> = (0..self.height).collect();
+        parallel::map(&rows, |&y| self.row_area(y, in_cycle.row(y))).into_iter().sum()
+    }
+
+    /// Describes the starting position with the tile inferred for it, the tiles of the loop in
+    /// traversal order beginning at the start, and the tiles enclosed by the loop row by row, all
+    /// as 0-based [x, y] coordinates.
+    pub fn to_json(&self) -> Result<String, &'static str> {
+        let mut loop_tiles = Vec::new();
+        self.walk_cycle(|x, y| loop_tiles.push(format!("    [{x}, {y}]")))?;
+        let mut interior = Vec::new();
+        self.classify(&self.cycle_rows()?, |x, y, inside| if inside { interior.push(format!("    [{x}, {y}]")) });
+        let list = |entries: Vec<String>| if entries.is_empty() { String::from("[]") } else { format!("[\n{}\n  ]", entries.join(",\n")) };
+        Ok(format!(
+            "{{\n  \"start\": {{\"x\": {}, \"y\": {}, \"tile\": \"{}\"}},\n  \"loop\": {},\n  \"interior\": {}\n}}\n",
+            self.start_col, self.start_row, self.tile(self.start_col, self.start_row).to_char(),
+            list(loop_tiles), list(interior),
+        ))
+    }
+
+    /// Number of pipes of each type in the maze, counting the starting position as the pipe
+    /// inferred for it
+    pub fn pipe_counts(&self) -> PipeCounts {
+        let mut counts = PipeCounts::default();
+        for i in self.tiles.iter().filter_map(|tile| tile.pipe_index()) {
+            counts.0[i] += 1;
+        }
+        counts
+    }
+
+    /// Replaces every pipe that is not part of the loop through the starting position with ground
+    /// and returns the number of pipes removed of each type.
+    pub fn prune_junk(&mut self) -> Result<PipeCounts, &'static str> {
+        let in_cycle = self.cycle_rows()?;
+        let mut removed = PipeCounts::default();
+        for (i, tile) in self.tiles.iter_mut().enumerate() {
+            if !in_cycle.contains(i % self.width, i / self.width) {
+                if let Some(pipe) = tile.pipe_index() {
+                    removed.0[pipe] += 1;
+                    *tile = Tile::Ground;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The maze in the input format, with 'S' at the starting position
+    pub fn to_input(&self) -> String {
+        let mut input = String::with_capacity((self.width + 1) * self.height);
+        for (y, row) in self.tiles.chunks(self.width).enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                input.push(if (x, y) == (self.start_col, self.start_row) { 'S' } else { tile.to_char() });
+            }
+            input.push('\n');
+        }
+        input
+    }
+
+    fn area_in_cycle(&self) -> Result<usize, &'static str> {
+        Ok(self.enclosed_area(&self.cycle_rows()?))
+    }
+
+    /// All loops of pipes in the maze, ordered by their first tile row by row. Each pipe tile is
+    /// tried as a starting position unless an earlier walk has already passed it: a walk that
+    /// fails only passes tiles that cannot lie on any loop, since the pipes of a loop lead nowhere
+    /// else.
+    fn loops(&mut self) -> Vec<Loop> {
+        let (start_col, start_row) = (self.start_col, self.start_row);
+        let mut seen = CycleRows::new(self.width, self.height);
+        let mut loops = Vec::new();
+        for i in 0..self.tiles.len() {
+            if seen.contains(i % self.width, i / self.width) || matches!(self.tiles[i], Tile::Ground | Tile::Start) {
+                continue;
+            }
+            (self.start_col, self.start_row) = (i % self.width, i / self.width);
+            let mut walked = Vec::new();
+            let walk = self.walk_cycle(|x, y| {
+                seen.insert(x, y);
+                walked.push((x, y));
+            });
+            if walk.is_ok() {
+                let mut in_loop = CycleRows::new(self.width, self.height);
+                for &(x, y) in &walked {
+                    in_loop.insert(x, y);
+                }
+                let area = self.enclosed_area(&in_loop);
+                loops.push(Loop { x: self.start_col, y: self.start_row, length: walked.len(), area });
+            }
+        }
+        (self.start_col, self.start_row) = (start_col, start_row);
+        loops
+    }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    println!("{}", puzzle);
-    Ok(puzzle.cycle_length() / 2)
+/// A closed loop of pipes in a maze
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Loop {
+    /// 0-based column of the loop's first tile in row-by-row order
+    pub x: usize,
+    /// 0-based row of the loop's first tile in row-by-row order
+    pub y: usize,
+    /// Number of tiles of the loop
+    pub length: usize,
+    /// Number of tiles enclosed by the loop
+    pub area: usize,
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    println!("{}", puzzle);
-    Ok(puzzle.area_in_cycle())
+impl Display for Loop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Loop at ({}, {}): length {}, area {}", self.x, self.y, self.length, self.area)
+    }
+}
+
+/// Finds all loops in a maze that may contain any number of them. A starting position is not
+/// required; if there is one, its pipe is inferred as for a partial maze.
+pub fn find_loops(input: &str) -> Result<Vec<Loop>, Box<dyn Error>> {
+    let mut puzzle = if input.contains('S') {
+        Puzzle::from_input_with_options(input, &ParseOptions { partial: true, ..ParseOptions::default() })?
+    } else {
+        let (width, height, tiles) = Puzzle::read_tiles(input)?;
+        Puzzle { width, height, tiles, start_row: 0, start_col: 0 }
+    };
+    Ok(puzzle.loops())
+}
+
+/// The loop enclosing the largest area, the first one among equally large ones, or `None` if the
+/// maze has no loop
+pub fn largest_loop(loops: &[Loop]) -> Option<&Loop> {
+    loops.iter().rev().max_by_key(|l| l.area)
+}
+
+/// A step of the traversal animation
+enum Step {
+    /// Walking onto the tile of the cycle at the given coordinates
+    Cycle(usize, usize),
+    /// Classifying the tile at the given coordinates as inside or outside the cycle
+    Classify(usize, usize, bool),
+}
+
+/// Frames showing the cycle being traversed tile by tile and then the remaining tiles being
+/// classified as inside ('I') or outside ('O') of it, starting with the untouched maze
+pub struct TraversalFrames {
+    width: usize,
+    cells: Vec<char>,
+    heavy: Vec<char>,
+    steps: VecDeque<Step>,
+    cycle_length: usize,
+    num_walked: usize,
+    num_inside: usize,
+    started: bool,
+}
+
+impl TraversalFrames {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(input)?;
+        let mut steps = VecDeque::new();
+        puzzle.walk_cycle(|x, y| steps.push_back(Step::Cycle(x, y)))?;
+        let cycle_length = steps.len();
+        puzzle.classify(&puzzle.cycle_rows()?, |x, y, inside| steps.push_back(Step::Classify(x, y, inside)));
+        Ok(Self {
+            width: puzzle.width,
+            cells: puzzle.to_string().lines().flat_map(str::chars).collect(),
+            heavy: puzzle.tiles.iter().map(Tile::heavy_char).collect(),
+            steps,
+            cycle_length,
+            num_walked: 0,
+            num_inside: 0,
+            started: false,
+        })
+    }
+}
+
+impl FrameSource for TraversalFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        let caption = if !self.started {
+            self.started = true;
+            String::from("Maze")
+        } else {
+            match self.steps.pop_front()? {
+                Step::Cycle(x, y) => {
+                    let i = y * self.width + x;
+                    self.cells[i] = self.heavy[i];
+                    self.num_walked += 1;
+                    format!("Cycle: {} of {} tiles", self.num_walked, self.cycle_length)
+                },
+                Step::Classify(x, y, inside) => {
+                    self.cells[y * self.width + x] = if inside { 'I' } else { 'O' };
+                    self.num_inside += usize::from(inside);
+                    format!("Inside: {} tiles", self.num_inside)
+                },
+            }
+        };
+        let lines = self.cells.chunks(self.width).map(|row| row.iter().collect()).collect();
+        Some(Frame::new(caption, lines))
+    }
+}
+
+/// Both parts, solved from the same maze
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input_with_options(input, &ParseOptions::default())
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok((puzzle.cycle_length()? / 2).into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.area_in_cycle()?.into())
+    }
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and invalid tiles, or otherwise problems with the starting position.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues = validate::check_grid(input, |c| Tile::try_from(c).is_ok());
+    if !issues.is_empty() {
+        return issues;
+    }
+    validate::check_all(Puzzle::from_input(input))
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let parse = |input: &str| Puzzle::from_input_with_options(input, &config.options);
+    let puzzle = parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&puzzle)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let mut puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, parse)?;
+    println!("Result of part 2: {}", Day::part2(&puzzle)?);
+    if let Some(json_path) = config.json_path {
+        println!("Writing loop and interior of part 2 to {json_path}");
+        fs::write(json_path, puzzle.to_json()?)?;
+    }
+    if let Some(prune_path) = config.prune_path {
+        let removed = puzzle.prune_junk()?;
+        println!("Removed {} junk pipes from the maze of part 2: {removed}", removed.total());
+        print!("{puzzle}");
+        println!("Writing the cleaned maze to {prune_path}");
+        fs::write(prune_path, puzzle.to_input())?;
+    }
+    if config.loops {
+        let loops = find_loops(&fs::read_to_string(&config.file_path2)?)?;
+        println!("Loops in the maze of part 2: {}", loops.len());
+        for l in &loops {
+            println!("  {l}");
+        }
+        if let Some(largest) = largest_loop(&loops) {
+            println!("Largest: {largest}");
+        }
+    }
 
     Ok(())
 }
@@ -300,6 +707,14 @@ pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    fn part1(input: &str, options: &ParseOptions) -> Result<Answer, Box<dyn Error>> {
+        Day::part1(&Puzzle::from_input_with_options(input, options)?)
+    }
+
+    fn part2(input: &str, options: &ParseOptions) -> Result<Answer, Box<dyn Error>> {
+        Day::part2(&Puzzle::from_input_with_options(input, options)?)
+    }
+
     const EXAMPLE1: &str = "
 .....
 .S-7.
@@ -384,7 +799,7 @@ L7JLJL-JLJLJL--JLJ.L
     #[test]
     fn example1_and_2_part1() -> Result<(), Box<dyn Error>> {
         for input in [EXAMPLE1, EXAMPLE2].iter() {
-            let result = part1(input.trim())?;
+            let result = part1(input.trim(), &ParseOptions::default())?;
             assert_eq!(result, 4);
         }
         Ok(())
@@ -393,7 +808,7 @@ L7JLJL-JLJLJL--JLJ.L
     #[test]
     fn example3_and_4_part1() -> Result<(), Box<dyn Error>> {
         for input in [EXAMPLE3, EXAMPLE4].iter() {
-            let result = part1(input.trim())?;
+            let result = part1(input.trim(), &ParseOptions::default())?;
             assert_eq!(result, 8);
         }
         Ok(())
@@ -401,29 +816,260 @@ L7JLJL-JLJLJL--JLJ.L
 
     #[test]
     fn example5_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE5.trim())?;
+        let result = part2(EXAMPLE5.trim(), &ParseOptions::default())?;
         assert_eq!(result, 4);
         Ok(())
     }
 
     #[test]
     fn example6_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE6.trim())?;
+        let result = part2(EXAMPLE6.trim(), &ParseOptions::default())?;
         assert_eq!(result, 4);
         Ok(())
     }
 
     #[test]
     fn example7_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE7.trim())?;
+        let result = part2(EXAMPLE7.trim(), &ParseOptions::default())?;
         assert_eq!(result, 8);
         Ok(())
     }
 
     #[test]
     fn example8_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE8.trim())?;
+        let result = part2(EXAMPLE8.trim(), &ParseOptions::default())?;
         assert_eq!(result, 10);
         Ok(())
     }
+
+    #[test]
+    fn example1_json() -> Result<(), Box<dyn Error>> {
+        let json = Puzzle::from_input(EXAMPLE1.trim())?.to_json()?;
+        assert_eq!(json, r#"{
+  "start": {"x": 1, "y": 1, "tile": "F"},
+  "loop": [
+    [1, 1],
+    [2, 1],
+    [3, 1],
+    [3, 2],
+    [3, 3],
+    [2, 3],
+    [1, 3],
+    [1, 2]
+  ],
+  "interior": [
+    [2, 2]
+  ]
+}
+"#);
+        let json = Puzzle::from_input("S7\nLJ")?.to_json()?;
+        assert!(json.ends_with("\"interior\": []\n}\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn start_tile_override() -> Result<(), Box<dyn Error>> {
+        let options = ParseOptions { start_tile: Some('F'), ..ParseOptions::default() };
+        assert_eq!(part1(EXAMPLE1.trim(), &options)?, 4);
+        let options = ParseOptions { start_tile: Some('J'), ..ParseOptions::default() };
+        assert_eq!(part1(EXAMPLE1.trim(), &options).unwrap_err().to_string(), BROKEN_LOOP);
+        let options = ParseOptions { start_tile: Some('.'), ..ParseOptions::default() };
+        assert!(part1(EXAMPLE1.trim(), &options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn partial_maze() -> Result<(), Box<dyn Error>> {
+        // The pipe left of the start leads out of the maze, so three neighbors connect to the start
+        let maze = "-S7\n.||\n.LJ";
+        let error = part1(maze, &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.to_string(), "There are 3 possibilities for the starting tile");
+        let options = ParseOptions { partial: true, ..ParseOptions::default() };
+        assert_eq!(part1(maze, &options)?, 3);
+        assert_eq!(Puzzle::from_input_with_options(maze, &options)?.tile(1, 0), Tile::SE);
+        assert_eq!(part2(maze, &options)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn example2_prune_junk() -> Result<(), Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(EXAMPLE2.trim())?;
+        assert_eq!(puzzle.pipe_counts().total(), 25);
+        let removed = puzzle.prune_junk()?;
+        assert_eq!(removed, PipeCounts([5, 3, 3, 1, 3, 2]));
+        assert_eq!(removed.get('|'), 5);
+        assert_eq!(removed.to_string(), "| 5, - 3, L 3, J 1, 7 3, F 2");
+        assert_eq!(puzzle.to_input(), format!("{}\n.....\n", EXAMPLE1.trim()));
+        assert_eq!(puzzle.pipe_counts(), PipeCounts([2, 2, 1, 1, 1, 1]));
+        assert_eq!(puzzle.prune_junk()?.total(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn pruned_maze_has_same_answers() -> Result<(), Box<dyn Error>> {
+        for input in [EXAMPLE4, EXAMPLE7, EXAMPLE8] {
+            let mut puzzle = Puzzle::from_input(input.trim())?;
+            puzzle.prune_junk()?;
+            let pruned = puzzle.to_input();
+            let options = ParseOptions::default();
+            assert_eq!(part1(&pruned, &options)?, part1(input.trim(), &options)?);
+            assert_eq!(part2(&pruned, &options)?, part2(input.trim(), &options)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn broken_loop() {
+        // The path from the start turns into a pipe that does not connect back
+        for input in ["S7\n|-", "S-\n|.", ".F7\n.S|\n.|J"] {
+            assert_eq!(part1(input, &ParseOptions::default()).unwrap_err().to_string(), BROKEN_LOOP, "{input}");
+        }
+        assert!(part2("S7\n|-", &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn example5_frames() -> Result<(), Box<dyn Error>> {
+        let mut frames = TraversalFrames::from_input(EXAMPLE5.trim())?;
+        assert_eq!(frames.next_frame().ok_or("No frame")?.caption, "Maze");
+        assert_eq!(frames.next_frame().ok_or("No frame")?.caption, "Cycle: 1 of 46 tiles");
+        let last = std::iter::from_fn(|| frames.next_frame()).last().ok_or("No frame")?;
+        assert_eq!(last.caption, "Inside: 4 tiles");
+        assert_eq!(last.lines[6], "O┃II┃O┃II┃O");
+        assert_eq!(last.lines[1], "O┏━━━━━━━┓O");
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_loops() -> Result<(), Box<dyn Error>> {
+        let maze = "\
+F7.F--7
+LJ.|F7|
+-7.|LJ|
+.L-J..|
+F-7.F-J
+L-J.|..";
+        let loops = find_loops(maze)?;
+        assert_eq!(loops, vec![
+            Loop { x: 0, y: 0, length: 4, area: 0 },
+            Loop { x: 4, y: 1, length: 4, area: 0 },
+            Loop { x: 0, y: 4, length: 6, area: 0 },
+        ]);
+        let loops = find_loops(EXAMPLE5.trim())?;
+        assert_eq!(loops.len(), 1);
+        assert_eq!((loops[0].length, loops[0].area), (46, 4));
+        let loops = find_loops(&format!("{}\n{}", rectangular_maze(6, 5).replace('S', "F").trim(), "F-7...\nL-J..."))?;
+        assert_eq!(largest_loop(&loops), Some(&Loop { x: 0, y: 0, length: 18, area: 12 }));
+        assert_eq!(loops.len(), 2);
+        assert_eq!(largest_loop(&find_loops("..\n.|")?), None);
+        Ok(())
+    }
+
+    /// Generates a maze whose cycle runs along the border of a `width` × `height` rectangle.
+    fn rectangular_maze(width: usize, height: usize) -> String {
+        let mut maze = String::with_capacity((width + 1) * height);
+        for y in 0..height {
+            for x in 0..width {
+                maze.push(match (x, y) {
+                    (0, 0) => 'S',
+                    (_, 0) if x == width - 1 => '7',
+                    (0, _) if y == height - 1 => 'L',
+                    _ if x == width - 1 && y == height - 1 => 'J',
+                    (_, 0) => '-',
+                    _ if y == height - 1 => '-',
+                    (0, _) => '|',
+                    _ if x == width - 1 => '|',
+                    _ => '.',
+                });
+            }
+            maze.push('\n');
+        }
+        maze
+    }
+
+    /// Generates a maze whose cycle runs along the border of a `width` × `height` rectangle, with
+    /// teeth two tiles wide rising from the bottom border at every column x with x % 4 == 3, up to
+    /// row 2. Most rows then cross the cycle many times. The height must be at least 4.
+    fn comb_maze(width: usize, height: usize) -> String {
+        let tooth = |x: usize| x % 4 == 3 && x + 1 < width;
+        let mut maze = String::with_capacity((width + 1) * height);
+        for y in 0..height {
+            for x in 0..width {
+                let bottom = y == height - 1;
+                maze.push(match (x, y) {
+                    (0, 0) => 'S',
+                    (_, 0) if x == width - 1 => '7',
+                    (_, 0) => '-',
+                    (0, _) if bottom => 'L',
+                    (0, _) => '|',
+                    _ if x == width - 1 => if bottom { 'J' } else { '|' },
+                    _ if y >= 2 && tooth(x) => if bottom { 'L' } else if y == 2 { '7' } else { '|' },
+                    _ if y >= 2 && tooth(x + 1) => if bottom { 'J' } else if y == 2 { 'F' } else { '|' },
+                    _ if bottom => '-',
+                    _ => '.',
+                });
+            }
+            maze.push('\n');
+        }
+        maze
+    }
+
+    /// Number of tiles enclosed by the cycle of `comb_maze`
+    fn comb_area(width: usize, height: usize) -> usize {
+        let teeth = (0..width).filter(|&x| x % 4 == 3 && x + 1 < width).count();
+        (width - 2) * (height - 2) - teeth * 2 * (height - 3)
+    }
+
+    #[test]
+    fn rows_scanned_in_parallel() -> Result<(), Box<dyn Error>> {
+        for (width, height) in [(4, 4), (9, 5), (70, 6), (129, 40), (200, 4)] {
+            let puzzle = Puzzle::from_input(&comb_maze(width, height))?;
+            let mut expected = 0;
+            puzzle.classify(&puzzle.cycle_rows()?, |_, _, inside| if inside { expected += 1 });
+            assert_eq!(expected, comb_area(width, height), "{width} × {height}");
+            assert_eq!(puzzle.area_in_cycle()?, expected, "{width} × {height}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn validate_maze() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        assert_eq!(validate(".S-7\n.|x|\n.L-J.\n"), vec![
+            Issue::at(2, 3, "Invalid character 'x'"),
+            Issue::at_line(3, "Line has length 5 but the first line has length 4"),
+        ]);
+        assert_eq!(validate("S-7\n|.|\nL-S"), vec![Issue::new("Multiple starting positions found")]);
+    }
+
+    #[test]
+    fn non_square_maze() -> Result<(), Box<dyn Error>> {
+        let maze = rectangular_maze(300, 70);
+        assert_eq!(part1(&maze, &ParseOptions::default())?, 300 + 68);
+        assert_eq!(part2(&maze, &ParseOptions::default())?, 298 * 68);
+        Ok(())
+    }
+
+    /// Solves a 10k × 10k maze. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_huge_maze() -> Result<(), Box<dyn Error>> {
+        let maze = rectangular_maze(10_000, 10_000);
+        let start = std::time::Instant::now();
+        assert_eq!(part1(&maze, &ParseOptions::default())?, 19_998);
+        assert_eq!(part2(&maze, &ParseOptions::default())?, 9_998 * 9_998);
+        println!("Huge maze: {:?}", start.elapsed());
+        Ok(())
+    }
+
+    /// Solves a 10k × 10k maze whose rows cross the cycle thousands of times. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_huge_comb_maze() -> Result<(), Box<dyn Error>> {
+        let maze = comb_maze(10_000, 10_000);
+        let start = std::time::Instant::now();
+        assert_eq!(part2(&maze, &ParseOptions::default())?, comb_area(10_000, 10_000));
+        println!("Huge comb maze: {:?}", start.elapsed());
+        Ok(())
+    }
 }
@@ -1,17 +1,35 @@
+use crate::Format;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// File to which the galaxies of part 1 are written as JSON
+    pub json_path: Option<String>,
+    /// Whether comment and metadata lines after the image are skipped
+    pub format: Format,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day11 <file for part 1> <file for part 2> [--json <file>] [--tolerant]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut json_path = None;
+        let mut format = Format::Strict;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--json" => json_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--tolerant" => format = Format::Tolerant,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            json_path,
+            format,
         })
     }
 }
-
@@ -1,5 +1,7 @@
 pub mod config;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::validate::{self, Issue};
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
@@ -29,25 +31,33 @@ impl Display for Tile {
     }
 }
 
-#[derive(Debug)]
-struct Galaxy {
+/// A galaxy with its position in the input and in the expanded universe
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Galaxy {
+    /// Row index in the input
+    pub row: usize,
+    /// Column index in the input
+    pub col: usize,
     // These coordinates take emptiness size into account, so they do not correspond to row /
     // column indices
-    x: usize,
-    y: usize,
+    pub x: usize,
+    pub y: usize,
 }
 
 impl Galaxy {
     fn distance_to(&self, other: &Galaxy) -> usize {
         // Manhattan distance
-        return ((self.x as isize - other.x as isize).abs() + (self.y as isize - other.y as isize).abs()) as usize;
+        ((self.x as isize - other.x as isize).abs() + (self.y as isize - other.y as isize).abs()) as usize
     }
 }
 
 #[derive(Debug)]
 struct Puzzle {
     rows: Vec<Vec<Tile>>,
-    galaxies: Vec<Galaxy>,
+    /// Whether each row is without galaxies
+    empty_rows: Vec<bool>,
+    /// Whether each column is without galaxies
+    empty_cols: Vec<bool>,
 }
 
 impl Display for Puzzle {
@@ -56,85 +66,200 @@ impl Display for Puzzle {
             for tile in row {
                 write!(f, "{}", tile)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
 impl Puzzle {
-    fn get_row_with_size_for_line(line: &str, emptiness_size: usize) -> Result<(Vec<Tile>, usize), &'static str> {
-        let tiles: Vec<Tile> = line.chars().map(|c| Tile::try_from(c)).collect::<Result<_, _>>()?;
-        let size = if tiles.iter().all(|&t| t == Tile::Empty) { emptiness_size } else { 1 };
-        Ok((tiles, size))
-    }
-
-    fn from_input(input: &str, emptiness_size: usize) -> Result<Self, Box<dyn Error>> {
+    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
         let mut rows = Vec::new();
-        let mut row_sizes = Vec::new();
+        let mut empty_rows = Vec::new();
         let line_len = input.lines().next().ok_or("Empty input")?.len();
-        let mut col_sizes = vec![emptiness_size; line_len];
+        let mut empty_cols = vec![true; line_len];
         for line in input.lines() {
             if line.len() != line_len {
                 return Err("Not all lines have the same length".into());
             }
-            let (row, row_size) = Self::get_row_with_size_for_line(line, emptiness_size)?;
-            for (&tile, size) in row.iter().zip(col_sizes.iter_mut()) {
+            let row: Vec<Tile> = line.chars().map(Tile::try_from).collect::<Result<_, _>>()?;
+            for (&tile, empty) in row.iter().zip(empty_cols.iter_mut()) {
                 if tile != Tile::Empty {
-                    *size = 1;
+                    *empty = false;
                 }
             }
+            empty_rows.push(row.iter().all(|&t| t == Tile::Empty));
             rows.push(row);
-            row_sizes.push(row_size);
         }
-        // Get galaxies
+        Ok(Self { rows, empty_rows, empty_cols })
+    }
+
+    /// Returns the galaxies in reading order, with every empty row and column expanded to
+    /// `emptiness_size` rows or columns.
+    fn galaxies(&self, emptiness_size: usize) -> Vec<Galaxy> {
+        let size = |empty: bool| if empty { emptiness_size } else { 1 };
         let mut galaxies = Vec::new();
         let mut y = 0;
-        for (i, row) in rows.iter().enumerate() {
+        for (i, row) in self.rows.iter().enumerate() {
             let mut x = 0;
             for (j, &tile) in row.iter().enumerate() {
                 if tile == Tile::Galaxy {
-                    galaxies.push(Galaxy { x, y });
+                    galaxies.push(Galaxy { row: i, col: j, x, y });
                 }
-                x += col_sizes[j];
+                x += size(self.empty_cols[j]);
             }
-            y += row_sizes[i];
+            y += size(self.empty_rows[i]);
         }
-        Ok(Self { rows, galaxies })
+        galaxies
+    }
+
+    fn sum_of_galaxy_pair_distances(&self, emptiness_size: usize) -> usize {
+        pair_distances(&self.galaxies(emptiness_size)).map(|pair| pair.distance).sum()
+    }
+}
+
+/// The distance between two galaxies, which are numbered from 1 in reading order as in the puzzle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PairDistance {
+    pub first: usize,
+    pub second: usize,
+    pub distance: usize,
+}
+
+/// Returns the distances of all pairs of galaxies, where the first galaxy of each pair has the
+/// smaller number, ordered by the first and then by the second galaxy.
+pub fn pair_distances(galaxies: &[Galaxy]) -> impl Iterator<Item = PairDistance> + '_ {
+    galaxies.iter().enumerate().flat_map(move |(i, g1)| {
+        galaxies.iter().enumerate().skip(i + 1).map(move |(j, g2)| {
+            PairDistance { first: i + 1, second: j + 1, distance: g1.distance_to(g2) }
+        })
+    })
+}
+
+/// Returns the galaxies of the input in reading order, with every empty row and column expanded to
+/// `emptiness_size` rows or columns.
+pub fn galaxies(input: &str, emptiness_size: usize) -> Result<Vec<Galaxy>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.galaxies(emptiness_size))
+}
+
+/// Writes the galaxies as a JSON object with the emptiness size and an array of galaxies.
+pub fn galaxies_to_json(galaxies: &[Galaxy], emptiness_size: usize) -> String {
+    let entries: Vec<String> = galaxies.iter()
+        .map(|g| format!("    {{\"row\": {}, \"col\": {}, \"x\": {}, \"y\": {}}}", g.row, g.col, g.x, g.y))
+        .collect();
+    format!("{{\n  \"emptiness_size\": {emptiness_size},\n  \"galaxies\": [\n{}\n  ]\n}}\n", entries.join(",\n"))
+}
+
+/// How lines following the image of the universe are treated
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    /// The input consists of the image only
+    #[default]
+    Strict,
+    /// The image may be followed by a blank line and then by comment or metadata lines starting
+    /// with ';' or '#', which are skipped along with further blank lines
+    Tolerant,
+}
+
+/// A comment or metadata line skipped after the image
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedLine {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+impl Display for SkippedLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.text)
     }
+}
 
-    fn sum_of_galaxy_pair_distances(&self) -> usize {
-        let mut sum = 0;
-        for (i, g1) in self.galaxies.iter().enumerate() {
-            for g2 in self.galaxies.iter().skip(i + 1) {
-                let distance = g1.distance_to(g2);
-                sum += distance;
+/// Splits the input into the image and the lines skipped after it as allowed by `format`. Fails
+/// if a line after the first blank line is neither blank nor a comment in tolerant mode.
+pub fn split_image(input: &str, format: Format) -> Result<(&str, Vec<SkippedLine>), String> {
+    let mut skipped = Vec::new();
+    if format == Format::Strict {
+        return Ok((input, skipped));
+    }
+    // Byte offset of the first blank line, where the image ends
+    let mut end = None;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if end.is_none() {
+            if text.trim().is_empty() {
+                end = Some(offset);
             }
+            offset += line.len();
+        } else if text.starts_with([';', '#']) {
+            skipped.push(SkippedLine { line: i + 1, text: text.to_string() });
+        } else if !text.trim().is_empty() {
+            return Err(format!("Line {} after the image is neither blank nor a comment starting with ';' or '#'", i + 1));
         }
-        sum
     }
+    Ok((&input[..end.unwrap_or(input.len())], skipped))
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, 2)?;
-    Ok(puzzle.sum_of_galaxy_pair_distances())
+/// Both parts, solved from the same image with different expansions
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_of_galaxy_pair_distances(2).into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_of_galaxy_pair_distances(1000000).into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, 1000000)?;
-    Ok(puzzle.sum_of_galaxy_pair_distances())
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and characters other than '.' and '#'.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_grid(input, |c| Tile::try_from(c).is_ok())
+}
+
+/// Parses the image of the contents of a file, reporting the lines skipped after it.
+fn parse_image(contents: &str, format: Format) -> Result<Puzzle, Box<dyn Error>> {
+    let (image, skipped) = split_image(contents, format)?;
+    for line in &skipped {
+        println!("Skipped {line}");
+    }
+    Day::parse(image)
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let puzzle = parse_image(&fs::read_to_string(&config.file_path1)?, config.format)?;
+    println!("Result of part 1: {}", Day::part1(&puzzle)?);
+
+    if let Some(json_path) = config.json_path {
+        println!("Writing galaxies of part 1 to {json_path}");
+        fs::write(json_path, galaxies_to_json(&puzzle.galaxies(2), 2))?;
+    }
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let parse = |contents: &str| parse_image(contents, config.format);
+    let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, parse)?;
+    println!("Result of part 2: {}", Day::part2(&puzzle)?);
 
     Ok(())
 }
@@ -156,9 +281,24 @@ mod tests {
 #...#.....
 ";
 
+    #[test]
+    fn example1_pair_distances() -> Result<(), Box<dyn Error>> {
+        let galaxies = galaxies(EXAMPLE1.trim(), 2)?;
+        let pairs: Vec<PairDistance> = pair_distances(&galaxies).collect();
+        assert_eq!(pairs.len(), 36);
+        assert_eq!(pairs[0], PairDistance { first: 1, second: 2, distance: 6 });
+        let distance = |first, second| pairs.iter().find(|p| (p.first, p.second) == (first, second)).map(|p| p.distance);
+        assert_eq!(distance(5, 9), Some(9));
+        assert_eq!(distance(1, 7), Some(15));
+        assert_eq!(distance(3, 6), Some(17));
+        assert_eq!(distance(8, 9), Some(5));
+        assert_eq!(distance(9, 8), None);
+        Ok(())
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 374);
         Ok(())
     }
@@ -166,10 +306,44 @@ mod tests {
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
         let input = EXAMPLE1.trim();
-        let puzzle = Puzzle::from_input(input, 10)?;
-        assert_eq!(puzzle.sum_of_galaxy_pair_distances(), 1030);
-        let puzzle = Puzzle::from_input(input, 100)?;
-        assert_eq!(puzzle.sum_of_galaxy_pair_distances(), 8410);
+        let puzzle = Puzzle::from_input(input)?;
+        assert_eq!(puzzle.sum_of_galaxy_pair_distances(10), 1030);
+        assert_eq!(puzzle.sum_of_galaxy_pair_distances(100), 8410);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_galaxies() -> Result<(), Box<dyn Error>> {
+        let galaxies = galaxies(EXAMPLE1.trim(), 2)?;
+        assert_eq!(galaxies.len(), 9);
+        assert_eq!(galaxies[0], Galaxy { row: 0, col: 3, x: 4, y: 0 });
+        assert_eq!(galaxies[8], Galaxy { row: 9, col: 4, x: 5, y: 11 });
+        let json = galaxies_to_json(&galaxies[..2], 2);
+        assert_eq!(json, r#"{
+  "emptiness_size": 2,
+  "galaxies": [
+    {"row": 0, "col": 3, "x": 4, "y": 0},
+    {"row": 1, "col": 7, "x": 9, "y": 1}
+  ]
+}
+"#);
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_metadata() -> Result<(), Box<dyn Error>> {
+        let input = format!("{}\n\n; shared by a friend\n\n# expansion: 2\n", EXAMPLE1.trim());
+        assert!(solve_part1(split_image(&input, Format::Strict)?.0).is_err());
+        let (image, skipped) = split_image(&input, Format::Tolerant)?;
+        assert_eq!(image, format!("{}\n", EXAMPLE1.trim()));
+        assert_eq!(solve_part1(image)?, 374);
+        assert_eq!(skipped, vec![
+            SkippedLine { line: 12, text: "; shared by a friend".to_string() },
+            SkippedLine { line: 14, text: "# expansion: 2".to_string() },
+        ]);
+        assert_eq!(split_image(EXAMPLE1.trim(), Format::Tolerant)?, (EXAMPLE1.trim(), vec![]));
+        let error = split_image("#.\n..\n\n..\n", Format::Tolerant).unwrap_err();
+        assert_eq!(error, "Line 4 after the image is neither blank nor a comment starting with ';' or '#'");
         Ok(())
     }
 }
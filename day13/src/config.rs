@@ -1,17 +1,32 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print each pattern of part 2 with its smudge fixed
+    pub show_fixes: bool,
+    /// Print the groups of patterns of part 2 that are rotations or reflections of each other
+    pub show_duplicates: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day13 <file for part 1> <file for part 2> [--show-fixes] [--duplicates]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut show_fixes = false;
+        let mut show_duplicates = false;
+        for arg in &args[3..] {
+            match arg.as_str() {
+                "--show-fixes" => show_fixes = true,
+                "--duplicates" => show_duplicates = true,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            show_fixes,
+            show_duplicates,
         })
     }
 }
-
@@ -0,0 +1,165 @@
+//! Random patterns with a known line of reflection, for property tests and benchmarks.
+
+use std::cmp;
+use std::fmt::Display;
+
+use aoc_core::rng::{Rng, SeededRng};
+
+use crate::Axis;
+
+/// Number of random patterns tried before giving up on finding one with a single line of
+/// reflection
+const MAX_ATTEMPTS: usize = 1000;
+
+/// A pattern with exactly one line of reflection, or exactly one line that becomes a line of
+/// reflection after fixing its smudge
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneratedPattern {
+    pub rows: Vec<String>,
+    pub axis: Axis,
+    /// Number of rows above or columns left of the line of reflection
+    pub position: usize,
+    /// 0-based row and column of the smudge, if any
+    pub smudge: Option<(usize, usize)>,
+}
+
+impl GeneratedPattern {
+    /// The summary of the pattern as in part 1, or as in part 2 if it has a smudge
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.position,
+            Axis::Vertical => self.position,
+        }
+    }
+}
+
+impl Display for GeneratedPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{row}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of mismatching characters between the strings mirrored at `i`, counting at most 2
+fn mismatches(strings: &[Vec<u8>], i: usize) -> usize {
+    let size = cmp::min(i, strings.len() - i);
+    (0..size)
+        .flat_map(|k| strings[i - 1 - k].iter().zip(&strings[i + k]).filter(|(c, d)| c != d))
+        .take(2)
+        .count()
+}
+
+/// Generates a random pattern of '.' and '#' with `num_rows` rows and `num_cols` columns that has
+/// exactly one line of reflection. With `smudge`, one character is changed afterwards, so that the
+/// pattern instead has exactly one line at which exactly one character is mirrored onto a different
+/// one. Equal seeds give equal patterns. Fails for patterns smaller than 2 × 2 and if no such
+/// pattern is found in a number of attempts, which happens for tiny sizes.
+pub fn generate_pattern(num_rows: usize, num_cols: usize, smudge: bool, seed: u64) -> Result<GeneratedPattern, &'static str> {
+    if num_rows < 2 || num_cols < 2 {
+        return Err("Patterns must have at least two rows and two columns");
+    }
+    let mut rng = SeededRng::new(seed);
+    let mut random = move |max: usize| rng.index(max);
+    for _ in 0..MAX_ATTEMPTS {
+        let (axis, len) = if random(2) == 0 { (Axis::Horizontal, num_rows) } else { (Axis::Vertical, num_cols) };
+        let position = 1 + random(len - 1);
+        let size = cmp::min(position, len - position);
+        // Build the strings parallel to the line, mirroring those within its reach
+        let other_len = if axis == Axis::Horizontal { num_cols } else { num_rows };
+        let mut strings: Vec<Vec<u8>> = (0..len)
+            .map(|_| (0..other_len).map(|_| if random(2) == 0 { b'.' } else { b'#' }).collect())
+            .collect();
+        for k in 0..size {
+            strings[position + k] = strings[position - 1 - k].clone();
+        }
+        let smudge = smudge.then(|| {
+            let (k, j) = (position - size + random(2 * size), random(other_len));
+            strings[k][j] = if strings[k][j] == b'.' { b'#' } else { b'.' };
+            (k, j)
+        });
+        let columns: Vec<Vec<u8>> = (0..other_len).map(|j| strings.iter().map(|string| string[j]).collect()).collect();
+        let wanted = usize::from(smudge.is_some());
+        let num_lines = (1..len).filter(|&i| mismatches(&strings, i) == wanted).count()
+            + (1..other_len).filter(|&i| mismatches(&columns, i) == wanted).count();
+        if num_lines != 1 {
+            continue;
+        }
+        let (rows, smudge) = match axis {
+            Axis::Horizontal => (strings, smudge),
+            Axis::Vertical => (columns, smudge.map(|(k, j)| (j, k))),
+        };
+        let rows = rows.into_iter().map(|row| row.into_iter().map(char::from).collect()).collect();
+        return Ok(GeneratedPattern { rows, axis, position, smudge });
+    }
+    Err("Found no pattern with a single line of reflection")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{pattern_summaries, smudge_fixes};
+
+    /// Joins the patterns to an input with empty lines between them.
+    fn to_input(patterns: &[GeneratedPattern]) -> String {
+        patterns.iter().map(|pattern| pattern.to_string()).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn solver_finds_generated_line() -> Result<(), Box<dyn Error>> {
+        for smudge in [false, true] {
+            let patterns = (0..300u64)
+                .map(|seed| generate_pattern(2 + seed as usize % 17, 2 + seed as usize / 17 % 17, smudge, seed))
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>();
+            assert!(patterns.len() > 250);
+            assert!(patterns.iter().any(|p| p.axis == Axis::Horizontal) && patterns.iter().any(|p| p.axis == Axis::Vertical));
+            let input = to_input(&patterns);
+            let expected: Vec<usize> = patterns.iter().map(GeneratedPattern::summary).collect();
+            assert_eq!(pattern_summaries(&input, smudge)?, expected);
+            if smudge {
+                for (fix, pattern) in smudge_fixes(&input)?.iter().zip(&patterns) {
+                    let (row, col) = pattern.smudge.ok_or("No smudge")?;
+                    // The fix may change the character mirrored onto the smudge instead
+                    let mirrored = match pattern.axis {
+                        Axis::Horizontal => (2 * pattern.position - 1 - row, col),
+                        Axis::Vertical => (row, 2 * pattern.position - 1 - col),
+                    };
+                    assert!((fix.row, fix.col) == (row, col) || (fix.row, fix.col) == mirrored);
+                }
+            } else {
+                assert!(patterns.iter().all(|pattern| pattern.smudge.is_none()));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generation_is_deterministic() -> Result<(), Box<dyn Error>> {
+        assert_eq!(generate_pattern(10, 12, true, 7)?, generate_pattern(10, 12, true, 7)?);
+        let pattern = generate_pattern(10, 12, false, 7)?;
+        assert_eq!(pattern.rows.len(), 10);
+        assert!(pattern.rows.iter().all(|row| row.len() == 12 && row.chars().all(|c| c == '.' || c == '#')));
+        assert!(generate_pattern(1, 5, false, 0).is_err());
+        Ok(())
+    }
+
+    /// Solves both parts for 20 generated patterns of 1000 × 1000 characters. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_large_patterns() -> Result<(), Box<dyn Error>> {
+        for smudge in [false, true] {
+            let patterns = (0..20).map(|seed| generate_pattern(1000, 1000, smudge, seed)).collect::<Result<Vec<_>, _>>()?;
+            let input = to_input(&patterns);
+            let start = std::time::Instant::now();
+            let summaries = pattern_summaries(&input, smudge)?;
+            println!("Smudge {smudge}: {:?}", start.elapsed());
+            assert_eq!(summaries, patterns.iter().map(GeneratedPattern::summary).collect::<Vec<_>>());
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,18 @@
 pub mod config;
+mod generate;
 
 use std::error::Error;
 use std::cmp;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parallel;
+use aoc_core::validate::{self, Issue};
+
+pub use generate::{generate_pattern, GeneratedPattern};
+
 #[derive(Debug)]
 struct Pattern {
     rows: Vec<String>,
@@ -15,10 +23,13 @@ impl Pattern {
     fn next_from_iter<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Result<Option<Self>, Box<dyn Error>> {
         let mut rows = Vec::new();
         let mut line_len = None;
-        while let Some(line) = iter.next() {
+        for line in iter.by_ref() {
             if line.is_empty() {
                 break;
             }
+            if !line.is_ascii() {
+                return Err("Non-ASCII character in pattern".into());
+            }
             if let Some(len) = line_len {
                 if line.len() != len {
                     return Err("Lines with different length".into())
@@ -31,10 +42,10 @@ impl Pattern {
         if rows.is_empty() {
             return Ok(None)
         }
-        let line_len = line_len.unwrap();
+        let line_len = rows[0].len();
         let mut columns = Vec::with_capacity(line_len);
         for i in 0..line_len {
-            let column = rows.iter().map(|l| l.chars().nth(i).unwrap()).collect();
+            let column = rows.iter().map(|l| l.as_bytes()[i] as char).collect();
             columns.push(column);
         }
         Ok(Some(Self { rows, columns }))
@@ -55,7 +66,7 @@ impl Pattern {
             let symmetry_size = cmp::min(i, strings.len() - i);
             let strings_before = &strings[i - symmetry_size..i];
             let strings_after = &strings[i..i + symmetry_size];
-            if Self::symmetric(&strings_before, &strings_after) {
+            if Self::symmetric(strings_before, strings_after) {
                 return Some(i);
             }
         }
@@ -68,18 +79,228 @@ impl Pattern {
             let symmetry_size = cmp::min(i, strings.len() - i);
             let strings_before = &strings[i - symmetry_size..i];
             let strings_after = &strings[i..i + symmetry_size];
-            if Self::symmetric_after_fixing_smudge(&strings_before, &strings_after) {
+            if Self::symmetric_after_fixing_smudge(strings_before, strings_after) {
                 return Some(i);
             }
         }
         None
     }
+
+    /// If the strings are symmetric at `i` after changing exactly one character, returns the index
+    /// of the string before `i` and the index of the character to change, and its replacement.
+    fn smudge_at(strings: &[String], i: usize) -> Option<(usize, usize, char)> {
+        let symmetry_size = cmp::min(i, strings.len() - i);
+        let mut mismatches = (0..symmetry_size).flat_map(|k| {
+            let (a, b) = (i - 1 - k, i + k);
+            strings[a].chars().zip(strings[b].chars()).enumerate()
+                .filter(|(_, (c, d))| c != d)
+                .map(move |(j, (_, d))| (a, j, d))
+        });
+        match (mismatches.next(), mismatches.next()) {
+            (Some(mismatch), None) => Some(mismatch),
+            _ => None,
+        }
+    }
+
+    /// Finds the line of reflection after fixing the smudge, preferring a horizontal one, and
+    /// checks that the corrected pattern is symmetric there.
+    fn smudge_fix(&self) -> Result<SmudgeFix, &'static str> {
+        let (axis, position, (k, j, value)) = [(Axis::Horizontal, &self.rows), (Axis::Vertical, &self.columns)].into_iter()
+            .find_map(|(axis, strings)| {
+                (1..strings.len()).find_map(|i| Self::smudge_at(strings, i).map(|smudge| (axis, i, smudge)))
+            })
+            .ok_or("No symmetry")?;
+        let (row, col) = match axis {
+            Axis::Horizontal => (k, j),
+            Axis::Vertical => (j, k),
+        };
+        let mut corrected = self.rows.clone();
+        corrected[row].replace_range(col..col + 1, &value.to_string());
+        let corrected = Pattern::next_from_iter(&mut corrected.iter().map(String::as_str))
+            .ok().flatten().ok_or("Corrected pattern is empty")?;
+        let strings = match axis {
+            Axis::Horizontal => &corrected.rows,
+            Axis::Vertical => &corrected.columns,
+        };
+        let symmetry_size = cmp::min(position, strings.len() - position);
+        if !Self::symmetric(&strings[position - symmetry_size..position], &strings[position..position + symmetry_size]) {
+            return Err("Corrected pattern is not symmetric");
+        }
+        Ok(SmudgeFix { axis, position, row, col, value, corrected: corrected.rows })
+    }
+
+    /// If the strings are symmetric at `i` when each wildcard may stand for any character, returns
+    /// the wildcards that must be replaced by a character for it, as index of the string, index of
+    /// the character and the replacement.
+    fn wildcard_symmetry_at(strings: &[String], i: usize) -> Option<Vec<(usize, usize, char)>> {
+        let symmetry_size = cmp::min(i, strings.len() - i);
+        let mut assignments = Vec::new();
+        for k in 0..symmetry_size {
+            let (a, b) = (i - 1 - k, i + k);
+            for (j, (c, d)) in strings[a].chars().zip(strings[b].chars()).enumerate() {
+                match (c, d) {
+                    _ if c == d => (),
+                    (WILDCARD, _) => assignments.push((a, j, d)),
+                    (_, WILDCARD) => assignments.push((b, j, c)),
+                    _ => return None,
+                }
+            }
+        }
+        Some(assignments)
+    }
+
+    /// Finds all lines of reflection when each wildcard may stand for any character.
+    fn wildcard_reflections(&self) -> Vec<WildcardReflection> {
+        let mut reflections = Vec::new();
+        for (axis, strings) in [(Axis::Horizontal, &self.rows), (Axis::Vertical, &self.columns)] {
+            for i in 1..strings.len() {
+                if let Some(assignments) = Self::wildcard_symmetry_at(strings, i) {
+                    let mut assignments: Vec<Assignment> = assignments.into_iter().map(|(k, j, value)| match axis {
+                        Axis::Horizontal => Assignment { row: k, col: j, value },
+                        Axis::Vertical => Assignment { row: j, col: k, value },
+                    }).collect();
+                    assignments.sort_by_key(|a| (a.row, a.col));
+                    reflections.push(WildcardReflection { axis, position: i, assignments });
+                }
+            }
+        }
+        reflections
+    }
+
+    /// The rows of each of the 8 rotations and reflections of the pattern. Reversing the order of
+    /// the strings and the characters of each string flips the pattern, and the columns are the
+    /// rows of its transpose.
+    fn transforms(&self) -> impl Iterator<Item = Vec<String>> + '_ {
+        [&self.rows, &self.columns].into_iter().flat_map(|strings| {
+            [(false, false), (false, true), (true, false), (true, true)].into_iter().map(move |(reverse_order, reverse_chars)| {
+                let mut transformed: Vec<String> = if reverse_chars {
+                    strings.iter().map(|s| s.chars().rev().collect()).collect()
+                } else {
+                    strings.clone()
+                };
+                if reverse_order {
+                    transformed.reverse();
+                }
+                transformed
+            })
+        })
+    }
+
+    /// The least rendering of the rotations and reflections of the pattern, which is the same for
+    /// all patterns that are rotations or reflections of each other
+    fn canonical_form(&self) -> String {
+        self.transforms().map(|rows| rows.join("\n")).min().unwrap_or_default()
+    }
+
+    /// Returns 100 times the number of rows above the line of reflection if it is horizontal, or
+    /// the number of columns left of it if it is vertical.
+    fn summary(&self, fix_smudge: bool) -> Result<usize, &'static str> {
+        let find = |strings| if fix_smudge {
+            self.find_symmetry_number_after_fixing_smudge(strings)
+        } else {
+            self.find_symmetry_number(strings)
+        };
+        if let Some(i) = find(&self.rows) {
+            Ok(100 * i)
+        } else if let Some(i) = find(&self.columns) {
+            Ok(i)
+        } else {
+            Err("No symmetry")
+        }
+    }
+}
+
+/// Character that may stand for either '.' or '#'
+const WILDCARD: char = '?';
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Axis {
+    /// The line of reflection runs between two rows
+    Horizontal,
+    /// The line of reflection runs between two columns
+    Vertical,
+}
+
+/// Replacement of the wildcard at the given 0-based row and column
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Assignment {
+    pub row: usize,
+    pub col: usize,
+    pub value: char,
+}
+
+/// A line of reflection of a pattern with wildcards, with the replacements of wildcards it
+/// requires. Wildcards that are mirrored onto wildcards are not assigned, but must be replaced by
+/// the same character.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WildcardReflection {
+    pub axis: Axis,
+    /// Number of rows above or columns left of the line
+    pub position: usize,
+    pub assignments: Vec<Assignment>,
+}
+
+impl WildcardReflection {
+    /// The summary of the pattern if this is its line of reflection, as in part 1
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.position,
+            Axis::Vertical => self.position,
+        }
+    }
+}
+
+/// The change of one character that gives a pattern a new line of reflection, as in part 2. The
+/// character mirrored at that line could be changed instead with the same result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SmudgeFix {
+    pub axis: Axis,
+    /// Number of rows above or columns left of the line of reflection
+    pub position: usize,
+    /// 0-based row of the changed character
+    pub row: usize,
+    /// 0-based column of the changed character
+    pub col: usize,
+    /// Character after the change
+    pub value: char,
+    /// Rows of the pattern after the change
+    pub corrected: Vec<String>,
+}
+
+impl SmudgeFix {
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.position,
+            Axis::Vertical => self.position,
+        }
+    }
+}
+
+impl Display for SmudgeFix {
+    /// Writes the corrected pattern with a '^' under the changed character.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let axis = match self.axis {
+            Axis::Horizontal => "horizontal",
+            Axis::Vertical => "vertical",
+        };
+        writeln!(
+            f, "Changed row {}, column {} to '{}'; {axis} line of reflection at {}",
+            self.row + 1, self.col + 1, self.value, self.position
+        )?;
+        for (i, row) in self.corrected.iter().enumerate() {
+            writeln!(f, "{row}")?;
+            if i == self.row {
+                writeln!(f, "{:>width$}", "^", width = self.col + 1)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Display for Pattern {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for row in &self.rows {
-            write!(f, "{row}\n")?;
+            writeln!(f, "{row}")?;
         }
         Ok(())
     }
@@ -99,52 +320,134 @@ impl Puzzle {
         }
         Ok(Self { patterns })
     }
-}
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    let mut result = 0;
-    for pattern in puzzle.patterns {
-        if let Some(i) = pattern.find_symmetry_number(&pattern.rows) {
-            result += 100 * i;
-        }
-        else if let Some(i) = pattern.find_symmetry_number(&pattern.columns) {
-            result += i;
-        }
-        else {
-            return Err("No symmetry".into());
+    fn summaries(&self, fix_smudge: bool) -> Result<Vec<usize>, Box<dyn Error>> {
+        let summaries = parallel::map(&self.patterns, |pattern| pattern.summary(fix_smudge));
+        Ok(summaries.into_iter().collect::<Result<_, _>>()?)
+    }
+
+    fn smudge_fixes(&self) -> Result<Vec<SmudgeFix>, Box<dyn Error>> {
+        let fixes = parallel::map(&self.patterns, Pattern::smudge_fix);
+        Ok(fixes.into_iter().collect::<Result<_, _>>()?)
+    }
+
+    fn duplicates(&self) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            groups.entry(pattern.canonical_form()).or_default().push(i);
         }
+        let mut duplicates: Vec<Vec<usize>> = groups.into_values().filter(|group| group.len() > 1).collect();
+        duplicates.sort();
+        duplicates
     }
-    Ok(result)
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
+/// Computes the summary of each pattern (in parallel), in the order of the input, where
+/// `fix_smudge` determines whether exactly one character must be changed for the symmetry.
+pub fn pattern_summaries(input: &str, fix_smudge: bool) -> Result<Vec<usize>, Box<dyn Error>> {
+    Puzzle::from_input(input)?.summaries(fix_smudge)
+}
+
+/// Finds the smudge of each pattern, in the order of the input, and the corrected pattern.
+pub fn smudge_fixes(input: &str) -> Result<Vec<SmudgeFix>, Box<dyn Error>> {
+    Puzzle::from_input(input)?.smudge_fixes()
+}
+
+/// Finds all lines of reflection of each pattern, in the order of the input, where '?' may stand
+/// for any character.
+pub fn wildcard_reflections(input: &str) -> Result<Vec<Vec<WildcardReflection>>, Box<dyn Error>> {
     let puzzle = Puzzle::from_input(input)?;
-    let mut result = 0;
-    for pattern in puzzle.patterns {
-        if let Some(i) = pattern.find_symmetry_number_after_fixing_smudge(&pattern.rows) {
-            result += 100 * i;
-        }
-        else if let Some(i) = pattern.find_symmetry_number_after_fixing_smudge(&pattern.columns) {
-            result += i;
-        }
-        else {
-            return Err("No symmetry".into());
-        }
+    Ok(parallel::map(&puzzle.patterns, Pattern::wildcard_reflections))
+}
+
+/// The canonical form of each pattern under rotation and reflection, in the order of the input,
+/// as its rows separated by newlines
+pub fn canonical_forms(input: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let puzzle = Puzzle::from_input(input)?;
+    Ok(puzzle.patterns.iter().map(Pattern::canonical_form).collect())
+}
+
+/// Whether two inputs of a single pattern each are rotations or reflections of each other
+pub fn equivalent(a: &str, b: &str) -> Result<bool, Box<dyn Error>> {
+    let single = |input| match canonical_forms(input)?.as_slice() {
+        [form] => Ok::<_, Box<dyn Error>>(form.clone()),
+        forms => Err(format!("Expected a single pattern, found {}", forms.len()).into()),
+    };
+    Ok(single(a)? == single(b)?)
+}
+
+/// Groups the patterns of the input that are rotations or reflections of each other. Returns the
+/// groups with at least two patterns as 0-based indices, each group in increasing order and the
+/// groups ordered by their first index.
+pub fn duplicate_patterns(input: &str) -> Result<Vec<Vec<usize>>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.duplicates())
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and characters other than '.', '#' and the wildcard '?' in each pattern.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::blocks(input).into_iter()
+        .flat_map(|(offset, block)| {
+            validate::check_grid(&block, |c| c == '.' || c == '#' || c == WILDCARD).into_iter().map(move |issue| issue.shifted(offset))
+        })
+        .collect()
+}
+
+/// Both parts, solved from the same patterns
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.summaries(false)?.iter().sum::<usize>().into())
     }
-    Ok(result)
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.summaries(true)?.iter().sum::<usize>().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let puzzle = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&puzzle)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&puzzle)?);
+
+    if config.show_duplicates {
+        let duplicates = puzzle.duplicates();
+        if duplicates.is_empty() {
+            println!("\nNo patterns are rotations or reflections of each other");
+        }
+        for group in duplicates {
+            let numbers: Vec<String> = group.iter().map(|i| (i + 1).to_string()).collect();
+            println!("\nPatterns {} are rotations or reflections of each other", numbers.join(", "));
+        }
+    }
+
+    if config.show_fixes {
+        for (i, fix) in puzzle.smudge_fixes()?.iter().enumerate() {
+            println!("\nPattern {}: {fix}", i + 1);
+        }
+    }
 
     Ok(())
 }
@@ -171,17 +474,92 @@ mod tests {
 #....#..#
 ";
 
+    #[test]
+    fn validate_patterns() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        assert_eq!(validate("#.\n.#\n\n##\n#o"), vec![Issue::at(5, 2, "Invalid character 'o'")]);
+    }
+
+    #[test]
+    fn wildcards() -> Result<(), Box<dyn Error>> {
+        // The first pattern of the example with some characters replaced by wildcards
+        let input = "#.##..##.\n..#.##.#.\n##......#\n##...?..#\n..#.##.#.\n..##..?#.\n#.#.##.#?";
+        let reflections = wildcard_reflections(input)?.remove(0);
+        assert_eq!(reflections, vec![WildcardReflection {
+            axis: Axis::Vertical,
+            position: 5,
+            assignments: vec![
+                Assignment { row: 3, col: 5, value: '.' },
+                Assignment { row: 5, col: 6, value: '#' },
+                Assignment { row: 6, col: 8, value: '.' },
+            ],
+        }]);
+        assert_eq!(reflections[0].summary(), 5);
+        // Everything is symmetric if all characters are wildcards
+        let reflections = wildcard_reflections("??\n??")?.remove(0);
+        assert_eq!(reflections.iter().map(WildcardReflection::summary).collect::<Vec<_>>(), vec![100, 1]);
+        assert!(reflections.iter().all(|reflection| reflection.assignments.is_empty()));
+        assert!(wildcard_reflections("#.\n.?")?[0].iter().all(|r| r.assignments.len() == 1));
+        Ok(())
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 405);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = solve_part2(EXAMPLE1.trim())?;
         assert_eq!(result, 400);
         Ok(())
     }
+
+    #[test]
+    fn example1_smudge_fixes() -> Result<(), Box<dyn Error>> {
+        let fixes = smudge_fixes(EXAMPLE1.trim())?;
+        assert_eq!(fixes.iter().map(SmudgeFix::summary).collect::<Vec<_>>(), vec![300, 100]);
+        assert_eq!((fixes[0].axis, fixes[0].row, fixes[0].col, fixes[0].value), (Axis::Horizontal, 0, 0, '.'));
+        assert_eq!(fixes[0].corrected[0], "..##..##.");
+        assert_eq!((fixes[1].row, fixes[1].col, fixes[1].value), (0, 4, '.'));
+        assert!(fixes[1].to_string().starts_with(concat!(
+            "Changed row 1, column 5 to '.'; horizontal line of reflection at 1\n",
+            "#....#..#\n",
+            "    ^\n",
+            "#....#..#\n",
+        )));
+        assert!(smudge_fixes("#.\n.#").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_form_under_symmetry() -> Result<(), Box<dyn Error>> {
+        let pattern = "#..\n##.";
+        // Rotated clockwise, flipped left to right, transposed and flipped upside down
+        for other in ["##\n#.\n..", "..#\n.##", "##\n.#\n..", "##.\n#.."] {
+            assert!(equivalent(pattern, other)?, "{other}");
+        }
+        assert!(!equivalent(pattern, "#.#\n#..")?);
+        assert!(!equivalent(pattern, "#..\n##.\n...")?);
+        assert_eq!(canonical_forms(pattern)?, ["##\n#.\n.."]);
+        assert!(equivalent(EXAMPLE1.trim(), pattern).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn duplicates() -> Result<(), Box<dyn Error>> {
+        let input = "#..\n##.\n\n#.\n#.\n\n..#\n.##\n\n.#\n.#\n\n##\n.#\n..";
+        assert_eq!(duplicate_patterns(input)?, [vec![0, 2, 4], vec![1, 3]]);
+        assert!(duplicate_patterns(EXAMPLE1.trim())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn example1_pattern_summaries() -> Result<(), Box<dyn Error>> {
+        assert_eq!(pattern_summaries(EXAMPLE1.trim(), false)?, vec![5, 400]);
+        assert_eq!(pattern_summaries(EXAMPLE1.trim(), true)?, vec![300, 100]);
+        Ok(())
+    }
 }
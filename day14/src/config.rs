@@ -1,17 +1,67 @@
+use crate::{TiltDirection, DEFAULT_NUM_CYCLES};
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// File to which the load after each tilting cycle of part 2 is written as CSV
+    pub csv_path: Option<String>,
+    /// Number of tilting cycles after which the platform of part 2 is written to a file, and the
+    /// file
+    pub state: Option<(usize, String)>,
+    /// Files with platforms to compare up to rotation and reflection instead of solving the puzzle
+    pub dedup_paths: Vec<String>,
+    /// Direction in which the platform of part 1 is tilted
+    pub tilt_direction: TiltDirection,
+    /// Number of tilting cycles after which the load of part 2 is computed
+    pub num_cycles: usize,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day14 <file for part 1> <file for part 2> [--csv <file>] [--state <cycles> <file>] [--tilt-direction <north|west|south|east>] [--cycles <n>] [--dedup <file>...]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut csv_path = None;
+        let mut state = None;
+        let mut dedup_paths = Vec::new();
+        let mut tilt_direction = TiltDirection::North;
+        let mut num_cycles = DEFAULT_NUM_CYCLES;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--csv" => csv_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--state" => {
+                    let num_cycles = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    state = Some((num_cycles, iter.next().ok_or(USAGE)?.clone()));
+                },
+                "--tilt-direction" => {
+                    tilt_direction = match iter.next().ok_or(USAGE)?.as_str() {
+                        "north" => TiltDirection::North,
+                        "west" => TiltDirection::West,
+                        "south" => TiltDirection::South,
+                        "east" => TiltDirection::East,
+                        _ => return Err(USAGE),
+                    }
+                },
+                "--cycles" => num_cycles = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?,
+                "--dedup" => {
+                    dedup_paths.extend(iter.by_ref().cloned());
+                    if dedup_paths.is_empty() {
+                        return Err(USAGE);
+                    }
+                },
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            csv_path,
+            state,
+            dedup_paths,
+            tilt_direction,
+            num_cycles,
         })
     }
 }
-
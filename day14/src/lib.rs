@@ -1,6 +1,9 @@
 pub mod config;
 
-use std::collections::HashMap;
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::render::{Frame, FrameSource};
+use aoc_core::validate::{self, Issue};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
@@ -36,8 +39,45 @@ impl TryFrom<char> for Tile {
     }
 }
 
+/// Direction in which the platform is tilted, so that the rounded rocks roll that way
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TiltDirection {
+    #[default]
+    North,
+    West,
+    South,
+    East,
+}
+
+impl TiltDirection {
+    /// Number of left rotations of the platform after which this direction points north
+    fn rotations(self) -> usize {
+        match self {
+            TiltDirection::North => 0,
+            TiltDirection::West => 1,
+            TiltDirection::South => 2,
+            TiltDirection::East => 3,
+        }
+    }
+}
+
+impl Display for TiltDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TiltDirection::North => "north",
+            TiltDirection::West => "west",
+            TiltDirection::South => "south",
+            TiltDirection::East => "east",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Start of every serialized state, including the version of the format
+const STATE_MAGIC: &[u8; 8] = b"D14STAT1";
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Puzzle {
+pub struct Puzzle {
     columns: Vec<Vec<Tile>>,
     num_rows: usize,
 }
@@ -48,16 +88,19 @@ impl Display for Puzzle {
             for tile in row {
                 write!(f, "{tile}")?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
 impl Puzzle {
-    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
         let mut rows = Vec::new();
-        let line_len = input.lines().next().ok_or("Empty input")?.len();
+        let line_len = input.lines().next().map_or(0, str::len);
+        if line_len == 0 {
+            return Err("Empty input".into());
+        }
         for line in input.lines() {
             if line.len() != line_len {
                 return Err("Not all lines have the same length".into());
@@ -73,7 +116,7 @@ impl Puzzle {
         Ok(Self { columns, num_rows })
     }
 
-    fn transpose_matrix<T: Clone>(matrix: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    fn transpose_matrix<T: Clone>(matrix: &[Vec<T>]) -> Vec<Vec<T>> {
         let mut rows = Vec::with_capacity(matrix[0].len());
         for i in 0..matrix[0].len() {
             let row = matrix.iter().map(|col| col[i].clone()).collect();
@@ -88,15 +131,15 @@ impl Puzzle {
 
     fn handle_end_of_segment(os_in_segment: usize, segment_len: usize, tilted_column: &mut Vec<Tile>) {
         let dots_in_segment = segment_len - os_in_segment;
-        tilted_column.extend(iter::repeat(Tile::O).take(os_in_segment));
-        tilted_column.extend(iter::repeat(Tile::Dot).take(dots_in_segment));
+        tilted_column.extend(iter::repeat_n(Tile::O, os_in_segment));
+        tilted_column.extend(iter::repeat_n(Tile::Dot, dots_in_segment));
     }
 
-    fn tilt_vector(vector: &Vec<Tile>) -> Vec<Tile> {
+    fn tilt_vector(vector: &[Tile]) -> Vec<Tile> {
         // A segment is a slice of a column/row between two "#" tiles. Process segments one by one,
         // counting the numbers of "O" tiles in them and, when we hit the end of the segment,
         // producing a new segment with the "O" tiles in the beginning, followed by "." tiles.
-        let mut tilted_vector = Vec::with_capacity(vector.capacity());
+        let mut tilted_vector = Vec::with_capacity(vector.len());
         let mut segment_start = 0;
         let mut os_in_segment = 0;
         for (i, &tile) in vector.iter().enumerate() {
@@ -121,7 +164,7 @@ impl Puzzle {
     }
 
     fn transpose(&mut self) {
-        self.columns = Self::transpose_matrix(&mut self.columns);
+        self.columns = Self::transpose_matrix(&self.columns);
         self.num_rows = self.columns[0].len();
     }
 
@@ -130,14 +173,43 @@ impl Puzzle {
         self.columns.reverse();
     }
 
-    fn tilting_cycle(&mut self) {
+    /// Tilts the platform in the given direction.
+    pub fn tilt_towards(&mut self, direction: TiltDirection) {
+        let rotations = direction.rotations();
+        for _ in 0..rotations {
+            self.rotate_left();
+        }
+        self.tilt();
+        for _ in 0..(4 - rotations) % 4 {
+            self.rotate_left();
+        }
+    }
+
+    /// Tilts the platform north, west, south and east.
+    pub fn tilting_cycle(&mut self) {
         for _ in 0..4 {
             self.tilt();
             self.rotate_left();
         }
     }
 
-    fn load(&self) -> usize {
+    /// The smallest rendering of the platform among all its rotations and reflections, which is the
+    /// same for platforms that are rotations or reflections of each other
+    fn canonical_form(&self) -> String {
+        let mut platform = self.clone();
+        let mut forms = Vec::with_capacity(8);
+        for _ in 0..2 {
+            for _ in 0..4 {
+                forms.push(platform.to_string());
+                platform.rotate_left();
+            }
+            platform.transpose();
+        }
+        forms.into_iter().min().unwrap_or_default()
+    }
+
+    /// Load on the north support beams
+    pub fn load(&self) -> usize {
         let mut sum = 0;
         for column in &self.columns {
             for (i, &tile) in column.iter().enumerate() {
@@ -150,45 +222,533 @@ impl Puzzle {
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input(input)?;
-    puzzle.tilt();
-    Ok(puzzle.load())
+impl Puzzle {
+    /// Encodes the platform in a compact binary format: the magic bytes, the number of rows and
+    /// of columns as little-endian 4-byte numbers, then the tiles column by column, 2 bits each
+    /// and four to a byte starting at the lowest bits, with 0 for '.', 1 for 'O' and 2 for '#'.
+    pub fn serialize_state(&self) -> Vec<u8> {
+        let num_tiles = self.num_rows * self.columns.len();
+        let mut bytes = Vec::with_capacity(STATE_MAGIC.len() + 8 + num_tiles.div_ceil(4));
+        bytes.extend(STATE_MAGIC);
+        bytes.extend((self.num_rows as u32).to_le_bytes());
+        bytes.extend((self.columns.len() as u32).to_le_bytes());
+        for (i, &tile) in self.columns.iter().flatten().enumerate() {
+            let code = match tile {
+                Tile::Dot => 0,
+                Tile::O => 1,
+                Tile::Hash => 2,
+            };
+            if i % 4 == 0 {
+                bytes.push(0);
+            }
+            if let Some(byte) = bytes.last_mut() {
+                *byte |= code << (2 * (i % 4));
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a platform written by `serialize_state`.
+    pub fn from_state(bytes: &[u8]) -> Result<Self, &'static str> {
+        let header = STATE_MAGIC.len() + 8;
+        if bytes.len() < header {
+            return Err("State ends unexpectedly");
+        }
+        if &bytes[..STATE_MAGIC.len()] != STATE_MAGIC {
+            return Err("Not a platform state");
+        }
+        let number = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as usize;
+        let (num_rows, num_columns) = (number(STATE_MAGIC.len()), number(STATE_MAGIC.len() + 4));
+        if num_rows == 0 || num_columns == 0 {
+            return Err("Empty platform");
+        }
+        let num_tiles = num_rows.checked_mul(num_columns).ok_or("Platform too large")?;
+        if bytes.len() - header != num_tiles.div_ceil(4) {
+            return Err("State size does not match the platform size");
+        }
+        let mut tiles = (0..num_tiles).map(|i| match (bytes[header + i / 4] >> (2 * (i % 4))) & 3 {
+            0 => Ok(Tile::Dot),
+            1 => Ok(Tile::O),
+            2 => Ok(Tile::Hash),
+            _ => Err("Invalid tile"),
+        });
+        let columns = (0..num_columns)
+            .map(|_| tiles.by_ref().take(num_rows).collect())
+            .collect::<Result<Vec<Vec<Tile>>, _>>()?;
+        Ok(Self { columns, num_rows })
+    }
+
+    /// The (x, y) positions at which the tiles of two platforms of the same size differ, row by
+    /// row
+    pub fn differing_tiles(&self, other: &Self) -> Result<Vec<(usize, usize)>, &'static str> {
+        if (self.num_rows, self.columns.len()) != (other.num_rows, other.columns.len()) {
+            return Err("Platforms differ in size");
+        }
+        let mut positions = Vec::new();
+        for y in 0..self.num_rows {
+            for (x, (column, other_column)) in self.columns.iter().zip(&other.columns).enumerate() {
+                if column[y] != other_column[y] {
+                    positions.push((x, y));
+                }
+            }
+        }
+        Ok(positions)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TrackedTile {
+    Rock(usize),
+    Hash,
+    Dot,
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input(input)?;
-    let mut seen_at_iteration = HashMap::new();
-    let num_operations = 1_000_000_000;
-    for i in 0..num_operations {
-        puzzle.tilting_cycle();
-        if let Some(cycle_start_iteration) = seen_at_iteration.insert(puzzle.clone(), i) {
-            println!("Cycle at i = {i}; same as in iteration {cycle_start_iteration}");
-            let cycle_length = i - cycle_start_iteration;
-            // We may still have to do a few tilts because 1 billion may be in the middle of the
-            // cycle and not at its end.
-            // The state at the end of iteration x is the same as
-            // tilting_cycle^{(x - cycle_start_iteration) % cycle_length}(puzzle).
-            let remaining_operations = (num_operations - 1 - cycle_start_iteration) % cycle_length;
-            for _ in 0..remaining_operations {
-                puzzle.tilting_cycle()
+/// Trajectory of a single "O" rock, with coordinates given as (x, y) where y = 0 is the northmost
+/// row
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rock {
+    /// Rocks are numbered in reading order of the input
+    pub id: usize,
+    /// Position in the input followed by the positions after each simulated tilting cycle
+    pub trajectory: Vec<(usize, usize)>,
+    /// Position after all tilting cycles
+    pub final_position: (usize, usize),
+}
+
+/// Keeps track of the identities of the "O" rocks. When tilting, a rock never overtakes another
+/// one, so the rocks keep their order within each segment.
+struct RockTracker {
+    rows: Vec<Vec<TrackedTile>>,
+    positions: Vec<(usize, usize)>,
+}
+
+impl RockTracker {
+    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let mut rows = Vec::new();
+        let mut positions = Vec::new();
+        let line_len = input.lines().next().map_or(0, str::len);
+        if line_len == 0 {
+            return Err("Empty input".into());
+        }
+        for (y, line) in input.lines().enumerate() {
+            if line.len() != line_len {
+                return Err("Not all lines have the same length".into());
+            }
+            let mut row = Vec::with_capacity(line_len);
+            for (x, c) in line.chars().enumerate() {
+                row.push(match Tile::try_from(c)? {
+                    Tile::O => {
+                        positions.push((x, y));
+                        TrackedTile::Rock(positions.len() - 1)
+                    },
+                    Tile::Hash => TrackedTile::Hash,
+                    Tile::Dot => TrackedTile::Dot,
+                });
+            }
+            rows.push(row);
+        }
+        Ok(Self { rows, positions })
+    }
+
+    /// Tilts each of `num_lines` lines of length `line_len` towards its start, where `coords`
+    /// maps a line and an offset in it to (x, y) coordinates.
+    fn tilt(&mut self, num_lines: usize, line_len: usize, coords: impl Fn(usize, usize) -> (usize, usize)) {
+        for line in 0..num_lines {
+            let mut free = 0;
+            for i in 0..line_len {
+                let (x, y) = coords(line, i);
+                match self.rows[y][x] {
+                    TrackedTile::Hash => free = i + 1,
+                    TrackedTile::Rock(id) => {
+                        let (xn, yn) = coords(line, free);
+                        self.rows[y][x] = TrackedTile::Dot;
+                        self.rows[yn][xn] = TrackedTile::Rock(id);
+                        self.positions[id] = (xn, yn);
+                        free += 1;
+                    },
+                    TrackedTile::Dot => (),
+                }
+            }
+        }
+    }
+
+    fn tilting_cycle(&mut self) {
+        let h = self.rows.len();
+        let w = self.rows[0].len();
+        self.tilt(w, h, |line, i| (line, i)); // north
+        self.tilt(h, w, |line, i| (i, line)); // west
+        self.tilt(w, h, |line, i| (line, h - 1 - i)); // south
+        self.tilt(h, w, |line, i| (w - 1 - i, line)); // east
+    }
+
+    /// Performs `num_cycles` tilting cycles and returns the trajectories of all rocks. Once the
+    /// arrangement of the rocks repeats, the remaining cycles are skipped using the periodicity.
+    fn track(&mut self, num_cycles: usize) -> Vec<Rock> {
+        let mut trajectories: Vec<Vec<(usize, usize)>> = self.positions.iter().map(|&p| vec![p]).collect();
+        let mut seen_at_iteration = HashMap::new();
+        let occupied = |positions: &[(usize, usize)]| {
+            let mut occupied = positions.to_vec();
+            occupied.sort_unstable();
+            occupied
+        };
+        seen_at_iteration.insert(occupied(&self.positions), 0);
+        for i in 1..=num_cycles {
+            self.tilting_cycle();
+            for (trajectory, &position) in trajectories.iter_mut().zip(&self.positions) {
+                trajectory.push(position);
+            }
+            if let Some(cycle_start_iteration) = seen_at_iteration.insert(occupied(&self.positions), i) {
+                let final_positions = Self::extrapolate(&trajectories, cycle_start_iteration, i, num_cycles);
+                return Self::rocks(trajectories, final_positions);
             }
-            break;
         }
+        let final_positions = self.positions.clone();
+        Self::rocks(trajectories, final_positions)
+    }
+
+    /// Computes the positions of all rocks after `num_cycles` tilting cycles, given their
+    /// trajectories up to the iteration `cycle_end`, which has the same arrangement of rocks as
+    /// the iteration `cycle_start`.
+    ///
+    /// The rocks need not end up in the same places after a cycle of arrangements, but they are
+    /// permuted the same way in each cycle.
+    fn extrapolate(
+        trajectories: &[Vec<(usize, usize)>], cycle_start: usize, cycle_end: usize, num_cycles: usize
+    ) -> Vec<(usize, usize)> {
+        let cycle_length = cycle_end - cycle_start;
+        let full_cycles = (num_cycles - cycle_start) / cycle_length;
+        let remaining = (num_cycles - cycle_start) % cycle_length;
+        // Where a rock at a certain position at iteration cycle_start ends up after one cycle
+        let permutation: HashMap<_, _> = trajectories.iter()
+            .map(|t| (t[cycle_start], t[cycle_end]))
+            .collect();
+        // Where a rock at a certain position at iteration cycle_start ends up after `remaining`
+        // more iterations
+        let rest: HashMap<_, _> = trajectories.iter()
+            .map(|t| (t[cycle_start], t[cycle_start + remaining]))
+            .collect();
+        trajectories.iter().map(|t| {
+            // Find the orbit of the rock's position under the permutation to apply it
+            // `full_cycles` times
+            let mut orbit = vec![t[cycle_start]];
+            loop {
+                let next = permutation[orbit.last().unwrap()];
+                if next == orbit[0] { break }
+                orbit.push(next);
+            }
+            rest[&orbit[full_cycles % orbit.len()]]
+        }).collect()
+    }
+
+    fn rocks(trajectories: Vec<Vec<(usize, usize)>>, final_positions: Vec<(usize, usize)>) -> Vec<Rock> {
+        trajectories.into_iter().zip(final_positions).enumerate()
+            .map(|(id, (trajectory, final_position))| Rock { id, trajectory, final_position })
+            .collect()
     }
-    Ok(puzzle.load())
+}
+
+/// Tracks each "O" rock through `num_cycles` tilting cycles.
+pub fn track_rocks(input: &str, num_cycles: usize) -> Result<Vec<Rock>, Box<dyn Error>> {
+    let mut tracker = RockTracker::from_input(input)?;
+    Ok(tracker.track(num_cycles))
+}
+
+/// A platform whose obstacles can be edited, keeping track of the load on the north support beams
+/// after tilting it north. Coordinates are given as (x, y) where y = 0 is the northmost row.
+pub struct Platform {
+    columns: Vec<Vec<Tile>>,
+    num_rows: usize,
+    tilted_load: usize,
+}
+
+/// Where to place an additional obstacle and the resulting load after tilting north
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WallPlacement {
+    pub x: usize,
+    pub y: usize,
+    pub load: usize,
+}
+
+impl Platform {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let Puzzle { columns, num_rows } = Puzzle::from_input(input)?;
+        let mut platform = Self { columns, num_rows, tilted_load: 0 };
+        let mut tilted_load = 0;
+        for (x, column) in platform.columns.iter().enumerate() {
+            let obstacles = column.iter().enumerate().filter(|&(_, &tile)| tile == Tile::Hash).map(|(y, _)| y);
+            let mut start = 0;
+            for end in obstacles.chain([platform.num_rows]) {
+                tilted_load += platform.segment_load(x, start, end);
+                start = end + 1;
+            }
+        }
+        platform.tilted_load = tilted_load;
+        Ok(platform)
+    }
+
+    /// Load after tilting north
+    pub fn tilted_load(&self) -> usize {
+        self.tilted_load
+    }
+
+    /// Load of the rocks between rows `start` (inclusive) and `end` (exclusive) of column `x`
+    /// after tilting north, assuming there is no obstacle in between
+    fn segment_load(&self, x: usize, start: usize, end: usize) -> usize {
+        let num_rocks = self.columns[x][start..end].iter().filter(|&&tile| tile == Tile::O).count();
+        (start..start + num_rocks).map(|y| self.num_rows - y).sum()
+    }
+
+    /// Returns the rows where the segment of column `x` around row `y` starts and ends (exclusive),
+    /// not counting the tile at row `y` itself as an obstacle.
+    fn segment_around(&self, x: usize, y: usize) -> (usize, usize) {
+        let column = &self.columns[x];
+        let start = column[..y].iter().rposition(|&tile| tile == Tile::Hash).map_or(0, |i| i + 1);
+        let end = column[y + 1..].iter().position(|&tile| tile == Tile::Hash).map_or(self.num_rows, |i| y + 1 + i);
+        (start, end)
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Result<Tile, &'static str> {
+        self.columns.get(x).and_then(|column| column.get(y)).copied().ok_or("Position out of bounds")
+    }
+
+    /// Places an obstacle on the empty tile at (x, y), updating the load of only the segment it
+    /// splits.
+    pub fn add_obstacle(&mut self, x: usize, y: usize) -> Result<(), &'static str> {
+        if self.tile(x, y)? != Tile::Dot {
+            return Err("Tile is not empty");
+        }
+        let (start, end) = self.segment_around(x, y);
+        self.tilted_load -= self.segment_load(x, start, end);
+        self.columns[x][y] = Tile::Hash;
+        self.tilted_load += self.segment_load(x, start, y) + self.segment_load(x, y + 1, end);
+        Ok(())
+    }
+
+    /// Removes the obstacle at (x, y), updating the load of only the two segments it joins.
+    pub fn remove_obstacle(&mut self, x: usize, y: usize) -> Result<(), &'static str> {
+        if self.tile(x, y)? != Tile::Hash {
+            return Err("Tile is not an obstacle");
+        }
+        let (start, end) = self.segment_around(x, y);
+        self.tilted_load -= self.segment_load(x, start, y) + self.segment_load(x, y + 1, end);
+        self.columns[x][y] = Tile::Dot;
+        self.tilted_load += self.segment_load(x, start, end);
+        Ok(())
+    }
+
+    /// Tries placing an additional obstacle on each empty tile and returns the resulting loads
+    /// after tilting north, in reading order.
+    pub fn wall_placements(&mut self) -> Vec<WallPlacement> {
+        let mut placements = Vec::new();
+        for y in 0..self.num_rows {
+            for x in 0..self.columns.len() {
+                if self.add_obstacle(x, y).is_ok() {
+                    placements.push(WallPlacement { x, y, load: self.tilted_load });
+                    self.remove_obstacle(x, y).expect("Obstacle was just added");
+                }
+            }
+        }
+        placements
+    }
+
+    /// Finds the empty tile where an additional obstacle maximizes the load after tilting north,
+    /// preferring the first such tile in reading order, or `None` if there is no empty tile.
+    pub fn best_wall_placement(&mut self) -> Option<WallPlacement> {
+        self.wall_placements().into_iter().rev().max_by_key(|placement| placement.load)
+    }
+}
+
+/// Groups the platforms that are rotations or reflections of each other by hashing their canonical
+/// forms. Returns the groups with at least two platforms as indices into `inputs`, each group in
+/// increasing order and the groups ordered by their first index.
+pub fn duplicate_platforms(inputs: &[&str]) -> Result<Vec<Vec<usize>>, Box<dyn Error>> {
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, input) in inputs.iter().enumerate() {
+        let puzzle = Puzzle::from_input(input).map_err(|e| format!("Platform {}: {e}", i + 1))?;
+        groups.entry(puzzle.canonical_form()).or_default().push(i);
+    }
+    let mut duplicates: Vec<Vec<usize>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    duplicates.sort();
+    Ok(duplicates)
+}
+
+/// Load on the north support beams after tilting the platform in the given direction
+pub fn load_after_tilt(input: &str, direction: TiltDirection) -> Result<usize, Box<dyn Error>> {
+    Ok(load_after_tilt_of(Puzzle::from_input(input)?, direction))
+}
+
+fn load_after_tilt_of(mut puzzle: Puzzle, direction: TiltDirection) -> usize {
+    puzzle.tilt_towards(direction);
+    puzzle.load()
+}
+
+/// Both parts, each tilting its own copy of the same platform
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(load_after_tilt_of(puzzle.clone(), TiltDirection::North).into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(LoadHistory::from_puzzle(puzzle.clone()).load_after(DEFAULT_NUM_CYCLES).into())
+    }
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Loads on the north support beams from the initial state until a state repeats
+pub struct LoadHistory {
+    /// Load after each number of tilting cycles, starting with the initial state
+    loads: Vec<usize>,
+    /// Number of tilting cycles after which the state is first seen that later repeats
+    cycle_start: usize,
+    cycle_length: usize,
+}
+
+impl LoadHistory {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::from_puzzle(Puzzle::from_input(input)?))
+    }
+
+    fn from_puzzle(mut puzzle: Puzzle) -> Self {
+        let mut seen_at_iteration = HashMap::new();
+        let mut loads = vec![puzzle.load()];
+        seen_at_iteration.insert(puzzle.clone(), 0);
+        for i in 1.. {
+            puzzle.tilting_cycle();
+            loads.push(puzzle.load());
+            if let Some(cycle_start) = seen_at_iteration.insert(puzzle.clone(), i) {
+                return Self { loads, cycle_start, cycle_length: i - cycle_start };
+            }
+        }
+        unreachable!()
+    }
+
+    /// The loads after 0, 1, 2, ... tilting cycles, up to and including the first repeated state
+    pub fn loads(&self) -> &[usize] {
+        &self.loads
+    }
+
+    /// Returns the number of tilting cycles after which the repeating part starts and its length.
+    pub fn cycle(&self) -> (usize, usize) {
+        (self.cycle_start, self.cycle_length)
+    }
+
+    /// Load after `num_cycles` tilting cycles
+    pub fn load_after(&self, num_cycles: usize) -> usize {
+        if num_cycles < self.loads.len() {
+            return self.loads[num_cycles];
+        }
+        // The state after x cycles is the same as after
+        // cycle_start + (x - cycle_start) % cycle_length cycles.
+        self.loads[self.cycle_start + (num_cycles - self.cycle_start) % self.cycle_length]
+    }
+
+    /// Writes the loads as CSV with the columns `cycle` and `load`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("cycle,load\n");
+        for (i, load) in self.loads.iter().enumerate() {
+            csv.push_str(&format!("{i},{load}\n"));
+        }
+        csv
+    }
+}
+
+/// Frames showing the platform after each tilting cycle, starting with the initial state and
+/// ending when a state repeats
+pub struct TiltFrames {
+    puzzle: Puzzle,
+    seen: HashSet<Puzzle>,
+    num_cycles: usize,
+    done: bool,
+}
+
+impl TiltFrames {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self { puzzle: Puzzle::from_input(input)?, seen: HashSet::new(), num_cycles: 0, done: false })
+    }
+}
+
+impl FrameSource for TiltFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        if self.done {
+            return None;
+        }
+        let caption = format!("Cycle {}, load {}", self.num_cycles, self.puzzle.load());
+        let frame = Frame::from_text(caption, &self.puzzle.to_string());
+        self.done = !self.seen.insert(self.puzzle.clone());
+        self.puzzle.tilting_cycle();
+        self.num_cycles += 1;
+        Some(frame)
+    }
+}
+
+/// Number of tilting cycles in part 2
+pub const DEFAULT_NUM_CYCLES: usize = 1_000_000_000;
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and characters other than 'O', '#' and '.'.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_grid(input, |c| Tile::try_from(c).is_ok())
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if !config.dedup_paths.is_empty() {
+        let contents = config.dedup_paths.iter().map(fs::read_to_string).collect::<Result<Vec<_>, _>>()?;
+        let inputs: Vec<&str> = contents.iter().map(String::as_str).collect();
+        let duplicates = duplicate_platforms(&inputs)?;
+        if duplicates.is_empty() {
+            println!("No two platforms are rotations or reflections of each other");
+        }
+        for group in duplicates {
+            let paths: Vec<&str> = group.iter().map(|&i| config.dedup_paths[i].as_str()).collect();
+            println!("Rotations or reflections of each other: {}", paths.join(", "));
+        }
+        return Ok(());
+    }
+
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let puzzle = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    let result = load_after_tilt_of(puzzle.clone(), config.tilt_direction);
+    println!("Result of part 1 (tilted {}): {result}", config.tilt_direction);
+
+    let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, Day::parse)?;
+    let history = LoadHistory::from_puzzle(puzzle.clone());
+    let (cycle_start, cycle_length) = history.cycle();
+    println!("Cycle at i = {}; same as in iteration {cycle_start}", cycle_start + cycle_length);
+    let result = history.load_after(config.num_cycles);
+    println!("Result of part 2 (after {} cycles): {result}", config.num_cycles);
+
+    if let Some((num_cycles, state_path)) = config.state {
+        let mut puzzle = puzzle.clone();
+        for _ in 0..num_cycles {
+            puzzle.tilting_cycle();
+        }
+        println!("Writing state of part 2 after {num_cycles} tilting cycles to {state_path}");
+        fs::write(state_path, puzzle.serialize_state())?;
+    }
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    if let Some(csv_path) = config.csv_path {
+        println!("Writing loads of part 2 to {csv_path}");
+        fs::write(csv_path, history.to_csv())?;
+    }
 
     Ok(())
 }
@@ -212,15 +772,185 @@ O.#..O.#.#
 
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 136);
         Ok(())
     }
 
+    #[test]
+    fn example1_tilt_directions() -> Result<(), Box<dyn Error>> {
+        let input = EXAMPLE1.trim();
+        assert_eq!(load_after_tilt(input, TiltDirection::North)?, 136);
+        // Tilting in each direction in turn is one tilting cycle
+        let mut cycled = Puzzle::from_input(input)?;
+        cycled.tilting_cycle();
+        let mut tilted = Puzzle::from_input(input)?;
+        for direction in [TiltDirection::North, TiltDirection::West, TiltDirection::South, TiltDirection::East] {
+            tilted.tilt_towards(direction);
+        }
+        assert_eq!(tilted, cycled);
+        let tilted = |direction| -> Result<String, Box<dyn Error>> {
+            let mut puzzle = Puzzle::from_input("O.\n.#\nO.")?;
+            puzzle.tilt_towards(direction);
+            Ok(puzzle.to_string())
+        };
+        assert_eq!(tilted(TiltDirection::South)?, "..\nO#\nO.\n");
+        assert_eq!(tilted(TiltDirection::East)?, ".O\n.#\n.O\n");
+        assert_eq!(tilted(TiltDirection::West)?, "O.\n.#\nO.\n");
+        assert_eq!(load_after_tilt("O.\n.#\nO.", TiltDirection::South)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_obstacle_editing() -> Result<(), Box<dyn Error>> {
+        let mut platform = Platform::from_input(EXAMPLE1.trim())?;
+        assert_eq!(platform.tilted_load(), 136);
+        // The rocks at rows 3 and 5 of column 0 now stop at rows 3 and 4 instead of 2 and 3
+        platform.add_obstacle(0, 2)?;
+        assert_eq!(platform.tilted_load(), 134);
+        assert_eq!(platform.add_obstacle(0, 2), Err("Tile is not empty"));
+        assert_eq!(platform.add_obstacle(0, 0), Err("Tile is not empty"));
+        assert_eq!(platform.add_obstacle(10, 0), Err("Position out of bounds"));
+        platform.remove_obstacle(0, 2)?;
+        assert_eq!(platform.tilted_load(), 136);
+        assert_eq!(platform.remove_obstacle(0, 2), Err("Tile is not an obstacle"));
+        // Removing an existing obstacle lets the rocks below it roll further
+        platform.remove_obstacle(2, 5)?;
+        let mut expected = EXAMPLE1.trim().to_owned();
+        expected.replace_range(5 * 11 + 2..5 * 11 + 3, ".");
+        let mut puzzle = Puzzle::from_input(&expected)?;
+        puzzle.tilt();
+        assert_eq!(platform.tilted_load(), puzzle.load());
+        Ok(())
+    }
+
+    #[test]
+    fn example1_best_wall_placement() -> Result<(), Box<dyn Error>> {
+        // Brute force by tilting the platform with each possible extra obstacle
+        let lines: Vec<&str> = EXAMPLE1.trim().lines().collect();
+        let mut placements = Vec::new();
+        for (y, line) in lines.iter().enumerate() {
+            for (x, _) in line.char_indices().filter(|&(_, c)| c == '.') {
+                let mut edited: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+                edited[y].replace_range(x..x + 1, "#");
+                let mut puzzle = Puzzle::from_input(&edited.join("\n"))?;
+                puzzle.tilt();
+                placements.push(WallPlacement { x, y, load: puzzle.load() });
+            }
+        }
+        let mut platform = Platform::from_input(EXAMPLE1.trim())?;
+        assert_eq!(platform.wall_placements(), placements);
+        let max_load = placements.iter().map(|placement| placement.load).max();
+        let expected = placements.iter().find(|placement| Some(placement.load) == max_load).copied();
+        assert_eq!(platform.best_wall_placement(), expected);
+        assert_eq!(platform.tilted_load(), 136);
+        // An obstacle can only hold rocks back, so the worst placement shows where it hurts most
+        let worst = platform.wall_placements().into_iter().min_by_key(|placement| placement.load);
+        assert_eq!(worst, Some(WallPlacement { x: 1, y: 2, load: 127 }));
+        Ok(())
+    }
+
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = solve_part2(EXAMPLE1.trim())?;
         assert_eq!(result, 64);
         Ok(())
     }
+
+    #[test]
+    fn example1_load_history() -> Result<(), Box<dyn Error>> {
+        let history = LoadHistory::from_input(EXAMPLE1.trim())?;
+        assert_eq!(history.cycle(), (3, 7));
+        assert_eq!(history.loads()[..4], [104, 87, 69, 69]);
+        assert_eq!(history.load_after(1_000_000_000), 64);
+        let csv = history.to_csv();
+        assert_eq!(csv.lines().count(), history.loads().len() + 1);
+        assert!(csv.starts_with("cycle,load\n0,104\n1,87\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn state_round_trip() -> Result<(), Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let state = puzzle.serialize_state();
+        assert_eq!(state.len(), 8 + 8 + 25);
+        assert_eq!(Puzzle::from_state(&state)?, puzzle);
+        // Resuming from a checkpoint gives the same platform as running through
+        puzzle.tilting_cycle();
+        let mut resumed = Puzzle::from_state(&puzzle.serialize_state())?;
+        resumed.tilting_cycle();
+        puzzle.tilting_cycle();
+        assert_eq!(resumed, puzzle);
+        assert_eq!(resumed.load(), 69);
+        assert_eq!(Puzzle::from_state(&state[..state.len() - 1]), Err("State size does not match the platform size"));
+        assert_eq!(Puzzle::from_state(b"D14STAT0"), Err("State ends unexpectedly"));
+        let mut invalid = state.clone();
+        invalid[16] |= 3;
+        assert_eq!(Puzzle::from_state(&invalid), Err("Invalid tile"));
+        Ok(())
+    }
+
+    #[test]
+    fn differing_tiles() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let mut tilted = puzzle.clone();
+        tilted.tilt();
+        let differences = puzzle.differing_tiles(&tilted)?;
+        assert_eq!(differences[..4], [(1, 0), (2, 0), (3, 0), (7, 0)]);
+        assert!(puzzle.differing_tiles(&puzzle)?.is_empty());
+        let other = Puzzle::from_input("O.\n.#")?;
+        assert_eq!(puzzle.differing_tiles(&other), Err("Platforms differ in size"));
+        Ok(())
+    }
+
+    #[test]
+    fn duplicates_up_to_symmetry() -> Result<(), Box<dyn Error>> {
+        let platform = "O.#\n..O\n#..\nO.O";
+        let rotated = "O#.O\n....\nO.O#";
+        let mirrored = "#.O\nO..\n..#\nO.O";
+        let transposed = "O.#O\n....\n#O.O";
+        let other = "O.#\n.O.\n#..\nO.O";
+        let inputs = [platform, other, rotated, mirrored, EXAMPLE1.trim(), transposed, other];
+        assert_eq!(duplicate_platforms(&inputs)?, [vec![0, 2, 3, 5], vec![1, 6]]);
+        assert!(duplicate_platforms(&[platform, EXAMPLE1.trim()])?.is_empty());
+        let error = duplicate_platforms(&[platform, "O.\nO"]).unwrap_err().to_string();
+        assert_eq!(error, "Platform 2: Not all lines have the same length");
+        Ok(())
+    }
+
+    #[test]
+    fn example1_frames() -> Result<(), Box<dyn Error>> {
+        let mut frames = TiltFrames::from_input(EXAMPLE1.trim())?;
+        let first = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(first.caption, "Cycle 0, load 104");
+        assert_eq!(first.lines.join("\n"), EXAMPLE1.trim());
+        // Frames up to and including the first repeated state after 10 cycles
+        assert_eq!(iter::from_fn(|| frames.next_frame()).count(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_track_rocks() -> Result<(), Box<dyn Error>> {
+        let rocks = track_rocks(EXAMPLE1.trim(), 1_000_000_000)?;
+        assert_eq!(rocks.len(), 18);
+        assert_eq!(rocks[0].trajectory[0], (0, 0));
+        let load: usize = rocks.iter().map(|rock| 10 - rock.final_position.1).sum();
+        assert_eq!(load, 64);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_track_rocks_matches_simulation() -> Result<(), Box<dyn Error>> {
+        // Each rock must end up where it would be without skipping any cycles
+        for num_cycles in [0, 1, 3, 10, 11, 12, 25] {
+            let rocks = track_rocks(EXAMPLE1.trim(), num_cycles)?;
+            let mut tracker = RockTracker::from_input(EXAMPLE1.trim())?;
+            for _ in 0..num_cycles {
+                tracker.tilting_cycle();
+            }
+            let final_positions: Vec<_> = rocks.iter().map(|rock| rock.final_position).collect();
+            assert_eq!(final_positions, tracker.positions);
+        }
+        Ok(())
+    }
 }
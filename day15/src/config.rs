@@ -1,17 +1,27 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Reject whitespace within the sequence of steps instead of ignoring it
+    pub strict: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day15 <file for part 1> <file for part 2> [--strict]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut strict = false;
+        for arg in &args[3..] {
+            match arg.as_str() {
+                "--strict" => strict = true,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            strict,
         })
     }
 }
-
@@ -1,5 +1,9 @@
 pub mod config;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parallel;
+use aoc_core::validate::Issue;
+use std::borrow::Cow;
 use std::error::Error;
 use std::{fs, array};
 
@@ -9,32 +13,79 @@ fn hash(string: &str) -> u8 {
 
 struct MapEntry<'a>(&'a str, u8);
 
-struct HashMap<'a> {
+/// Map from labels to focal lengths with 256 buckets that keep their entries in insertion order.
+/// Labels are compared case-sensitively and stored as given. The focusing power is kept up to date
+/// as lenses are set and removed, by recomputing only the contribution of the changed bucket.
+pub struct HashMap<'a> {
     buckets: [Vec<MapEntry<'a>>; 256],
+    /// Contribution of each bucket to the focusing power
+    bucket_powers: [usize; 256],
+    power: usize,
+}
+
+impl Default for HashMap<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> HashMap<'a> {
-    fn new() -> Self {
-        Self { buckets: array::from_fn(|_| Vec::new()) }
+    pub fn new() -> Self {
+        Self { buckets: array::from_fn(|_| Vec::new()), bucket_powers: [0; 256], power: 0 }
     }
 
-    fn set(&mut self, key: &'a str, value: u8) {
-        let bucket = &mut self.buckets[hash(&key) as usize];
-        for MapEntry(k, v) in &mut *bucket {
-            if *k == key {
-                *v = value;
-                return;
-            }
+    /// Recomputes the contribution of the bucket with the given index to the focusing power.
+    fn update_power(&mut self, index: usize) {
+        let bucket_power = (index + 1) * self.buckets[index].iter().enumerate()
+            .map(|(j, MapEntry(_, v))| (j + 1) * (*v as usize))
+            .sum::<usize>();
+        self.power = self.power - self.bucket_powers[index] + bucket_power;
+        self.bucket_powers[index] = bucket_power;
+    }
+
+    /// Sets the focal length of the lens with the given label and returns its previous focal
+    /// length. A new lens is put at the back of its bucket.
+    pub fn set(&mut self, key: &'a str, value: u8) -> Option<u8> {
+        let index = hash(key) as usize;
+        let bucket = &mut self.buckets[index];
+        let previous = match bucket.iter_mut().find(|MapEntry(k, _)| *k == key) {
+            Some(MapEntry(_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                bucket.push(MapEntry(key, value));
+                None
+            },
+        };
+        self.update_power(index);
+        previous
+    }
+
+    /// Removes the lens with the given label and returns whether there was one.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let index = hash(key) as usize;
+        let bucket = &mut self.buckets[index];
+        let len = bucket.len();
+        bucket.retain(|MapEntry(k, _)| *k != key);
+        let removed = bucket.len() != len;
+        if removed {
+            self.update_power(index);
         }
-        bucket.push(MapEntry(key, value));
+        removed
     }
 
-    fn remove(&mut self, key: &str) {
-        let bucket = &mut self.buckets[hash(&key) as usize];
-        bucket.retain(|MapEntry(k, _)| *k != key)
+    /// Returns the focal length of the lens with the given label.
+    pub fn get(&self, key: &str) -> Option<u8> {
+        self.buckets[hash(key) as usize].iter()
+            .find(|MapEntry(k, _)| *k == key)
+            .map(|&MapEntry(_, v)| v)
     }
 
-    fn focusing_power(&self) -> u32 {
+    /// Returns the focusing power of all lenses without recomputing it.
+    pub fn current_power(&self) -> u32 {
+        self.power as u32
+    }
+
+    /// Computes the focusing power of all lenses from scratch.
+    pub fn focusing_power(&self) -> u32 {
         let mut sum = 0;
         for (i, bucket) in self.buckets.iter().enumerate() {
             for (j, MapEntry(_, v)) in bucket.iter().enumerate() {
@@ -45,37 +96,141 @@ impl<'a> HashMap<'a> {
     }
 }
 
-fn part1(input: &str) -> Result<u32, Box<dyn Error>> {
-    Ok(input.trim().split(',').map(|s| hash(s) as u32).sum())
+/// How whitespace in the initialization sequence is treated
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    /// The sequence must be a single line without whitespace, apart from leading and trailing
+    /// whitespace
+    Strict,
+    /// Whitespace, including line breaks, is ignored anywhere in the sequence
+    #[default]
+    Lenient,
+}
+
+/// Removes whitespace from the sequence as allowed by `format`.
+fn normalize(input: &str, format: Format) -> Result<Cow<'_, str>, String> {
+    let input = input.trim();
+    match format {
+        Format::Strict => match input.find(char::is_whitespace) {
+            Some(i) => Err(format!("Unexpected whitespace at column {}", input[..i].chars().count() + 1)),
+            None => Ok(Cow::Borrowed(input)),
+        },
+        Format::Lenient => Ok(input.chars().filter(|c| !c.is_whitespace()).collect()),
+    }
+}
+
+/// Approximate length in bytes of the pieces of the sequence that are hashed in parallel
+const CHUNK_LEN: usize = 1 << 20;
+
+/// Splits the sequence into pieces of about `chunk_len` bytes, each ending before a comma, such
+/// that no step is split.
+fn chunks(sequence: &str, chunk_len: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = sequence;
+    while rest.len() > chunk_len {
+        let Some(i) = rest.bytes().skip(chunk_len).position(|b| b == b',') else {
+            break;
+        };
+        let (chunk, tail) = rest.split_at(chunk_len + i);
+        chunks.push(chunk);
+        rest = &tail[1..];
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Sums the hashes of the comma-separated steps, hashing pieces of long sequences in parallel.
+pub fn hash_sum(sequence: &str) -> u64 {
+    let sums = parallel::map(&chunks(sequence, CHUNK_LEN), |chunk| {
+        chunk.split(',').map(|s| hash(s) as u64).sum::<u64>()
+    });
+    sums.into_iter().sum()
 }
 
-fn part2(input: &str) -> Result<u32, Box<dyn Error>> {
-    let steps = input.trim().split(',');
-    let mut map = HashMap::new();
-    for step in steps {
+enum Step<'a> {
+    Set(&'a str, u8),
+    Remove(&'a str),
+}
+
+impl<'a> Step<'a> {
+    fn from_str(step: &'a str) -> Result<Self, Box<dyn Error>> {
         if let Some((key, value)) = step.split_once('=') {
-            map.set(key, value.parse()?);
+            Ok(Step::Set(key, value.parse()?))
         }
         else if let Some((key, _)) = step.split_once('-') {
-            map.remove(key);
+            Ok(Step::Remove(key))
         }
         else {
-            return Err("Invalid step".into());
+            Err("Invalid step".into())
         }
     }
-    Ok(map.focusing_power())
+}
+
+/// Both parts, solved from the same sequence without whitespace
+struct Day;
+
+impl Solution for Day {
+    type Parsed = String;
+
+    fn parse(input: &str) -> Result<String, Box<dyn Error>> {
+        parse(input, Format::Lenient)
+    }
+
+    fn part1(sequence: &String) -> Result<Answer, Box<dyn Error>> {
+        Ok(hash_sum(sequence).into())
+    }
+
+    fn part2(sequence: &String) -> Result<Answer, Box<dyn Error>> {
+        let mut map = HashMap::new();
+        for step in sequence.split(',') {
+            match Step::from_str(step)? {
+                Step::Set(key, value) => { map.set(key, value); },
+                Step::Remove(key) => { map.remove(key); },
+            }
+        }
+        Ok(map.current_power().into())
+    }
+}
+
+/// Reads the sequence, removing whitespace as allowed by `format`.
+fn parse(input: &str, format: Format) -> Result<String, Box<dyn Error>> {
+    Ok(normalize(input, format)?.into_owned())
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the steps that cannot be read, at the column where they start.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut column = input.len() - input.trim_start().len() + 1;
+    for step in input.trim().split(',') {
+        if let Err(e) = Step::from_str(step) {
+            issues.push(Issue::at(1, column, format!("{e}: {step:?}")));
+        }
+        column += step.len() + 1;
+    }
+    issues
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    let format = if config.strict { Format::Strict } else { Format::Lenient };
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let sequence = parse(&fs::read_to_string(&config.file_path1)?, format)?;
+    println!("Result of part 1: {}", Day::part1(&sequence)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let sequence = answer::parse_part2(sequence, &config.file_path1, &config.file_path2, |input| parse(input, format))?;
+    println!("Result of part 2: {}", Day::part2(&sequence)?);
 
     Ok(())
 }
@@ -90,15 +245,94 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
 
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = Day::part1(&parse(EXAMPLE1.trim(), Format::Strict)?)?;
         assert_eq!(result, 1320);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = Day::part2(&parse(EXAMPLE1.trim(), Format::Strict)?)?;
         assert_eq!(result, 145);
         Ok(())
     }
+
+    #[test]
+    fn chunked_hash_sum() {
+        let sequence = EXAMPLE1.trim();
+        for chunk_len in [0, 1, 4, 5, 10, 100] {
+            let chunks = chunks(sequence, chunk_len);
+            assert_eq!(chunks.join(","), sequence);
+        }
+        assert_eq!(chunks(sequence, 4)[..2], ["rn=1", "cm-,qp=3"]);
+        assert_eq!(chunks("", 4), [""]);
+        assert_eq!(hash_sum(&[sequence; 1000].join(",")), 1_320_000);
+    }
+
+    /// Hashes a sequence of 100 MB. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_long_sequence() {
+        let steps = ["rn=1", "cm-", "qp=3", "cm=2", "qp-", "pc=4", "ot=9", "ab=5", "pc-", "pc=6", "ot=7"];
+        let mut sequence = steps.join(",");
+        while sequence.len() < 100_000_000 {
+            sequence = format!("{sequence},{sequence}");
+        }
+        let start = std::time::Instant::now();
+        let sequential = sequence.split(',').map(|s| hash(s) as u64).sum::<u64>();
+        println!("Sequential: {sequential} in {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let parallel = hash_sum(&sequence);
+        println!("Parallel: {parallel} in {:?}", start.elapsed());
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn reflowed_example() -> Result<(), Box<dyn Error>> {
+        let reflowed = "rn=1,cm-,qp=3,\ncm=2, qp-,pc=4,ot\n=9,ab=5,pc-,\tpc=6,ot=7\n";
+        assert_eq!(Day::part1(&parse(reflowed, Format::Lenient)?)?, 1320);
+        assert_eq!(Day::part2(&parse(reflowed, Format::Lenient)?)?, 145);
+        assert_eq!(parse(reflowed, Format::Strict).unwrap_err().to_string(), "Unexpected whitespace at column 15");
+        Ok(())
+    }
+
+    #[test]
+    fn validate_steps() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        assert_eq!(validate("rn=1,cm,qp=x"), vec![
+            Issue::at(1, 6, "Invalid step: \"cm\""),
+            Issue::at(1, 9, "invalid digit found in string: \"qp=x\""),
+        ]);
+    }
+
+    #[test]
+    fn map_return_values() {
+        let mut map = HashMap::new();
+        assert_eq!(map.set("rn", 1), None);
+        assert_eq!(map.set("RN", 2), None);
+        assert_eq!(map.set("rn", 3), Some(1));
+        assert_eq!(map.get("rn"), Some(3));
+        assert_eq!(map.get("RN"), Some(2));
+        assert_eq!(map.get("cm"), None);
+        assert!(map.remove("rn"));
+        assert!(!map.remove("rn"));
+        assert_eq!(map.get("rn"), None);
+        assert_eq!(map.get("RN"), Some(2));
+    }
+
+    #[test]
+    fn incremental_power() -> Result<(), Box<dyn Error>> {
+        let mut map = HashMap::new();
+        let mut powers = Vec::new();
+        for step in EXAMPLE1.trim().split(',') {
+            match Step::from_str(step)? {
+                Step::Set(key, value) => { map.set(key, value); },
+                Step::Remove(key) => { map.remove(key); },
+            }
+            assert_eq!(map.current_power(), map.focusing_power());
+            powers.push(map.current_power());
+        }
+        assert_eq!(powers, [1, 1, 7, 11, 5, 21, 93, 153, 81, 153, 145]);
+        Ok(())
+    }
 }
@@ -1,17 +1,48 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print the sizes of the classes of entry points of part 2 that energize the same tiles
+    pub stats: bool,
+    /// File to which the number of entry points energizing each tile of part 2 is written as CSV
+    pub heatmap_path: Option<String>,
+    /// Width and height of the subgrids of part 2 among which to find the one in which the most
+    /// tiles can be energized
+    pub subgrid: Option<(usize, usize)>,
+    /// Number of entry points of part 2 energizing the most tiles to list with their timings
+    pub top: Option<usize>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day16 <file for part 1> <file for part 2> [--stats] [--heatmap <file>] [--subgrid <width> <height>] [--top <k>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut stats = false;
+        let mut heatmap_path = None;
+        let mut subgrid = None;
+        let mut top = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--stats" => stats = true,
+                "--heatmap" => heatmap_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--subgrid" => {
+                    let width = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    let height = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    subgrid = Some((width, height));
+                },
+                "--top" => top = Some(iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?),
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            stats,
+            heatmap_path,
+            subgrid,
+            top,
         })
     }
 }
-
@@ -1,10 +1,28 @@
+#![cfg_attr(all(feature = "no-panic-strict", not(test)), deny(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
 pub mod config;
+mod subgrid;
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt::Display;
 use std::fs;
+use std::mem;
+use std::time::{Duration, Instant};
 
-#[derive(Copy, Clone)]
-enum Direction {
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::bitvec::BitVec;
+use aoc_core::grid::{self, Estimate};
+use aoc_core::rng::{Rng, SeededRng};
+use aoc_core::validate::{self, Issue};
+
+pub use subgrid::{best_subgrid, SubgridSearch};
+
+/// Default upper bound for the memory used when solving a contraption (1 GiB)
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 30;
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
     Left,
     Right,
     Up,
@@ -12,6 +30,13 @@ enum Direction {
 }
 
 impl Direction {
+    const ALL: [Direction; 4] = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+
+    /// Position of the direction in `ALL`
+    fn index(self) -> usize {
+        self as usize
+    }
+
     fn apply(&self, x: i32, y: i32) -> (i32, i32) {
         match self {
             Direction::Left => (x-1, y),
@@ -70,6 +95,7 @@ impl Tile {
     }
 }
 
+#[derive(Clone)]
 struct LightedTile {
     tile: Tile,
     left: bool,
@@ -93,55 +119,97 @@ impl LightedTile {
     }
 
     fn energize(&mut self, direction: Direction) {
+        self.set_energized(direction, true);
+    }
+
+    fn set_energized(&mut self, direction: Direction, value: bool) {
         match direction {
             Direction::Left => {
-                self.left = true;
+                self.left = value;
             },
             Direction::Right => {
-                self.right = true;
+                self.right = value;
             },
             Direction::Up => {
-                self.up = true;
+                self.up = value;
             },
             Direction::Down => {
-                self.down = true;
+                self.down = value;
             },
         }
     }
 }
 
-struct Puzzle {
+#[derive(Clone)]
+pub struct Puzzle {
     rows: Vec<Vec<LightedTile>>,
+    /// Positions of the tiles energized by `energize` since the last reset, so that resetting
+    /// visits only these
+    energized: Vec<(usize, usize)>,
+}
+
+/// The directions in which each tile of a contraption is energized, four bits per tile
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+    states: BitVec,
 }
 
 impl Puzzle {
+    // Each tile can be entered from four directions, and each of these may end up on the stack
+    const STATES_PER_TILE: usize = 4;
+
+    fn bytes_per_tile() -> usize {
+        mem::size_of::<LightedTile>() + mem::size_of::<(usize, usize)>()
+            + Self::STATES_PER_TILE * mem::size_of::<(i32, i32, Direction)>()
+    }
+
+    /// Estimates the number of states and the memory needed for solving a contraption of the given
+    /// size. Returns `None` if the numbers are too large to be represented.
+    pub fn estimate(width: usize, height: usize) -> Option<Estimate> {
+        Estimate::for_grid(width, height, Self::STATES_PER_TILE, Self::bytes_per_tile())
+    }
+
     fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_input_with_limit(input, DEFAULT_MEMORY_LIMIT)
+    }
+
+    /// Parses the contraption, refusing to do so if solving it would need more than `limit` bytes.
+    pub fn from_input_with_limit(input: &str, limit: usize) -> Result<Self, Box<dyn Error>> {
+        let (w, h) = grid::dimensions(input);
+        grid::check_grid(w, h, Self::STATES_PER_TILE, Self::bytes_per_tile(), limit)?;
         let mut rows = Vec::new();
-        let line_len = input.lines().next().ok_or("Empty input")?.len();
+        let line_len = input.lines().next().map_or(0, str::len);
+        if line_len == 0 {
+            return Err("Empty input".into());
+        }
         for line in input.lines() {
             if line.len() != line_len {
                 return Err("Not all lines have the same length".into());
             }
-            let tiles: Vec<Tile> = line.chars().map(|c| Tile::try_from(c)).collect::<Result<_,_>>()?;
+            let tiles: Vec<Tile> = line.chars().map(Tile::try_from).collect::<Result<_,_>>()?;
             let lighted_tiles = tiles.into_iter()
                 .map(|tile| LightedTile { tile, left: false, right: false, up: false, down: false })
                 .collect();
             rows.push(lighted_tiles);
         }
         // let light = rows.iter().map(|row| row.iter().map(|_| Light::None).collect()).collect();
-        Ok(Self { rows })
+        Ok(Self { rows, energized: Vec::new() })
     }
 
     fn in_range(&self, x: i32, y: i32) -> bool {
         y >= 0 && y < self.rows.len() as i32 && x >= 0 && x < self.rows[0].len() as i32
     }
 
+    /// Energizes the tiles reached by a beam entering (x, y) in the given direction. Nothing is
+    /// energized if (x, y) is outside of the contraption.
     fn energize(&mut self, x: i32, y: i32, direction: Direction) {
-        let mut stack = vec![(x, y, direction)];
-        while !stack.is_empty() {
-            let (x, y, direction) = stack.pop().unwrap();
+        let mut stack: Vec<_> = [(x, y, direction)].into_iter().filter(|&(x, y, _)| self.in_range(x, y)).collect();
+        while let Some((x, y, direction)) = stack.pop() {
             let lighted_tile = &mut self.rows[y as usize][x as usize];
             if !lighted_tile.is_energized_in_direction(direction) {
+                if !lighted_tile.is_energized() {
+                    self.energized.push((x as usize, y as usize));
+                }
                 lighted_tile.energize(direction);
                 let out_directions = lighted_tile.tile.out_directions(direction);
                 for next_direction in out_directions {
@@ -159,9 +227,65 @@ impl Puzzle {
     }
 
     fn reset(&mut self) {
-        for row in &mut self.rows {
-            row.iter_mut().for_each(|t| { t.left = false; t.right = false; t.up = false; t.down = false });
+        for (x, y) in self.energized.drain(..) {
+            let t = &mut self.rows[y][x];
+            t.left = false;
+            t.right = false;
+            t.up = false;
+            t.down = false;
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// Saves the directions in which the tiles are currently energized.
+    pub fn snapshot(&self) -> Snapshot {
+        let w = self.width();
+        let mut states = BitVec::new(Direction::ALL.len() * w * self.rows.len());
+        for &(x, y) in &self.energized {
+            for direction in Direction::ALL {
+                if self.rows[y][x].is_energized_in_direction(direction) {
+                    states.set(Direction::ALL.len() * (y * w + x) + direction.index(), true);
+                }
+            }
         }
+        Snapshot { states }
+    }
+
+    /// Energizes the tiles exactly as when the snapshot was taken. Fails if the snapshot was taken
+    /// of a contraption of a different size.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), &'static str> {
+        let w = self.width();
+        if snapshot.states.len() != Direction::ALL.len() * w * self.rows.len() {
+            return Err("Snapshot does not fit the contraption");
+        }
+        self.reset();
+        for i in snapshot.states.ones() {
+            let tile = i / Direction::ALL.len();
+            let (x, y) = (tile % w, tile / w);
+            let lighted_tile = &mut self.rows[y][x];
+            if !lighted_tile.is_energized() {
+                self.energized.push((x, y));
+            }
+            lighted_tile.energize(Direction::ALL[i % Direction::ALL.len()]);
+        }
+        Ok(())
+    }
+
+    /// Adds a beam entering the contraption at the given entry point, energizing the tiles it
+    /// reaches in addition to those already energized, and returns the number of energized tiles.
+    /// Fails if the entry is not on the contraption.
+    pub fn add_beam(&mut self, entry: Entry) -> Result<usize, &'static str> {
+        let (Ok(x), Ok(y)) = (i32::try_from(entry.x), i32::try_from(entry.y)) else {
+            return Err("Entry point outside of the contraption");
+        };
+        if !self.in_range(x, y) {
+            return Err("Entry point outside of the contraption");
+        }
+        self.energize(x, y, entry.direction);
+        Ok(self.energized.len())
     }
 
     fn entry_points(&self) -> Vec<(i32, i32, Direction)> {
@@ -176,33 +300,432 @@ impl Puzzle {
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input(input)?;
-    puzzle.energize(0, 0, Direction::Right);
-    Ok(puzzle.energized_tiles())
+/// A beam entering a tile in a direction
+type State = (i32, i32, Direction);
+
+/// The beam entering the contraption at the top-left corner heading right, kept up to date while
+/// tiles are changed.
+///
+/// Each beam state remembers the states that were first reached from it. When a tile changes, only
+/// the states reached through that tile are invalidated; they are re-propagated from the states
+/// that are still lit instead of recomputing everything.
+pub struct Beam {
+    puzzle: Puzzle,
+    children: HashMap<State, Vec<State>>,
+}
+
+impl Beam {
+    /// The state of the beam entering the contraption
+    const ORIGIN: State = (0, 0, Direction::Right);
+
+    pub fn new(puzzle: Puzzle) -> Self {
+        let mut beam = Self { puzzle, children: HashMap::new() };
+        beam.propagate(vec![(None, Self::ORIGIN)]);
+        beam
+    }
+
+    pub fn energized_tiles(&self) -> usize {
+        self.puzzle.energized_tiles()
+    }
+
+    /// Replaces the tile at the given position, given as its character in the input, and updates
+    /// the energized tiles.
+    pub fn change_tile(&mut self, x: usize, y: usize, tile: char) -> Result<(), &'static str> {
+        let tile = Tile::try_from(tile)?;
+        let (x, y) = (x as i32, y as i32);
+        if !self.puzzle.in_range(x, y) {
+            return Err("Position outside of the contraption");
+        }
+        self.puzzle.rows[y as usize][x as usize].tile = tile;
+
+        // Forget the beams on the changed tile and everything first reached through them
+        let mut stack: Vec<State> = Direction::ALL.into_iter().map(|direction| (x, y, direction)).collect();
+        let mut invalidated = Vec::new();
+        while let Some(state) = stack.pop() {
+            if !self.is_lit(state) {
+                continue;
+            }
+            self.set_lit(state, false);
+            stack.extend(self.children.remove(&state).unwrap_or_default());
+            invalidated.push(state);
+        }
+
+        // Continue from every lit state leading to a forgotten one, and from the entry if it was
+        // forgotten. The lit states are still reached from the entry as their beams did not pass
+        // the changed tile.
+        let mut seeds: Vec<(Option<State>, State)> = Vec::new();
+        for state in invalidated {
+            if state == Self::ORIGIN {
+                seeds.push((None, state));
+            } else if let Some(predecessor) = self.predecessors(state).into_iter().find(|&p| self.is_lit(p)) {
+                seeds.push((Some(predecessor), state));
+            }
+        }
+        self.propagate(seeds);
+        Ok(())
+    }
+
+    fn is_lit(&self, (x, y, direction): State) -> bool {
+        self.puzzle.rows[y as usize][x as usize].is_energized_in_direction(direction)
+    }
+
+    fn set_lit(&mut self, (x, y, direction): State, value: bool) {
+        self.puzzle.rows[y as usize][x as usize].set_energized(direction, value);
+    }
+
+    fn successors(&self, (x, y, direction): State) -> Vec<State> {
+        self.puzzle.rows[y as usize][x as usize].tile.out_directions(direction).into_iter()
+            .map(|next_direction| {
+                let (xn, yn) = next_direction.apply(x, y);
+                (xn, yn, next_direction)
+            })
+            .filter(|&(xn, yn, _)| self.puzzle.in_range(xn, yn))
+            .collect()
+    }
+
+    fn predecessors(&self, state: State) -> Vec<State> {
+        let (x, y, direction) = state;
+        // The beam left the previous tile in the direction it has when entering this one
+        let (xp, yp) = match direction {
+            Direction::Left => (x + 1, y),
+            Direction::Right => (x - 1, y),
+            Direction::Up => (x, y + 1),
+            Direction::Down => (x, y - 1),
+        };
+        if !self.puzzle.in_range(xp, yp) {
+            return Vec::new();
+        }
+        Direction::ALL.into_iter()
+            .map(|in_direction| (xp, yp, in_direction))
+            .filter(|&p| self.successors(p).contains(&state))
+            .collect()
+    }
+
+    /// Lights the given states, each reached from the given parent, and everything reachable from
+    /// them that is not lit yet.
+    fn propagate(&mut self, mut stack: Vec<(Option<State>, State)>) {
+        while let Some((parent, state)) = stack.pop() {
+            if self.is_lit(state) {
+                continue;
+            }
+            self.set_lit(state, true);
+            if let Some(parent) = parent {
+                self.children.entry(parent).or_default().push(state);
+            }
+            stack.extend(self.successors(state).into_iter().map(|next| (Some(state), next)));
+        }
+    }
+}
+
+/// Estimates the number of states and the memory needed for solving the given contraption
+/// without parsing it.
+pub fn estimate(input: &str) -> Result<Estimate, &'static str> {
+    let (w, h) = grid::dimensions(input);
+    Puzzle::estimate(w, h).ok_or("Contraption too large")
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// How to search for the entry point that energizes the most tiles
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// Try every entry point
+    Exhaustive,
+    /// Hill-climb along the border from `restarts` (at least one) random entry points, moving to a better entry
+    /// point nearby as long as there is one. Contraptions with at most `exhaustive_below` entry
+    /// points are searched exhaustively instead.
+    Heuristic { seed: u64, restarts: usize, exhaustive_below: usize },
+}
+
+/// A beam entering the contraption at a border tile
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Entry {
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+}
+
+/// The best entry point found by a search
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EntrySearch {
+    pub entry: Entry,
+    /// Number of tiles energized from the entry, which is a lower bound for the maximum unless
+    /// `exact` is set
+    pub energized: usize,
+    /// Whether every entry point was tried, so that `energized` is the maximum
+    pub exact: bool,
+    /// Number of entry points tried
+    pub evaluations: usize,
+}
+
+impl Puzzle {
+    fn energized_from(&mut self, (x, y, direction): (i32, i32, Direction)) -> usize {
+        self.energize(x, y, direction);
+        let energized = self.energized.len();
+        self.reset();
+        energized
+    }
+
+    fn search_entry(&mut self, strategy: Strategy) -> Result<EntrySearch, &'static str> {
+        let entries = self.entry_points();
+        let n = entries.len();
+        if n == 0 {
+            return Err("Contraption has no entry points");
+        }
+        let mut energized: Vec<Option<usize>> = vec![None; n];
+        let mut evaluate = |puzzle: &mut Self, i: usize| *energized[i].get_or_insert_with(|| puzzle.energized_from(entries[i]));
+        let (best, exact) = match strategy {
+            Strategy::Heuristic { seed, restarts, exhaustive_below } if n > exhaustive_below => {
+                let mut rng = SeededRng::new(seed);
+                // Besides the adjacent entry points, look a bit further to get over small dips
+                let stride = (n / 64).max(2);
+                let mut best = 0;
+                for restart in 0..restarts.max(1) {
+                    let mut i = rng.index(n);
+                    loop {
+                        let current = evaluate(self, i);
+                        let neighbors = [1, n - 1, stride % n, n - stride % n].map(|offset| (i + offset) % n);
+                        // The last of the best neighbors
+                        let next = neighbors.into_iter()
+                            .fold(neighbors[0], |best, j| if evaluate(self, j) >= evaluate(self, best) { j } else { best });
+                        if evaluate(self, next) <= current {
+                            break;
+                        }
+                        i = next;
+                    }
+                    if restart == 0 || evaluate(self, i) > evaluate(self, best) {
+                        best = i;
+                    }
+                }
+                (best, false)
+            },
+            // The first of the best entry points
+            _ => ((0..n).fold(0, |best, i| if evaluate(self, i) > evaluate(self, best) { i } else { best }), true),
+        };
+        let (x, y, direction) = entries[best];
+        Ok(EntrySearch {
+            entry: Entry { x: x as usize, y: y as usize, direction },
+            energized: evaluate(self, best),
+            exact,
+            evaluations: energized.iter().flatten().count(),
+        })
+    }
+}
+
+/// The number of tiles energized from an entry point and how long it took to compute it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EntryResult {
+    pub entry: Entry,
+    pub energized: usize,
+    pub duration: Duration,
+}
+
+impl Puzzle {
+    /// Energizes the contraption from every entry point, in the order of `entry_points`.
+    fn entry_results(&mut self) -> Vec<EntryResult> {
+        self.entry_points()
+            .into_iter()
+            .map(|(x, y, direction)| {
+                let start = Instant::now();
+                let energized = self.energized_from((x, y, direction));
+                let entry = Entry { x: x as usize, y: y as usize, direction };
+                EntryResult { entry, energized, duration: start.elapsed() }
+            })
+            .collect()
+    }
+
+    fn top_entries(&mut self, k: usize) -> Vec<EntryResult> {
+        let mut results = self.entry_results();
+        results.sort_by_key(|result| std::cmp::Reverse(result.energized));
+        results.truncate(k);
+        results
+    }
+}
+
+/// Entry points from which exactly the same tiles are energized
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryClass {
+    pub entries: Vec<Entry>,
+    pub energized: usize,
+}
+
+impl Puzzle {
+    fn energized_set(&self) -> BitVec {
+        let w = self.width();
+        let mut set = BitVec::new(w * self.rows.len());
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                set.set(y * w + x, tile.is_energized());
+            }
+        }
+        set
+    }
+
+    fn entry_classes(&mut self) -> Vec<EntryClass> {
+        let mut classes: Vec<EntryClass> = Vec::new();
+        let mut class_of_set: HashMap<BitVec, usize> = HashMap::new();
+        for (x, y, direction) in self.entry_points() {
+            self.energize(x, y, direction);
+            let set = self.energized_set();
+            self.reset();
+            let entry = Entry { x: x as usize, y: y as usize, direction };
+            match class_of_set.get(&set) {
+                Some(&i) => classes[i].entries.push(entry),
+                None => {
+                    class_of_set.insert(set.clone(), classes.len());
+                    classes.push(EntryClass { entries: vec![entry], energized: set.count_ones() });
+                },
+            }
+        }
+        classes
+    }
+}
+
+impl Puzzle {
+    /// For each tile, row by row, the number of directions in which it is currently energized
+    fn crossings(&self) -> Vec<Vec<u8>> {
+        self.rows.iter()
+            .map(|row| row.iter().map(|t| Direction::ALL.iter().filter(|&&d| t.is_energized_in_direction(d)).count() as u8).collect())
+            .collect()
+    }
+
+    fn energized_counts(&mut self) -> Vec<Vec<usize>> {
+        let mut counts: Vec<Vec<usize>> = self.rows.iter().map(|row| vec![0; row.len()]).collect();
+        for (x, y, direction) in self.entry_points() {
+            self.energize(x, y, direction);
+            for (count_row, row) in counts.iter_mut().zip(&self.rows) {
+                for (count, tile) in count_row.iter_mut().zip(row) {
+                    *count += usize::from(tile.is_energized());
+                }
+            }
+            self.reset();
+        }
+        counts
+    }
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
+/// Counts, for each tile row by row, the distinct beams crossing it when entering at `entry`, that
+/// is, the number of directions (0 to 4) in which it is passed. Fails if the entry is not on the
+/// contraption.
+pub fn crossings(input: &str, entry: Entry) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
     let mut puzzle = Puzzle::from_input(input)?;
-    let mut max_energized = 0;
-    for (x, y, direction) in puzzle.entry_points() {
-        puzzle.energize(x, y, direction);
-        max_energized = max_energized.max(puzzle.energized_tiles());
-        puzzle.reset();
+    let (x, y) = (i32::try_from(entry.x)?, i32::try_from(entry.y)?);
+    if !puzzle.in_range(x, y) {
+        return Err("Entry point outside of the contraption".into());
+    }
+    puzzle.energize(x, y, entry.direction);
+    Ok(puzzle.crossings())
+}
+
+/// Counts, for each tile row by row, from how many entry points it is energized.
+pub fn energized_counts(input: &str) -> Result<Vec<Vec<usize>>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.energized_counts())
+}
+
+/// Writes a matrix as comma-separated values, one line per row.
+pub fn matrix_to_csv<T: Display>(matrix: &[Vec<T>]) -> String {
+    matrix.iter()
+        .map(|row| row.iter().map(T::to_string).collect::<Vec<_>>().join(",") + "\n")
+        .collect()
+}
+
+/// Groups the entry points by the set of tiles they energize. The classes are ordered by their
+/// first entry point, where the entry points on the left border come first, followed by those on
+/// the right, top and bottom border.
+pub fn entry_classes(input: &str) -> Result<Vec<EntryClass>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.entry_classes())
+}
+
+/// Searches for the entry point from which the most tiles are energized.
+pub fn search_entry(input: &str, strategy: Strategy) -> Result<EntrySearch, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.search_entry(strategy)?)
+}
+
+/// The `k` entry points from which the most tiles are energized, best first, each with the time it
+/// took to energize the contraption from it. Entry points energizing equally many tiles are in the
+/// order of `entry_classes`.
+pub fn top_entries(input: &str, k: usize) -> Result<Vec<EntryResult>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.top_entries(k))
+}
+
+/// Both parts, each energizing its own copy of the same contraption
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        let mut puzzle = puzzle.clone();
+        puzzle.energize(0, 0, Direction::Right);
+        Ok(puzzle.energized_tiles().into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.clone().search_entry(Strategy::Exhaustive)?.energized.into())
+    }
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines, invalid tiles and contraptions that exceed the default memory limit.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = validate::check_grid(input, |c| Tile::try_from(c).is_ok());
+    let (w, h) = grid::dimensions(input);
+    if let Err(e) = grid::check_grid(w, h, Puzzle::STATES_PER_TILE, Puzzle::bytes_per_tile(), DEFAULT_MEMORY_LIMIT) {
+        issues.push(Issue::new(e));
     }
-    Ok(max_energized)
+    issues
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let puzzle = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&puzzle)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let mut puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&puzzle)?);
+
+    if config.stats {
+        let mut sizes: Vec<usize> = puzzle.entry_classes().iter().map(|class| class.entries.len()).collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        let num_entries: usize = sizes.iter().sum();
+        let sizes: Vec<String> = sizes.iter().map(usize::to_string).collect();
+        println!("{num_entries} entry points in {} classes of sizes {}", sizes.len(), sizes.join(", "));
+    }
+    if let Some(k) = config.top {
+        for (rank, result) in puzzle.top_entries(k).iter().enumerate() {
+            let Entry { x, y, direction } = result.entry;
+            println!(
+                "{}. {} tiles energized when entering ({x}, {y}) heading {direction:?}, in {:?}",
+                rank + 1, result.energized, result.duration
+            );
+        }
+    }
+    if let Some((width, height)) = config.subgrid {
+        let search = puzzle.best_subgrid(width, height)?;
+        let Entry { x, y, direction } = search.entry;
+        println!(
+            "Best of {} subgrids of {width} × {height} tiles at ({}, {}): {} tiles energized when entering ({x}, {y}) heading {direction:?}",
+            search.subgrids, search.x, search.y, search.energized
+        );
+    }
+    if let Some(heatmap_path) = config.heatmap_path {
+        println!("Writing the number of entry points energizing each tile of part 2 to {heatmap_path}");
+        fs::write(heatmap_path, matrix_to_csv(&puzzle.energized_counts()))?;
+    }
 
     Ok(())
 }
@@ -226,15 +749,213 @@ mod tests {
 
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 46);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = solve_part2(EXAMPLE1.trim())?;
         assert_eq!(result, 51);
         Ok(())
     }
+
+    #[test]
+    fn example1_top_entries() -> Result<(), Box<dyn Error>> {
+        let top = top_entries(EXAMPLE1.trim(), 3)?;
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].entry, Entry { x: 3, y: 0, direction: Direction::Down });
+        assert_eq!(top[0].energized, 51);
+        assert!(top.windows(2).all(|pair| pair[0].energized >= pair[1].energized));
+        // All entry points, ranked consistently with the classes they are in
+        let all = top_entries(EXAMPLE1.trim(), usize::MAX)?;
+        let classes = entry_classes(EXAMPLE1.trim())?;
+        assert_eq!(all.len(), classes.iter().map(|class| class.entries.len()).sum());
+        for result in &all {
+            let class = classes.iter().find(|class| class.entries.contains(&result.entry)).ok_or("Entry in no class")?;
+            assert_eq!(result.energized, class.energized);
+        }
+        assert!(top_entries(EXAMPLE1.trim(), 0)?.is_empty());
+        Ok(())
+    }
+
+    /// Generates a contraption of the given size with about one in `density` tiles not empty.
+    pub(crate) fn random_contraption(width: usize, height: usize, density: u64, seed: u64) -> String {
+        const TILES: [char; 4] = ['/', '\\', '|', '-'];
+        let mut rng = SeededRng::new(seed);
+        (0..height).map(|_| (0..width).map(|_| {
+            let r = rng.next_u64();
+            if r.is_multiple_of(density) { TILES[(r / density % 4) as usize] } else { '.' }
+        }).collect::<String>()).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn entry_search_strategies() -> Result<(), Box<dyn Error>> {
+        let exhaustive = search_entry(EXAMPLE1.trim(), Strategy::Exhaustive)?;
+        assert_eq!(exhaustive.entry, Entry { x: 3, y: 0, direction: Direction::Down });
+        assert_eq!((exhaustive.energized, exhaustive.exact, exhaustive.evaluations), (51, true, 40));
+        let fallback = Strategy::Heuristic { seed: 1, restarts: 3, exhaustive_below: 40 };
+        assert_eq!(search_entry(EXAMPLE1.trim(), fallback)?, exhaustive);
+        let contraption = random_contraption(40, 40, 8, 7);
+        let maximum = search_entry(&contraption, Strategy::Exhaustive)?.energized;
+        for seed in 0..5 {
+            let heuristic = search_entry(&contraption, Strategy::Heuristic { seed, restarts: 4, exhaustive_below: 0 })?;
+            assert!(!heuristic.exact && heuristic.energized <= maximum);
+            assert!(heuristic.evaluations < 160);
+            let mut puzzle = Puzzle::from_input(&contraption)?;
+            let Entry { x, y, direction } = heuristic.entry;
+            assert_eq!(puzzle.energized_from((x as i32, y as i32, direction)), heuristic.energized);
+        }
+        Ok(())
+    }
+
+    /// Compares the strategies on a 400 × 400 contraption. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_entry_search() -> Result<(), Box<dyn Error>> {
+        let contraption = random_contraption(400, 400, 10, 42);
+        let start = std::time::Instant::now();
+        let exhaustive = search_entry(&contraption, Strategy::Exhaustive)?;
+        println!("Exhaustive: {} tiles in {:?}", exhaustive.energized, start.elapsed());
+        let start = std::time::Instant::now();
+        let heuristic = search_entry(&contraption, Strategy::Heuristic { seed: 42, restarts: 10, exhaustive_below: 0 })?;
+        println!("Heuristic: {} tiles after {} evaluations in {:?}", heuristic.energized, heuristic.evaluations, start.elapsed());
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_changes_match_full_recomputation() -> Result<(), Box<dyn Error>> {
+        const TILES: [char; 5] = ['.', '/', '\\', '|', '-'];
+        let mut grid: Vec<Vec<char>> = EXAMPLE1.trim().lines().map(|line| line.chars().collect()).collect();
+        let mut beam = Beam::new(Puzzle::from_input(EXAMPLE1.trim())?);
+        assert_eq!(beam.energized_tiles(), 46);
+        // Change each tile in turn, keeping the previous changes
+        for step in 0..3 {
+            for y in 0..grid.len() {
+                for x in 0..grid[y].len() {
+                    let old = TILES.iter().position(|&t| t == grid[y][x]).unwrap();
+                    grid[y][x] = TILES[(old + step + 1) % TILES.len()];
+                    beam.change_tile(x, y, grid[y][x])?;
+                    let input: Vec<String> = grid.iter().map(|row| row.iter().collect()).collect();
+                    let expected = solve_part1(&input.join("\n"))?;
+                    assert_eq!(expected, beam.energized_tiles(), "after changing ({x}, {y}) in step {step}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_changes_match_full_recomputation() -> Result<(), Box<dyn Error>> {
+        const TILES: [char; 5] = ['.', '/', '\\', '|', '-'];
+        for seed in 0..50 {
+            let (width, height) = (3 + seed as usize % 4, 3 + seed as usize / 4 % 4);
+            let input = random_contraption(width, height, 2, seed);
+            let mut grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+            let mut beam = Beam::new(Puzzle::from_input(&input)?);
+            let mut rng = SeededRng::new(seed);
+            for change in 0..30 {
+                let (x, y) = (rng.index(width), rng.index(height));
+                grid[y][x] = TILES[rng.index(TILES.len())];
+                beam.change_tile(x, y, grid[y][x])?;
+                let input: Vec<String> = grid.iter().map(|row| row.iter().collect()).collect();
+                let expected = solve_part1(&input.join("\n"))?;
+                assert_eq!(expected, beam.energized_tiles(), "seed {seed}, change {change} at ({x}, {y}):\n{}", input.join("\n"));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn change_tile_errors() -> Result<(), Box<dyn Error>> {
+        let mut beam = Beam::new(Puzzle::from_input(EXAMPLE1.trim())?);
+        assert!(beam.change_tile(10, 0, '.').is_err());
+        assert!(beam.change_tile(0, 0, 'x').is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn entry_classes_of_contraptions() -> Result<(), Box<dyn Error>> {
+        // In an empty contraption, the beams entering a row or column at either end are equivalent
+        let classes = entry_classes("..\n..")?;
+        assert_eq!(classes.len(), 4);
+        assert!(classes.iter().all(|class| class.entries.len() == 2 && class.energized == 2));
+        assert_eq!(classes[0].entries, vec![
+            Entry { x: 0, y: 0, direction: Direction::Right },
+            Entry { x: 1, y: 0, direction: Direction::Left },
+        ]);
+        let classes = entry_classes(EXAMPLE1.trim())?;
+        assert_eq!(classes.iter().map(|class| class.entries.len()).sum::<usize>(), 40);
+        let mut puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        for class in &classes {
+            for entry in &class.entries {
+                assert_eq!(puzzle.energized_from((entry.x as i32, entry.y as i32, entry.direction)), class.energized);
+            }
+        }
+        assert_eq!(classes.iter().map(|class| class.energized).max(), Some(51));
+        Ok(())
+    }
+
+    #[test]
+    fn beam_statistics() -> Result<(), Box<dyn Error>> {
+        let entry = Entry { x: 0, y: 0, direction: Direction::Right };
+        let beams = crossings(EXAMPLE1.trim(), entry)?;
+        assert_eq!(beams.iter().flatten().filter(|&&c| c > 0).count(), 46);
+        // The splitter at the top is entered from the left and again by the beam coming back up
+        assert_eq!(beams[0][..3], [1, 2, 1]);
+        assert_eq!(beams[6], [0, 1, 0, 0, 1, 2, 2, 1, 0, 0]);
+        assert!(crossings(EXAMPLE1.trim(), Entry { x: 10, y: 0, direction: Direction::Left }).is_err());
+
+        // In an empty contraption, each tile is energized by the four beams along its row and column
+        let counts = energized_counts("...\n...")?;
+        assert_eq!(counts, vec![vec![4; 3]; 2]);
+        assert_eq!(matrix_to_csv(&counts), "4,4,4\n4,4,4\n");
+
+        let counts = energized_counts(EXAMPLE1.trim())?;
+        let classes = entry_classes(EXAMPLE1.trim())?;
+        let total: usize = classes.iter().map(|class| class.entries.len() * class.energized).sum();
+        assert_eq!(counts.iter().flatten().sum::<usize>(), total);
+        assert!(counts.iter().flatten().all(|&count| count <= 40));
+        Ok(())
+    }
+
+    #[test]
+    fn example1_estimate() -> Result<(), Box<dyn Error>> {
+        let estimate = estimate(EXAMPLE1.trim())?;
+        assert_eq!(estimate.states, 400);
+        assert_eq!(estimate.bytes, 100 * Puzzle::bytes_per_tile());
+        Ok(())
+    }
+
+    #[test]
+    fn example1_memory_limit() {
+        let bytes = estimate(EXAMPLE1.trim()).unwrap().bytes;
+        assert!(Puzzle::from_input_with_limit(EXAMPLE1.trim(), bytes).is_ok());
+        let error = Puzzle::from_input_with_limit(EXAMPLE1.trim(), bytes - 1).err().unwrap();
+        assert!(error.is::<grid::MemoryLimitExceeded>());
+    }
+
+    #[test]
+    fn snapshots() -> Result<(), Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let empty = puzzle.snapshot();
+        assert_eq!(puzzle.add_beam(Entry { x: 0, y: 0, direction: Direction::Right })?, 46);
+        let saved = puzzle.snapshot();
+        let crossings = puzzle.crossings();
+        // Branch off with another beam and go back
+        assert!(puzzle.add_beam(Entry { x: 3, y: 0, direction: Direction::Down })? > 51);
+        puzzle.restore(&saved)?;
+        assert_eq!(puzzle.energized_tiles(), 46);
+        assert_eq!(puzzle.crossings(), crossings);
+        assert_eq!(puzzle.snapshot(), saved);
+        puzzle.restore(&empty)?;
+        assert_eq!(puzzle.energized_tiles(), 0);
+        assert_eq!(puzzle.add_beam(Entry { x: 3, y: 0, direction: Direction::Down })?, 51);
+        assert!(puzzle.add_beam(Entry { x: 10, y: 0, direction: Direction::Down }).is_err());
+        let other = Puzzle::from_input(".|.\n...")?;
+        assert!(puzzle.restore(&other.snapshot()).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,258 @@
+//! The subgrid of a given size in which a beam entering from its border energizes the most tiles.
+//!
+//! Each subgrid is treated as a contraption of its own, so beams leaving it are lost. Beams move
+//! along segments from one non-empty tile to the next, which are computed once for the whole
+//! contraption and shared by all subgrids.
+
+use std::error::Error;
+
+use aoc_core::parallel;
+
+use crate::{Direction, Entry, Puzzle, Tile};
+
+/// The best subgrid found by `best_subgrid`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SubgridSearch {
+    /// Column of the left border of the subgrid
+    pub x: usize,
+    /// Row of the top border of the subgrid
+    pub y: usize,
+    /// The entry point on the border of the subgrid, in coordinates of the whole contraption
+    pub entry: Entry,
+    /// Number of tiles of the subgrid energized from the entry
+    pub energized: usize,
+    /// Number of subgrids evaluated
+    pub subgrids: usize,
+}
+
+/// For each tile and direction, the number of steps to the next non-empty tile in that direction,
+/// or to the first position outside of the contraption
+struct Segments {
+    width: usize,
+    tiles: Vec<Tile>,
+    steps: Vec<[usize; 4]>,
+}
+
+impl Segments {
+    fn new(puzzle: &Puzzle) -> Self {
+        let width = puzzle.width();
+        let height = puzzle.rows.len();
+        let tiles: Vec<Tile> = puzzle.rows.iter().flatten().map(|t| t.tile).collect();
+        let mut steps = vec![[0; 4]; tiles.len()];
+        let stops = |i: usize| !matches!(tiles[i], Tile::Empty);
+        // Sweep each line against the direction, so that the tile ahead is already done
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                steps[i][Direction::Left.index()] = if x == 0 || stops(i - 1) { 1 } else { steps[i - 1][Direction::Left.index()] + 1 };
+                steps[i][Direction::Up.index()] = if y == 0 || stops(i - width) { 1 } else { steps[i - width][Direction::Up.index()] + 1 };
+            }
+        }
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                let i = y * width + x;
+                steps[i][Direction::Right.index()] = if x + 1 == width || stops(i + 1) { 1 } else { steps[i + 1][Direction::Right.index()] + 1 };
+                steps[i][Direction::Down.index()] = if y + 1 == height || stops(i + width) { 1 } else { steps[i + width][Direction::Down.index()] + 1 };
+            }
+        }
+        Self { width, tiles, steps }
+    }
+}
+
+/// Follows beams within one subgrid, marking tiles and states with the number of the current
+/// evaluation so that nothing needs to be cleared in between
+struct Walker<'a> {
+    segments: &'a Segments,
+    x0: usize,
+    y0: usize,
+    width: usize,
+    height: usize,
+    generation: u32,
+    energized_in: Vec<u32>,
+    state_in: Vec<u32>,
+    count: usize,
+    stack: Vec<(usize, usize, Direction)>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(segments: &'a Segments, width: usize, height: usize) -> Self {
+        Self {
+            segments, x0: 0, y0: 0, width, height, generation: 0,
+            energized_in: vec![0; width * height],
+            state_in: vec![0; 4 * width * height],
+            count: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    fn energize(&mut self, x: usize, y: usize) {
+        let local = (y - self.y0) * self.width + x - self.x0;
+        if self.energized_in[local] != self.generation {
+            self.energized_in[local] = self.generation;
+            self.count += 1;
+        }
+    }
+
+    /// Number of tiles of the subgrid at (x0, y0) energized by a beam entering (x, y) in the given
+    /// direction
+    fn energized_from(&mut self, x: usize, y: usize, direction: Direction) -> usize {
+        self.generation += 1;
+        self.count = 0;
+        self.stack.push((x, y, direction));
+        while let Some((x, y, direction)) = self.stack.pop() {
+            self.energize(x, y);
+            let i = y * self.segments.width + x;
+            let tile = self.segments.tiles[i];
+            if !matches!(tile, Tile::Empty) {
+                let state = 4 * ((y - self.y0) * self.width + x - self.x0) + direction.index();
+                if self.state_in[state] == self.generation {
+                    continue;
+                }
+                self.state_in[state] = self.generation;
+            }
+            for out in tile.out_directions(direction) {
+                self.follow(x, y, out);
+            }
+        }
+        self.count
+    }
+
+    /// Energizes the tiles after (x, y) in the given direction up to the next non-empty tile, which
+    /// is pushed on the stack, or up to the border of the subgrid.
+    fn follow(&mut self, x: usize, y: usize, direction: Direction) {
+        let steps = self.segments.steps[y * self.segments.width + x][direction.index()];
+        // Steps until leaving the subgrid
+        let inside = match direction {
+            Direction::Left => x - self.x0,
+            Direction::Right => self.x0 + self.width - 1 - x,
+            Direction::Up => y - self.y0,
+            Direction::Down => self.y0 + self.height - 1 - y,
+        };
+        let (x, y) = (x as i64, y as i64);
+        let (dx, dy) = match direction {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        };
+        for k in 1..steps.min(inside + 1) {
+            self.energize((x + dx * k as i64) as usize, (y + dy * k as i64) as usize);
+        }
+        if steps <= inside {
+            let k = steps as i64;
+            self.stack.push(((x + dx * k) as usize, (y + dy * k) as usize, direction));
+        }
+    }
+
+    /// The entry point on the border of the subgrid at (x0, y0) that energizes the most tiles, the
+    /// first of them in the order of `Puzzle::entry_points`
+    fn best_entry(&mut self, x0: usize, y0: usize) -> (Entry, usize) {
+        (self.x0, self.y0) = (x0, y0);
+        let (w, h) = (self.width, self.height);
+        let entries = (0..h).map(|y| (x0, y0 + y, Direction::Right))
+            .chain((0..h).map(|y| (x0 + w - 1, y0 + y, Direction::Left)))
+            .chain((0..w).map(|x| (x0 + x, y0, Direction::Down)))
+            .chain((0..w).map(|x| (x0 + x, y0 + h - 1, Direction::Up)));
+        let mut best = (Entry { x: x0, y: y0, direction: Direction::Right }, 0);
+        for (x, y, direction) in entries {
+            let energized = self.energized_from(x, y, direction);
+            if energized > best.1 {
+                best = (Entry { x, y, direction }, energized);
+            }
+        }
+        best
+    }
+}
+
+impl Puzzle {
+    /// Evaluates every subgrid of the given size, row by row in parallel, and returns the first
+    /// best one in reading order of the top-left corners.
+    pub(crate) fn best_subgrid(&self, width: usize, height: usize) -> Result<SubgridSearch, &'static str> {
+        if width == 0 || height == 0 || width > self.width() || height > self.rows.len() {
+            return Err("Subgrid does not fit the contraption");
+        }
+        let segments = Segments::new(self);
+        let num_columns = self.width() - width + 1;
+        let tops: Vec<usize> = (0..=self.rows.len() - height).collect();
+        let best_in_rows = parallel::map(&tops, |&y0| {
+            let mut walker = Walker::new(&segments, width, height);
+            (0..num_columns)
+                .map(|x0| (x0, walker.best_entry(x0, y0)))
+                .fold(None, |best: Option<(usize, (Entry, usize))>, candidate| match best {
+                    Some(best) if best.1.1 >= candidate.1.1 => Some(best),
+                    _ => Some(candidate),
+                })
+        });
+        let (x, y, (entry, energized)) = tops.iter().zip(best_in_rows)
+            .filter_map(|(&y, best)| best.map(|(x, best)| (x, y, best)))
+            .fold(None, |best: Option<(usize, usize, (Entry, usize))>, candidate| match best {
+                Some(best) if best.2.1 >= candidate.2.1 => Some(best),
+                _ => Some(candidate),
+            })
+            .ok_or("Subgrid does not fit the contraption")?;
+        Ok(SubgridSearch { x, y, entry, energized, subgrids: tops.len() * num_columns })
+    }
+}
+
+/// Finds the subgrid of `width` × `height` tiles in which a beam entering from its border, and
+/// staying in it, energizes the most tiles. Fails if the subgrid is empty or larger than the
+/// contraption.
+pub fn best_subgrid(input: &str, width: usize, height: usize) -> Result<SubgridSearch, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input)?.best_subgrid(width, height)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{search_entry, Strategy};
+    use crate::tests::random_contraption;
+
+    const EXAMPLE1: &str = r"
+.|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....
+";
+
+    #[test]
+    fn whole_contraption_is_part2() -> Result<(), Box<dyn Error>> {
+        let search = best_subgrid(EXAMPLE1.trim(), 10, 10)?;
+        assert_eq!((search.x, search.y, search.energized, search.subgrids), (0, 0, 51, 1));
+        assert_eq!(search.entry, Entry { x: 3, y: 0, direction: Direction::Down });
+        assert!(best_subgrid(EXAMPLE1.trim(), 11, 1).is_err());
+        assert!(best_subgrid(EXAMPLE1.trim(), 0, 1).is_err());
+        // A single tile is always energized
+        assert_eq!(best_subgrid(EXAMPLE1.trim(), 1, 1)?.energized, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_cut_out_contraptions() -> Result<(), Box<dyn Error>> {
+        for seed in 0..4 {
+            let contraption = random_contraption(9, 7, 4, seed);
+            let lines: Vec<&str> = contraption.lines().collect();
+            for (width, height) in [(3, 4), (5, 2), (9, 7), (6, 6)] {
+                let search = best_subgrid(&contraption, width, height)?;
+                let mut expected: Option<(usize, usize, usize)> = None;
+                for y in 0..=7 - height {
+                    for x in 0..=9 - width {
+                        let cut: Vec<&str> = lines[y..y + height].iter().map(|line| &line[x..x + width]).collect();
+                        let energized = search_entry(&cut.join("\n"), Strategy::Exhaustive)?.energized;
+                        if expected.is_none_or(|(_, _, best)| energized > best) {
+                            expected = Some((x, y, energized));
+                        }
+                    }
+                }
+                assert_eq!(Some((search.x, search.y, search.energized)), expected, "seed {seed}, {width} × {height}");
+                assert_eq!(search.subgrids, (10 - width) * (8 - height));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,45 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    pub mask_path: Option<String>,
+    /// Solve the grid of part 1 for both crucibles and compare their routes instead
+    pub compare: bool,
+    /// File to which the order in which the search of part 1 settles states is written
+    pub record_path: Option<String>,
+    /// Instead of solving, play back the search recorded in this file
+    pub play_path: Option<String>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day17 <file for part 1> <file for part 2> [<mask file>] [--compare] [--record <file> | --play <file>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut mask_path = None;
+        let mut compare = false;
+        let mut record_path = None;
+        let mut play_path = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--compare" => compare = true,
+                "--record" => record_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--play" => play_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                _ if arg.starts_with("--") || mask_path.is_some() => return Err(USAGE),
+                _ => mask_path = Some(arg.clone()),
+            }
+        }
+        if record_path.is_some() && play_path.is_some() {
+            return Err(USAGE);
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            mask_path,
+            compare,
+            record_path,
+            play_path,
         })
     }
 }
-
@@ -1,31 +1,28 @@
+#![cfg_attr(all(feature = "no-panic-strict", not(test)), deny(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
 pub mod config;
+mod replay;
 
-use core::panic;
-use std::collections::BinaryHeap;
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::search::{self, DenseSearch};
+use aoc_core::validate::{self, Issue};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::error::Error;
+use std::fmt::Display;
 use std::fs;
+use std::time::Duration;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    x: usize,
-    y: usize,
-    cost: usize,
-    vertical: bool, // whether node (x, y) was entered vertically
-}
+pub use replay::{Replay, ReplayFrames, SettledState};
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.x.cmp(&other.x))
-            .then_with(|| self.y.cmp(&other.y))
-            .then_with(|| self.vertical.cmp(&other.vertical))
-    }
-}
+const OVERFLOW: &str = "Heat loss overflow";
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// A cell together with whether it was entered vertically
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct Node {
+    x: usize,
+    y: usize,
+    vertical: bool,
 }
 
 #[derive(Debug)]
@@ -35,8 +32,10 @@ struct Edge {
     cost: usize,
 }
 
-struct Puzzle {
-    rows: Vec<Vec<u8>>,
+#[derive(Clone)]
+pub struct Puzzle {
+    rows: Vec<Vec<u32>>,
+    blocked: Vec<Vec<bool>>, // cells that must not be entered
     w: usize,
     h: usize,
     min_move: usize,
@@ -44,7 +43,7 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn from_input(input: &str, min_move: usize, max_move: usize) -> Result<Self, Box<dyn Error>> {
+    pub fn from_input(input: &str, min_move: usize, max_move: usize) -> Result<Self, Box<dyn Error>> {
         let mut rows = Vec::new();
         let line_len = input.lines().next().ok_or("Empty input")?.len();
         for line in input.lines() {
@@ -52,14 +51,61 @@ impl Puzzle {
                 return Err("Not all lines have the same length".into());
             }
             let row = line.chars()
-                .map(|c| c.to_digit(10)).collect::<Option<Vec<_>>>().ok_or("Could not parse digit")?.into_iter()
-                .map(|i| i as u8).collect();
+                .map(|c| c.to_digit(10)).collect::<Option<Vec<_>>>().ok_or("Could not parse digit")?;
             rows.push(row);
         }
-        Ok(Self { w: line_len, h: rows.len(), rows, min_move, max_move })
+        Ok(Self::from_costs(rows, min_move, max_move)?)
+    }
+
+    /// Creates a puzzle from the heat loss of each cell, which may exceed a single digit.
+    pub fn from_costs(rows: Vec<Vec<u32>>, min_move: usize, max_move: usize) -> Result<Self, &'static str> {
+        let w = rows.first().ok_or("Empty input")?.len();
+        if w == 0 {
+            return Err("Empty input");
+        }
+        if rows.iter().any(|row| row.len() != w) {
+            return Err("Not all lines have the same length");
+        }
+        let blocked = vec![vec![false; w]; rows.len()];
+        Ok(Self { w, h: rows.len(), rows, blocked, min_move, max_move })
+    }
+
+    /// Marks the cell at (x, y) as impassable.
+    pub fn block_cell(&mut self, x: usize, y: usize) -> Result<(), &'static str> {
+        if x >= self.w || y >= self.h {
+            return Err("Cell out of bounds");
+        }
+        self.blocked[y][x] = true;
+        Ok(())
+    }
+
+    /// Marks cells as impassable according to a mask with the same dimensions as the map, where
+    /// '#' denotes an impassable cell and '.' a passable one.
+    pub fn apply_mask(&mut self, mask: &str) -> Result<(), Box<dyn Error>> {
+        if mask.lines().count() != self.h {
+            return Err("Mask does not have the same height as the map".into());
+        }
+        for (y, line) in mask.lines().enumerate() {
+            if line.len() != self.w {
+                return Err("Mask does not have the same width as the map".into());
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => self.blocked[y][x] = true,
+                    '.' => (),
+                    _ => return Err("Invalid character in mask".into()),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the heat loss of the cell at (x, y) to `cost`, failing instead of wrapping around.
+    fn add_cost(&self, cost: usize, x: usize, y: usize) -> Result<usize, &'static str> {
+        cost.checked_add(self.rows[y][x] as usize).ok_or(OVERFLOW)
     }
 
-    fn edges_h(&self, x: usize, y: usize) -> Vec<Edge> {
+    fn edges_h(&self, x: usize, y: usize) -> Result<Vec<Edge>, &'static str> {
         let mut edges = Vec::new();
         let mut cost = 0;
         for distance in 1..=self.max_move {
@@ -67,7 +113,10 @@ impl Puzzle {
                 break;
             }
             let x = x - distance;
-            cost += self.rows[y][x] as usize;
+            if self.blocked[y][x] {
+                break;
+            }
+            cost = self.add_cost(cost, x, y)?;
             if distance >= self.min_move {
                 edges.push(Edge { cost, x, y });
             }
@@ -75,18 +124,18 @@ impl Puzzle {
         cost = 0;
         for distance in 1..=self.max_move {
             let x = x + distance;
-            if x >= self.w {
+            if x >= self.w || self.blocked[y][x] {
                 break;
             }
-            cost += self.rows[y][x] as usize;
+            cost = self.add_cost(cost, x, y)?;
             if distance >= self.min_move {
                 edges.push(Edge { cost, x, y});
             }
         }
-        edges
+        Ok(edges)
     }
 
-    fn edges_v(&self, x: usize, y: usize) -> Vec<Edge> {
+    fn edges_v(&self, x: usize, y: usize) -> Result<Vec<Edge>, &'static str> {
         let mut edges = Vec::new();
         let mut cost = 0;
         for distance in 1..=self.max_move {
@@ -94,7 +143,10 @@ impl Puzzle {
                 break;
             }
             let y = y - distance;
-            cost += self.rows[y][x] as usize;
+            if self.blocked[y][x] {
+                break;
+            }
+            cost = self.add_cost(cost, x, y)?;
             if distance >= self.min_move {
                 edges.push(Edge { cost, x, y });
             }
@@ -102,79 +154,340 @@ impl Puzzle {
         cost = 0;
         for distance in 1..=self.max_move {
             let y = y + distance;
-            if y >= self.h {
+            if y >= self.h || self.blocked[y][x] {
                 break;
             }
-            cost += self.rows[y][x] as usize;
+            cost = self.add_cost(cost, x, y)?;
             if distance >= self.min_move {
                 edges.push(Edge { cost, x, y});
             }
         }
-        edges
-    }
-
-    fn shortest_path(&self) -> usize {
-        // dist_h[y][x] is the minimum distance to get to node at (x, y) that enters the node
-        // horizontally. dist_v[y][x] is analogous.
-        let mut dist_h: Vec<Vec<usize>> = (0..self.rows.len()).map(|_| (0..self.w).map(|_| usize::MAX).collect()).collect();
-        let mut dist_v: Vec<Vec<usize>> = (0..self.rows.len()).map(|_| (0..self.w).map(|_| usize::MAX).collect()).collect();
-        let mut heap = BinaryHeap::new();
-        dist_h[0][0] = 0;
-        dist_v[0][0] = 0;
-        heap.push(State { cost: 0, x: 0, y: 0, vertical: false });
-        heap.push(State { cost: 0, x: 0, y: 0, vertical: true });
-        while let Some(State { cost, x, y, vertical }) = heap.pop() {
-            if x == self.w - 1 && y == self.h - 1 {
-                return cost;
-            }
-            if vertical {
-                // Move horizontally now
-                if cost <= dist_v[y][x] {
-                    for edge in self.edges_h(x, y) {
-                        let next = State { cost: cost + edge.cost, x: edge.x, y: edge.y, vertical: false};
-                        if next.cost < dist_h[next.y][next.x] {
-                            heap.push(next);
-                            dist_h[next.y][next.x] = next.cost;
-                        }
-                    }
-                }
-            } else {
-                // Move vertically now
-                if cost <= dist_h[y][x] {
-                    for edge in self.edges_v(x, y) {
-                        let next = State { cost: cost + edge.cost, x: edge.x, y: edge.y, vertical: true};
-                        if next.cost < dist_v[next.y][next.x] {
-                            heap.push(next);
-                            dist_v[next.y][next.x] = next.cost;
-                        }
-                    }
+        Ok(edges)
+    }
+
+    /// Runs Dijkstra's algorithm from the top-left cell and calls `stop` for every node when its
+    /// cost is final, stopping the search as soon as `stop` returns true.
+    fn search(&self, stop: impl FnMut(&Node, usize) -> bool) -> Result<DenseSearch<Node, usize>, &'static str> {
+        let starts = [
+            (Node { x: 0, y: 0, vertical: false }, 0),
+            (Node { x: 0, y: 0, vertical: true }, 0),
+        ];
+        self.search_from(starts, stop)
+    }
+
+    /// Like `search`, but starts from the given nodes with their costs. Fails if a heat loss does
+    /// not fit into `usize`.
+    fn search_from(
+        &self,
+        starts: impl IntoIterator<Item = (Node, usize)>,
+        stop: impl FnMut(&Node, usize) -> bool,
+    ) -> Result<DenseSearch<Node, usize>, &'static str> {
+        let successors = |&Node { x, y, vertical }: &Node| {
+            // Turn after each move
+            let edges = if vertical { self.edges_h(x, y)? } else { self.edges_v(x, y)? };
+            Ok(edges.into_iter().map(move |edge| (Node { x: edge.x, y: edge.y, vertical: !vertical }, edge.cost)))
+        };
+        search::dijkstra_dense(self.w * self.h * 2, |node| self.index(node), starts, successors, stop).map_err(|_| OVERFLOW)
+    }
+
+    /// Index of the node for the dense search, below `2 · w · h`
+    fn index(&self, node: &Node) -> usize {
+        (node.y * self.w + node.x) * 2 + usize::from(node.vertical)
+    }
+
+    pub fn shortest_path(&self) -> Result<usize, &'static str> {
+        self.shortest_path_to(self.w - 1, self.h - 1)
+    }
+
+    fn shortest_path_to(&self, goal_x: usize, goal_y: usize) -> Result<usize, &'static str> {
+        if self.blocked[0][0] || self.blocked[goal_y][goal_x] {
+            return Err("Start or goal is blocked");
+        }
+        let search = self.search(|node, _| (node.x, node.y) == (goal_x, goal_y))?;
+        search.goal.map(|(_, cost)| cost).ok_or("Goal unreachable")
+    }
+
+    /// Computes the minimum heat loss from the top-left cell to the bottom-right one when the
+    /// crucible must stop at each of the waypoints (x, y) in the given order.
+    ///
+    /// The path is chained from shortest segments between consecutive waypoints. Since the
+    /// crucible has to turn after stopping, each segment continues from the cheapest way to reach
+    /// its starting waypoint in either orientation.
+    pub fn shortest_path_via(&self, waypoints: &[(usize, usize)]) -> Result<usize, &'static str> {
+        let goal = (self.w - 1, self.h - 1);
+        if self.blocked[0][0] || self.blocked[goal.1][goal.0] {
+            return Err("Start or goal is blocked");
+        }
+        for &(x, y) in waypoints {
+            if x >= self.w || y >= self.h {
+                return Err("Waypoint out of bounds");
+            }
+            if self.blocked[y][x] {
+                return Err("Waypoint is blocked");
+            }
+        }
+        let mut starts = vec![
+            (Node { x: 0, y: 0, vertical: false }, 0),
+            (Node { x: 0, y: 0, vertical: true }, 0),
+        ];
+        for &(x, y) in waypoints.iter().chain([&goal]) {
+            // Stop once the target has been settled in both orientations
+            let mut settled = 0;
+            let search = self.search_from(starts, |node, _| {
+                if (node.x, node.y) == (x, y) {
+                    settled += 1;
                 }
+                settled == 2
+            })?;
+            starts = [false, true].into_iter()
+                .map(|vertical| Node { x, y, vertical })
+                .filter_map(|node| Some((node, search.cost(self.index(&node))?)))
+                .collect();
+            if starts.is_empty() {
+                return Err("Waypoint unreachable");
             }
         }
-        panic!("Goal unreachable");
+        starts.iter().map(|&(_, cost)| cost).min().ok_or("Goal unreachable")
+    }
+
+    /// Changes the number of cells the crucible must and may move in a straight line, keeping the
+    /// heat losses and blocked cells.
+    pub fn set_moves(&mut self, min_move: usize, max_move: usize) {
+        self.min_move = min_move;
+        self.max_move = max_move;
+    }
+
+    /// Finds a path with minimum heat loss from the top-left cell to the bottom-right one.
+    pub fn best_route(&self) -> Result<Route, &'static str> {
+        let goal = (self.w - 1, self.h - 1);
+        if self.blocked[0][0] || self.blocked[goal.1][goal.0] {
+            return Err("Start or goal is blocked");
+        }
+        let search = self.search(|node, _| (node.x, node.y) == goal)?;
+        let (node, heat_loss) = search.goal.ok_or("Goal unreachable")?;
+        let turns = search.path_to(self.index(&node)).ok_or("Goal unreachable")?;
+        // Fill in the cells of each straight move between consecutive turns
+        let mut cells = vec![(0, 0)];
+        for pair in turns.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let mut cell = (from.x, from.y);
+            while cell != (to.x, to.y) {
+                cell = (step_toward(cell.0, to.x), step_toward(cell.1, to.y));
+                cells.push(cell);
+            }
+        }
+        Ok(Route { heat_loss, cells })
+    }
+
+    /// Computes the minimum heat loss from the top-left cell to every cell in one search.
+    pub fn shortest_paths_to_all(&self) -> Result<HeatLosses, &'static str> {
+        if self.blocked[0][0] {
+            return Err("Start is blocked");
+        }
+        let search = self.search(|_, _| false)?;
+        let dist = |vertical| (0..self.h)
+            .map(|y| (0..self.w).map(|x| search.cost(self.index(&Node { x, y, vertical })).unwrap_or(usize::MAX)).collect())
+            .collect();
+        Ok(HeatLosses { horizontal: dist(false), vertical: dist(true) })
+    }
+}
+
+/// Moves `from` one step toward `to`.
+fn step_toward(from: usize, to: usize) -> usize {
+    match from.cmp(&to) {
+        Ordering::Less => from + 1,
+        Ordering::Equal => from,
+        Ordering::Greater => from - 1,
+    }
+}
+
+/// A path with minimum heat loss from the top-left cell to the bottom-right one
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    pub heat_loss: usize,
+    /// The cells (x, y) visited in order, including the start
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// The best routes of the crucible and the ultra crucible through the same grid
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Comparison {
+    width: usize,
+    height: usize,
+    /// Route when moving 1 to 3 cells in a straight line
+    pub crucible: Route,
+    /// Route when moving 4 to 10 cells in a straight line
+    pub ultra: Route,
+}
+
+impl Comparison {
+    /// The cells on both routes, row by row
+    pub fn shared_cells(&self) -> Vec<(usize, usize)> {
+        let crucible: HashSet<_> = self.crucible.cells.iter().collect();
+        let mut shared: Vec<_> = self.ultra.cells.iter().filter(|cell| crucible.contains(cell)).copied().collect();
+        shared.sort_unstable_by_key(|&(x, y)| (y, x));
+        shared
+    }
+
+    /// Draws the grid with '#' for cells on both routes, 'c' for cells only on the crucible's
+    /// route, 'u' for cells only on the ultra crucible's and '.' for the rest.
+    pub fn overlay(&self) -> Vec<String> {
+        let mut grid = vec![vec!['.'; self.width]; self.height];
+        for &(x, y) in &self.crucible.cells {
+            grid[y][x] = 'c';
+        }
+        for &(x, y) in &self.ultra.cells {
+            grid[y][x] = if grid[y][x] == 'c' { '#' } else { 'u' };
+        }
+        grid.into_iter().map(String::from_iter).collect()
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Crucible (1 to 3 moves): heat loss {}, {} cells", self.crucible.heat_loss, self.crucible.cells.len())?;
+        writeln!(f, "Ultra crucible (4 to 10 moves): heat loss {}, {} cells", self.ultra.heat_loss, self.ultra.cells.len())?;
+        writeln!(f, "Shared cells: {}", self.shared_cells().len())?;
+        for line in self.overlay() {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, 1, 3)?;
-    Ok(puzzle.shortest_path())
+/// Solves the grid for both the crucible and the ultra crucible, parsing it only once.
+pub fn compare_crucibles(input: &str, mask: Option<&str>) -> Result<Comparison, Box<dyn Error>> {
+    compare(read_masked(input, mask)?)
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, 4, 10)?;
-    Ok(puzzle.shortest_path())
+fn compare(mut puzzle: Puzzle) -> Result<Comparison, Box<dyn Error>> {
+    let crucible = puzzle.best_route()?;
+    puzzle.set_moves(4, 10);
+    let ultra = puzzle.best_route()?;
+    Ok(Comparison { width: puzzle.w, height: puzzle.h, crucible, ultra })
+}
+
+/// Minimum heat loss from the top-left cell to each cell, separately for entering the cell
+/// horizontally and vertically. The start has a heat loss of 0 in both orientations.
+pub struct HeatLosses {
+    horizontal: Vec<Vec<usize>>,
+    vertical: Vec<Vec<usize>>,
+}
+
+impl HeatLosses {
+    fn lookup(dist: &[Vec<usize>], x: usize, y: usize) -> Option<usize> {
+        dist.get(y)?.get(x).copied().filter(|&d| d != usize::MAX)
+    }
+
+    /// Minimum heat loss for reaching (x, y) with the last move being horizontal, or `None` if
+    /// that is impossible
+    pub fn horizontal(&self, x: usize, y: usize) -> Option<usize> {
+        Self::lookup(&self.horizontal, x, y)
+    }
+
+    /// Minimum heat loss for reaching (x, y) with the last move being vertical, or `None` if that
+    /// is impossible
+    pub fn vertical(&self, x: usize, y: usize) -> Option<usize> {
+        Self::lookup(&self.vertical, x, y)
+    }
+
+    /// Minimum heat loss for reaching (x, y) in any orientation, or `None` if it is unreachable
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        match (self.horizontal(x, y), self.vertical(x, y)) {
+            (Some(h), Some(v)) => Some(h.min(v)),
+            (h, v) => h.or(v),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.horizontal.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.horizontal.len()
+    }
+}
+
+/// Reads the grid with the moves of the crucible of part 1 and blocks the cells of the mask, if
+/// any.
+fn read_masked(input: &str, mask: Option<&str>) -> Result<Puzzle, Box<dyn Error>> {
+    let mut puzzle = Puzzle::from_input(input, 1, 3)?;
+    if let Some(mask) = mask {
+        puzzle.apply_mask(mask)?;
+    }
+    Ok(puzzle)
+}
+
+/// Both parts, solved from the same grid with the moves of each crucible
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        read_masked(input, None)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.shortest_path()?.into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        let mut puzzle = puzzle.clone();
+        puzzle.set_moves(4, 10);
+        Ok(puzzle.shortest_path()?.into())
+    }
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and characters other than the digits 1 to 9.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_grid(input, |c| matches!(c, '1'..='9'))
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = config.play_path {
+        let replay = Replay::from_bytes(&fs::read(path)?)?;
+        aoc_core::render::play_ansi(&mut ReplayFrames::new(replay), &mut std::io::stdout().lock(), Duration::from_millis(50))?;
+        return Ok(());
+    }
+
+    let mask = match config.mask_path {
+        Some(path) => {
+            println!("Reading mask file {path}");
+            Some(fs::read_to_string(path)?)
+        },
+        None => None,
+    };
+
+    let parse = |input: &str| read_masked(input, mask.as_deref());
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let puzzle = parse(&fs::read_to_string(&config.file_path1)?)?;
+    if config.compare {
+        print!("{}", compare(puzzle)?);
+        return Ok(());
+    }
+    if let Some(path) = config.record_path {
+        let replay = puzzle.replay()?;
+        println!("Writing the {} states settled by the search of part 1 to {path}", replay.settled.len());
+        fs::write(path, replay.to_bytes())?;
+    }
+    println!("Result of part 1: {}", Day::part1(&puzzle)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, parse)?;
+    println!("Result of part 2: {}", Day::part2(&puzzle)?);
 
     Ok(())
 }
@@ -207,24 +520,135 @@ mod tests {
 999999999991
 ";
 
+    fn part1(input: &str, mask: Option<&str>) -> Result<Answer, Box<dyn Error>> {
+        Day::part1(&read_masked(input, mask)?)
+    }
+
+    fn part2(input: &str, mask: Option<&str>) -> Result<Answer, Box<dyn Error>> {
+        Day::part2(&read_masked(input, mask)?)
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = part1(EXAMPLE1.trim(), None)?;
         assert_eq!(result, 102);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = part2(EXAMPLE1.trim(), None)?;
         assert_eq!(result, 94);
         Ok(())
     }
 
+    #[test]
+    fn waypoints() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim(), 1, 3)?;
+        assert_eq!(puzzle.shortest_path_via(&[])?, 102);
+        assert_eq!(puzzle.shortest_path_via(&[(12, 12)])?, 102);
+        let puzzle = Puzzle::from_input("111\n111\n111", 1, 3)?;
+        assert_eq!(puzzle.shortest_path_via(&[(0, 2)])?, 4);
+        // Reaching (0, 2) vertically from (2, 0) allows moving right to the goal without detour
+        assert_eq!(puzzle.shortest_path_via(&[(2, 0), (0, 2)])?, 8);
+        assert_eq!(puzzle.shortest_path_via(&[(3, 0)]), Err("Waypoint out of bounds"));
+        let mut puzzle = Puzzle::from_input("111\n111\n111", 1, 3)?;
+        puzzle.block_cell(1, 0)?;
+        puzzle.block_cell(1, 1)?;
+        assert_eq!(puzzle.shortest_path_via(&[(1, 1)]), Err("Waypoint is blocked"));
+        puzzle.block_cell(0, 1)?;
+        assert_eq!(puzzle.shortest_path_via(&[(2, 0)]), Err("Waypoint unreachable"));
+        Ok(())
+    }
+
     #[test]
     fn example2_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE2.trim())?;
+        let result = part2(EXAMPLE2.trim(), None)?;
         assert_eq!(result, 71);
         Ok(())
     }
+
+    #[test]
+    fn example2_part1_with_mask() -> Result<(), Box<dyn Error>> {
+        // Blocking the top row forces the crucible through the expensive cells, alternating between
+        // four single steps down and four steps right, such that only the goal is cheap
+        let mask = "
+.###########
+............
+............
+............
+............
+";
+        let result = part1(EXAMPLE2.trim(), Some(mask.trim()))?;
+        assert_eq!(result, 14 * 9 + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_paths_to_all() -> Result<(), Box<dyn Error>> {
+        for (min_move, max_move) in [(1, 3), (4, 10)] {
+            let puzzle = Puzzle::from_input(EXAMPLE1.trim(), min_move, max_move)?;
+            let heat_losses = puzzle.shortest_paths_to_all()?;
+            assert_eq!((heat_losses.width(), heat_losses.height()), (13, 13));
+            for y in 0..13 {
+                for x in 0..13 {
+                    assert_eq!(heat_losses.get(x, y), puzzle.shortest_path_to(x, y).ok());
+                }
+            }
+        }
+        let heat_losses = Puzzle::from_input(EXAMPLE1.trim(), 1, 3)?.shortest_paths_to_all()?;
+        assert_eq!(heat_losses.get(12, 12), Some(102));
+        assert_eq!(heat_losses.horizontal(1, 0), Some(4));
+        assert_eq!(heat_losses.get(13, 0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_comparison() -> Result<(), Box<dyn Error>> {
+        let comparison = compare_crucibles(EXAMPLE1.trim(), None)?;
+        assert_eq!(comparison.crucible.heat_loss, 102);
+        assert_eq!(comparison.ultra.heat_loss, 94);
+        let costs = Puzzle::from_input(EXAMPLE1.trim(), 1, 3)?.rows;
+        for route in [&comparison.crucible, &comparison.ultra] {
+            assert_eq!(route.cells.first(), Some(&(0, 0)));
+            assert_eq!(route.cells.last(), Some(&(12, 12)));
+            assert!(route.cells.windows(2).all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+            let heat_loss: u32 = route.cells[1..].iter().map(|&(x, y)| costs[y][x]).sum();
+            assert_eq!(heat_loss as usize, route.heat_loss);
+        }
+        let overlay = comparison.overlay();
+        assert_eq!(overlay.len(), 13);
+        assert!(overlay[0].starts_with('#') && overlay[12].ends_with('#'));
+        let shared = comparison.shared_cells();
+        assert_eq!(overlay.iter().flat_map(|line| line.chars()).filter(|&c| c == '#').count(), shared.len());
+        Ok(())
+    }
+
+    #[test]
+    fn multi_digit_costs() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_costs(vec![vec![1, 1000, 1], vec![10, 20, 300], vec![4000, 50, 6]], 1, 3)?;
+        assert_eq!(puzzle.shortest_path()?, 10 + 20 + 50 + 6);
+        assert!(Puzzle::from_costs(vec![vec![1, 2], vec![3]], 1, 3).is_err());
+        let puzzle = Puzzle::from_costs(vec![vec![u32::MAX; 40]; 40], 1, 3)?;
+        assert_eq!(puzzle.shortest_path()?, 78 * u32::MAX as usize);
+        Ok(())
+    }
+
+    #[test]
+    fn heat_loss_overflow() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_costs(vec![vec![0, 2], vec![2, 2]], 1, 3)?;
+        let start = (Node { x: 0, y: 0, vertical: false }, usize::MAX - 1);
+        assert_eq!(puzzle.search_from([start], |_, _| false).err(), Some(OVERFLOW));
+        Ok(())
+    }
+
+    #[test]
+    fn example2_blocked_goal_unreachable() -> Result<(), Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(EXAMPLE2.trim(), 4, 10)?;
+        for y in 0..5 {
+            puzzle.block_cell(5, y)?;
+        }
+        assert_eq!(puzzle.shortest_path(), Err("Goal unreachable"));
+        Ok(())
+    }
 }
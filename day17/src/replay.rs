@@ -0,0 +1,238 @@
+//! Recording the order in which the search settles states, so that it can be played back without
+//! searching again.
+
+use std::error::Error;
+
+use aoc_core::render::{Frame, FrameSource};
+
+use crate::{Node, Puzzle};
+
+/// Start of every replay file, including the version of the format
+const MAGIC: &[u8; 8] = b"D17RPLY1";
+
+/// Stored instead of the heat loss if the goal is unreachable
+const UNREACHABLE: u64 = u64::MAX;
+
+/// A state whose minimum heat loss the search has found
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SettledState {
+    pub x: usize,
+    pub y: usize,
+    /// Whether the cell was entered vertically
+    pub vertical: bool,
+    pub heat_loss: usize,
+}
+
+/// The states settled by a search from the top-left cell, in the order in which they were settled,
+/// until it reached the bottom-right cell
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Replay {
+    pub width: usize,
+    pub height: usize,
+    pub settled: Vec<SettledState>,
+    /// Minimum heat loss at the goal, or `None` if it is unreachable
+    pub heat_loss: Option<usize>,
+}
+
+impl Replay {
+    /// Encodes the replay in a binary format: the magic bytes, then the width, height, heat loss
+    /// and number of states, and for each state its x, y, orientation (0 for horizontal, 1 for
+    /// vertical) and heat loss, all in little-endian order. Coordinates take 4 bytes, orientations
+    /// 1 and all other numbers 8.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 24 + 17 * self.settled.len());
+        bytes.extend(MAGIC);
+        bytes.extend((self.width as u32).to_le_bytes());
+        bytes.extend((self.height as u32).to_le_bytes());
+        bytes.extend(self.heat_loss.map_or(UNREACHABLE, |heat_loss| heat_loss as u64).to_le_bytes());
+        bytes.extend((self.settled.len() as u64).to_le_bytes());
+        for state in &self.settled {
+            bytes.extend((state.x as u32).to_le_bytes());
+            bytes.extend((state.y as u32).to_le_bytes());
+            bytes.push(u8::from(state.vertical));
+            bytes.extend((state.heat_loss as u64).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a replay written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("Not a replay file");
+        }
+        let width = reader.u32()? as usize;
+        let height = reader.u32()? as usize;
+        let heat_loss = match reader.u64()? {
+            UNREACHABLE => None,
+            heat_loss => Some(usize::try_from(heat_loss).map_err(|_| "Heat loss too large")?),
+        };
+        let count = usize::try_from(reader.u64()?).map_err(|_| "Too many states")?;
+        let mut settled = Vec::with_capacity(count.min(reader.0.len() / 17));
+        for _ in 0..count {
+            let (x, y) = (reader.u32()? as usize, reader.u32()? as usize);
+            let vertical = match reader.take(1)? {
+                [0] => false,
+                [1] => true,
+                _ => return Err("Invalid orientation"),
+            };
+            let heat_loss = usize::try_from(reader.u64()?).map_err(|_| "Heat loss too large")?;
+            if x >= width || y >= height {
+                return Err("State outside of the grid");
+            }
+            settled.push(SettledState { x, y, vertical, heat_loss });
+        }
+        if !reader.0.is_empty() {
+            return Err("Unexpected data after the last state");
+        }
+        Ok(Self { width, height, settled, heat_loss })
+    }
+}
+
+/// Reads numbers from the front of a byte slice
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], &'static str> {
+        if self.0.len() < n {
+            return Err("Replay file ends unexpectedly");
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().map_err(|_| "Replay file ends unexpectedly")?))
+    }
+
+    fn u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().map_err(|_| "Replay file ends unexpectedly")?))
+    }
+}
+
+impl Puzzle {
+    /// Searches for the minimum heat loss at the bottom-right cell and records the states in the
+    /// order in which they are settled.
+    pub fn replay(&self) -> Result<Replay, &'static str> {
+        let goal = (self.w - 1, self.h - 1);
+        if self.blocked[0][0] || self.blocked[goal.1][goal.0] {
+            return Err("Start or goal is blocked");
+        }
+        let mut settled = Vec::new();
+        let search = self.search(|&Node { x, y, vertical }, heat_loss| {
+            settled.push(SettledState { x, y, vertical, heat_loss });
+            (x, y) == goal
+        })?;
+        let heat_loss = search.goal.map(|(_, heat_loss)| heat_loss);
+        Ok(Replay { width: self.w, height: self.h, settled, heat_loss })
+    }
+}
+
+/// Frames showing how the search spreads from the top-left cell, one for each heat loss at which
+/// states are settled. Cells are drawn as '-' when settled after a horizontal move, '|' after a
+/// vertical one, '+' after both and '.' otherwise.
+pub struct ReplayFrames {
+    replay: Replay,
+    lines: Vec<Vec<char>>,
+    /// Number of states drawn, or `None` before the initial frame
+    drawn: Option<usize>,
+}
+
+impl ReplayFrames {
+    pub fn new(replay: Replay) -> Self {
+        let lines = vec![vec!['.'; replay.width]; replay.height];
+        Self { replay, lines, drawn: None }
+    }
+
+    /// Frames of the search of part 1, which is run right away
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(Puzzle::from_input(input, 1, 3)?.replay()?))
+    }
+
+    fn frame(&self, caption: String) -> Frame {
+        Frame::new(caption, self.lines.iter().map(|line| line.iter().collect()).collect())
+    }
+}
+
+impl FrameSource for ReplayFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        let total = self.replay.settled.len();
+        let drawn = match self.drawn {
+            None => {
+                self.drawn = Some(0);
+                return Some(self.frame(format!("Search from the top-left cell, {total} states settled")));
+            },
+            Some(drawn) if drawn < total => drawn,
+            Some(_) => return None,
+        };
+        // Draw all states with the heat loss of the next one
+        let heat_loss = self.replay.settled[drawn].heat_loss;
+        let mut drawn = drawn;
+        while let Some(state) = self.replay.settled.get(drawn).filter(|state| state.heat_loss == heat_loss) {
+            let c = &mut self.lines[state.y][state.x];
+            *c = match (*c, state.vertical) {
+                ('.', false) => '-',
+                ('.', true) => '|',
+                ('-', false) | ('|', true) => *c,
+                _ => '+',
+            };
+            drawn += 1;
+        }
+        self.drawn = Some(drawn);
+        let mut caption = format!("Heat loss {heat_loss}: {drawn} of {total} states settled");
+        if drawn == total {
+            match self.replay.heat_loss {
+                Some(heat_loss) => caption.push_str(&format!(", goal reached with heat loss {heat_loss}")),
+                None => caption.push_str(", goal unreachable"),
+            }
+        }
+        Some(self.frame(caption))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_core::render;
+
+    const GRID: &str = "
+1911
+1911
+1111
+";
+
+    #[test]
+    fn replay_round_trip() -> Result<(), Box<dyn Error>> {
+        let replay = Puzzle::from_input(GRID.trim(), 1, 3)?.replay()?;
+        assert_eq!(replay.heat_loss, Some(5));
+        assert_eq!(replay.settled[..2], [
+            SettledState { x: 0, y: 0, vertical: false, heat_loss: 0 },
+            SettledState { x: 0, y: 0, vertical: true, heat_loss: 0 },
+        ]);
+        assert!(replay.settled.windows(2).all(|pair| pair[0].heat_loss <= pair[1].heat_loss));
+        let bytes = replay.to_bytes();
+        assert_eq!(bytes.len(), 8 + 24 + 17 * replay.settled.len());
+        assert_eq!(Replay::from_bytes(&bytes)?, replay);
+        assert_eq!(Replay::from_bytes(&bytes[..bytes.len() - 1]), Err("Replay file ends unexpectedly"));
+        assert_eq!(Replay::from_bytes(b"D17RPLY0"), Err("Not a replay file"));
+        Ok(())
+    }
+
+    #[test]
+    fn replay_frames() -> Result<(), Box<dyn Error>> {
+        let replay = Puzzle::from_input(GRID.trim(), 1, 3)?.replay()?;
+        let total = replay.settled.len();
+        let mut frames = ReplayFrames::new(replay);
+        let first = frames.next_frame().ok_or("No frames")?;
+        assert_eq!(first.lines, vec!["....", "....", "...."]);
+        let second = frames.next_frame().ok_or("No frames")?;
+        assert_eq!(second.caption, format!("Heat loss 0: 2 of {total} states settled"));
+        assert_eq!(second.lines[0], "+...");
+        let last = render::last_frame(&mut frames).ok_or("No frames")?;
+        assert!(last.caption.ends_with("goal reached with heat loss 5"), "{}", last.caption);
+        // The best route enters the goal from the left
+        assert_eq!(last.lines[2].chars().last(), Some('-'));
+        Ok(())
+    }
+}
@@ -1,17 +1,40 @@
+use crate::Dialect;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Check that the grid and shoelace engines agree on the plan of part 1, and compare the areas
+    /// of both decodings of each plan
+    pub crosscheck: bool,
+    /// Print the perimeter, interior, bounding box and number of corners of each lagoon
+    pub stats: bool,
+    /// How the directions of the plan of part 1 are written
+    pub dialect: Dialect,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day18 <file for part 1> <file for part 2> [--crosscheck] [--stats] [--relative]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut crosscheck = false;
+        let mut stats = false;
+        let mut dialect = Dialect::Absolute;
+        for arg in &args[3..] {
+            match arg.as_str() {
+                "--crosscheck" => crosscheck = true,
+                "--stats" => stats = true,
+                "--relative" => dialect = Dialect::Relative,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            crosscheck,
+            stats,
+            dialect,
         })
     }
 }
-
@@ -0,0 +1,184 @@
+//! Random dig plans whose trenches neither cross nor touch themselves, for property tests and
+//! benchmarks.
+
+use std::collections::{HashMap, HashSet};
+
+use aoc_core::rng::{Rng, SeededRng};
+
+/// Number of cells tried per cell of the shape before giving up on growing it
+const MAX_ATTEMPTS_PER_CELL: usize = 100;
+
+/// Number of most recently added cells from which the shape mostly grows
+const RECENT_CELLS: u64 = 16;
+
+/// A dig plan together with the size of the lagoon it digs out
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneratedPlan {
+    /// The plan, where the color of each instruction encodes the same direction and length as in
+    /// part 2, so that both parts dig out the same lagoon
+    pub input: String,
+    /// Number of cubic meters of lava the lagoon holds
+    pub area: i128,
+    /// Number of places where the trench turns, which equals the number of instructions
+    pub corners: usize,
+}
+
+/// Offsets of the eight neighbors of a cell in clockwise order, starting above it
+const NEIGHBORS: [(i64, i64); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+/// Whether adding `cell` to the shape keeps it free of holes and of cells that touch only at a
+/// corner, given that the shape has both properties. This holds if the occupied neighbors of
+/// `cell` form a single run around it and no diagonal neighbor is occupied without one of the two
+/// cells between it and `cell`.
+fn can_add(shape: &HashSet<(i64, i64)>, (x, y): (i64, i64)) -> bool {
+    let occupied = NEIGHBORS.map(|(dx, dy)| shape.contains(&(x + dx, y + dy)));
+    let pinched = (1..8).step_by(2).any(|i| occupied[i] && !occupied[i - 1] && !occupied[(i + 1) % 8]);
+    let runs = (0..8).filter(|&i| occupied[i] && !occupied[(i + 7) % 8]).count();
+    !pinched && runs == 1
+}
+
+/// Generates a random dig plan whose lagoon is the outline of a random shape of `num_cells` cells
+/// grown from a single one. The rows and columns of the shape get random sizes between 2 and
+/// `max_length`, so the plan has more corners for more cells and longer instructions for a larger
+/// `max_length`. Sizes of at least 2 keep parts of the trench that are not consecutive from lying
+/// next to each other, which would cut off pockets of ground outside of the lagoon. Equal seeds
+/// give equal plans. Fails if `num_cells` is 0 or `max_length` is less than 2, or if the shape
+/// gets stuck while growing, which is unlikely.
+pub fn generate_plan(num_cells: usize, max_length: i64, seed: u64) -> Result<GeneratedPlan, &'static str> {
+    if num_cells == 0 || max_length < 2 {
+        return Err("A plan needs at least one cell and lengths of at least 2");
+    }
+    let mut rng = SeededRng::new(seed);
+    let mut random = move |max: u64| rng.below(max);
+
+    // Grow the shape by adding neighbors of random cells, mostly of recently added ones so that it
+    // branches out instead of growing into a blob
+    let mut cells = vec![(0, 0)];
+    let mut shape: HashSet<(i64, i64)> = cells.iter().copied().collect();
+    let mut attempts = 0;
+    while cells.len() < num_cells {
+        attempts += 1;
+        if attempts > num_cells * MAX_ATTEMPTS_PER_CELL {
+            return Err("Could not grow the shape to the requested number of cells");
+        }
+        let n = cells.len() as u64;
+        let i = if random(4) == 0 { random(n) } else { n - 1 - random(n.min(RECENT_CELLS)) };
+        let (x, y) = cells[i as usize];
+        let (dx, dy) = NEIGHBORS[2 * random(4) as usize];
+        let cell = (x + dx, y + dy);
+        if !shape.contains(&cell) && can_add(&shape, cell) {
+            shape.insert(cell);
+            cells.push(cell);
+        }
+    }
+
+    // Give each column and row of the shape a size and find where they start
+    let (min_x, min_y) = (cells.iter().map(|c| c.0).min().unwrap_or(0), cells.iter().map(|c| c.1).min().unwrap_or(0));
+    let (max_x, max_y) = (cells.iter().map(|c| c.0).max().unwrap_or(0), cells.iter().map(|c| c.1).max().unwrap_or(0));
+    let mut offsets = |min: i64, max: i64| {
+        let mut offsets = vec![0i128];
+        for _ in min..=max {
+            offsets.push(offsets[offsets.len() - 1] + 2 + random(max_length as u64 - 1) as i128);
+        }
+        offsets
+    };
+    let (xs, ys) = (offsets(min_x, max_x), offsets(min_y, max_y));
+    let (column, row) = (|x: i64| xs[(x - min_x) as usize], |y: i64| ys[(y - min_y) as usize]);
+    let interior: i128 = cells.iter().map(|&(x, y)| (column(x + 1) - column(x)) * (row(y + 1) - row(y))).sum();
+
+    // Walk the outline clockwise along the borders of the cells
+    let mut next = HashMap::new();
+    for &(x, y) in &cells {
+        for (dx, dy, from, to) in [
+            (0, -1, (x, y), (x + 1, y)),
+            (1, 0, (x + 1, y), (x + 1, y + 1)),
+            (0, 1, (x + 1, y + 1), (x, y + 1)),
+            (-1, 0, (x, y + 1), (x, y)),
+        ] {
+            if !shape.contains(&(x + dx, y + dy)) {
+                next.insert(from, to);
+            }
+        }
+    }
+    let start = next.keys().min().copied().ok_or("Shape has no outline")?;
+    let mut points = vec![start];
+    let mut point = next[&start];
+    while point != start {
+        points.push(point);
+        point = next[&point];
+    }
+    // Keep only the corners
+    let n = points.len();
+    let corners: Vec<(i64, i64)> = (0..n).map(|i| (points[(i + n - 1) % n], points[i], points[(i + 1) % n]))
+        .filter(|&((x0, y0), (x1, y1), (x2, y2))| (x1 - x0) * (y2 - y1) != (y1 - y0) * (x2 - x1))
+        .map(|(_, corner, _)| corner)
+        .collect();
+
+    let mut input = String::new();
+    let mut perimeter = 0;
+    for (i, &(x, y)) in corners.iter().enumerate() {
+        let (next_x, next_y) = corners[(i + 1) % corners.len()];
+        let (dx, dy) = (column(next_x) - column(x), row(next_y) - row(y));
+        let (direction, code) = match (dx.signum(), dy.signum()) {
+            (1, _) => ('R', 0),
+            (_, 1) => ('D', 1),
+            (-1, _) => ('L', 2),
+            _ => ('U', 3),
+        };
+        let length = dx.abs() + dy.abs();
+        perimeter += length;
+        input.push_str(&format!("{direction} {length} (#{length:05x}{code})\n"));
+    }
+    // Pick: the trench adds half its length plus one to the area enclosed by its middle
+    Ok(GeneratedPlan { input, area: interior + perimeter / 2 + 1, corners: corners.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{grid_area, lagoon_stats, read_plan, shoelace_area, solve_part1, solve_part2, Instruction};
+
+    #[test]
+    fn engines_agree_on_generated_plans() -> Result<(), Box<dyn Error>> {
+        for seed in 0..200 {
+            let plan = generate_plan(1 + seed as usize % 60, 2 + seed as i64 % 9, seed)?;
+            let instructions = read_plan(&plan.input, Instruction::from_line_part1)?;
+            assert_eq!(shoelace_area(&instructions)?, plan.area, "{}", plan.input);
+            assert_eq!(grid_area(&instructions)?, plan.area, "{}", plan.input);
+            assert_eq!(solve_part1(&plan.input)?, plan.area as i64);
+            assert_eq!(solve_part2(&plan.input)?, plan.area as i64);
+            assert_eq!(lagoon_stats(&plan.input, true)?.corners, plan.corners);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generation_is_deterministic() -> Result<(), Box<dyn Error>> {
+        assert_eq!(generate_plan(50, 10, 3)?, generate_plan(50, 10, 3)?);
+        let square = generate_plan(1, 2, 0)?;
+        assert_eq!(square.input, "R 2 (#000020)\nD 2 (#000021)\nL 2 (#000022)\nU 2 (#000023)\n");
+        assert_eq!(square.area, 9);
+        assert!(generate_plan(0, 2, 0).is_err());
+        assert!(generate_plan(1, 1, 0).is_err());
+        Ok(())
+    }
+
+    /// Compares both engines on a plan of 20000 cells. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_generated_plan() -> Result<(), Box<dyn Error>> {
+        let plan = generate_plan(20_000, 1_000_000, 1)?;
+        let instructions = read_plan(&plan.input, Instruction::from_line_part1)?;
+        println!("{} corners", plan.corners);
+        let start = std::time::Instant::now();
+        assert_eq!(shoelace_area(&instructions)?, plan.area);
+        println!("Shoelace engine: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        assert_eq!(grid_area(&instructions)?, plan.area);
+        println!("Grid engine: {:?}", start.elapsed());
+        Ok(())
+    }
+}
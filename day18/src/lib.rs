@@ -1,6 +1,11 @@
 pub mod config;
+mod generate;
 
-use std::collections::BTreeSet;
+pub use generate::{generate_plan, GeneratedPlan};
+
+use aoc_core::answer::{Answer, PartSolver};
+use aoc_core::validate::Issue;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
@@ -20,18 +25,223 @@ impl Display for Tile {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 enum Direction { L, R, U, D }
 
+impl Direction {
+    /// Offset of a single step, where y grows downwards
+    fn delta(&self) -> (i128, i128) {
+        match self {
+            Direction::L => (-1, 0),
+            Direction::R => (1, 0),
+            Direction::U => (0, -1),
+            Direction::D => (0, 1),
+        }
+    }
+
+    /// The direction after turning left (`L`) or right (`R`)
+    fn turned(self, turn: Direction) -> Result<Self, &'static str> {
+        let right = match self {
+            Direction::U => Direction::R,
+            Direction::R => Direction::D,
+            Direction::D => Direction::L,
+            Direction::L => Direction::U,
+        };
+        match turn {
+            Direction::L => right.turned(Direction::R)?.turned(Direction::R),
+            Direction::R => Ok(right),
+            Direction::U | Direction::D => Err("Invalid turn"),
+        }
+    }
+}
+
+/// How the directions of a dig plan are written
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Dialect {
+    /// U, D, L and R are the directions to dig in, as in the puzzle
+    #[default]
+    Absolute,
+    /// L and R turn left or right before digging, starting out facing up
+    Relative,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Instruction {
+    direction: Direction,
+    length: i64,
+}
+
+impl Instruction {
+    fn split_line(line: &str) -> Result<[&str; 3], Box<dyn Error>> {
+        let parts: Vec<&str> = line.split(' ').collect();
+        Ok(parts.try_into().map_err(|_| "Invalid number of parts in line")?)
+    }
+
+    fn new(direction: Direction, length: i64) -> Result<Self, Box<dyn Error>> {
+        if length < 0 {
+            return Err("Negative length".into());
+        }
+        Ok(Self { direction, length })
+    }
+
+    fn parse_direction(direction: &str) -> Result<Direction, Box<dyn Error>> {
+        Ok(match direction {
+            "L" => Direction::L,
+            "R" => Direction::R,
+            "U" => Direction::U,
+            "D" => Direction::D,
+            _ => return Err("Invalid direction".into()),
+        })
+    }
+
+    fn from_line_part1(line: &str) -> Result<Self, Box<dyn Error>> {
+        let [direction, length, _] = Self::split_line(line)?;
+        Self::new(Self::parse_direction(direction)?, length.parse()?)
+    }
+
+    /// Reads a line like "L 5" or "L 5 (#70c710)" of a plan written as turns into the turn, `L` or
+    /// `R`, and the length.
+    fn turn_from_line(line: &str) -> Result<(Direction, i64), Box<dyn Error>> {
+        let parts: Vec<&str> = line.split(' ').collect();
+        let [turn, length, ..] = parts[..] else {
+            return Err("Invalid number of parts in line".into());
+        };
+        if parts.len() > 3 {
+            return Err("Invalid number of parts in line".into());
+        }
+        match Self::parse_direction(turn)? {
+            turn @ (Direction::L | Direction::R) => Ok((turn, length.parse()?)),
+            _ => Err("Invalid turn".into()),
+        }
+    }
+
+    fn from_line_part2(line: &str) -> Result<Self, Box<dyn Error>> {
+        let [_, _, color] = Self::split_line(line)?;
+        let hex_str = color.strip_prefix("(#").and_then(|s| s.strip_suffix(')')).ok_or("Invalid hex string")?;
+        let mut hex_str = hex_str.to_owned();
+        let direction = match hex_str.pop().ok_or("Invalid hex string")? {
+            '0' => Direction::R,
+            '1' => Direction::D,
+            '2' => Direction::L,
+            '3' => Direction::U,
+            _ => return Err("Invalid direction".into()),
+        };
+        Self::new(direction, i64::from_str_radix(&hex_str, 16)?)
+    }
+}
+
+/// Decodes a plan written as turns into instructions with absolute directions, keeping track of
+/// the heading from line to line.
+fn turn_decoder() -> impl FnMut(&str) -> Result<Instruction, Box<dyn Error>> {
+    let mut heading = Direction::U;
+    move |line| {
+        let (turn, length) = Instruction::turn_from_line(line)?;
+        heading = heading.turned(turn)?;
+        Instruction::new(heading, length)
+    }
+}
+
+/// Decodes the plan line by line with `decode`, which may keep state from one line to the next.
+fn read_plan(
+    input: &str,
+    decode: impl FnMut(&str) -> Result<Instruction, Box<dyn Error>>,
+) -> Result<Vec<Instruction>, Box<dyn Error>> {
+    let instructions: Vec<Instruction> = input.lines().map(decode).collect::<Result<_, _>>()?;
+    if instructions.is_empty() {
+        return Err("Empty dig plan".into());
+    }
+    Ok(instructions)
+}
+
+/// Computes the corners of the trench, starting at the origin. Fails if the trench does not
+/// return to the origin.
+fn vertices(instructions: &[Instruction]) -> Result<Vec<(i128, i128)>, &'static str> {
+    let mut vertices = vec![(0, 0)];
+    let (mut x, mut y) = (0, 0);
+    for instruction in instructions {
+        let (dx, dy) = instruction.direction.delta();
+        x += dx * instruction.length as i128;
+        y += dy * instruction.length as i128;
+        vertices.push((x, y));
+    }
+    if (x, y) != (0, 0) {
+        return Err("Dig plan does not return to the start");
+    }
+    vertices.pop();
+    Ok(vertices)
+}
+
+/// Figures of a lagoon computed from the corners of its trench
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LagoonStats {
+    /// Number of cubic meters dug out, that is, trench and interior together
+    pub area: i128,
+    /// Length of the trench
+    pub perimeter: i128,
+    /// Number of cubic meters enclosed by the trench
+    pub interior: i128,
+    /// Width and height of the smallest rectangle containing the trench
+    pub bounding_box: (i128, i128),
+    /// Number of places where the trench turns
+    pub corners: usize,
+}
 
+impl Display for LagoonStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (w, h) = self.bounding_box;
+        write!(
+            f, "Area {} (trench {}, interior {}), bounding box {w} × {h}, {} corners",
+            self.area, self.perimeter, self.interior, self.corners
+        )
+    }
+}
+
+/// Computes the figures of the lagoon from the corners of the trench. The number of cubic meters
+/// of lava the lagoon holds, that is, the trench and its interior, follows from the shoelace
+/// formula and Pick's theorem.
+fn stats(instructions: &[Instruction]) -> Result<LagoonStats, &'static str> {
+    let vertices = vertices(instructions)?;
+    let twice_area: i128 = vertices.iter().zip(vertices.iter().cycle().skip(1))
+        .map(|(&(x1, y1), &(x2, y2))| x1 * y2 - x2 * y1)
+        .sum();
+    let perimeter: i128 = instructions.iter().map(|instruction| instruction.length as i128).sum();
+    // Pick: area = interior + outline / 2 - 1, and the lagoon consists of interior and outline
+    let area = (twice_area.abs() + perimeter) / 2 + 1;
+    let xs = vertices.iter().map(|&(x, _)| x);
+    let ys = vertices.iter().map(|&(_, y)| y);
+    let bounding_box = (
+        xs.clone().max().unwrap_or(0) - xs.min().unwrap_or(0) + 1,
+        ys.clone().max().unwrap_or(0) - ys.min().unwrap_or(0) + 1,
+    );
+    // Corners in the middle of a straight line are not counted
+    let mut distinct = vertices.clone();
+    distinct.dedup();
+    if distinct.len() > 1 && distinct.first() == distinct.last() {
+        distinct.pop();
+    }
+    let n = distinct.len();
+    let corners = (0..n).filter(|&i| {
+        let ((x0, y0), (x1, y1), (x2, y2)) = (distinct[(i + n - 1) % n], distinct[i], distinct[(i + 1) % n]);
+        (x1 - x0) * (y2 - y1) != (y1 - y0) * (x2 - x1)
+    }).count();
+    Ok(LagoonStats { area, perimeter, interior: area - perimeter, bounding_box, corners })
+}
+
+/// Computes the number of cubic meters of lava the lagoon holds with the shoelace formula.
+fn shoelace_area(instructions: &[Instruction]) -> Result<i128, &'static str> {
+    Ok(stats(instructions)?.area)
+}
+
+/// The trench dug into a grid whose rows and columns are compressed so that each one stands for
+/// either a corner coordinate or a range of coordinates between corners
 #[derive(Debug)]
 struct Puzzle {
     w: usize,
     h: usize,
-    outline_length: usize,
+    outline_length: i128,
     rows: Vec<Vec<Tile>>,
-    row_heights: Vec<usize>,
-    column_widths: Vec<usize>,
+    row_heights: Vec<i128>,
+    column_widths: Vec<i128>,
 }
 
 impl Display for Puzzle {
@@ -40,191 +250,89 @@ impl Display for Puzzle {
             for tile in row {
                 write!(f, "{tile}")?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
 impl Puzzle {
-    fn from_input_part1(input: &str) -> Result<Self, Box<dyn Error>> {
-        let mut instructions: Vec<(Direction, usize)> = Vec::new();
-        for line in input.lines() {
-            let parts: Vec<&str> = line.split(' ').collect();
-            if parts.len() != 3 {
-                return Err("Invalid number of parts in line".into());
-            }
-            let direction = match parts[0] {
-                "L" => Direction::L,
-                "R" => Direction::R,
-                "U" => Direction::U,
-                "D" => Direction::D,
-                _ => return Err("Invalid direction".into()),
-            };
-            let length: usize = parts[1].parse()?;
-            instructions.push((direction, length));
-        }
-        // Get dimensions and starting position
-        let mut x = 0;
-        let mut y = 0;
-        let mut min_x = 0;
-        let mut max_x = 0;
-        let mut min_y = 0;
-        let mut max_y = 0;
-        for (direction, length) in &instructions {
-            match direction {
-                Direction::L => x -= *length as i32,
-                Direction::R => x += *length as i32,
-                Direction::U => y -= *length as i32,
-                Direction::D => y += *length as i32,
+    /// Splits the coordinates into ranges: one for each corner coordinate and one for each gap
+    /// between consecutive corner coordinates. Returns the size of each range and the index of
+    /// the range of each corner coordinate.
+    fn compress(coordinates: BTreeSet<i128>) -> (Vec<i128>, BTreeMap<i128, usize>) {
+        let mut sizes = Vec::new();
+        let mut indices = BTreeMap::new();
+        let mut previous = None;
+        for c in coordinates {
+            if let Some(p) = previous {
+                if c - p > 1 {
+                    sizes.push(c - p - 1);
+                }
             }
-            min_x = min_x.min(x);
-            min_y = min_y.min(y);
-            max_x = max_x.max(x);
-            max_y = max_y.max(y);
+            indices.insert(c, sizes.len());
+            sizes.push(1);
+            previous = Some(c);
         }
-        let w = (max_x - min_x + 1) as usize;
-        let h = (max_y - min_y + 1) as usize;
+        (sizes, indices)
+    }
+
+    fn from_instructions(instructions: &[Instruction]) -> Result<Self, Box<dyn Error>> {
+        let vertices = vertices(instructions)?;
+        let (column_widths, column_indices) = Self::compress(vertices.iter().map(|&(x, _)| x).collect());
+        let (row_heights, row_indices) = Self::compress(vertices.iter().map(|&(_, y)| y).collect());
+        let w = column_widths.len();
+        let h = row_heights.len();
         let mut rows = vec![vec![Tile { dug: false }; w]; h];
-        // Starting position
-        let mut x = -min_x as usize;
-        let mut y = -min_y as usize;
-        // Dig
-        let mut outline_length = 0;
-        for (direction, length) in instructions {
-            let mut dig_x = x..x;
-            let mut dig_y = y..y;
-            match direction {
-                Direction::L => dig_x.start = x-length,
-                Direction::R => dig_x = x+1..x+length+1,
-                Direction::U => dig_y.start = y-length,
-                Direction::D => dig_y = y+1..y+length+1,
-            };
-            for x in dig_x {
-                let tile = &mut rows[y][x];
-                assert!(!tile.dug);
-                tile.dug = true;
-            }
-            for y in dig_y {
+        // Dig from each corner to the next one
+        let (mut x, mut y) = (column_indices[&0], row_indices[&0]);
+        for &(next_x, next_y) in vertices.iter().cycle().skip(1).take(vertices.len()) {
+            let (next_x, next_y) = (column_indices[&next_x], row_indices[&next_y]);
+            while (x, y) != (next_x, next_y) {
+                x = if next_x > x { x + 1 } else if next_x < x { x - 1 } else { x };
+                y = if next_y > y { y + 1 } else if next_y < y { y - 1 } else { y };
                 let tile = &mut rows[y][x];
-                assert!(!tile.dug);
+                if tile.dug {
+                    return Err("Trench crosses itself".into());
+                }
                 tile.dug = true;
             }
-            outline_length += length;
-            x = match direction {
-                Direction::L => x - length,
-                Direction::R => x + length,
-                _ => x,
-            };
-            y = match direction {
-                Direction::U => y - length,
-                Direction::D => y + length,
-                _ => y,
-            };
         }
-        let row_heights = vec![1; h];
-        let column_widths = vec![1; w];
-        Ok(Self { rows, w, h, outline_length, row_heights, column_widths })
+        let outline_length = instructions.iter().map(|instruction| instruction.length as i128).sum();
+        Ok(Self { rows, w, h, outline_length, column_widths, row_heights })
     }
 
-    fn from_input_part2(input: &str) -> Result<Self, Box<dyn Error>> {
-        let mut instructions: Vec<(Direction, usize)> = Vec::new();
-        for line in input.lines() {
-            let parts: Vec<&str> = line.split(' ').collect();
-            if parts.len() != 3 {
-                return Err("Invalid number of parts in line".into());
+    /// Digs out the tiles enclosed by the trench and returns their area.
+    fn dig_interior(&mut self) -> i128 {
+        // Tiles that can be reached from the border without crossing the trench are outside
+        let mut outside = vec![vec![false; self.w]; self.h];
+        let mut stack: Vec<(usize, usize)> = (0..self.w).flat_map(|x| [(x, 0), (x, self.h - 1)])
+            .chain((0..self.h).flat_map(|y| [(0, y), (self.w - 1, y)]))
+            .collect();
+        while let Some((x, y)) = stack.pop() {
+            if self.rows[y][x].dug || outside[y][x] {
+                continue;
             }
-            let mut hex_str: String = parts[2].chars().skip(2).take(parts[2].len() - 3).collect();
-            let direction = match hex_str.pop().ok_or("Invalid hex string")? {
-                '0' => Direction::R,
-                '1' => Direction::D,
-                '2' => Direction::L,
-                '3' => Direction::U,
-                _ => return Err("Invalid direction".into()),
-            };
-            let length: usize = usize::from_str_radix(&hex_str, 16)?;
-            instructions.push((direction, length));
-        }
-        // Get dimensions and starting position
-        let mut x_coordinates = BTreeSet::from([0]);
-        let mut y_coordinates = BTreeSet::from([0]);
-        let mut x = 0;
-        let mut y = 0;
-        for (direction, length) in &instructions {
-            match direction {
-                Direction::L => x -= *length as i32,
-                Direction::R => x += *length as i32,
-                Direction::U => y -= *length as i32,
-                Direction::D => y += *length as i32,
+            outside[y][x] = true;
+            if x > 0 {
+                stack.push((x - 1, y));
             }
-            x_coordinates.insert(x);
-            y_coordinates.insert(y);
-        }
-        let w = 2 * x_coordinates.len() - 1;
-        let h = 2 * y_coordinates.len() - 1;
-        let mut rows = vec![vec![Tile { dug: false }; w]; h];
-        let mut row_heights = Vec::from([1]);
-        for (y, yn) in y_coordinates.iter().zip(y_coordinates.iter().skip(1)) {
-            row_heights.push((yn - y - 1) as usize);
-            row_heights.push(1);
-        }
-        let mut column_widths = Vec::from([1]);
-        for (x, xn) in x_coordinates.iter().zip(x_coordinates.iter().skip(1)) {
-            column_widths.push((xn - x - 1) as usize);
-            column_widths.push(1);
-        }
-
-        // Starting position
-        let mut x = 2 * x_coordinates.range(..0).count();
-        let mut y = 2 * y_coordinates.range(..0).count();
-        // Dig
-        let mut outline_length = 0;
-        for (direction, length) in instructions {
-            let mut dug = 0;
-            while dug < length {
-                let tile = &mut rows[y][x];
-                assert_eq!(tile.dug, false);
-                tile.dug = true;
-                assert!(row_heights[y] == 1 || column_widths[x] == 1);
-                dug += row_heights[y] * column_widths[x];
-                match direction {
-                    Direction::L => x -= 1,
-                    Direction::R => x += 1,
-                    Direction::U => y -= 1,
-                    Direction::D => y += 1,
-                }
+            if x + 1 < self.w {
+                stack.push((x + 1, y));
+            }
+            if y > 0 {
+                stack.push((x, y - 1));
+            }
+            if y + 1 < self.h {
+                stack.push((x, y + 1));
             }
-            assert_eq!(dug, length);
-            outline_length += length;
         }
-        Ok(Self { rows, w, h, outline_length, column_widths, row_heights })
-    }
-
-    fn get_fill_start(&self) -> (usize, usize) {
-        let y = 1;
-        let x = self.rows[y].iter().position(|tile| tile.dug).unwrap() + 1;
-        assert!(!self.rows[y][x].dug);
-        (x, y)
-    }
-
-    fn dig_interior(&mut self) -> usize {
         let mut area = 0;
-        let mut stack = vec![self.get_fill_start()];
-        while let Some((x, y)) = stack.pop() {
-            if !self.rows[y][x].dug {
-                self.rows[y][x].dug = true;
-                area += self.row_heights[y] * self.column_widths[x];
-                if x > 0 {
-                    stack.push((x - 1, y));
-                }
-                if x < self.w {
-                    stack.push((x + 1, y));
-                }
-                if y > 0 {
-                    stack.push((x, y - 1));
-                }
-                if y < self.h {
-                    stack.push((x, y + 1));
+        for (y, row) in self.rows.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                if !tile.dug && !outside[y][x] {
+                    tile.dug = true;
+                    area += self.row_heights[y] * self.column_widths[x];
                 }
             }
         }
@@ -232,30 +340,130 @@ impl Puzzle {
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input_part1(input)?;
+/// Computes the area of the lagoon by digging out a compressed grid.
+fn grid_area(instructions: &[Instruction]) -> Result<i128, Box<dyn Error>> {
+    let mut puzzle = Puzzle::from_instructions(instructions)?;
     let area_dug = puzzle.dig_interior();
-    let area = puzzle.outline_length + area_dug;
-    Ok(area)
+    Ok(puzzle.outline_length + area_dug)
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input_part2(input)?;
-    let area_dug = puzzle.dig_interior();
-    let area = puzzle.outline_length + area_dug;
-    Ok(area)
+/// Decodes the plan of part 1 written in the given dialect.
+fn read_plan_part1(input: &str, dialect: Dialect) -> Result<Vec<Instruction>, Box<dyn Error>> {
+    match dialect {
+        Dialect::Absolute => read_plan(input, Instruction::from_line_part1),
+        Dialect::Relative => read_plan(input, turn_decoder()),
+    }
+}
+
+/// Number of cubic meters of lava the lagoon holds if the plan is decoded as in part 1, with the
+/// directions written in the given dialect
+pub fn plan_area(input: &str, dialect: Dialect) -> Result<i128, Box<dyn Error>> {
+    grid_area(&read_plan_part1(input, dialect)?)
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    let instructions = read_plan(input, Instruction::from_line_part1)?;
+    Ok(grid_area(&instructions)?.into())
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    let instructions = read_plan(input, Instruction::from_line_part2)?;
+    Ok(shoelace_area(&instructions)?.into())
+}
+
+/// Areas of the lagoon when the same dig plan is decoded as in part 1 and as in part 2
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecoderComparison {
+    pub area1: i128,
+    pub area2: i128,
+}
+
+impl DecoderComparison {
+    /// How many times larger the lagoon of part 2 is than that of part 1
+    pub fn ratio(&self) -> f64 {
+        self.area2 as f64 / self.area1 as f64
+    }
+}
+
+impl Display for DecoderComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Area decoded as in part 1: {}, as in part 2: {}, ratio {:.3}", self.area1, self.area2, self.ratio())
+    }
+}
+
+/// Decodes the plan with both decoders and computes both areas.
+pub fn compare_decoders(input: &str) -> Result<DecoderComparison, Box<dyn Error>> {
+    let area1 = shoelace_area(&read_plan(input, Instruction::from_line_part1)?)?;
+    let area2 = shoelace_area(&read_plan(input, Instruction::from_line_part2)?)?;
+    Ok(DecoderComparison { area1, area2 })
+}
+
+/// Computes the figures of the lagoon, decoding the instructions from the colors as in part 2 if
+/// `decode_colors` is set.
+pub fn lagoon_stats(input: &str, decode_colors: bool) -> Result<LagoonStats, Box<dyn Error>> {
+    let decode = if decode_colors { Instruction::from_line_part2 } else { Instruction::from_line_part1 };
+    Ok(stats(&read_plan(input, decode)?)?)
+}
+
+/// Computes the area of the plan decoded as in part 1 with both the grid and the shoelace engine
+/// and fails if they disagree.
+pub fn crosscheck(input: &str) -> Result<i128, Box<dyn Error>> {
+    check_engines(&read_plan(input, Instruction::from_line_part1)?)
+}
+
+fn check_engines(instructions: &[Instruction]) -> Result<i128, Box<dyn Error>> {
+    let (grid, shoelace) = (grid_area(instructions)?, shoelace_area(instructions)?);
+    if grid != shoelace {
+        return Err(format!("Grid engine computes area {grid} but shoelace engine computes {shoelace}").into());
+    }
+    Ok(grid)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as instructions for either part.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if let Err(e) = Instruction::from_line_part1(line) {
+            issues.push(Issue::at_line(i + 1, format!("Part 1: {e}")));
+        }
+        if let Err(e) = Instruction::from_line_part2(line) {
+            issues.push(Issue::at_line(i + 1, format!("Part 2: {e}")));
+        }
+    }
+    issues
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
     let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
+    let instructions = read_plan_part1(&contents, config.dialect)?;
+    let result = grid_area(&instructions)?;
     println!("Result of part 1: {result}");
+    if config.crosscheck {
+        println!("Engines agree on area {}", check_engines(&instructions)?);
+        if config.dialect == Dialect::Absolute {
+            println!("{}", compare_decoders(&contents)?);
+        }
+    }
+    if config.stats {
+        println!("{}", stats(&instructions)?);
+    }
 
     println!("Part 2: Reading file {}", config.file_path2);
     let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
+    let result = solve_part2(&contents)?;
     println!("Result of part 2: {result}");
+    if config.crosscheck {
+        println!("{}", compare_decoders(&contents)?);
+    }
+    if config.stats {
+        println!("{}", lagoon_stats(&contents, true)?);
+    }
 
     Ok(())
 }
@@ -281,17 +489,130 @@ L 2 (#015232)
 U 2 (#7a21e3)
 ";
 
+    #[test]
+    fn validate_instructions() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        assert_eq!(validate("R 6 (#70c710)\nX 6 (#70c714)\nR 6 #70c710"), vec![
+            Issue::at_line(2, "Part 1: Invalid direction"),
+            Issue::at_line(2, "Part 2: Invalid direction"),
+            Issue::at_line(3, "Part 2: Invalid hex string"),
+        ]);
+    }
+
+    /// A plan whose trench is the outline of a staircase with `steps` steps of the given size
+    fn staircase(steps: usize, size: i64) -> Vec<Instruction> {
+        let step = |direction, length| Instruction::new(direction, length).unwrap();
+        let mut instructions = Vec::new();
+        for _ in 0..steps {
+            instructions.push(step(Direction::R, size));
+            instructions.push(step(Direction::D, size));
+        }
+        instructions.push(step(Direction::L, size * steps as i64));
+        instructions.push(step(Direction::U, size * steps as i64));
+        instructions
+    }
+
+    /// Number of cells of the staircase, including its outline
+    fn staircase_area(steps: usize, size: i64) -> i128 {
+        let (n, size) = (steps as i128, size as i128);
+        // Besides the leftmost column of height n * size + 1, there are size columns each of
+        // heights size * k + 1 for k from 1 to n
+        (1..=n).map(|k| size * (size * k + 1)).sum::<i128>() + n * size + 1
+    }
+
+    #[test]
+    fn engines_agree_on_staircases() -> Result<(), Box<dyn Error>> {
+        for (steps, size) in [(1, 1), (3, 2), (5, 7)] {
+            let instructions = staircase(steps, size);
+            assert_eq!(grid_area(&instructions)?, staircase_area(steps, size));
+            assert_eq!(shoelace_area(&instructions)?, staircase_area(steps, size));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gigantic_instructions() -> Result<(), Box<dyn Error>> {
+        let size = (1 << 40) - 3;
+        let instructions = staircase(1000, size);
+        assert_eq!(shoelace_area(&instructions)?, staircase_area(1000, size));
+        let mut puzzle = Puzzle::from_instructions(&staircase(3, size))?;
+        assert_eq!(puzzle.outline_length + puzzle.dig_interior(), staircase_area(3, size));
+        Ok(())
+    }
+
+    #[test]
+    fn gigantic_hex_lengths() -> Result<(), Box<dyn Error>> {
+        let input = "R 1 (#ffffffffff0)\nD 1 (#ffffffffff1)\nL 1 (#ffffffffff2)\nU 1 (#ffffffffff3)";
+        let side = 0xffffffffff_i128;
+        assert_eq!(solve_part2(input)?, (side + 1) * (side + 1));
+        Ok(())
+    }
+
+    #[test]
+    fn open_plan() {
+        assert!(solve_part1("R 6 (#70c710)\nD 5 (#0dc571)").is_err());
+        assert!(solve_part2("R 6 (#70c710)\nD 5 (#0dc571)").is_err());
+    }
+
+    #[test]
+    fn example1_stats() -> Result<(), Box<dyn Error>> {
+        let stats = lagoon_stats(EXAMPLE1.trim(), false)?;
+        assert_eq!(stats, LagoonStats { area: 62, perimeter: 38, interior: 24, bounding_box: (7, 10), corners: 14 });
+        assert_eq!(stats.to_string(), "Area 62 (trench 38, interior 24), bounding box 7 × 10, 14 corners");
+        assert_eq!(lagoon_stats(EXAMPLE1.trim(), true)?.area, 952408144115);
+        Ok(())
+    }
+
+    #[test]
+    fn stats_ignore_straight_joins() -> Result<(), Box<dyn Error>> {
+        let input = "R 2 (#000000)\nR 0 (#000000)\nR 2 (#000000)\nD 1 (#000000)\nL 4 (#000000)\nU 1 (#000000)";
+        let stats = lagoon_stats(input, false)?;
+        assert_eq!((stats.area, stats.bounding_box, stats.corners), (10, (5, 2), 4));
+        Ok(())
+    }
+
+    #[test]
+    fn example1_crosscheck() -> Result<(), Box<dyn Error>> {
+        assert_eq!(crosscheck(EXAMPLE1.trim())?, 62);
+        let comparison = compare_decoders(EXAMPLE1.trim())?;
+        assert_eq!(comparison, DecoderComparison { area1: 62, area2: 952408144115 });
+        assert_eq!(comparison.ratio(), 952408144115.0 / 62.0);
+        Ok(())
+    }
+
+    #[test]
+    fn relative_turns() -> Result<(), Box<dyn Error>> {
+        // The example written as turns, starting out facing up
+        let turns = [("R", 6), ("R", 5), ("R", 2), ("L", 2), ("L", 2), ("R", 2), ("R", 5), ("R", 2), ("L", 1), ("R", 2),
+            ("R", 2), ("L", 3), ("L", 2), ("R", 2)];
+        let plan: Vec<String> = turns.iter().map(|(turn, length)| format!("{turn} {length}")).collect();
+        assert_eq!(plan_area(&plan.join("\n"), Dialect::Relative)?, 62);
+        let instructions = read_plan_part1(&plan.join("\n"), Dialect::Relative)?;
+        let absolute: Vec<String> = instructions.iter().map(|i| format!("{:?} {}", i.direction, i.length)).collect();
+        let expected: Vec<&str> = EXAMPLE1.trim().lines().map(|line| line.rsplit_once(' ').unwrap().0).collect();
+        assert_eq!(absolute, expected);
+        // Colors are allowed but ignored
+        assert_eq!(plan_area("R 1 (#70c710)\nR 1\nR 1\nR 1", Dialect::Relative)?, 4);
+        assert_eq!(plan_area(EXAMPLE1.trim(), Dialect::Absolute)?, 62);
+        let error = |plan: &str| plan_area(plan, Dialect::Relative).err().map(|e| e.to_string());
+        assert_eq!(error("R 1\nU 1").as_deref(), Some("Invalid turn"));
+        assert_eq!(error("R").as_deref(), Some("Invalid number of parts in line"));
+        assert_eq!(error("R 1 (#70c710) x").as_deref(), Some("Invalid number of parts in line"));
+        assert_eq!(error("R 1\nR 1").as_deref(), Some("Dig plan does not return to the start"));
+        Ok(())
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 62);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
-        assert_eq!(result, 952408144115);
+        let result = solve_part2(EXAMPLE1.trim())?;
+        assert_eq!(result, 952408144115_i64);
         Ok(())
     }
 }
@@ -1,17 +1,40 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Part to trace through the workflows of the first file instead of solving the puzzle
+    pub trace_part: Option<String>,
+    /// Count how often the parts of the first file take each workflow, rule and path instead of
+    /// solving the puzzle
+    pub profile: bool,
+    /// Check whether the workflows of both files accept the same parts instead of solving the
+    /// puzzle
+    pub compare: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day19 <file for part 1> <file for part 2> [--trace-part '{x=..,m=..,a=..,s=..}'] [--profile] [--compare]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut trace_part = None;
+        let mut profile = false;
+        let mut compare = false;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--trace-part" => trace_part = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--profile" => profile = true,
+                "--compare" => compare = true,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            trace_part,
+            profile,
+            compare,
         })
     }
 }
-
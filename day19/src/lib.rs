@@ -1,28 +1,50 @@
 pub mod config;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::ops::{Index, IndexMut};
 use std::str;
 use std::str::FromStr;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parse::{self, ParseError, Parser};
+use aoc_core::validate::Issue;
 
-#[derive(Eq, PartialEq)]
+/// A category of ratings. Parts have one rating per category, given in the order of `ALL`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Category { X, M, A, S }
 
+impl Category {
+    const ALL: [Category; 4] = [Category::X, Category::M, Category::A, Category::S];
+    const COUNT: usize = Self::ALL.len();
+
+    fn name(self) -> &'static str {
+        match self {
+            Category::X => "x",
+            Category::M => "m",
+            Category::A => "a",
+            Category::S => "s",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 impl FromStr for Category {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "x" => Ok(Category::X),
-            "m" => Ok(Category::M),
-            "a" => Ok(Category::A),
-            "s" => Ok(Category::S),
-            _ => Err("Invalid category"),
-        }
+        Self::ALL.into_iter().find(|category| category.name() == s).ok_or("Invalid category")
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
     }
 }
 
@@ -33,46 +55,79 @@ enum Rule {
     Jump(String),
 }
 
-impl TryFrom<&str> for Rule {
-    type Error = &'static str;
-
-    fn try_from(string: &str) -> Result<Self, Self::Error> {
-        if string == "A" || string == "R" { return Ok(Rule::Jump(string.to_owned())) }
-        static RULE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(
-            r"^(?<category>[a-z]+)(?<op>[<>])(?<rhs>[0-9]+):(?<next>[a-z]+|[AR])$"
-        ).unwrap());
-        if let Some(captures) = RULE_RE.captures(string) {
-            let lhs = Category::from_str(captures.name("category").unwrap().as_str())?;
-            let op = captures.name("op").unwrap().as_str();
-            let rhs = captures.name("rhs").unwrap().as_str().parse().map_err(|_| "Could not parse rhs")?;
-            let next = captures.name("next").unwrap().as_str().to_owned();
-            match op {
-                "<" => Ok(Rule::Less { lhs, rhs, next }),
-                ">" => Ok(Rule::Greater { lhs, rhs, next }),
-                _ => Err("Invalid rule".into()),
-            }
-        } else {
-            Ok(Rule::Jump(string.to_owned()))
+impl Rule {
+    /// Reads a rule like "a<2006:qkq" or a jump like "rfg".
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let column = parser.column();
+        let name = parser.word("workflow or category", |c| c.is_ascii_alphabetic())?;
+        let less = match parser.peek() {
+            Some('<') => true,
+            Some('>') => false,
+            _ => return Ok(Rule::Jump(name.to_owned())),
+        };
+        let lhs = Category::from_str(name).map_err(|e| ParseError::new(column, e))?;
+        parser.tag(if less { "<" } else { ">" })?;
+        let rhs_column = parser.column();
+        let rhs = parser.number()?;
+        if rhs < 0 {
+            // Ratings are positive, and the rating boxes of part 2 cannot hold negative bounds
+            return Err(ParseError::new(rhs_column, format!("Negative threshold {rhs}")));
         }
+        parser.tag(":")?;
+        let next = parser.word("workflow", |c| c.is_ascii_alphabetic())?.to_owned();
+        Ok(if less { Rule::Less { lhs, rhs, next } } else { Rule::Greater { lhs, rhs, next } })
     }
 }
 
-struct Part {
-    ratings: [i32; 4],
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Greater { lhs, rhs, next } => write!(f, "{lhs}>{rhs}:{next}"),
+            Rule::Less { lhs, rhs, next } => write!(f, "{lhs}<{rhs}:{next}"),
+            Rule::Jump(next) => write!(f, "{next}"),
+        }
+    }
+}
+
+impl Rule {
+    /// Returns the workflow to continue with if the part satisfies the rule.
+    fn apply(&self, part: &Part) -> Option<&str> {
+        match self {
+            Rule::Jump(next) => Some(next),
+            Rule::Greater { lhs, rhs, next } => (part.get_rating(*lhs) > *rhs).then_some(next),
+            Rule::Less { lhs, rhs, next } => (part.get_rating(*lhs) < *rhs).then_some(next),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Part {
+    ratings: [i32; Category::COUNT],
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ratings: Vec<String> = Category::ALL.iter().map(|&category| format!("{category}={}", self.get_rating(category))).collect();
+        write!(f, "{{{}}}", ratings.join(","))
+    }
+}
+
+impl FromStr for Part {
+    type Err = ParseError;
+
+    /// Reads a part of the form "{x=787,m=2655,a=1222,s=2876}".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Puzzle::read_part(s)
+    }
 }
 
 impl Part {
-    fn sum_ratings(&self) -> i32 {
-        self.ratings.iter().sum()
+    fn sum_ratings(&self) -> i64 {
+        self.ratings.iter().map(|&rating| i64::from(rating)).sum()
     }
 
-    fn get_rating(&self, category: &Category) -> i32 {
-        match category {
-            Category::X => self.ratings[0],
-            Category::M => self.ratings[1],
-            Category::A => self.ratings[2],
-            Category::S => self.ratings[3],
-        }
+    fn get_rating(&self, category: Category) -> i32 {
+        self.ratings[category.index()]
     }
 }
 
@@ -81,198 +136,720 @@ struct Workflow {
     rules: Vec<Rule>,
 }
 
-type WorkflowMap = HashMap<String, Workflow>;
+impl fmt::Display for Workflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules: Vec<String> = self.rules.iter().map(Rule::to_string).collect();
+        write!(f, "{}{{{}}}", self.name, rules.join(","))
+    }
+}
+
+/// Workflows by name, iterated in the order in which they were inserted, so that everything
+/// derived from them is the same in every run
+#[derive(Default)]
+struct WorkflowMap {
+    workflows: Vec<Workflow>,
+    index: HashMap<String, usize>,
+}
+
+impl WorkflowMap {
+    /// Adds the workflow, replacing the one with the same name at its position if there is one.
+    fn insert(&mut self, workflow: Workflow) {
+        match self.index.get(&workflow.name) {
+            Some(&i) => self.workflows[i] = workflow,
+            None => {
+                self.index.insert(workflow.name.clone(), self.workflows.len());
+                self.workflows.push(workflow);
+            },
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&Workflow> {
+        self.index.get(name).map(|&i| &self.workflows[i])
+    }
+
+    fn contains_key(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// The workflows in the order in which they were inserted
+    fn values(&self) -> impl Iterator<Item = &Workflow> {
+        self.workflows.iter()
+    }
+}
+
+const MAX_RATING: u64 = 4000;
+
+/// The parts whose rating of each category lies between inclusive lower and upper bounds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct RatingBox {
+    bounds: [[u64; 2]; Category::COUNT],
+}
+
+impl RatingBox {
+    /// The box containing all parts
+    fn full() -> Self {
+        Self { bounds: [[1, MAX_RATING]; Category::COUNT] }
+    }
+
+    /// A box containing no parts
+    fn empty() -> Self {
+        Self { bounds: [[1, 0]; Category::COUNT] }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bounds.iter().any(|[lo, hi]| lo > hi)
+    }
+
+    /// Number of parts in the box
+    fn volume(&self) -> u64 {
+        if self.is_empty() { return 0 }
+        self.bounds.iter().map(|[lo, hi]| hi - lo + 1).product()
+    }
+
+    /// Splits the box into the parts whose rating of the category is less than (or greater than,
+    /// if `less` is false) `rhs` and the remaining ones. Either may be empty.
+    fn split(&self, category: Category, less: bool, rhs: u64) -> (Self, Self) {
+        let (mut matched, mut rest) = (*self, *self);
+        let [lo, hi] = &mut matched[category];
+        let [rest_lo, rest_hi] = &mut rest[category];
+        if less {
+            *hi = (*hi).min(rhs.saturating_sub(1));
+            *rest_lo = (*rest_lo).max(rhs);
+        } else {
+            *lo = (*lo).max(rhs + 1);
+            *rest_hi = (*rest_hi).min(rhs);
+        }
+        (matched, rest)
+    }
+
+    /// The parts in both boxes
+    fn intersection(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for (bounds, [other_lo, other_hi]) in result.bounds.iter_mut().zip(other.bounds) {
+            *bounds = [bounds[0].max(other_lo), bounds[1].min(other_hi)];
+        }
+        result
+    }
+
+    /// Splits the parts of this box that are not in `other` into disjoint boxes.
+    fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = self.intersection(other);
+        if overlap.is_empty() {
+            return vec![*self];
+        }
+        // Cut off the slices below and above the overlap, one category after the other, until only
+        // the overlap remains
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for category in Category::ALL {
+            let [lo, hi] = overlap[category];
+            let (below, remaining) = rest.split(category, true, lo);
+            let (above, remaining) = remaining.split(category, false, hi);
+            pieces.extend([below, above].into_iter().filter(|piece| !piece.is_empty()));
+            rest = remaining;
+        }
+        pieces
+    }
+
+    /// The part with the lowest rating of each category in the box
+    fn corner(&self) -> Part {
+        Part { ratings: self.bounds.map(|[lo, _]| lo as i32) }
+    }
+}
+
+impl Index<Category> for RatingBox {
+    type Output = [u64; 2];
+
+    fn index(&self, category: Category) -> &Self::Output {
+        &self.bounds[category.index()]
+    }
+}
+
+impl IndexMut<Category> for RatingBox {
+    fn index_mut(&mut self, category: Category) -> &mut Self::Output {
+        &mut self.bounds[category.index()]
+    }
+}
+
+/// Probability distribution of a single rating over the values from 1 to 4000
+pub struct Distribution {
+    /// Element r is the probability that the rating is at most r
+    cumulative: Vec<f64>,
+}
+
+impl Distribution {
+    pub fn uniform() -> Self {
+        Self::from_weights(&[1.0; MAX_RATING as usize]).unwrap()
+    }
+
+    /// Creates a distribution where the probability of rating r is proportional to
+    /// `weights[r - 1]`.
+    pub fn from_weights(weights: &[f64]) -> Result<Self, &'static str> {
+        if weights.len() != MAX_RATING as usize { return Err("Need exactly one weight per rating") }
+        if weights.iter().any(|&w| w < 0.0 || !w.is_finite()) { return Err("Invalid weight") }
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 { return Err("Weights must not all be zero") }
+        let mut cumulative = Vec::with_capacity(weights.len() + 1);
+        cumulative.push(0.0);
+        let mut sum = 0.0;
+        for w in weights {
+            sum += w;
+            cumulative.push(sum / total);
+        }
+        Ok(Self { cumulative })
+    }
+
+    /// Probability that the rating is between `lo` and `hi` (inclusive)
+    fn probability(&self, lo: u64, hi: u64) -> f64 {
+        self.cumulative[hi as usize] - self.cumulative[lo as usize - 1]
+    }
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
+
+/// A node of the decision tree that the workflows are compiled to
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Node {
+    Accept,
+    Reject,
+    /// Continues with node `then` if the rating of the category is less than (or greater than, if
+    /// `less` is false) `rhs`, and with node `otherwise` if not
+    Test { category: usize, less: bool, rhs: i32, then: usize, otherwise: usize },
+}
+
+/// Where a rule sends a part before workflow names are resolved to node indices
+#[derive(Clone, Copy)]
+enum Target<'a> {
+    Node(usize),
+    Workflow(&'a str),
+}
+
+/// The workflows flattened into a decision tree that refers to nodes by index instead of looking
+/// up workflows by name
+struct DecisionTree {
+    nodes: Vec<Node>,
+    start: usize,
+}
+
+impl DecisionTree {
+    const ACCEPT: usize = 0;
+    const REJECT: usize = 1;
+
+    fn compile<'a>(workflows: &'a WorkflowMap) -> Result<Self, String> {
+        let mut nodes = vec![Node::Accept, Node::Reject];
+        // Tests with unresolved targets, and the entry point of each workflow
+        let mut tests: Vec<(usize, Target<'a>, Target<'a>)> = Vec::new();
+        let mut entries: HashMap<&'a str, Target<'a>> = HashMap::from([
+            ("A", Target::Node(Self::ACCEPT)), ("R", Target::Node(Self::REJECT)),
+        ]);
+        for workflow in workflows.values().filter(|w| w.name != "A" && w.name != "R") {
+            // The part continues at `entry` unless it is sent elsewhere by an earlier rule, so
+            // compile the rules from last to first
+            let mut entry = None;
+            for rule in workflow.rules.iter().rev() {
+                entry = Some(match rule {
+                    Rule::Jump(next) => Target::Workflow(next),
+                    Rule::Greater { lhs, rhs, next } | Rule::Less { lhs, rhs, next } => {
+                        let otherwise = entry.ok_or(format!("Workflow {} does not end with a jump", workflow.name))?;
+                        let less = matches!(rule, Rule::Less { .. });
+                        nodes.push(Node::Test { category: lhs.index(), less, rhs: *rhs, then: 0, otherwise: 0 });
+                        tests.push((nodes.len() - 1, Target::Workflow(next), otherwise));
+                        Target::Node(nodes.len() - 1)
+                    },
+                });
+            }
+            entries.insert(&workflow.name, entry.ok_or(format!("Workflow {} has no rules", workflow.name))?);
+        }
+        // Follow chains of workflows consisting of a single jump
+        let resolve = |mut target: Target<'a>| -> Result<usize, String> {
+            let mut seen = HashSet::new();
+            loop {
+                match target {
+                    Target::Node(i) => return Ok(i),
+                    Target::Workflow(name) => {
+                        if !seen.insert(name) { return Err(format!("Workflows jump to each other forever at {name}")) }
+                        target = *entries.get(name).ok_or(format!("Unknown workflow {name}"))?;
+                    },
+                }
+            }
+        };
+        for &(i, then_target, otherwise_target) in &tests {
+            let (then_index, otherwise_index) = (resolve(then_target)?, resolve(otherwise_target)?);
+            if let Node::Test { then, otherwise, .. } = &mut nodes[i] {
+                (*then, *otherwise) = (then_index, otherwise_index);
+            }
+        }
+        let start = resolve(Target::Workflow("in"))?;
+        Ok(Self { nodes, start })
+    }
+
+    fn accepts(&self, part: &Part) -> bool {
+        let mut i = self.start;
+        loop {
+            match self.nodes[i] {
+                Node::Accept => return true,
+                Node::Reject => return false,
+                Node::Test { category, less, rhs, then, otherwise } => {
+                    let rating = part.ratings[category];
+                    let matched = if less { rating < rhs } else { rating > rhs };
+                    i = if matched { then } else { otherwise };
+                },
+            }
+        }
+    }
+}
+
+/// A step taken while sending a part through the workflows
+#[derive(Debug, Eq, PartialEq)]
+pub enum TraceStep {
+    /// Entering the workflow with the given name
+    Workflow(String),
+    /// Evaluating a rule of the current workflow
+    Rule { rule: String, matched: bool },
+    /// Reaching "A" or "R"
+    Verdict { accepted: bool },
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceStep::Workflow(name) => write!(f, "Workflow {name}"),
+            TraceStep::Rule { rule, matched: true } => write!(f, "  {rule}: matched"),
+            TraceStep::Rule { rule, matched: false } => write!(f, "  {rule}: not matched"),
+            TraceStep::Verdict { accepted: true } => write!(f, "Accepted"),
+            TraceStep::Verdict { accepted: false } => write!(f, "Rejected"),
+        }
+    }
+}
+
+/// How many parts a rule was evaluated on and how many of them satisfied it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RuleProfile {
+    pub rule: String,
+    pub evaluated: usize,
+    pub matched: usize,
+}
+
+/// How many parts entered a workflow, and how they fared with each of its rules
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkflowProfile {
+    pub name: String,
+    pub visits: usize,
+    pub rules: Vec<RuleProfile>,
+}
+
+/// How often the parts of part 1 traverse each workflow, rule and path
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Profile {
+    /// All workflows from the most to the least visited, with ties ordered by name
+    pub workflows: Vec<WorkflowProfile>,
+    /// Each path taken by some part as the visited workflows followed by "A" or "R", with the
+    /// number of parts taking it, from the most to the least common, with ties ordered by path
+    pub paths: Vec<(Vec<String>, usize)>,
+}
+
+impl Profile {
+    /// Number of paths shown by `Display`
+    const PATHS_SHOWN: usize = 10;
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for workflow in &self.workflows {
+            writeln!(f, "Workflow {}: {} parts", workflow.name, workflow.visits)?;
+            for rule in &workflow.rules {
+                writeln!(f, "  {}: {} evaluated, {} matched", rule.rule, rule.evaluated, rule.matched)?;
+            }
+        }
+        writeln!(f, "Hottest paths:")?;
+        for (path, count) in self.paths.iter().take(Self::PATHS_SHOWN) {
+            writeln!(f, "  {count} parts: {}", path.join(" -> "))?;
+        }
+        if self.paths.len() > Self::PATHS_SHOWN {
+            writeln!(f, "  ... and {} more paths", self.paths.len() - Self::PATHS_SHOWN)?;
+        }
+        Ok(())
+    }
+}
 
-struct Puzzle {
+pub struct Puzzle {
     workflows: WorkflowMap,
+    decision_tree: DecisionTree,
     parts: Vec<Part>,
 }
 
 impl Puzzle {
-    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
-        let mut lines = input.lines();
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let mut lines = input.lines().enumerate();
         let workflows = Self::read_workflows(&mut lines)?;
         let parts = Self::read_parts(&mut lines)?;
-        Ok(Self { workflows, parts })
+        let decision_tree = DecisionTree::compile(&workflows)?;
+        Ok(Self { workflows, decision_tree, parts })
     }
 
-    fn read_workflows(lines: &mut str::Lines<'_>) -> Result<WorkflowMap, Box<dyn Error>> {
-        let mut workflows = WorkflowMap::new();
-        for line in lines {
+    fn read_workflows<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>) -> Result<WorkflowMap, Box<dyn Error>> {
+        let mut workflows = WorkflowMap::default();
+        for (i, line) in lines {
             if line.is_empty() { break }
-            let (name, rest) = line.split_once('{').ok_or("Invalid workflow")?;
-            let mut chars = rest.chars();
-            if chars.next_back() != Some('}') { return Err("Invalid workflow".into()) }
-            let rules = Self::read_rules(chars.as_str())?;
-            let name = name.to_owned();
-            workflows.insert(name.clone(), Workflow { name, rules });
+            let workflow = Self::read_workflow(line).map_err(|e| e.on_line(i + 1))?;
+            workflows.insert(workflow);
         }
         if !workflows.contains_key("in") { return Err("No workflow named 'in'".into()) }
-        workflows.insert(String::from("A"), Workflow { name: String::from("A"), rules: Vec::new() });
-        workflows.insert(String::from("R"), Workflow { name: String::from("R"), rules: Vec::new() });
+        workflows.insert(Workflow { name: String::from("A"), rules: Vec::new() });
+        workflows.insert(Workflow { name: String::from("R"), rules: Vec::new() });
         Ok(workflows)
     }
 
-    fn read_rules(string: &str) -> Result<Vec<Rule>, &str> {
-        string.split(',').map(|r| Rule::try_from(r)).collect::<Result<_,_>>()
+    /// Reads a workflow like "px{a<2006:qkq,m>2090:A,rfg}".
+    fn read_workflow(line: &str) -> Result<Workflow, ParseError> {
+        parse::parse_all(line, |parser| {
+            let name = parser.word("workflow name", |c| c.is_ascii_lowercase())?.to_owned();
+            parser.tag("{")?;
+            let rules = parser.separated(",", Rule::parse)?;
+            parser.tag("}")?;
+            Ok(Workflow { name, rules })
+        })
     }
 
-    fn read_parts(lines: &mut str::Lines<'_>) -> Result<Vec<Part>, Box<dyn Error>> {
+    fn read_parts<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>) -> Result<Vec<Part>, Box<dyn Error>> {
         let mut parts = Vec::new();
-        for line in lines {
-            static PART_RE: Lazy<Regex> = Lazy::new(|| Regex::new(
-                r"^\{x=([0-9]+),m=([0-9]+),a=([0-9]+),s=([0-9]+)\}$"
-            ).unwrap());
-            let captures = PART_RE.captures(line).ok_or("Failed to parse part")?;
-            let ratings: [i32; 4] = (1..=4)
-                .map(|i| captures.get(i).unwrap().as_str().parse())
-                .collect::<Result<Vec<_>,_>>()?
-                .try_into().unwrap();
-            parts.push(Part { ratings });
+        for (i, line) in lines {
+            parts.push(Self::read_part(line).map_err(|e| e.on_line(i + 1))?);
         }
         Ok(parts)
     }
 
-    fn part_accepted(&self, part: &Part) -> bool {
+    fn read_part(line: &str) -> Result<Part, ParseError> {
+        parse::parse_all(line, |parser| {
+            parser.tag("{")?;
+            let column = parser.column();
+            let fields = parser.tagged_fields(",", "=", |parser| parser.number())?;
+            parser.tag("}")?;
+            let names: Vec<&str> = fields.iter().map(|&(name, _)| name).collect();
+            let expected = Category::ALL.map(Category::name);
+            if names != expected {
+                let (last, rest) = expected.split_last().unwrap();
+                return Err(ParseError::new(column, format!("Expected ratings {} and {last} in this order", rest.join(", "))));
+            }
+            let mut ratings = [0; Category::COUNT];
+            for (rating, (_, value)) in ratings.iter_mut().zip(fields) {
+                *rating = value;
+            }
+            Ok(Part { ratings })
+        })
+    }
+
+    /// Sends the part through the workflows, starting at "in", and calls `visit` for each step.
+    /// Returns whether the part is accepted.
+    fn process(&self, part: &Part, mut visit: impl FnMut(TraceStep)) -> bool {
         let mut workflow = self.workflows.get("in").unwrap();
-        // FIXME: Code duplication
         loop {
-            if workflow.name == "A" { return true }
-            if workflow.name == "R" { return false }
+            if workflow.name == "A" || workflow.name == "R" {
+                let accepted = workflow.name == "A";
+                visit(TraceStep::Verdict { accepted });
+                return accepted;
+            }
+            visit(TraceStep::Workflow(workflow.name.clone()));
             for rule in &workflow.rules {
-                match rule {
-                    Rule::Jump(next) => {
-                        workflow = self.workflows.get(next).unwrap_or_else(|| panic!("Unknown workflow {next}"));
-                        break;
+                let next = rule.apply(part);
+                visit(TraceStep::Rule { rule: rule.to_string(), matched: next.is_some() });
+                if let Some(next) = next {
+                    workflow = self.workflows.get(next).unwrap_or_else(|| panic!("Unknown workflow {next}"));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn part_accepted(&self, part: &Part) -> bool {
+        self.decision_tree.accepts(part)
+    }
+
+    /// Like `part_accepted`, but looks up the workflows by name as it goes.
+    #[cfg(test)]
+    fn part_accepted_interpreted(&self, part: &Part) -> bool {
+        self.process(part, |_| ())
+    }
+
+    /// The workflows in the order of the input, each written as in the input, like
+    /// "px{a<2006:qkq,m>2090:A,rfg}"
+    pub fn workflows(&self) -> impl Iterator<Item = String> + '_ {
+        self.workflows.values().filter(|w| w.name != "A" && w.name != "R").map(Workflow::to_string)
+    }
+
+    /// Records every workflow the part visits, every rule evaluated on it and whether the part
+    /// satisfied it, and finally whether the part is accepted.
+    pub fn trace_part(&self, part: &Part) -> Vec<TraceStep> {
+        let mut steps = Vec::new();
+        self.process(part, |step| steps.push(step));
+        steps
+    }
+
+    /// Sends every part through the workflows and counts how often each workflow, rule and path
+    /// is taken.
+    pub fn profile(&self) -> Profile {
+        let mut workflows: HashMap<&str, WorkflowProfile> = self.workflows.values()
+            .filter(|workflow| workflow.name != "A" && workflow.name != "R")
+            .map(|workflow| (workflow.name.as_str(), WorkflowProfile {
+                name: workflow.name.clone(),
+                visits: 0,
+                rules: workflow.rules.iter().map(|rule| RuleProfile { rule: rule.to_string(), evaluated: 0, matched: 0 }).collect(),
+            }))
+            .collect();
+        let mut paths: HashMap<Vec<String>, usize> = HashMap::new();
+        for part in &self.parts {
+            let mut path = Vec::new();
+            let mut rule_index = 0;
+            self.process(part, |step| match step {
+                TraceStep::Workflow(name) => {
+                    path.push(name);
+                    rule_index = 0;
+                },
+                TraceStep::Rule { matched, .. } => {
+                    if let Some(workflow) = path.last().and_then(|name| workflows.get_mut(name.as_str())) {
+                        let rule = &mut workflow.rules[rule_index];
+                        rule.evaluated += 1;
+                        rule.matched += usize::from(matched);
                     }
-                    Rule::Greater { lhs, rhs, next } => {
-                        if part.get_rating(lhs) > *rhs {
-                            workflow = self.workflows.get(next).unwrap_or_else(|| panic!("Unknown workflow {next}"));
-                            break;
-                        }
-                    },
-                    Rule::Less { lhs, rhs, next } => {
-                        if part.get_rating(lhs) < *rhs {
-                            workflow = self.workflows.get(next).unwrap_or_else(|| panic!("Unknown workflow {next}"));
-                            break;
-                        }
-                    },
+                    rule_index += 1;
+                },
+                TraceStep::Verdict { accepted } => path.push(String::from(if accepted { "A" } else { "R" })),
+            });
+            for name in &path {
+                if let Some(workflow) = workflows.get_mut(name.as_str()) {
+                    workflow.visits += 1;
                 }
             }
+            *paths.entry(path).or_default() += 1;
         }
+        let mut workflows: Vec<WorkflowProfile> = workflows.into_values().collect();
+        workflows.sort_by(|a, b| b.visits.cmp(&a.visits).then_with(|| a.name.cmp(&b.name)));
+        let mut paths: Vec<(Vec<String>, usize)> = paths.into_iter().collect();
+        paths.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+        Profile { workflows, paths }
     }
 
-    fn sum_accepted(&self) -> i32 {
-        self.parts.iter().filter(|p| self.part_accepted(p)).map(|p| p.sum_ratings()).sum()
+    /// Sum of the ratings of all accepted parts, failing if it does not fit into 64 bits
+    fn sum_accepted(&self) -> Result<i64, &'static str> {
+        self.parts.iter().filter(|p| self.part_accepted(p))
+            .try_fold(0i64, |sum, p| sum.checked_add(p.sum_ratings()))
+            .ok_or("Sum of ratings overflow")
     }
 
-    fn possibilities(&self) -> u64 {
+    /// Computes the boxes of the rating space whose parts are accepted. The boxes are pairwise
+    /// disjoint.
+    fn accepted_boxes(&self) -> Vec<RatingBox> {
         let start = self.workflows.get("in").expect("No 'in' workflow");
-        let mut stack = vec![(start, [1u64, 4000u64], [1u64, 4000u64], [1u64, 4000u64], [1u64, 4000u64])];
-        let mut sum = 0;
-        while let Some((workflow, mut x, mut m, mut a, mut s)) = stack.pop() {
+        let mut stack = vec![(start, RatingBox::full())];
+        let mut boxes = Vec::new();
+        while let Some((workflow, mut rest)) = stack.pop() {
             if workflow.name == "R" { continue }
             if workflow.name == "A" {
-                sum += (x[1] - x[0] + 1) * (m[1] - m[0] + 1) * (a[1] - a[0] + 1) * (s[1] - s[0] + 1);
+                boxes.push(rest);
                 continue;
             }
             for rule in &workflow.rules {
-                match rule {
-                    Rule::Jump(next) => {
-                        stack.push((self.workflows.get(next).expect("Unknown workflow"), x, m, a, s));
-                    },
-                    // FIXME: Code duplication
-                    Rule::Greater { lhs, rhs, next } => {
-                        let rhs = *rhs as u64;
-                        let mut xn = x;
-                        let mut mn = m;
-                        let mut an = a;
-                        let mut sn = s;
-                        match lhs {
-                            Category::X => {
-                                xn[0] = rhs + 1;
-                                x[1] = rhs;
-                            },
-                            Category::M => {
-                                mn[0] = rhs + 1;
-                                m[1] = rhs;
-                            },
-                            Category::A => {
-                                an[0] = rhs + 1;
-                                a[1] = rhs;
-                            },
-                            Category::S => {
-                                sn[0] = rhs + 1;
-                                s[1] = rhs;
-                            },
-                        }
-                        if xn[1] >= xn[0] && mn[1] >= mn[0] && an[1] >= an[0] && sn[1] >= sn[0] {
-                            stack.push((self.workflows.get(next).expect("Unknown workflow"), xn, mn, an, sn))
-                        }
-                    },
-                    Rule::Less { lhs, rhs, next } => {
-                        let rhs = *rhs as u64;
-                        let mut xn = x;
-                        let mut mn = m;
-                        let mut an = a;
-                        let mut sn = s;
-                        match lhs {
-                            Category::X => {
-                                xn[1] = rhs - 1;
-                                x[0] = rhs;
-                            },
-                            Category::M => {
-                                mn[1] = rhs - 1;
-                                m[0] = rhs;
-                            },
-                            Category::A => {
-                                an[1] = rhs - 1;
-                                a[0] = rhs;
-                            },
-                            Category::S => {
-                                sn[1] = rhs - 1;
-                                s[0] = rhs;
-                            },
-                        }
-                        if xn[1] >= xn[0] && mn[1] >= mn[0] && an[1] >= an[0] && sn[1] >= sn[0] {
-                            stack.push((self.workflows.get(next).expect("Unknown workflow"), xn, mn, an, sn))
-                        }
+                let (matched, next) = match rule {
+                    Rule::Jump(next) => (std::mem::replace(&mut rest, RatingBox::empty()), next),
+                    Rule::Greater { lhs, rhs, next } | Rule::Less { lhs, rhs, next } => {
+                        let (matched, remaining) = rest.split(*lhs, matches!(rule, Rule::Less { .. }), *rhs as u64);
+                        rest = remaining;
+                        (matched, next)
                     },
+                };
+                if !matched.is_empty() {
+                    stack.push((self.workflows.get(next).expect("Unknown workflow"), matched));
                 }
+                if rest.is_empty() { break }
             }
         }
-        sum
+        boxes
+    }
+
+    fn possibilities(&self) -> u64 {
+        self.accepted_boxes().iter().map(RatingBox::volume).sum()
+    }
+
+    /// Computes the probability that a random part is accepted, where the rating of each category
+    /// (in the order x, m, a, s) is drawn independently from the respective distribution.
+    pub fn acceptance_probability(&self, distributions: [Distribution; Category::COUNT]) -> f64 {
+        self.accepted_boxes().iter()
+            .map(|rating_box| Category::ALL.iter()
+                .zip(&distributions)
+                .map(|(&category, distribution)| {
+                    let [lo, hi] = rating_box[category];
+                    distribution.probability(lo, hi)
+                })
+                .product::<f64>())
+            .sum()
     }
 }
 
-fn part1(input: &str) -> Result<i32, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    Ok(puzzle.sum_accepted())
+/// The boxes of parts in any of `boxes` but in none of `others`, which are pairwise disjoint if
+/// `boxes` are.
+fn difference(boxes: &[RatingBox], others: &[RatingBox]) -> Vec<RatingBox> {
+    boxes.iter().flat_map(|rating_box| {
+        others.iter().fold(vec![*rating_box], |pieces, other| {
+            pieces.iter().flat_map(|piece| piece.subtract(other)).collect()
+        })
+    }).collect()
 }
 
-fn part2(input: &str) -> Result<u64, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    Ok(puzzle.possibilities())
+/// How the parts accepted by two sets of workflows differ
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkflowComparison {
+    /// Number of parts with ratings from 1 to 4000 that only the first or only the second
+    /// workflows accept
+    pub only_first: u64,
+    pub only_second: u64,
+    /// A part that only one of the workflows accepts, with whether it is the first one, or `None`
+    /// if both accept the same parts
+    pub counterexample: Option<(Part, bool)>,
 }
 
-pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
-    println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+impl WorkflowComparison {
+    pub fn is_equivalent(&self) -> bool {
+        self.counterexample.is_none()
+    }
+}
+
+impl fmt::Display for WorkflowComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((part, first)) = &self.counterexample else {
+            return write!(f, "Both workflows accept the same parts");
+        };
+        writeln!(f, "Parts accepted only by the first workflows: {}", self.only_first)?;
+        writeln!(f, "Parts accepted only by the second workflows: {}", self.only_second)?;
+        write!(f, "Counterexample: {part} is accepted only by the {} workflows", if *first { "first" } else { "second" })
+    }
+}
+
+/// Decides whether the workflows of two inputs accept the same parts by comparing the boxes of the
+/// rating space they accept, and finds a part accepted by only one of them if not. The parts of the
+/// inputs are ignored, so they may consist of workflows only.
+pub fn compare_workflows(first: &str, second: &str) -> Result<WorkflowComparison, Box<dyn Error>> {
+    let first = Puzzle::from_input(first).map_err(|e| format!("First workflows: {e}"))?.accepted_boxes();
+    let second = Puzzle::from_input(second).map_err(|e| format!("Second workflows: {e}"))?.accepted_boxes();
+    let only_first = difference(&first, &second);
+    let only_second = difference(&second, &first);
+    let counterexample = only_first.first().map(|rating_box| (rating_box.corner(), true))
+        .or_else(|| only_second.first().map(|rating_box| (rating_box.corner(), false)));
+    Ok(WorkflowComparison {
+        only_first: only_first.iter().map(RatingBox::volume).sum(),
+        only_second: only_second.iter().map(RatingBox::volume).sum(),
+        counterexample,
+    })
+}
+
+/// Both parts, solved from the same parsed workflows and parts
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_accepted()?.into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.possibilities().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports workflows and parts that cannot be read, duplicate workflow names, jumps to unknown
+/// workflows and a missing workflow named "in".
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::from(["A", "R"]);
+    let mut jumps = Vec::new();
+    let mut lines = input.lines().enumerate();
+    for (i, line) in lines.by_ref() {
+        if line.is_empty() { break }
+        match Puzzle::read_workflow(line) {
+            Ok(workflow) => {
+                let name = line.split_once('{').unwrap().0;
+                if !names.insert(name) {
+                    issues.push(Issue::at_line(i + 1, format!("Duplicate workflow {name}")));
+                }
+                for rule in workflow.rules {
+                    let (Rule::Jump(next) | Rule::Greater { next, .. } | Rule::Less { next, .. }) = rule;
+                    jumps.push((i + 1, next));
+                }
+            },
+            Err(e) => issues.push(Issue::at(i + 1, e.column, e.message)),
+        }
+    }
+    for (line_nr, next) in jumps {
+        if !names.contains(next.as_str()) {
+            issues.push(Issue::at_line(line_nr, format!("Unknown workflow {next}")));
+        }
+    }
+    if !names.contains("in") {
+        issues.push(Issue::new("No workflow named 'in'"));
+    }
+    for (i, line) in lines {
+        if let Err(e) = Puzzle::read_part(line) {
+            issues.push(Issue::at(i + 1, e.column, e.message));
+        }
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if config.compare {
+        println!("Comparing the workflows in {} and {}", config.file_path1, config.file_path2);
+        let first = fs::read_to_string(config.file_path1)?;
+        let second = fs::read_to_string(config.file_path2)?;
+        println!("{}", compare_workflows(&first, &second)?);
+        return Ok(());
+    }
+    if config.profile {
+        println!("Profiling the workflows in {}", config.file_path1);
+        let contents = fs::read_to_string(config.file_path1)?;
+        print!("{}", Puzzle::from_input(&contents)?.profile());
+        return Ok(());
+    }
+    if let Some(part) = config.trace_part {
+        println!("Tracing part {part} through the workflows in {}", config.file_path1);
+        let contents = fs::read_to_string(config.file_path1)?;
+        let puzzle = Puzzle::from_input(&contents)?;
+        for step in puzzle.trace_part(&part.parse()?) {
+            println!("{step}");
+        }
+        return Ok(());
+    }
 
-    Ok(())
+    answer::run_parts::<Day>(&config.file_path1, &config.file_path2)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use aoc_core::rng::{Rng, SeededRng};
+
     const EXAMPLE1: &str = "
 px{a<2006:qkq,m>2090:A,rfg}
 pv{a>1716:R,A}
@@ -293,17 +870,207 @@ hdj{m>838:A,pv}
 {x=2127,m=1623,a=2188,s=1013}
 ";
 
+    /// Generates parts with pseudo-random ratings between 1 and 4000.
+    fn random_parts(n: usize) -> Vec<Part> {
+        let mut rng = SeededRng::new(42);
+        let mut random = move || rng.below(4000) as i32 + 1;
+        (0..n).map(|_| Part { ratings: [(); Category::COUNT].map(|_| random()) }).collect()
+    }
+
+    #[test]
+    fn decision_tree_matches_interpreter() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        for part in puzzle.parts.iter().chain(&random_parts(10_000)) {
+            assert_eq!(puzzle.part_accepted(part), puzzle.part_accepted_interpreted(part));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decision_tree_errors() {
+        let error = |workflows: &str| Puzzle::from_input(&format!("{workflows}\n\n")).err().unwrap().to_string();
+        assert_eq!(error("in{a<5:A,foo}"), "Unknown workflow foo");
+        assert_eq!(error("in{ab}\nab{cd}\ncd{ab}"), "Workflows jump to each other forever at ab");
+        assert_eq!(error("in{a<5:A}"), "Workflow in does not end with a jump");
+        assert_eq!(error("in{x<-5:A,R}"), "Line 1, column 6: Negative threshold -5");
+        assert_eq!(error("in{x>-1:A,R}"), "Line 1, column 6: Negative threshold -1");
+        assert!(solve_part2("in{x>-1:A,R}\n\n{x=1,m=1,a=1,s=1}").is_err());
+        // A chain of plain jumps is followed to the first test
+        assert!(Puzzle::from_input("in{ab}\nab{cd}\ncd{x>10:A,R}\n\n").is_ok());
+    }
+
+    #[test]
+    fn workflows_in_input_order() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let workflows: Vec<String> = puzzle.workflows().collect();
+        assert_eq!(workflows, EXAMPLE1.trim().lines().take_while(|line| !line.is_empty()).collect::<Vec<_>>());
+        // Of several broken workflows, the first one is reported
+        let error = Puzzle::from_input("in{zz}\nzz{a<5:A}\nyy{m<5:R}\nxx{s<5:R}\n\n").err().map(|e| e.to_string());
+        assert_eq!(error.as_deref(), Some("Workflow zz does not end with a jump"));
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_decision_tree() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let parts = random_parts(1_000_000);
+        let start = std::time::Instant::now();
+        let interpreted = parts.iter().filter(|p| puzzle.part_accepted_interpreted(p)).count();
+        println!("Interpreter: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let compiled = parts.iter().filter(|p| puzzle.part_accepted(p)).count();
+        println!("Decision tree: {:?}", start.elapsed());
+        assert_eq!(interpreted, compiled);
+        Ok(())
+    }
+
+    #[test]
+    fn trace_example_part() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let steps = puzzle.trace_part(&"{x=787,m=2655,a=1222,s=2876}".parse()?);
+        let rule = |rule: &str, matched| TraceStep::Rule { rule: rule.to_owned(), matched };
+        assert_eq!(steps, vec![
+            TraceStep::Workflow("in".to_owned()),
+            rule("s<1351:px", false),
+            rule("qqz", true),
+            TraceStep::Workflow("qqz".to_owned()),
+            rule("s>2770:qs", true),
+            TraceStep::Workflow("qs".to_owned()),
+            rule("s>3448:A", false),
+            rule("lnx", true),
+            TraceStep::Workflow("lnx".to_owned()),
+            rule("m>1548:A", true),
+            TraceStep::Verdict { accepted: true },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn profile_example() -> Result<(), Box<dyn Error>> {
+        let profile = Puzzle::from_input(EXAMPLE1.trim())?.profile();
+        let visits: Vec<(&str, usize)> = profile.workflows.iter().map(|w| (w.name.as_str(), w.visits)).collect();
+        assert_eq!(visits, [
+            ("in", 5), ("px", 3), ("qqz", 2), ("rfg", 2), ("crn", 1), ("gd", 1), ("hdj", 1), ("lnx", 1), ("pv", 1),
+            ("qkq", 1), ("qs", 1),
+        ]);
+        let rule = |rule: &str, evaluated, matched| RuleProfile { rule: rule.to_owned(), evaluated, matched };
+        assert_eq!(profile.workflows[1].rules, [rule("a<2006:qkq", 3, 1), rule("m>2090:A", 2, 0), rule("rfg", 2, 2)]);
+        assert_eq!(profile.paths.len(), 5);
+        assert_eq!(profile.paths[0], (["in", "px", "qkq", "crn", "R"].map(String::from).to_vec(), 1));
+        assert!(profile.to_string().contains("  1 parts: in -> qqz -> qs -> lnx -> A\n"));
+        // Workflows that no part reaches are still listed
+        let profile = Puzzle::from_input("in{A}\nzz{R}\n\n{x=1,m=1,a=1,s=1}\n{x=2,m=1,a=1,s=1}")?.profile();
+        assert_eq!(profile.workflows[1], WorkflowProfile { name: "zz".to_owned(), visits: 0, rules: vec![rule("R", 0, 0)] });
+        assert_eq!(profile.paths, [(vec!["in".to_owned(), "A".to_owned()], 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn validate_workflows_and_parts() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        let input = "px{a<2006:qkq,R}\npx{A}\nqs{s>3448:A,lnx}\n\n{x=787,m=2655,a=1222,s=2876}\n{x=787,m=2655}";
+        assert_eq!(validate(input), vec![
+            Issue::new("No workflow named 'in'"),
+            Issue::at_line(1, "Unknown workflow qkq"),
+            Issue::at_line(2, "Duplicate workflow px"),
+            Issue::at_line(3, "Unknown workflow lnx"),
+            Issue::at(6, 2, "Expected ratings x, m, a and s in this order"),
+        ]);
+    }
+
+    #[test]
+    fn rating_box_split() {
+        let (matched, rest) = RatingBox::full().split(Category::M, true, 1000);
+        assert_eq!((matched[Category::M], rest[Category::M]), ([1, 999], [1000, 4000]));
+        assert_eq!(matched[Category::S], [1, MAX_RATING]);
+        assert_eq!(matched.volume() + rest.volume(), RatingBox::full().volume());
+        let (matched, rest) = rest.split(Category::M, false, 4000);
+        assert!(matched.is_empty() && matched.volume() == 0);
+        assert_eq!(rest, RatingBox::full().split(Category::M, true, 1000).1);
+    }
+
+    #[test]
+    fn categories() {
+        assert!(Category::ALL.iter().enumerate().all(|(i, category)| category.index() == i));
+        assert!(Category::ALL.iter().all(|category| category.name().parse() == Ok(*category)));
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 19114);
         Ok(())
     }
 
+    #[test]
+    fn ratings_beyond_32_bits() -> Result<(), Box<dyn Error>> {
+        let parts = "{x=2000000000,m=2000000000,a=2000000000,s=2000000000}\n".repeat(3);
+        let result = solve_part1(&format!("in{{A}}\n\n{parts}"))?;
+        assert_eq!(result, 24_000_000_000u64);
+        Ok(())
+    }
+
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
-        assert_eq!(result, 167409079868000);
+        let result = solve_part2(EXAMPLE1.trim())?;
+        assert_eq!(result, 167409079868000_u64);
         Ok(())
     }
+
+    #[test]
+    fn example1_uniform_acceptance_probability() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let result = puzzle.acceptance_probability(Default::default());
+        assert!((result - 167409079868000.0 / 4000f64.powi(4)).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_point_acceptance_probability() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        let point = |rating: usize| {
+            let mut weights = vec![0.0; 4000];
+            weights[rating - 1] = 1.0;
+            Distribution::from_weights(&weights).unwrap()
+        };
+        let accepted = puzzle.acceptance_probability([point(787), point(2655), point(1222), point(2876)]);
+        assert!((accepted - 1.0).abs() < 1e-12);
+        let rejected = puzzle.acceptance_probability([point(1679), point(44), point(2067), point(496)]);
+        assert!(rejected.abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn compare_refactored_workflows() -> Result<(), Box<dyn Error>> {
+        let simplified = EXAMPLE1.replace("gd{a>3333:R,R}", "gd{R}").replace("lnx{m>1548:A,A}", "lnx{A}");
+        let comparison = compare_workflows(EXAMPLE1.trim(), simplified.trim())?;
+        assert!(comparison.is_equivalent());
+        assert_eq!(comparison.to_string(), "Both workflows accept the same parts");
+
+        let changed = EXAMPLE1.replace("crn{x>2662:A,R}", "crn{x>2663:A,R}");
+        let comparison = compare_workflows(EXAMPLE1.trim(), changed.trim())?;
+        assert_eq!(comparison.only_second, 0);
+        let total: u64 = Puzzle::from_input(EXAMPLE1.trim())?.possibilities();
+        assert_eq!(comparison.only_first, total - Puzzle::from_input(changed.trim())?.possibilities());
+        let (part, first) = comparison.counterexample.clone().ok_or("No counterexample")?;
+        assert!(first);
+        assert_eq!(part.get_rating(Category::X), 2663);
+        assert!(Puzzle::from_input(EXAMPLE1.trim())?.part_accepted(&part));
+        assert!(!Puzzle::from_input(changed.trim())?.part_accepted(&part));
+        assert!(comparison.to_string().ends_with(&format!("Counterexample: {part} is accepted only by the first workflows")));
+        Ok(())
+    }
+
+    #[test]
+    fn subtract_boxes() {
+        let mut inner = RatingBox::full();
+        inner[Category::M] = [10, 20];
+        inner[Category::S] = [4000, 4000];
+        let pieces = RatingBox::full().subtract(&inner);
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces.iter().map(RatingBox::volume).sum::<u64>(), RatingBox::full().volume() - inner.volume());
+        assert_eq!(inner.subtract(&RatingBox::full()), vec![]);
+        assert_eq!(difference(&[inner], &pieces), vec![inner]);
+    }
 }
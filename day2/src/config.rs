@@ -1,17 +1,52 @@
+use crate::{IdCheck, ParseOptions};
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print this many random games possible with the bag of part 1 instead of solving the puzzle
+    pub simulate: Option<usize>,
+    /// Seed for simulating games, so that the same games are generated each time
+    pub seed: Option<u64>,
+    /// Print statistics of the sets drawn in the games of the first file instead of solving the
+    /// puzzle
+    pub stats: bool,
+    pub options: ParseOptions,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day2 <file for part 1> <file for part 2> [--ids none|unique|contiguous] [--simulate <number of games> [--seed <n>]] [--stats]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut simulate = None;
+        let mut seed = None;
+        let mut stats = false;
+        let mut options = ParseOptions::default();
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--stats" {
+                stats = true;
+                continue;
+            }
+            let value = iter.next().ok_or(USAGE)?;
+            match arg.as_str() {
+                "--simulate" => simulate = Some(value.parse().map_err(|_| USAGE)?),
+                "--seed" => seed = Some(value.parse().map_err(|_| USAGE)?),
+                "--ids" => options.ids = IdCheck::try_from(value.as_str()).map_err(|_| USAGE)?,
+                _ => return Err(USAGE),
+            }
+        }
+        if (seed.is_some() && simulate.is_none()) || (stats && simulate.is_some()) {
+            return Err(USAGE);
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            simulate,
+            seed,
+            stats,
+            options,
         })
     }
 }
-
@@ -1,12 +1,13 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::fmt;
+
+use aoc_core::parse::{ParseError, Parser};
 
 #[derive(Debug)]
 #[derive(Default)]
 pub struct CubeNumbers {
-    red: u32,
-    green: u32,
-    blue: u32,
+    pub(crate) red: u32,
+    pub(crate) green: u32,
+    pub(crate) blue: u32,
 }
 
 impl CubeNumbers {
@@ -14,22 +15,22 @@ impl CubeNumbers {
         Self { red, green, blue }
     }
 
-    pub fn from_str(s: &str, line_nr: usize) -> Result<Self, String> {
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?<n>[0-9]+) (?<color>[a-z]+)$").unwrap());
+    /// Reads a set of cubes like "3 blue, 4 red".
+    pub fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         let mut result = Self { ..Default::default() };
-        for part in s.split(", ") {
-            let Some(groups) = RE.captures(part) else {
-                return Err(format!("Could not parse '{part}' on line {line_nr}"));
-            };
-            let n: u32 = groups["n"].parse().unwrap();
-            let color = &groups["color"];
+        parser.separated(", ", |parser| {
+            let n: u32 = parser.number()?;
+            parser.tag(" ")?;
+            let column = parser.column();
+            let color = parser.word("color", |c| c.is_ascii_lowercase())?;
             match color {
                 "red" => { result.red = n },
                 "green" => { result.green = n },
                 "blue" => { result.blue = n },
-                _ => return Err(format!("Invalid color '{color}' on line {line_nr}")),
+                _ => return Err(ParseError::new(column, format!("Invalid color '{color}'"))),
             }
-        }
+            Ok(())
+        })?;
         Ok(result)
     }
 
@@ -37,6 +38,10 @@ impl CubeNumbers {
         self.red <= other.red && self.green <= other.green && self.blue <= other.blue
     }
 
+    pub fn total(&self) -> u32 {
+        self.red + self.green + self.blue
+    }
+
     pub fn power(&self) -> u32 {
         self.red * self.green * self.blue
     }
@@ -53,3 +58,12 @@ impl CubeNumbers {
         }
     }
 }
+
+impl fmt::Display for CubeNumbers {
+    /// Writes the set like "4 red, 3 blue", leaving out colors without cubes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors = [(self.red, "red"), (self.green, "green"), (self.blue, "blue")];
+        let parts: Vec<String> = colors.iter().filter(|(n, _)| *n > 0).map(|(n, color)| format!("{n} {color}")).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
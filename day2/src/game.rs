@@ -1,5 +1,4 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use aoc_core::parse::{self, ParseError};
 
 use crate::cube_numbers::CubeNumbers;
 
@@ -11,20 +10,17 @@ pub struct Game {
 
 impl Game {
     pub fn from_line(line: &str, line_nr: usize) -> Result<Self, String> {
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Game (?<id>[0-9]+): (?<sets>.*)$").unwrap());
-        let Some(result) = RE.captures(line) else {
-            return Err(format!("Syntax error on line {line_nr}"));
-        };
-        let id: u32 = result["id"].parse().unwrap();
-        let sets_str = &result["sets"];
-        let mut sets = vec![];
-        for set_str in sets_str.split("; ") {
-            let set = CubeNumbers::from_str(set_str, line_nr)?;
-            sets.push(set);
-        }
-        Ok(Self {
-            id,
-            sets,
+        Self::parse(line).map_err(|e| e.on_line(line_nr))
+    }
+
+    /// Reads a line like "Game 1: 3 blue, 4 red; 1 red, 2 green".
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        parse::parse_all(line, |parser| {
+            parser.tag("Game ")?;
+            let id = parser.number()?;
+            parser.tag(": ")?;
+            let sets = parser.separated("; ", CubeNumbers::parse)?;
+            Ok(Self { id, sets })
         })
     }
 
@@ -32,6 +28,10 @@ impl Game {
         self.id
     }
 
+    pub fn sets(&self) -> &[CubeNumbers] {
+        &self.sets
+    }
+
     pub fn is_possible(&self, bag: &CubeNumbers) -> bool {
         self.sets.iter().all(|set| set.at_most(bag))
     }
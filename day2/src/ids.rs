@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use aoc_core::validate::Issue;
+
+/// How strictly the game IDs of an input are checked
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdCheck {
+    /// Accept any IDs
+    #[default]
+    None,
+    /// Each ID must occur only once
+    Unique,
+    /// Each ID must occur only once and be one more than the ID of the previous game
+    Contiguous,
+}
+
+impl TryFrom<&str> for IdCheck {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "none" => Ok(IdCheck::None),
+            "unique" => Ok(IdCheck::Unique),
+            "contiguous" => Ok(IdCheck::Contiguous),
+            _ => Err("Expected none, unique or contiguous"),
+        }
+    }
+}
+
+/// Options for reading the games
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    pub ids: IdCheck,
+}
+
+/// A game ID that violates an `IdCheck`. Lines are 1-based.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IdAnomaly {
+    /// The ID was already used by the game on `first_line`
+    Duplicate { id: u32, first_line: usize, line: usize },
+    /// The ID does not follow the ID of the previous game
+    Gap { previous: u32, id: u32, line: usize },
+}
+
+impl IdAnomaly {
+    pub fn line(&self) -> usize {
+        match *self {
+            IdAnomaly::Duplicate { line, .. } | IdAnomaly::Gap { line, .. } => line,
+        }
+    }
+
+    pub fn to_issue(&self) -> Issue {
+        Issue::at_line(self.line(), self)
+    }
+}
+
+impl Display for IdAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdAnomaly::Duplicate { id, first_line, .. } => write!(f, "Game {id} already occurs on line {first_line}"),
+            IdAnomaly::Gap { previous, id, .. } => write!(f, "Game {id} follows game {previous}"),
+        }
+    }
+}
+
+/// The anomalies found among the game IDs of an input, in input order
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IdReport {
+    pub anomalies: Vec<IdAnomaly>,
+}
+
+impl IdReport {
+    /// Checks the IDs of the games, given in input order.
+    pub fn new(ids: impl IntoIterator<Item = u32>, check: IdCheck) -> Self {
+        let mut anomalies = Vec::new();
+        if check == IdCheck::None {
+            return Self { anomalies };
+        }
+        let mut first_lines: HashMap<u32, usize> = HashMap::new();
+        let mut previous: Option<u32> = None;
+        for (i, id) in ids.into_iter().enumerate() {
+            let line = i + 1;
+            // A duplicate is not reported as a gap as well
+            if let Some(&first_line) = first_lines.get(&id) {
+                anomalies.push(IdAnomaly::Duplicate { id, first_line, line });
+            } else {
+                first_lines.insert(id, line);
+                if check == IdCheck::Contiguous {
+                    if let Some(previous) = previous.filter(|&previous| previous.checked_add(1) != Some(id)) {
+                        anomalies.push(IdAnomaly::Gap { previous, id, line });
+                    }
+                }
+            }
+            previous = Some(id);
+        }
+        Self { anomalies }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+impl Display for IdReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Game ID anomalies: {}", self.anomalies.len())?;
+        for anomaly in &self.anomalies {
+            write!(f, "\n  line {}: {anomaly}", anomaly.line())?;
+        }
+        Ok(())
+    }
+}
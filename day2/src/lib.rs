@@ -2,45 +2,265 @@ pub mod config;
 
 mod cube_numbers;
 mod game;
+mod ids;
+mod simulation;
+mod stats;
 
 use std::error::Error;
 use std::fs;
 
-use cube_numbers::CubeNumbers;
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::bitvec::BitVec;
+use aoc_core::validate::Issue;
+pub use cube_numbers::CubeNumbers;
 use game::Game;
+pub use ids::{IdAnomaly, IdCheck, IdReport, ParseOptions};
+pub use simulation::{simulate, simulate_input, Rng, SeededRng};
+pub use stats::{draw_stats, ColorStats, DrawStats, LargestDraw};
 
-fn part1(input: &str) -> Result<(), Box<dyn Error>> {
-    let bag_contents = CubeNumbers::new(12, 13, 14);
-    let mut sum = 0;
-    for (i, line) in input.lines().enumerate() {
-        let game = Game::from_line(&line, i)?;
-        if game.is_possible(&bag_contents) {
-            sum += game.id();
+/// Reads the games and fails if their IDs do not pass the check of the options.
+fn read_games(input: &str, options: &ParseOptions) -> Result<Vec<Game>, Box<dyn Error>> {
+    let games = input.lines().enumerate()
+        .map(|(i, line)| Game::from_line(line, i + 1))
+        .collect::<Result<Vec<_>, _>>()?;
+    let report = IdReport::new(games.iter().map(Game::id), options.ids);
+    if !report.is_clean() {
+        return Err(report.to_string().into());
+    }
+    Ok(games)
+}
+
+/// Both parts, solved from the same games
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Vec<Game>;
+
+    fn parse(input: &str) -> Result<Vec<Game>, Box<dyn Error>> {
+        read_games(input, &ParseOptions::default())
+    }
+
+    fn part1(games: &Vec<Game>) -> Result<Answer, Box<dyn Error>> {
+        let bag_contents = CubeNumbers::new(12, 13, 14);
+        let mut sum = 0;
+        for game in games {
+            if game.is_possible(&bag_contents) {
+                sum += game.id();
+            }
         }
+        Ok(sum.into())
     }
-    println!("Sum for part 1: {sum}");
-    Ok(())
+
+    fn part2(games: &Vec<Game>) -> Result<Answer, Box<dyn Error>> {
+        let mut sum = 0;
+        for game in games {
+            let fitting_set = game.smallest_fitting_set();
+            sum += fitting_set.power();
+        }
+        Ok(sum.into())
+    }
+}
+
+/// Checks the game IDs of the input and reports all anomalies instead of failing at the first.
+pub fn check_ids(input: &str, check: IdCheck) -> Result<IdReport, String> {
+    let ids = input.lines().enumerate()
+        .map(|(i, line)| Ok(Game::from_line(line, i + 1)?.id()))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(IdReport::new(ids, check))
+}
+
+/// Determines for each game which of the candidate bags it is possible with: bit `j` of the
+/// result for a game is set if the game is possible with `bags[j]`.
+pub fn possible_with_bags(input: &str, bags: &[CubeNumbers]) -> Result<Vec<BitVec>, String> {
+    input.lines().enumerate()
+        .map(|(i, line)| {
+            let fitting_set = Game::from_line(line, i + 1)?.smallest_fitting_set();
+            Ok(bags.iter().map(|bag| fitting_set.at_most(bag)).collect())
+        })
+        .collect()
 }
 
-fn part2(input: &str) -> Result<(), Box<dyn Error>> {
-    let mut sum = 0;
-    for (i, line) in input.lines().enumerate() {
-        let game = Game::from_line(&line, i)?;
-        let fitting_set = game.smallest_fitting_set();
-        sum += fitting_set.power();
+/// Returns the indices of the candidate bags with which all games are possible.
+pub fn bags_possible_for_all(input: &str, bags: &[CubeNumbers]) -> Result<Vec<usize>, String> {
+    let mut all = BitVec::new(bags.len());
+    for i in 0..bags.len() {
+        all.set(i, true);
     }
-    println!("Sum for part 2: {sum}");
-    Ok(())
+    for possible in possible_with_bags(input, bags)? {
+        all.and_assign(&possible);
+    }
+    Ok(all.ones().collect())
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as games, or otherwise the games whose IDs occur earlier.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues: Vec<Issue> = input.lines().enumerate()
+        .filter_map(|(i, line)| Game::parse(line).err().map(|e| Issue::at(i + 1, e.column, e.message)))
+        .collect();
+    if !issues.is_empty() {
+        return issues;
+    }
+    check_ids(input, IdCheck::Unique).map_or_else(
+        |e| vec![Issue::new(e)],
+        |report| report.anomalies.iter().map(IdAnomaly::to_issue).collect(),
+    )
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if let Some(num_games) = config.simulate {
+        let mut rng = config.seed.map_or_else(SeededRng::from_time, SeededRng::new);
+        print!("{}", simulate_input(&CubeNumbers::new(12, 13, 14), num_games, 5, &mut rng)?);
+        return Ok(());
+    }
+
+    if config.stats {
+        println!("Reading file {}", config.file_path1);
+        print!("{}", draw_stats(&fs::read_to_string(config.file_path1)?)?);
+        return Ok(());
+    }
+
+    let parse = |input: &str| read_games(input, &config.options);
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    part1(&contents)?;
+    let games = parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&games)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    part2(&contents)?;
+    let games = answer::parse_part2(games, &config.file_path1, &config.file_path2, parse)?;
+    println!("Result of part 2: {}", Day::part2(&games)?);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    const EXAMPLE: &str = "
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    #[test]
+    fn example_part1() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part1(EXAMPLE.trim())?, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn example_part2() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part2(EXAMPLE.trim())?, 2286);
+        Ok(())
+    }
+
+    #[test]
+    fn example_bags() -> Result<(), String> {
+        let bags = [CubeNumbers::new(12, 13, 14), CubeNumbers::new(20, 13, 15), CubeNumbers::new(4, 3, 6)];
+        let possible = possible_with_bags(EXAMPLE.trim(), &bags)?;
+        let possible: Vec<Vec<usize>> = possible.iter().map(|bags| bags.ones().collect()).collect();
+        assert_eq!(possible, [vec![0, 1, 2], vec![0, 1, 2], vec![1], vec![1], vec![0, 1]]);
+        assert_eq!(bags_possible_for_all(EXAMPLE.trim(), &bags)?, [1]);
+        assert_eq!(bags_possible_for_all(EXAMPLE.trim(), &bags[..1])?, []);
+        // Without games, every bag is possible
+        assert_eq!(bags_possible_for_all("", &bags)?, [0, 1, 2]);
+        assert!(possible_with_bags("Game 1: 3 purple", &bags).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn seeded_simulation() -> Result<(), Box<dyn Error>> {
+        let bag = CubeNumbers::new(12, 13, 14);
+        let input = simulate_input(&bag, 3, 4, &mut SeededRng::new(7))?;
+        assert_eq!(input, concat!(
+            "Game 1: 7 red, 6 green, 3 blue; 10 red, 10 green, 12 blue; 3 red, 8 green, 6 blue; 7 red, 11 green, 14 blue\n",
+            "Game 2: 4 red, 4 green, 5 blue; 7 red, 9 green, 9 blue\n",
+            "Game 3: 7 red, 5 green, 10 blue\n",
+        ));
+        // The simulated games parse back and are all possible with the bag they were drawn from
+        assert!(validate(&input).is_empty());
+        assert_eq!(solve_part1(&input)?, 1 + 2 + 3);
+        assert_eq!(bags_possible_for_all(&input, &[bag, CubeNumbers::new(12, 13, 13)])?, [0]);
+
+        let draws = simulate(&CubeNumbers::new(1, 0, 2), 50, &mut SeededRng::new(1))?;
+        assert!(draws.iter().all(|draw| (1..=3).contains(&draw.total()) && draw.at_most(&CubeNumbers::new(1, 0, 2))));
+        assert_eq!(simulate(&CubeNumbers::default(), 1, &mut SeededRng::new(1)).unwrap_err(), "Bag is empty");
+        Ok(())
+    }
+
+    #[test]
+    fn ids() -> Result<(), Box<dyn Error>> {
+        for check in [IdCheck::None, IdCheck::Unique, IdCheck::Contiguous] {
+            assert!(check_ids(EXAMPLE.trim(), check)?.is_clean());
+        }
+        // The games of the example stitched to those of another file, with games 3 to 6 missing
+        let stitched = format!("{}\nGame 1: 1 red\nGame 2: 1 red\nGame 7: 1 red", EXAMPLE.trim());
+        assert!(check_ids(&stitched, IdCheck::None)?.is_clean());
+        let report = check_ids(&stitched, IdCheck::Unique)?;
+        assert_eq!(report.anomalies, [
+            IdAnomaly::Duplicate { id: 1, first_line: 1, line: 6 },
+            IdAnomaly::Duplicate { id: 2, first_line: 2, line: 7 },
+        ]);
+        let report = check_ids(&stitched, IdCheck::Contiguous)?;
+        assert_eq!(report.anomalies, [
+            IdAnomaly::Duplicate { id: 1, first_line: 1, line: 6 },
+            IdAnomaly::Duplicate { id: 2, first_line: 2, line: 7 },
+            IdAnomaly::Gap { previous: 2, id: 7, line: 8 },
+        ]);
+        assert_eq!(report.to_string(), concat!(
+            "Game ID anomalies: 3\n",
+            "  line 6: Game 1 already occurs on line 1\n",
+            "  line 7: Game 2 already occurs on line 2\n",
+            "  line 8: Game 7 follows game 2",
+        ));
+        assert_eq!(validate(&stitched), [
+            Issue::at_line(6, "Game 1 already occurs on line 1"),
+            Issue::at_line(7, "Game 2 already occurs on line 2"),
+        ]);
+        // Only the options of the runner check the IDs when solving
+        assert_eq!(solve_part1(&stitched)?, 8 + 1 + 2 + 7);
+        let error = read_games(&stitched, &ParseOptions { ids: IdCheck::Unique }).unwrap_err();
+        assert!(error.to_string().starts_with("Game ID anomalies: 2\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn example_draw_stats() -> Result<(), String> {
+        let stats = draw_stats(EXAMPLE.trim())?;
+        assert_eq!(stats.red.histogram, BTreeMap::from([(1, 4), (3, 1), (4, 2), (6, 2), (14, 1), (20, 1)]));
+        assert_eq!(stats.green.histogram, BTreeMap::from([(1, 2), (2, 4), (3, 4), (5, 1), (8, 1), (13, 1)]));
+        assert_eq!(stats.blue.histogram, BTreeMap::from([(1, 3), (2, 1), (3, 1), (4, 1), (5, 1), (6, 3), (15, 1)]));
+        assert_eq!([stats.red.num_draws(), stats.green.num_draws(), stats.blue.num_draws()], [11, 13, 11]);
+        assert_eq!(stats.red.largest, Some(LargestDraw { count: 20, game: 3, set: 1 }));
+        assert_eq!(stats.green.largest, Some(LargestDraw { count: 13, game: 3, set: 2 }));
+        assert_eq!(stats.blue.largest, Some(LargestDraw { count: 15, game: 4, set: 3 }));
+        let table = stats.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[..3], ["Cubes    Red  Green   Blue", "    1      4      2      3", "    2      0      4      1"]);
+        assert_eq!(lines[lines.len() - 4..], [
+            "Total     11     13     11",
+            "Largest red: 20 (game 3, set 1)",
+            "Largest green: 13 (game 3, set 2)",
+            "Largest blue: 15 (game 4, set 3)",
+        ]);
+        let stats = draw_stats("Game 1: 2 red")?;
+        assert_eq!(stats.blue.largest, None);
+        assert!(stats.to_string().ends_with("Largest blue: none\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+pub use aoc_core::rng::{Rng, SeededRng};
+
+use crate::cube_numbers::CubeNumbers;
+
+/// Simulates a game: for each of `draws` draws, a random number of cubes (at least one) is taken
+/// out of the bag without replacement, and they are put back before the next draw.
+pub fn simulate(bag: &CubeNumbers, draws: usize, rng: &mut impl Rng) -> Result<Vec<CubeNumbers>, &'static str> {
+    let total = bag.total();
+    if total == 0 {
+        return Err("Bag is empty");
+    }
+    Ok((0..draws).map(|_| {
+        let num_cubes = 1 + rng.below(total as u64) as u32;
+        let mut left = [bag.red, bag.green, bag.blue];
+        let mut drawn = [0; 3];
+        for _ in 0..num_cubes {
+            // Each cube left in the bag is equally likely to be taken next
+            let mut k = rng.below(left.iter().sum::<u32>() as u64) as u32;
+            let color = left.iter().position(|&n| if k < n { true } else { k -= n; false }).unwrap();
+            left[color] -= 1;
+            drawn[color] += 1;
+        }
+        CubeNumbers::new(drawn[0], drawn[1], drawn[2])
+    }).collect())
+}
+
+/// Generates a puzzle input of `num_games` games, each with between 1 and `max_draws` draws from
+/// the bag.
+pub fn simulate_input(bag: &CubeNumbers, num_games: usize, max_draws: usize, rng: &mut impl Rng) -> Result<String, &'static str> {
+    let mut input = String::new();
+    for id in 1..=num_games {
+        let draws = simulate(bag, 1 + rng.below(max_draws.max(1) as u64) as usize, rng)?;
+        let draws: Vec<String> = draws.iter().map(CubeNumbers::to_string).collect();
+        input.push_str(&format!("Game {id}: {}\n", draws.join("; ")));
+    }
+    Ok(input)
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::game::Game;
+
+/// The largest number of cubes of one color drawn at once, with the ID of the game and the 1-based
+/// number of the set within the game
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LargestDraw {
+    pub count: u32,
+    pub game: u32,
+    pub set: usize,
+}
+
+/// How many cubes of one color were drawn at once across all games. Sets without cubes of the
+/// color are not counted.
+#[derive(Debug, Default)]
+pub struct ColorStats {
+    /// Number of sets for each number of cubes of the color
+    pub histogram: BTreeMap<u32, usize>,
+    /// The first of the sets with the most cubes of the color
+    pub largest: Option<LargestDraw>,
+}
+
+impl ColorStats {
+    fn add(&mut self, count: u32, game: u32, set: usize) {
+        if count == 0 {
+            return;
+        }
+        *self.histogram.entry(count).or_default() += 1;
+        if self.largest.is_none_or(|largest| count > largest.count) {
+            self.largest = Some(LargestDraw { count, game, set });
+        }
+    }
+
+    /// Number of sets with cubes of the color
+    pub fn num_draws(&self) -> usize {
+        self.histogram.values().sum()
+    }
+}
+
+/// Statistics of the sets drawn in all games of an input, per color
+#[derive(Debug, Default)]
+pub struct DrawStats {
+    pub red: ColorStats,
+    pub green: ColorStats,
+    pub blue: ColorStats,
+}
+
+impl DrawStats {
+    fn from_games<'a>(games: impl IntoIterator<Item = &'a Game>) -> Self {
+        let mut stats = Self::default();
+        for game in games {
+            for (i, set) in game.sets().iter().enumerate() {
+                stats.red.add(set.red, game.id(), i + 1);
+                stats.green.add(set.green, game.id(), i + 1);
+                stats.blue.add(set.blue, game.id(), i + 1);
+            }
+        }
+        stats
+    }
+
+    fn colors(&self) -> [(&'static str, &ColorStats); 3] {
+        [("Red", &self.red), ("Green", &self.green), ("Blue", &self.blue)]
+    }
+}
+
+/// Reads the games of the input and gathers the statistics of their sets.
+pub fn draw_stats(input: &str) -> Result<DrawStats, String> {
+    let games = input.lines().enumerate()
+        .map(|(i, line)| Game::from_line(line, i + 1))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(DrawStats::from_games(&games))
+}
+
+impl fmt::Display for DrawStats {
+    /// Writes a table with the number of sets for each number of cubes and color, followed by the
+    /// largest draw of each color.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors = self.colors();
+        writeln!(f, "{:>5} {:>6} {:>6} {:>6}", "Cubes", colors[0].0, colors[1].0, colors[2].0)?;
+        let mut counts: Vec<u32> = colors.iter().flat_map(|(_, stats)| stats.histogram.keys().copied()).collect();
+        counts.sort_unstable();
+        counts.dedup();
+        for count in counts {
+            write!(f, "{count:>5}")?;
+            for (_, stats) in colors {
+                write!(f, " {:>6}", stats.histogram.get(&count).copied().unwrap_or(0))?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{:>5}", "Total")?;
+        for (_, stats) in colors {
+            write!(f, " {:>6}", stats.num_draws())?;
+        }
+        writeln!(f)?;
+        for (color, stats) in colors {
+            match stats.largest {
+                Some(largest) => writeln!(f, "Largest {}: {} (game {}, set {})", color.to_lowercase(), largest.count, largest.game, largest.set)?,
+                None => writeln!(f, "Largest {}: none", color.to_lowercase())?,
+            }
+        }
+        Ok(())
+    }
+}
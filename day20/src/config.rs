@@ -0,0 +1,39 @@
+pub struct Config {
+    pub file_path1: String,
+    pub file_path2: String,
+    /// Instead of solving, read commands for pressing the button of the machine of part 1 from
+    /// stdin
+    pub repl: bool,
+    /// Number of presses of the button of part 2 whose pulses and module levels are written as
+    /// CSV, and the files for the pulses and the levels
+    pub waveform: Option<(u64, String, String)>,
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        const USAGE: &str = "Usage: day20 <file for part 1> <file for part 2> [--repl] [--waveform <presses> <pulses file> <states file>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut repl = false;
+        let mut waveform = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--repl" => repl = true,
+                "--waveform" => {
+                    let presses = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    let pulses_path = iter.next().ok_or(USAGE)?.clone();
+                    waveform = Some((presses, pulses_path, iter.next().ok_or(USAGE)?.clone()));
+                },
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config {
+            file_path1: args[1].clone(),
+            file_path2: args[2].clone(),
+            repl,
+            waveform,
+        })
+    }
+}
@@ -0,0 +1,661 @@
+pub mod config;
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::ops::AddAssign;
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::math;
+use aoc_core::parse::{self, ParseError};
+use aoc_core::validate::{self, Issue};
+
+/// Number of pulses after which a press is given up, since some machines never come to rest
+pub const MAX_PULSES_PER_PRESS: u64 = 1 << 24;
+
+/// Number of presses after which part 2 gives up looking for the cycles of the modules feeding rx
+const MAX_PRESSES: u64 = 1 << 16;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pulse {
+    Low,
+    High,
+}
+
+impl fmt::Display for Pulse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", if *self == Pulse::Low { "low" } else { "high" })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    Broadcaster,
+    FlipFlop,
+    Conjunction,
+    /// A module that is only mentioned as a destination, like "output" or "rx"
+    Untyped,
+}
+
+#[derive(Clone)]
+struct Module {
+    name: String,
+    kind: Kind,
+    /// Destinations with the index of this module among the inputs of the destination
+    outputs: Vec<(usize, usize)>,
+    inputs: Vec<usize>,
+}
+
+impl Module {
+    /// Reads a line like "%a -> inv, con" and returns the kind, name and destinations.
+    fn parse(line: &str) -> Result<(Kind, &str, Vec<&str>), ParseError> {
+        parse::parse_all(line, |parser| {
+            let kind = if parser.eat("%") {
+                Kind::FlipFlop
+            } else if parser.eat("&") {
+                Kind::Conjunction
+            } else {
+                Kind::Broadcaster
+            };
+            let name = parser.word("module name", |c| c.is_ascii_alphanumeric())?;
+            if kind == Kind::Broadcaster && name != "broadcaster" {
+                return Err(ParseError::new(1, "Expected '%', '&' or broadcaster"));
+            }
+            parser.tag(" -> ")?;
+            let outputs = parser.separated(", ", |parser| parser.word("module name", |c| c.is_ascii_alphanumeric()))?;
+            Ok((kind, name, outputs))
+        })
+    }
+}
+
+/// Numbers of low and high pulses sent
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PulseStats {
+    pub low: u64,
+    pub high: u64,
+}
+
+impl PulseStats {
+    fn count(&mut self, pulse: Pulse) {
+        match pulse {
+            Pulse::Low => self.low += 1,
+            Pulse::High => self.high += 1,
+        }
+    }
+}
+
+impl AddAssign for PulseStats {
+    fn add_assign(&mut self, other: Self) {
+        self.low += other.low;
+        self.high += other.high;
+    }
+}
+
+impl fmt::Display for PulseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} low, {} high", self.low, self.high)
+    }
+}
+
+/// The state of a module between presses
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModuleState {
+    Broadcaster,
+    FlipFlop { on: bool },
+    /// The last pulse received from each input, in the order of the input
+    Conjunction { remembered: Vec<(String, Pulse)> },
+    Untyped,
+}
+
+impl fmt::Display for ModuleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModuleState::Broadcaster => write!(f, "broadcaster"),
+            ModuleState::FlipFlop { on } => write!(f, "flip-flop {}", if *on { "on" } else { "off" }),
+            ModuleState::Conjunction { remembered } => {
+                let remembered: Vec<String> = remembered.iter().map(|(name, pulse)| format!("{name}={pulse}")).collect();
+                write!(f, "conjunction {}", remembered.join(", "))
+            },
+            ModuleState::Untyped => write!(f, "untyped"),
+        }
+    }
+}
+
+/// The level of a flip-flop or conjunction after each press
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleTimeline {
+    pub name: String,
+    /// Whether the module is a conjunction rather than a flip-flop
+    pub conjunction: bool,
+    /// Whether a flip-flop is on, or whether a conjunction would send a high pulse, after each
+    /// press
+    pub levels: Vec<bool>,
+    /// Whether the module sent a high pulse at any time during each press, which a conjunction may
+    /// do only briefly
+    pub sent_high: Vec<bool>,
+}
+
+/// Pulse counts and module levels over a number of presses, for plotting how the flip-flops count
+/// presses in binary
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Waveform {
+    /// Pulses sent by each press
+    pub pulses: Vec<PulseStats>,
+    /// Timelines of the flip-flops followed by those of the conjunctions, each in the order of
+    /// their definitions
+    pub timelines: Vec<ModuleTimeline>,
+}
+
+impl Waveform {
+    /// The pulses of each press as CSV with the columns press, low and high
+    pub fn pulses_csv(&self) -> String {
+        let mut csv = String::from("press,low,high\n");
+        for (i, stats) in self.pulses.iter().enumerate() {
+            csv.push_str(&format!("{},{},{}\n", i + 1, stats.low, stats.high));
+        }
+        csv
+    }
+
+    /// The levels after each press as CSV with a column of 0 and 1 for each module, followed by a
+    /// column "<name> sent high" for each conjunction
+    pub fn states_csv(&self) -> String {
+        let conjunctions: Vec<&ModuleTimeline> = self.timelines.iter().filter(|timeline| timeline.conjunction).collect();
+        let header = ["press".to_owned()].into_iter()
+            .chain(self.timelines.iter().map(|timeline| timeline.name.clone()))
+            .chain(conjunctions.iter().map(|timeline| format!("{} sent high", timeline.name)))
+            .collect::<Vec<_>>();
+        let mut csv = header.join(",") + "\n";
+        for press in 0..self.pulses.len() {
+            let row = [(press + 1).to_string()].into_iter()
+                .chain(self.timelines.iter().map(|timeline| u8::from(timeline.levels[press]).to_string()))
+                .chain(conjunctions.iter().map(|timeline| u8::from(timeline.sent_high[press]).to_string()))
+                .collect::<Vec<_>>();
+            csv.push_str(&(row.join(",") + "\n"));
+        }
+        csv
+    }
+}
+
+/// The modules connected to the button, with their states and the pulses sent so far
+#[derive(Clone)]
+pub struct Machine {
+    modules: Vec<Module>,
+    index_of_name: HashMap<String, usize>,
+    broadcaster: usize,
+    /// Whether each module is on, which only changes for flip-flops
+    on: Vec<bool>,
+    /// The last pulse each module received from each of its inputs, which only matters for
+    /// conjunctions
+    memory: Vec<Vec<Pulse>>,
+    presses: u64,
+    totals: PulseStats,
+}
+
+impl Machine {
+    pub fn from_input(input: &str) -> Result<Self, String> {
+        let mut modules = Vec::new();
+        let mut index_of_name = HashMap::new();
+        let mut output_names = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let (kind, name, outputs) = Module::parse(line).map_err(|e| e.on_line(i + 1))?;
+            if index_of_name.insert(name.to_owned(), modules.len()).is_some() {
+                return Err(format!("Line {}: Module {name} is defined twice", i + 1));
+            }
+            modules.push(Module { name: name.to_owned(), kind, outputs: Vec::new(), inputs: Vec::new() });
+            output_names.push(outputs);
+        }
+        let broadcaster = *index_of_name.get("broadcaster").ok_or("No broadcaster")?;
+        for (i, names) in output_names.into_iter().enumerate() {
+            for name in names {
+                let j = *index_of_name.entry(name.to_owned()).or_insert_with(|| {
+                    modules.push(Module { name: name.to_owned(), kind: Kind::Untyped, outputs: Vec::new(), inputs: Vec::new() });
+                    modules.len() - 1
+                });
+                let slot = modules[j].inputs.len();
+                modules[j].inputs.push(i);
+                modules[i].outputs.push((j, slot));
+            }
+        }
+        let on = vec![false; modules.len()];
+        let memory = modules.iter().map(|module| vec![Pulse::Low; module.inputs.len()]).collect();
+        Ok(Self { modules, index_of_name, broadcaster, on, memory, presses: 0, totals: PulseStats::default() })
+    }
+
+    /// Number of presses since the machine was created or reset
+    pub fn presses(&self) -> u64 {
+        self.presses
+    }
+
+    /// Pulses sent by all presses since the machine was created or reset
+    pub fn totals(&self) -> PulseStats {
+        self.totals
+    }
+
+    /// Turns all flip-flops off and makes all conjunctions forget their pulses.
+    pub fn reset(&mut self) {
+        self.on.fill(false);
+        self.memory.iter_mut().for_each(|memory| memory.fill(Pulse::Low));
+        self.presses = 0;
+        self.totals = PulseStats::default();
+    }
+
+    /// Presses the button and returns the pulses sent until the machine comes to rest, including
+    /// the low pulse from the button to the broadcaster. Fails if the machine is still sending
+    /// pulses after `MAX_PULSES_PER_PRESS`, leaving it in the state it was in at that point.
+    pub fn press(&mut self) -> Result<PulseStats, &'static str> {
+        self.press_observed(|_, _, _| ())
+    }
+
+    /// Presses the button, calling `observe` with the sender, the destination and the pulse of
+    /// every pulse sent by a module.
+    fn press_observed(&mut self, mut observe: impl FnMut(usize, usize, Pulse)) -> Result<PulseStats, &'static str> {
+        let mut stats = PulseStats::default();
+        let mut queue = VecDeque::from([(self.broadcaster, 0, Pulse::Low)]);
+        while let Some((to, slot, pulse)) = queue.pop_front() {
+            stats.count(pulse);
+            if stats.low + stats.high > MAX_PULSES_PER_PRESS {
+                return Err("Too many pulses in one press");
+            }
+            let module = &self.modules[to];
+            let output = match module.kind {
+                Kind::Broadcaster => pulse,
+                Kind::FlipFlop if pulse == Pulse::Low => {
+                    self.on[to] = !self.on[to];
+                    if self.on[to] { Pulse::High } else { Pulse::Low }
+                },
+                Kind::FlipFlop | Kind::Untyped => continue,
+                Kind::Conjunction => {
+                    self.memory[to][slot] = pulse;
+                    if self.memory[to].iter().all(|&pulse| pulse == Pulse::High) { Pulse::Low } else { Pulse::High }
+                },
+            };
+            for &(next, next_slot) in &module.outputs {
+                observe(to, next, output);
+                queue.push_back((next, next_slot, output));
+            }
+        }
+        self.presses += 1;
+        self.totals += stats;
+        Ok(stats)
+    }
+
+    /// The state of the module with the given name, if there is one
+    pub fn state(&self, name: &str) -> Option<ModuleState> {
+        self.index_of_name.get(name).map(|&i| self.state_of(i))
+    }
+
+    /// The names and states of all modules, in the order of their definitions followed by the
+    /// untyped modules
+    pub fn states(&self) -> impl Iterator<Item = (&str, ModuleState)> {
+        self.modules.iter().enumerate().map(|(i, module)| (module.name.as_str(), self.state_of(i)))
+    }
+
+    fn state_of(&self, i: usize) -> ModuleState {
+        let module = &self.modules[i];
+        match module.kind {
+            Kind::Broadcaster => ModuleState::Broadcaster,
+            Kind::FlipFlop => ModuleState::FlipFlop { on: self.on[i] },
+            Kind::Conjunction => ModuleState::Conjunction {
+                remembered: module.inputs.iter().zip(&self.memory[i])
+                    .map(|(&input, &pulse)| (self.modules[input].name.clone(), pulse))
+                    .collect(),
+            },
+            Kind::Untyped => ModuleState::Untyped,
+        }
+    }
+
+    /// The level of the module with index `i`, as in `ModuleTimeline::levels`
+    fn level(&self, i: usize) -> bool {
+        match self.modules[i].kind {
+            Kind::FlipFlop => self.on[i],
+            Kind::Conjunction => !self.memory[i].iter().all(|&pulse| pulse == Pulse::High),
+            Kind::Broadcaster | Kind::Untyped => false,
+        }
+    }
+
+    /// Presses the button `presses` times, recording the pulses of each press and the levels of
+    /// the flip-flops and conjunctions after it.
+    pub fn waveform(&mut self, presses: u64) -> Result<Waveform, &'static str> {
+        let modules = &self.modules;
+        let recorded: Vec<usize> = [Kind::FlipFlop, Kind::Conjunction].into_iter()
+            .flat_map(|kind| (0..modules.len()).filter(move |&i| modules[i].kind == kind))
+            .collect();
+        let mut timelines: Vec<ModuleTimeline> = recorded.iter()
+            .map(|&i| ModuleTimeline {
+                name: self.modules[i].name.clone(),
+                conjunction: self.modules[i].kind == Kind::Conjunction,
+                levels: Vec::new(),
+                sent_high: Vec::new(),
+            })
+            .collect();
+        let mut pulses = Vec::new();
+        for _ in 0..presses {
+            let mut sent_high = vec![false; self.modules.len()];
+            pulses.push(self.press_observed(|from, _, pulse| sent_high[from] |= pulse == Pulse::High)?);
+            for (timeline, &i) in timelines.iter_mut().zip(&recorded) {
+                timeline.levels.push(self.level(i));
+                timeline.sent_high.push(sent_high[i]);
+            }
+        }
+        Ok(Waveform { pulses, timelines })
+    }
+
+    /// Runs one command, which is one of
+    ///
+    /// - `press [n]` (or an empty line) to press the button `n` times (once by default),
+    /// - `state [module]` to show the state of a module or of all modules,
+    /// - `totals` to show the number of presses and pulses so far,
+    /// - `reset` to turn all flip-flops off and reset the counts.
+    fn command(&mut self, line: &str, output: &mut impl Write) -> Result<(), Box<dyn Error>> {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (None, _, _) | (Some("press"), None, _) => self.press_and_report(1, output)?,
+            (Some("press"), Some(n), None) => self.press_and_report(n.parse()?, output)?,
+            (Some("state"), None, _) => {
+                for (name, state) in self.states() {
+                    writeln!(output, "{name}: {state}")?;
+                }
+            },
+            (Some("state"), Some(name), None) => {
+                let state = self.state(name).ok_or_else(|| format!("No module {name}"))?;
+                writeln!(output, "{name}: {state}")?;
+            },
+            (Some("totals"), None, _) => writeln!(output, "{} presses: {}", self.presses, self.totals)?,
+            (Some("reset"), None, _) => {
+                self.reset();
+                writeln!(output, "Reset")?;
+            },
+            _ => return Err(format!("Unknown command {line:?}").into()),
+        }
+        Ok(())
+    }
+
+    fn press_and_report(&mut self, n: u64, output: &mut impl Write) -> Result<(), Box<dyn Error>> {
+        let first = self.presses + 1;
+        let mut stats = PulseStats::default();
+        for _ in 0..n {
+            stats += self.press()?;
+        }
+        let presses = if n == 1 { format!("Press {first}") } else { format!("Presses {first} to {}", self.presses) };
+        writeln!(output, "{presses}: {stats} (total {})", self.totals)?;
+        Ok(())
+    }
+
+    /// Runs each line of `input` as a command until the input ends or a line is "quit", writing
+    /// the results or "Error: " and the reason to `output`.
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim() == "quit" {
+                break;
+            }
+            if let Err(e) = self.command(&line, &mut output) {
+                writeln!(output, "Error: {e}")?;
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Number of presses until rx first receives a low pulse. This assumes that rx is fed by a
+    /// single conjunction whose inputs each send a high pulse to it for the first time after a
+    /// number of presses and then periodically with that number as period, as in the puzzle inputs.
+    fn presses_until_rx_low(&mut self) -> Result<u64, Box<dyn Error>> {
+        let rx = *self.index_of_name.get("rx").ok_or("No module rx")?;
+        let hub = match self.modules[rx].inputs[..] {
+            [hub] if self.modules[hub].kind == Kind::Conjunction => hub,
+            _ => return Err("Module rx must be fed by a single conjunction".into()),
+        };
+        let mut periods: Vec<Option<u64>> = vec![None; self.modules[hub].inputs.len()];
+        while periods.iter().any(Option::is_none) {
+            if self.presses >= MAX_PRESSES {
+                return Err(format!("No cycles found within {MAX_PRESSES} presses").into());
+            }
+            let presses = self.presses + 1;
+            let mut sent_high = Vec::new();
+            self.press_observed(|from, to, pulse| if to == hub && pulse == Pulse::High { sent_high.push(from) })?;
+            for from in sent_high {
+                if let Some(i) = self.modules[hub].inputs.iter().position(|&input| input == from) {
+                    periods[i].get_or_insert(presses);
+                }
+            }
+        }
+        periods.into_iter().flatten().try_fold(1u64, |result, period| {
+            (result / math::gcd(result, period)).checked_mul(period).ok_or_else(|| "Number of presses overflows".into())
+        })
+    }
+}
+
+/// Presses the button of the machine `presses` times and returns the CSV of the pulses of each
+/// press and the CSV of the levels of the modules after each press, as `Waveform::pulses_csv` and
+/// `Waveform::states_csv`.
+pub fn waveform_csv(input: &str, presses: u64) -> Result<(String, String), Box<dyn Error>> {
+    let waveform = Machine::from_input(input)?.waveform(presses)?;
+    Ok((waveform.pulses_csv(), waveform.states_csv()))
+}
+
+/// Both parts, each pressing the button of its own copy of the same machine
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Machine;
+
+    fn parse(input: &str) -> Result<Machine, Box<dyn Error>> {
+        Ok(Machine::from_input(input)?)
+    }
+
+    fn part1(machine: &Machine) -> Result<Answer, Box<dyn Error>> {
+        let mut machine = machine.clone();
+        for _ in 0..1000 {
+            machine.press()?;
+        }
+        let totals = machine.totals();
+        Ok((totals.low * totals.high).into())
+    }
+
+    fn part2(machine: &Machine) -> Result<Answer, Box<dyn Error>> {
+        Ok(machine.clone().presses_until_rx_low()?.into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as modules and a missing broadcaster.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues: Vec<Issue> = input.lines().enumerate()
+        .filter_map(|(i, line)| Module::parse(line).err().map(|e| Issue::at(i + 1, e.column, e.message)))
+        .collect();
+    if !issues.is_empty() {
+        return issues;
+    }
+    validate::check_all(Machine::from_input(input))
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if config.repl {
+        eprintln!("Reading file {}", config.file_path1);
+        let mut machine = Machine::from_input(&fs::read_to_string(config.file_path1)?)?;
+        eprintln!("Commands: press [n], state [module], totals, reset, quit");
+        machine.serve(io::stdin().lock(), io::stdout().lock())?;
+        return Ok(());
+    }
+
+    println!("Part 1: Reading file {}", config.file_path1);
+    let machine = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&machine)?);
+
+    let mut machine = answer::parse_part2(machine, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&machine)?);
+
+    if let Some((presses, pulses_path, states_path)) = config.waveform {
+        let waveform = machine.waveform(presses)?;
+        let (pulses, states) = (waveform.pulses_csv(), waveform.states_csv());
+        println!("Writing the pulses of {presses} presses of part 2 to {pulses_path}");
+        fs::write(pulses_path, pulses)?;
+        println!("Writing the module levels after each press to {states_path}");
+        fs::write(states_path, states)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &str = "
+broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a
+";
+
+    const EXAMPLE2: &str = "
+broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output
+";
+
+    /// A two-bit counter whose bits are inverted by x and y, such that hub sends a low pulse to rx
+    /// when both bits turn off, which happens every fourth press
+    const COUNTER: &str = "
+broadcaster -> a
+%a -> b, x
+%b -> y
+&x -> hub
+&y -> hub
+&hub -> rx
+";
+
+    #[test]
+    fn example1_part1() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1(EXAMPLE1.trim())?;
+        assert_eq!(result, 32000000);
+        Ok(())
+    }
+
+    #[test]
+    fn example2_part1() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1(EXAMPLE2.trim())?;
+        assert_eq!(result, 11687500);
+        Ok(())
+    }
+
+    #[test]
+    fn counter_part2() -> Result<(), Box<dyn Error>> {
+        let result = solve_part2(COUNTER.trim())?;
+        assert_eq!(result, 4);
+        // Check against pressing until rx receives a low pulse
+        let mut machine = Machine::from_input(COUNTER.trim())?;
+        let rx = machine.index_of_name["rx"];
+        let mut presses = 0;
+        let mut rx_low = false;
+        while !rx_low {
+            machine.press_observed(|_, to, pulse| rx_low |= to == rx && pulse == Pulse::Low)?;
+            presses += 1;
+        }
+        assert_eq!(presses, 4);
+        assert!(solve_part2(EXAMPLE2.trim()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn press_step_by_step() -> Result<(), Box<dyn Error>> {
+        let mut machine = Machine::from_input(EXAMPLE2.trim())?;
+        assert_eq!(machine.press()?, PulseStats { low: 4, high: 4 });
+        assert_eq!(machine.state("a"), Some(ModuleState::FlipFlop { on: true }));
+        assert_eq!(machine.state("b"), Some(ModuleState::FlipFlop { on: true }));
+        let remembered = vec![("a".to_owned(), Pulse::High), ("b".to_owned(), Pulse::High)];
+        assert_eq!(machine.state("con"), Some(ModuleState::Conjunction { remembered }));
+        assert_eq!(machine.state("output"), Some(ModuleState::Untyped));
+        assert_eq!(machine.state("rx"), None);
+        assert_eq!(machine.press()?, PulseStats { low: 4, high: 2 });
+        assert_eq!((machine.presses(), machine.totals()), (2, PulseStats { low: 8, high: 6 }));
+        machine.reset();
+        assert_eq!((machine.presses(), machine.totals()), (0, PulseStats::default()));
+        assert_eq!(machine.state("b"), Some(ModuleState::FlipFlop { on: false }));
+        Ok(())
+    }
+
+    #[test]
+    fn serve() -> Result<(), Box<dyn Error>> {
+        let mut machine = Machine::from_input(EXAMPLE2.trim())?;
+        let mut output = Vec::new();
+        machine.serve("press\nstate b\npress 3\nstate con\ntotals\nstate x\npush\nreset\nquit\npress\n".as_bytes(), &mut output)?;
+        assert_eq!(String::from_utf8(output)?, "\
+Press 1: 4 low, 4 high (total 4 low, 4 high)
+b: flip-flop on
+Presses 2 to 4: 13 low, 7 high (total 17 low, 11 high)
+con: conjunction a=low, b=low
+4 presses: 17 low, 11 high
+Error: No module x
+Error: Unknown command \"push\"
+Reset
+");
+        Ok(())
+    }
+
+    #[test]
+    fn counter_waveform() -> Result<(), Box<dyn Error>> {
+        let mut machine = Machine::from_input(COUNTER.trim())?;
+        let waveform = machine.waveform(4)?;
+        assert_eq!(machine.presses(), 4);
+        let names: Vec<&str> = waveform.timelines.iter().map(|timeline| timeline.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "x", "y", "hub"]);
+        // a is the low bit and b the high bit of the number of presses
+        assert_eq!(waveform.timelines[0].levels, [true, false, true, false]);
+        assert_eq!(waveform.timelines[1].levels, [false, true, true, false]);
+        // hub would send a low pulse only after both bits turned off, but on its way there it
+        // briefly sends a high pulse in every press
+        assert_eq!(waveform.timelines[4].levels, [true, true, true, false]);
+        assert_eq!(waveform.timelines[4].sent_high, [true; 4]);
+        assert_eq!(waveform.pulses_csv().lines().collect::<Vec<_>>(), ["press,low,high", "1,3,3", "2,5,4", "3,3,3", "4,6,3"]);
+        let pulses: u64 = waveform.pulses.iter().map(|stats| stats.low + stats.high).sum();
+        assert_eq!(pulses, machine.totals().low + machine.totals().high);
+        let (pulses_csv, states_csv) = waveform_csv(COUNTER.trim(), 4)?;
+        assert_eq!(pulses_csv, waveform.pulses_csv());
+        assert_eq!(states_csv, "\
+press,a,b,x,y,hub,x sent high,y sent high,hub sent high
+1,1,0,0,1,1,0,0,1
+2,0,1,1,0,1,1,0,1
+3,1,1,0,0,1,0,0,1
+4,0,0,1,1,0,1,1,1
+");
+        Ok(())
+    }
+
+    #[test]
+    fn runaway_machine() -> Result<(), Box<dyn Error>> {
+        // The conjunction keeps sending pulses to itself
+        let mut machine = Machine::from_input("broadcaster -> loop\n&loop -> loop")?;
+        assert_eq!(machine.press(), Err("Too many pulses in one press"));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_machines() {
+        assert_eq!(validate(EXAMPLE1.trim()), vec![]);
+        assert_eq!(validate("broadcaster -> a\n%a -> b\nb -> a\n%a"), vec![
+            Issue::at(3, 1, "Expected '%', '&' or broadcaster"),
+            Issue::at(4, 3, "Expected ' -> '"),
+        ]);
+        assert_eq!(validate("%a -> b"), vec![Issue::new("No broadcaster")]);
+        assert_eq!(validate("broadcaster -> a\n%a -> b\n&a -> b"), vec![Issue::new("Line 3: Module a is defined twice")]);
+    }
+}
@@ -0,0 +1,34 @@
+pub struct Config {
+    pub file_path1: String,
+    pub file_path2: String,
+    /// Print which plots of the garden of part 1 are reachable after an even or odd number of steps
+    pub parity: bool,
+    /// Number of steps for which to count the reachable plots of the garden of part 2, with and
+    /// without leaving it
+    pub steps: Option<u64>,
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        const USAGE: &str = "Usage: day21 <file for part 1> <file for part 2> [--parity] [--steps <n>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut parity = false;
+        let mut steps = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--parity" => parity = true,
+                "--steps" => steps = Some(iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config {
+            file_path1: args[1].clone(),
+            file_path2: args[2].clone(),
+            parity,
+            steps,
+        })
+    }
+}
@@ -0,0 +1,497 @@
+pub mod config;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::render::{Frame, FrameSource};
+use aoc_core::search::{self, Search};
+use aoc_core::validate::{self, Issue};
+
+/// Number of steps of part 1
+const STEPS1: u64 = 64;
+
+/// Number of steps of part 2
+const STEPS2: u64 = 26501365;
+
+/// Most steps `Garden::reachable_after` explores when it cannot extrapolate, as the number of
+/// plots to explore grows quadratically with the steps
+const MAX_FALLBACK_STEPS: u64 = 2000;
+
+/// Number of steps shown by the frames created by `ReachFrames::from_input`
+pub const DEFAULT_FRAME_STEPS: u64 = STEPS1;
+
+/// A garden with rocks and the starting position of the elf
+pub struct Garden {
+    rocks: Vec<Vec<bool>>,
+    width: usize,
+    height: usize,
+    start: (i64, i64),
+}
+
+/// How `Garden::reachable_after` counts the reachable plots
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Method {
+    /// Exploring the plots up to the number of steps
+    Bfs,
+    /// Exploring the finite region the elf can reach once and counting the plots whose distance
+    /// has the parity of the number of steps
+    Parity,
+    /// Exploring a few multiples of the garden size and extrapolating quadratically
+    Extrapolation,
+}
+
+impl Garden {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err("Empty input".into());
+        }
+        let mut rocks = Vec::new();
+        let mut start = None;
+        for (y, line) in input.lines().enumerate() {
+            if line.chars().count() != width {
+                return Err("Not all lines have the same length".into());
+            }
+            let row = line.chars().enumerate().map(|(x, c)| match c {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                'S' if start.is_none() => {
+                    start = Some((x as i64, y as i64));
+                    Ok(false)
+                },
+                'S' => Err("More than one starting position"),
+                _ => Err("Invalid character"),
+            }).collect::<Result<_, _>>()?;
+            rocks.push(row);
+        }
+        let start = start.ok_or("No starting position")?;
+        Ok(Self { width, height: rocks.len(), rocks, start })
+    }
+
+    /// Whether there is a rock at the position, where the garden repeats infinitely in all
+    /// directions
+    fn is_rock(&self, (x, y): (i64, i64)) -> bool {
+        self.rocks[y.rem_euclid(self.height as i64) as usize][x.rem_euclid(self.width as i64) as usize]
+    }
+
+    fn in_garden(&self, (x, y): (i64, i64)) -> bool {
+        (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y)
+    }
+
+    /// Finds the least number of steps to each plot reachable within `max_steps`. If `infinite` is
+    /// set, the garden repeats in all directions, otherwise the elf cannot leave it.
+    fn distances(&self, max_steps: u64, infinite: bool) -> Search<(i64, i64), usize> {
+        let successors = |&(x, y): &(i64, i64)| {
+            [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].into_iter()
+                .filter(move |&position| (infinite || self.in_garden(position)) && !self.is_rock(position))
+        };
+        search::bfs([self.start], successors, |_, distance| distance as u64 > max_steps)
+    }
+
+    /// Whether a plot at the given least distance can be reached in exactly `steps` steps. Since
+    /// every step changes the parity of x + y, this is the case if it is not too far and the
+    /// parities of its distance and `steps` agree, as the elf can always step back and forth.
+    fn reachable_in(distance: usize, steps: u64) -> bool {
+        distance as u64 <= steps && distance as u64 % 2 == steps % 2
+    }
+
+    /// Counts the plots reachable in exactly `steps` steps by exploring them.
+    fn reachable(&self, steps: u64, infinite: bool) -> usize {
+        self.distances(steps, infinite).costs().filter(|&(_, distance)| Self::reachable_in(distance, steps)).count()
+    }
+
+    /// Counts the plots reachable in exactly `steps` steps in the infinite garden. For many steps,
+    /// this assumes that the number grows quadratically when `steps` grows by the size of the
+    /// garden, which is the case for the puzzle inputs with their clear middle row and column; the
+    /// assumption is checked against one more exploration. Returns `None` if the garden is not
+    /// square or the assumption does not hold.
+    fn reachable_extrapolated(&self, steps: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        if self.width != self.height {
+            return Ok(None);
+        }
+        let size = self.width as u64;
+        let (n, rem) = (steps / size, steps % size);
+        if n < 4 {
+            return Ok(Some(self.reachable(steps, true) as u64));
+        }
+        let f: Vec<i128> = (0..4).map(|k| self.reachable(rem + k * size, true) as i128).collect();
+        let (d1, d2) = (f[1] - f[0], f[2] - 2 * f[1] + f[0]);
+        let quadratic = |n: i128| -> Option<i128> {
+            f[0].checked_add(n.checked_mul(d1)?)?.checked_add((n.checked_mul(n - 1)? / 2).checked_mul(d2)?)
+        };
+        if quadratic(3) != Some(f[3]) {
+            return Ok(None);
+        }
+        let result = quadratic(n as i128).ok_or("Number of plots overflows")?;
+        Ok(Some(u64::try_from(result)?))
+    }
+
+    /// Whether the elf cannot leave the garden. The exploration stops at the first plot outside
+    /// it, so it never visits more than the plots of one garden.
+    fn enclosed(&self, infinite: bool) -> bool {
+        if !infinite {
+            return true;
+        }
+        let successors = |&(x, y): &(i64, i64)| {
+            [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].into_iter().filter(|&position| !self.is_rock(position))
+        };
+        search::bfs([self.start], successors, |&position, _| !self.in_garden(position)).goal.is_none()
+    }
+
+    /// Chooses how to count the plots reachable in exactly `steps` steps: by parity if the elf
+    /// cannot get farther than `steps`, by extrapolation if it can get more than four garden
+    /// sizes away, and by exploration otherwise. `reachable_after` falls back to exploration if
+    /// extrapolation is not possible.
+    pub fn method(&self, steps: u64, infinite: bool) -> Method {
+        if self.enclosed(infinite) {
+            if steps >= (self.width * self.height) as u64 { Method::Parity } else { Method::Bfs }
+        } else if steps >= 4 * self.width.max(self.height) as u64 {
+            Method::Extrapolation
+        } else {
+            Method::Bfs
+        }
+    }
+
+    /// Counts the plots reachable in exactly `steps` steps for any number of steps, using the
+    /// method chosen by `method`, and returns the method used. If the garden is not square or the
+    /// number of plots does not grow quadratically, the plots are explored instead of
+    /// extrapolating, which fails for more than `MAX_FALLBACK_STEPS` steps.
+    pub fn reachable_after(&self, steps: u64, infinite: bool) -> Result<(u64, Method), Box<dyn Error>> {
+        let method = self.method(steps, infinite);
+        if method == Method::Extrapolation {
+            if let Some(plots) = self.reachable_extrapolated(steps)? {
+                return Ok((plots, method));
+            }
+            if steps > MAX_FALLBACK_STEPS {
+                return Err(format!("Cannot extrapolate and too many steps to explore: {steps}").into());
+            }
+        }
+        let plots = match method {
+            Method::Parity => {
+                let plots = (self.width * self.height) as u64;
+                self.distances(plots, infinite).costs().filter(|&(_, distance)| distance as u64 % 2 == steps % 2).count()
+            },
+            Method::Bfs | Method::Extrapolation => self.reachable(steps, infinite),
+        };
+        let method = if method == Method::Parity { Method::Parity } else { Method::Bfs };
+        Ok((plots as u64, method))
+    }
+}
+
+/// Whether a plot can be reached in an even or odd number of steps
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parity {
+    Even,
+    Odd,
+    /// A plot that is cut off by rocks
+    Unreachable,
+    Rock,
+}
+
+/// The parity of each plot of a garden whose elf cannot leave it. A plot is reachable in exactly
+/// n steps if n has its parity and is at least its distance from the start.
+pub struct ParityMap {
+    pub rows: Vec<Vec<Parity>>,
+}
+
+impl ParityMap {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let garden = Garden::from_input(input)?;
+        let search = garden.distances(u64::MAX, false);
+        let rows = (0..garden.height as i64).map(|y| (0..garden.width as i64).map(|x| {
+            match search.cost(&(x, y)) {
+                _ if garden.is_rock((x, y)) => Parity::Rock,
+                Some(distance) if distance % 2 == 0 => Parity::Even,
+                Some(_) => Parity::Odd,
+                None => Parity::Unreachable,
+            }
+        }).collect()).collect();
+        Ok(Self { rows })
+    }
+
+    /// Number of plots with the given parity
+    pub fn count(&self, parity: Parity) -> usize {
+        self.rows.iter().flatten().filter(|&&p| p == parity).count()
+    }
+}
+
+/// Shows even plots as 'E', odd plots as 'O', unreachable plots as '.' and rocks as '#'.
+impl fmt::Display for ParityMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            let line: String = row.iter().map(|parity| match parity {
+                Parity::Even => 'E',
+                Parity::Odd => 'O',
+                Parity::Unreachable => '.',
+                Parity::Rock => '#',
+            }).collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Frames showing the plots reachable in exactly 0, 1, … steps, marked with 'O'. With `copies` of
+/// zero, only the garden is shown and the elf cannot leave it. Otherwise the garden repeats in all
+/// directions, and the frames show `copies` copies of it on each side of the original.
+pub struct ReachFrames {
+    garden: Garden,
+    search: Search<(i64, i64), usize>,
+    steps: u64,
+    copies: usize,
+    next: u64,
+}
+
+impl ReachFrames {
+    pub fn new(input: &str, steps: u64, copies: usize) -> Result<Self, Box<dyn Error>> {
+        let garden = Garden::from_input(input)?;
+        let search = garden.distances(steps, copies > 0);
+        Ok(Self { garden, search, steps, copies, next: 0 })
+    }
+
+    /// Frames for `DEFAULT_FRAME_STEPS` steps in the garden alone
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Self::new(input, DEFAULT_FRAME_STEPS, 0)
+    }
+
+    fn lines(&self, steps: u64) -> Vec<String> {
+        let copies = self.copies as i64;
+        let (width, height) = (self.garden.width as i64, self.garden.height as i64);
+        (-copies * height..(copies + 1) * height).map(|y| (-copies * width..(copies + 1) * width).map(|x| {
+            match self.search.cost(&(x, y)) {
+                _ if self.garden.is_rock((x, y)) => '#',
+                Some(distance) if Garden::reachable_in(distance, steps) => 'O',
+                _ if (x, y) == self.garden.start => 'S',
+                _ => '.',
+            }
+        }).collect()).collect()
+    }
+}
+
+impl FrameSource for ReachFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        if self.next > self.steps {
+            return None;
+        }
+        let steps = self.next;
+        let reachable = self.search.costs().filter(|&(_, distance)| Garden::reachable_in(distance, steps)).count();
+        self.next += 1;
+        Some(Frame::new(format!("Step {steps}, {reachable} plots reachable"), self.lines(steps)))
+    }
+}
+
+/// Counts the plots reachable in exactly `steps` steps. If `infinite` is set, the garden repeats in
+/// all directions, otherwise the elf cannot leave it.
+pub fn reachable(input: &str, steps: u64, infinite: bool) -> Result<usize, Box<dyn Error>> {
+    Ok(Garden::from_input(input)?.reachable(steps, infinite))
+}
+
+/// Counts the plots reachable in exactly `steps` steps for any number of steps, choosing the
+/// method automatically, and returns the method as well.
+pub fn reachable_after(input: &str, steps: u64, infinite: bool) -> Result<(u64, Method), Box<dyn Error>> {
+    Garden::from_input(input)?.reachable_after(steps, infinite)
+}
+
+/// Both parts, solved from the same garden
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Garden;
+
+    fn parse(input: &str) -> Result<Garden, Box<dyn Error>> {
+        Garden::from_input(input)
+    }
+
+    fn part1(garden: &Garden) -> Result<Answer, Box<dyn Error>> {
+        Ok(garden.reachable(STEPS1, false).into())
+    }
+
+    fn part2(garden: &Garden) -> Result<Answer, Box<dyn Error>> {
+        Ok(garden.reachable_after(STEPS2, true)?.0.into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines, characters other than '.', '#' and 'S' and a missing or repeated starting
+/// position.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues = validate::check_grid(input, |c| matches!(c, '.' | '#' | 'S'));
+    if !issues.is_empty() {
+        return issues;
+    }
+    validate::check_all(Garden::from_input(input))
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    println!("Part 1: Reading file {}", config.file_path1);
+    let contents = fs::read_to_string(&config.file_path1)?;
+    let garden = Day::parse(&contents)?;
+    println!("Result of part 1: {}", Day::part1(&garden)?);
+
+    if config.parity {
+        let map = ParityMap::from_input(&contents)?;
+        print!("{map}");
+        println!("{} even, {} odd, {} unreachable plots", map.count(Parity::Even), map.count(Parity::Odd), map.count(Parity::Unreachable));
+    }
+
+    let garden = answer::parse_part2(garden, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&garden)?);
+
+    if let Some(steps) = config.steps {
+        for (infinite, name) in [(false, "bounded"), (true, "infinite")] {
+            match garden.reachable_after(steps, infinite) {
+                Ok((plots, method)) => println!("{plots} plots reachable in the {name} garden after {steps} steps ({method:?})"),
+                Err(e) => println!("Plots reachable in the {name} garden after {steps} steps: {e}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc_core::render;
+
+    const EXAMPLE: &str = "
+...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........
+";
+
+    #[test]
+    fn example_reachable() -> Result<(), Box<dyn Error>> {
+        assert_eq!(reachable(EXAMPLE.trim(), 6, false)?, 16);
+        assert_eq!(reachable(EXAMPLE.trim(), 6, true)?, 16);
+        assert_eq!(reachable(EXAMPLE.trim(), 10, true)?, 50);
+        assert_eq!(reachable(EXAMPLE.trim(), 50, true)?, 1594);
+        assert_eq!(reachable(EXAMPLE.trim(), 100, true)?, 6536);
+        Ok(())
+    }
+
+    #[test]
+    fn extrapolation() -> Result<(), Box<dyn Error>> {
+        // Without rocks, the plots reachable in n steps form a diamond of (n + 1)² plots
+        let garden = Garden::from_input(".....\n.....\n..S..\n.....\n.....")?;
+        assert_eq!(garden.reachable_extrapolated(52)?, Some(53 * 53));
+        assert_eq!(garden.reachable_extrapolated(10_000_002)?, Some(10_000_003 * 10_000_003));
+        // The example has no clear middle row and column
+        let garden = Garden::from_input(EXAMPLE.trim())?;
+        assert_eq!(garden.reachable_extrapolated(40)?, Some(garden.reachable(40, true) as u64));
+        assert_eq!(garden.reachable_extrapolated(100)?, None);
+        assert_eq!(Garden::from_input("...\n.S.")?.reachable_extrapolated(100)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn reachable_after_any_steps() -> Result<(), Box<dyn Error>> {
+        let garden = Garden::from_input(EXAMPLE.trim())?;
+        for steps in [0, 1, 6, 7, 30, 120, 121, 1_000_000_001] {
+            let expected = garden.reachable(steps.min(1000 + steps % 2), false) as u64;
+            assert_eq!(garden.reachable_after(steps, false)?.0, expected, "{steps} steps");
+        }
+        assert_eq!(garden.method(6, false), Method::Bfs);
+        assert_eq!(garden.method(121, false), Method::Parity);
+        assert_eq!(reachable_after(EXAMPLE.trim(), 10, true)?, (50, Method::Bfs));
+        // The example has no clear middle row and column, so its plots are explored
+        assert_eq!(garden.method(50, true), Method::Extrapolation);
+        assert_eq!(garden.reachable_after(50, true)?, (1594, Method::Bfs));
+        assert!(garden.reachable_after(STEPS2, true).is_err());
+
+        // Enclosed by rocks, the elf is as limited in the infinite garden as in the bounded one
+        let enclosed = Garden::from_input("#####\n#...#\n#.S.#\n#...#\n#####")?;
+        assert_eq!(enclosed.method(1_000_000, true), Method::Parity);
+        assert_eq!(enclosed.reachable_after(1_000_000, true)?, (5, Method::Parity));
+        assert_eq!(enclosed.reachable_after(1_000_001, true)?, (4, Method::Parity));
+        assert_eq!(enclosed.reachable_after(1, true)?, (4, Method::Bfs));
+
+        let open = Garden::from_input(".....\n.....\n..S..\n.....\n.....")?;
+        assert_eq!(open.method(19, true), Method::Bfs);
+        assert_eq!(open.method(20, true), Method::Extrapolation);
+        assert_eq!(open.reachable_after(1_000_000, true)?, (1_000_001 * 1_000_001, Method::Extrapolation));
+        Ok(())
+    }
+
+    /// A garden of the size of the puzzle inputs with isolated rocks and a clear border, middle
+    /// row and middle column
+    fn full_size_garden(rocks: bool) -> String {
+        (0..131).map(|y| {
+            let row: String = (0..131).map(|x| match (x, y) {
+                (65, 65) => 'S',
+                _ if rocks && x % 4 == 2 && y % 4 == 2 && x != 65 && y != 65 => '#',
+                _ => '.',
+            }).collect();
+            row + "\n"
+        }).collect()
+    }
+
+    #[test]
+    fn full_size_part2() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part2(&full_size_garden(false))?, (STEPS2 + 1) * (STEPS2 + 1));
+        let garden = Garden::from_input(&full_size_garden(true))?;
+        assert_eq!(garden.reachable_after(STEPS2, true)?.1, Method::Extrapolation);
+        Ok(())
+    }
+
+    #[test]
+    fn parity_map() -> Result<(), Box<dyn Error>> {
+        let map = ParityMap::from_input("S.#\n.#.\n#..")?;
+        assert_eq!(map.to_string(), "EO#\nO#.\n#..\n");
+        assert_eq!((map.count(Parity::Even), map.count(Parity::Odd), map.count(Parity::Unreachable)), (1, 2, 3));
+        let map = ParityMap::from_input(EXAMPLE.trim())?;
+        assert_eq!(map.count(Parity::Even), reachable(EXAMPLE.trim(), 100, false)?);
+        Ok(())
+    }
+
+    #[test]
+    fn frames() -> Result<(), Box<dyn Error>> {
+        let mut frames = ReachFrames::new(EXAMPLE.trim(), 6, 0)?;
+        let first = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(first.caption, "Step 0, 1 plots reachable");
+        assert_eq!(first.lines[5], ".##..O####.");
+        let second = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(second.caption, "Step 1, 2 plots reachable");
+        assert_eq!(second.lines[4..7], ["....#O#....", ".##.OS####.", ".##..#...#."]);
+        let last = render::last_frame(&mut frames).ok_or("No frame")?;
+        assert_eq!(last.caption, "Step 6, 16 plots reachable");
+        assert_eq!(last.lines.iter().map(|line| line.matches('O').count()).sum::<usize>(), 16);
+        let mut frames = ReachFrames::new(EXAMPLE.trim(), 10, 1)?;
+        let last = render::last_frame(&mut frames).ok_or("No frame")?;
+        assert_eq!(last.caption, "Step 10, 50 plots reachable");
+        assert_eq!((last.lines.len(), last.lines[0].len()), (33, 33));
+        assert_eq!(last.lines.iter().map(|line| line.matches('O').count()).sum::<usize>(), 50);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_gardens() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        assert_eq!(validate("..\n.."), vec![Issue::new("No starting position")]);
+        assert_eq!(validate("S.\n.S"), vec![Issue::new("More than one starting position")]);
+        assert_eq!(validate("S.\n.x"), vec![Issue::at(2, 2, "Invalid character 'x'")]);
+    }
+}
@@ -1,17 +1,28 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Instead of solving, find the largest set of bricks of the first file that can be
+    /// disintegrated together
+    pub safe_removal: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day22 <file for part 1> <file for part 2> [--safe-removal]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut safe_removal = false;
+        for arg in &args[3..] {
+            match arg.as_str() {
+                "--safe-removal" => safe_removal = true,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            safe_removal,
         })
     }
 }
-
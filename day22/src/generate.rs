@@ -0,0 +1,154 @@
+//! Random snapshots of falling bricks, for property tests and benchmarks.
+
+use std::collections::{HashMap, HashSet};
+
+use aoc_core::rng::{Rng, SeededRng};
+
+/// Number of placements tried per brick before giving up on finding a free spot
+const MAX_ATTEMPTS_PER_BRICK: usize = 100;
+
+/// How the lower ends of generated bricks are distributed along the z axis
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Heights {
+    /// Uniformly between 1 and the given height, so that most bricks fall
+    Uniform(usize),
+    /// Right above the highest brick generated so far below the brick, plus a random gap of at
+    /// most `max_gap`, so that the bricks form a dense stack
+    Stacked { max_gap: usize },
+}
+
+/// Shape of a generated snapshot
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BrickOptions {
+    /// Number of bricks
+    pub count: usize,
+    /// Extent of the area the bricks cover along the x and y axis
+    pub footprint: (usize, usize),
+    /// Greatest number of cubes of a brick, which extends along a random axis
+    pub max_length: usize,
+    pub heights: Heights,
+}
+
+/// Generates a snapshot of bricks in the puzzle input format, no two of which overlap. Equal seeds
+/// give equal snapshots. Fails if the footprint or `max_length` is 0, if a brick longer than the
+/// footprint is requested, or if no free spot is found for a brick with uniform heights, which
+/// happens if the space is too crowded.
+pub fn generate_bricks(options: &BrickOptions, seed: u64) -> Result<String, &'static str> {
+    let (width, depth) = options.footprint;
+    if width == 0 || depth == 0 || options.max_length == 0 {
+        return Err("Footprint and brick length must be positive");
+    }
+    if options.max_length > width.min(depth) {
+        return Err("Bricks must fit into the footprint");
+    }
+    let mut rng = SeededRng::new(seed);
+    let mut random = move |max: usize| rng.index(max);
+    // Highest cube at each (x, y) position for stacked heights, and all cubes for uniform ones
+    let mut tops: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut cubes: HashSet<(usize, usize, usize)> = HashSet::new();
+    let mut lines = Vec::with_capacity(options.count);
+    for _ in 0..options.count {
+        let mut attempts = 0;
+        let (min, max) = loop {
+            attempts += 1;
+            if attempts > MAX_ATTEMPTS_PER_BRICK {
+                return Err("Found no free spot for a brick");
+            }
+            let extent = random(options.max_length);
+            let axis = random(3);
+            let (dx, dy, dz) = match axis {
+                0 => (extent, 0, 0),
+                1 => (0, extent, 0),
+                _ => (0, 0, extent),
+            };
+            let (x, y) = (random(width - dx), random(depth - dy));
+            let cells = || (x..=x + dx).flat_map(move |x| (y..=y + dy).map(move |y| (x, y)));
+            let z = match options.heights {
+                Heights::Uniform(max_z) => 1 + random(max_z.max(1)),
+                Heights::Stacked { max_gap } => {
+                    1 + cells().filter_map(|cell| tops.get(&cell)).max().copied().unwrap_or(0) + random(max_gap + 1)
+                },
+            };
+            let brick_cubes = || cells().flat_map(move |(x, y)| (z..=z + dz).map(move |z| (x, y, z)));
+            if let Heights::Uniform(_) = options.heights {
+                if brick_cubes().any(|cube| cubes.contains(&cube)) {
+                    continue;
+                }
+                cubes.extend(brick_cubes());
+            } else {
+                for cell in cells() {
+                    tops.insert(cell, z + dz);
+                }
+            }
+            break ((x, y, z), (x + dx, y + dy, z + dz));
+        };
+        lines.push(format!("{},{},{}~{},{},{}", min.0, min.1, min.2, max.0, max.1, max.2));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::time::Instant;
+
+    use super::*;
+    use crate::Puzzle;
+
+    #[test]
+    fn generated_bricks_do_not_overlap() -> Result<(), Box<dyn Error>> {
+        for seed in 0..20 {
+            for heights in [Heights::Uniform(50), Heights::Stacked { max_gap: 3 }] {
+                let options = BrickOptions { count: 200, footprint: (5, 8), max_length: 4, heights };
+                let input = generate_bricks(&options, seed)?;
+                assert_eq!(input.lines().count(), 200);
+                let mut puzzle = Puzzle::from_input(&input)?;
+                puzzle.drop_blocks();
+                assert!(puzzle.num_disintegratable() > 0);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stacked_bricks_without_gaps_rest_in_place() -> Result<(), Box<dyn Error>> {
+        let options = BrickOptions { count: 500, footprint: (10, 10), max_length: 5, heights: Heights::Stacked { max_gap: 0 } };
+        let mut puzzle = Puzzle::from_input(&generate_bricks(&options, 7)?)?;
+        assert!(puzzle.drop_blocks().iter().all(|&distance| distance == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn generation_is_deterministic() -> Result<(), Box<dyn Error>> {
+        let options = BrickOptions { count: 50, footprint: (4, 4), max_length: 3, heights: Heights::Uniform(20) };
+        assert_eq!(generate_bricks(&options, 1)?, generate_bricks(&options, 1)?);
+        assert!(generate_bricks(&BrickOptions { max_length: 5, ..options }, 1).is_err());
+        assert!(generate_bricks(&BrickOptions { footprint: (0, 4), ..options }, 1).is_err());
+        // 17 cubes do not fit into a 2 × 2 × 4 space
+        let crowded = BrickOptions { count: 17, footprint: (2, 2), max_length: 1, heights: Heights::Uniform(4) };
+        assert!(generate_bricks(&crowded, 1).is_err());
+        Ok(())
+    }
+
+    /// Times each step of solving dense snapshots of 10k and 20k bricks. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_large_snapshots() -> Result<(), Box<dyn Error>> {
+        for count in [10_000, 20_000] {
+            let options = BrickOptions { count, footprint: (10, 10), max_length: 5, heights: Heights::Stacked { max_gap: 2 } };
+            let input = generate_bricks(&options, 1)?;
+            let mut puzzle = Puzzle::from_input(&input)?;
+            let start = Instant::now();
+            puzzle.drop_blocks();
+            println!("{count} bricks: drop_blocks {:?}", start.elapsed());
+            let start = Instant::now();
+            let disintegratable = puzzle.num_disintegratable();
+            println!("{count} bricks: num_disintegratable {:?} ({disintegratable})", start.elapsed());
+            let start = Instant::now();
+            let falling = puzzle.sum_falling();
+            println!("{count} bricks: sum_falling {:?} ({falling})", start.elapsed());
+        }
+        Ok(())
+    }
+}
@@ -1,27 +1,80 @@
 pub mod config;
+mod generate;
+mod removal;
 
-use std::collections::HashSet;
+pub use generate::{generate_bricks, BrickOptions, Heights};
+pub use removal::{max_safe_removal, SafeRemoval, DEFAULT_NODE_BUDGET};
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::render::{Frame, FrameSource};
+use aoc_core::validate::{self, Issue};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::fs;
 
+/// Index of a brick in the input
 type BlockId = usize;
 
+/// Coordinate along any axis. Coordinates may be negative, except that bricks must lie above the
+/// ground at z = 0.
+type Coordinate = i64;
+
+/// Letter labeling the brick at the given index of the input, as in the puzzle description, where
+/// the 27th brick is labeled 'A' again
+pub fn label(original_index: usize) -> char {
+    (b'A' + (original_index % 26) as u8) as char
+}
+
 #[derive(Clone, Debug)]
 struct Block {
     id: BlockId,
-    min_x: usize,
-    max_x: usize,
-    min_y: usize,
-    max_y: usize,
-    min_z: usize,
-    max_z: usize,
+    min_x: Coordinate,
+    max_x: Coordinate,
+    min_y: Coordinate,
+    max_y: Coordinate,
+    min_z: Coordinate,
+    max_z: Coordinate,
 }
 
+impl Block {
+    fn from_line(id: BlockId, line: &str) -> Result<Self, Box<dyn Error>> {
+        let (pos1, pos2) = line.split_once('~').ok_or("Tilde not found")?;
+        let mut iter1 = pos1.splitn(3, ',');
+        let mut iter2 = pos2.splitn(3, ',');
+        let x1: Coordinate = iter1.next().ok_or("Syntax error")?.parse()?;
+        let y1: Coordinate = iter1.next().ok_or("Syntax error")?.parse()?;
+        let z1: Coordinate = iter1.next().ok_or("Syntax error")?.parse()?;
+        let x2: Coordinate = iter2.next().ok_or("Syntax error")?.parse()?;
+        let y2: Coordinate = iter2.next().ok_or("Syntax error")?.parse()?;
+        let z2: Coordinate = iter2.next().ok_or("Syntax error")?.parse()?;
+        // The ground is at z = 0
+        if z1.min(z2) < 1 {
+            return Err(format!("Brick below the ground: its lowest z is {}, but must be at least 1", z1.min(z2)).into());
+        }
+        Ok(Block {
+            id,
+            min_x: x1.min(x2),
+            max_x: x1.max(x2),
+            min_y: y1.min(y2),
+            max_y: y1.max(y2),
+            min_z: z1.min(z2),
+            max_z: z1.max(z2),
+        })
+    }
+
+    /// The (x, y) coordinates covered by the block
+    fn cells(&self) -> impl Iterator<Item = (Coordinate, Coordinate)> {
+        let (min_y, max_y) = (self.min_y, self.max_y);
+        (self.min_x..=self.max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+    }
+}
+
+/// Top of the bricks stacked at an (x, y) position: its height and the brick reaching it. Only
+/// positions covered by some brick are stored, so the memory does not depend on the magnitude of
+/// the coordinates.
+type HeightMap = HashMap<(Coordinate, Coordinate), (Coordinate, BlockId)>;
+
 struct Puzzle {
     blocks: Vec<Block>,
-    heights: Vec<Vec<usize>>,
-    max_x: usize,
-    max_y: usize,
     supports: Vec<HashSet<BlockId>>,
     supported_by: Vec<HashSet<BlockId>>,
     supports_exclusively: Vec<HashSet<BlockId>>,
@@ -29,46 +82,28 @@ struct Puzzle {
 
 impl Puzzle {
     fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
-        let mut blocks = Vec::new();
-        let mut global_max_x = 0;
-        let mut global_max_y = 0;
-        let mut id = 0;
-        for line in input.lines() {
-            let (pos1, pos2) = line.split_once("~").ok_or("Tilde not found")?;
-            let mut iter1 = pos1.splitn(3, ",");
-            let mut iter2 = pos2.splitn(3, ",");
-            let x1: usize = iter1.next().ok_or("Syntax error")?.parse()?;
-            let y1: usize = iter1.next().ok_or("Syntax error")?.parse()?;
-            let z1: usize = iter1.next().ok_or("Syntax error")?.parse()?;
-            let x2: usize = iter2.next().ok_or("Syntax error")?.parse()?;
-            let y2: usize = iter2.next().ok_or("Syntax error")?.parse()?;
-            let z2: usize = iter2.next().ok_or("Syntax error")?.parse()?;
-            let min_x = x1.min(x2);
-            let min_y = y1.min(y2);
-            let min_z = z1.min(z2);
-            let max_x = x1.max(x2);
-            let max_y = y1.max(y2);
-            let max_z = z1.max(z2);
-            global_max_x = global_max_x.max(x1).max(x2);
-            global_max_y = global_max_y.max(y1).max(y2);
-            let block = Block { id, min_x, max_x, min_y, max_y, min_z, max_z };
-            blocks.push(block);
-            id += 1;
-        }
-        blocks.sort_by_key(|b| b.min_z);
-        let mut heights = vec![vec![0; global_max_y+1]; global_max_x+1];
+        let mut blocks = input.lines().enumerate()
+            .map(|(id, line)| Block::from_line(id, line))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Bricks are only ever compared by their z coordinates, so gaps along the z axis cost
+        // nothing. They settle from the bottom up, and bricks starting at the same height settle
+        // in input order.
+        blocks.sort_by_key(|b| (b.min_z, b.id));
+        let mut heights = HeightMap::new();
         for block in &blocks {
-            for x in block.min_x..=block.max_x {
-                for y in block.min_y..=block.max_y {
-                    assert!(heights[x][y] < block.max_z, "{} >= {}", heights[x][y], block.max_z);
-                    heights[x][y] = block.max_z;
+            for cell in block.cells() {
+                if let Some(&(height, other)) = heights.get(&cell) {
+                    if height >= block.min_z {
+                        return Err(format!(
+                            "Bricks {} (line {}) and {} (line {}) overlap",
+                            label(other), other + 1, label(block.id), block.id + 1,
+                        ).into());
+                    }
                 }
+                heights.insert(cell, (block.max_z, block.id));
             }
         }
         Ok(Self {
-            heights,
-            max_x: global_max_y,
-            max_y: global_max_y,
             supports: vec![HashSet::new(); blocks.len()],
             supported_by: vec![HashSet::new(); blocks.len()],
             supports_exclusively: vec![HashSet::new(); blocks.len()],
@@ -76,93 +111,293 @@ impl Puzzle {
         })
     }
 
-    fn drop_blocks(&mut self) {
-        for vec in &mut self.heights {
-            vec.fill(0);
-        }
-        // Block ID and its max_z
-        let mut highest_block: Vec<Vec<Option<(BlockId, usize)>>> = vec![vec![None; self.max_y + 1]; self.max_x + 1];
-        for block in &mut self.blocks {
-            let mut z = 0;  // greatest height value under the block
-            for x in block.min_x..=block.max_x {
-                for y in block.min_y..=block.max_y {
-                    z = z.max(self.heights[x][y]);
-                }
-            }
-            for x in block.min_x..=block.max_x {
-                for y in block.min_y..=block.max_y {
-                    if let Some((block_below_id, block_below_max_z)) = highest_block[x][y] {
-                        if block_below_max_z == z {
-                            self.supported_by[block.id].insert(block_below_id);
-                            self.supports[block_below_id].insert(block.id);
-                        }
-                    }
-                }
-            }
-            let block_height = block.max_z - block.min_z + 1;
-            block.min_z = z + 1;
-            block.max_z = z + block_height;
-            for x in block.min_x..=block.max_x {
-                for y in block.min_y..=block.max_y {
-                    assert!(self.heights[x][y] < block.max_z, "{} >= {}", self.heights[x][y], block.max_z);
-                    self.heights[x][y] = block.max_z;
-                    highest_block[x][y] = Some((block.id, block.max_z));
+    /// Lets the block at index `i` fall onto the blocks recorded in `heights` and returns the
+    /// distance it fell.
+    fn settle(&mut self, i: usize, heights: &mut HeightMap) -> Coordinate {
+        let block = &mut self.blocks[i];
+        // Greatest height under the block
+        let z = block.cells().filter_map(|cell| heights.get(&cell)).map(|&(height, _)| height).max().unwrap_or(0);
+        for cell in block.cells() {
+            if let Some(&(height, block_below_id)) = heights.get(&cell) {
+                if height == z {
+                    self.supported_by[block.id].insert(block_below_id);
+                    self.supports[block_below_id].insert(block.id);
                 }
             }
         }
+        let distance = block.min_z - (z + 1);
+        block.min_z -= distance;
+        block.max_z -= distance;
+        for cell in block.cells() {
+            heights.insert(cell, (block.max_z, block.id));
+        }
+        distance
+    }
+
+    /// Lets all blocks fall and returns the distance each one fell, in settle order.
+    fn drop_blocks(&mut self) -> Vec<Coordinate> {
+        let mut heights = HeightMap::new();
+        let distances = (0..self.blocks.len()).map(|i| self.settle(i, &mut heights)).collect();
         for block in &self.blocks {
             self.supports_exclusively[block.id] = self.supports[block.id].iter().cloned().filter(
                 |&id| self.supported_by[id].len() == 1
             ).collect();
         }
+        distances
     }
 
     fn num_disintegratable(&self) -> usize {
         self.blocks.iter().filter(|b| self.supports_exclusively[b.id].is_empty()).count()
     }
 
+    /// Number of other bricks that fall if the brick at index `i` of the (dropped) blocks is
+    /// disintegrated
+    fn num_falling(&self, i: usize) -> usize {
+        let mut removed = HashSet::new();
+        removed.insert(self.blocks[i].id);
+        for other_block in &self.blocks[i+1..] {
+            let support = &self.supported_by[other_block.id];
+            // Is support a subset of removed?
+            if !support.is_empty() && support.iter().all(|id| removed.contains(id)) {
+                removed.insert(other_block.id);
+            }
+        }
+        removed.len() - 1
+    }
+
     fn sum_falling(&self) -> usize {
-        let mut n = 0;
-        for (i, block) in self.blocks.iter().enumerate() {
-            let mut removed = HashSet::new();
-            removed.insert(block.id);
-            for other_block in &self.blocks[i+1..] {
-                let support = &self.supported_by[other_block.id];
-                // Is support a subset of removed?
-                if !support.is_empty() && support.iter().all(|id| removed.contains(id)) {
-                    removed.insert(other_block.id);
-                }
+        (0..self.blocks.len()).map(|i| self.num_falling(i)).sum()
+    }
+
+    fn stability_report(&self) -> StabilityReport {
+        let mut bricks: Vec<BrickStability> = self.blocks.iter().enumerate().map(|(i, block)| {
+            let supporters = self.supported_by[block.id].len();
+            BrickStability {
+                line: block.id + 1,
+                supporters,
+                margin: (block.min_z > 1).then(|| supporters - 1),
+                num_falling: self.num_falling(i),
             }
-            n += removed.len() - 1;
+        }).collect();
+        bricks.sort_by_key(|brick| brick.line);
+        StabilityReport { bricks }
+    }
+}
+
+/// How firmly a brick rests in the settled stack
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrickStability {
+    /// 1-based line of the brick in the input
+    pub line: usize,
+    /// Number of bricks the brick rests on
+    pub supporters: usize,
+    /// How many of the supporters could be removed before the brick falls, or `None` if it rests
+    /// on the ground
+    pub margin: Option<usize>,
+    /// Number of other bricks that fall if this brick is disintegrated
+    pub num_falling: usize,
+}
+
+/// Stability of every brick in the settled stack, in input order
+#[derive(Clone, Debug, PartialEq)]
+pub struct StabilityReport {
+    pub bricks: Vec<BrickStability>,
+}
+
+impl StabilityReport {
+    /// Number of bricks whose disintegration makes some other brick fall
+    pub fn num_critical(&self) -> usize {
+        self.bricks.iter().filter(|brick| brick.num_falling > 0).count()
+    }
+
+    /// Number of bricks above the ground that rest on a single brick
+    pub fn num_precarious(&self) -> usize {
+        self.bricks.iter().filter(|brick| brick.margin == Some(0)).count()
+    }
+
+    /// Mean number of bricks that fall when one brick is disintegrated, divided by the number of
+    /// other bricks: 0 if no brick depends on any other, 1 if removing any brick topples all
+    /// the rest
+    pub fn fragility(&self) -> f64 {
+        if self.bricks.len() < 2 {
+            return 0.0;
         }
-        n
+        let total: usize = self.bricks.iter().map(|brick| brick.num_falling).sum();
+        total as f64 / (self.bricks.len() * (self.bricks.len() - 1)) as f64
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
+/// A brick after the stack has settled
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettledBrick {
+    /// 0-based index of the brick in the input
+    pub original_index: usize,
+    /// Letter of the brick as in the puzzle description
+    pub label: char,
+    /// Corner with the smallest coordinates as (x, y, z)
+    pub min: (i64, i64, i64),
+    /// Corner with the greatest coordinates as (x, y, z)
+    pub max: (i64, i64, i64),
+    /// Distance the brick fell
+    pub fell: i64,
+}
+
+/// Drops the bricks and returns where they came to rest, in input order.
+pub fn settled_bricks(input: &str) -> Result<Vec<SettledBrick>, Box<dyn Error>> {
     let mut puzzle = Puzzle::from_input(input)?;
-    puzzle.drop_blocks();
-    Ok(puzzle.num_disintegratable())
+    let distances = puzzle.drop_blocks();
+    let mut bricks: Vec<SettledBrick> = puzzle.blocks.iter().zip(distances).map(|(block, fell)| SettledBrick {
+        original_index: block.id,
+        label: label(block.id),
+        min: (block.min_x, block.min_y, block.min_z),
+        max: (block.max_x, block.max_y, block.max_z),
+        fell,
+    }).collect();
+    bricks.sort_by_key(|brick| brick.original_index);
+    Ok(bricks)
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
+/// Drops the bricks and reports how stable each of them is.
+pub fn stability_report(input: &str) -> Result<StabilityReport, Box<dyn Error>> {
     let mut puzzle = Puzzle::from_input(input)?;
     puzzle.drop_blocks();
-    Ok(puzzle.sum_falling())
+    Ok(puzzle.stability_report())
 }
 
-pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
-    println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+/// Frames are only rendered for bricks within this many cells along each axis.
+const MAX_EXTENT: Coordinate = 1000;
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+/// Frames showing the bricks settling one after another, each with a top-down view and a side
+/// view. Bricks are labeled with letters in the order of the input; a '?' marks a cell where
+/// several bricks are seen.
+pub struct SettleFrames {
+    puzzle: Puzzle,
+    heights: HeightMap,
+    /// Index of the next brick to settle, or `None` before the initial frame
+    next: Option<usize>,
+    origin: (Coordinate, Coordinate),
+    size: (Coordinate, Coordinate),
+}
+
+impl SettleFrames {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(input)?;
+        let blocks = &puzzle.blocks;
+        let min_x = blocks.iter().map(|b| b.min_x).min().unwrap_or(0);
+        let min_y = blocks.iter().map(|b| b.min_y).min().unwrap_or(0);
+        let width = blocks.iter().map(|b| b.max_x - min_x + 1).max().unwrap_or(0);
+        let depth = blocks.iter().map(|b| b.max_y - min_y + 1).max().unwrap_or(0);
+        let height = blocks.iter().map(|b| b.max_z).max().unwrap_or(0);
+        if width > MAX_EXTENT || depth > MAX_EXTENT || height > MAX_EXTENT {
+            return Err(format!("Bricks span more than {MAX_EXTENT} cells").into());
+        }
+        Ok(Self { puzzle, heights: HeightMap::new(), next: None, origin: (min_x, min_y), size: (width, depth) })
+    }
+
+    /// Label of the only brick in `ids`, '?' if there are several, or `empty` if there is none
+    fn cell_char(mut ids: impl Iterator<Item = BlockId>, empty: char) -> char {
+        match (ids.next(), ids.next()) {
+            (None, _) => empty,
+            (Some(id), None) => label(id),
+            _ => '?',
+        }
+    }
 
-    Ok(())
+    fn lines(&self) -> Vec<String> {
+        let (min_x, min_y) = self.origin;
+        let (width, depth) = self.size;
+        let blocks = &self.puzzle.blocks;
+        let mut lines = vec!["Top (x right, y down):".to_owned()];
+        for y in min_y..min_y + depth {
+            lines.push((min_x..min_x + width).map(|x| {
+                let top = blocks.iter()
+                    .filter(|b| (b.min_x..=b.max_x).contains(&x) && (b.min_y..=b.max_y).contains(&y))
+                    .max_by_key(|b| b.max_z);
+                top.map_or('.', |b| label(b.id))
+            }).collect());
+        }
+        lines.push("Side (x right, z up):".to_owned());
+        let height = blocks.iter().map(|b| b.max_z).max().unwrap_or(0);
+        for z in (1..=height).rev() {
+            lines.push((min_x..min_x + width).map(|x| Self::cell_char(
+                blocks.iter()
+                    .filter(|b| (b.min_x..=b.max_x).contains(&x) && (b.min_z..=b.max_z).contains(&z))
+                    .map(|b| b.id),
+                '.',
+            )).collect());
+        }
+        lines.push("-".repeat(width as usize));
+        lines
+    }
+}
+
+impl FrameSource for SettleFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        let caption = match self.next {
+            None => "Initial".to_owned(),
+            Some(i) if i < self.puzzle.blocks.len() => {
+                let distance = self.puzzle.settle(i, &mut self.heights);
+                let id = self.puzzle.blocks[i].id;
+                format!("Brick {} (line {}) fell {distance}", label(id), id + 1)
+            }
+            Some(_) => return None,
+        };
+        self.next = Some(self.next.map_or(0, |i| i + 1));
+        Some(Frame::new(caption, self.lines()))
+    }
+}
+
+/// Both parts, solved from the same settled stack
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle;
+
+    /// Reads the bricks and lets them fall.
+    fn parse(input: &str) -> Result<Puzzle, Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(input)?;
+        puzzle.drop_blocks();
+        Ok(puzzle)
+    }
+
+    fn part1(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.num_disintegratable().into())
+    }
+
+    fn part2(puzzle: &Puzzle) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_falling().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as bricks.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_lines(input, |line| Block::from_line(0, line))
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if config.safe_removal {
+        let input = std::fs::read_to_string(&config.file_path1)?;
+        let removal = max_safe_removal(&input, DEFAULT_NODE_BUDGET)?;
+        let lines: Vec<String> = removal.lines.iter().map(|line| line.to_string()).collect();
+        println!("{} bricks can be disintegrated together{}", removal.lines.len(),
+            if removal.exact { "" } else { " (search budget exhausted, there may be more)" });
+        println!("Lines: {}", lines.join(", "));
+        return Ok(());
+    }
+    answer::run_parts::<Day>(&config.file_path1, &config.file_path2)
 }
 
 #[cfg(test)]
@@ -179,16 +414,131 @@ mod tests {
 1,1,8~1,1,9
 ";
 
+    /// The example with x and y shifted far from the origin and the bricks spread out over a
+    /// huge range of z
+    fn spread_out_example() -> String {
+        let shift = |pos: &str, dz: usize| {
+            let v: Vec<usize> = pos.split(',').map(|c| c.parse().unwrap()).collect();
+            format!("{},{},{}", v[0] + (1 << 40), v[1] + (1 << 40), v[2] + dz)
+        };
+        EXAMPLE1.trim().lines().enumerate().map(|(i, line)| {
+            let (pos1, pos2) = line.split_once('~').unwrap();
+            format!("{}~{}", shift(pos1, i * 1_000_000), shift(pos2, i * 1_000_000))
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn spread_out_example_part1() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1(&spread_out_example())?;
+        assert_eq!(result, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn spread_out_example_part2() -> Result<(), Box<dyn Error>> {
+        let result = solve_part2(&spread_out_example())?;
+        assert_eq!(result, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn signed_coordinates() -> Result<(), Box<dyn Error>> {
+        let shifted: String = EXAMPLE1.trim().lines().map(|line| {
+            let (pos1, pos2) = line.split_once('~').unwrap();
+            let shift = |pos: &str| {
+                let v: Vec<i64> = pos.split(',').map(|c| c.parse().unwrap()).collect();
+                format!("{},{},{}", v[0] - 5, v[1] - 1_000_000_000_000, v[2])
+            };
+            format!("{}~{}\n", shift(pos1), shift(pos2))
+        }).collect();
+        assert_eq!(solve_part1(&shifted)?, 5);
+        assert_eq!(solve_part2(&shifted)?, 7);
+        assert_eq!(settled_bricks(&shifted)?[0].min, (-4, -1_000_000_000_000, 1));
+        let error = |input: &str| Puzzle::from_input(input).err().map(|e| e.to_string());
+        assert_eq!(error("0,0,1~0,0,0").as_deref(), Some("Brick below the ground: its lowest z is 0, but must be at least 1"));
+        assert_eq!(error("0,0,-3~0,0,2").as_deref(), Some("Brick below the ground: its lowest z is -3, but must be at least 1"));
+        assert_eq!(validate("0,0,1~0,0,1\n-1,-1,-1~-1,-1,1"), vec![
+            Issue::at_line(2, "Brick below the ground: its lowest z is -1, but must be at least 1"),
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_bricks() {
+        let error = Puzzle::from_input("0,0,1~2,0,1\n1,0,1~1,2,1").err().map(|e| e.to_string());
+        assert_eq!(error.as_deref(), Some("Bricks A (line 1) and B (line 2) overlap"));
+    }
+
+    #[test]
+    fn example1_settled_bricks() -> Result<(), Box<dyn Error>> {
+        // Shuffle the bricks so that the input order differs from the settle order
+        let mut lines: Vec<&str> = EXAMPLE1.trim().lines().collect();
+        lines.reverse();
+        let bricks = settled_bricks(&lines.join("\n"))?;
+        let labels: String = bricks.iter().map(|brick| brick.label).collect();
+        assert_eq!(labels, "ABCDEFG");
+        assert!(bricks.iter().enumerate().all(|(i, brick)| brick.original_index == i));
+        // The original brick A, now last in the input, ends up at the bottom
+        assert_eq!(bricks[6].min, (1, 0, 1));
+        assert_eq!(bricks[6].fell, 0);
+        let falls: Vec<_> = bricks.iter().map(|brick| brick.fell).collect();
+        assert_eq!(falls, vec![3, 2, 2, 1, 1, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn same_height_settles_in_input_order() -> Result<(), Box<dyn Error>> {
+        let mut frames = SettleFrames::from_input("1,0,3~1,0,3\n0,0,3~0,0,3")?;
+        let captions: Vec<String> = std::iter::from_fn(|| frames.next_frame()).map(|frame| frame.caption).collect();
+        assert_eq!(captions, ["Initial", "Brick A (line 1) fell 2", "Brick B (line 2) fell 2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_stability() -> Result<(), Box<dyn Error>> {
+        let report = stability_report(EXAMPLE1.trim())?;
+        let margins: Vec<_> = report.bricks.iter().map(|brick| brick.margin).collect();
+        assert_eq!(margins, vec![None, Some(0), Some(0), Some(1), Some(1), Some(1), Some(0)]);
+        let falling: Vec<_> = report.bricks.iter().map(|brick| brick.num_falling).collect();
+        assert_eq!(falling, vec![6, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(report.num_critical(), 2);
+        assert_eq!(report.num_precarious(), 3);
+        assert_eq!(report.fragility(), 7.0 / 42.0);
+        Ok(())
+    }
+
+    #[test]
+    fn example1_frames() -> Result<(), Box<dyn Error>> {
+        let mut frames = SettleFrames::from_input(EXAMPLE1.trim())?;
+        let first = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(first.caption, "Initial");
+        assert_eq!(first.lines[1..4], ["DBE", "FGF", "DCE"]);
+        assert_eq!(first.lines[5..], [".G.", ".G.", "...", "FFF", "..E", "D..", "CCC", "BBB", ".A.", "---"]);
+        let rest: Vec<Frame> = std::iter::from_fn(|| frames.next_frame()).collect();
+        assert_eq!(rest.len(), 7);
+        assert_eq!(rest[0].caption, "Brick A (line 1) fell 0");
+        assert_eq!(rest[4].caption, "Brick E (line 5) fell 2");
+        let last = rest.last().ok_or("No frame")?;
+        assert_eq!(last.caption, "Brick G (line 7) fell 3");
+        assert_eq!(last.lines[5..], [".G.", ".G.", "FFF", "D.E", "???", ".A.", "---"]);
+        Ok(())
+    }
+
+    #[test]
+    fn huge_frames_rejected() {
+        assert!(SettleFrames::from_input("0,0,1~0,0,5000").is_err());
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 5);
         Ok(())
     }
 
     #[test]
     fn example1_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE1.trim())?;
+        let result = solve_part2(EXAMPLE1.trim())?;
         assert_eq!(result, 7);
         Ok(())
     }
@@ -0,0 +1,278 @@
+//! The largest set of bricks that can be disintegrated at the same time without making any other
+//! brick fall.
+//!
+//! Removing every brick trivially lets nothing fall, so only bricks that could each be
+//! disintegrated on their own are considered. Together, they can still topple a brick: one resting
+//! on several of them falls once all of its supporters are gone. Finding the largest set is a
+//! hitting set problem, so it is solved by a branch and bound search with a limit on its size.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::Puzzle;
+
+/// Number of search nodes after which the best set found so far is returned
+pub const DEFAULT_NODE_BUDGET: usize = 1_000_000;
+
+/// A set of bricks that can be disintegrated at the same time
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SafeRemoval {
+    /// 1-based lines of the bricks in the input, in increasing order
+    pub lines: Vec<usize>,
+    /// Whether the search finished, so that no larger set exists
+    pub exact: bool,
+    /// Number of search nodes visited
+    pub nodes: usize,
+}
+
+/// A brick resting only on candidates for removal, by the numbers of the candidates. It falls if
+/// it stays while all of its supporters are removed.
+struct Constraint {
+    /// The brick itself, if it is a candidate as well
+    own: Option<usize>,
+    supporters: Vec<usize>,
+}
+
+impl Constraint {
+    fn holds(&self, removed: &[bool]) -> bool {
+        self.own.is_some_and(|v| removed[v]) || self.supporters.iter().any(|&s| !removed[s])
+    }
+
+    /// The candidate decided last, since candidates are decided in settle order
+    fn last(&self) -> usize {
+        self.own.unwrap_or_else(|| self.supporters.iter().copied().max().unwrap_or(0))
+    }
+}
+
+/// Depth-first search over the candidates of a component, deciding for each of them in settle order
+/// whether it is removed
+struct Search {
+    /// Constraints by the candidate decided last
+    constraints_at: Vec<Vec<Constraint>>,
+    removed: Vec<bool>,
+    count: usize,
+    best: Vec<bool>,
+    best_count: usize,
+    nodes: usize,
+    budget: usize,
+}
+
+impl Search {
+    fn new(n: usize, constraints: Vec<Constraint>, budget: usize) -> Self {
+        let mut constraints_at: Vec<Vec<Constraint>> = (0..n).map(|_| Vec::new()).collect();
+        for constraint in constraints {
+            constraints_at[constraint.last()].push(constraint);
+        }
+        let mut search = Self { constraints_at, removed: vec![false; n], count: 0, best: vec![false; n], best_count: 0, nodes: 0, budget };
+        search.greedy();
+        search
+    }
+
+    /// Starts from the set found by removing candidates from the top down whenever nothing falls,
+    /// counting candidates not decided yet as staying
+    fn greedy(&mut self) {
+        let n = self.removed.len();
+        let mut involving: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+        for (at, constraints) in self.constraints_at.iter().enumerate() {
+            for (j, constraint) in constraints.iter().enumerate() {
+                for &s in &constraint.supporters {
+                    involving[s].push((at, j));
+                }
+            }
+        }
+        let mut removed = vec![false; n];
+        for v in (0..n).rev() {
+            removed[v] = true;
+            if !involving[v].iter().all(|&(at, j)| self.constraints_at[at][j].holds(&removed)) {
+                removed[v] = false;
+            }
+        }
+        self.best_count = removed.iter().filter(|&&r| r).count();
+        self.best = removed;
+    }
+
+    /// Returns false if the budget ran out.
+    fn visit(&mut self, i: usize) -> bool {
+        let n = self.removed.len();
+        if self.count + (n - i) <= self.best_count {
+            return true;
+        }
+        if i == n {
+            self.best.clone_from(&self.removed);
+            self.best_count = self.count;
+            return true;
+        }
+        self.nodes += 1;
+        if self.nodes > self.budget {
+            return false;
+        }
+        for remove in [true, false] {
+            self.removed[i] = remove;
+            self.count += usize::from(remove);
+            let holds = self.constraints_at[i].iter().all(|c| c.holds(&self.removed));
+            if holds && !self.visit(i + 1) {
+                return false;
+            }
+            self.count -= usize::from(remove);
+        }
+        self.removed[i] = false;
+        true
+    }
+}
+
+fn find(parents: &mut [usize], mut v: usize) -> usize {
+    while parents[v] != v {
+        parents[v] = parents[parents[v]];
+        v = parents[v];
+    }
+    v
+}
+
+impl Puzzle {
+    /// The largest set of individually disintegratable bricks, as indices of the (dropped) blocks,
+    /// that can be disintegrated together. Candidates that share no constraint are searched
+    /// separately, sharing a budget of `node_budget` search nodes.
+    fn max_safe_removal(&self, node_budget: usize) -> (Vec<usize>, bool, usize) {
+        let index_of: HashMap<usize, usize> = self.blocks.iter().enumerate().map(|(i, block)| (block.id, i)).collect();
+        let candidates: Vec<usize> = (0..self.blocks.len())
+            .filter(|&i| self.supports_exclusively[self.blocks[i].id].is_empty())
+            .collect();
+        let mut var_of: Vec<Option<usize>> = vec![None; self.blocks.len()];
+        for (v, &i) in candidates.iter().enumerate() {
+            var_of[i] = Some(v);
+        }
+        // Constraints by global candidate numbers
+        let mut constraints: Vec<Constraint> = Vec::new();
+        for (i, block) in self.blocks.iter().enumerate() {
+            let supporters: Option<Vec<usize>> = self.supported_by[block.id].iter()
+                .map(|id| var_of[index_of[id]])
+                .collect();
+            match supporters {
+                Some(supporters) if !supporters.is_empty() => constraints.push(Constraint { own: var_of[i], supporters }),
+                _ => {},
+            }
+        }
+        let mut parents: Vec<usize> = (0..candidates.len()).collect();
+        for constraint in &constraints {
+            for &v in constraint.own.iter().chain(&constraint.supporters) {
+                let (a, b) = (find(&mut parents, v), find(&mut parents, constraint.supporters[0]));
+                parents[a] = b;
+            }
+        }
+        let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+        for v in 0..candidates.len() {
+            components.entry(find(&mut parents, v)).or_default().push(v);
+        }
+        let mut component_constraints: HashMap<usize, Vec<Constraint>> = HashMap::new();
+        for constraint in constraints {
+            component_constraints.entry(find(&mut parents, constraint.supporters[0])).or_default().push(constraint);
+        }
+        let mut components: Vec<(usize, Vec<usize>)> = components.into_iter().collect();
+        components.sort();
+
+        let mut removed = Vec::new();
+        let mut exact = true;
+        let mut nodes = 0;
+        for (root, vars) in components {
+            // Candidates are numbered in settle order, and so are the members of the component
+            let local: HashMap<usize, usize> = vars.iter().enumerate().map(|(l, &v)| (v, l)).collect();
+            let constraints = component_constraints.remove(&root).unwrap_or_default().into_iter()
+                .map(|constraint| Constraint {
+                    own: constraint.own.map(|v| local[&v]),
+                    supporters: constraint.supporters.iter().map(|v| local[v]).collect(),
+                })
+                .collect();
+            let mut search = Search::new(vars.len(), constraints, node_budget.saturating_sub(nodes));
+            exact &= search.visit(0);
+            nodes += search.nodes.min(search.budget);
+            removed.extend(vars.iter().zip(&search.best).filter(|(_, &r)| r).map(|(&v, _)| candidates[v]));
+        }
+        (removed, exact, nodes)
+    }
+}
+
+/// Drops the bricks and searches for the largest set of them that can be disintegrated at the same
+/// time without any other brick falling, where each of them could also be disintegrated on its
+/// own. After `node_budget` search nodes, the best set found so far is returned.
+pub fn max_safe_removal(input: &str, node_budget: usize) -> Result<SafeRemoval, Box<dyn Error>> {
+    let mut puzzle = Puzzle::from_input(input)?;
+    puzzle.drop_blocks();
+    let (removed, exact, nodes) = puzzle.max_safe_removal(node_budget);
+    let mut lines: Vec<usize> = removed.into_iter().map(|i| puzzle.blocks[i].id + 1).collect();
+    lines.sort();
+    Ok(SafeRemoval { lines, exact, nodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_bricks, BrickOptions, Heights};
+    use std::collections::HashSet;
+
+    const EXAMPLE1: &str = "
+1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9
+";
+
+    /// Whether no brick falls if the bricks on the given lines are disintegrated
+    fn nothing_falls(puzzle: &Puzzle, lines: &HashSet<usize>) -> bool {
+        puzzle.blocks.iter().filter(|block| !lines.contains(&(block.id + 1))).all(|block| {
+            let supporters = &puzzle.supported_by[block.id];
+            supporters.is_empty() || supporters.iter().any(|id| !lines.contains(&(id + 1)))
+        })
+    }
+
+    #[test]
+    fn example1_safe_removal() -> Result<(), Box<dyn Error>> {
+        let removal = max_safe_removal(EXAMPLE1.trim(), DEFAULT_NODE_BUDGET)?;
+        // B and C both hold up D and E, which both hold up F, so of the five disintegratable
+        // bricks, at most one of B and C and one of D and E can go along with G
+        assert_eq!(removal.lines.len(), 3);
+        assert!(removal.exact);
+        assert!(removal.lines.contains(&7));
+        let mut puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        puzzle.drop_blocks();
+        assert!(nothing_falls(&puzzle, &removal.lines.iter().copied().collect()));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_brute_force() -> Result<(), Box<dyn Error>> {
+        let options = BrickOptions { count: 14, footprint: (3, 3), max_length: 3, heights: Heights::Stacked { max_gap: 1 } };
+        for seed in 0..30 {
+            let input = generate_bricks(&options, seed)?;
+            let mut puzzle = Puzzle::from_input(&input)?;
+            puzzle.drop_blocks();
+            let candidates: Vec<usize> = puzzle.blocks.iter()
+                .filter(|block| puzzle.supports_exclusively[block.id].is_empty())
+                .map(|block| block.id + 1)
+                .collect();
+            let maximum = (0..1u32 << candidates.len())
+                .map(|mask| candidates.iter().enumerate().filter(|(k, _)| mask & 1 << k != 0).map(|(_, &line)| line).collect::<HashSet<usize>>())
+                .filter(|lines| nothing_falls(&puzzle, lines))
+                .map(|lines| lines.len())
+                .max();
+            let removal = max_safe_removal(&input, DEFAULT_NODE_BUDGET)?;
+            assert!(removal.exact);
+            assert_eq!(Some(removal.lines.len()), maximum, "seed {seed}");
+            assert!(nothing_falls(&puzzle, &removal.lines.iter().copied().collect()), "seed {seed}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn budget_exhausted() -> Result<(), Box<dyn Error>> {
+        let removal = max_safe_removal(EXAMPLE1.trim(), 0)?;
+        assert!(!removal.exact);
+        // The greedy start is still a valid set
+        let mut puzzle = Puzzle::from_input(EXAMPLE1.trim())?;
+        puzzle.drop_blocks();
+        assert!(nothing_falls(&puzzle, &removal.lines.iter().copied().collect()));
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+pub struct Config {
+    pub file_path1: String,
+    pub file_path2: String,
+    /// Print the longest paths on top of the maps
+    pub show_paths: bool,
+    /// File to which the graph of junctions of part 2 is written in the DOT language
+    pub dot_path: Option<String>,
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        const USAGE: &str = "Usage: day23 <file for part 1> <file for part 2> [--show-paths] [--dot <file>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut show_paths = false;
+        let mut dot_path = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--show-paths" => show_paths = true,
+                "--dot" => dot_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config {
+            file_path1: args[1].clone(),
+            file_path2: args[2].clone(),
+            show_paths,
+            dot_path,
+        })
+    }
+}
@@ -0,0 +1,588 @@
+pub mod config;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::mem;
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::render::{Frame, FrameSource};
+use aoc_core::validate::{self, Issue};
+
+type Position = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Tile {
+    Path,
+    Forest,
+    /// A slope that can only be left in the direction given by (dx, dy)
+    Slope(i8, i8),
+}
+
+impl TryFrom<char> for Tile {
+    type Error = &'static str;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Tile::Path),
+            '#' => Ok(Tile::Forest),
+            '^' => Ok(Tile::Slope(0, -1)),
+            '>' => Ok(Tile::Slope(1, 0)),
+            'v' => Ok(Tile::Slope(0, 1)),
+            '<' => Ok(Tile::Slope(-1, 0)),
+            _ => Err("Invalid tile"),
+        }
+    }
+}
+
+/// How slopes restrict the directions in which they can be left
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Slopes {
+    /// Slopes must be left downhill, as in part 1
+    Enforced,
+    /// Slopes are walked like paths, as in part 2
+    Ignored,
+    /// Slopes must be left uphill
+    Reversed,
+}
+
+struct Map {
+    tiles: Vec<Vec<Tile>>,
+    /// The lines of the input, for drawing paths on top of them
+    lines: Vec<String>,
+    start: Position,
+    end: Position,
+}
+
+impl Map {
+    fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let lines: Vec<String> = input.lines().map(str::to_owned).collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err("Empty input".into());
+        }
+        let tiles: Vec<Vec<Tile>> = lines.iter().map(|line| {
+            if line.chars().count() != width {
+                return Err("Not all lines have the same length");
+            }
+            line.chars().map(Tile::try_from).collect()
+        }).collect::<Result<_, _>>()?;
+        let only_path = |y: usize, what: &str| -> Result<Position, String> {
+            let mut xs = (0..width).filter(|&x| tiles[y][x] == Tile::Path);
+            match (xs.next(), xs.next()) {
+                (Some(x), None) => Ok((x, y)),
+                _ => Err(format!("The {what} row must contain exactly one path tile")),
+            }
+        };
+        let start = only_path(0, "top")?;
+        let end = only_path(tiles.len() - 1, "bottom")?;
+        Ok(Self { tiles, lines, start, end })
+    }
+
+    fn tile(&self, (x, y): Position) -> Tile {
+        self.tiles[y][x]
+    }
+
+    /// The positions next to the given one that are not forest, ignoring slopes
+    fn open_neighbors(&self, (x, y): Position) -> impl Iterator<Item = Position> + '_ {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].into_iter().filter_map(move |(dx, dy)| self.step((x, y), dx, dy))
+    }
+
+    fn step(&self, (x, y): Position, dx: i8, dy: i8) -> Option<Position> {
+        let next = (x.checked_add_signed(dx as isize)?, y.checked_add_signed(dy as isize)?);
+        let row = self.tiles.get(next.1)?;
+        (row.get(next.0)? != &Tile::Forest).then_some(next)
+    }
+
+    /// The positions that can be reached in one step, where `slopes` determines how slopes are
+    /// left
+    fn moves(&self, position: Position, slopes: Slopes) -> Vec<Position> {
+        match (self.tile(position), slopes) {
+            (Tile::Slope(dx, dy), Slopes::Enforced) => self.step(position, dx, dy).into_iter().collect(),
+            (Tile::Slope(dx, dy), Slopes::Reversed) => self.step(position, -dx, -dy).into_iter().collect(),
+            _ => self.open_neighbors(position).collect(),
+        }
+    }
+
+    /// Start, end and the positions where paths meet or fork
+    fn junctions(&self) -> Vec<Position> {
+        let mut junctions = vec![self.start];
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile != Tile::Forest && self.open_neighbors((x, y)).count() > 2 {
+                    junctions.push((x, y));
+                }
+            }
+        }
+        if self.end != self.start {
+            junctions.push(self.end);
+        }
+        junctions
+    }
+}
+
+/// A trail from one junction to another without passing any other junction
+#[derive(Clone, Debug)]
+struct Trail {
+    to: usize,
+    /// The positions after the first junction up to and including the second one
+    cells: Vec<Position>,
+}
+
+/// The junctions of a map and the trails between them. Junction 0 is the start and the last one
+/// is the end.
+pub struct Graph {
+    junctions: Vec<Position>,
+    trails: Vec<Vec<Trail>>,
+    directed: bool,
+}
+
+impl Graph {
+    fn from_map(map: &Map, slopes: Slopes) -> Self {
+        let junctions = map.junctions();
+        let index: HashMap<Position, usize> = junctions.iter().enumerate().map(|(i, &position)| (position, i)).collect();
+        let trails = junctions.iter().map(|&junction| {
+            map.moves(junction, slopes).into_iter().filter_map(|first| {
+                let (mut previous, mut current) = (junction, first);
+                let mut cells = vec![first];
+                loop {
+                    if let Some(&to) = index.get(&current) {
+                        return Some(Trail { to, cells });
+                    }
+                    let mut next = map.moves(current, slopes).into_iter().filter(|&position| position != previous);
+                    match (next.next(), next.next()) {
+                        (Some(position), None) => {
+                            (previous, current) = (current, position);
+                            cells.push(position);
+                        },
+                        // A dead end or a slope leading back
+                        _ => return None,
+                    }
+                }
+            }).collect()
+        }).collect();
+        Self { junctions, trails, directed: slopes != Slopes::Ignored }
+    }
+
+    /// The positions of the junctions
+    pub fn junctions(&self) -> &[Position] {
+        &self.junctions
+    }
+
+    /// The index of the end junction
+    pub fn end(&self) -> usize {
+        self.junctions.len() - 1
+    }
+
+    /// The junctions reachable from the given one without passing another junction, each with the
+    /// number of steps to it. Two junctions may be connected by several trails.
+    pub fn trails_from(&self, junction: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.trails[junction].iter().map(|trail| (trail.to, trail.cells.len()))
+    }
+
+    /// The longest trail from one junction to another, if there is one
+    fn trail(&self, from: usize, to: usize) -> Option<&Trail> {
+        self.trails[from].iter().filter(|trail| trail.to == to).max_by_key(|trail| trail.cells.len())
+    }
+
+    /// Describes the graph in the DOT language. Junctions are labeled with their positions, and
+    /// trails with their lengths. Without slopes, each trail is given once.
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = if self.directed { ("digraph", "->") } else { ("graph", "--") };
+        let mut dot = format!("{kind} trails {{\n");
+        let last = self.junctions.len() - 1;
+        for (i, &(x, y)) in self.junctions.iter().enumerate() {
+            let name = match i {
+                0 => "start ",
+                _ if i == last => "end ",
+                _ => "",
+            };
+            let _ = writeln!(dot, "    j{i} [label=\"{name}{x},{y}\"];");
+        }
+        for (i, trails) in self.trails.iter().enumerate() {
+            for trail in trails.iter().filter(|trail| self.directed || i < trail.to) {
+                let _ = writeln!(dot, "    j{i} {arrow} j{} [label={}];", trail.to, trail.cells.len());
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+}
+
+/// Strategy for finding a long path through the graph of junctions
+pub trait PathSearch {
+    /// Returns the junctions of a path from the start to the end that passes no junction twice,
+    /// or `None` if none was found.
+    fn longest_path(&mut self, graph: &Graph) -> Option<Vec<usize>>;
+}
+
+/// Exhaustive depth-first search, which finds the longest path
+pub struct DepthFirst;
+
+impl PathSearch for DepthFirst {
+    fn longest_path(&mut self, graph: &Graph) -> Option<Vec<usize>> {
+        struct Walk<'a> {
+            graph: &'a Graph,
+            visited: Vec<bool>,
+            junctions: Vec<usize>,
+            best: Option<(usize, Vec<usize>)>,
+        }
+
+        impl Walk<'_> {
+            fn visit(&mut self, junction: usize, length: usize) {
+                if junction == self.graph.end() {
+                    if self.best.as_ref().is_none_or(|(best, _)| length > *best) {
+                        self.best = Some((length, self.junctions.clone()));
+                    }
+                    return;
+                }
+                self.visited[junction] = true;
+                for (to, steps) in self.graph.trails_from(junction) {
+                    if !self.visited[to] {
+                        self.junctions.push(to);
+                        self.visit(to, length + steps);
+                        self.junctions.pop();
+                    }
+                }
+                self.visited[junction] = false;
+            }
+        }
+
+        let mut walk = Walk { graph, visited: vec![false; graph.junctions.len()], junctions: vec![0], best: None };
+        walk.visit(0, 0);
+        walk.best.map(|(_, junctions)| junctions)
+    }
+}
+
+/// The contracted graph of the map, whose trails leave slopes as given by `slopes`.
+pub fn graph(input: &str, slopes: Slopes) -> Result<Graph, Box<dyn Error>> {
+    Ok(Graph::from_map(&Map::from_input(input)?, slopes))
+}
+
+/// The longest hike from the start to the end
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LongestPath {
+    /// Number of steps
+    pub length: usize,
+    /// The junctions passed, from the start to the end
+    pub junctions: Vec<Position>,
+    /// Every position of the hike, from the start to the end
+    pub cells: Vec<Position>,
+}
+
+/// Finds the longest hike that never visits a position twice, where slopes are left as given by
+/// `slopes`.
+pub fn longest_path(input: &str, slopes: Slopes) -> Result<LongestPath, Box<dyn Error>> {
+    longest_path_with(input, slopes, &mut DepthFirst)
+}
+
+/// Finds a hike with the given search strategy, taking the longest trail between each two
+/// junctions it passes. Fails if the search finds no path or one that is not a hike from the start
+/// to the end.
+pub fn longest_path_with(input: &str, slopes: Slopes, search: &mut impl PathSearch) -> Result<LongestPath, Box<dyn Error>> {
+    let map = Map::from_input(input)?;
+    longest_path_on(&map, slopes, search)
+}
+
+fn longest_path_on(map: &Map, slopes: Slopes, search: &mut impl PathSearch) -> Result<LongestPath, Box<dyn Error>> {
+    let graph = Graph::from_map(map, slopes);
+    let junctions = search.longest_path(&graph).ok_or("No path to the end")?;
+    let mut visited = vec![false; graph.junctions.len()];
+    if junctions.first() != Some(&0) || junctions.last() != Some(&graph.end())
+        || junctions.iter().any(|&j| j >= visited.len() || mem::replace(&mut visited[j], true)) {
+        return Err("Path must lead from the start to the end without passing a junction twice".into());
+    }
+    let trails: Vec<&Trail> = junctions.windows(2)
+        .map(|pair| graph.trail(pair[0], pair[1]))
+        .collect::<Option<_>>()
+        .ok_or("Path follows a trail that does not exist")?;
+    let length = trails.iter().map(|trail| trail.cells.len()).sum();
+    let cells = [map.start].into_iter().chain(trails.iter().flat_map(|trail| trail.cells.iter().copied())).collect();
+    let junctions = junctions.into_iter().map(|i| graph.junctions[i]).collect();
+    Ok(LongestPath { length, junctions, cells })
+}
+
+/// Frames drawing the longest hike on top of the map, one trail between two junctions after the
+/// other. Positions of the hike are marked with 'O'.
+pub struct PathFrames {
+    lines: Vec<Vec<char>>,
+    path: LongestPath,
+    /// Number of junctions whose trails have been drawn, or `None` before the initial frame
+    drawn: Option<usize>,
+    next_cell: usize,
+}
+
+impl PathFrames {
+    pub fn new(input: &str, slopes: Slopes) -> Result<Self, Box<dyn Error>> {
+        Self::on_map(&Map::from_input(input)?, slopes)
+    }
+
+    fn on_map(map: &Map, slopes: Slopes) -> Result<Self, Box<dyn Error>> {
+        let path = longest_path_on(map, slopes, &mut DepthFirst)?;
+        let lines = map.lines.iter().map(|line| line.chars().collect()).collect();
+        Ok(Self { lines, path, drawn: None, next_cell: 0 })
+    }
+
+    /// Frames of the longest hike that walks slopes downhill
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        Self::new(input, Slopes::Enforced)
+    }
+
+    fn frame(&self, caption: String) -> Frame {
+        Frame::new(caption, self.lines.iter().map(|line| line.iter().collect()).collect())
+    }
+}
+
+impl FrameSource for PathFrames {
+    fn next_frame(&mut self) -> Option<Frame> {
+        let drawn = match self.drawn {
+            None => {
+                self.drawn = Some(0);
+                return Some(self.frame(format!("Longest hike: {} steps", self.path.length)));
+            },
+            Some(drawn) if drawn < self.path.junctions.len() => drawn,
+            Some(_) => return None,
+        };
+        // Draw up to and including the next junction
+        let junction = self.path.junctions[drawn];
+        while let Some(&(x, y)) = self.path.cells.get(self.next_cell) {
+            self.lines[y][x] = 'O';
+            self.next_cell += 1;
+            if (x, y) == junction {
+                break;
+            }
+        }
+        self.drawn = Some(drawn + 1);
+        let (x, y) = junction;
+        Some(self.frame(format!("Junction {} of {} at {x},{y}, {} steps", drawn + 1, self.path.junctions.len(), self.next_cell - 1)))
+    }
+}
+
+/// Both parts, solved from the same map
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Map;
+
+    fn parse(input: &str) -> Result<Map, Box<dyn Error>> {
+        Map::from_input(input)
+    }
+
+    fn part1(map: &Map) -> Result<Answer, Box<dyn Error>> {
+        Ok(longest_path_on(map, Slopes::Enforced, &mut DepthFirst)?.length.into())
+    }
+
+    fn part2(map: &Map) -> Result<Answer, Box<dyn Error>> {
+        Ok(longest_path_on(map, Slopes::Ignored, &mut DepthFirst)?.length.into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines, invalid tiles and a missing start or end.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues = validate::check_grid(input, |c| Tile::try_from(c).is_ok());
+    if !issues.is_empty() {
+        return issues;
+    }
+    validate::check_all(Map::from_input(input))
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    println!("Part 1: Reading file {}", config.file_path1);
+    let map = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    if config.show_paths {
+        let frame = aoc_core::render::last_frame(&mut PathFrames::on_map(&map, Slopes::Enforced)?).ok_or("No frames")?;
+        println!("{}", frame.lines.join("\n"));
+    }
+    println!("Result of part 1: {}", Day::part1(&map)?);
+
+    let map = answer::parse_part2(map, &config.file_path1, &config.file_path2, Day::parse)?;
+    if config.show_paths {
+        let frame = aoc_core::render::last_frame(&mut PathFrames::on_map(&map, Slopes::Ignored)?).ok_or("No frames")?;
+        println!("{}", frame.lines.join("\n"));
+    }
+    println!("Result of part 2: {}", Day::part2(&map)?);
+
+    if let Some(dot_path) = config.dot_path {
+        println!("Writing the graph of part 2 to {dot_path}");
+        fs::write(dot_path, Graph::from_map(&map, Slopes::Ignored).to_dot())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc_core::render;
+
+    const EXAMPLE: &str = "
+#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#
+";
+
+    #[test]
+    fn example_part1() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1(EXAMPLE.trim())?;
+        assert_eq!(result, 94);
+        Ok(())
+    }
+
+    #[test]
+    fn example_part2() -> Result<(), Box<dyn Error>> {
+        let result = solve_part2(EXAMPLE.trim())?;
+        assert_eq!(result, 154);
+        Ok(())
+    }
+
+    #[test]
+    fn example_path() -> Result<(), Box<dyn Error>> {
+        for slopes in [Slopes::Enforced, Slopes::Ignored] {
+            let path = longest_path(EXAMPLE.trim(), slopes)?;
+            assert_eq!(path.cells.len(), path.length + 1);
+            assert_eq!((path.cells.first(), path.cells.last()), (Some(&(1, 0)), Some(&(21, 22))));
+            assert_eq!((path.junctions.first(), path.junctions.last()), (Some(&(1, 0)), Some(&(21, 22))));
+            assert!(path.cells.windows(2).all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+            let mut cells = path.cells.clone();
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), path.cells.len());
+        }
+        assert_eq!(longest_path(EXAMPLE.trim(), Slopes::Enforced)?.junctions[1], (3, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn slope_policies() -> Result<(), Box<dyn Error>> {
+        let downhill = "#.###\n#.>.#\n###.#";
+        let uphill = "#.###\n#.<.#\n###.#";
+        for (map, passable) in [(downhill, Slopes::Enforced), (uphill, Slopes::Reversed)] {
+            for slopes in [Slopes::Enforced, Slopes::Ignored, Slopes::Reversed] {
+                let path = longest_path(map, slopes);
+                if slopes == passable || slopes == Slopes::Ignored {
+                    assert_eq!(path?.length, 4);
+                } else {
+                    assert!(path.is_err());
+                }
+            }
+        }
+        // The example's slopes all lead away from the start
+        assert!(longest_path(EXAMPLE.trim(), Slopes::Reversed).is_err());
+        Ok(())
+    }
+
+    /// Always takes the first trail to a junction not passed yet
+    struct Greedy;
+
+    impl PathSearch for Greedy {
+        fn longest_path(&mut self, graph: &Graph) -> Option<Vec<usize>> {
+            let mut junctions = vec![0];
+            while junctions.last() != Some(&graph.end()) {
+                let (next, _) = graph.trails_from(*junctions.last()?).find(|(to, _)| !junctions.contains(to))?;
+                junctions.push(next);
+            }
+            Some(junctions)
+        }
+    }
+
+    /// Claims a path through junctions that are not connected
+    struct Shortcut;
+
+    impl PathSearch for Shortcut {
+        fn longest_path(&mut self, graph: &Graph) -> Option<Vec<usize>> {
+            Some(vec![0, graph.end()])
+        }
+    }
+
+    #[test]
+    fn custom_search() -> Result<(), Box<dyn Error>> {
+        let graph = graph(EXAMPLE.trim(), Slopes::Ignored)?;
+        assert_eq!(graph.junctions()[0], (1, 0));
+        assert_eq!(graph.junctions()[graph.end()], (21, 22));
+        assert_eq!(graph.trails_from(0).collect::<Vec<_>>().len(), 1);
+        for slopes in [Slopes::Enforced, Slopes::Ignored] {
+            let greedy = longest_path_with(EXAMPLE.trim(), slopes, &mut Greedy)?;
+            assert!(greedy.length <= longest_path(EXAMPLE.trim(), slopes)?.length);
+            assert_eq!(greedy.cells.len(), greedy.length + 1);
+        }
+        assert!(longest_path_with(EXAMPLE.trim(), Slopes::Ignored, &mut Shortcut).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn example_frames() -> Result<(), Box<dyn Error>> {
+        let mut frames = PathFrames::from_input(EXAMPLE.trim())?;
+        let first = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(first.caption, "Longest hike: 94 steps");
+        assert_eq!(first.lines.join("\n"), EXAMPLE.trim());
+        let second = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(second.caption, "Junction 1 of 7 at 1,0, 0 steps");
+        let third = frames.next_frame().ok_or("No frame")?;
+        assert_eq!(third.caption, "Junction 2 of 7 at 3,5, 15 steps");
+        let last = render::last_frame(&mut frames).ok_or("No frame")?;
+        assert_eq!(last.caption, "Junction 7 of 7 at 21,22, 94 steps");
+        assert_eq!(last.lines.iter().map(|line| line.matches('O').count()).sum::<usize>(), 95);
+        assert_eq!(last.lines[0], "#O#####################");
+        Ok(())
+    }
+
+    #[test]
+    fn dot_export() -> Result<(), Box<dyn Error>> {
+        let dot = graph(EXAMPLE.trim(), Slopes::Ignored)?.to_dot();
+        assert!(dot.starts_with("graph trails {\n    j0 [label=\"start 1,0\"];\n"));
+        assert!(dot.contains(" [label=\"3,5\"];\n"));
+        assert!(dot.contains("    j0 -- j2 [label=15];\n"));
+        assert!(dot.contains(" [label=\"end 21,22\"];\n"));
+        assert!(dot.ends_with("}\n"));
+        // Each trail once, in both directions when following the slopes
+        let undirected = dot.matches(" -- ").count();
+        let directed = graph(EXAMPLE.trim(), Slopes::Enforced)?.to_dot();
+        assert!(directed.starts_with("digraph trails {\n"));
+        assert!(directed.matches(" -> ").count() < 2 * undirected);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_maps() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        assert_eq!(validate("#.#\n#.#\n###"), vec![Issue::new("The bottom row must contain exactly one path tile")]);
+        assert_eq!(validate("#x#"), vec![Issue::at(1, 2, "Invalid character 'x'")]);
+        assert!(longest_path("#.#\n###\n#.#", Slopes::Enforced).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+use crate::DEFAULT_AREA;
+
+pub struct Config {
+    pub file_path1: String,
+    pub file_path2: String,
+    /// Bounds of the test area of part 1 for both x and y
+    pub area: (i64, i64),
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        const USAGE: &str = "Usage: day24 <file for part 1> <file for part 2> [--area <min> <max>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut area = DEFAULT_AREA;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--area" => {
+                    let min = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    let max = iter.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+                    if min > max {
+                        return Err(USAGE);
+                    }
+                    area = (min, max);
+                },
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config {
+            file_path1: args[1].clone(),
+            file_path2: args[2].clone(),
+            area,
+        })
+    }
+}
@@ -0,0 +1,482 @@
+pub mod config;
+
+use std::error::Error;
+use std::fs;
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parallel;
+use aoc_core::parse::{self, ParseError, Parser};
+use aoc_core::validate::{self, Issue};
+
+/// The test area of part 1, for both x and y
+pub const DEFAULT_AREA: (i64, i64) = (200_000_000_000_000, 400_000_000_000_000);
+
+const TOO_LARGE: &str = "Hailstone coordinates too large for 128-bit arithmetic";
+
+/// Greatest speed along x and y that part 2 tries for the rock
+const MAX_ROCK_SPEED: i128 = 1000;
+
+/// The closest points of the future paths of two hailstones
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Approach {
+    /// Distance between the closest points
+    pub distance: f64,
+    /// Times at which the first and the second hailstone pass their closest point
+    pub times: (f64, f64),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Hailstone {
+    pub position: [i64; 3],
+    pub velocity: [i64; 3],
+}
+
+impl Hailstone {
+    /// Reads a line like "19, 13, 30 @ -2,  1, -2".
+    fn parse(line: &str) -> Result<Self, ParseError> {
+        parse::parse_all(line, |parser| {
+            let position = Self::parse_triple(parser)?;
+            parser.spaces();
+            parser.tag("@")?;
+            let velocity = Self::parse_triple(parser)?;
+            Ok(Self { position, velocity })
+        })
+    }
+
+    fn parse_triple(parser: &mut Parser) -> Result<[i64; 3], ParseError> {
+        let mut triple = [0; 3];
+        for (i, n) in triple.iter_mut().enumerate() {
+            if i > 0 {
+                parser.spaces();
+                parser.tag(",")?;
+            }
+            parser.spaces();
+            *n = parser.number()?;
+        }
+        Ok(triple)
+    }
+
+    fn from_input(input: &str) -> Result<Vec<Self>, String> {
+        input.lines().enumerate().map(|(i, line)| Self::parse(line).map_err(|e| e.on_line(i + 1))).collect()
+    }
+
+    /// The bounding box of the part of the future path along x and y that lies in the test area,
+    /// or `None` if there is no such part. Both the area and the box are enlarged a bit, so that
+    /// rounding cannot make the box miss a crossing.
+    fn extent(&self, (lo, hi): (i64, i64)) -> Option<[(f64, f64); 2]> {
+        let pad = (hi as f64 - lo as f64) * 1e-9 + 1.0;
+        let (lo, hi) = (lo as f64 - pad, hi as f64 + pad);
+        let (mut t_min, mut t_max) = (0.0, f64::INFINITY);
+        for axis in 0..2 {
+            let (p, v) = (self.position[axis] as f64, self.velocity[axis] as f64);
+            if v == 0.0 {
+                if p < lo || p > hi {
+                    return None;
+                }
+            } else {
+                let (t1, t2) = ((lo - p) / v, (hi - p) / v);
+                t_min = f64::max(t_min, t1.min(t2));
+                t_max = f64::min(t_max, t1.max(t2));
+            }
+        }
+        if t_min > t_max {
+            return None;
+        }
+        if t_max.is_infinite() {
+            // A hailstone that does not move along x and y
+            t_max = t_min;
+        }
+        Some([0, 1].map(|axis| {
+            let (p, v) = (self.position[axis] as f64, self.velocity[axis] as f64);
+            let (a, b) = (p + v * t_min, p + v * t_max);
+            (a.min(b) - pad, a.max(b) + pad)
+        }))
+    }
+
+    /// Whether the future paths of both hailstones along x and y cross inside the test area.
+    /// Parallel paths never cross, even if they overlap. Fails if the coordinates are too large
+    /// to compare the crossing with the test area exactly.
+    fn crosses(&self, other: &Self, (lo, hi): (i64, i64)) -> Result<bool, &'static str> {
+        let mul = |a: i128, b: i128| a.checked_mul(b).ok_or(TOO_LARGE);
+        let sub = |a: i128, b: i128| a.checked_sub(b).ok_or(TOO_LARGE);
+        let [pax, pay, _] = self.position.map(i128::from);
+        let [vax, vay, _] = self.velocity.map(i128::from);
+        let [pbx, pby, _] = other.position.map(i128::from);
+        let [vbx, vby, _] = other.velocity.map(i128::from);
+        let mut det = sub(mul(vax, vby)?, mul(vay, vbx)?)?;
+        if det == 0 {
+            return Ok(false);
+        }
+        let (dx, dy) = (pbx - pax, pby - pay);
+        // The paths cross at times num_a / det and num_b / det of the hailstones
+        let (mut num_a, mut num_b) = (sub(mul(dx, vby)?, mul(dy, vbx)?)?, sub(mul(dx, vay)?, mul(dy, vax)?)?);
+        if det < 0 {
+            (det, num_a, num_b) = (-det, -num_a, -num_b);
+        }
+        if num_a < 0 || num_b < 0 {
+            return Ok(false);
+        }
+        let (lo, hi) = (mul(i128::from(lo), det)?, mul(i128::from(hi), det)?);
+        let x = mul(pax, det)?.checked_add(mul(vax, num_a)?).ok_or(TOO_LARGE)?;
+        let y = mul(pay, det)?.checked_add(mul(vay, num_a)?).ok_or(TOO_LARGE)?;
+        Ok((lo..=hi).contains(&x) && (lo..=hi).contains(&y))
+    }
+}
+
+/// Finds the points of the future paths of both hailstones in 3D that are closest to each other.
+/// The hailstones may pass them at different times; they collide if the distance is 0 and the
+/// times agree.
+pub fn closest_approach(a: &Hailstone, b: &Hailstone) -> Approach {
+    let dot = |p: [i128; 3], q: [i128; 3]| p[0] * q[0] + p[1] * q[1] + p[2] * q[2];
+    let (u, v) = (a.velocity.map(i128::from), b.velocity.map(i128::from));
+    let w = [0, 1, 2].map(|axis| i128::from(a.position[axis]) - i128::from(b.position[axis]));
+    let (uu, uv, vv, uw, vw) = (dot(u, u), dot(u, v), dot(v, v), dot(u, w), dot(v, w));
+    let distance_at = |ta: f64, tb: f64| {
+        (0..3).map(|axis| (w[axis] as f64 + ta * u[axis] as f64 - tb * v[axis] as f64).powi(2)).sum::<f64>().sqrt()
+    };
+    // The distance is a convex function of both times, so its minimum over non-negative times is
+    // either the unconstrained minimum or lies on one of the borders ta = 0 and tb = 0
+    let mut candidates = vec![(0.0, 0.0)];
+    if vv > 0 && vw > 0 {
+        candidates.push((0.0, vw as f64 / vv as f64));
+    }
+    if uu > 0 && uw < 0 {
+        candidates.push((-uw as f64 / uu as f64, 0.0));
+    }
+    let det = uu * vv - uv * uv;
+    if det != 0 {
+        let (ta, tb) = ((uv * vw - vv * uw) as f64 / det as f64, (uu * vw - uv * uw) as f64 / det as f64);
+        if ta >= 0.0 && tb >= 0.0 {
+            candidates.push((ta, tb));
+        }
+    }
+    candidates.into_iter()
+        .map(|(ta, tb)| Approach { distance: distance_at(ta, tb), times: (ta, tb) })
+        .fold(None, |best: Option<Approach>, approach| match best {
+            Some(best) if best.distance <= approach.distance => Some(best),
+            _ => Some(approach),
+        })
+        .unwrap_or(Approach { distance: distance_at(0.0, 0.0), times: (0.0, 0.0) })
+}
+
+/// Counts the pairs of hailstones whose future paths along x and y cross inside the test area.
+/// Only hailstones that enter the area are considered, and only pairs of them whose bounding boxes
+/// in the area overlap are checked, found by sweeping along x in parallel.
+fn count_crossings_swept(hailstones: &[Hailstone], area: (i64, i64)) -> Result<usize, &'static str> {
+    let mut extents: Vec<([(f64, f64); 2], &Hailstone)> = hailstones.iter()
+        .filter_map(|hailstone| Some((hailstone.extent(area)?, hailstone)))
+        .collect();
+    extents.sort_by(|(a, _), (b, _)| a[0].0.total_cmp(&b[0].0));
+    let indices: Vec<usize> = (0..extents.len()).collect();
+    let counts = parallel::map(&indices, |&i| {
+        let ([(_, max_x), (min_y, max_y)], a) = extents[i];
+        extents[i + 1..].iter()
+            .take_while(|([(min_x, _), _], _)| *min_x <= max_x)
+            .filter(|([_, (other_min_y, other_max_y)], _)| *other_min_y <= max_y && min_y <= *other_max_y)
+            .try_fold(0, |count, (_, b)| Ok(count + usize::from(a.crosses(b, area)?)))
+    });
+    counts.into_iter().sum()
+}
+
+/// Counts the pairs of hailstones whose future paths along x and y cross inside the test area,
+/// where `area` gives the bounds for both x and y.
+pub fn count_crossings(input: &str, area: (i64, i64)) -> Result<usize, Box<dyn Error>> {
+    Ok(count_crossings_swept(&Hailstone::from_input(input)?, area)?)
+}
+
+/// The rock that hits every hailstone if it flies with the velocity (vx, vy, ·) along x and y,
+/// if there is one. In a frame moving along with the rock, all hailstones pass through the
+/// starting position of the rock, which is where the paths of any two of them cross. Fails if the
+/// coordinates are too large to compute the rock in 128 bits.
+fn rock_with_velocity(hailstones: &[Hailstone], vx: i128, vy: i128) -> Result<Option<Hailstone>, &'static str> {
+    let add = |a: i128, b: i128| a.checked_add(b).ok_or(TOO_LARGE);
+    let sub = |a: i128, b: i128| a.checked_sub(b).ok_or(TOO_LARGE);
+    let mul = |a: i128, b: i128| a.checked_mul(b).ok_or(TOO_LARGE);
+    // Quotient of a division with a non-zero divisor if it has no remainder
+    let div_exact = |a: i128, b: i128| match a.checked_rem(b) {
+        Some(0) => a.checked_div(b).map(Some).ok_or(TOO_LARGE),
+        Some(_) => Ok(None),
+        None => Err(TOO_LARGE),
+    };
+    let relative = |h: &Hailstone| Ok((sub(i128::from(h.velocity[0]), vx)?, sub(i128::from(h.velocity[1]), vy)?));
+    let position = |h: &Hailstone| h.position.map(i128::from);
+    let Some(a) = hailstones.first() else {
+        return Ok(None);
+    };
+    let [pax, pay, _] = position(a);
+    let (ax, ay) = relative(a)?;
+    let mut pair = None;
+    for b in &hailstones[1..] {
+        let (bx, by) = relative(b)?;
+        let det = sub(mul(ax, by)?, mul(ay, bx)?)?;
+        if det != 0 {
+            pair = Some((b, det, (bx, by)));
+            break;
+        }
+    }
+    let Some((b, det, (bx, by))) = pair else {
+        return Ok(None);
+    };
+    let [pbx, pby, _] = position(b);
+    let num = sub(mul(pbx - pax, by)?, mul(pby - pay, bx)?)?;
+    let Some(t) = div_exact(num, det)? else {
+        return Ok(None);
+    };
+    let (x, y) = (add(pax, mul(ax, t)?)?, add(pay, mul(ay, t)?)?);
+    // The time at which each hailstone is hit, or `None` if it moves along with the rock
+    let mut times = Vec::with_capacity(hailstones.len());
+    for hailstone in hailstones {
+        let [px, py, _] = position(hailstone);
+        let ((cx, cy), (ex, ey)) = (relative(hailstone)?, (sub(x, px)?, sub(y, py)?));
+        let time = match (cx, cy) {
+            (0, 0) if (ex, ey) == (0, 0) => None,
+            (0, 0) => return Ok(None),
+            _ if mul(ex, cy)? != mul(ey, cx)? => return Ok(None),
+            (0, _) => div_exact(ey, cy)?,
+            (_, _) => div_exact(ex, cx)?,
+        };
+        match time {
+            Some(time) if time >= 0 => times.push(Some(time)),
+            Some(_) => return Ok(None),
+            None if (cx, cy) == (0, 0) => times.push(None),
+            None => return Ok(None),
+        }
+    }
+    // Solve z + vz t = pz + w t for two hailstones hit at different times
+    let mut hits = hailstones.iter().zip(&times).filter_map(|(h, t)| t.map(|t| (position(h)[2], i128::from(h.velocity[2]), t)));
+    let Some((z1, w1, t1)) = hits.next() else {
+        return Ok(None);
+    };
+    let Some((z2, w2, t2)) = hits.find(|&(_, _, t)| t != t1) else {
+        return Ok(None);
+    };
+    let difference = sub(add(z1, mul(w1, t1)?)?, add(z2, mul(w2, t2)?)?)?;
+    let Some(vz) = div_exact(difference, sub(t1, t2)?)? else {
+        return Ok(None);
+    };
+    let z = add(z1, mul(sub(w1, vz)?, t1)?)?;
+    for (h, t) in hailstones.iter().zip(times) {
+        let (pz, w) = (position(h)[2], i128::from(h.velocity[2]));
+        let hit = match t {
+            Some(t) => add(z, mul(vz, t)?)? == add(pz, mul(w, t)?)?,
+            None if vz == w => z == pz,
+            None => div_exact(sub(pz, z)?, sub(vz, w)?)?.is_some_and(|t| t >= 0),
+        };
+        if !hit {
+            return Ok(None);
+        }
+    }
+    let position = [x, y, z].map(i64::try_from);
+    let velocity = [vx, vy, vz].map(i64::try_from);
+    Ok(match (position, velocity) {
+        ([Ok(x), Ok(y), Ok(z)], [Ok(vx), Ok(vy), Ok(vz)]) => Some(Hailstone { position: [x, y, z], velocity: [vx, vy, vz] }),
+        _ => None,
+    })
+}
+
+/// Finds the rock that hits every hailstone, trying speeds along x and y up to `max_speed`.
+pub fn throw_rock(hailstones: &[Hailstone], max_speed: i128) -> Result<Hailstone, Box<dyn Error>> {
+    if hailstones.len() < 3 {
+        return Err("At least three hailstones are needed".into());
+    }
+    for vx in -max_speed..=max_speed {
+        for vy in -max_speed..=max_speed {
+            if let Some(rock) = rock_with_velocity(hailstones, vx, vy)? {
+                return Ok(rock);
+            }
+        }
+    }
+    Err(format!("No rock with a speed of at most {max_speed} along x and y hits every hailstone").into())
+}
+
+/// Both parts, solved from the same hailstones
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Vec<Hailstone>;
+
+    fn parse(input: &str) -> Result<Vec<Hailstone>, Box<dyn Error>> {
+        Ok(Hailstone::from_input(input)?)
+    }
+
+    fn part1(hailstones: &Vec<Hailstone>) -> Result<Answer, Box<dyn Error>> {
+        Ok(count_crossings_swept(hailstones, DEFAULT_AREA)?.into())
+    }
+
+    fn part2(hailstones: &Vec<Hailstone>) -> Result<Answer, Box<dyn Error>> {
+        let rock = throw_rock(hailstones, MAX_ROCK_SPEED)?;
+        Ok(rock.position.iter().map(|&p| i128::from(p)).sum::<i128>().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as hailstones.
+pub fn validate(input: &str) -> Vec<Issue> {
+    input.lines().enumerate()
+        .filter_map(|(i, line)| Hailstone::parse(line).err().map(|e| Issue::at(i + 1, e.column, e.message)))
+        .chain(validate::check_all(if input.trim().is_empty() { Err("Empty input") } else { Ok(()) }))
+        .collect()
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    println!("Part 1: Reading file {}", config.file_path1);
+    let hailstones = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", count_crossings_swept(&hailstones, config.area)?);
+
+    let hailstones = answer::parse_part2(hailstones, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&hailstones)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc_core::rng::{Rng, SeededRng};
+
+    const EXAMPLE: &str = "
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3
+";
+
+    #[test]
+    fn example_part1() -> Result<(), Box<dyn Error>> {
+        let result = count_crossings(EXAMPLE.trim(), (7, 27))?;
+        assert_eq!(result, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn closest_approaches() -> Result<(), Box<dyn Error>> {
+        let hailstones = Hailstone::from_input(EXAMPLE.trim())?;
+        let rock = Hailstone { position: [24, 13, 10], velocity: [-3, 1, 2] };
+        // The rock hits the first hailstone after 5 nanoseconds
+        assert_eq!(closest_approach(&hailstones[0], &rock), Approach { distance: 0.0, times: (5.0, 5.0) });
+        assert_eq!(closest_approach(&rock, &hailstones[0]).times, (5.0, 5.0));
+        // Paths crossing at different times
+        let a = Hailstone { position: [0, 0, 0], velocity: [1, 0, 0] };
+        let b = Hailstone { position: [5, -6, 3], velocity: [0, 2, 0] };
+        assert_eq!(closest_approach(&a, &b), Approach { distance: 3.0, times: (5.0, 3.0) });
+        // Moving apart, they are closest now
+        let c = Hailstone { position: [-1, 0, 0], velocity: [-1, 0, 0] };
+        assert_eq!(closest_approach(&c, &b), Approach { distance: 45.0f64.sqrt(), times: (0.0, 3.0) });
+        let d = Hailstone { position: [-1, 4, 0], velocity: [-1, 0, 0] };
+        assert_eq!(closest_approach(&c, &d), Approach { distance: 4.0, times: (0.0, 0.0) });
+        // Parallel paths
+        let e = Hailstone { position: [3, 4, 0], velocity: [2, 0, 0] };
+        assert_eq!(closest_approach(&a, &e).distance, 4.0);
+        Ok(())
+    }
+
+    #[test]
+    fn example_part2() -> Result<(), Box<dyn Error>> {
+        let rock = throw_rock(&Hailstone::from_input(EXAMPLE.trim())?, 10)?;
+        assert_eq!(rock, Hailstone { position: [24, 13, 10], velocity: [-3, 1, 2] });
+        let result = solve_part2(EXAMPLE.trim())?;
+        assert_eq!(result, 47);
+        Ok(())
+    }
+
+    /// Counts the pairs of hailstones whose future paths along x and y cross inside the test area by
+    /// checking every pair.
+    fn count_crossings_naive(hailstones: &[Hailstone], area: (i64, i64)) -> Result<usize, &'static str> {
+        let mut count = 0;
+        for (i, a) in hailstones.iter().enumerate() {
+            for b in &hailstones[i + 1..] {
+                count += usize::from(a.crosses(b, area)?);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Generates `n` hailstones in a square of side `size` flying in random directions, of which
+    /// only some pass through the test area in the middle with side `size / 10`.
+    fn random_hailstones(n: usize, size: i64, seed: u64) -> (Vec<Hailstone>, (i64, i64)) {
+        let mut rng = SeededRng::new(seed);
+        let mut random = move |max: i64| rng.below(max as u64) as i64;
+        let hailstones = (0..n).map(|_| Hailstone {
+            position: [random(size), random(size), random(size)],
+            velocity: [random(2001) - 1000, random(2001) - 1000, random(2001) - 1000],
+        }).collect();
+        (hailstones, (size / 2 - size / 20, size / 2 + size / 20))
+    }
+
+    #[test]
+    fn sweep_matches_naive() -> Result<(), Box<dyn Error>> {
+        let hailstones = Hailstone::from_input(EXAMPLE.trim())?;
+        assert_eq!(count_crossings_swept(&hailstones, (7, 27))?, count_crossings_naive(&hailstones, (7, 27))?);
+        for seed in 0..5 {
+            let (hailstones, area) = random_hailstones(2000, 1 << 40, seed);
+            let naive = count_crossings_naive(&hailstones, area)?;
+            assert!(naive > 0);
+            assert_eq!(count_crossings_swept(&hailstones, area)?, naive);
+            // All hailstones pass through the area
+            let area = (0, 1 << 40);
+            assert_eq!(count_crossings_swept(&hailstones[..300], area)?, count_crossings_naive(&hailstones[..300], area)?);
+        }
+        Ok(())
+    }
+
+    /// Compares checking all pairs of 10k hailstones with sweeping, and sweeps 100k hailstones. Run
+    /// with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_crossings() {
+        let (hailstones, area) = random_hailstones(100_000, DEFAULT_AREA.1, 42);
+        let start = std::time::Instant::now();
+        let naive = count_crossings_naive(&hailstones[..10_000], area).unwrap();
+        println!("Naive, 10k hailstones: {naive} in {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let swept = count_crossings_swept(&hailstones[..10_000], area).unwrap();
+        println!("Swept, 10k hailstones: {swept} in {:?}", start.elapsed());
+        assert_eq!(naive, swept);
+        let start = std::time::Instant::now();
+        let swept = count_crossings_swept(&hailstones, area).unwrap();
+        println!("Swept, 100k hailstones: {swept} in {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn crossings_at_extreme_velocities() -> Result<(), Box<dyn Error>> {
+        // The paths cross in the past
+        let input = "300000000000000, 300000000000000, 0 @ 9223372036854775807, 1, 0
+300000000000000, 300000000000001, 0 @ 1, 9223372036854775807, 0";
+        assert_eq!(count_crossings(input, DEFAULT_AREA)?, 0);
+        // The paths cross right after the start, but the crossing cannot be compared with the
+        // area in 128 bits
+        let input = "300000000000000, 300000000000000, 0 @ 9223372036854775807, 1, 0
+300000000000000, 299999999999999, 0 @ 1, 9223372036854775807, 0";
+        assert_eq!(count_crossings(input, DEFAULT_AREA).unwrap_err().to_string(), TOO_LARGE);
+        let input = "-9223372036854775808, 9223372036854775807, 0 @ -9223372036854775808, 9223372036854775807, 1
+9223372036854775807, -9223372036854775808, 0 @ 9223372036854775807, -9223372036854775808, -1
+0, 0, -9223372036854775808 @ 1, -1, 9223372036854775807";
+        assert_eq!(solve_part2(input).unwrap_err().to_string(), TOO_LARGE);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_hailstones() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        assert_eq!(validate("1, 2, 3 @ 1, 2\n1, 2 @ 1, 2, 3"), vec![
+            Issue::at(1, 15, "Expected ','"),
+            Issue::at(2, 6, "Expected ','"),
+        ]);
+        assert_eq!(validate(""), vec![Issue::new("Empty input")]);
+        assert!(solve_part2("1, 2, 3 @ 1, 2, 3").is_err());
+    }
+}
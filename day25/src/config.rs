@@ -0,0 +1,27 @@
+pub struct Config {
+    pub file_path1: String,
+    pub file_path2: String,
+    /// Print the cut and check it by removing its edges and counting the components left
+    pub verify: bool,
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        const USAGE: &str = "Usage: day25 <file for part 1> <file for part 2> [--verify]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut verify = false;
+        for arg in &args[3..] {
+            match arg.as_str() {
+                "--verify" => verify = true,
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config {
+            file_path1: args[1].clone(),
+            file_path2: args[2].clone(),
+            verify,
+        })
+    }
+}
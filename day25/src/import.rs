@@ -0,0 +1,330 @@
+//! Reading graphs in formats other than the puzzle's, so that any graph can be split: a list of
+//! edges, one per line, or an undirected graph in the DOT language of Graphviz.
+
+use aoc_core::parse::{self, ParseError};
+
+/// The format of a graph
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Lines like "jqt: rhn xhk nvd", as in the puzzle
+    Colon,
+    /// Lines like "jqt rhn" with the names of two connected components, or a single name for a
+    /// component without connections. Blank lines and lines starting with '#' are skipped.
+    EdgeList,
+    /// An undirected graph in the DOT language, like "graph { jqt -- rhn; rhn -- xhk -- hfx }".
+    /// Attributes are ignored, and subgraphs and ports are not supported.
+    Dot,
+}
+
+impl Format {
+    /// Recognizes DOT by its leading "graph", "strict" or "digraph" keyword and the colon format by
+    /// a ':' in any line, so that a malformed line of the puzzle's format is reported as such.
+    /// Anything else is read as an edge list.
+    pub fn detect(input: &str) -> Self {
+        let first_line = input.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"));
+        let keyword = first_line.and_then(|line| line.split(|c| !is_name_char(c)).next()).unwrap_or_default();
+        if ["graph", "strict", "digraph"].iter().any(|k| k.eq_ignore_ascii_case(keyword)) {
+            Format::Dot
+        } else if input.contains(':') {
+            Format::Colon
+        } else {
+            Format::EdgeList
+        }
+    }
+}
+
+/// A component, connected to another one if given, and the 1-based line it was read from
+pub(crate) type Item<'a> = (&'a str, Option<&'a str>, usize);
+
+/// A parse failure with the 1-based line it occurred in
+pub(crate) type LineError = (usize, ParseError);
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Reads an edge list.
+pub(crate) fn parse_edge_list(input: &str) -> Result<Vec<Item<'_>>, LineError> {
+    let mut items = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let (a, b) = parse::parse_all(line, |parser| {
+            parser.spaces();
+            let a = parser.word("component name", is_name_char)?;
+            let b = if parser.spaces() > 0 && parser.peek().is_some() {
+                Some(parser.word("component name", is_name_char)?)
+            } else {
+                None
+            };
+            parser.spaces();
+            Ok((a, b))
+        }).map_err(|e| (i + 1, e))?;
+        items.push((a, b, i + 1));
+    }
+    Ok(items)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token<'a> {
+    /// A name, number or quoted string without its quotes
+    Id(&'a str),
+    /// "--" or "->"
+    EdgeOp(&'a str),
+    Punct(char),
+}
+
+/// A token with its 1-based line and column
+type Located<'a> = (Token<'a>, usize, usize);
+
+/// Splits DOT into tokens, skipping whitespace and comments
+fn tokenize(input: &str) -> Result<Vec<Located<'_>>, LineError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let (mut line, mut line_start) = (1, 0);
+    let column = |line_start: usize, pos: usize| input[line_start..pos].chars().count() + 1;
+    while let Some((pos, c)) = chars.next() {
+        let col = column(line_start, pos);
+        let rest = &input[pos..];
+        match c {
+            '\n' => (line, line_start) = (line + 1, pos + 1),
+            _ if c.is_whitespace() => (),
+            '#' if col == 1 => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            '/' if rest.starts_with("//") => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            '/' if rest.starts_with("/*") => {
+                let end = rest[2..].find("*/").ok_or((line, ParseError::new(col, "Unterminated comment")))? + 4;
+                while chars.next_if(|&(p, _)| p < pos + end).is_some() {}
+                let comment = &rest[..end];
+                if let Some(last_break) = comment.rfind('\n') {
+                    (line, line_start) = (line + comment.matches('\n').count(), pos + last_break + 1);
+                }
+            },
+            '"' => {
+                let end = rest[1..].find('"').ok_or((line, ParseError::new(col, "Unterminated string")))? + 1;
+                let id = &rest[1..end];
+                if id.ends_with('\\') {
+                    return Err((line, ParseError::new(col, "Escaped quotes are not supported")));
+                }
+                if id.contains('\n') {
+                    return Err((line, ParseError::new(col, "Line breaks in names are not supported")));
+                }
+                while chars.next_if(|&(p, _)| p <= pos + end).is_some() {}
+                tokens.push((Token::Id(id), line, col));
+            },
+            '-' if rest.starts_with("--") || rest.starts_with("->") => {
+                chars.next();
+                tokens.push((Token::EdgeOp(&rest[..2]), line, col));
+            },
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' => tokens.push((Token::Punct(c), line, col)),
+            _ if is_name_char(c) || c == '.' || c == '-' => {
+                let mut end = c.len_utf8();
+                while let Some((p, _)) = chars.next_if(|&(_, c)| is_name_char(c) || c == '.') {
+                    end = p - pos + 1;
+                }
+                tokens.push((Token::Id(&rest[..end]), line, col));
+            },
+            '<' => return Err((line, ParseError::new(col, "HTML strings are not supported"))),
+            ':' => return Err((line, ParseError::new(col, "Ports are not supported"))),
+            _ => return Err((line, ParseError::new(col, format!("Unexpected character '{c}'")))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Walks through the tokens of a DOT graph
+struct DotParser<'a> {
+    tokens: Vec<Located<'a>>,
+    pos: usize,
+    /// Position of the end of the input, for errors there
+    end: (usize, usize),
+}
+
+impl<'a> DotParser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|&(token, _, _)| token)
+    }
+
+    fn error(&self, message: impl ToString) -> LineError {
+        let (line, column) = self.tokens.get(self.pos).map_or(self.end, |&(_, line, column)| (line, column));
+        (line, ParseError::new(column, message))
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end.0, |&(_, line, _)| line)
+    }
+
+    fn eat(&mut self, token: Token) -> bool {
+        let found = self.peek() == Some(token);
+        self.pos += usize::from(found);
+        found
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), LineError> {
+        if self.eat(token) { Ok(()) } else { Err(self.error(format!("Expected {what}"))) }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword));
+        self.pos += usize::from(found);
+        found
+    }
+
+    fn id(&mut self) -> Result<&'a str, LineError> {
+        match self.peek() {
+            Some(Token::Id(id)) => {
+                self.pos += 1;
+                Ok(id)
+            },
+            _ => Err(self.error("Expected a name")),
+        }
+    }
+
+    /// Skips attribute lists like "[label=x, color=red][style=bold]".
+    fn attributes(&mut self) -> Result<(), LineError> {
+        while self.eat(Token::Punct('[')) {
+            while !self.eat(Token::Punct(']')) {
+                self.id()?;
+                if self.eat(Token::Punct('=')) {
+                    self.id()?;
+                }
+                let _ = self.eat(Token::Punct(',')) || self.eat(Token::Punct(';'));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a whole graph.
+    fn graph(&mut self) -> Result<Vec<Item<'a>>, LineError> {
+        self.keyword("strict");
+        if self.keyword("digraph") {
+            self.pos -= 1;
+            return Err(self.error("Directed graphs are not supported"));
+        }
+        if !self.keyword("graph") {
+            return Err(self.error("Expected 'graph'"));
+        }
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.pos += 1;
+        }
+        self.expect(Token::Punct('{'), "'{'")?;
+        let mut items = Vec::new();
+        while !self.eat(Token::Punct('}')) {
+            if self.peek().is_none() {
+                return Err(self.error("Expected '}'"));
+            }
+            self.statement(&mut items)?;
+            self.eat(Token::Punct(';'));
+        }
+        if self.peek().is_some() {
+            return Err(self.error("Expected the end of the input"));
+        }
+        Ok(items)
+    }
+
+    fn statement(&mut self, items: &mut Vec<Item<'a>>) -> Result<(), LineError> {
+        if self.peek() == Some(Token::Punct('{')) || self.keyword("subgraph") {
+            return Err(self.error("Subgraphs are not supported"));
+        }
+        if ["graph", "node", "edge"].iter().any(|&keyword| self.keyword(keyword)) {
+            return self.attributes();
+        }
+        let line = self.line();
+        let name = self.id()?;
+        if self.eat(Token::Punct('=')) {
+            self.id()?;
+            return Ok(());
+        }
+        let mut previous = name;
+        let mut connected = false;
+        while let Some(Token::EdgeOp(op)) = self.peek() {
+            if op == "->" {
+                return Err(self.error("Expected '--' in an undirected graph"));
+            }
+            self.pos += 1;
+            if self.peek() == Some(Token::Punct('{')) || self.keyword("subgraph") {
+                return Err(self.error("Subgraphs are not supported"));
+            }
+            let line = self.line();
+            let next = self.id()?;
+            items.push((previous, Some(next), line));
+            previous = next;
+            connected = true;
+        }
+        if !connected {
+            items.push((name, None, line));
+        }
+        self.attributes()
+    }
+}
+
+/// Reads an undirected graph in the DOT language.
+pub(crate) fn parse_dot(input: &str) -> Result<Vec<Item<'_>>, LineError> {
+    let tokens = tokenize(input)?;
+    let last_line = input.lines().count().max(1);
+    let end_column = input.lines().last().map_or(0, |line| line.chars().count()) + 1;
+    DotParser { tokens, pos: 0, end: (last_line, end_column) }.graph()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_formats() {
+        assert_eq!(Format::detect("jqt: rhn xhk"), Format::Colon);
+        assert_eq!(Format::detect("# edges\njqt rhn"), Format::EdgeList);
+        assert_eq!(Format::detect("\n  graph G { a -- b }"), Format::Dot);
+        assert_eq!(Format::detect("strict graph { a -- b }"), Format::Dot);
+        assert_eq!(Format::detect("// comment\nDiGraph { a -> b }"), Format::Dot);
+        assert_eq!(Format::detect("graph_node other"), Format::EdgeList);
+        assert_eq!(Format::detect("jqt rhn\nxhk: hfx"), Format::Colon);
+    }
+
+    #[test]
+    fn edge_lists() {
+        assert_eq!(parse_edge_list("# comment\na b\n\n  c_1   d \ne"), Ok(vec![
+            ("a", Some("b"), 2), ("c_1", Some("d"), 4), ("e", None, 5),
+        ]));
+        assert_eq!(parse_edge_list("a b c"), Err((1, ParseError::new(5, "Unexpected 'c'"))));
+        assert_eq!(parse_edge_list("a\na -- b").unwrap_err().0, 2);
+    }
+
+    #[test]
+    fn dot_graphs() {
+        let dot = r#"
+/* Two triangles
+   joined by one edge */
+strict graph "example" {
+    node [shape=circle]; rankdir=LR
+    a -- b -- c -- a [color=red, style="bold"]
+    "d" -- e; e -- f // the second triangle
+    f -- d
+# a preprocessor line
+    c -- d
+    lonely
+}
+"#;
+        let items = parse_dot(dot).unwrap();
+        assert_eq!(items, vec![
+            ("a", Some("b"), 6), ("b", Some("c"), 6), ("c", Some("a"), 6),
+            ("d", Some("e"), 7), ("e", Some("f"), 7), ("f", Some("d"), 8),
+            ("c", Some("d"), 10), ("lonely", None, 11),
+        ]);
+    }
+
+    #[test]
+    fn dot_errors() {
+        let error = |input| parse_dot(input).unwrap_err();
+        assert_eq!(error("digraph { a -> b }"), (1, ParseError::new(1, "Directed graphs are not supported")));
+        assert_eq!(error("graph {\n  a -> b\n}"), (2, ParseError::new(5, "Expected '--' in an undirected graph")));
+        assert_eq!(error("graph {\n  a -- { b c }\n}"), (2, ParseError::new(8, "Subgraphs are not supported")));
+        assert_eq!(error("graph { a:n -- b }"), (1, ParseError::new(10, "Ports are not supported")));
+        assert_eq!(error("graph { a -- }"), (1, ParseError::new(14, "Expected a name")));
+        assert_eq!(error("graph { a -- b"), (1, ParseError::new(15, "Expected '}'")));
+        assert_eq!(error("graph { \"a -- b }"), (1, ParseError::new(9, "Unterminated string")));
+        assert_eq!(error("graph { a } b"), (1, ParseError::new(13, "Expected the end of the input")));
+        assert_eq!(error("graph { a -- b }\n/* open"), (2, ParseError::new(1, "Unterminated comment")));
+        assert_eq!(error("a -- b"), (1, ParseError::new(1, "Expected 'graph'")));
+    }
+}
@@ -0,0 +1,412 @@
+pub mod config;
+mod import;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parse::{self, ParseError};
+use aoc_core::search;
+use aoc_core::union_find::UnionFind;
+use aoc_core::validate::{self, Issue};
+
+pub use import::Format;
+
+/// Number of wires that have to be disconnected
+const CUT_SIZE: usize = 3;
+
+/// The components as an undirected graph without parallel edges
+struct Graph<'a> {
+    names: Vec<&'a str>,
+    edges: Vec<(usize, usize)>,
+    /// For each vertex, its neighbors together with the indices of the edges to them
+    adjacency: Vec<Vec<(usize, usize)>>,
+}
+
+impl<'a> Graph<'a> {
+    /// Reads a line like "jqt: rhn xhk nvd".
+    fn parse_line(line: &'a str) -> Result<(&'a str, Vec<&'a str>), ParseError> {
+        parse::parse_all(line, |parser| {
+            let name = parser.word("component name", |c| c.is_ascii_alphanumeric())?;
+            parser.tag(":")?;
+            let mut others = Vec::new();
+            while parser.peek().is_some() {
+                parser.tag(" ")?;
+                others.push(parser.word("component name", |c| c.is_ascii_alphanumeric())?);
+            }
+            if others.is_empty() {
+                return Err(parser.error("Expected connected components"));
+            }
+            Ok((name, others))
+        })
+    }
+
+    /// Reads the graph in the format detected by `Format::detect`.
+    fn from_input(input: &'a str) -> Result<Self, String> {
+        let items = match Format::detect(input) {
+            Format::Colon => Self::parse_colon(input)?,
+            Format::EdgeList => import::parse_edge_list(input).map_err(|(line, e)| e.on_line(line))?,
+            Format::Dot => import::parse_dot(input).map_err(|(line, e)| e.on_line(line))?,
+        };
+        Self::from_items(&items)
+    }
+
+    fn parse_colon(input: &'a str) -> Result<Vec<import::Item<'a>>, String> {
+        let mut items = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let (name, others) = Self::parse_line(line).map_err(|e| e.on_line(i + 1))?;
+            items.extend(others.into_iter().map(|other| (name, Some(other), i + 1)));
+        }
+        Ok(items)
+    }
+
+    /// Builds the graph from components and the connections between them, where connections
+    /// given twice count once.
+    fn from_items(items: &[import::Item<'a>]) -> Result<Self, String> {
+        let mut graph = Graph { names: Vec::new(), edges: Vec::new(), adjacency: Vec::new() };
+        let mut indices = HashMap::new();
+        let mut index = |graph: &mut Graph<'a>, name: &'a str| *indices.entry(name).or_insert_with(|| {
+            graph.names.push(name);
+            graph.adjacency.push(Vec::new());
+            graph.names.len() - 1
+        });
+        for &(name, other, line) in items {
+            let u = index(&mut graph, name);
+            let Some(other) = other else {
+                continue;
+            };
+            let v = index(&mut graph, other);
+            if u == v {
+                return Err(format!("Line {line}: {name} is connected to itself"));
+            }
+            if graph.adjacency[u].iter().all(|&(w, _)| w != v) {
+                graph.adjacency[u].push((v, graph.edges.len()));
+                graph.adjacency[v].push((u, graph.edges.len()));
+                graph.edges.push((u, v));
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Vertices reachable from `s` in the residual graph of a flow along the edges, each of which
+    /// has capacity 1 in both directions. `flow[e]` is the flow from the first to the second
+    /// vertex of edge `e`.
+    fn residual_search(&self, s: usize, t: usize, flow: &[i8]) -> search::Search<usize, usize> {
+        search::bfs(
+            [s],
+            |&u| self.adjacency[u].iter()
+                .filter(move |&&(_, e)| if self.edges[e].0 == u { flow[e] < 1 } else { flow[e] > -1 })
+                .map(|&(v, _)| v),
+            |&u, _| u == t,
+        )
+    }
+
+    /// The minimum cut separating `s` and `t` as the vertices on the side of `s`, or `None` if
+    /// more than `max_size` edges have to be cut. Augments a unit flow along shortest paths.
+    fn min_cut(&self, s: usize, t: usize, max_size: usize) -> Option<Vec<usize>> {
+        let mut flow = vec![0i8; self.edges.len()];
+        for _ in 0..=max_size {
+            let search = self.residual_search(s, t, &flow);
+            let Some(path) = search.path_to(&t) else {
+                let mut side: Vec<usize> = search.costs().map(|(&u, _)| u).collect();
+                side.sort_unstable();
+                return Some(side);
+            };
+            for step in path.windows(2) {
+                let (u, v) = (step[0], step[1]);
+                let &(_, e) = self.adjacency[u].iter().find(|&&(w, _)| w == v)?;
+                flow[e] += if self.edges[e].0 == u { 1 } else { -1 };
+            }
+        }
+        None
+    }
+
+    /// Indices of the edges with exactly one end in `side`
+    fn crossing_edges(&self, side: &[usize]) -> Vec<usize> {
+        let mut in_side = vec![false; self.names.len()];
+        for &u in side {
+            in_side[u] = true;
+        }
+        (0..self.edges.len()).filter(|&e| in_side[self.edges[e].0] != in_side[self.edges[e].1]).collect()
+    }
+}
+
+/// A set of wires whose removal splits the components into two groups
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cut {
+    /// The names in both groups, each sorted, with the group of the first component in the input
+    /// first
+    pub groups: [Vec<String>; 2],
+    /// The wires between the groups as pairs of names, each pair and the list sorted
+    pub wires: Vec<(String, String)>,
+}
+
+impl Cut {
+    fn new(graph: &Graph, side: &[usize], edges: &[usize]) -> Self {
+        let mut in_side = vec![false; graph.names.len()];
+        for &u in side {
+            in_side[u] = true;
+        }
+        let group = |inside| {
+            let mut names: Vec<String> = (0..graph.names.len())
+                .filter(|&u| in_side[u] == inside)
+                .map(|u| graph.names[u].to_owned())
+                .collect();
+            names.sort_unstable();
+            names
+        };
+        let mut wires: Vec<(String, String)> = edges.iter().map(|&e| {
+            let (a, b) = (graph.names[graph.edges[e].0], graph.names[graph.edges[e].1]);
+            (a.min(b).to_owned(), a.max(b).to_owned())
+        }).collect();
+        wires.sort_unstable();
+        Cut { groups: [group(true), group(false)], wires }
+    }
+
+    /// Product of the sizes of both groups
+    pub fn product(&self) -> usize {
+        self.groups[0].len() * self.groups[1].len()
+    }
+
+    /// The wires like "bvb/cmg, hfx/pzl, jqt/nvd"
+    pub fn wire_list(&self) -> String {
+        self.wires.iter().map(|(a, b)| format!("{a}/{b}")).collect::<Vec<_>>().join(", ")
+    }
+}
+
+impl fmt::Display for Cut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Wires to disconnect: {}", self.wire_list())?;
+        for (i, group) in self.groups.iter().enumerate() {
+            writeln!(f, "Group {} ({} components): {}", i + 1, group.len(), group.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the wires to disconnect to split the components into two groups. Fails unless exactly
+/// `CUT_SIZE` wires have to be disconnected.
+pub fn find_cut(input: &str) -> Result<Cut, Box<dyn Error>> {
+    let graph = Graph::from_input(input)?;
+    if graph.names.is_empty() {
+        return Err("No components".into());
+    }
+    // The first component is in one group, so some other one is in the other group, and the
+    // minimum cut separating both has `CUT_SIZE` edges
+    for t in 1..graph.names.len() {
+        if let Some(side) = graph.min_cut(0, t, CUT_SIZE) {
+            let edges = graph.crossing_edges(&side);
+            if edges.len() < CUT_SIZE {
+                return Err(format!("Fewer than {CUT_SIZE} wires suffice to split the components").into());
+            }
+            return Ok(Cut::new(&graph, &side, &edges));
+        }
+    }
+    Err(format!("The components cannot be split by disconnecting {CUT_SIZE} wires").into())
+}
+
+/// Checks the cut independently of how it was found: removing its wires must leave exactly its
+/// two groups connected, and each of its wires must join both groups.
+pub fn verify_cut(input: &str, cut: &Cut) -> Result<(), Box<dyn Error>> {
+    let graph = Graph::from_input(input)?;
+    let index: HashMap<&str, usize> = graph.names.iter().enumerate().map(|(i, &name)| (name, i)).collect();
+    let find = |name: &str| index.get(name).copied().ok_or_else(|| format!("Unknown component {name}"));
+    let mut removed = Vec::with_capacity(cut.wires.len());
+    for (a, b) in &cut.wires {
+        let (u, v) = (find(a)?, find(b)?);
+        let &(_, e) = graph.adjacency[u].iter().find(|&&(w, _)| w == v).ok_or_else(|| format!("There is no wire {a}/{b}"))?;
+        removed.push(e);
+    }
+    let mut components = UnionFind::new(graph.names.len());
+    for (e, &(u, v)) in graph.edges.iter().enumerate() {
+        if !removed.contains(&e) {
+            components.union(u, v);
+        }
+    }
+    if components.num_sets() != 2 {
+        return Err(format!("Disconnecting the wires leaves {} groups instead of 2", components.num_sets()).into());
+    }
+    let mut groups = components.sets().into_iter().map(|set| {
+        let mut names: Vec<String> = set.into_iter().map(|u| graph.names[u].to_owned()).collect();
+        names.sort_unstable();
+        names
+    });
+    let found = [groups.next(), groups.next()];
+    if found != [Some(cut.groups[0].clone()), Some(cut.groups[1].clone())] {
+        return Err("The groups left after disconnecting the wires differ from those of the cut".into());
+    }
+    for &e in &removed {
+        let (u, v) = graph.edges[e];
+        if components.connected(u, v) {
+            return Err(format!("The wire {}/{} is not between the groups", graph.names[u], graph.names[v]).into());
+        }
+    }
+    Ok(())
+}
+
+/// Both parts, solved from the same cut
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Cut;
+
+    /// Reads the components and finds the wires to disconnect.
+    fn parse(input: &str) -> Result<Cut, Box<dyn Error>> {
+        find_cut(input)
+    }
+
+    fn part1(cut: &Cut) -> Result<Answer, Box<dyn Error>> {
+        Ok(cut.product().into())
+    }
+
+    /// Day 25 has no second puzzle, so this names the wires to disconnect.
+    fn part2(cut: &Cut) -> Result<Answer, Box<dyn Error>> {
+        Ok(cut.wire_list().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Day 25 has no second puzzle, so this names the wires to disconnect.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as connections and components connected to themselves,
+/// in any of the formats.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let issues = match Format::detect(input) {
+        Format::Colon => validate::check_lines(input, |line| Graph::parse_line(line).map(|_| ())),
+        Format::EdgeList => import::parse_edge_list(input).err().map(|(line, e)| Issue::at(line, e.column, e.message)).into_iter().collect(),
+        Format::Dot => import::parse_dot(input).err().map(|(line, e)| Issue::at(line, e.column, e.message)).into_iter().collect(),
+    };
+    if !issues.is_empty() {
+        return issues;
+    }
+    validate::check_all(Graph::from_input(input).and_then(|graph| {
+        if graph.names.is_empty() { Err("No components".to_owned()) } else { Ok(()) }
+    }))
+}
+
+pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    println!("Part 1: Reading file {}", config.file_path1);
+    let contents = fs::read_to_string(&config.file_path1)?;
+    let cut = Day::parse(&contents)?;
+    println!("Result of part 1: {}", Day::part1(&cut)?);
+    if config.verify {
+        print!("{cut}");
+        verify_cut(&contents, &cut)?;
+        println!("Verified: disconnecting the wires leaves exactly these two groups");
+    }
+
+    let cut = answer::parse_part2(cut, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&cut)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+";
+
+    #[test]
+    fn example_part1() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1(EXAMPLE.trim())?;
+        assert_eq!(result, 54);
+        Ok(())
+    }
+
+    #[test]
+    fn example_cut() -> Result<(), Box<dyn Error>> {
+        let cut = find_cut(EXAMPLE.trim())?;
+        assert_eq!(cut.wire_list(), "bvb/cmg, hfx/pzl, jqt/nvd");
+        assert_eq!(cut.groups[0], ["bvb", "hfx", "jqt", "ntq", "rhn", "xhk"]);
+        assert_eq!(cut.groups[1], ["cmg", "frs", "lhk", "lsr", "nvd", "pzl", "qnr", "rsh", "rzs"]);
+        verify_cut(EXAMPLE.trim(), &cut)?;
+        assert_eq!(solve_part2(EXAMPLE.trim())?, Answer::from("bvb/cmg, hfx/pzl, jqt/nvd"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_rejects_wrong_cuts() -> Result<(), Box<dyn Error>> {
+        let cut = find_cut(EXAMPLE.trim())?;
+        let mut missing_wire = cut.clone();
+        missing_wire.wires.pop();
+        assert!(verify_cut(EXAMPLE.trim(), &missing_wire).is_err());
+        let mut wrong_wire = cut.clone();
+        wrong_wire.wires[0] = ("jqt".to_owned(), "rhn".to_owned());
+        assert!(verify_cut(EXAMPLE.trim(), &wrong_wire).is_err());
+        let mut wrong_groups = cut.clone();
+        let moved = wrong_groups.groups[1].pop().unwrap_or_default();
+        wrong_groups.groups[0].push(moved);
+        assert!(verify_cut(EXAMPLE.trim(), &wrong_groups).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn no_cut_of_three() {
+        // A square with a diagonal needs only two cuts to split off a corner
+        assert!(find_cut("a: b c d\nb: c\nc: d").is_err());
+        // Two cliques of five joined by four wires
+        let cliques = "a: b c d e\nb: c d e\nc: d e\nd: e\nf: g h i j\ng: h i j\nh: i j\ni: j\na: f\nb: g\nc: h\nd: i";
+        assert!(find_cut(cliques).is_err());
+    }
+
+    #[test]
+    fn other_formats() -> Result<(), Box<dyn Error>> {
+        let edges: Vec<String> = EXAMPLE.trim().lines().flat_map(|line| {
+            let (name, others) = line.split_once(": ").unwrap_or_default();
+            others.split(' ').map(move |other| format!("{name} {other}")).collect::<Vec<_>>()
+        }).collect();
+        let edge_list = format!("# The example as an edge list\n{}", edges.join("\n"));
+        let dot_edges: Vec<String> = edges.iter().map(|edge| edge.replace(' ', " -- ")).collect();
+        let dot = format!("graph example {{\n    {};\n}}", dot_edges.join(";\n    "));
+        let expected = find_cut(EXAMPLE.trim())?;
+        for input in [&edge_list, &dot] {
+            let cut = find_cut(input)?;
+            assert_eq!(cut, expected);
+            verify_cut(input, &cut)?;
+            assert!(validate(input).is_empty());
+        }
+        assert_eq!(validate("graph {\n  a -- b\n  b -> c\n}"), vec![Issue::at(3, 5, "Expected '--' in an undirected graph")]);
+        assert_eq!(validate("a b\nb c d"), vec![Issue::at(2, 5, "Unexpected 'd'")]);
+        assert_eq!(validate("a b\nc c"), vec![Issue::new("Line 2: c is connected to itself")]);
+        assert_eq!(find_cut("digraph { a -> b }").unwrap_err().to_string(), "Line 1, column 1: Directed graphs are not supported");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_connections() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        assert_eq!(validate("jqt rhn\nxhk:\nab: cd  ef"), vec![
+            Issue::at_line(1, "column 4: Expected ':'"),
+            Issue::at_line(2, "column 5: Expected connected components"),
+            Issue::at_line(3, "column 8: Expected component name"),
+        ]);
+        assert_eq!(validate("ab: cd ab"), vec![Issue::new("Line 1: ab is connected to itself")]);
+    }
+}
@@ -1,17 +1,28 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Compare the two files as schematics instead of solving the puzzle
+    pub diff: bool,
+    /// Read part 1 column by column, for lines too long to be held in memory
+    pub by_columns: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
-        }
+        let diff = args.get(1).is_some_and(|arg| arg == "diff");
+        let paths = if diff { &args[2..] } else { args.get(1..).unwrap_or_default() };
+        let (paths, by_columns) = match paths {
+            [paths @ .., last] if last == "--by-columns" => (paths, true),
+            _ => (paths, false),
+        };
+        let [file_path1, file_path2] = paths else {
+            return Err("Usage: day3 [diff] <file 1> <file 2> [--by-columns]");
+        };
         Ok(Config {
-            file_path1: args[1].clone(),
-            file_path2: args[2].clone(),
+            file_path1: file_path1.clone(),
+            file_path2: file_path2.clone(),
+            diff,
+            by_columns,
         })
     }
 }
-
@@ -1,19 +1,50 @@
 pub mod config;
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
-use std::fs;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 
-fn is_symbol(c: char) -> bool {
-    !(c.is_ascii_digit() || c == '.')
+use aoc_core::answer::{Answer, PartSolver};
+use aoc_core::validate::{self, Issue};
+
+const TOO_LARGE: &str = "Number too large";
+
+/// Error for a number or gear ratio whose value does not fit into 64 bits, at 0-based coordinates
+fn too_large(item: &str, x: usize, y: usize) -> String {
+    format!("{item} at line {}, column {} too large", y + 1, x + 1)
+}
+
+/// Which characters of a schematic count as symbols. Digits never do. The default treats every
+/// character except digits and periods as a symbol.
+#[derive(Clone, Debug, Default)]
+pub struct SchematicRules {
+    /// The characters that count as symbols, or `None` for all except digits and periods
+    pub symbols: Option<BTreeSet<char>>,
 }
 
-fn symbol_at_nth_char(n: usize, line: &str) -> Option<char> {
-    return line.chars().nth(n).filter(|&c| is_symbol(c));
+impl SchematicRules {
+    /// Rules under which only the given characters are symbols, which may include periods
+    pub fn with_symbols(symbols: impl IntoIterator<Item = char>) -> Self {
+        Self { symbols: Some(symbols.into_iter().collect()) }
+    }
+
+    pub fn is_symbol(&self, c: char) -> bool {
+        !c.is_ascii_digit() && match &self.symbols {
+            Some(symbols) => symbols.contains(&c),
+            None => c != '.',
+        }
+    }
+
+    fn symbol_at_nth_char(&self, n: usize, line: &str) -> Option<char> {
+        line.chars().nth(n).filter(|&c| self.is_symbol(c))
+    }
 }
 
 #[derive(Debug)]
 struct Number {
-    value: u32,
+    value: u64,
     // coordinates of first character
     x: usize,
     y: usize,
@@ -22,36 +53,34 @@ struct Number {
 }
 
 impl Number {
-    fn adjacent_symbol(&self, lines: &[&str]) -> Option<char> {
+    fn adjacent_symbol(&self, lines: &[&str], rules: &SchematicRules) -> Option<char> {
         if self.y > 0 {
-            let symbol = lines.get(self.y - 1).and_then(|line_before| self.adjacent_symbol_in_line(line_before));
+            let symbol = lines.get(self.y - 1).and_then(|line_before| self.adjacent_symbol_in_line(line_before, rules));
             if let Some(s) = symbol {
                 return Some(s);
             }
         }
         if let Some(same_line) = lines.get(self.y) {
             if self.x > 0 {
-                if let Some(s) = symbol_at_nth_char(self.x - 1, same_line) {
+                if let Some(s) = rules.symbol_at_nth_char(self.x - 1, same_line) {
                     return Some(s);
                 }
             }
-            if let Some(s) = symbol_at_nth_char(self.x + self.len, same_line) {
+            if let Some(s) = rules.symbol_at_nth_char(self.x + self.len, same_line) {
                 return Some(s);
             }
         }
-        let symbol = lines.get(self.y + 1).and_then(|line_after| self.adjacent_symbol_in_line(line_after));
+        let symbol = lines.get(self.y + 1).and_then(|line_after| self.adjacent_symbol_in_line(line_after, rules));
         if let Some(s) = symbol {
             return Some(s);
         }
         None
     }
 
-    fn adjacent_symbol_in_line(&self, line: &str) -> Option<char> {
+    fn adjacent_symbol_in_line(&self, line: &str, rules: &SchematicRules) -> Option<char> {
         let x_minus_one = self.x.saturating_sub(1);
         let x_plus_len_plus_one = self.x + self.len + 1;
-        let end_index = x_plus_len_plus_one.min(line.len());
-        let slice = &line[x_minus_one..end_index];
-        slice.chars().find(|&c| is_symbol(c))
+        line.chars().take(x_plus_len_plus_one).skip(x_minus_one).find(|&c| rules.is_symbol(c))
     }
 
     fn is_adjacent_to(&self, x: usize, y: usize) -> bool {
@@ -74,41 +103,54 @@ impl Gear {
         let mut result: Vec<&Number> = Vec::new();
         for number in numbers {
             if number.is_adjacent_to(self.x, self.y) {
-                result.push(&number);
+                result.push(number);
             }
         }
         result
     }
 }
 
-fn build_numbers(lines: &[&str]) -> Vec<Number> {
-    let mut numbers: Vec<Number> = Vec::new();
-    for (y, line) in lines.iter().enumerate() {
-        let mut cur_number: Option<Number> = None;
-        for (x, c) in line.chars().enumerate() {
-            if let Some(digit) = c.to_digit(10) {
-                let n = cur_number.get_or_insert(Number { value: 0, x, y, len: 0 });
-                n.len += 1;
-                n.value = 10 * n.value + digit;
-            } else {
-                // Commit cur_number to vector and set to None
-                if let Some(n) = cur_number.take() {
-                    numbers.push(n);
-                }
+/// Appends the value of a digit to a number, failing with the position of the number if it gets
+/// too large.
+fn push_digit(value: u64, digit: u32, x: usize, y: usize) -> Result<u64, String> {
+    value.checked_mul(10).and_then(|v| v.checked_add(digit as u64)).ok_or_else(|| too_large("Number", x, y))
+}
+
+fn build_numbers_in_line(line: &str, y: usize, numbers: &mut Vec<Number>) -> Result<(), String> {
+    let mut cur_number: Option<Number> = None;
+    for (x, c) in line.chars().enumerate() {
+        if let Some(digit) = c.to_digit(10) {
+            let n = cur_number.get_or_insert(Number { value: 0, x, y, len: 0 });
+            n.len += 1;
+            n.value = push_digit(n.value, digit, n.x, y)?;
+        } else {
+            // Commit cur_number to vector and set to None
+            if let Some(n) = cur_number.take() {
+                numbers.push(n);
             }
-            // println!("({x},{y}): {c}");
-        }
-        // Commit number at end of line even if no characters after it are read
-        if let Some(n) = cur_number.take() {
-            numbers.push(n);
         }
     }
-    numbers
+    // Commit number at end of line even if no characters after it are read
+    if let Some(n) = cur_number.take() {
+        numbers.push(n);
+    }
+    Ok(())
 }
 
-fn build_gears(lines: &[&str]) -> Vec<Gear> {
+fn build_numbers(lines: &[&str]) -> Result<Vec<Number>, String> {
+    let mut numbers: Vec<Number> = Vec::new();
+    for (y, line) in lines.iter().enumerate() {
+        build_numbers_in_line(line, y, &mut numbers)?;
+    }
+    Ok(numbers)
+}
+
+fn build_gears(lines: &[&str], rules: &SchematicRules) -> Vec<Gear> {
     // TODO: Could be done in one read together with build_numbers
     let mut gears: Vec<Gear> = Vec::new();
+    if !rules.is_symbol('*') {
+        return gears;
+    }
     for (y, line) in lines.iter().enumerate() {
         for (x, c) in line.chars().enumerate() {
             if c == '*' {
@@ -119,42 +161,437 @@ fn build_gears(lines: &[&str]) -> Vec<Gear> {
     gears
 }
 
-fn part1(input: &str) -> Result<(), Box<dyn Error>> {
-    let lines: Vec<&str> = input.lines().collect();
-    let numbers = build_numbers(&lines);
-    let mut sum = 0;
-    for number in numbers {
-        if number.adjacent_symbol(&lines).is_some() {
-            sum += number.value;
+/// Position of a number or gear as (x, y) coordinates, where (0, 0) is the top-left character and
+/// the position of a number is that of its first digit
+pub type Position = (usize, usize);
+
+/// The numbers and gears of an engine schematic
+pub struct Schematic {
+    numbers: Vec<Number>,
+    gears: Vec<Gear>,
+    part_numbers: BTreeMap<Position, u64>,
+}
+
+impl Schematic {
+    pub fn from_input(input: &str) -> Result<Self, String> {
+        Self::from_input_with(input, &SchematicRules::default())
+    }
+
+    /// Reads a schematic whose symbols are those of `rules`. Gears are only found if `*` is a
+    /// symbol.
+    pub fn from_input_with(input: &str, rules: &SchematicRules) -> Result<Self, String> {
+        let lines: Vec<&str> = input.lines().collect();
+        let numbers = build_numbers(&lines)?;
+        let gears = build_gears(&lines, rules);
+        let part_numbers = numbers.iter()
+            .filter(|number| number.adjacent_symbol(&lines, rules).is_some())
+            .map(|number| ((number.x, number.y), number.value))
+            .collect();
+        Ok(Self { numbers, gears, part_numbers })
+    }
+
+    /// The numbers adjacent to a symbol, by position
+    pub fn part_numbers(&self) -> &BTreeMap<Position, u64> {
+        &self.part_numbers
+    }
+
+    /// The gear ratios of the `*` symbols adjacent to exactly two numbers, by position
+    pub fn gear_ratios(&self) -> Result<BTreeMap<Position, u64>, String> {
+        self.gears.iter().filter_map(|gear| {
+            let numbers = gear.adjacent_numbers(&self.numbers);
+            (numbers.len() == 2).then(|| {
+                let ratio = numbers[0].value.checked_mul(numbers[1].value)
+                    .ok_or_else(|| too_large("Gear ratio", gear.x, gear.y))?;
+                Ok(((gear.x, gear.y), ratio))
+            })
+        }).collect()
+    }
+
+    /// Compares this schematic to `other` and returns the changes from this one to the other one.
+    pub fn diff(&self, other: &Self) -> Result<Vec<Change>, String> {
+        let mut changes = diff_maps(Item::PartNumber, &self.part_numbers, &other.part_numbers);
+        changes.extend(diff_maps(Item::GearRatio, &self.gear_ratios()?, &other.gear_ratios()?));
+        Ok(changes)
+    }
+}
+
+/// What a change in a schematic concerns
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Item {
+    PartNumber,
+    GearRatio,
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::PartNumber => write!(f, "Part number"),
+            Item::GearRatio => write!(f, "Gear ratio"),
         }
     }
-    println!("Sum for part 1: {sum}");
-    Ok(())
 }
 
-fn part2(input: &str) -> Result<(), Box<dyn Error>> {
-    let lines: Vec<&str> = input.lines().collect();
-    let numbers = build_numbers(&lines);
-    let gears = build_gears(&lines);
+/// A part number or gear ratio that differs between two schematics. `None` means that there is no
+/// such item at the position.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Change {
+    pub item: Item,
+    pub position: Position,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.position;
+        write!(f, "line {}, column {}: {} ", y + 1, x + 1, self.item)?;
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => write!(f, "changed from {old} to {new}"),
+            (Some(old), None) => write!(f, "{old} removed"),
+            (None, Some(new)) => write!(f, "{new} added"),
+            (None, None) => write!(f, "unchanged"),
+        }
+    }
+}
+
+fn diff_maps(item: Item, old: &BTreeMap<Position, u64>, new: &BTreeMap<Position, u64>) -> Vec<Change> {
+    let positions: BTreeSet<&Position> = old.keys().chain(new.keys()).collect();
+    positions.into_iter().filter_map(|position| {
+        let (old, new) = (old.get(position).copied(), new.get(position).copied());
+        (old != new).then_some(Change { item, position: *position, old, new })
+    }).collect()
+}
+
+/// Sums the part numbers of a schematic read line by line, holding only the line before and the
+/// line after the current one in memory.
+pub fn sum_part_numbers_from_reader(reader: impl BufRead, rules: &SchematicRules) -> io::Result<u64> {
+    let mut lines = reader.lines();
+    let mut previous: Option<String> = None;
+    let mut current = lines.next().transpose()?;
     let mut sum = 0;
-    for gear in gears {
-        let numbers = gear.adjacent_numbers(&numbers);
-        if numbers.len() == 2 {
-            sum += numbers[0].value * numbers[1].value;
+    let mut line_number = 0;
+    while let Some(line) = current {
+        let next = lines.next().transpose()?;
+        // The window of lines around the current one, which is at index y
+        let mut window: Vec<&str> = previous.iter().map(String::as_str).collect();
+        let y = window.len();
+        window.push(&line);
+        window.extend(next.as_deref());
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut numbers = Vec::new();
+        build_numbers_in_line(&line, line_number, &mut numbers).map_err(invalid)?;
+        sum = numbers.into_iter()
+            .map(|number| Number { y, ..number })
+            .filter(|number| number.adjacent_symbol(&window, rules).is_some())
+            .try_fold(sum, |sum: u64, number| sum.checked_add(number.value))
+            .ok_or_else(|| invalid(TOO_LARGE.to_string()))?;
+        previous = Some(line);
+        current = next;
+        line_number += 1;
+    }
+    Ok(sum)
+}
+
+/// Reads one line of a schematic byte by byte, yielding `None` from the end of the line on.
+struct LineCursor<R: Read> {
+    reader: Option<io::Bytes<BufReader<R>>>,
+}
+
+impl<R: Read + Seek> LineCursor<R> {
+    fn new(mut reader: R, start: Option<u64>) -> io::Result<Self> {
+        let Some(start) = start else {
+            return Ok(Self { reader: None });
+        };
+        reader.seek(SeekFrom::Start(start))?;
+        Ok(Self { reader: Some(BufReader::new(reader).bytes()) })
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = match &mut self.reader {
+            Some(bytes) => bytes.next().transpose()?.filter(|&b| b != b'\n' && b != b'\r'),
+            None => None,
+        };
+        if byte.is_none() {
+            self.reader = None;
+        }
+        Ok(byte)
+    }
+}
+
+/// Sums the part numbers of a schematic like `sum_part_numbers_from_reader`, but column by column,
+/// so that memory does not depend on the width of the lines. `open` is called to get a reader for
+/// the schematic once for finding where lines start and three times for each line, to read the
+/// line before, the line itself and the line after it side by side. Characters are read as bytes.
+pub fn sum_part_numbers_by_columns<R: Read + Seek>(
+    open: impl Fn() -> io::Result<R>,
+    rules: &SchematicRules,
+) -> io::Result<u64> {
+    let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+    // Offsets where lines start; a final line break does not start another line
+    let mut starts = vec![0];
+    let mut offset = 0;
+    for byte in BufReader::new(open()?).bytes() {
+        offset += 1;
+        if byte? == b'\n' {
+            starts.push(offset);
         }
     }
-    println!("Sum for part 2: {sum}");
+    if starts.last() == Some(&offset) {
+        starts.pop();
+    }
+
+    let is_symbol_byte = |byte: Option<u8>| byte.is_some_and(|b| rules.is_symbol(b as char));
+    let mut sum: u64 = 0;
+    for y in 0..starts.len() {
+        let start = |i: Option<usize>| i.and_then(|i| starts.get(i).copied());
+        let mut before = LineCursor::new(open()?, start(y.checked_sub(1)))?;
+        let mut line = LineCursor::new(open()?, start(Some(y)))?;
+        let mut after = LineCursor::new(open()?, start(Some(y + 1)))?;
+        // The current number with the column where it starts, whether it is adjacent to a symbol
+        // so far, and whether there is a symbol in the column before the current one
+        let mut number: Option<(u64, usize, bool)> = None;
+        let mut symbol_before = false;
+        for x in 0.. {
+            let (b, c, a) = (before.next_byte()?, line.next_byte()?, after.next_byte()?);
+            if b.is_none() && c.is_none() && a.is_none() {
+                if let Some((value, _, true)) = number {
+                    sum = sum.checked_add(value).ok_or_else(|| invalid(TOO_LARGE.to_string()))?;
+                }
+                break;
+            }
+            let symbol_here = is_symbol_byte(b) || is_symbol_byte(c) || is_symbol_byte(a);
+            match (c.and_then(|c| (c as char).to_digit(10)), &mut number) {
+                (Some(digit), Some((value, start, adjacent))) => {
+                    *value = push_digit(*value, digit, *start, y).map_err(invalid)?;
+                    *adjacent |= symbol_here;
+                },
+                (Some(digit), None) => number = Some((digit as u64, x, symbol_before || symbol_here)),
+                (None, _) => {
+                    if let Some((value, _, adjacent)) = number.take() {
+                        if adjacent || symbol_here {
+                            sum = sum.checked_add(value).ok_or_else(|| invalid(TOO_LARGE.to_string()))?;
+                        }
+                    }
+                },
+            }
+            symbol_before = symbol_here;
+        }
+    }
+    Ok(sum)
+}
+
+fn part1(reader: impl BufRead) -> Result<Answer, Box<dyn Error>> {
+    Ok(sum_part_numbers_from_reader(reader, &SchematicRules::default())?.into())
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    let sum = Schematic::from_input(input)?.gear_ratios()?.values()
+        .try_fold(0u64, |sum, &ratio| sum.checked_add(ratio))
+        .ok_or(TOO_LARGE)?;
+    Ok(sum.into())
+}
+
+/// Prints the part numbers and gear ratios that differ between the two schematics.
+fn diff(old_input: &str, new_input: &str) -> Result<(), String> {
+    let changes = Schematic::from_input(old_input)?.diff(&Schematic::from_input(new_input)?)?;
+    if changes.is_empty() {
+        println!("No changes in part numbers or gear ratios");
+    }
+    for change in changes {
+        println!("{change}");
+    }
     Ok(())
 }
 
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    part1(input.as_bytes())
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports ragged lines and characters that are neither digits, periods nor printable ASCII
+/// symbols.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_grid(input, |c| c.is_ascii_graphic())
+}
+
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if config.diff {
+        let old_contents = fs::read_to_string(config.file_path1)?;
+        let new_contents = fs::read_to_string(config.file_path2)?;
+        diff(&old_contents, &new_contents)?;
+        return Ok(());
+    }
+
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    part1(&contents)?;
+    let result = if config.by_columns {
+        sum_part_numbers_by_columns(|| File::open(&config.file_path1), &SchematicRules::default())?.into()
+    } else {
+        part1(BufReader::new(File::open(&config.file_path1)?))?
+    };
+    println!("Result of part 1: {result}");
 
     println!("Part 2: Reading file {}", config.file_path2);
     let contents = fs::read_to_string(config.file_path2)?;
-    part2(&contents)?;
+    let result = solve_part2(&contents)?;
+    println!("Result of part 2: {result}");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    const EXAMPLE: &str = "
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    /// Sums the part numbers of the input in memory, line by line and column by column.
+    fn sums(input: &str, rules: &SchematicRules) -> Result<[u64; 3], Box<dyn Error>> {
+        Ok([
+            Schematic::from_input_with(input, rules)?.part_numbers().values().sum(),
+            sum_part_numbers_from_reader(input.as_bytes(), rules)?,
+            sum_part_numbers_by_columns(|| Ok(Cursor::new(input.as_bytes())), rules)?,
+        ])
+    }
+
+    #[test]
+    fn example_part1() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part1(EXAMPLE.trim())?, 4361);
+        assert_eq!(sums(EXAMPLE.trim(), &SchematicRules::default())?, [4361; 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn example_part2() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part2(EXAMPLE.trim())?, 467835);
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() -> Result<(), Box<dyn Error>> {
+        let rules = SchematicRules::default();
+        // Numbers on the first line with symbols below, on the last line with symbols above, at
+        // both ends of lines, and numbers whose symbols are just out of reach
+        let cases = [
+            ("12.\n..*", 12),
+            ("*..\n.34", 34),
+            ("1.5\n#..", 1),
+            ("5..\n..#", 0),
+            ("..7\n.#.\n9..", 16),
+            ("...\n.8.\n...", 0),
+            ("....1\n.....\n*.2.3\n...$.", 5),
+            ("42", 0),
+            ("", 0),
+        ];
+        for (input, sum) in cases {
+            assert_eq!(sums(input, &rules)?, [sum; 3], "{input:?}");
+            let with_newline = format!("{input}\n");
+            assert_eq!(sums(&with_newline, &rules)?, [sum; 3], "{with_newline:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn number_too_large() -> Result<(), Box<dyn Error>> {
+        let rules = SchematicRules::default();
+        let input = "......\n..18446744073709551616*\n";
+        let error = "Number at line 2, column 3 too large";
+        assert_eq!(Schematic::from_input(input).err().as_deref(), Some(error));
+        assert_eq!(sum_part_numbers_from_reader(input.as_bytes(), &rules).unwrap_err().to_string(), error);
+        let by_columns = sum_part_numbers_by_columns(|| Ok(Cursor::new(input.as_bytes())), &rules);
+        assert_eq!(by_columns.unwrap_err().to_string(), error);
+        // The largest number still fits, but not together with another one
+        assert_eq!(sums("18446744073709551615*", &rules)?, [u64::MAX; 3]);
+        let input = "18446744073709551615*1";
+        assert_eq!(sum_part_numbers_from_reader(input.as_bytes(), &rules).unwrap_err().to_string(), TOO_LARGE);
+        let by_columns = sum_part_numbers_by_columns(|| Ok(Cursor::new(input.as_bytes())), &rules);
+        assert_eq!(by_columns.unwrap_err().to_string(), TOO_LARGE);
+        Ok(())
+    }
+
+    #[test]
+    fn columns_match_rows_on_wide_lines() -> Result<(), Box<dyn Error>> {
+        let rules = SchematicRules::default();
+        let line = |symbol_every: usize| (0..2000)
+            .map(|i| if i % symbol_every == 0 { '*' } else if i % 7 < 3 { '.' } else { char::from(b'0' + (i % 10) as u8) })
+            .collect::<String>();
+        let input = [line(50), line(13), line(97), line(31)].join("\n");
+        let [in_memory, rows, columns] = sums(&input, &rules)?;
+        assert!(rows > 0);
+        assert_eq!((in_memory, columns), (rows, rows));
+        Ok(())
+    }
+
+    #[test]
+    fn custom_symbols() -> Result<(), Box<dyn Error>> {
+        let rules = SchematicRules::with_symbols(['#']);
+        let schematic = Schematic::from_input_with(EXAMPLE.trim(), &rules)?;
+        assert_eq!(schematic.part_numbers(), &BTreeMap::from([((6, 2), 633)]));
+        assert!(schematic.gear_ratios()?.is_empty());
+        assert_eq!(sums(EXAMPLE.trim(), &rules)?, [633; 3]);
+        let rules = SchematicRules::with_symbols(['*', '$']);
+        assert_eq!(sums(EXAMPLE.trim(), &rules)?, [467 + 35 + 617 + 755 + 598 + 664; 3]);
+        assert_eq!(Schematic::from_input_with(EXAMPLE.trim(), &rules)?.gear_ratios()?.len(), 2);
+        // Every number touches a period, which counts as a symbol when listed
+        let rules = SchematicRules::with_symbols(['.']);
+        assert_eq!(sums(EXAMPLE.trim(), &rules)?, [4361 + 114 + 58; 3]);
+        // Digits never count as symbols
+        let rules = SchematicRules::with_symbols(['1', '2']);
+        assert_eq!(sums("12\n21", &rules)?, [0; 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn diff_edited_example() -> Result<(), String> {
+        let old = Schematic::from_input(EXAMPLE.trim())?;
+        // A symbol next to 114 instead of the gear above 35, no symbol below 633, and 756 instead
+        // of 755
+        let edited = EXAMPLE.trim()
+            .replace("...*......", "...#+.....")
+            .replace("......#...", "..........")
+            .replace("......755.", "......756.");
+        let changes = old.diff(&Schematic::from_input(&edited)?)?;
+        assert_eq!(changes, vec![
+            Change { item: Item::PartNumber, position: (5, 0), old: None, new: Some(114) },
+            Change { item: Item::PartNumber, position: (6, 2), old: Some(633), new: None },
+            Change { item: Item::PartNumber, position: (6, 7), old: Some(755), new: Some(756) },
+            Change { item: Item::GearRatio, position: (3, 1), old: Some(16345), new: None },
+            Change { item: Item::GearRatio, position: (5, 8), old: Some(451490), new: Some(452088) },
+        ]);
+        let lines: Vec<String> = changes.iter().map(Change::to_string).collect();
+        assert_eq!(lines, [
+            "line 1, column 6: Part number 114 added",
+            "line 3, column 7: Part number 633 removed",
+            "line 8, column 7: Part number changed from 755 to 756",
+            "line 2, column 4: Gear ratio 16345 removed",
+            "line 9, column 6: Gear ratio changed from 451490 to 452088",
+        ]);
+        assert!(old.diff(&old)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn diff_gear_ratio_too_large() -> Result<(), String> {
+        let old = Schematic::from_input(EXAMPLE.trim())?;
+        let new = Schematic::from_input("4294967296*4294967296")?;
+        assert_eq!(old.diff(&new).unwrap_err(), "Gear ratio at line 1, column 11 too large");
+        assert_eq!(new.diff(&old).unwrap_err(), "Gear ratio at line 1, column 11 too large");
+        Ok(())
+    }
+}
@@ -1,17 +1,30 @@
+use crate::IdPolicy;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// How the cards won by a card are found in part 2
+    pub policy: IdPolicy,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day4 <file for part 1> <file for part 2> [--ids strict|positional]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut policy = IdPolicy::default();
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--ids" => policy = IdPolicy::try_from(iter.next().ok_or(USAGE)?.as_str()).map_err(|_| USAGE)?,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            policy,
         })
     }
 }
-
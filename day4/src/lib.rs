@@ -1,122 +1,453 @@
 pub mod config;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::bitvec::BitVec;
+use aoc_core::validate::Issue;
+use aoc_core::parse::{self, ParseError, Parser};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs;
 
+const CARDS_OVERFLOW: &str = "Number of cards overflow";
+
+/// Numbers of instances of cards, which may be expected values
+trait Copies: Copy {
+    const ONE: Self;
+
+    /// `self + instances · gain`, or `None` if it overflows
+    fn add_product(self, instances: Self, gain: Self) -> Option<Self>;
+}
+
+impl Copies for usize {
+    const ONE: Self = 1;
+
+    fn add_product(self, instances: Self, gain: Self) -> Option<Self> {
+        instances.checked_mul(gain).and_then(|copies| self.checked_add(copies))
+    }
+}
+
+impl Copies for f64 {
+    const ONE: Self = 1.0;
+
+    fn add_product(self, instances: Self, gain: Self) -> Option<Self> {
+        Some(self + instances * gain)
+    }
+}
+
 #[derive(Debug)]
-struct Card {
+pub struct Card {
     id: u32,
-    winning_numbers: Vec<i32>,
-    own_numbers: Vec<i32>,
+    winning: Vec<i32>,
+    own: Vec<i32>,
+    num_winning_numbers: usize, // number of distinct own numbers that are winning numbers
 }
 
 impl Card {
-    pub fn from_line(line: &str, line_nr: usize) -> Result<Self, String> {
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(
-                r"^Card +(?<id>[0-9]+): (?<winning_numbers>[0-9 ]+) \| (?<own_numbers>[0-9 ]+)$"
-                ).unwrap());
-        let Some(result) = RE.captures(line) else {
-            return Err(format!("Syntax error on line {line_nr}"));
-        };
-        let id: u32 = result["id"].parse().map_err(|err| format!("Could not parse ID on line {line_nr}: {err}"))?;
-        let winning_numbers = Self::parse_whitespace_separated_numbers(&result["winning_numbers"])
-            .map_err(|err| format!("Could not parse winning numbers on line {line_nr}: {err}"))?;
-        let own_numbers = Self::parse_whitespace_separated_numbers(&result["own_numbers"])
-            .map_err(|err| format!("Could not parse own numbers on line {line_nr}: {err}"))?;
-        Ok(Card {id, winning_numbers, own_numbers})
+    fn from_line(line: &str, line_nr: usize) -> Result<Self, String> {
+        Self::parse(line).map_err(|e| e.on_line(line_nr))
+    }
+
+    /// Reads a line like "Card 1: 41 48 | 83 86 17".
+    fn parse(line: &str) -> Result<Self, ParseError> {
+        parse::parse_all(line, |parser| {
+            parser.tag("Card")?;
+            parser.spaces();
+            let id = parser.number()?;
+            parser.tag(":")?;
+            let winning = Self::parse_whitespace_separated_numbers(parser)?;
+            parser.tag("|")?;
+            let own = Self::parse_whitespace_separated_numbers(parser)?;
+            let num_winning_numbers = Self::count_common(&winning, &own);
+            Ok(Card { id, winning, own, num_winning_numbers })
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The numbers before the `|` in the order of the input
+    pub fn winning_numbers(&self) -> &[i32] {
+        &self.winning
+    }
+
+    /// The numbers after the `|` in the order of the input
+    pub fn own_numbers(&self) -> &[i32] {
+        &self.own
     }
 
     fn num_winning_numbers(&self) -> usize {
-        let winning_set: HashSet<i32> = self.winning_numbers.iter().cloned().collect();
-        let own_set: HashSet<i32> = self.own_numbers.iter().cloned().collect();
-        own_set.intersection(&winning_set).count()
+        self.num_winning_numbers
     }
 
-    fn points(&self) -> i32 {
-        let n = self.num_winning_numbers();
-        if n > 0 {
-            1 << (n - 1)
-        } else {
-            0
+    /// Counts the distinct numbers occurring in both `a` and `b`. Uses bit sets if all numbers are
+    /// small, which is the case for all puzzle inputs, and intersects sorted copies otherwise.
+    fn count_common(a: &[i32], b: &[i32]) -> usize {
+        let to_bits = |numbers: &[i32]| numbers.iter()
+            .try_fold(0u128, |bits, &n| (0..128).contains(&n).then(|| bits | 1 << n));
+        if let (Some(a), Some(b)) = (to_bits(a), to_bits(b)) {
+            return (a & b).count_ones() as usize;
+        }
+        let mut a = a.to_vec();
+        a.sort_unstable();
+        a.dedup();
+        let mut b = b.to_vec();
+        b.sort_unstable();
+        b.dedup();
+        let (mut i, mut j, mut count) = (0, 0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    count += 1;
+                    i += 1;
+                    j += 1;
+                },
+            }
         }
+        count
     }
 
-    fn parse_whitespace_separated_numbers(string: &str) -> Result<Vec<i32>, String> {
+    /// Points of the card, or `None` if they do not fit into 64 bits
+    fn points(&self) -> Option<u64> {
+        match self.num_winning_numbers() {
+            0 => Some(0),
+            n => 1u64.checked_shl(u32::try_from(n - 1).ok()?),
+        }
+    }
+
+    fn parse_whitespace_separated_numbers(parser: &mut Parser) -> Result<Vec<i32>, ParseError> {
         let mut numbers = vec![];
-        for s in string.split(' ').map(|s| s.trim()).filter(|s| !s.is_empty()) {
-            if let Ok(number) = s.parse::<i32>() {
-                numbers.push(number);
-            } else {
-                return Err(format!("Could not parse number '{s}'"));
-            }
+        parser.spaces();
+        while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+            numbers.push(parser.number()?);
+            parser.spaces();
         }
         Ok(numbers)
     }
 }
 
+/// How the cards whose copies a card wins are found
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdPolicy {
+    /// Require the ID of each card to be one more than that of the previous card, so that the
+    /// cards following a card in the pile are those with the next IDs, and fail otherwise
+    #[default]
+    Strict,
+    /// Ignore the IDs, so that a card with n winning numbers wins copies of the n cards following
+    /// it in the pile, whatever their IDs
+    Positional,
+}
+
+impl TryFrom<&str> for IdPolicy {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "strict" => Ok(IdPolicy::Strict),
+            "positional" => Ok(IdPolicy::Positional),
+            _ => Err("Expected strict or positional"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Pile {
     cards: Vec<Card>,
 }
 
 impl Pile {
-    fn from_input(input: &str) -> Result<Self, String> {
+    pub fn from_input(input: &str) -> Result<Self, String> {
         let mut cards = vec![];
         for (i, line) in input.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).enumerate() {
-            let card = Card::from_line(line, i)?;
+            let card = Card::from_line(line, i + 1)?;
             cards.push(card);
         }
         Ok(Pile {cards})
     }
 
-    fn points(&self) -> i32 {
-        let mut sum = 0;
-        for card in &self.cards {
-            sum += card.points()
+    /// Reads the pile and checks that its IDs can be used for winning copies as required by
+    /// `policy`.
+    fn from_input_with_policy(input: &str, policy: IdPolicy) -> Result<Self, String> {
+        let pile = Self::from_input(input)?;
+        pile.check_ids(policy)?;
+        Ok(pile)
+    }
+
+    /// Checks that the IDs can be used for winning copies as required by `policy`.
+    fn check_ids(&self, policy: IdPolicy) -> Result<(), String> {
+        if policy == IdPolicy::Strict {
+            for (i, pair) in self.cards.windows(2).enumerate() {
+                if pair[0].id.checked_add(1) != Some(pair[1].id) {
+                    return Err(format!(
+                        "Card {} (card {} of the pile) follows card {}, but IDs must increase by one",
+                        pair[1].id, i + 2, pair[0].id,
+                    ));
+                }
+            }
         }
-        sum
+        Ok(())
+    }
+
+    /// The cards in the order of the input
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Precomputes the bit sets for querying the winning numbers of the cards.
+    pub fn winning_sets(&self) -> WinningSets {
+        let mut numbers: Vec<i32> = self.cards.iter()
+            .flat_map(|card| card.winning.iter().chain(&card.own).copied())
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        let to_bits = |card_numbers: &[i32]| {
+            let mut bits = BitVec::new(numbers.len());
+            for i in card_numbers.iter().filter_map(|n| numbers.binary_search(n).ok()) {
+                bits.set(i, true);
+            }
+            bits
+        };
+        let winning: Vec<BitVec> = self.cards.iter().map(|card| to_bits(&card.winning)).collect();
+        let matches = self.cards.iter().zip(&winning)
+            .map(|(card, winning)| {
+                let mut matches = to_bits(&card.own);
+                matches.and_assign(winning);
+                matches
+            })
+            .collect();
+        WinningSets { ids: self.cards.iter().map(|card| card.id).collect(), winning, matches, numbers }
     }
 
-    fn num_cards_after_copying(&self) -> usize {
-        let mut num_cards_with_id = self.cards.iter().map(|card| (card.id, 1)).collect::<HashMap<u32, usize>>();
-        let mut num_cards_total = self.cards.len();
+    fn points(&self) -> Result<u64, &'static str> {
+        let mut sum: u64 = 0;
         for card in &self.cards {
-            let num_cards_with_this_id = num_cards_with_id.get(&card.id).unwrap().to_owned();
-            let num_winning_numbers = u32::try_from(card.num_winning_numbers()).unwrap();
-            for i in card.id + 1 .. card.id + 1 + num_winning_numbers {
-                if let Some(num_cards) = num_cards_with_id.get_mut(&i) {
-                    *num_cards += num_cards_with_this_id;
-                    num_cards_total += num_cards_with_this_id;
+            sum = card.points().and_then(|points| sum.checked_add(points)).ok_or("Points overflow")?;
+        }
+        Ok(sum)
+    }
+
+    fn num_cards_after_copying(&self) -> Result<usize, &'static str> {
+        self.propagate(|card| vec![1; card.num_winning_numbers()])?.into_iter()
+            .try_fold(0usize, |sum, n| sum.checked_add(n))
+            .ok_or(CARDS_OVERFLOW)
+    }
+
+    /// Wins copies in the order of the cards, where each instance of a card wins `gains(card)[d - 1]`
+    /// copies of the card `d` positions later in the pile. Returns the number of instances of each
+    /// card by position, or an error if a number of instances overflows. The IDs are not used;
+    /// `from_input_with_policy` checks whether they agree with the positions.
+    fn propagate<T: Copies>(&self, gains: impl Fn(&Card) -> Vec<T>) -> Result<Vec<T>, &'static str> {
+        let mut instances = vec![T::ONE; self.cards.len()];
+        for (i, card) in self.cards.iter().enumerate() {
+            let num_instances = instances[i];
+            for (num_copies, gain) in instances[i + 1..].iter_mut().zip(gains(card)) {
+                *num_copies = num_copies.add_product(num_instances, gain).ok_or(CARDS_OVERFLOW)?;
+            }
+        }
+        Ok(instances)
+    }
+
+    /// Plays the cards by creating every copy explicitly, recording for each instance the original
+    /// card whose chain of wins produced it. The instances are returned grouped by card, in the
+    /// order of the cards.
+    fn simulate_copying(&self) -> Vec<CardInstance> {
+        // Origins of the instances of each card
+        let mut origins: Vec<Vec<u32>> = self.cards.iter().map(|card| vec![card.id]).collect();
+        for (i, card) in self.cards.iter().enumerate() {
+            let end = (i + 1 + card.num_winning_numbers()).min(self.cards.len());
+            for j in i + 1..end {
+                let new_origins = origins[i].clone();
+                origins[j].extend(new_origins);
+            }
+        }
+        self.cards.iter().zip(origins)
+            .flat_map(|(card, origins)| origins.into_iter().map(|origin| CardInstance { card_id: card.id, origin }))
+            .collect()
+    }
+}
+
+/// The winning numbers of the cards of a pile as bit sets over all numbers occurring in the pile.
+/// A number is winning on a card if it is both among the winning numbers and the own numbers of
+/// the card.
+#[derive(Clone, Debug)]
+pub struct WinningSets {
+    /// The distinct numbers of the pile in increasing order, indexing the bits of the sets
+    numbers: Vec<i32>,
+    /// Card IDs by position in the pile
+    ids: Vec<u32>,
+    /// Numbers before the `|` of each card
+    winning: Vec<BitVec>,
+    /// Numbers winning on each card
+    matches: Vec<BitVec>,
+}
+
+impl WinningSets {
+    /// Returns the numbers winning on more than `k` cards in increasing order.
+    pub fn numbers_winning_on_more_than(&self, k: usize) -> Vec<i32> {
+        let mut counts = vec![0; self.numbers.len()];
+        for matches in &self.matches {
+            for i in matches.ones() {
+                counts[i] += 1;
+            }
+        }
+        counts.iter().zip(&self.numbers)
+            .filter(|(&count, _)| count > k)
+            .map(|(_, &number)| number)
+            .collect()
+    }
+
+    /// Returns the IDs of the pairs of cards having at least `n` numbers before the `|` in common,
+    /// in the order of the pile.
+    pub fn cards_sharing_at_least(&self, n: usize) -> Vec<(u32, u32)> {
+        let mut pairs = Vec::new();
+        for (i, a) in self.winning.iter().enumerate() {
+            for (j, b) in self.winning.iter().enumerate().skip(i + 1) {
+                if a.count_common_ones(b) >= n {
+                    pairs.push((self.ids[i], self.ids[j]));
                 }
             }
         }
-        num_cards_total
+        pairs
     }
 }
 
- fn part1(input: &str) -> Result<i32, Box<dyn Error>> {
-    let pile = Pile::from_input(input)?;
-    Ok(pile.points())
+/// How copies of cards are computed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Copying {
+    /// Only count the copies of each card
+    #[default]
+    Counting,
+    /// Create every copy and track where it came from
+    Tracking,
+}
+
+/// A card in the final pile, either an original or a copy
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CardInstance {
+    pub card_id: u32,
+    /// ID of the original card whose chain of wins produced this instance, which is the ID of the
+    /// card itself for original cards
+    pub origin: u32,
 }
 
-fn part2(input: &str) -> Result<i32, Box<dyn Error>> {
+/// The pile after all copies have been won
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    pub num_cards: usize,
+    /// Every card of the final pile, if tracked
+    pub instances: Option<Vec<CardInstance>>,
+}
+
+impl Outcome {
+    /// Number of instances produced by each original card, if tracked
+    pub fn provenance_counts(&self) -> Option<BTreeMap<u32, usize>> {
+        let instances = self.instances.as_ref()?;
+        let mut counts = BTreeMap::new();
+        for instance in instances {
+            *counts.entry(instance.origin).or_default() += 1;
+        }
+        Some(counts)
+    }
+}
+
+/// Wins copies of cards as in part 2, computing them as specified by `copying` and finding the
+/// cards won as specified by `policy`. With positional copying, the instances of cards sharing an
+/// ID are counted together by `Outcome::provenance_counts`.
+pub fn play(input: &str, copying: Copying, policy: IdPolicy) -> Result<Outcome, Box<dyn Error>> {
+    let pile = Pile::from_input_with_policy(input, policy)?;
+    Ok(match copying {
+        Copying::Counting => Outcome { num_cards: pile.num_cards_after_copying()?, instances: None },
+        Copying::Tracking => {
+            let instances = pile.simulate_copying();
+            Outcome { num_cards: instances.len(), instances: Some(instances) }
+        },
+    })
+}
+
+/// Expected number of cards after winning copies as in part 2 if the number of winning numbers of
+/// each card were drawn independently from `distribution`, where `distribution[k]` is the
+/// probability of k winning numbers. Only the number of cards in the input is used.
+pub fn expected_num_cards(input: &str, distribution: &[f64]) -> Result<f64, Box<dyn Error>> {
+    if distribution.iter().any(|&p| !(0.0..=1.0).contains(&p)) || (distribution.iter().sum::<f64>() - 1.0).abs() > 1e-9 {
+        return Err("Probabilities must be between 0 and 1 and sum to 1".into());
+    }
+    // The number of instances of a card only depends on the draws of the cards before it, so it is
+    // independent of its own draw, and each instance wins a copy of the card d positions later with
+    // probability P(at least d winning numbers).
+    let mut at_least: Vec<f64> = distribution.iter().rev()
+        .scan(0.0, |tail, &p| { *tail += p; Some(*tail) })
+        .collect();
+    at_least.reverse();
+    let gains = at_least.get(1..).unwrap_or_default().to_vec();
     let pile = Pile::from_input(input)?;
-    Ok(i32::try_from(pile.num_cards_after_copying())?)
+    Ok(pile.propagate(|_| gains.clone())?.into_iter().sum())
+}
+
+/// Both parts, solved from the same pile
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Pile;
+
+    fn parse(input: &str) -> Result<Pile, Box<dyn Error>> {
+        Ok(Pile::from_input(input)?)
+    }
+
+    fn part1(pile: &Pile) -> Result<Answer, Box<dyn Error>> {
+        Ok(pile.points()?.into())
+    }
+
+    fn part2(pile: &Pile) -> Result<Answer, Box<dyn Error>> {
+        part2(pile, IdPolicy::default())
+    }
+}
+
+fn part2(pile: &Pile, policy: IdPolicy) -> Result<Answer, Box<dyn Error>> {
+    pile.check_ids(policy)?;
+    Ok(u64::try_from(pile.num_cards_after_copying()?)?.into())
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as cards and cards whose ID does not follow that of the
+/// previous card.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut previous_id: u32 = 0;
+    for (i, line) in input.lines().enumerate().map(|(i, line)| (i, line.trim())).filter(|(_, line)| !line.is_empty()) {
+        match Card::parse(line) {
+            Ok(card) => {
+                if previous_id.checked_add(1) != Some(card.id) {
+                    issues.push(Issue::at_line(i + 1, format!("Card {} follows card {previous_id}", card.id)));
+                }
+                previous_id = card.id;
+            },
+            Err(e) => issues.push(Issue::at(i + 1, e.column, e.message)),
+        }
+    }
+    issues
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let pile = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    println!("Result of part 1: {}", Day::part1(&pile)?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let pile = answer::parse_part2(pile, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", part2(&pile, config.policy)?);
 
     Ok(())
 }
@@ -125,6 +456,8 @@ pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    use aoc_core::rng::{Rng, SeededRng};
+
     const EXAMPLE: &str = "
         Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
         Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
@@ -134,17 +467,172 @@ mod tests {
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
     ";
 
+    #[test]
+    fn validate_example() {
+        assert!(validate(EXAMPLE).is_empty());
+        let issues = validate("Card 1: 1 2 | 3 4\nCard 3: 1 2 | 3 4\nCard 4: 1 2 3 4");
+        assert_eq!(issues, vec![
+            Issue::at_line(2, "Card 3 follows card 1"),
+            Issue::at(3, 16, "Expected '|'"),
+        ]);
+        // No card can follow the largest ID
+        assert_eq!(validate("Card 4294967295: 1 | 1\nCard 1: 1 | 1"), vec![
+            Issue::at_line(1, "Card 4294967295 follows card 0"),
+            Issue::at_line(2, "Card 1 follows card 4294967295"),
+        ]);
+    }
+
     #[test]
     fn example_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE)?;
+        let result = solve_part1(EXAMPLE)?;
         assert_eq!(result, 13);
         Ok(())
     }
 
+    #[test]
+    fn points_beyond_32_bits() -> Result<(), Box<dyn Error>> {
+        let card = |n: usize| {
+            let numbers = (1..=n).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+            format!("Card 1: {numbers} | {numbers}")
+        };
+        assert_eq!(solve_part1(&card(40))?, 1u64 << 39);
+        assert_eq!(solve_part1(&card(64))?, 1u64 << 63);
+        assert!(solve_part1(&card(65)).is_err());
+        assert!(solve_part1(&format!("{}\nCard 2: 1 | 1", card(64))).is_ok());
+        assert!(solve_part1(&format!("{}\n{}", card(64), card(64).replace("Card 1", "Card 2"))).is_err());
+        Ok(())
+    }
+
     #[test]
     fn example_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE)?;
+        let result = part2(&Pile::from_input(EXAMPLE)?, IdPolicy::Strict)?;
         assert_eq!(result, 30);
         Ok(())
     }
+
+    #[test]
+    fn non_contiguous_ids() -> Result<(), Box<dyn Error>> {
+        // The example with its cards renumbered and shuffled: each card still wins copies of the
+        // cards following it in the pile
+        let ids = [1, 20, 50, 4, 3, 6];
+        let renumbered: String = EXAMPLE.trim().lines().zip(ids)
+            .map(|(line, id)| format!("Card {id}:{}\n", line.split_once(':').unwrap().1))
+            .collect();
+        let error = part2(&Pile::from_input(&renumbered)?, IdPolicy::Strict).unwrap_err().to_string();
+        assert_eq!(error, "Card 20 (card 2 of the pile) follows card 1, but IDs must increase by one");
+        assert_eq!(part2(&Pile::from_input(&renumbered)?, IdPolicy::Positional)?, 30);
+        let outcome = play(&renumbered, Copying::Tracking, IdPolicy::Positional)?;
+        let counts = outcome.provenance_counts().ok_or("Not tracked")?;
+        assert_eq!(counts, BTreeMap::from([(1, 15), (20, 7), (50, 4), (4, 2), (3, 1), (6, 1)]));
+
+        // Duplicate and decreasing IDs are rejected, but win copies by position otherwise
+        let input = "Card 2: 1 2 | 1 2\nCard 2: 1 | 1\nCard 1: 1 | 2";
+        assert!(part2(&Pile::from_input(input)?, IdPolicy::Strict).is_err());
+        assert_eq!(part2(&Pile::from_input(input)?, IdPolicy::Positional)?, 1 + 2 + 4);
+        // Cards at the end of the pile win nothing beyond it
+        assert_eq!(part2(&Pile::from_input("Card 7: 1 2 3 | 1 2 3\nCard 9: 1 | 2")?, IdPolicy::Positional)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn number_of_cards_overflow() {
+        // Each card wins a copy of each of the next ten, so the numbers of instances grow
+        // exponentially
+        let numbers = (1..=10).map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let input: String = (1..=100).map(|i| format!("Card {i}: {numbers} | {numbers}\n")).collect();
+        assert_eq!(solve_part2(&input).unwrap_err().to_string(), "Number of cards overflow");
+        assert!(play(&input, Copying::Counting, IdPolicy::Strict).is_err());
+    }
+
+    #[test]
+    fn example_provenance() -> Result<(), Box<dyn Error>> {
+        let outcome = play(EXAMPLE, Copying::Tracking, IdPolicy::Strict)?;
+        assert_eq!(outcome.num_cards, 30);
+        let counts = outcome.provenance_counts().ok_or("Not tracked")?;
+        assert_eq!(counts, BTreeMap::from([(1, 15), (2, 7), (3, 4), (4, 2), (5, 1), (6, 1)]));
+        let instances = outcome.instances.ok_or("Not tracked")?;
+        assert_eq!(instances.iter().filter(|instance| instance.card_id == 4).count(), 8);
+        assert!(instances.iter().all(|instance| instance.origin <= instance.card_id));
+        let outcome = play(EXAMPLE, Copying::Counting, IdPolicy::Strict)?;
+        assert_eq!((outcome.num_cards, outcome.provenance_counts()), (30, None));
+        Ok(())
+    }
+
+    #[test]
+    fn winning_set_queries() -> Result<(), Box<dyn Error>> {
+        let pile = Pile::from_input(EXAMPLE)?;
+        let card = &pile.cards()[0];
+        assert_eq!(card.id(), 1);
+        assert_eq!(card.winning_numbers(), [41, 48, 83, 86, 17]);
+        assert_eq!(card.own_numbers().len(), 8);
+        let sets = pile.winning_sets();
+        // Card 1 wins with 17, 48, 83 and 86, card 2 with 32 and 61, card 3 with 1 and 21 and
+        // card 4 with 84
+        assert_eq!(sets.numbers_winning_on_more_than(0), vec![1, 17, 21, 32, 48, 61, 83, 84, 86]);
+        assert!(sets.numbers_winning_on_more_than(1).is_empty());
+        // Cards 1 and 4 share 41, cards 1 and 5 share 83, cards 2 and 5 share 32 and cards 2 and
+        // 6 share 13
+        assert_eq!(sets.cards_sharing_at_least(1), vec![(1, 4), (1, 5), (2, 5), (2, 6)]);
+        assert!(sets.cards_sharing_at_least(2).is_empty());
+        assert_eq!(sets.cards_sharing_at_least(0).len(), 15);
+        let sets = Pile::from_input("Card 1: 5 7 | 5 7
+Card 2: 7 1000000 | 7
+Card 3: 1000000 7 | 1")?.winning_sets();
+        assert_eq!(sets.numbers_winning_on_more_than(1), vec![7]);
+        assert_eq!(sets.cards_sharing_at_least(2), vec![(2, 3)]);
+        Ok(())
+    }
+
+    #[test]
+    fn expected_cards() -> Result<(), Box<dyn Error>> {
+        // Every card wins exactly one copy of the next card, so card n has n instances
+        assert_eq!(expected_num_cards(EXAMPLE, &[0.0, 1.0])?, 21.0);
+        // No card wins anything
+        assert_eq!(expected_num_cards(EXAMPLE, &[1.0])?, 6.0);
+        // Card 2 has 1.5 instances on average and card 3 has 1 + 0.5 · 1.5
+        assert_eq!(expected_num_cards("Card 1: 1 | 2\nCard 2: 1 | 2\nCard 3: 1 | 2", &[0.5, 0.5])?, 1.0 + 1.5 + 1.75);
+        // Two wins with probability 1/2 also give card 3 half a copy from card 1
+        assert_eq!(expected_num_cards("Card 1: 1 | 2\nCard 2: 1 | 2\nCard 3: 1 | 2", &[0.5, 0.0, 0.5])?, 1.0 + 1.5 + 2.25);
+        assert!(expected_num_cards(EXAMPLE, &[0.5, 0.6]).is_err());
+        assert!(expected_num_cards(EXAMPLE, &[1.5, -0.5]).is_err());
+        Ok(())
+    }
+
+    fn count_common_with_hash_sets(a: &[i32], b: &[i32]) -> usize {
+        let a: std::collections::HashSet<i32> = a.iter().cloned().collect();
+        let b: std::collections::HashSet<i32> = b.iter().cloned().collect();
+        a.intersection(&b).count()
+    }
+
+    /// Generates `n` pairs of winning and own numbers with 10 and 90 numbers, respectively, below
+    /// `max`.
+    fn random_cards(n: usize, max: i32) -> Vec<(Vec<i32>, Vec<i32>)> {
+        let mut rng = SeededRng::new(42);
+        let mut random = move || rng.below(max as u64) as i32;
+        (0..n).map(|_| ((0..10).map(|_| random()).collect(), (0..90).map(|_| random()).collect())).collect()
+    }
+
+    #[test]
+    fn count_common_matches_hash_sets() {
+        for (winning, own) in random_cards(1000, 100).into_iter().chain(random_cards(1000, 1000)) {
+            assert_eq!(Card::count_common(&winning, &own), count_common_with_hash_sets(&winning, &own));
+        }
+        assert_eq!(Card::count_common(&[], &[1, 2]), 0);
+        assert_eq!(Card::count_common(&[1, 1, 2], &[1, 1]), 1);
+    }
+
+    /// Compares counting winning numbers with hash sets and bit sets for 1M cards with 100
+    /// numbers each. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_count_common() {
+        let cards = random_cards(1_000_000, 100);
+        let start = std::time::Instant::now();
+        let hash_sets: usize = cards.iter().map(|(w, o)| count_common_with_hash_sets(w, o)).sum();
+        println!("Hash sets: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let bit_sets: usize = cards.iter().map(|(w, o)| Card::count_common(w, o)).sum();
+        println!("Bit sets: {:?}", start.elapsed());
+        assert_eq!(hash_sets, bit_sets);
+    }
 }
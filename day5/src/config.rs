@@ -1,17 +1,46 @@
+/// Format in which the splitting of the seed ranges of part 2 is printed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SplitFormat {
+    Text,
+    Json,
+}
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print how the seed ranges of part 2 are split by the maps
+    pub splits: Option<SplitFormat>,
+    /// Compare the two files as almanacs instead of solving the puzzle
+    pub diff: bool,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day5 <file for part 1> <file for part 2> [--splits text|json]\n       day5 diff <old file> <new file>";
+        if let [_, command, file_path1, file_path2] = args {
+            if command == "diff" {
+                return Ok(Config {
+                    file_path1: file_path1.clone(),
+                    file_path2: file_path2.clone(),
+                    splits: None,
+                    diff: true,
+                });
+            }
         }
+        let splits = match args.len() {
+            3 => None,
+            5 if args[3] == "--splits" => match args[4].as_str() {
+                "text" => Some(SplitFormat::Text),
+                "json" => Some(SplitFormat::Json),
+                _ => return Err(USAGE),
+            },
+            _ => return Err(USAGE),
+        };
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            splits,
+            diff: false,
         })
     }
 }
-
@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{Map, Puzzle};
+
+/// A range of values on which two maps between the same types disagree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MappingChange {
+    pub from_type: String,
+    pub to_type: String,
+    /// First value of the range
+    pub start: u64,
+    /// First value after the range
+    pub end: u64,
+    /// Where the first value of the range is mapped to by the old and by the new map
+    pub old_dest: u64,
+    pub new_dest: u64,
+}
+
+impl fmt::Display for MappingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.end - self.start;
+        write!(f, "{}-to-{} map: [{}, {}) changed from [{}, {}) to [{}, {})", self.from_type, self.to_type,
+            self.start, self.end, self.old_dest, self.old_dest + len, self.new_dest, self.new_dest + len)
+    }
+}
+
+/// A seed whose location differs between two almanacs
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LocationChange {
+    pub seed: u64,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl fmt::Display for LocationChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Seed {}: location changed from {} to {}", self.seed, self.old, self.new)
+    }
+}
+
+/// The differences between an old and a new almanac
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AlmanacDiff {
+    /// Maps, as pairs of their types, that only the old or only the new almanac has
+    pub removed_maps: Vec<(String, String)>,
+    pub added_maps: Vec<(String, String)>,
+    /// Ranges mapped differently by maps that both almanacs have, by map and then by range
+    pub mapping_changes: Vec<MappingChange>,
+    /// Seeds of either almanac, read as single seeds as in part 1, whose location differs, by seed
+    pub location_changes: Vec<LocationChange>,
+}
+
+impl AlmanacDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed_maps.is_empty() && self.added_maps.is_empty()
+            && self.mapping_changes.is_empty() && self.location_changes.is_empty()
+    }
+}
+
+impl fmt::Display for AlmanacDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for (from_type, to_type) in &self.removed_maps {
+            writeln!(f, "{from_type}-to-{to_type} map removed")?;
+        }
+        for (from_type, to_type) in &self.added_maps {
+            writeln!(f, "{from_type}-to-{to_type} map added")?;
+        }
+        for change in &self.mapping_changes {
+            writeln!(f, "{change}")?;
+        }
+        for change in &self.location_changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The maps of `maps` that are not in `others`, as pairs of their types. Of several maps between
+/// the same types, only the first one counts, as when chaining maps.
+fn missing_maps(maps: &[Map], others: &[Map]) -> Vec<(String, String)> {
+    let mut missing: Vec<(String, String)> = Vec::new();
+    for map in maps {
+        let types = (map.from_type.to_owned(), map.to_type.to_owned());
+        if !others.iter().any(|other| (other.from_type, other.to_type) == (map.from_type, map.to_type))
+            && !missing.contains(&types) {
+            missing.push(types);
+        }
+    }
+    missing
+}
+
+/// Splits the values at the boundaries of the entries of both maps and returns the pieces on which
+/// the maps differ, joining adjacent pieces that both maps send to adjacent ranges.
+fn differing_ranges(old: &Map, new: &Map) -> Vec<MappingChange> {
+    let mut boundaries: Vec<u64> = old.entries.iter().chain(&new.entries).flat_map(|entry| [entry.a, entry.b]).collect();
+    boundaries.push(0);
+    boundaries.sort();
+    boundaries.dedup();
+    let mut changes: Vec<MappingChange> = Vec::new();
+    // Beyond the last boundary, both maps map values to themselves
+    for piece in boundaries.windows(2) {
+        let (start, end) = (piece[0], piece[1]);
+        let (old_dest, new_dest) = (old.apply(start), new.apply(start));
+        if old_dest == new_dest {
+            continue;
+        }
+        match changes.last_mut() {
+            Some(last) if last.end == start
+                && last.old_dest + (start - last.start) == old_dest
+                && last.new_dest + (start - last.start) == new_dest => last.end = end,
+            _ => changes.push(MappingChange {
+                from_type: old.from_type.to_owned(),
+                to_type: old.to_type.to_owned(),
+                start,
+                end,
+                old_dest,
+                new_dest,
+            }),
+        }
+    }
+    changes
+}
+
+/// The location of each of the seeds according to the chain of maps from seeds to locations.
+fn locations(puzzle: &Puzzle, seeds: &[u64]) -> Result<Vec<u64>, String> {
+    let mut puzzle = Puzzle { seeds: Vec::new(), maps: puzzle.maps.clone() };
+    puzzle.chain_maps()?;
+    puzzle.compress();
+    Ok(seeds.iter().map(|&seed| puzzle.maps.iter().fold(seed, |value, map| map.apply(value))).collect())
+}
+
+/// Compares two almanacs: which maps only one of them has, on which values the maps they share
+/// differ, and which seeds end up at a different location. Fails if either almanac cannot be read
+/// or has no chain of maps from seeds to locations.
+pub fn diff_almanacs(old_input: &str, new_input: &str) -> Result<AlmanacDiff, Box<dyn Error>> {
+    let old = Puzzle::from_input(old_input).map_err(|e| format!("Old almanac: {e}"))?;
+    let new = Puzzle::from_input(new_input).map_err(|e| format!("New almanac: {e}"))?;
+
+    let mut mapping_changes = Vec::new();
+    for (i, old_map) in old.maps.iter().enumerate() {
+        let is_first = !old.maps[..i].iter().any(|map| (map.from_type, map.to_type) == (old_map.from_type, old_map.to_type));
+        let new_map = new.maps.iter().find(|map| (map.from_type, map.to_type) == (old_map.from_type, old_map.to_type));
+        if let (true, Some(new_map)) = (is_first, new_map) {
+            mapping_changes.extend(differing_ranges(old_map, new_map));
+        }
+    }
+
+    let mut seeds: Vec<u64> = old.seeds.iter().chain(&new.seeds).copied().collect();
+    seeds.sort();
+    seeds.dedup();
+    let old_locations = locations(&old, &seeds).map_err(|e| format!("Old almanac: {e}"))?;
+    let new_locations = locations(&new, &seeds).map_err(|e| format!("New almanac: {e}"))?;
+    let location_changes = seeds.into_iter().zip(old_locations.into_iter().zip(new_locations))
+        .filter(|(_, (old, new))| old != new)
+        .map(|(seed, (old, new))| LocationChange { seed, old, new })
+        .collect();
+
+    Ok(AlmanacDiff {
+        removed_maps: missing_maps(&old.maps, &new.maps),
+        added_maps: missing_maps(&new.maps, &old.maps),
+        mapping_changes,
+        location_changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "
+seeds: 1 4 5 10
+
+seed-to-soil map:
+10 0 5
+
+seed-to-fertilizer map:
+0 0 1
+
+soil-to-location map:
+0 10 3
+";
+
+    const NEW: &str = "
+seeds: 1 4 5 10
+
+seed-to-soil map:
+10 0 3
+20 3 2
+
+soil-to-location map:
+0 10 3
+
+soil-to-water map:
+5 5 5
+";
+
+    #[test]
+    fn changed_almanac() -> Result<(), Box<dyn Error>> {
+        let diff = diff_almanacs(OLD.trim(), NEW.trim())?;
+        assert_eq!(diff.removed_maps, vec![("seed".to_string(), "fertilizer".to_string())]);
+        assert_eq!(diff.added_maps, vec![("soil".to_string(), "water".to_string())]);
+        assert_eq!(diff.mapping_changes, vec![MappingChange {
+            from_type: "seed".to_string(), to_type: "soil".to_string(), start: 3, end: 5, old_dest: 13, new_dest: 20,
+        }]);
+        assert_eq!(diff.location_changes, vec![LocationChange { seed: 4, old: 14, new: 21 }]);
+        assert_eq!(diff.to_string(), "\
+seed-to-fertilizer map removed
+soil-to-water map added
+seed-to-soil map: [3, 5) changed from [13, 15) to [20, 22)
+Seed 4: location changed from 14 to 21
+");
+        Ok(())
+    }
+
+    #[test]
+    fn equal_maps_with_different_entries() -> Result<(), Box<dyn Error>> {
+        let split = OLD.replace("10 0 5", "10 0 3\n13 3 2");
+        let diff = diff_almanacs(OLD.trim(), split.trim())?;
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+        let shifted = OLD.replace("10 0 5", "11 0 5");
+        let diff = diff_almanacs(OLD.trim(), shifted.trim())?;
+        assert_eq!(diff.mapping_changes.len(), 1);
+        assert_eq!((diff.mapping_changes[0].start, diff.mapping_changes[0].end), (0, 5));
+        Ok(())
+    }
+}
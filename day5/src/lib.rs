@@ -1,8 +1,19 @@
 pub mod config;
+mod diff;
 
 use std::error::Error;
 use std::{fs, fmt};
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::{parallel, search};
+use aoc_core::validate::Issue;
+
+pub use diff::{diff_almanacs, AlmanacDiff, LocationChange, MappingChange};
+
+/// Chains of at most this many maps are composed sequentially instead of being split up, which
+/// keeps the seven maps of a puzzle input on one thread
+const SEQUENTIAL_THRESHOLD: usize = 16;
+
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 struct IntervalMapping {
     a: u64, // start of interval (inclusive)
@@ -45,7 +56,7 @@ impl fmt::Display for IntervalMapping {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct Map<'a> {
     from_type: &'a str,
     to_type: &'a str,
@@ -78,19 +89,15 @@ impl<'a> Map<'a> {
     fn entries_from_iter_until_end_of_block<'b>(iter: &mut impl Iterator<Item = &'b str>) -> Result<Vec<IntervalMapping>, String> {
         let mut entries = vec![];
         // Read until empty line or EOF
-        loop {
-            if let Some(line) = iter.next() {
-                if line.is_empty() {
-                    break;
-                }
-                let entry = IntervalMapping::from_line(line)?;
-                if entries.iter().any(|e| entry.source_overlaps(e)) {
-                    return Err("Overlapping sources".into());
-                }
-                entries.push(entry);
-            } else {
-                break
+        for line in iter.by_ref() {
+            if line.is_empty() {
+                break;
             }
+            let entry = IntervalMapping::from_line(line)?;
+            if entries.iter().any(|e| entry.source_overlaps(e)) {
+                return Err("Overlapping sources".into());
+            }
+            entries.push(entry);
         }
         Ok(entries)
     }
@@ -151,6 +158,53 @@ impl<'a> Map<'a> {
         Map { from_type: self.from_type, to_type: other.to_type, entries }
     }
 
+    /// Composes a nonempty chain of maps, where the output type of each map is the input type of
+    /// the next one. Chains longer than `sequential_threshold` are split into chunks of that many
+    /// maps (but at least two), which are composed in parallel and then composed in turn, which is
+    /// possible because composition is associative.
+    fn combine_all(maps: &[Map<'a>], sequential_threshold: usize) -> Map<'a> {
+        if maps.len() <= sequential_threshold.max(1) {
+            return Self::combine_sequentially(maps);
+        }
+        let chunks: Vec<&[Map<'a>]> = maps.chunks(sequential_threshold.max(2)).collect();
+        let combined = parallel::map(&chunks, |chunk| Self::combine_sequentially(chunk));
+        Self::combine_all(&combined, sequential_threshold)
+    }
+
+    fn combine_sequentially(maps: &[Map<'a>]) -> Map<'a> {
+        let (last, rest) = maps.split_last().expect("Expected at least one map");
+        rest.iter().rev().fold(last.clone(), |x, next| next.combine(&x))
+    }
+
+    /// Splits the range [a, b) at the boundaries of the entries, on each of which the map is a
+    /// translation, and returns the pieces in order as their start, end and image of the start.
+    fn pieces(&self, a: u64, b: u64) -> Vec<IntervalMapping> {
+        let mut entries = self.entries.clone();
+        entries.sort();
+        let mut pieces = Vec::new();
+        let mut x = a;
+        for entry in entries.iter().filter(|entry| entry.b > a && entry.a < b) {
+            if entry.a > x {
+                // Values not covered by any entry are mapped to themselves
+                pieces.push(IntervalMapping { a: x, b: entry.a, dest: x });
+                x = entry.a;
+            }
+            let end = entry.b.min(b);
+            pieces.push(IntervalMapping { a: x, b: end, dest: entry.dest + (x - entry.a) });
+            x = end;
+        }
+        if x < b {
+            pieces.push(IntervalMapping { a: x, b, dest: x });
+        }
+        pieces
+    }
+
+    /// Splits the range [a, b) at the boundaries of the entries and returns the images of the
+    /// pieces in the order of the pieces.
+    fn split(&self, a: u64, b: u64) -> Vec<(u64, u64)> {
+        self.pieces(a, b).iter().map(|piece| (piece.dest, piece.dest + (piece.b - piece.a))).collect()
+    }
+
     fn apply(&self, x: u64) -> u64 {
         for entry in &self.entries {
             if let Some(y) = entry.apply(x) {
@@ -161,14 +215,15 @@ impl<'a> Map<'a> {
     }
 }
 
-#[derive(Debug)]
-struct Puzzle<'a> {
+/// An almanac with its seeds and maps
+#[derive(Debug, Eq, PartialEq)]
+pub struct Puzzle<'a> {
     seeds: Vec<u64>,
     maps: Vec<Map<'a>>,
 }
 
 impl<'a> Puzzle<'a> {
-    fn from_input(input: &'a str) -> Result<Self, Box<dyn Error>> {
+    pub fn from_input(input: &'a str) -> Result<Self, Box<dyn Error>> {
         let mut iter = input.lines();
 
         // Read seeds
@@ -195,21 +250,57 @@ impl<'a> Puzzle<'a> {
         Ok(Self { seeds, maps })
     }
 
-    fn compress(&mut self) {
-        if let Some(mut x) = self.maps.pop() {
-            while let Some(next) = self.maps.pop() {
-                x = next.combine(&x);
+    /// Writes the seeds and maps in the format of the puzzle input, from which `from_input` reads
+    /// the same almanac again.
+    pub fn to_input_string(&self) -> String {
+        let mut text = String::from("seeds:");
+        for seed in &self.seeds {
+            text.push_str(&format!(" {seed}"));
+        }
+        text.push('\n');
+        for map in &self.maps {
+            text.push_str(&format!("\n{}-to-{} map:\n", map.from_type, map.to_type));
+            for entry in &map.entries {
+                text.push_str(&format!("{} {} {}\n", entry.dest, entry.a, entry.b - entry.a));
             }
-            assert!(self.maps.is_empty());
-            self.maps.push(x);
+        }
+        text
+    }
+
+    /// Keeps only the maps that lead from seeds to locations, in the order in which they have to be
+    /// applied, regardless of their order in the input. If there are several such chains, the
+    /// shortest one is used, preferring maps that appear earlier in the input.
+    fn chain_maps(&mut self) -> Result<(), String> {
+        let successors = |value_type: &&'a str| {
+            self.maps.iter().filter(|map| map.from_type == *value_type).map(|map| map.to_type).collect::<Vec<_>>()
+        };
+        let search = search::bfs(["seed"], successors, |&value_type, _| value_type == "location");
+        let Some(path) = search.path_to(&"location") else {
+            let mut reachable: Vec<&str> = search.costs().map(|(&value_type, _)| value_type).collect();
+            reachable.sort();
+            return Err(format!("No chain of maps leads from seed to location; types reachable from seed: {}", reachable.join(", ")));
+        };
+        // Of several maps between the same types, the first one reached the type first
+        self.maps = path.windows(2)
+            .filter_map(|pair| self.maps.iter().find(|map| (map.from_type, map.to_type) == (pair[0], pair[1])).cloned())
+            .collect();
+        Ok(())
+    }
+
+    fn compress(&mut self) {
+        if !self.maps.is_empty() {
+            self.maps = vec![Map::combine_all(&self.maps, SEQUENTIAL_THRESHOLD)];
         }
     }
 
-    fn seeds_to_ranges(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Returns the seeds that can attain the minimum location when the seeds are read as ranges,
+    /// each with the index of its range.
+    fn range_candidates(&self) -> Result<Vec<(u64, usize)>, Box<dyn Error>> {
         let mut result = vec![];
-        let mut seed_iter = self.seeds.iter();
-        while let Some(&start) = seed_iter.next() {
-            let range_len = *seed_iter.next().ok_or("Expected range length")?;
+        for (i, range) in self.seeds.chunks(2).enumerate() {
+            let [start, range_len] = *range else {
+                return Err("Expected range length".into());
+            };
             // Abuse IntervalMapping with a dummy destination
             let interval = IntervalMapping { a: start, b: start + range_len, dest: 0 };
             assert!(self.maps.len() == 1);
@@ -217,20 +308,20 @@ impl<'a> Puzzle<'a> {
             // Add all source interval starts that lie within `interval` to the seeds
             for mapping in &map.entries {
                 if interval.contains(mapping.a) {
-                    result.push(mapping.a);
+                    result.push((mapping.a, i));
                 }
             }
-            result.push(interval.a);
+            result.push((interval.a, i));
         }
-        self.seeds = result;
-        Ok(())
+        Ok(result)
     }
 
-    fn min_for_seeds(&self) -> Result<u64, Box<dyn Error>> {
-        // Apply all maps in turn to the seeds and remember minimum of the results
-        let mut maybe_min: Option<u64> = None;
-        for seed in &self.seeds {
-            let mut value = *seed;
+    /// Applies all maps in turn to the seeds, each given with the index of its range if any, and
+    /// returns the smallest location. Among seeds with the same location, the smallest one wins.
+    fn min_for_seeds(&self, seeds: impl Iterator<Item = (u64, Option<usize>)>) -> Result<MinResult, Box<dyn Error>> {
+        let mut maybe_min: Option<MinResult> = None;
+        for (seed, range_index) in seeds {
+            let mut value = seed;
             let mut value_type = "seed";
             for map in &self.maps {
                 // Map must have the right type as input
@@ -240,38 +331,276 @@ impl<'a> Puzzle<'a> {
                 value_type = map.to_type;
                 value = map.apply(value);
             }
-            maybe_min = match maybe_min {
-                None => Some(value),
-                Some(m) => Some(m.min(value)),
+            let offset = range_index.map(|i| seed - self.seeds[2 * i]);
+            let candidate = MinResult { location: value, seed, range_index, offset };
+            if maybe_min.as_ref().is_none_or(|m| (value, seed) < (m.location, m.seed)) {
+                maybe_min = Some(candidate);
             }
         }
         Ok(maybe_min.ok_or("Expected at least one seed")?)
     }
 }
 
- fn part1(input: &str) -> Result<u64, Box<dyn Error>> {
-    let mut puzzle = Puzzle::from_input(input)?;
-    puzzle.compress();
-    Ok(puzzle.min_for_seeds()?)
+impl Puzzle<'_> {
+    /// Reads the almanac and composes the chain of maps from seeds to locations into one map, whose
+    /// types no longer borrow from the input.
+    fn compressed_from_input(input: &str) -> Result<Puzzle<'static>, Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(input)?;
+        puzzle.chain_maps()?;
+        puzzle.compress();
+        let entries = puzzle.maps.pop().map(|map| map.entries).unwrap_or_default();
+        let map = Map { from_type: "seed", to_type: "location", entries };
+        Ok(Puzzle { seeds: puzzle.seeds, maps: vec![map] })
+    }
+
+    /// Returns the lowest location and the seed leading to it, where the maps have been compressed
+    /// into one, reading the seeds as ranges if `seed_ranges` is set.
+    fn min_location(&self, seed_ranges: bool) -> Result<MinResult, Box<dyn Error>> {
+        if seed_ranges {
+            let candidates = self.range_candidates()?;
+            self.min_for_seeds(candidates.into_iter().map(|(seed, i)| (seed, Some(i))))
+        } else {
+            self.min_for_seeds(self.seeds.iter().map(|&seed| (seed, None)))
+        }
+    }
+
+    /// Returns the lowest location among the seeds for which `pred` holds, where the maps have been
+    /// compressed into one. The seeds are split into pieces on which the map is a translation, so
+    /// within a piece the first seed satisfying `pred` has the lowest location. Pieces are visited
+    /// by their lowest location, and the search stops as soon as no piece can do better.
+    fn min_where(&self, seed_ranges: bool, pred: impl Fn(u64) -> bool) -> Result<MinResult, Box<dyn Error>> {
+        let mut ranges = Vec::new();
+        if seed_ranges {
+            for (i, range) in self.seeds.chunks(2).enumerate() {
+                let [start, range_len] = *range else {
+                    return Err("Expected range length".into());
+                };
+                ranges.push((start, start + range_len, Some(i)));
+            }
+        } else {
+            ranges.extend(self.seeds.iter().map(|&seed| (seed, seed + 1, None)));
+        }
+        let mut pieces: Vec<(IntervalMapping, Option<usize>)> = Vec::new();
+        for (a, b, range_index) in ranges {
+            match self.maps.first() {
+                Some(map) => pieces.extend(map.pieces(a, b).into_iter().map(|piece| (piece, range_index))),
+                None => pieces.push((IntervalMapping { a, b, dest: a }, range_index)),
+            }
+        }
+        pieces.sort_by_key(|(piece, _)| (piece.dest, piece.a));
+
+        let mut maybe_min: Option<MinResult> = None;
+        for (piece, range_index) in pieces {
+            if maybe_min.is_some_and(|m| piece.dest > m.location) {
+                break;
+            }
+            // Seeds beyond the best location found so far cannot win
+            let end = maybe_min.map_or(piece.b, |m| piece.b.min(piece.a + (m.location - piece.dest) + 1));
+            if let Some(seed) = (piece.a..end).find(|&seed| pred(seed)) {
+                let location = piece.dest + (seed - piece.a);
+                if maybe_min.is_none_or(|m| (location, seed) < (m.location, m.seed)) {
+                    let offset = range_index.map(|i| seed - self.seeds[2 * i]);
+                    maybe_min = Some(MinResult { location, seed, range_index, offset });
+                }
+            }
+        }
+        Ok(maybe_min.ok_or("No seed satisfies the predicate")?)
+    }
+}
+
+/// The lowest location and the seed it belongs to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MinResult {
+    pub location: u64,
+    pub seed: u64,
+    /// 0-based index of the seed range containing the seed if the seeds are read as ranges
+    pub range_index: Option<usize>,
+    /// Position of the seed within its range if the seeds are read as ranges
+    pub offset: Option<u64>,
+}
+
+impl fmt::Display for MinResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Location {} for seed {}", self.location, self.seed)?;
+        if let (Some(i), Some(offset)) = (self.range_index, self.offset) {
+            write!(f, " at offset {offset} in seed range {}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the lowest location and the seed leading to it, reading the seeds as ranges as in part 2
+/// if `seed_ranges` is set.
+pub fn min_location(input: &str, seed_ranges: bool) -> Result<MinResult, Box<dyn Error>> {
+    Puzzle::compressed_from_input(input)?.min_location(seed_ranges)
+}
+
+/// Finds the lowest location among the seeds for which `pred` holds, and the seed leading to it,
+/// reading the seeds as ranges as in part 2 if `seed_ranges` is set. Only seeds in the pieces of the
+/// ranges that can still beat the best location found so far are passed to `pred`, so selective
+/// predicates may need to look at many seeds, but predicates holding for many seeds need few.
+pub fn min_location_where(input: &str, seed_ranges: bool, pred: impl Fn(u64) -> bool) -> Result<MinResult, Box<dyn Error>> {
+    Puzzle::compressed_from_input(input)?.min_where(seed_ranges, pred)
+}
+
+/// A range of values of some type, split into the ranges of the next type it is mapped to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RangeNode {
+    pub value_type: String,
+    /// First value of the range
+    pub start: u64,
+    /// First value after the range
+    pub end: u64,
+    /// The pieces of the range mapped to the next type
+    pub children: Vec<RangeNode>,
+}
+
+impl RangeNode {
+    fn new(maps: &[Map], value_type: &str, start: u64, end: u64) -> Self {
+        let children = match maps.split_first() {
+            Some((map, rest)) => map.split(start, end).into_iter()
+                .map(|(a, b)| Self::new(rest, map.to_type, a, b))
+                .collect(),
+            None => Vec::new(),
+        };
+        Self { value_type: value_type.to_owned(), start, end, children }
+    }
+
+    /// The ranges without children, which are the locations if the tree starts with seeds
+    pub fn leaves(&self) -> Vec<&RangeNode> {
+        if self.children.is_empty() {
+            return vec![self];
+        }
+        self.children.iter().flat_map(RangeNode::leaves).collect()
+    }
+
+    fn write_json(&self, json: &mut String, indent: usize) {
+        let pad = " ".repeat(indent);
+        json.push_str(&format!("{pad}{{\"type\": \"{}\", \"start\": {}, \"end\": {}, \"children\": [",
+            self.value_type, self.start, self.end));
+        if !self.children.is_empty() {
+            json.push('\n');
+            for (i, child) in self.children.iter().enumerate() {
+                child.write_json(json, indent + 2);
+                json.push_str(if i + 1 < self.children.len() { ",\n" } else { "\n" });
+            }
+            json.push_str(&pad);
+        }
+        json.push_str("]}");
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        writeln!(f, "{:indent$}{} [{}, {})", "", self.value_type, self.start, self.end)?;
+        for child in &self.children {
+            child.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
 }
 
-fn part2(input: &str) -> Result<u64, Box<dyn Error>> {
+impl fmt::Display for RangeNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Propagates each seed range of part 2 through the maps from seeds to locations, recording how
+/// it is split at the boundaries of the map entries on each level.
+pub fn seed_range_splits(input: &str) -> Result<Vec<RangeNode>, Box<dyn Error>> {
     let mut puzzle = Puzzle::from_input(input)?;
-    puzzle.compress();
-    puzzle.seeds_to_ranges()?;
-    Ok(puzzle.min_for_seeds()?)
+    puzzle.chain_maps()?;
+    if puzzle.seeds.len() % 2 != 0 {
+        return Err("Expected range length".into());
+    }
+    Ok(puzzle.seeds.chunks(2).map(|range| RangeNode::new(&puzzle.maps, "seed", range[0], range[0] + range[1])).collect())
+}
+
+/// Writes the trees of seed ranges as a JSON array.
+pub fn splits_to_json(trees: &[RangeNode]) -> String {
+    let mut json = String::from("[\n");
+    for (i, tree) in trees.iter().enumerate() {
+        tree.write_json(&mut json, 2);
+        json.push_str(if i + 1 < trees.len() { ",\n" } else { "\n" });
+    }
+    json.push_str("]\n");
+    json
+}
+
+/// Both parts, solved from the same almanac with its maps compressed into one
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle<'static>;
+
+    fn parse(input: &str) -> Result<Puzzle<'static>, Box<dyn Error>> {
+        Puzzle::compressed_from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle<'static>) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.min_location(false)?.location.into())
+    }
+
+    fn part2(puzzle: &Puzzle<'static>) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.min_location(true)?.location.into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports syntax errors, the lack of a chain of maps from seeds to locations, and an odd number of
+/// seeds, which cannot be read as ranges in part 2.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut puzzle = match Puzzle::from_input(input) {
+        Ok(puzzle) => puzzle,
+        Err(e) => return vec![Issue::new(e)],
+    };
+    let mut issues = Vec::new();
+    if let Err(e) = puzzle.chain_maps() {
+        issues.push(Issue::new(e));
+    }
+    if puzzle.seeds.len() % 2 != 0 {
+        issues.push(Issue::new("Odd number of seeds cannot be read as ranges"));
+    }
+    issues
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
-    println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    if config.diff {
+        let old_contents = fs::read_to_string(config.file_path1)?;
+        let new_contents = fs::read_to_string(config.file_path2)?;
+        print!("{}", diff_almanacs(&old_contents, &new_contents)?);
+        return Ok(());
+    }
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    println!("Part 1: Reading file {}", config.file_path1);
+    let puzzle = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    let min = puzzle.min_location(false)?;
+    println!("Result of part 1: {}", min.location);
+    println!("{min}");
+
+    let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, Day::parse)?;
+    let min = puzzle.min_location(true)?;
+    println!("Result of part 2: {}", min.location);
+    println!("{min}");
+    // The splits follow the seed ranges through the uncompressed maps
+    if let Some(format) = config.splits {
+        let trees = seed_range_splits(&fs::read_to_string(&config.file_path2)?)?;
+        match format {
+            config::SplitFormat::Text => trees.iter().for_each(|tree| print!("{tree}")),
+            config::SplitFormat::Json => print!("{}", splits_to_json(&trees)),
+        }
+    }
 
     Ok(())
 }
@@ -280,6 +609,8 @@ pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    use aoc_core::rng::{Rng, SeededRng};
+
     const EXAMPLE: &str = "
 seeds: 79 14 55 13
 
@@ -316,20 +647,150 @@ humidity-to-location map:
 56 93 4
     ";
 
+    #[test]
+    fn validate_headers() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        let input = "seeds: 1 2 3\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3";
+        assert_eq!(validate(input), vec![
+            Issue::new("No chain of maps leads from seed to location; types reachable from seed: seed, soil"),
+            Issue::new("Odd number of seeds cannot be read as ranges"),
+        ]);
+    }
+
+    /// The example with its map blocks in reverse order and an extra block that is not on the
+    /// way from seeds to locations
+    fn reordered_example() -> String {
+        let (seeds, maps) = EXAMPLE.trim().split_once("\n\n").unwrap();
+        let mut blocks: Vec<&str> = maps.split("\n\n").collect();
+        blocks.reverse();
+        blocks.insert(3, "soil-to-compost map:\n0 0 100");
+        format!("{seeds}\n\n{}", blocks.join("\n\n"))
+    }
+
+    #[test]
+    fn reordered_blocks() -> Result<(), Box<dyn Error>> {
+        assert_eq!(solve_part1(&reordered_example())?, 35);
+        assert_eq!(solve_part2(&reordered_example())?, 46);
+        assert!(validate(&reordered_example()).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn input_round_trip() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input(EXAMPLE.trim())?;
+        assert_eq!(puzzle.to_input_string(), format!("{}\n", EXAMPLE.trim()));
+        let reordered = reordered_example();
+        let puzzle = Puzzle::from_input(&reordered)?;
+        let text = puzzle.to_input_string();
+        assert_eq!(Puzzle::from_input(&text)?, puzzle);
+        // A modified almanac with all maps composed into one still has the same answers
+        let mut puzzle = Puzzle::from_input(EXAMPLE.trim())?;
+        puzzle.chain_maps()?;
+        puzzle.compress();
+        let text = puzzle.to_input_string();
+        assert!(text.contains("\nseed-to-location map:\n"));
+        assert_eq!(Puzzle::from_input(&text)?, puzzle);
+        assert_eq!(solve_part1(&text)?, 35);
+        assert_eq!(solve_part2(&text)?, 46);
+        // Maps without entries and almanacs without seeds
+        let text = "seeds:\n\nseed-to-location map:\n";
+        assert_eq!(Puzzle::from_input(text)?.to_input_string(), text);
+        Ok(())
+    }
+
+    #[test]
+    fn example_splits() -> Result<(), Box<dyn Error>> {
+        let trees = seed_range_splits(EXAMPLE.trim())?;
+        assert_eq!(trees.len(), 2);
+        let min_location = trees.iter().flat_map(RangeNode::leaves).map(|leaf| leaf.start).min();
+        assert_eq!(min_location, Some(46));
+        // Each level covers as many values as the seed range
+        for tree in &trees {
+            let size: u64 = tree.leaves().iter().map(|leaf| leaf.end - leaf.start).sum();
+            assert_eq!(size, tree.end - tree.start);
+            assert!(tree.leaves().iter().all(|leaf| leaf.value_type == "location"));
+        }
+        let text = trees[0].to_string();
+        assert!(text.starts_with("seed [79, 93)\n  soil [81, 95)\n    fertilizer [81, 95)\n      water [81, 95)\n"));
+        let small = "seeds: 5 10\n\nseed-to-location map:\n0 10 5";
+        let json = splits_to_json(&seed_range_splits(small)?);
+        assert_eq!(json, r#"[
+  {"type": "seed", "start": 5, "end": 15, "children": [
+    {"type": "location", "start": 5, "end": 10, "children": []},
+    {"type": "location", "start": 0, "end": 5, "children": []}
+  ]}
+]
+"#);
+        Ok(())
+    }
+
+    #[test]
+    fn gap_in_chain() {
+        let input = EXAMPLE.trim().replace("water-to-light", "water-to-sunlight");
+        let error = solve_part1(&input).unwrap_err().to_string();
+        assert_eq!(error, "No chain of maps leads from seed to location; types reachable from seed: fertilizer, seed, soil, sunlight, water");
+    }
+
     #[test]
     fn example_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE.trim())?;
+        let result = solve_part1(EXAMPLE.trim())?;
         assert_eq!(result, 35);
         Ok(())
     }
 
     #[test]
     fn example_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE.trim())?;
+        let result = solve_part2(EXAMPLE.trim())?;
         assert_eq!(result, 46);
         Ok(())
     }
 
+    #[test]
+    fn example_min_location_seeds() -> Result<(), Box<dyn Error>> {
+        let min = min_location(EXAMPLE.trim(), false)?;
+        assert_eq!(min, MinResult { location: 35, seed: 13, range_index: None, offset: None });
+        assert_eq!(min.to_string(), "Location 35 for seed 13");
+        let min = min_location(EXAMPLE.trim(), true)?;
+        assert_eq!(min, MinResult { location: 46, seed: 82, range_index: Some(0), offset: Some(3) });
+        assert_eq!(min.to_string(), "Location 46 for seed 82 at offset 3 in seed range 1");
+        Ok(())
+    }
+
+    /// Lowest location among the seeds for which `pred` holds, by trying every seed
+    fn min_location_where_brute_force(input: &str, seed_ranges: bool, pred: impl Fn(u64) -> bool) -> Result<MinResult, Box<dyn Error>> {
+        let mut puzzle = Puzzle::from_input(input)?;
+        puzzle.chain_maps()?;
+        let seeds: Vec<(u64, Option<usize>)> = if seed_ranges {
+            puzzle.seeds.chunks(2).enumerate().flat_map(|(i, range)| (range[0]..range[0] + range[1]).map(move |seed| (seed, Some(i)))).collect()
+        } else {
+            puzzle.seeds.iter().map(|&seed| (seed, None)).collect()
+        };
+        puzzle.min_for_seeds(seeds.into_iter().filter(|&(seed, _)| pred(seed)))
+    }
+
+    #[test]
+    fn example_min_location_where() -> Result<(), Box<dyn Error>> {
+        let input = EXAMPLE.trim();
+        // Without restrictions, the result is the same as for the parts
+        assert_eq!(min_location_where(input, false, |_| true)?, min_location(input, false)?);
+        assert_eq!(min_location_where(input, true, |_| true)?, min_location(input, true)?);
+        let min = min_location_where(input, true, |seed| seed % 2 == 0)?;
+        assert_eq!(min, MinResult { location: 46, seed: 82, range_index: Some(0), offset: Some(3) });
+        let min = min_location_where(input, true, |seed| seed % 2 == 1)?;
+        assert_eq!(min, min_location_where_brute_force(input, true, |seed| seed % 2 == 1)?);
+        assert!(min.location > 46);
+        let predicates: [fn(u64) -> bool; 4] = [|seed| seed % 7 == 3, |seed| seed > 85, |seed| seed < 60, |seed| seed == 66];
+        for seed_ranges in [false, true] {
+            for pred in predicates {
+                let expected = min_location_where_brute_force(input, seed_ranges, pred).ok();
+                assert_eq!(min_location_where(input, seed_ranges, pred).ok(), expected);
+            }
+        }
+        let error = min_location_where(input, true, |_| false).err().map(|e| e.to_string());
+        assert_eq!(error.as_deref(), Some("No seed satisfies the predicate"));
+        Ok(())
+    }
+
     #[test]
     #[should_panic(expected="Overlapping sources")]
     fn map_entries_overlapping_sources() {
@@ -341,4 +802,49 @@ humidity-to-location map:
         let mut iter = input.lines();
         Map::entries_from_iter_until_end_of_block(&mut iter).unwrap();
     }
+
+    /// Generates a chain of maps that each permute `num_intervals` blocks of length `block_len`.
+    fn permutation_maps<'a>(types: &'a [String], num_intervals: u64, block_len: u64) -> Vec<Map<'a>> {
+        let mut rng = SeededRng::new(42);
+        types.windows(2).map(|types| {
+            let mut blocks: Vec<u64> = (0..num_intervals).collect();
+            for i in (1..blocks.len()).rev() {
+                blocks.swap(i, rng.index(i + 1));
+            }
+            let entries = blocks.iter().enumerate()
+                .map(|(i, &j)| IntervalMapping { a: i as u64 * block_len, b: (i as u64 + 1) * block_len, dest: j * block_len })
+                .collect();
+            Map { from_type: &types[0], to_type: &types[1], entries }
+        }).collect()
+    }
+
+    #[test]
+    fn parallel_compression_matches_sequential() {
+        let types: Vec<String> = (0..=20).map(|i| format!("type{i}")).collect();
+        let maps = permutation_maps(&types, 50, 7);
+        let sequential = Map::combine_all(&maps, usize::MAX);
+        let parallel = Map::combine_all(&maps, 1);
+        assert_eq!(parallel.from_type, "type0");
+        assert_eq!(parallel.to_type, "type20");
+        for x in 0..400 {
+            assert_eq!(parallel.apply(x), sequential.apply(x));
+            assert_eq!(parallel.apply(x), maps.iter().fold(x, |x, map| map.apply(x)));
+        }
+    }
+
+    /// Compares sequential and parallel compression of a long chain of large maps. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_compression() {
+        let types: Vec<String> = (0..=64).map(|i| format!("type{i}")).collect();
+        let maps = permutation_maps(&types, 2000, 1000);
+        let start = std::time::Instant::now();
+        let sequential = Map::combine_all(&maps, usize::MAX);
+        println!("Sequential compression: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let parallel = Map::combine_all(&maps, SEQUENTIAL_THRESHOLD);
+        println!("Parallel compression: {:?}", start.elapsed());
+        assert_eq!(sequential.entries, parallel.entries);
+    }
 }
@@ -1,17 +1,38 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print the distances for hold times around the winning boundaries of each race of part 1
+    pub table: bool,
+    /// Evaluate the races of part 1 as a bulk table with integer math in parallel
+    pub bulk: bool,
+    /// Number of hold times shown on each side of a winning boundary
+    pub window: u64,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day6 <file for part 1> <file for part 2> [--table] [--window <n>] [--bulk]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut table = false;
+        let mut window = 3;
+        let mut bulk = false;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--table" => table = true,
+                "--bulk" => bulk = true,
+                "--window" => window = iter.next().and_then(|n| n.parse().ok()).ok_or(USAGE)?,
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            table,
+            bulk,
+            window,
         })
     }
 }
-
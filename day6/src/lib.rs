@@ -1,10 +1,14 @@
 pub mod config;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::parallel;
+use aoc_core::validate::{self, Issue};
+use aoc_core::parse::{self, ParseError};
 use std::error::Error;
-use std::iter::zip;
+use std::fmt::Display;
 use std::fs;
+use std::iter::zip;
+use std::ops::RangeInclusive;
 
 #[derive(Debug)]
 struct Race {
@@ -13,11 +17,27 @@ struct Race {
 }
 
 impl Race {
-    fn num_ways_to_win(&self) -> u64 {
+    /// Distance the boat travels if the button is held for `hold` milliseconds
+    fn distance(&self, hold: u64) -> u64 {
+        hold * (self.time - hold)
+    }
+
+    /// The hold times in `holds` together with the distances they achieve
+    fn distances(&self, holds: RangeInclusive<u64>) -> impl Iterator<Item = (u64, u64)> + '_ {
+        holds.map(|hold| (hold, self.distance(hold)))
+    }
+
+    /// The least and the greatest hold time beating the record, if any
+    fn winning_range(&self) -> Option<(u64, u64)> {
         // Solve quadratic inequality (time - x) * x > distance
         let time = self.time as f64;
         let distance = self.distance as f64;
-        let sqrt = f64::sqrt(time*time - 4.0 * distance);
+        let discriminant = time*time - 4.0 * distance;
+        if discriminant <= 0.0 {
+            // The record cannot be beaten
+            return None;
+        }
+        let sqrt = f64::sqrt(discriminant);
         let solution1 = (-time + sqrt) / -2.0;
         let solution2 = (-time - sqrt) / -2.0;
         assert!(solution1 < solution2);
@@ -31,8 +51,142 @@ impl Race {
             at_most -= 1.0;
         }
         let at_most = at_most as u64;
-        assert!(at_most >= at_least);
-        at_most - at_least + 1
+        (at_least <= at_most).then_some((at_least, at_most))
+    }
+
+    fn num_ways_to_win(&self) -> u64 {
+        self.winning_range().map_or(0, |(at_least, at_most)| at_most - at_least + 1)
+    }
+
+    /// Like `num_ways_to_win`, but with integer math only, which is exact for any times and
+    /// distances
+    fn num_ways_to_win_exact(&self) -> u64 {
+        let (time, record) = (self.time as u128, self.distance as u128);
+        let wins = |hold: u128| hold * (time - hold) > record;
+        // The best hold time is half the time, and the distance is symmetric around it
+        if !wins(time / 2) {
+            return 0;
+        }
+        // The least winning hold time is close to the smaller root of hold * (time - hold) = record
+        let mut at_least = (time - (time * time - 4 * record.min(time * time / 4)).isqrt()) / 2;
+        while at_least > 0 && wins(at_least - 1) {
+            at_least -= 1;
+        }
+        while !wins(at_least) {
+            at_least += 1;
+        }
+        (time - 2 * at_least + 1) as u64
+    }
+
+    /// Tabulates the distances for the hold times at most `window` away from the winning
+    /// boundaries, or from the best hold time if the record cannot be beaten.
+    fn strategy_table(&self, window: u64) -> StrategyTable {
+        let centers = match self.winning_range() {
+            Some((at_least, at_most)) => vec![at_least, at_most],
+            None => vec![self.time / 2],
+        };
+        let mut rows: Vec<(u64, u64)> = Vec::new();
+        for center in centers {
+            let start = center.saturating_sub(window);
+            // Skip hold times already tabulated around the previous boundary
+            let start = rows.last().map_or(start, |&(hold, _)| start.max(hold + 1));
+            rows.extend(self.distances(start..=(center + window).min(self.time)));
+        }
+        StrategyTable { time: self.time, record: self.distance, winning_range: self.winning_range(), rows }
+    }
+}
+
+/// Distances of a race for some hold times
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyTable {
+    pub time: u64,
+    pub record: u64,
+    /// The least and the greatest hold time beating the record, if any
+    pub winning_range: Option<(u64, u64)>,
+    /// Hold times in increasing order with the distances they achieve
+    pub rows: Vec<(u64, u64)>,
+}
+
+impl Display for StrategyTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Time {}, record {}: ", self.time, self.record)?;
+        match self.winning_range {
+            Some((at_least, at_most)) => writeln!(f, "hold between {at_least} and {at_most} to win")?,
+            None => writeln!(f, "record cannot be beaten")?,
+        }
+        writeln!(f, "{:>12} {:>12}", "hold", "distance")?;
+        let mut previous = None;
+        for &(hold, distance) in &self.rows {
+            if previous.is_some_and(|p| hold > p + 1) {
+                writeln!(f, "{:>12}", "...")?;
+            }
+            let verdict = if distance > self.record { " win" } else { "" };
+            writeln!(f, "{hold:>12} {distance:>12}{verdict}")?;
+            previous = Some(hold);
+        }
+        Ok(())
+    }
+}
+
+/// The times and distances of the races as written, before they are read as numbers
+struct Sheet {
+    times: Vec<String>,
+    distances: Vec<String>,
+}
+
+impl Sheet {
+    /// Reads the races either from the two-row format with a line of times followed by a line of
+    /// distances, or from a table with one race per line containing its time and distance.
+    fn from_input(input: &str) -> Result<Self, String> {
+        let (times, distances) = if input.trim_start().starts_with("Time:") {
+            Self::read_rows(input)?
+        } else {
+            Self::read_table(input)?
+        };
+        if times.len() != distances.len() {
+            return Err("Number of times different from number of distances".into());
+        }
+        let to_owned = |numbers: Vec<&str>| numbers.into_iter().map(str::to_owned).collect();
+        Ok(Self { times: to_owned(times), distances: to_owned(distances) })
+    }
+
+    fn read_rows(input: &str) -> Result<(Vec<&str>, Vec<&str>), String> {
+        let mut lines = input.trim().lines();
+        let times = Self::read_row(lines.next().ok_or("Expected times")?, "Time:").map_err(|e| e.on_line(1))?;
+        let distances = Self::read_row(lines.next().ok_or("Expected distances")?, "Distance:").map_err(|e| e.on_line(2))?;
+        if lines.next().is_some() {
+            return Err("Unexpected line after distances".into());
+        }
+        Ok((times, distances))
+    }
+
+    /// Reads a line like "Time:      7  15   30" into the numbers following the given header.
+    fn read_row<'a>(line: &'a str, header: &str) -> Result<Vec<&'a str>, ParseError> {
+        parse::parse_all(line, |parser| {
+            parser.tag(header)?;
+            let mut numbers = Vec::new();
+            while !parser.take_while(char::is_whitespace).is_empty() && parser.peek().is_some() {
+                numbers.push(parser.word("number", |c| c.is_ascii_digit())?);
+            }
+            if numbers.is_empty() {
+                return Err(parser.error("Expected number"));
+            }
+            Ok(numbers)
+        })
+    }
+
+    fn read_table(input: &str) -> Result<(Vec<&str>, Vec<&str>), String> {
+        let mut times = Vec::new();
+        let mut distances = Vec::new();
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let mut iter = line.split_whitespace();
+            let (Some(time), Some(distance), None) = (iter.next(), iter.next(), iter.next()) else {
+                return Err(format!("Expected time and distance in line {line}"));
+            };
+            times.push(time);
+            distances.push(distance);
+        }
+        Ok((times, distances))
     }
 }
 
@@ -41,58 +195,133 @@ struct Puzzle {
 }
 
 impl Puzzle {
+    /// Reads the races of the input like `Sheet::from_input` and then like `from_sheet`.
     fn from_input(input: &str, ignore_spaces: bool) -> Result<Self, String> {
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(
-            r"^\s*Time:(?<times>(\s+[0-9]+)+).*\nDistance:(?<distances>(\s+[0-9]+)+)[.\n]*$"
-        ).unwrap());
-        let result = RE.captures(input).ok_or("Syntax error")?;
-        let times;
-        let distances;
-        if ignore_spaces {
-            let time_str = result["times"].chars().filter(|&c| c != ' ').collect::<String>();
-            times = vec![time_str.parse().unwrap()];
-            let distance_str = result["distances"].chars().filter(|&c| c != ' ').collect::<String>();
-            distances = vec![distance_str.parse().unwrap()];
+        Self::from_sheet(&Sheet::from_input(input)?, ignore_spaces)
+    }
+
+    /// Reads the numbers of the sheet. If `ignore_spaces` is true, the times and distances of all
+    /// races are concatenated into a single race.
+    fn from_sheet(sheet: &Sheet, ignore_spaces: bool) -> Result<Self, String> {
+        let parse = |s: &str| s.parse::<u64>().map_err(|_| format!("Could not parse number {s}"));
+        let races = if ignore_spaces {
+            let time = parse(&sheet.times.concat())?;
+            let distance = parse(&sheet.distances.concat())?;
+            vec![Race { time, distance }]
         } else {
-            times = result["times"].split(' ').filter_map(|s| s.trim().parse().ok()).collect();
-            distances = result["distances"].split(' ').filter_map(|s| s.trim().parse().ok()).collect();
-        }
-        if times.len() != distances.len() {
-            return Err("Number of times different from number of distances".into());
-        }
-        let races = zip(times, distances).map(|(time, distance)| Race { time, distance }).collect();
+            zip(&sheet.times, &sheet.distances)
+                .map(|(time, distance)| Ok(Race { time: parse(time)?, distance: parse(distance)? }))
+                .collect::<Result<_, String>>()?
+        };
         Ok(Self { races })
     }
+
+    fn evaluate(&self, parallel: bool) -> BulkEvaluation {
+        let counts = if parallel {
+            parallel::map(&self.races, Race::num_ways_to_win_exact)
+        } else {
+            self.races.iter().map(Race::num_ways_to_win_exact).collect()
+        };
+        let product = counts.iter().try_fold(1u64, |product, &count| product.checked_mul(count));
+        BulkEvaluation { counts, product }
+    }
+
+    fn strategy_tables(&self, window: u64) -> Vec<StrategyTable> {
+        self.races.iter().map(|race| race.strategy_table(window)).collect()
+    }
+
+    fn product_of_ways_to_win(&self) -> u64 {
+        self.races.iter().map(Race::num_ways_to_win).product()
+    }
 }
 
- fn part1(input: &str) -> Result<u64, Box<dyn Error>> {
+/// Computes the number of ways to win each race.
+pub fn ways_to_win_per_race(input: &str) -> Result<Vec<u64>, Box<dyn Error>> {
     let puzzle = Puzzle::from_input(input, false)?;
-    let mut product = 1;
-    for race in puzzle.races {
-        product *= race.num_ways_to_win();
+    Ok(puzzle.races.iter().map(Race::num_ways_to_win).collect())
+}
+
+/// The number of ways to win each of many races and their product
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BulkEvaluation {
+    /// Number of ways to win each race, in input order
+    pub counts: Vec<u64>,
+    /// Product of the counts, or `None` if it does not fit into 64 bits
+    pub product: Option<u64>,
+}
+
+/// Computes the number of ways to win each race of a large table with integer math, distributing
+/// the races over all threads if `parallel` is set.
+pub fn evaluate_races(input: &str, parallel: bool) -> Result<BulkEvaluation, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input, false)?.evaluate(parallel))
+}
+
+/// Tabulates, for each race, the distances for the hold times at most `window` away from the
+/// winning boundaries.
+pub fn strategy_tables(input: &str, window: u64) -> Result<Vec<StrategyTable>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input, false)?.strategy_tables(window))
+}
+
+/// Both parts, solved from the same sheet, whose numbers each part reads differently
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Sheet;
+
+    fn parse(input: &str) -> Result<Sheet, Box<dyn Error>> {
+        Ok(Sheet::from_input(input)?)
+    }
+
+    fn part1(sheet: &Sheet) -> Result<Answer, Box<dyn Error>> {
+        Ok(Puzzle::from_sheet(sheet, false)?.product_of_ways_to_win().into())
     }
-    Ok(product)
+
+    fn part2(sheet: &Sheet) -> Result<Answer, Box<dyn Error>> {
+        Ok(Puzzle::from_sheet(sheet, true)?.product_of_ways_to_win().into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
 }
 
-fn part2(input: &str) -> Result<u64, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, true)?;
-    let mut product = 1;
-    for race in puzzle.races {
-        product *= race.num_ways_to_win();
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports syntax errors and, for part 2, concatenated numbers that are too large.
+pub fn validate(input: &str) -> Vec<Issue> {
+    match Puzzle::from_input(input, false) {
+        Ok(_) => validate::check_all(Puzzle::from_input(input, true).map_err(|e| format!("Part 2: {e}"))),
+        Err(e) => vec![Issue::new(e)],
     }
-    Ok(product)
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let sheet = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    let puzzle = Puzzle::from_sheet(&sheet, false)?;
+    println!("Result of part 1: {}", puzzle.product_of_ways_to_win());
+    if config.bulk {
+        let evaluation = puzzle.evaluate(true);
+        match evaluation.product {
+            Some(product) => println!("Product of the ways to win {} races: {product}", evaluation.counts.len()),
+            None => println!("Product of the ways to win {} races does not fit into 64 bits", evaluation.counts.len()),
+        }
+    }
+    if config.table {
+        for (i, table) in puzzle.strategy_tables(config.window).iter().enumerate() {
+            print!("Race {}: {table}", i + 1);
+        }
+    }
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let sheet = answer::parse_part2(sheet, &config.file_path1, &config.file_path2, Day::parse)?;
+    println!("Result of part 2: {}", Day::part2(&sheet)?);
 
     Ok(())
 }
@@ -101,19 +330,127 @@ pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    use aoc_core::rng::{Rng, SeededRng};
+
     const EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200";
 
     #[test]
     fn example_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE.trim())?;
+        let result = solve_part1(EXAMPLE.trim())?;
         assert_eq!(result, 288);
         Ok(())
     }
 
     #[test]
     fn example_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE.trim())?;
+        let result = solve_part2(EXAMPLE.trim())?;
+        assert_eq!(result, 71503);
+        Ok(())
+    }
+
+    const EXAMPLE_TABLE: &str = "7 9\n15 40\n30   200\n";
+
+    #[test]
+    fn example_table_part1() -> Result<(), Box<dyn Error>> {
+        assert_eq!(ways_to_win_per_race(EXAMPLE_TABLE)?, vec![4, 8, 9]);
+        let result = solve_part1(EXAMPLE_TABLE)?;
+        assert_eq!(result, 288);
+        Ok(())
+    }
+
+    #[test]
+    fn example_table_part2() -> Result<(), Box<dyn Error>> {
+        let result = solve_part2(EXAMPLE_TABLE)?;
         assert_eq!(result, 71503);
         Ok(())
     }
+
+    #[test]
+    fn example_strategy_tables() -> Result<(), Box<dyn Error>> {
+        let tables = strategy_tables(EXAMPLE, 1)?;
+        assert_eq!(tables[0].winning_range, Some((2, 5)));
+        assert_eq!(tables[0].rows, vec![(1, 6), (2, 10), (3, 12), (4, 12), (5, 10), (6, 6)]);
+        assert_eq!(tables[2].rows, vec![(10, 200), (11, 209), (12, 216), (18, 216), (19, 209), (20, 200)]);
+        assert_eq!(tables[2].to_string(), "\
+Time 30, record 200: hold between 11 and 19 to win
+        hold     distance
+          10          200
+          11          209 win
+          12          216 win
+         ...
+          18          216 win
+          19          209 win
+          20          200
+");
+        let table = strategy_tables("4 4", 3)?.remove(0);
+        assert_eq!(table.winning_range, None);
+        assert_eq!(table.rows, vec![(0, 0), (1, 3), (2, 4), (3, 3), (4, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn unbeatable_race() -> Result<(), Box<dyn Error>> {
+        assert_eq!(ways_to_win_per_race("4 4\n5 10")?, vec![0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_table() {
+        assert!(ways_to_win_per_race("7 9 1").is_err());
+        assert!(ways_to_win_per_race("7").is_err());
+        assert!(ways_to_win_per_race("7 x").is_err());
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let error = ways_to_win_per_race("Time: 7 15\nDistance: 9 x0").unwrap_err();
+        assert_eq!(error.to_string(), "Line 2, column 13: Expected number");
+        assert!(ways_to_win_per_race("Time:\nDistance: 9").is_err());
+        assert!(ways_to_win_per_race("Time: 7\nDistance: 9\nTime: 1").is_err());
+    }
+
+    #[test]
+    fn exact_ways_to_win() -> Result<(), Box<dyn Error>> {
+        let evaluation = evaluate_races(EXAMPLE.trim(), false)?;
+        assert_eq!(evaluation, BulkEvaluation { counts: vec![4, 8, 9], product: Some(288) });
+        assert_eq!(evaluate_races(EXAMPLE.trim(), true)?, evaluation);
+        // Distances exactly on the record do not win, and neither does any hold time here
+        let edge_cases = "7 10\n8 16\n8 15\n0 0\n1 0\n18446744073709551615 18446744073709551615";
+        let evaluation = evaluate_races(edge_cases, false)?;
+        assert_eq!(evaluation.counts[..5], [2, 0, 1, 0, 0]);
+        assert_eq!(evaluation.counts[5], u64::MAX - 3);
+        assert_eq!(evaluation.product, Some(0));
+        // The integer math agrees with the floating point one where the latter is precise
+        let mut rng = SeededRng::new(1);
+        let mut random = move |max: u64| rng.below(max);
+        for _ in 0..10_000 {
+            let time = random(100_000);
+            let race = Race { time, distance: random(time * time / 4 + 10) };
+            assert_eq!(race.num_ways_to_win_exact(), race.num_ways_to_win(), "{race:?}");
+        }
+        Ok(())
+    }
+
+    /// Evaluates a million races sequentially and in parallel. Run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_million_races() -> Result<(), Box<dyn Error>> {
+        let mut rng = SeededRng::new(42);
+        let mut random = move |max: u64| rng.below(max);
+        let mut input = String::new();
+        for _ in 0..1_000_000 {
+            let time = 1 + random(1 << 40);
+            input.push_str(&format!("{time} {}\n", random(time / 2 * (time - time / 2))));
+        }
+        let start = std::time::Instant::now();
+        let sequential = evaluate_races(&input, false)?;
+        println!("Sequential: {:?}", start.elapsed());
+        let start = std::time::Instant::now();
+        let parallel = evaluate_races(&input, true)?;
+        println!("Parallel: {:?}", start.elapsed());
+        assert_eq!(parallel, sequential);
+        assert_eq!(sequential.counts.len(), 1_000_000);
+        Ok(())
+    }
 }
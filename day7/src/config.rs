@@ -1,17 +1,61 @@
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Instead of solving, score hands read from stdin against the hands of part 1 (or part 2 if
+    /// `jokers` is set)
+    pub serve: bool,
+    /// Serve with the rules and the hands of part 2
+    pub jokers: bool,
+    /// Instead of solving, play the piles of both parts head to head against the hands in this file
+    pub against: Option<String>,
+    /// Files to which the scored hands of part 1 and part 2 are written as JSON
+    pub json_paths: [Option<String>; 2],
+    /// Instead of solving, explain which of these two hands is stronger with the rules of both
+    /// parts
+    pub compare: Option<[String; 2]>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day7 <file for part 1> <file for part 2> [--serve [--jokers] | --against <file> | --compare <hand> <hand>] [--json <file for part 1> <file for part 2>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut serve = false;
+        let mut jokers = false;
+        let mut against = None;
+        let mut json_paths = [None, None];
+        let mut compare = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--serve" => serve = true,
+                "--jokers" => jokers = true,
+                "--against" => against = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--compare" => {
+                    let hand1 = iter.next().ok_or(USAGE)?.clone();
+                    compare = Some([hand1, iter.next().ok_or(USAGE)?.clone()]);
+                },
+                "--json" => {
+                    for path in &mut json_paths {
+                        *path = Some(iter.next().ok_or(USAGE)?.clone());
+                    }
+                },
+                _ => return Err(USAGE),
+            }
+        }
+        let modes = usize::from(serve) + usize::from(against.is_some()) + usize::from(compare.is_some());
+        if (jokers && !serve) || modes > 1 {
+            return Err(USAGE);
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            serve,
+            jokers,
+            against,
+            json_paths,
+            compare,
         })
     }
 }
-
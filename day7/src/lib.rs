@@ -1,13 +1,17 @@
 pub mod config;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::validate::{self, Issue};
 use counter::Counter;
 use core::panic;
 use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::io::{self, BufRead, Write};
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Card {
     Joker, // J in the second part
     Two,
@@ -44,10 +48,29 @@ impl Card {
             _ => Err("Invalid card type"),
         }
     }
+
+    fn to_char(&self) -> char {
+        match self {
+            Self::Joker | Self::Jack => 'J',
+            Self::Two => '2',
+            Self::Three => '3',
+            Self::Four => '4',
+            Self::Five => '5',
+            Self::Six => '6',
+            Self::Seven => '7',
+            Self::Eight => '8',
+            Self::Nine => '9',
+            Self::T => 'T',
+            Self::Q => 'Q',
+            Self::K => 'K',
+            Self::A => 'A',
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
-enum HandType {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -79,9 +102,10 @@ fn hand_type_for_cards(cards: &[Card; 5]) -> HandType {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Hand {
     cards: [Card; 5],
-    bid: i32,
+    bid: u64,
     hand_type: HandType,
 }
 
@@ -98,6 +122,79 @@ impl Hand {
         let hand_type = hand_type_for_cards(&cards);
         Ok(Hand { cards, bid, hand_type })
     }
+
+    /// Returns the hand with its jacks turned into jokers.
+    fn with_jokers(&self) -> Self {
+        let cards = self.cards.clone().map(|card| if card == Card::Jack { Card::Joker } else { card });
+        let hand_type = hand_type_for_cards(&cards);
+        Hand { cards, bid: self.bid, hand_type }
+    }
+
+    /// Compares the hand with another one like `cmp`, but also tells what decided the comparison.
+    fn compare_explain(&self, other: &Self) -> ComparisonReport {
+        let ordering = self.cmp(other);
+        let decided_by = if self.hand_type != other.hand_type {
+            DecidedBy::HandType
+        } else {
+            match self.cards.iter().zip(&other.cards).position(|(a, b)| a != b) {
+                Some(position) => DecidedBy::Card(position),
+                None => DecidedBy::Tie,
+            }
+        };
+        ComparisonReport {
+            hands: [self, other].map(|hand| hand.cards.iter().map(Card::to_char).collect()),
+            hand_types: [self.hand_type.clone(), other.hand_type.clone()],
+            ordering,
+            decided_by,
+        }
+    }
+}
+
+/// What decided the comparison of two hands
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecidedBy {
+    /// The hands have different types
+    HandType,
+    /// The hands have the same type, and the first card in which they differ is at this 0-based
+    /// position
+    Card(usize),
+    /// The hands have exactly the same cards
+    Tie,
+}
+
+/// Which of two hands is stronger and why
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComparisonReport {
+    /// Cards of the first and the second hand
+    pub hands: [String; 2],
+    pub hand_types: [HandType; 2],
+    /// How the first hand compares to the second one
+    pub ordering: Ordering,
+    pub decided_by: DecidedBy,
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (winner, loser) = if self.ordering == Ordering::Less { (1, 0) } else { (0, 1) };
+        let (hands, types) = (&self.hands, &self.hand_types);
+        match self.decided_by {
+            DecidedBy::HandType => write!(f, "{} beats {}: {:?} beats {:?}",
+                hands[winner], hands[loser], types[winner], types[loser]),
+            DecidedBy::Card(i) => {
+                let card = |hand: usize| hands[hand].chars().nth(i).unwrap_or('?');
+                write!(f, "{} beats {}: both are {:?}, and card {} is {} against {}",
+                    hands[winner], hands[loser], types[winner], i + 1, card(winner), card(loser))
+            },
+            DecidedBy::Tie => write!(f, "{} ties with {}: both have the same cards", hands[0], hands[1]),
+        }
+    }
+}
+
+/// Compares two hands given as lines like "T55J5 684", telling what decided the comparison.
+pub fn compare_hands(line1: &str, line2: &str, rules: &Rules) -> Result<ComparisonReport, Box<dyn Error>> {
+    let hand1 = Hand::from_line(line1, &rules.j_value()).map_err(|e| format!("Hand 1: {e}"))?;
+    let hand2 = Hand::from_line(line2, &rules.j_value()).map_err(|e| format!("Hand 2: {e}"))?;
+    Ok(hand1.compare_explain(&hand2))
 }
 
 impl Ord for Hand {
@@ -117,11 +214,49 @@ impl PartialOrd for Hand {
     }
 }
 
+/// How to rank hands with exactly the same cards
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TieBreak {
+    /// The hand that comes first in the input is weaker
+    #[default]
+    InputOrder,
+    /// All tied hands get the lowest of their ranks
+    SharedRank,
+    /// All tied hands get the lowest of their ranks, but their winnings are computed from the
+    /// average of their ranks
+    AverageWinnings,
+}
+
+/// Rules for scoring hands
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rules {
+    /// Whether J cards are jokers (part 2) instead of jacks (part 1)
+    pub jokers: bool,
+    pub tie_break: TieBreak,
+}
+
+impl Rules {
+    fn j_value(&self) -> Card {
+        if self.jokers { Card::Joker } else { Card::Jack }
+    }
+}
+
+/// A hand with its rank among all hands and the resulting winnings
 #[derive(Debug)]
-struct ScoredHand {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoredHand {
     hand: Hand,
-    rank: i32,
-    winnings: i32,
+    /// Rank of the hand, where the weakest hand has rank 1
+    pub rank: u64,
+    /// Number of other hands with exactly the same cards
+    pub ties: usize,
+    pub winnings: f64,
+}
+
+impl ScoredHand {
+    pub fn bid(&self) -> u64 {
+        self.hand.bid
+    }
 }
 
 struct Puzzle {
@@ -129,55 +264,280 @@ struct Puzzle {
 }
 
 impl Puzzle {
-    fn from_input(input: &str, j_value: &Card) -> Result<Self, Box<dyn Error>> {
-        let hands: Vec<Hand> = input.lines().map(|line| Hand::from_line(line, j_value)).collect::<Result<_, _>>()?;
-        let mut sorted_hands: Vec<(usize, &Hand)> = hands.iter().enumerate().collect();
-        sorted_hands.sort_by(|(_, a), (_, b)| a.cmp(b));
-        let rank_of_hand_index = sorted_hands.into_iter().enumerate()
-            .map(|(rank, (i, _))| (i, (rank + 1) as i32))
-            .collect::<HashMap<_, _>>();
-        // let mut hands_with_rank = vec![];
-        // for (i, hand) in hands.drain(..).enumerate() {
-        //     let rank = *rank_of_hand_index.get(&i).unwrap();
-        //     hands_with_rank.push((hand, rank));
-        // }
-        // Or shorter:
-        let hands_with_rank = hands.into_iter().enumerate()
-            .map(|(i, hand)| (hand, *rank_of_hand_index.get(&i).unwrap()))
-            .collect::<Vec<_>>();
-        // let mut scored_hands = vec![];
-        // for (hand, rank) in hands_with_rank {
-        //     let winnings = rank * hand.bid;
-        //     scored_hands.push(ScoredHand { hand, rank, winnings })
-        // }
-        // Or shorter:
-        let scored_hands = hands_with_rank.into_iter()
-            .map(|(hand, rank)| { let winnings = rank * hand.bid; ScoredHand { hand, rank, winnings } })
+    fn from_input(input: &str, rules: &Rules) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::from_hands(read_hands(input, rules)?, rules))
+    }
+
+    fn from_hands(hands: Vec<Hand>, rules: &Rules) -> Self {
+        // The sort is stable, so identical hands stay in input order
+        let mut sorted_indices: Vec<usize> = (0..hands.len()).collect();
+        sorted_indices.sort_by_key(|&i| &hands[i]);
+        let mut scores = vec![(0, 0, 0.0); hands.len()];
+        let mut lowest_rank = 1;
+        for group in sorted_indices.chunk_by(|&i, &j| hands[i].cmp(&hands[j]) == Ordering::Equal) {
+            let ties = group.len() - 1;
+            let average_rank = lowest_rank as f64 + ties as f64 / 2.0;
+            for (k, &i) in group.iter().enumerate() {
+                let bid = hands[i].bid as f64;
+                scores[i] = match rules.tie_break {
+                    TieBreak::InputOrder => (lowest_rank + k as u64, ties, (lowest_rank + k as u64) as f64 * bid),
+                    TieBreak::SharedRank => (lowest_rank, ties, lowest_rank as f64 * bid),
+                    TieBreak::AverageWinnings => (lowest_rank, ties, average_rank * bid),
+                };
+            }
+            lowest_rank += group.len() as u64;
+        }
+        let scored_hands = hands.into_iter().zip(scores)
+            .map(|(hand, (rank, ties, winnings))| ScoredHand { hand, rank, ties, winnings })
             .collect();
-        Ok(Self { scored_hands })
+        Self { scored_hands }
+    }
+
+    /// The scored hands in input order as a JSON array.
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.scored_hands)
+    }
+
+    /// Sum of rank times bid over all hands, which are the total winnings unless the tie break
+    /// averages ranks. Fails if the sum does not fit into 64 bits.
+    fn total_winnings(&self) -> Result<u64, &'static str> {
+        self.scored_hands.iter().try_fold(0u64, |sum, scored_hand| {
+            scored_hand.rank.checked_mul(scored_hand.hand.bid)
+                .and_then(|winnings| sum.checked_add(winnings))
+                .ok_or("Total winnings overflow")
+        })
+    }
+}
+
+fn read_hands(input: &str, rules: &Rules) -> Result<Vec<Hand>, Box<dyn Error>> {
+    input.lines().map(|line| Hand::from_line(line, &rules.j_value())).collect()
+}
+
+/// Scores each hand of the input, in input order, according to the given rules.
+pub fn score_hands(input: &str, rules: &Rules) -> Result<Vec<ScoredHand>, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input, rules)?.scored_hands)
+}
+
+/// Scores each hand of the input like `score_hands` and writes the scored hands as a JSON array.
+#[cfg(feature = "serde")]
+pub fn scored_hands_to_json(input: &str, rules: &Rules) -> Result<String, Box<dyn Error>> {
+    Ok(Puzzle::from_input(input, rules)?.to_json()?)
+}
+
+/// Scores hands as if each of them were added to a pile of hands
+pub struct Scorer {
+    rules: Rules,
+    /// The hands of the pile from weakest to strongest
+    pile: Vec<Hand>,
+}
+
+/// Type, rank and winnings of a hand added to the pile of a `Scorer`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    pub hand_type: HandType,
+    pub rank: u64,
+    /// Number of hands in the pile with exactly the same cards
+    pub ties: usize,
+    pub winnings: f64,
+}
+
+impl Scorer {
+    pub fn from_input(input: &str, rules: Rules) -> Result<Self, Box<dyn Error>> {
+        let mut pile = read_hands(input, &rules)?;
+        pile.sort();
+        Ok(Self { rules, pile })
+    }
+
+    /// Scores a line like "KK677 28", where the hand comes after all identical hands of the pile.
+    pub fn score(&self, line: &str) -> Result<Score, Box<dyn Error>> {
+        let hand = Hand::from_line(line, &self.rules.j_value())?;
+        let num_weaker = self.pile.partition_point(|other| other < &hand);
+        let ties = self.pile.partition_point(|other| other <= &hand) - num_weaker;
+        let lowest_rank = num_weaker as u64 + 1;
+        let bid = hand.bid as f64;
+        let (rank, winnings) = match self.rules.tie_break {
+            TieBreak::InputOrder => (lowest_rank + ties as u64, (lowest_rank + ties as u64) as f64 * bid),
+            TieBreak::SharedRank => (lowest_rank, lowest_rank as f64 * bid),
+            TieBreak::AverageWinnings => (lowest_rank, (lowest_rank as f64 + ties as f64 / 2.0) * bid),
+        };
+        Ok(Score { hand_type: hand.hand_type, rank, ties, winnings })
+    }
+
+    /// Scores each line of `input` until it ends, writing the hand, its type, rank and winnings
+    /// separated by tabs, or "error" and the reason if the line cannot be scored.
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            match self.score(&line) {
+                Ok(score) => writeln!(output, "{line}\t{:?}\t{}\t{}", score.hand_type, score.rank, score.winnings)?,
+                Err(e) => writeln!(output, "{line}\terror\t{e}")?,
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// How one player's pile fared when ranked together with the piles of the other players
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub hands: usize,
+    /// Rank of the player's strongest hand in the joint ranking, or 0 if the pile is empty
+    pub best_rank: u64,
+    pub winnings: f64,
+}
+
+/// Ranks the hands of all piles together and sums up the winnings of each pile, in the order of
+/// the piles. With `TieBreak::InputOrder`, identical hands of earlier piles are weaker.
+pub fn tournament(piles: &[&str], rules: &Rules) -> Result<Vec<Standing>, Box<dyn Error>> {
+    let mut hands = Vec::new();
+    let mut owners = Vec::new();
+    for (player, pile) in piles.iter().enumerate() {
+        let pile_hands = read_hands(pile, rules).map_err(|e| format!("Pile {}: {e}", player + 1))?;
+        owners.extend(std::iter::repeat_n(player, pile_hands.len()));
+        hands.extend(pile_hands);
+    }
+    let puzzle = Puzzle::from_hands(hands, rules);
+    let mut standings = vec![Standing { hands: 0, best_rank: 0, winnings: 0.0 }; piles.len()];
+    for (scored_hand, &player) in puzzle.scored_hands.iter().zip(&owners) {
+        let standing = &mut standings[player];
+        standing.hands += 1;
+        standing.best_rank = standing.best_rank.max(scored_hand.rank);
+        standing.winnings += scored_hand.winnings;
+    }
+    Ok(standings)
+}
+
+/// Outcome of playing every hand of one pile against every hand of another pile
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadToHead {
+    /// Number of pairs of hands won by the first and by the second pile
+    pub wins: [usize; 2],
+    /// Number of pairs of hands with exactly the same cards
+    pub draws: usize,
+    /// Winnings of both piles in the joint ranking of their hands
+    pub winnings: [f64; 2],
+}
+
+impl fmt::Display for HeadToHead {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} : {} ({} draws), winnings {} : {}",
+            self.wins[0], self.wins[1], self.draws, self.winnings[0], self.winnings[1])
     }
 }
 
- fn part1(input: &str) -> Result<i32, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, &Card::Jack)?;
-    Ok(puzzle.scored_hands.iter().fold(0, |sum, scored_hand| sum + scored_hand.winnings))
+/// Compares two piles hand by hand and by their winnings when ranked together.
+pub fn head_to_head(pile1: &str, pile2: &str, rules: &Rules) -> Result<HeadToHead, Box<dyn Error>> {
+    let hands1 = read_hands(pile1, rules).map_err(|e| format!("Pile 1: {e}"))?;
+    let mut hands2 = read_hands(pile2, rules).map_err(|e| format!("Pile 2: {e}"))?;
+    hands2.sort();
+    let mut wins = [0, 0];
+    let mut draws = 0;
+    for hand in &hands1 {
+        let weaker = hands2.partition_point(|other| other < hand);
+        let tied = hands2.partition_point(|other| other <= hand) - weaker;
+        wins[0] += weaker;
+        draws += tied;
+        wins[1] += hands2.len() - weaker - tied;
+    }
+    let standings = tournament(&[pile1, pile2], rules)?;
+    Ok(HeadToHead { wins, draws, winnings: [standings[0].winnings, standings[1].winnings] })
 }
 
-fn part2(input: &str) -> Result<i32, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input, &Card::Joker)?;
-    Ok(puzzle.scored_hands.iter().fold(0, |sum, scored_hand| sum + scored_hand.winnings))
+/// Both parts, solved from the same hands, read with J as jacks
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Vec<Hand>;
+
+    fn parse(input: &str) -> Result<Vec<Hand>, Box<dyn Error>> {
+        read_hands(input, &Rules::default())
+    }
+
+    fn part1(hands: &Vec<Hand>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Puzzle::from_hands(hands.clone(), &Rules::default()).total_winnings()?.into())
+    }
+
+    fn part2(hands: &Vec<Hand>) -> Result<Answer, Box<dyn Error>> {
+        let rules = Rules { jokers: true, ..Rules::default() };
+        Ok(Puzzle::from_hands(hands.iter().map(Hand::with_jokers).collect(), &rules).total_winnings()?.into())
+    }
+}
+
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as hands.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_lines(input, |line| Hand::from_line(line, &Card::Jack))
+}
+
+/// Writes the scored hands of the puzzle as JSON to `json_path` if there is one.
+fn write_json(puzzle: &Puzzle, json_path: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let Some(json_path) = json_path else {
+        return Ok(());
+    };
+    #[cfg(feature = "serde")]
+    {
+        println!("Writing scored hands to {json_path}");
+        fs::write(json_path, puzzle.to_json()?)?;
+        Ok(())
+    }
+    #[cfg(not(feature = "serde"))]
+    {
+        let _ = puzzle;
+        Err(format!("Cannot write {json_path}: built without the serde feature").into())
+    }
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
+    if let Some([line1, line2]) = config.compare {
+        for (part, jokers) in [false, true].into_iter().enumerate() {
+            let report = compare_hands(&line1, &line2, &Rules { jokers, ..Rules::default() })?;
+            println!("Part {}: {report}", part + 1);
+        }
+        return Ok(());
+    }
+
+    if let Some(opponent_path) = config.against {
+        for (part, (file_path, jokers)) in [(config.file_path1, false), (config.file_path2, true)].into_iter().enumerate() {
+            println!("Part {}: Reading files {file_path} and {opponent_path}", part + 1);
+            let rules = Rules { jokers, ..Rules::default() };
+            let result = head_to_head(&fs::read_to_string(file_path)?, &fs::read_to_string(&opponent_path)?, &rules)?;
+            println!("Head to head of part {}: {result}", part + 1);
+        }
+        return Ok(());
+    }
+
+    if config.serve {
+        let file_path = if config.jokers { config.file_path2 } else { config.file_path1 };
+        eprintln!("Serving: Reading file {file_path}");
+        let scorer = Scorer::from_input(&fs::read_to_string(file_path)?, Rules { jokers: config.jokers, ..Rules::default() })?;
+        scorer.serve(io::stdin().lock(), io::stdout().lock())?;
+        return Ok(());
+    }
+
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let hands = Day::parse(&fs::read_to_string(&config.file_path1)?)?;
+    let puzzle = Puzzle::from_hands(hands.clone(), &Rules::default());
+    println!("Result of part 1: {}", puzzle.total_winnings()?);
+    write_json(&puzzle, config.json_paths[0].as_deref())?;
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let hands = answer::parse_part2(hands, &config.file_path1, &config.file_path2, Day::parse)?;
+    let puzzle = Puzzle::from_hands(hands.iter().map(Hand::with_jokers).collect(), &Rules { jokers: true, ..Rules::default() });
+    println!("Result of part 2: {}", puzzle.total_winnings()?);
+    write_json(&puzzle, config.json_paths[1].as_deref())?;
 
     Ok(())
 }
@@ -229,8 +589,8 @@ QQQJA 483
 
     #[test]
     fn ranks() {
-        let puzzle = Puzzle::from_input(EXAMPLE.trim(), &Card::Jack).unwrap();
-        let expected_ranks = vec![1, 4, 3, 2, 5];
+        let puzzle = Puzzle::from_input(EXAMPLE.trim(), &Rules::default()).unwrap();
+        let expected_ranks = [1, 4, 3, 2, 5];
         for (scored_hand, rank) in puzzle.scored_hands.iter().zip(expected_ranks.iter()) {
             assert_eq!(scored_hand.rank, *rank);
         }
@@ -244,24 +604,147 @@ QQQJA 483
 
     #[test]
     fn ranks_with_jokers() {
-        let puzzle = Puzzle::from_input(EXAMPLE.trim(), &Card::Joker).unwrap();
-        let expected_ranks = vec![1, 3, 2, 5, 4];
+        let puzzle = Puzzle::from_input(EXAMPLE.trim(), &Rules { jokers: true, ..Rules::default() }).unwrap();
+        let expected_ranks = [1, 3, 2, 5, 4];
         for (scored_hand, rank) in puzzle.scored_hands.iter().zip(expected_ranks.iter()) {
             assert_eq!(scored_hand.rank, *rank);
         }
     }
 
+    #[test]
+    fn validate_hands() {
+        assert!(validate(EXAMPLE.trim()).is_empty());
+        let issues = validate("32T3K 765\n32T3X 1\n32T3 1\nKK677 x");
+        let lines: Vec<_> = issues.iter().map(|issue| issue.line).collect();
+        assert_eq!(lines, vec![Some(2), Some(3), Some(4)]);
+    }
+
+    #[test]
+    fn tie_breaks() -> Result<(), Box<dyn Error>> {
+        let input = "KK677 10\n32T3K 1\nKK677 20\nKK677 30";
+        let scores = |tie_break| -> Result<Vec<_>, Box<dyn Error>> {
+            let scored_hands = score_hands(input, &Rules { jokers: false, tie_break })?;
+            Ok(scored_hands.iter().map(|h| (h.rank, h.ties, h.winnings)).collect())
+        };
+        assert_eq!(scores(TieBreak::InputOrder)?, vec![(2, 2, 20.0), (1, 0, 1.0), (3, 2, 60.0), (4, 2, 120.0)]);
+        assert_eq!(scores(TieBreak::SharedRank)?, vec![(2, 2, 20.0), (1, 0, 1.0), (2, 2, 40.0), (2, 2, 60.0)]);
+        assert_eq!(scores(TieBreak::AverageWinnings)?, vec![(2, 2, 30.0), (1, 0, 1.0), (2, 2, 60.0), (2, 2, 90.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn scorer() -> Result<(), Box<dyn Error>> {
+        let scorer = Scorer::from_input(EXAMPLE.trim(), Rules::default())?;
+        let score = scorer.score("KK677 10")?;
+        assert_eq!(score, Score { hand_type: HandType::TwoPair, rank: 4, ties: 1, winnings: 40.0 });
+        assert_eq!(scorer.score("22345 3")?.rank, 1);
+        let scorer = Scorer::from_input(EXAMPLE.trim(), Rules { jokers: true, tie_break: TieBreak::AverageWinnings })?;
+        let score = scorer.score("KTJJT 10")?;
+        assert_eq!(score, Score { hand_type: HandType::FourOfAKind, rank: 5, ties: 1, winnings: 55.0 });
+        Ok(())
+    }
+
+    #[test]
+    fn serve() -> Result<(), Box<dyn Error>> {
+        let scorer = Scorer::from_input(EXAMPLE.trim(), Rules::default())?;
+        let mut output = Vec::new();
+        scorer.serve("AAAAA 2\nAAAA\n".as_bytes(), &mut output)?;
+        assert_eq!(String::from_utf8(output)?, "AAAAA 2\tFiveOfAKind\t6\t12\nAAAA\terror\tHands must consist of five cards\n");
+        Ok(())
+    }
+
+    #[test]
+    fn tournament_standings() -> Result<(), Box<dyn Error>> {
+        // Same pile as the example, split between two players, plus an empty third pile
+        let standings = tournament(&["32T3K 765\nKK677 28", "T55J5 684\nKTJJT 220\nQQQJA 483", ""], &Rules::default())?;
+        assert_eq!(standings, vec![
+            Standing { hands: 2, best_rank: 3, winnings: 765.0 + 3.0 * 28.0 },
+            Standing { hands: 3, best_rank: 5, winnings: 4.0 * 684.0 + 2.0 * 220.0 + 5.0 * 483.0 },
+            Standing { hands: 0, best_rank: 0, winnings: 0.0 },
+        ]);
+        let total: f64 = standings.iter().map(|standing| standing.winnings).sum();
+        assert_eq!(total, 6440.0);
+        let err = tournament(&["32T3K 765", "32T3 1"], &Rules::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Pile 2: Hands must consist of five cards");
+        Ok(())
+    }
+
+    #[test]
+    fn head_to_head_against_friend() -> Result<(), Box<dyn Error>> {
+        let result = head_to_head("KK677 10\n32T3K 1", "KK677 20\nAAAAA 1", &Rules::default())?;
+        // KK677 beats nothing and ties once, 32T3K loses twice
+        assert_eq!(result.wins, [0, 3]);
+        assert_eq!(result.draws, 1);
+        // Joint ranking: 32T3K, KK677 (pile 1), KK677 (pile 2), AAAAA
+        assert_eq!(result.winnings, [1.0 + 2.0 * 10.0, 3.0 * 20.0 + 4.0]);
+        assert_eq!(result.to_string(), "0 : 3 (1 draws), winnings 21 : 64");
+        Ok(())
+    }
+
+    #[test]
+    fn explained_comparisons() -> Result<(), Box<dyn Error>> {
+        let report = compare_hands("T55J5 0", "QQQJA 0", &Rules::default())?;
+        assert_eq!(report.ordering, Ordering::Less);
+        assert_eq!(report.decided_by, DecidedBy::Card(0));
+        assert_eq!(report.to_string(), "QQQJA beats T55J5: both are ThreeOfAKind, and card 1 is Q against T");
+        let report = compare_hands("T55J5 0", "QQQJA 0", &Rules { jokers: true, ..Rules::default() })?;
+        assert_eq!(report.to_string(), "QQQJA beats T55J5: both are FourOfAKind, and card 1 is Q against T");
+        let report = compare_hands("KTJJT 0", "KK677 0", &Rules { jokers: true, ..Rules::default() })?;
+        assert_eq!(report.hand_types, [HandType::FourOfAKind, HandType::TwoPair]);
+        assert_eq!(report.to_string(), "KTJJT beats KK677: FourOfAKind beats TwoPair");
+        let report = compare_hands("KK677 1", "KK677 2", &Rules::default())?;
+        assert_eq!((report.ordering, report.decided_by), (Ordering::Equal, DecidedBy::Tie));
+        assert_eq!(report.to_string(), "KK677 ties with KK677: both have the same cards");
+        // The explanation agrees with the ranking of hands
+        let lines: Vec<&str> = EXAMPLE.trim().lines().collect();
+        for rules in [Rules::default(), Rules { jokers: true, ..Rules::default() }] {
+            for &line1 in &lines {
+                for &line2 in &lines {
+                    let (hand1, hand2) = (Hand::from_line(line1, &rules.j_value())?, Hand::from_line(line2, &rules.j_value())?);
+                    assert_eq!(compare_hands(line1, line2, &rules)?.ordering, hand1.cmp(&hand2));
+                }
+            }
+        }
+        let err = compare_hands("KK677 1", "KK67 2", &Rules::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Hand 2: Hands must consist of five cards");
+        Ok(())
+    }
+
     #[test]
     fn example_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE.trim())?;
+        let result = solve_part1(EXAMPLE.trim())?;
         assert_eq!(result, 6440);
         Ok(())
     }
 
     #[test]
     fn example_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE.trim())?;
+        let result = solve_part2(EXAMPLE.trim())?;
         assert_eq!(result, 5905);
         Ok(())
     }
+
+    #[test]
+    fn winnings_beyond_32_bits() -> Result<(), Box<dyn Error>> {
+        let result = solve_part1("32T3K 3000000000\nKK677 2000000000")?;
+        assert_eq!(result, 3_000_000_000u64 + 2 * 2_000_000_000);
+        assert!(solve_part1("32T3K 1\nKK677 18446744073709551615").is_err());
+        assert!(solve_part1("32T3K -1").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() -> Result<(), Box<dyn Error>> {
+        let puzzle = Puzzle::from_input("32T3K 765\nT55J5 684", &Rules::default())?;
+        let json = puzzle.to_json()?;
+        let scored_hands: Vec<ScoredHand> = serde_json::from_str(&json)?;
+        assert_eq!(scored_hands.len(), 2);
+        assert_eq!(scored_hands[1].hand, puzzle.scored_hands[1].hand);
+        assert_eq!((scored_hands[1].rank, scored_hands[1].winnings), (2, 1368.0));
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(value[0]["hand"]["cards"][0], "Three");
+        assert_eq!(value[0]["hand"]["hand_type"], "OnePair");
+        Ok(())
+    }
 }
@@ -1,17 +1,40 @@
+use crate::Solver;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// How part 2 is solved
+    pub solver: Solver,
+    /// File to which the goal timelines of the ghosts of part 2 are written as JSON
+    pub json_path: Option<String>,
+    /// File to which the goal timelines of the ghosts of part 2 are written as CSV
+    pub csv_path: Option<String>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day8 <file for part 1> <file for part 2> [--exact] [--json <file>] [--csv <file>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut solver = Solver::Lcm;
+        let mut json_path = None;
+        let mut csv_path = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--exact" => solver = Solver::Exact,
+                "--json" => json_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--csv" => csv_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            solver,
+            json_path,
+            csv_path,
         })
     }
 }
-
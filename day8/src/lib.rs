@@ -1,13 +1,15 @@
 pub mod config;
 
-use num::integer::lcm;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use aoc_core::answer::{Answer, PartSolver};
+use aoc_core::math::{crt, gcd, lcm};
+use aoc_core::parse::{self, ParseError, Parser};
+use aoc_core::validate::Issue;
 use std::cell::Cell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
-use typed_arena::Arena;
+pub use typed_arena::Arena;
 
 enum Instruction {
     L,
@@ -26,44 +28,145 @@ impl TryFrom<char> for Instruction {
     }
 }
 
+/// Number of lines of the instructions, which end at the first blank line
+fn num_instruction_lines(input: &str) -> usize {
+    input.lines().take_while(|line| !line.trim().is_empty()).count()
+}
+
+/// Reads the instructions, ignoring whitespace and line breaks since editors may hard-wrap the
+/// long instruction string. Reports every invalid character.
+fn read_instructions(input: &str) -> Result<Vec<Instruction>, Vec<Issue>> {
+    let mut instructions = Vec::new();
+    let mut issues = Vec::new();
+    for (i, line) in input.lines().take(num_instruction_lines(input)).enumerate() {
+        for (j, c) in line.chars().enumerate().filter(|(_, c)| !c.is_whitespace()) {
+            match Instruction::try_from(c) {
+                Ok(instruction) => instructions.push(instruction),
+                Err(e) => issues.push(Issue::at(i + 1, j + 1, e)),
+            }
+        }
+    }
+    if instructions.is_empty() && issues.is_empty() {
+        issues.push(Issue::at_line(1, "Expected instructions"));
+    }
+    if issues.is_empty() { Ok(instructions) } else { Err(issues) }
+}
+
 // For some interesting reads on how to define graphs in Rust, see these:
 // https://github.com/nrc/r4cppp/blob/master/graphs/README.md
 // https://crates.io/crates/typed-arena
-struct Node<'a> {
+pub struct Node<'a> {
     label: &'a str,
     left: Cell<Option<&'a Node<'a>>>,
     right: Cell<Option<&'a Node<'a>>>,
 }
 
 impl<'a> Node<'a> {
-    fn apply_instructions(&self, instructions: &Vec<Instruction>) -> &Node<'a> {
-        let mut node = self;
-        for instruction in instructions {
-            match instruction {
-                Instruction::L => node = node.left.get().unwrap(),
-                Instruction::R => node = node.right.get().unwrap(),
+    fn apply_instruction(&self, instruction: &Instruction) -> &'a Node<'a> {
+        match instruction {
+            Instruction::L => self.left.get().unwrap(),
+            Instruction::R => self.right.get().unwrap(),
+        }
+    }
+}
+
+/// A reference from a node to an undefined node
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DanglingReference {
+    pub node: String,
+    /// The instruction following the reference, 'L' or 'R'
+    pub instruction: char,
+    pub target: String,
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = if self.instruction == 'L' { "left" } else { "right" };
+        write!(f, "Node {} refers to undefined node {} on the {side}", self.node, self.target)
+    }
+}
+
+/// Structure of a network: its strongly connected components, the nodes a walk from AAA never
+/// visits and references to undefined nodes
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetworkHealth {
+    pub num_nodes: usize,
+    /// Number of strongly connected components
+    pub num_components: usize,
+    /// Labels of the nodes that cannot be reached from AAA, sorted, or `None` if there is no node
+    /// AAA
+    pub unreachable: Option<Vec<String>>,
+    /// References to undefined nodes, sorted by the referring node
+    pub dangling: Vec<DanglingReference>,
+}
+
+/// Greatest number of unreachable nodes listed in a warning
+const MAX_LISTED: usize = 10;
+
+impl NetworkHealth {
+    /// Describes each dangling reference and the unreachable nodes, if any.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self.dangling.iter().map(DanglingReference::to_string).collect();
+        if let Some(unreachable) = self.unreachable.as_ref().filter(|unreachable| !unreachable.is_empty()) {
+            let mut listed = unreachable[..unreachable.len().min(MAX_LISTED)].join(", ");
+            if unreachable.len() > MAX_LISTED {
+                listed.push_str(&format!(" and {} more", unreachable.len() - MAX_LISTED));
             }
+            warnings.push(format!("{} nodes unreachable from AAA: {listed}", unreachable.len()));
+        }
+        warnings
+    }
+}
+
+impl fmt::Display for NetworkHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes in {} strongly connected components", self.num_nodes, self.num_components)?;
+        match &self.unreachable {
+            Some(unreachable) => write!(f, ", {} unreachable from AAA", unreachable.len())?,
+            None => write!(f, ", no node AAA")?,
         }
-        node
+        write!(f, ", {} dangling references", self.dangling.len())
     }
 }
 
-struct Network<'a> {
+pub struct Network<'a> {
     nodes: HashMap<&'a str, &'a Node<'a>>,
+    /// References to undefined nodes as the referring node, the instruction and the target,
+    /// sorted. The references are left unset.
+    dangling: Vec<(&'a str, char, &'a str)>,
 }
 
 impl<'a> Network<'a> {
-    fn from_iter(iter: impl Iterator<Item = &'a str>, arena: &'a Arena<Node<'a>>) -> Result<Self, &'a str> {
+    /// Reads a line of the form "AAA = (BBB, CCC)" into the label and its left and right neighbors.
+    fn read_node(line: &str) -> Result<(&str, &str, &str), ParseError> {
+        parse::parse_all(line, |parser| {
+            let label = Self::read_label(parser)?;
+            parser.tag(" = (")?;
+            let left = Self::read_label(parser)?;
+            parser.tag(", ")?;
+            let right = Self::read_label(parser)?;
+            parser.tag(")")?;
+            Ok((label, left, right))
+        })
+    }
+
+    /// Reads a label consisting of three uppercase letters or digits.
+    fn read_label<'b>(parser: &mut Parser<'b>) -> Result<&'b str, ParseError> {
+        let column = parser.column();
+        let label = parser.word("node label", |c| c.is_ascii_uppercase() || c.is_ascii_digit())?;
+        if label.len() != 3 {
+            return Err(ParseError::new(column, format!("Node label {label} does not have three characters")));
+        }
+        Ok(label)
+    }
+
+    /// Reads the nodes from the lines of the network, where `first_line_nr` is the 1-based number
+    /// of the first of these lines in the input.
+    fn from_iter(iter: impl Iterator<Item = &'a str>, first_line_nr: usize, arena: &'a Arena<Node<'a>>) -> Result<Self, Box<dyn Error>> {
         let mut nodes: HashMap<&str, &Node<'a>> = HashMap::new();
         let mut edges: HashMap<&str, (&str, &str)> = HashMap::new();
-        static NODE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(
-            r"^(?<node>[A-Z0-9]{3}) = \((?<left>[A-Z0-9]{3}), (?<right>[A-Z0-9]{3})\)$"
-        ).unwrap());
-        for line in iter {
-            let captures = NODE_RE.captures(line).ok_or("Syntax error")?;
-            let label = captures.name("node").unwrap().as_str();
-            let left = captures.name("left").unwrap().as_str();
-            let right = captures.name("right").unwrap().as_str();
+        for (i, line) in iter.enumerate() {
+            let (label, left, right) = Self::read_node(line).map_err(|e| e.on_line(first_line_nr + i))?;
             edges.insert(label, (left, right));
             nodes.insert(label, arena.alloc(Node {
                 label,
@@ -71,100 +174,581 @@ impl<'a> Network<'a> {
                 right: Cell::new(None),
             }));
         }
-        for (node, (left, right)) in edges {
-            let node = nodes.get(node).unwrap();
-            let left = nodes.get(left).unwrap();
-            let right = nodes.get(right).unwrap();
-            node.left.set(Some(left));
-            node.right.set(Some(right));
+        let mut dangling = Vec::new();
+        for (label, (left, right)) in edges {
+            let node = nodes[label];
+            for (cell, instruction, target) in [(&node.left, 'L', left), (&node.right, 'R', right)] {
+                match nodes.get(target) {
+                    Some(&target) => cell.set(Some(target)),
+                    None => dangling.push((label, instruction, target)),
+                }
+            }
         }
-        Ok(Self { nodes })
+        dangling.sort();
+        Ok(Self { nodes, dangling })
     }
 
-    fn num_applications_to_goal(
-        &self, instructions: &Vec<Instruction>, node: &Node, goal_condition: impl Fn(&Node) -> bool
-    ) -> usize {
-        // How many times we must fully apply all instructions to reach a goal from the given node
-        let mut node = node;
-        let mut i = 0;
-        // Apparently we can only reach the goal when applying the whole instructions a whole
-        // number of times, but I'm not sure why we couldn't reach the goal after, say, applying
-        // the instructions once fully and then only half of them...
-        while !goal_condition(node) {
-            node = node.apply_instructions(&instructions);
-            i += 1;
+    /// Counts the strongly connected components, finds the nodes that cannot be reached from AAA
+    /// and lists the references to undefined nodes.
+    pub fn health_check(&self) -> NetworkHealth {
+        let mut labels: Vec<&str> = self.nodes.keys().copied().collect();
+        labels.sort();
+        let index: HashMap<&str, usize> = labels.iter().enumerate().map(|(i, &label)| (label, i)).collect();
+        let successors: Vec<Vec<usize>> = labels.iter().map(|label| {
+            let node = self.nodes[label];
+            [node.left.get(), node.right.get()].into_iter().flatten().map(|target| index[target.label]).collect()
+        }).collect();
+        let n = labels.len();
+
+        // Kosaraju's algorithm: order the nodes by when a depth-first search finishes them, then
+        // collect the components in the reversed graph in reverse finishing order
+        let mut visited = vec![false; n];
+        let mut finished = Vec::with_capacity(n);
+        for start in 0..n {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some((v, next)) = stack.last_mut() {
+                if let Some(&w) = successors[*v].get(*next) {
+                    *next += 1;
+                    if !visited[w] {
+                        visited[w] = true;
+                        stack.push((w, 0));
+                    }
+                } else {
+                    finished.push(*v);
+                    stack.pop();
+                }
+            }
+        }
+        let mut predecessors = vec![Vec::new(); n];
+        for (v, targets) in successors.iter().enumerate() {
+            for &w in targets {
+                predecessors[w].push(v);
+            }
+        }
+        let mut assigned = vec![false; n];
+        let mut num_components = 0;
+        for &start in finished.iter().rev() {
+            if assigned[start] {
+                continue;
+            }
+            assigned[start] = true;
+            let mut stack = vec![start];
+            while let Some(v) = stack.pop() {
+                for &w in &predecessors[v] {
+                    if !assigned[w] {
+                        assigned[w] = true;
+                        stack.push(w);
+                    }
+                }
+            }
+            num_components += 1;
+        }
+
+        let unreachable = index.get("AAA").map(|&start| {
+            let mut reached = vec![false; n];
+            reached[start] = true;
+            let mut stack = vec![start];
+            while let Some(v) = stack.pop() {
+                for &w in &successors[v] {
+                    if !reached[w] {
+                        reached[w] = true;
+                        stack.push(w);
+                    }
+                }
+            }
+            labels.iter().zip(reached).filter(|(_, reached)| !reached).map(|(label, _)| label.to_string()).collect()
+        });
+
+        NetworkHealth {
+            num_nodes: n,
+            num_components,
+            unreachable,
+            dangling: self.dangling.iter().map(|&(node, instruction, target)| DanglingReference {
+                node: node.to_string(),
+                instruction,
+                target: target.to_string(),
+            }).collect(),
         }
-        i
     }
+}
+
+/// Precomputed information about applying the whole instructions to each node
+struct JumpTable<'a> {
+    /// The node reached from each node after applying all instructions
+    jumps: HashMap<&'a str, &'a Node<'a>>,
+    /// For each node v, all pairs (u, k) such that v is reached from u after k steps, where k is
+    /// between 1 and the number of instructions
+    visits: HashMap<&'a str, Vec<(&'a str, usize)>>,
+}
 
-    fn distance_to_goal(&self, instructions: &Vec<Instruction>) -> usize {
-        let applications = self.num_applications_to_goal(&instructions, self.nodes["AAA"], |node| node.label == "ZZZ");
-        applications * instructions.len()
+impl<'a> JumpTable<'a> {
+    fn new(network: &Network<'a>, instructions: &[Instruction]) -> Self {
+        let mut jumps = HashMap::new();
+        let mut visits: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+        for (&label, &start) in &network.nodes {
+            let mut node = start;
+            for (k, instruction) in instructions.iter().enumerate() {
+                node = node.apply_instruction(instruction);
+                visits.entry(node.label).or_default().push((label, k + 1));
+            }
+            jumps.insert(label, node);
+        }
+        Self { jumps, visits }
     }
+}
 
-    fn ghost_distance_to_goal(&self, instructions: &Vec<Instruction>) -> usize {
-        let goal_condition = |node: &Node| node.label.chars().last().unwrap() == 'Z';
-        // Apparently, when a ghost reaches a goal and applies the same instructions for reaching
-        // it again, it will visit the exact same nodes. I'm not sure why.
-        self.nodes.iter()
-            .filter(|(&label, _)| label.chars().last().unwrap() == 'A')
-            .map(|(_, &node)| node)
-            .map(|node| self.num_applications_to_goal(instructions, node, goal_condition))
-            .reduce(|acc, x| lcm(acc, x))
-            .expect("Found no starting nodes")
-            * instructions.len()
+/// The loop that a walk from some node ends up in, observed at the start of each pass through the
+/// instructions
+#[derive(Debug, Eq, PartialEq)]
+pub struct Cycle {
+    /// Number of passes through the instructions before the loop is entered
+    pub lead_in: usize,
+    /// Nodes at the start of each pass in the loop, in the order in which they are visited
+    pub nodes: Vec<String>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "after {} pass(es) through the instructions, loops every {} pass(es) via {}",
+            self.lead_in, self.nodes.len(), self.nodes.join(", ")
+        )
+    }
+}
+
+/// How to find the number of steps until all ghosts are on goals simultaneously
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Solver {
+    /// Take the least common multiple of the distances to the first goals, which is only correct if
+    /// each ghost then loops back to its goal after exactly that many steps
+    #[default]
+    Lcm,
+    /// Analyze the walk of each ghost through the product of nodes and instruction positions,
+    /// which is correct for any input
+    Exact,
+}
+
+/// The steps at which a walk is on a goal. The walk through the states (node, instruction index)
+/// eventually repeats, so these are some steps before the loop and then a periodic pattern.
+#[derive(Debug, Eq, PartialEq)]
+struct GoalTimes {
+    /// Number of steps before the loop is entered
+    lead_in: usize,
+    /// Steps before the loop at which a goal is reached
+    early: HashSet<usize>,
+    /// Length of the loop in steps
+    period: usize,
+    /// Offsets into the loop at which a goal is reached
+    offsets: Vec<usize>,
+}
+
+impl GoalTimes {
+    fn contains(&self, step: usize) -> bool {
+        if step < self.lead_in {
+            self.early.contains(&step)
+        } else {
+            self.offsets.contains(&((step - self.lead_in) % self.period))
+        }
+    }
+}
+
+/// The steps at which a ghost stands on a goal, up to the end of the first pass through the loop
+/// its walk ends up in. Afterwards, the pattern of the loop repeats every `period` steps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Timeline {
+    /// Label of the node where the ghost starts
+    pub start: String,
+    /// Number of steps before the loop is entered
+    pub lead_in: usize,
+    /// Length of the loop in steps
+    pub period: usize,
+    /// Steps before `lead_in + period` at which the ghost is on a goal, in increasing order
+    pub steps: Vec<usize>,
+}
+
+impl Timeline {
+    fn new(start: &str, times: GoalTimes) -> Self {
+        let mut steps: Vec<usize> = times.early.into_iter()
+            .chain(times.offsets.iter().map(|offset| times.lead_in + offset))
+            .collect();
+        steps.sort_unstable();
+        Self { start: start.to_owned(), lead_in: times.lead_in, period: times.period, steps }
+    }
+
+    /// Whether the ghost is on a goal at exactly the positive multiples of the first positive step
+    /// at which it is, which is what the LCM shortcut for part 2 assumes
+    pub fn fits_lcm(&self) -> bool {
+        let mut positive = self.steps.iter().copied().filter(|&step| step > 0).peekable();
+        let Some(&first) = positive.peek() else { return false };
+        let end = self.lead_in + self.period;
+        self.period.is_multiple_of(first) && positive.eq((1..).map(|k| k * first).take_while(|&step| step < end))
+    }
+}
+
+/// Writes the timelines as a JSON array with one object per ghost.
+pub fn timelines_to_json(timelines: &[Timeline]) -> String {
+    let entries: Vec<String> = timelines.iter().map(|timeline| {
+        let steps: Vec<String> = timeline.steps.iter().map(|step| step.to_string()).collect();
+        format!(
+            "  {{\"start\": \"{}\", \"lead_in\": {}, \"period\": {}, \"steps\": [{}], \"fits_lcm\": {}}}",
+            timeline.start, timeline.lead_in, timeline.period, steps.join(", "), timeline.fits_lcm(),
+        )
+    }).collect();
+    if entries.is_empty() { String::from("[]\n") } else { format!("[\n{}\n]\n", entries.join(",\n")) }
+}
+
+/// Writes the timelines as CSV with the columns `start`, `lead_in`, `period`, `steps` and
+/// `fits_lcm`, where the steps are separated by spaces.
+pub fn timelines_to_csv(timelines: &[Timeline]) -> String {
+    let mut csv = String::from("start,lead_in,period,steps,fits_lcm\n");
+    for timeline in timelines {
+        let steps: Vec<String> = timeline.steps.iter().map(|step| step.to_string()).collect();
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n", timeline.start, timeline.lead_in, timeline.period, steps.join(" "), timeline.fits_lcm(),
+        ));
     }
+    csv
 }
 
-struct Puzzle<'a> {
+pub struct Puzzle<'a> {
     instructions: Vec<Instruction>,
     network: Network<'a>,
+    jump_table: JumpTable<'a>,
+    health: NetworkHealth,
 }
 
 impl<'a> Puzzle<'a> {
-    fn from_input(input: &'a str, arena: &'a Arena<Node<'a>>) -> Result<Self, Box<dyn Error>> {
-        let mut iter = input.lines();
-        let instructions: Vec<Instruction> = iter.next().ok_or("Expected instructions")?
-            .chars().map(|c| Instruction::try_from(c))
-            .collect::<Result<_, _>>()?;
-        if !iter.next().ok_or("Expected separating line")?.is_empty() {
-            return Err("Separating line not empty".into());
+    /// Reads the puzzle and checks the health of its network. Fails if the network has references
+    /// to undefined nodes.
+    pub fn from_input(input: &'a str, arena: &'a Arena<Node<'a>>) -> Result<Self, Box<dyn Error>> {
+        let (instructions, network) = Self::read_parts(input, arena)?;
+        let health = network.health_check();
+        Self::from_parts(instructions, network, health)
+    }
+
+    /// Puts together the puzzle from its parts and the health of its network. Fails if the network
+    /// has references to undefined nodes.
+    fn from_parts(instructions: Vec<Instruction>, network: Network<'a>, health: NetworkHealth) -> Result<Self, Box<dyn Error>> {
+        if !health.dangling.is_empty() {
+            return Err(health.dangling.iter().map(DanglingReference::to_string).collect::<Vec<_>>().join("\n").into());
         }
-        let network = Network::from_iter(iter, &arena)?;
-        Ok(Self { instructions, network })
+        let jump_table = JumpTable::new(&network, &instructions);
+        Ok(Self { instructions, network, jump_table, health })
+    }
+
+    /// Reads the instructions and the network, which may have references to undefined nodes
+    fn read_parts(input: &'a str, arena: &'a Arena<Node<'a>>) -> Result<(Vec<Instruction>, Network<'a>), Box<dyn Error>> {
+        let instructions = read_instructions(input).map_err(|issues| issues[0].to_string())?;
+        // The network starts after the instructions and a blank line
+        let num_lines = num_instruction_lines(input);
+        let mut iter = input.lines().skip(num_lines);
+        iter.next().ok_or("Expected separating line")?;
+        let network = Network::from_iter(iter, num_lines + 2, arena)?;
+        Ok((instructions, network))
+    }
+
+    pub fn network(&self) -> &Network<'a> {
+        &self.network
     }
 
-    fn distance_to_goal(&self) -> usize {
-        self.network.distance_to_goal(&self.instructions)
+    /// The health of the network, checked while reading the puzzle
+    pub fn health(&self) -> &NetworkHealth {
+        &self.health
     }
 
-    fn ghost_distance_to_goal(&self) -> usize {
-        self.network.ghost_distance_to_goal(&self.instructions)
+    /// Computes the number of steps needed to get from the node labeled `from_label` to any node
+    /// whose label satisfies `to_predicate`, or `None` if no such node is reachable.
+    pub fn distance(
+        &self, from_label: &str, to_predicate: impl Fn(&str) -> bool
+    ) -> Result<Option<usize>, &'static str> {
+        let start = *self.network.nodes.get(from_label).ok_or("Unknown node")?;
+        if to_predicate(start.label) {
+            return Ok(Some(0));
+        }
+        // For each node u, the first step at which a goal is reached when applying the
+        // instructions from u
+        let mut first_hit: HashMap<&str, usize> = HashMap::new();
+        for (_, visits) in self.jump_table.visits.iter().filter(|(&label, _)| to_predicate(label)) {
+            for &(label, k) in visits {
+                first_hit.entry(label).and_modify(|first| *first = k.min(*first)).or_insert(k);
+            }
+        }
+        let mut node = start;
+        let mut steps = 0;
+        let mut seen = HashSet::new();
+        while seen.insert(node.label) {
+            if let Some(k) = first_hit.get(node.label) {
+                return Ok(Some(steps + k));
+            }
+            node = self.jump_table.jumps[node.label];
+            steps += self.instructions.len();
+        }
+        Ok(None)
+    }
+
+    /// Finds the loop that repeatedly applying the instructions from the node labeled `from_label`
+    /// ends up in.
+    pub fn eventual_cycle(&self, from_label: &str) -> Result<Cycle, &'static str> {
+        let mut node = *self.network.nodes.get(from_label).ok_or("Unknown node")?;
+        let mut passes: Vec<&str> = Vec::new();
+        let mut seen_at_pass = HashMap::new();
+        while !seen_at_pass.contains_key(node.label) {
+            seen_at_pass.insert(node.label, passes.len());
+            passes.push(node.label);
+            node = self.jump_table.jumps[node.label];
+        }
+        let lead_in = seen_at_pass[node.label];
+        let nodes = passes[lead_in..].iter().map(|label| label.to_string()).collect();
+        Ok(Cycle { lead_in, nodes })
+    }
+
+    /// Like `distance`, but explains an unreachable goal by the loop the walk ends up in.
+    fn distance_or_cycle(&self, from_label: &str, to_predicate: impl Fn(&str) -> bool) -> Result<usize, String> {
+        match self.distance(from_label, to_predicate)? {
+            Some(distance) => Ok(distance),
+            None => Err(format!("Goal unreachable from {from_label}: {}", self.eventual_cycle(from_label)?)),
+        }
+    }
+
+    /// Walks from the node labeled `from_label` until a state (node, instruction index) repeats
+    /// and records when nodes whose labels satisfy `to_predicate` are visited.
+    fn goal_times(&self, from_label: &str, to_predicate: impl Fn(&str) -> bool) -> Result<GoalTimes, &'static str> {
+        let mut node = *self.network.nodes.get(from_label).ok_or("Unknown node")?;
+        let mut seen_at_step: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut goal_steps = Vec::new();
+        let mut step = 0;
+        let lead_in = loop {
+            let position = step % self.instructions.len();
+            if let Some(&first) = seen_at_step.get(&(node.label, position)) {
+                break first;
+            }
+            seen_at_step.insert((node.label, position), step);
+            if to_predicate(node.label) {
+                goal_steps.push(step);
+            }
+            node = node.apply_instruction(&self.instructions[position]);
+            step += 1;
+        };
+        let (early, looping): (Vec<usize>, Vec<usize>) = goal_steps.into_iter().partition(|&s| s < lead_in);
+        Ok(GoalTimes {
+            lead_in,
+            early: early.into_iter().collect(),
+            period: step - lead_in,
+            offsets: looping.into_iter().map(|s| s - lead_in).collect(),
+        })
+    }
+
+    /// Computes the least number of steps after which walks starting at all of `from_labels` are
+    /// simultaneously on nodes satisfying `to_predicate`, or `None` if this never happens.
+    pub fn simultaneous_distance(
+        &self, from_labels: &[&str], to_predicate: impl Fn(&str) -> bool
+    ) -> Result<Option<usize>, &'static str> {
+        let times = from_labels.iter()
+            .map(|label| self.goal_times(label, &to_predicate))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Before every walk is in its loop, the steps need to be checked one by one
+        let lead_in = times.iter().map(|t| t.lead_in).max().unwrap_or(0);
+        if let Some(step) = (0..lead_in).find(|&step| times.iter().all(|t| t.contains(step))) {
+            return Ok(Some(step));
+        }
+        // Afterwards, each walk is on a goal exactly at the steps congruent to one of its offsets,
+        // so combine the congruences of all walks
+        let mut classes: Vec<(i64, i64)> = vec![(0, 1)];
+        for t in &times {
+            let mut combined = Vec::new();
+            for &(residue, modulus) in &classes {
+                for &offset in &t.offsets {
+                    let congruence = ((t.lead_in + offset) as i64, t.period as i64);
+                    let g = gcd(modulus as u64, congruence.1 as u64) as i64;
+                    if (residue - congruence.0).rem_euclid(g) != 0 {
+                        continue;
+                    }
+                    combined.push(crt(&[(residue, modulus), congruence]).ok_or("Period too large")?);
+                }
+            }
+            combined.sort();
+            combined.dedup();
+            classes = combined;
+        }
+        let lead_in = lead_in as i64;
+        Ok(classes.into_iter()
+            .map(|(residue, modulus)| residue + ((lead_in - residue).max(0) as u64).div_ceil(modulus as u64) as i64 * modulus)
+            .min()
+            .map(|step| step as usize))
+    }
+
+    fn distance_to_goal(&self) -> Result<usize, String> {
+        self.distance_or_cycle("AAA", |label| label == "ZZZ")
+    }
+
+    fn ghost_starts(&self) -> Vec<&'a str> {
+        let mut starts: Vec<&str> = self.network.nodes.keys().copied().filter(|label| label.ends_with('A')).collect();
+        starts.sort();
+        starts
+    }
+
+    /// The timeline of each ghost of part 2, ordered by starting node
+    pub fn ghost_timelines(&self) -> Result<Vec<Timeline>, &'static str> {
+        self.ghost_starts().into_iter()
+            .map(|start| Ok(Timeline::new(start, self.goal_times(start, |label| label.ends_with('Z'))?)))
+            .collect()
+    }
+
+    fn ghost_distance_with(&self, solver: Solver) -> Result<usize, String> {
+        match solver {
+            Solver::Lcm => self.ghost_distance_to_goal(),
+            Solver::Exact => self.exact_ghost_distance_to_goal(),
+        }
+    }
+
+    fn exact_ghost_distance_to_goal(&self) -> Result<usize, String> {
+        let starts = self.ghost_starts();
+        if starts.is_empty() {
+            return Err("Found no starting nodes".to_string());
+        }
+        self.simultaneous_distance(&starts, |label| label.ends_with('Z'))?
+            .ok_or("Ghosts are never on goals simultaneously".to_string())
+    }
+
+    fn ghost_distance_to_goal(&self) -> Result<usize, String> {
+        // Apparently, when a ghost reaches a goal and applies the same instructions for reaching
+        // it again, it will visit the exact same nodes. I'm not sure why.
+        let starts = self.ghost_starts();
+        let mut distance = None;
+        let mut errors = Vec::new();
+        for start in starts {
+            match self.distance_or_cycle(start, |label| label.ends_with('Z')) {
+                Ok(d) => distance = Some(distance.map_or(d as u64, |acc| lcm(acc, d as u64))),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        distance.map(|distance| distance as usize).ok_or("Found no starting nodes".to_string())
     }
 }
 
-fn part1(input: &str) -> Result<usize, Box<dyn Error>> {
+/// Solves part 1 of the input.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
     let arena = Arena::new();
     let puzzle = Puzzle::from_input(input, &arena)?;
-    Ok(puzzle.distance_to_goal())
+    Ok(puzzle.distance_to_goal()?.into())
 }
 
-fn part2(input: &str) -> Result<usize, Box<dyn Error>> {
+fn part2(input: &str, solver: Solver) -> Result<Answer, Box<dyn Error>> {
     let arena = Arena::new();
     let puzzle = Puzzle::from_input(input, &arena)?;
-    Ok(puzzle.ghost_distance_to_goal())
+    Ok(puzzle.ghost_distance_with(solver)?.into())
+}
+
+/// The timeline of each ghost of part 2, ordered by starting node.
+pub fn ghost_timelines(input: &str) -> Result<Vec<Timeline>, Box<dyn Error>> {
+    let arena = Arena::new();
+    let puzzle = Puzzle::from_input(input, &arena)?;
+    Ok(puzzle.ghost_timelines()?)
+}
+
+/// Checks the health of the network of a puzzle, which, unlike when solving it, may have
+/// references to undefined nodes.
+pub fn network_health(input: &str) -> Result<NetworkHealth, Box<dyn Error>> {
+    let arena = Arena::new();
+    let (_, network) = Puzzle::read_parts(input, &arena)?;
+    Ok(network.health_check())
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    part2(input, Solver::Lcm)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports invalid instructions, syntax errors, duplicate node labels and references to undefined
+/// nodes.
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if input.lines().next().is_none() {
+        return vec![Issue::new("Expected instructions")];
+    }
+    if let Err(e) = read_instructions(input) {
+        issues.extend(e);
+    }
+    let num_lines = num_instruction_lines(input);
+    let mut lines = input.lines().skip(num_lines);
+    if lines.next().is_none() {
+        issues.push(Issue::at_line(num_lines + 1, "Expected empty separating line"));
+    }
+    let mut labels = HashSet::new();
+    let mut references = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line_nr = num_lines + 2 + i;
+        match Network::read_node(line) {
+            Ok((label, left, right)) => {
+                if !labels.insert(label) {
+                    issues.push(Issue::at_line(line_nr, format!("Duplicate node {label}")));
+                }
+                references.push((line_nr, left));
+                references.push((line_nr, right));
+            },
+            Err(e) => issues.push(Issue::at(line_nr, e.column, e.message)),
+        }
+    }
+    for (line_nr, label) in references {
+        if !labels.contains(label) {
+            issues.push(Issue::at_line(line_nr, format!("Reference to undefined node {label}")));
+        }
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Reads the puzzle like `Puzzle::from_input`, printing the warnings about its network first.
+fn read_with_warnings<'a>(input: &'a str, arena: &'a Arena<Node<'a>>) -> Result<Puzzle<'a>, Box<dyn Error>> {
+    let (instructions, network) = Puzzle::read_parts(input, arena)?;
+    let health = network.health_check();
+    for warning in health.warnings() {
+        println!("Warning: {warning}");
+    }
+    Puzzle::from_parts(instructions, network, health)
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
-    println!("Result of part 1: {result}");
+    let contents1 = fs::read_to_string(&config.file_path1)?;
+    let contents2;
+    let (arena1, arena2) = (Arena::new(), Arena::new());
+    let puzzle = read_with_warnings(&contents1, &arena1)?;
+    println!("Result of part 1: {}", puzzle.distance_to_goal()?);
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
-    println!("Result of part 2: {result}");
+    let puzzle = if config.file_path2 == config.file_path1 {
+        println!("Part 2: Reusing the input of part 1");
+        puzzle
+    } else {
+        println!("Part 2: Reading file {}", config.file_path2);
+        contents2 = fs::read_to_string(&config.file_path2)?;
+        read_with_warnings(&contents2, &arena2)?
+    };
+    println!("Result of part 2: {}", puzzle.ghost_distance_with(config.solver)?);
+    if config.json_path.is_some() || config.csv_path.is_some() {
+        let timelines = puzzle.ghost_timelines()?;
+        if let Some(json_path) = config.json_path {
+            println!("Writing ghost timelines of part 2 to {json_path}");
+            fs::write(json_path, timelines_to_json(&timelines))?;
+        }
+        if let Some(csv_path) = config.csv_path {
+            println!("Writing ghost timelines of part 2 to {csv_path}");
+            fs::write(csv_path, timelines_to_csv(&timelines))?;
+        }
+        if !timelines.iter().all(Timeline::fits_lcm) {
+            println!("Warning: not every ghost fits the assumptions of the LCM shortcut; try --exact");
+        }
+    }
 
     Ok(())
 }
@@ -206,24 +790,187 @@ LR
 XXX = (XXX, XXX)
 ";
 
+    #[test]
+    fn validate_network() {
+        assert!(validate(EXAMPLE1.trim()).is_empty());
+        let input = "LXR\n\nAAA = (BBB, CCC)\nBBB = (AAA, AAA)\nAAA = (BBB, BBB)\nCCC = BBB";
+        assert_eq!(validate(input), vec![
+            Issue::at(1, 2, "Unexpected instruction"),
+            Issue::at_line(3, "Reference to undefined node CCC"),
+            Issue::at_line(5, "Duplicate node AAA"),
+            Issue::at(6, 4, "Expected ' = ('"),
+        ]);
+    }
+
+    #[test]
+    fn wrapped_instructions() -> Result<(), Box<dyn Error>> {
+        // EXAMPLE2 with the instructions hard-wrapped and indented
+        let input = EXAMPLE2.trim().replacen("LLR", "L L\n  R\t", 1);
+        assert_eq!(solve_part1(&input)?, solve_part1(EXAMPLE2.trim())?);
+        assert!(validate(&input).is_empty());
+        // Lines of the network are counted after the wrapped instructions
+        let input = "LR\nLX\n\nAAA = (AAA, BBB)";
+        assert_eq!(validate(input), vec![Issue::at(2, 2, "Unexpected instruction"), Issue::at_line(4, "Reference to undefined node BBB")]);
+        let arena = Arena::new();
+        let error = |input| Puzzle::from_input(input, &arena).err().map(|e| e.to_string());
+        assert_eq!(error(input).as_deref(), Some("Line 2, column 2: Unexpected instruction"));
+        assert_eq!(error("LR\nL\n\nAAA = AAA").as_deref(), Some("Line 4, column 4: Expected ' = ('"));
+        assert_eq!(error("LR\nR").as_deref(), Some("Expected separating line"));
+        assert_eq!(error("\nAAA = (AAA, AAA)").as_deref(), Some("Line 1: Expected instructions"));
+        Ok(())
+    }
+
+    #[test]
+    fn network_health_checks() -> Result<(), Box<dyn Error>> {
+        let arena = Arena::new();
+        let puzzle = Puzzle::from_input(EXAMPLE1.trim(), &arena)?;
+        // Apart from self-loops, the walks only lead downwards
+        assert_eq!(puzzle.health(), &NetworkHealth { num_nodes: 7, num_components: 7, unreachable: Some(vec![]), dangling: vec![] });
+        assert!(puzzle.health().warnings().is_empty());
+        let health = network_health(EXAMPLE3.trim())?;
+        assert_eq!((health.num_components, health.unreachable), (5, None));
+        assert_eq!(puzzle.network().health_check().to_string(),
+            "7 nodes in 7 strongly connected components, 0 unreachable from AAA, 0 dangling references");
+        Ok(())
+    }
+
+    #[test]
+    fn dangling_references() -> Result<(), Box<dyn Error>> {
+        let input = "L\n\nAAA = (BBB, CCC)\nBBB = (AAA, BBB)\nDDD = (DDD, DDD)";
+        let health = network_health(input)?;
+        assert_eq!(health.num_components, 2);
+        assert_eq!(health.unreachable, Some(vec!["DDD".to_string()]));
+        assert_eq!(health.warnings(), [
+            "Node AAA refers to undefined node CCC on the right",
+            "1 nodes unreachable from AAA: DDD",
+        ]);
+        let arena = Arena::new();
+        let error = Puzzle::from_input(input, &arena).err().map(|e| e.to_string());
+        assert_eq!(error.as_deref(), Some("Node AAA refers to undefined node CCC on the right"));
+        Ok(())
+    }
+
     #[test]
     fn example1_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE1.trim())?;
+        let result = solve_part1(EXAMPLE1.trim())?;
         assert_eq!(result, 2);
         Ok(())
     }
 
     #[test]
     fn example2_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE2.trim())?;
+        let result = solve_part1(EXAMPLE2.trim())?;
         assert_eq!(result, 6);
         Ok(())
     }
 
     #[test]
     fn example3_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE3.trim())?;
+        let result = part2(EXAMPLE3.trim(), Solver::Lcm)?;
+        assert_eq!(result, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn example3_part2_exact() -> Result<(), Box<dyn Error>> {
+        let result = part2(EXAMPLE3.trim(), Solver::Exact)?;
         assert_eq!(result, 6);
         Ok(())
     }
+
+    #[test]
+    fn exact_solver_without_lcm_assumptions() -> Result<(), Box<dyn Error>> {
+        // 11A reaches 11Z after 1 step and then every 4 steps; 22A reaches 22Z after 3 steps and
+        // then every 2 steps. The LCM of the first distances is 3, but the ghosts meet at step 5.
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11C, 11C)
+11C = (11D, 11D)
+11D = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22D, 22D)
+22D = (22Z, 22Z)";
+        assert_eq!(part2(input, Solver::Lcm)?, 3);
+        assert_eq!(part2(input, Solver::Exact)?, 5);
+        // Both timelines refute the LCM shortcut
+        let timelines = ghost_timelines(input)?;
+        assert_eq!(timelines[0], Timeline { start: "11A".to_owned(), lead_in: 1, period: 4, steps: vec![1] });
+        assert_eq!(timelines[1], Timeline { start: "22A".to_owned(), lead_in: 3, period: 2, steps: vec![3] });
+        assert!(!timelines[0].fits_lcm() && !timelines[1].fits_lcm());
+        let arena = Arena::new();
+        let puzzle = Puzzle::from_input(input, &arena)?;
+        assert_eq!(puzzle.simultaneous_distance(&["11B", "22A"], |label| label.ends_with('Z'))?, Some(3));
+        assert_eq!(puzzle.simultaneous_distance(&["11Z"], |label| label.ends_with('Z'))?, Some(0));
+        assert_eq!(puzzle.simultaneous_distance(&["11A", "22A"], |label| label == "11Z")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn example3_goal_times() -> Result<(), Box<dyn Error>> {
+        let arena = Arena::new();
+        let puzzle = Puzzle::from_input(EXAMPLE3.trim(), &arena)?;
+        // 22A, 22B, 22C, 22Z, 22B, 22C, 22Z, ...; the state (22B, 1) repeats after 6 steps
+        let times = puzzle.goal_times("22A", |label| label.ends_with('Z'))?;
+        assert_eq!(times, GoalTimes { lead_in: 1, early: HashSet::new(), period: 6, offsets: vec![2, 5] });
+        assert!(times.contains(3) && times.contains(6) && !times.contains(7));
+        Ok(())
+    }
+
+    #[test]
+    fn example3_timelines() -> Result<(), Box<dyn Error>> {
+        let timelines = ghost_timelines(EXAMPLE3.trim())?;
+        assert_eq!(timelines, [
+            Timeline { start: "11A".to_owned(), lead_in: 1, period: 2, steps: vec![2] },
+            Timeline { start: "22A".to_owned(), lead_in: 1, period: 6, steps: vec![3, 6] },
+        ]);
+        assert!(timelines.iter().all(Timeline::fits_lcm));
+        assert_eq!(timelines_to_csv(&timelines), "start,lead_in,period,steps,fits_lcm\n11A,1,2,2,true\n22A,1,6,3 6,true\n");
+        assert_eq!(timelines_to_json(&timelines), concat!(
+            "[\n",
+            "  {\"start\": \"11A\", \"lead_in\": 1, \"period\": 2, \"steps\": [2], \"fits_lcm\": true},\n",
+            "  {\"start\": \"22A\", \"lead_in\": 1, \"period\": 6, \"steps\": [3, 6], \"fits_lcm\": true}\n",
+            "]\n",
+        ));
+        assert_eq!(timelines_to_json(&[]), "[]\n");
+        Ok(())
+    }
+
+    #[test]
+    fn example3_distance_queries() -> Result<(), Box<dyn Error>> {
+        let arena = Arena::new();
+        let puzzle = Puzzle::from_input(EXAMPLE3.trim(), &arena)?;
+        assert_eq!(puzzle.distance("11A", |label| label == "11Z")?, Some(2));
+        assert_eq!(puzzle.distance("22A", |label| label == "22Z")?, Some(3));
+        assert_eq!(puzzle.distance("22A", |label| label == "22C")?, Some(2));
+        assert_eq!(puzzle.distance("11Z", |label| label == "11Z")?, Some(0));
+        assert_eq!(puzzle.distance("11A", |label| label == "22Z")?, None);
+        assert_eq!(puzzle.distance("XXX", |label| label.ends_with('Z'))?, None);
+        assert!(puzzle.distance("ABC", |_| true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn example3_eventual_cycles() -> Result<(), Box<dyn Error>> {
+        let arena = Arena::new();
+        let puzzle = Puzzle::from_input(EXAMPLE3.trim(), &arena)?;
+        assert_eq!(puzzle.eventual_cycle("11A")?, Cycle { lead_in: 1, nodes: vec!["11Z".to_string()] });
+        assert_eq!(puzzle.eventual_cycle("22A")?, Cycle { lead_in: 1, nodes: vec!["22C".to_string(), "22B".to_string(), "22Z".to_string()] });
+        assert_eq!(puzzle.eventual_cycle("XXX")?, Cycle { lead_in: 0, nodes: vec!["XXX".to_string()] });
+        Ok(())
+    }
+
+    #[test]
+    fn unreachable_ghost_goals_reported() {
+        // 33A only ever visits itself and XXX, and so does 44A
+        let input = format!("{}\n33A = (XXX, XXX)\n44A = (44A, XXX)", EXAMPLE3.trim());
+        let error = part2(&input, Solver::Lcm).unwrap_err().to_string();
+        assert_eq!(error, concat!(
+            "Goal unreachable from 33A: after 1 pass(es) through the instructions, loops every 1 pass(es) via XXX\n",
+            "Goal unreachable from 44A: after 1 pass(es) through the instructions, loops every 1 pass(es) via XXX",
+        ));
+    }
 }
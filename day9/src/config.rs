@@ -1,17 +1,48 @@
+use crate::Method;
+
 pub struct Config {
     pub file_path1: String,
     pub file_path2: String,
+    /// Print how the sequences of part 1 behave under differencing
+    pub report: bool,
+    /// File to which the sequence profiles of part 1 are written as CSV
+    pub csv_path: Option<String>,
+    /// How values are extrapolated in both parts
+    pub method: Method,
+    /// Print the sum of the values this many steps past the sequences of part 1
+    pub ahead: Option<u64>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() != 3 {
-            return Err("Not enough arguments");
+        const USAGE: &str = "Usage: day9 <file for part 1> <file for part 2> [--report] [--csv <file>] [--binomial] [--ahead <steps>]";
+        if args.len() < 3 {
+            return Err(USAGE);
+        }
+        let mut report = false;
+        let mut csv_path = None;
+        let mut method = Method::DifferenceTable;
+        let mut ahead = None;
+        let mut iter = args[3..].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--report" => report = true,
+                "--csv" => csv_path = Some(iter.next().ok_or(USAGE)?.clone()),
+                "--binomial" => method = Method::Binomial,
+                "--ahead" => match iter.next().ok_or(USAGE)?.parse() {
+                    Ok(steps) if steps > 0 => ahead = Some(steps),
+                    _ => return Err(USAGE),
+                },
+                _ => return Err(USAGE),
+            }
         }
         Ok(Config {
             file_path1: args[1].clone(),
             file_path2: args[2].clone(),
+            report,
+            csv_path,
+            method,
+            ahead,
         })
     }
 }
-
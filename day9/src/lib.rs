@@ -1,16 +1,59 @@
 pub mod config;
 
+use aoc_core::answer::{self, Answer, PartSolver, Solution};
+use aoc_core::bigint::BigInt;
+use aoc_core::validate::{self, Issue};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::fmt::{self, Display};
 use std::fs;
-use std::num::ParseIntError;
+use std::ops::{Add, Neg, Sub};
 use std::str::FromStr;
 
-struct Sequence {
-    elements: Vec<i32>,
+/// Integer types that the elements of sequences can have
+pub trait Element:
+    Clone + PartialEq + FromStr<Err: Display> + Add<Output = Self> + Sub<Output = Self> + Neg<Output = Self> + Into<Answer>
+{
+    fn zero() -> Self;
+
+    fn to_bigint(&self) -> BigInt;
 }
 
-impl FromStr for Sequence {
-    type Err = ParseIntError;
+macro_rules! primitive_elements {
+    ($($t:ty),*) => {$(
+        impl Element for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn to_bigint(&self) -> BigInt {
+                BigInt::from(*self as i128)
+            }
+        }
+    )*};
+}
+
+primitive_elements!(i32, i64, i128);
+
+impl Element for BigInt {
+    fn zero() -> Self {
+        BigInt::zero()
+    }
+
+    fn to_bigint(&self) -> BigInt {
+        self.clone()
+    }
+}
+
+/// A sequence of values from the report, whose elements are `i64` unless a wider or narrower type
+/// is needed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence<T: Element = i64> {
+    elements: Vec<T>,
+}
+
+impl<T: Element> FromStr for Sequence<T> {
+    type Err = T::Err;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let elements = s.split(' ').map(|v| v.trim()).filter(|v| !v.is_empty())
@@ -20,67 +63,311 @@ impl FromStr for Sequence {
     }
 }
 
-impl Sequence {
-    fn from_differences(&self) -> Self {
-        if self.elements.len() < 2 {
-            Self { elements: vec![] }
-        } else {
-            Self {
-                elements: self.elements.iter().zip(self.elements.iter().skip(1)).map(|(a, b)| b - a).collect()
+impl<T: Element> Sequence<T> {
+    pub fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    fn differences(&self) -> Self {
+        Self {
+            elements: self.elements.windows(2).map(|pair| pair[1].clone() - pair[0].clone()).collect()
+        }
+    }
+
+    /// Returns the values before the first element and after the last one, building the table of
+    /// differences only once. The value after the last element is the sum of the last elements of
+    /// the rows, and the one before the first element is the alternating sum of their first ones.
+    pub fn extrapolate_both(&self) -> (T, T) {
+        let (mut previous, mut next) = (T::zero(), T::zero());
+        let mut add = true;
+        let mut row = self.clone();
+        while let (Some(first), Some(last)) = (row.elements.first(), row.elements.last()) {
+            if row.elements.iter().all(|i| *i == T::zero()) {
+                break;
             }
+            previous = if add { previous + first.clone() } else { previous - first.clone() };
+            next = next + last.clone();
+            add = !add;
+            row = row.differences();
         }
+        (previous, next)
     }
+}
 
-    fn extrapolate_next_value(&self, reverse: bool) -> i32 {
-        if self.elements.iter().all(|&i| i == 0) {
-            0
+const TOO_FAR: &str = "Too many steps to extrapolate";
+
+/// How values are extrapolated
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Method {
+    /// Build the table of differences and extend it by one element
+    #[default]
+    DifferenceTable,
+    /// Weight the elements with binomial coefficients, which also works for values far ahead
+    Binomial,
+}
+
+impl<T: Element> Sequence<T> {
+    /// Returns the value `steps` positions after the last element, or before the first one if
+    /// `reverse`, of the polynomial of least degree through the elements.
+    ///
+    /// For elements a_0, ..., a_{n-1} and m = n - 1 + steps, this is the sum of
+    /// (-1)^(n-1-i) C(m, i) C(m-i-1, n-1-i) a_i, which for m = n is the familiar
+    /// sum of (-1)^(n-1-i) C(n, i) a_i. `steps` must be positive. Fails if m exceeds the range
+    /// of `i64`.
+    pub fn extrapolate_binomial(&self, steps: u64, reverse: bool) -> Result<BigInt, &'static str> {
+        let elements: Vec<BigInt> = if reverse {
+            self.elements.iter().rev().map(T::to_bigint).collect()
         } else {
-            let next_sequence = self.from_differences();
-            let extrapolated = next_sequence.extrapolate_next_value(reverse);
-            if reverse {
-                self.elements.first().unwrap() - extrapolated
-            } else {
-                self.elements.last().unwrap() + extrapolated
+            self.elements.iter().map(T::to_bigint).collect()
+        };
+        let n = elements.len() as u64;
+        if n == 0 {
+            return Ok(BigInt::zero());
+        }
+        let m = (n - 1).checked_add(steps).ok_or(TOO_FAR)?;
+        let factor = |x: u64| i64::try_from(x).map_err(|_| TOO_FAR);
+        // C(m, i) and C(m-i-1, n-1-i) for the current i
+        let mut left = BigInt::from(1i64);
+        let mut right = (1..n).try_fold(BigInt::from(1i64), |c, k| Ok(c.mul_small(factor(m - k)?).div_rem_small(k).0))?;
+        let mut sum = BigInt::zero();
+        for (i, a) in elements.iter().enumerate() {
+            let i = i as u64;
+            let term = &(&left * &right) * a;
+            sum = if (n - 1 - i).is_multiple_of(2) { sum + term } else { sum - term };
+            if i + 1 < n {
+                left = left.mul_small(factor(m - i)?).div_rem_small(i + 1).0;
+                // C(a-1, b-1) = C(a, b) b / a for a = m-i-1 and b = n-1-i
+                right = right.mul_small(factor(n - 1 - i)?).div_rem_small(m - i - 1).0;
+            }
+        }
+        Ok(sum)
+    }
+}
+
+/// How a sequence behaves under repeated differencing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SequenceProfile {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub length: usize,
+    /// Number of times differences have to be taken until all elements are zero, or `None` if the
+    /// elements run out before that, in which case the extrapolation is not backed by the data
+    pub zero_depth: Option<usize>,
+}
+
+impl SequenceProfile {
+    /// Degree of the polynomial generating the sequence, where a sequence of zeros has degree 0
+    pub fn degree(&self) -> Option<usize> {
+        self.zero_depth.map(|depth| depth.saturating_sub(1))
+    }
+}
+
+impl<T: Element> Sequence<T> {
+    fn profile(&self, line: usize) -> SequenceProfile {
+        let mut sequence = self.clone();
+        let mut depth = 0;
+        while !sequence.elements.is_empty() && sequence.elements.iter().any(|i| *i != T::zero()) {
+            sequence = sequence.differences();
+            depth += 1;
+        }
+        let zero_depth = (!sequence.elements.is_empty()).then_some(depth);
+        SequenceProfile { line, length: self.elements.len(), zero_depth }
+    }
+}
+
+/// Profiles of all sequences of an input, with aggregate statistics
+pub struct Report {
+    pub sequences: Vec<SequenceProfile>,
+}
+
+impl Report {
+    pub fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
+        let sequences = input.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| Ok(Sequence::<i64>::from_str(line).map_err(|e| e.to_string())?.profile(i + 1)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self { sequences })
+    }
+
+    /// Number of sequences of each degree, where `None` counts the sequences whose degree is not
+    /// determined by the data
+    pub fn degree_histogram(&self) -> BTreeMap<Option<usize>, usize> {
+        let mut histogram = BTreeMap::new();
+        for profile in &self.sequences {
+            *histogram.entry(profile.degree()).or_default() += 1;
+        }
+        histogram
+    }
+
+    pub fn max_degree(&self) -> Option<usize> {
+        self.sequences.iter().filter_map(SequenceProfile::degree).max()
+    }
+
+    /// Mean degree of the sequences whose degree is determined
+    pub fn mean_degree(&self) -> Option<f64> {
+        let degrees: Vec<usize> = self.sequences.iter().filter_map(SequenceProfile::degree).collect();
+        (!degrees.is_empty()).then(|| degrees.iter().sum::<usize>() as f64 / degrees.len() as f64)
+    }
+
+    /// Writes the profiles as CSV with the columns `line`, `length`, `zero_depth` and `degree`,
+    /// leaving the last two empty for sequences whose degree is not determined.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line,length,zero_depth,degree\n");
+        for profile in &self.sequences {
+            let optional = |value: Option<usize>| value.map(|v| v.to_string()).unwrap_or_default();
+            csv.push_str(&format!("{},{},{},{}\n",
+                profile.line, profile.length, optional(profile.zero_depth), optional(profile.degree())));
+        }
+        csv
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Sequences: {}", self.sequences.len())?;
+        if let (Some(max), Some(mean)) = (self.max_degree(), self.mean_degree()) {
+            writeln!(f, "Max degree: {max}")?;
+            writeln!(f, "Mean degree: {mean:.2}")?;
+        }
+        writeln!(f, "Degrees:")?;
+        for (degree, n) in self.degree_histogram() {
+            match degree {
+                Some(degree) => writeln!(f, "{degree:>12} {n:>6}")?,
+                None => writeln!(f, "{:>12} {n:>6}", "undetermined")?,
             }
         }
+        Ok(())
     }
 }
 
-struct Puzzle {
-    histories: Vec<Sequence>,
+struct Puzzle<T: Element = i64> {
+    histories: Vec<Sequence<T>>,
 }
 
-impl Puzzle {
+impl<T: Element> Puzzle<T> {
     fn from_input(input: &str) -> Result<Self, Box<dyn Error>> {
         let histories = input.lines().map(|line| line.trim()).filter(|line| !line.is_empty())
-            .map(|line| Sequence::from_str(line)).collect::<Result<_, _>>()?;
+            .map(|line| Sequence::<T>::from_str(line).map_err(|e| e.to_string())).collect::<Result<_, _>>()?;
         Ok(Self { histories })
     }
 
-    fn sum_extrapolated_values(&self, reverse: bool) -> i32 {
-        self.histories.iter().map(|seq| seq.extrapolate_next_value(reverse)).sum()
+    fn sum_extrapolated_values(&self, reverse: bool, method: Method) -> Result<Answer, &'static str> {
+        let (previous, next) = self.sum_extrapolated_both(method)?;
+        Ok(if reverse { previous } else { next })
+    }
+
+    /// Sums of the values before the first and after the last elements of the sequences
+    fn sum_extrapolated_both(&self, method: Method) -> Result<(Answer, Answer), &'static str> {
+        Ok(match method {
+            Method::DifferenceTable => {
+                let (previous, next) = self.histories.iter().map(Sequence::extrapolate_both)
+                    .fold((T::zero(), T::zero()), |(p, n), (previous, next)| (p + previous, n + next));
+                (previous.into(), next.into())
+            },
+            Method::Binomial => (self.sum_extrapolated_ahead(1, true)?.into(), self.sum_extrapolated_ahead(1, false)?.into()),
+        })
     }
+
+    fn sum_extrapolated_ahead(&self, steps: u64, reverse: bool) -> Result<BigInt, &'static str> {
+        self.histories.iter().map(|seq| seq.extrapolate_binomial(steps, reverse)).sum()
+    }
+}
+
+/// Extrapolates each sequence `steps` positions past its end, or before its start if `reverse`,
+/// and returns the sum of these values. `steps` must be positive.
+pub fn sum_extrapolated_ahead(input: &str, steps: u64, reverse: bool) -> Result<BigInt, Box<dyn Error>> {
+    if steps == 0 {
+        return Err("Number of steps must be positive".into());
+    }
+    Ok(Puzzle::<i64>::from_input(input)?.sum_extrapolated_ahead(steps, reverse)?)
 }
 
-fn part1(input: &str) -> Result<i32, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    Ok(puzzle.sum_extrapolated_values(false))
+/// Extrapolates each sequence in both directions and returns the answers of part 2 and part 1,
+/// that is, the sums of the values before and after the sequences.
+pub fn sum_extrapolated_both(input: &str, method: Method) -> Result<(Answer, Answer), Box<dyn Error>> {
+    sum_extrapolated_both_as::<i64>(input, method)
 }
 
-fn part2(input: &str) -> Result<i32, Box<dyn Error>> {
-    let puzzle = Puzzle::from_input(input)?;
-    Ok(puzzle.sum_extrapolated_values(true))
+/// Like `sum_extrapolated_both`, but with elements and sums of type `T`, for example `i128` or
+/// `BigInt` for sequences whose values or sums overflow `i64`
+pub fn sum_extrapolated_both_as<T: Element>(input: &str, method: Method) -> Result<(Answer, Answer), Box<dyn Error>> {
+    Ok(Puzzle::<T>::from_input(input)?.sum_extrapolated_both(method)?)
+}
+
+/// Both parts, solved from the same sequences
+struct Day;
+
+impl Solution for Day {
+    type Parsed = Puzzle<i64>;
+
+    fn parse(input: &str) -> Result<Puzzle<i64>, Box<dyn Error>> {
+        Puzzle::from_input(input)
+    }
+
+    fn part1(puzzle: &Puzzle<i64>) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_extrapolated_values(false, Method::DifferenceTable)?)
+    }
+
+    fn part2(puzzle: &Puzzle<i64>) -> Result<Answer, Box<dyn Error>> {
+        Ok(puzzle.sum_extrapolated_values(true, Method::DifferenceTable)?)
+    }
+}
+
+/// Solves part 1 of the input with the default options.
+pub fn solve_part1(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part1::<Day>(input)
+}
+
+/// Solves part 2 of the input with the default options.
+pub fn solve_part2(input: &str) -> Result<Answer, Box<dyn Error>> {
+    answer::part2::<Day>(input)
+}
+
+/// Both parts with their default options
+pub const PARTS: [PartSolver; 2] = [solve_part1, solve_part2];
+
+/// Reports the lines that cannot be read as sequences of numbers.
+pub fn validate(input: &str) -> Vec<Issue> {
+    validate::check_lines(input, Sequence::<i64>::from_str)
 }
 
 pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
     println!("Part 1: Reading file {}", config.file_path1);
-    let contents = fs::read_to_string(config.file_path1)?;
-    let result = part1(&contents)?;
+    let contents = fs::read_to_string(&config.file_path1)?;
+    let puzzle = Day::parse(&contents)?;
+    // Both parts extrapolate the same sequences if they share their input
+    let both = (config.file_path1 == config.file_path2).then(|| puzzle.sum_extrapolated_both(config.method)).transpose()?;
+    let result = match &both {
+        Some((_, next)) => next.clone(),
+        None => puzzle.sum_extrapolated_values(false, config.method)?,
+    };
     println!("Result of part 1: {result}");
 
-    println!("Part 2: Reading file {}", config.file_path2);
-    let contents = fs::read_to_string(config.file_path2)?;
-    let result = part2(&contents)?;
+    if let Some(steps) = config.ahead {
+        println!("Sum of values {steps} step(s) ahead: {}", sum_extrapolated_ahead(&contents, steps, false)?);
+    }
+
+    if config.report || config.csv_path.is_some() {
+        let report = Report::from_input(&contents)?;
+        if config.report {
+            print!("{report}");
+        }
+        if let Some(csv_path) = config.csv_path {
+            println!("Writing sequence profiles of part 1 to {csv_path}");
+            fs::write(csv_path, report.to_csv())?;
+        }
+    }
+
+    let result = match both {
+        Some((previous, _)) => {
+            println!("Part 2: Reusing the sequences of part 1");
+            previous
+        },
+        None => {
+            let puzzle = answer::parse_part2(puzzle, &config.file_path1, &config.file_path2, Day::parse)?;
+            puzzle.sum_extrapolated_values(true, config.method)?
+        },
+    };
     println!("Result of part 2: {result}");
 
     Ok(())
@@ -90,23 +377,181 @@ pub fn run(config: config::Config) -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    use aoc_core::rng::{Rng, SeededRng};
+    use std::time::Instant;
+
     const EXAMPLE: &str = "
 0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45
 ";
 
+    fn part1(input: &str, method: Method) -> Result<Answer, Box<dyn Error>> {
+        Ok(Puzzle::<i64>::from_input(input)?.sum_extrapolated_values(false, method)?)
+    }
+
+    fn part2(input: &str, method: Method) -> Result<Answer, Box<dyn Error>> {
+        Ok(Puzzle::<i64>::from_input(input)?.sum_extrapolated_values(true, method)?)
+    }
+
     #[test]
     fn example_part1() -> Result<(), Box<dyn Error>> {
-        let result = part1(EXAMPLE.trim())?;
+        let result = part1(EXAMPLE.trim(), Method::DifferenceTable)?;
         assert_eq!(result, 114);
         Ok(())
     }
 
     #[test]
     fn example_part2() -> Result<(), Box<dyn Error>> {
-        let result = part2(EXAMPLE.trim())?;
+        let result = part2(EXAMPLE.trim(), Method::DifferenceTable)?;
         assert_eq!(result, 2);
         Ok(())
     }
+
+    #[test]
+    fn example_both_directions() -> Result<(), Box<dyn Error>> {
+        let values: Vec<_> = EXAMPLE.trim().lines().map(|line| Ok(Sequence::<i64>::from_str(line)?.extrapolate_both()))
+            .collect::<Result<_, std::num::ParseIntError>>()?;
+        assert_eq!(values, vec![(-3, 18), (0, 28), (5, 68)]);
+        assert_eq!(Sequence::<i64>::from_str("4 4 4")?.extrapolate_both(), (4, 4));
+        assert_eq!(Sequence::<i64>::from_str("")?.extrapolate_both(), (0, 0));
+        for method in [Method::DifferenceTable, Method::Binomial] {
+            let (previous, next) = sum_extrapolated_both(EXAMPLE.trim(), method)?;
+            assert_eq!((previous, next), (Answer::from(2), Answer::from(114)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn element_types() -> Result<(), Box<dyn Error>> {
+        for method in [Method::DifferenceTable, Method::Binomial] {
+            let expected = (Answer::from(2), Answer::from(114));
+            assert_eq!(sum_extrapolated_both_as::<i32>(EXAMPLE.trim(), method)?, expected);
+            assert_eq!(sum_extrapolated_both_as::<i128>(EXAMPLE.trim(), method)?, expected);
+            assert_eq!(sum_extrapolated_both_as::<BigInt>(EXAMPLE.trim(), method)?, expected);
+        }
+        // Values beyond i64 and beyond i128
+        let wide = "4000000000000000000 8000000000000000000";
+        assert_eq!(Sequence::<i128>::from_str(wide)?.extrapolate_both(), (0, 12_000_000_000_000_000_000));
+        let huge = "100000000000000000000000000000000000000 200000000000000000000000000000000000000";
+        let (previous, next) = sum_extrapolated_both_as::<BigInt>(huge, Method::DifferenceTable)?;
+        assert_eq!(previous, 0);
+        assert_eq!(next.to_string(), "300000000000000000000000000000000000000");
+        assert_eq!(sum_extrapolated_both_as::<BigInt>(huge, Method::Binomial)?, (previous, next));
+        assert!(sum_extrapolated_both_as::<i32>(wide, Method::DifferenceTable).is_err());
+        assert!(sum_extrapolated_both_as::<i128>(huge, Method::DifferenceTable).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn example_report() -> Result<(), Box<dyn Error>> {
+        let report = Report::from_input(&format!("{}\n0 0 0\n1 2 4 8\n", EXAMPLE.trim()))?;
+        let degrees: Vec<_> = report.sequences.iter().map(|p| p.degree()).collect();
+        assert_eq!(degrees, vec![Some(1), Some(2), Some(3), Some(0), None]);
+        assert_eq!(report.sequences[3].zero_depth, Some(0));
+        assert_eq!(report.max_degree(), Some(3));
+        assert_eq!(report.mean_degree(), Some(1.5));
+        assert_eq!(report.degree_histogram()[&None], 1);
+        let csv = report.to_csv();
+        assert!(csv.starts_with("line,length,zero_depth,degree\n1,6,2,1\n"));
+        assert!(csv.ends_with("5,4,,\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn example_binomial() -> Result<(), Box<dyn Error>> {
+        assert_eq!(part1(EXAMPLE.trim(), Method::Binomial)?, 114);
+        assert_eq!(part2(EXAMPLE.trim(), Method::Binomial)?, 2);
+        // 1 3 6 10 15 21 are the triangular numbers t(1) to t(6)
+        let sequence = Sequence::<i64>::from_str("1 3 6 10 15 21")?;
+        assert_eq!(sequence.extrapolate_binomial(4, false)?.to_i128(), Some(55));
+        assert_eq!(sequence.extrapolate_binomial(1_000_000, true)?.to_i128(), Some(999_999 * 999_998 / 2));
+        assert_eq!(sum_extrapolated_ahead(EXAMPLE.trim(), 3, false)?.to_i128(), Some(24 + 45 + 146));
+        assert!(sum_extrapolated_ahead(EXAMPLE.trim(), 0, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn binomial_step_limits() -> Result<(), Box<dyn Error>> {
+        let input = "0 3 6 9 12 15\n1 3 6 10 15 21";
+        // The last element is at position 5, so the farthest position of i64::MAX is this far ahead
+        let steps = i64::MAX as u64 - 5;
+        let m = i64::MAX as i128;
+        let expected = 3 * m + (m + 1) * (m + 2) / 2;
+        assert_eq!(sum_extrapolated_ahead(input, steps, false)?.to_i128(), Some(expected));
+        for steps in [steps + 1, 1 << 63, u64::MAX] {
+            let error = sum_extrapolated_ahead(input, steps, false).unwrap_err();
+            assert_eq!(error.to_string(), TOO_FAR);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn binomial_beyond_128_bits() -> Result<(), Box<dyn Error>> {
+        // 1, 2, 4, ..., 2^20 is extrapolated by the polynomial of degree 20 through these points
+        let sequence = Sequence::<i64>::from_str(&(0..21).map(|i| (1 << i).to_string()).collect::<Vec<_>>().join(" "))?;
+        assert_eq!(sequence.extrapolate_binomial(1, false)?.to_i128(), Some(sequence.extrapolate_both().1 as i128));
+        let far = sequence.extrapolate_binomial(1 << 40, false)?;
+        assert_eq!(far.to_i128(), None);
+        assert!(!far.is_negative());
+        Ok(())
+    }
+
+    /// Random sequences of length 21 generated by polynomials of degree up to 6 with small
+    /// coefficients
+    fn random_sequences(count: usize, seed: u64) -> Vec<Sequence> {
+        let mut rng = SeededRng::new(seed);
+        let mut next = || rng.next_u64() as i64;
+        (0..count).map(|_| {
+            let coefficients: Vec<i64> = (0..=next() % 7).map(|_| next() % 7 - 3).collect();
+            let elements = (0..21i64)
+                .map(|x| coefficients.iter().rev().fold(0, |acc, c| acc * x + c))
+                .collect();
+            Sequence { elements }
+        }).collect()
+    }
+
+    /// Extends the difference table of the sequence `steps` times and returns the last value.
+    fn extrapolate_by_table(sequence: &Sequence, steps: u64) -> i128 {
+        // Last element of each row of the difference table
+        let mut lasts = Vec::new();
+        let mut row: Vec<i128> = sequence.elements.iter().map(|&a| a as i128).collect();
+        while !row.is_empty() {
+            lasts.push(*row.last().unwrap());
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        for _ in 0..steps {
+            for level in (0..lasts.len().saturating_sub(1)).rev() {
+                lasts[level] += lasts[level + 1];
+            }
+        }
+        lasts.first().copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn binomial_matches_difference_table() -> Result<(), Box<dyn Error>> {
+        for sequence in random_sequences(100, 9) {
+            for steps in [1, 2, 17, 100] {
+                assert_eq!(sequence.extrapolate_binomial(steps, false)?.to_i128(), Some(extrapolate_by_table(&sequence, steps)));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_binomial() {
+        let sequences = random_sequences(1000, 42);
+        for steps in [1, 1000, 100_000] {
+            let start = Instant::now();
+            let table: Vec<i128> = sequences.iter().map(|sequence| extrapolate_by_table(sequence, steps)).collect();
+            println!("Difference table, {steps} step(s): {:?}", start.elapsed());
+            let start = Instant::now();
+            let binomial: Vec<i128> = sequences.iter()
+                .map(|sequence| sequence.extrapolate_binomial(steps, false).unwrap().to_i128().unwrap())
+                .collect();
+            println!("Binomial coefficients, {steps} step(s): {:?}", start.elapsed());
+            assert_eq!(table, binomial);
+        }
+    }
 }
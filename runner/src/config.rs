@@ -0,0 +1,106 @@
+const USAGE: &str = "Usage: aoc2023 <day> <file for part 1> <file for part 2> [<options of the day>...]\n       aoc2023 validate --day <day> --input <file>\n       aoc2023 report --inputs <directory> --output <file>\n       aoc2023 timings record --inputs <directory> [--db <file>]\n       aoc2023 timings history --day <day> [--db <file>]";
+
+/// File in which timings are recorded unless another one is given
+const DEFAULT_TIMINGS_DB: &str = "timings.sqlite";
+
+pub enum Config {
+    /// Solve both parts of a day, passing any further options on to it
+    Solve { day: u32, file_path1: String, file_path2: String, options: Vec<String> },
+    /// Only parse the input of a day and report problems with it
+    Validate { day: u32, file_path: String },
+    /// Solve every day whose input is in a directory and write the answers, timings and
+    /// visualizations as an HTML page
+    Report { inputs: String, output: String },
+    /// Solve every day whose input is in a directory and record how long the parts took at the
+    /// current git commit
+    RecordTimings { inputs: String, db: String },
+    /// Show the recorded times of the parts of a day at each commit
+    TimingHistory { day: u32, db: String },
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        if args.get(1).is_some_and(|arg| arg == "validate") {
+            return Self::build_validate(&args[2..]);
+        }
+        if args.get(1).is_some_and(|arg| arg == "report") {
+            return Self::build_report(&args[2..]);
+        }
+        if args.get(1).is_some_and(|arg| arg == "timings") {
+            return Self::build_timings(&args[2..]);
+        }
+        if args.len() < 4 {
+            return Err(USAGE);
+        }
+        Ok(Config::Solve {
+            day: Self::parse_day(&args[1])?,
+            file_path1: args[2].clone(),
+            file_path2: args[3].clone(),
+            options: args[4..].to_vec(),
+        })
+    }
+
+    fn build_validate(args: &[String]) -> Result<Config, &'static str> {
+        let mut day = None;
+        let mut file_path = None;
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or(USAGE)?;
+            match flag.as_str() {
+                "--day" => day = Some(Self::parse_day(value)?),
+                "--input" => file_path = Some(value.clone()),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config::Validate {
+            day: day.ok_or(USAGE)?,
+            file_path: file_path.ok_or(USAGE)?,
+        })
+    }
+
+    fn build_report(args: &[String]) -> Result<Config, &'static str> {
+        let mut inputs = None;
+        let mut output = None;
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or(USAGE)?;
+            match flag.as_str() {
+                "--inputs" => inputs = Some(value.clone()),
+                "--output" => output = Some(value.clone()),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config::Report {
+            inputs: inputs.ok_or(USAGE)?,
+            output: output.ok_or(USAGE)?,
+        })
+    }
+
+    fn build_timings(args: &[String]) -> Result<Config, &'static str> {
+        let [command, flags @ ..] = args else {
+            return Err(USAGE);
+        };
+        let mut inputs = None;
+        let mut day = None;
+        let mut db = DEFAULT_TIMINGS_DB.to_owned();
+        let mut iter = flags.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or(USAGE)?;
+            match (command.as_str(), flag.as_str()) {
+                ("record", "--inputs") => inputs = Some(value.clone()),
+                ("history", "--day") => day = Some(Self::parse_day(value)?),
+                (_, "--db") => db = value.clone(),
+                _ => return Err(USAGE),
+            }
+        }
+        match command.as_str() {
+            "record" => Ok(Config::RecordTimings { inputs: inputs.ok_or(USAGE)?, db }),
+            "history" => Ok(Config::TimingHistory { day: day.ok_or(USAGE)?, db }),
+            _ => Err(USAGE),
+        }
+    }
+
+    fn parse_day(arg: &str) -> Result<u32, &'static str> {
+        arg.parse().map_err(|_| "Could not parse day")
+    }
+}
@@ -0,0 +1,253 @@
+pub mod config;
+mod report;
+mod timings;
+
+use std::any::Any;
+use std::error::Error;
+use std::fs;
+use std::panic::{self, UnwindSafe};
+
+use aoc_core::answer::PartSolver;
+use aoc_core::render::FrameSource;
+use aoc_core::validate::Issue;
+
+use config::Config;
+
+/// Runs the solution of a day, given the arguments expected by that day's binary
+type Solver = fn(&[String]) -> Result<(), Box<dyn Error>>;
+
+/// Runs only the parser of a day on an input and returns the problems found
+type Validator = fn(&str) -> Vec<Issue>;
+
+/// Creates the frames visualizing the solution of a day for an input
+type Visualizer = fn(&str) -> Result<Box<dyn FrameSource>, Box<dyn Error>>;
+
+struct Day {
+    number: u32,
+    solve: Solver,
+    validate: Validator,
+    /// Both parts with their default options
+    parts: [PartSolver; 2],
+}
+
+#[allow(unused_macros)] // Unused if no days are enabled
+macro_rules! day {
+    ($number:literal, $day:ident) => { day!($number, $day, $day::config::Config) };
+    ($number:literal, $day:ident, $config:ty) => {
+        Day {
+            number: $number,
+            solve: |args: &[String]| -> Result<(), Box<dyn Error>> { $day::run(<$config>::build(args)?) },
+            validate: $day::validate,
+            parts: $day::PARTS,
+        }
+    };
+}
+
+/// The days whose solutions are compiled in, which depends on the enabled features
+const DAYS: &[Day] = &[
+    #[cfg(feature = "day1")] day!(1, day1, day1::Config),
+    #[cfg(feature = "day2")] day!(2, day2),
+    #[cfg(feature = "day3")] day!(3, day3),
+    #[cfg(feature = "day4")] day!(4, day4),
+    #[cfg(feature = "day5")] day!(5, day5),
+    #[cfg(feature = "day6")] day!(6, day6),
+    #[cfg(feature = "day7")] day!(7, day7),
+    #[cfg(feature = "day8")] day!(8, day8),
+    #[cfg(feature = "day9")] day!(9, day9),
+    #[cfg(feature = "day10")] day!(10, day10),
+    #[cfg(feature = "day11")] day!(11, day11),
+    #[cfg(feature = "day13")] day!(13, day13),
+    #[cfg(feature = "day14")] day!(14, day14),
+    #[cfg(feature = "day15")] day!(15, day15),
+    #[cfg(feature = "day16")] day!(16, day16),
+    #[cfg(feature = "day17")] day!(17, day17),
+    #[cfg(feature = "day18")] day!(18, day18),
+    #[cfg(feature = "day19")] day!(19, day19),
+    #[cfg(feature = "day20")] day!(20, day20),
+    #[cfg(feature = "day21")] day!(21, day21),
+    #[cfg(feature = "day22")] day!(22, day22),
+    #[cfg(feature = "day23")] day!(23, day23),
+    #[cfg(feature = "day24")] day!(24, day24),
+    #[cfg(feature = "day25")] day!(25, day25),
+];
+
+/// The days that can visualize their solutions
+const VISUALIZERS: &[(u32, Visualizer)] = &[
+    #[cfg(feature = "day10")] (10, |input| Ok(Box::new(day10::TraversalFrames::from_input(input)?))),
+    #[cfg(feature = "day14")] (14, |input| Ok(Box::new(day14::TiltFrames::from_input(input)?))),
+    #[cfg(feature = "day17")] (17, |input| Ok(Box::new(day17::ReplayFrames::from_input(input)?))),
+    #[cfg(feature = "day21")] (21, |input| Ok(Box::new(day21::ReachFrames::from_input(input)?))),
+    #[cfg(feature = "day22")] (22, |input| Ok(Box::new(day22::SettleFrames::from_input(input)?))),
+    #[cfg(feature = "day23")] (23, |input| Ok(Box::new(day23::PathFrames::from_input(input)?))),
+];
+
+/// Returns the days whose solutions are compiled in.
+pub fn available_days() -> Vec<u32> {
+    DAYS.iter().map(|day| day.number).collect()
+}
+
+fn day(number: u32) -> Result<&'static Day, String> {
+    DAYS.iter()
+        .find(|day| day.number == number)
+        .ok_or_else(|| format!("No solution for day {number} available; available days: {:?}", available_days()))
+}
+
+/// Returns the message a panic was raised with, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+/// Calls a solver of a day, turning a panic into an error so that one misbehaving day does not
+/// abort a run over several days.
+fn catch_panic<T>(number: u32, f: impl FnOnce() -> Result<T, Box<dyn Error>> + UnwindSafe) -> Result<T, Box<dyn Error>> {
+    panic::catch_unwind(f).unwrap_or_else(|payload| {
+        Err(format!("Day {number} panicked: {}", panic_message(payload.as_ref())).into())
+    })
+}
+
+/// Parses the input of a day without solving it and prints the problems found. Fails if there are
+/// any.
+fn validate(number: u32, file_path: &str) -> Result<(), Box<dyn Error>> {
+    let day = day(number)?;
+    let contents = fs::read_to_string(file_path)?;
+    let issues = catch_panic(number, || Ok((day.validate)(&contents)))?;
+    if issues.is_empty() {
+        println!("Day {number}: no issues found in {file_path}");
+        return Ok(());
+    }
+    println!("Day {number}: {} issue(s) found in {file_path}", issues.len());
+    for issue in &issues {
+        println!("  {issue}");
+    }
+    Err(format!("Input {file_path} is not valid for day {number}").into())
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    match config {
+        Config::Solve { day: number, file_path1, file_path2, options } => {
+            let day = day(number)?;
+            let mut args = vec![format!("day{number}"), file_path1, file_path2];
+            args.extend(options);
+            catch_panic(number, || (day.solve)(&args))
+        },
+        Config::Validate { day, file_path } => validate(day, &file_path),
+        Config::Report { inputs, output } => report::write_report(&inputs, &output),
+        Config::RecordTimings { inputs, db } => timings::record(&inputs, &db),
+        Config::TimingHistory { day, db } => timings::history(day, &db),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn available_days_sorted() {
+        let days = available_days();
+        assert!(days.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn unknown_day() {
+        assert!(day(12).is_err());
+        assert!(day(26).is_err());
+    }
+
+    #[test]
+    fn validate_arguments() {
+        let config = Config::build(&args(&["aoc2023", "validate", "--input", "in.txt", "--day", "7"]));
+        assert!(matches!(config, Ok(Config::Validate { day: 7, file_path }) if file_path == "in.txt"));
+        assert!(Config::build(&args(&["aoc2023", "validate", "--day", "7"])).is_err());
+        assert!(Config::build(&args(&["aoc2023", "validate", "--day", "x", "--input", "in.txt"])).is_err());
+        assert!(Config::build(&args(&["aoc2023", "validate", "--day"])).is_err());
+    }
+
+    #[test]
+    fn report_arguments() {
+        let config = Config::build(&args(&["aoc2023", "report", "--output", "r.html", "--inputs", "in"]));
+        assert!(matches!(config, Ok(Config::Report { inputs, output }) if inputs == "in" && output == "r.html"));
+        assert!(Config::build(&args(&["aoc2023", "report", "--inputs", "in"])).is_err());
+    }
+
+    #[test]
+    fn panic_to_error() {
+        std::panic::set_hook(Box::new(|_| ()));
+        let failed = catch_panic(17, || -> Result<(), Box<dyn Error>> { panic!("Goal unreachable") });
+        let formatted = catch_panic(17, || -> Result<(), Box<dyn Error>> { panic!("Goal {} unreachable", 1) });
+        let _ = std::panic::take_hook();
+        assert_eq!(failed.map_err(|e| e.to_string()), Err("Day 17 panicked: Goal unreachable".to_owned()));
+        assert_eq!(formatted.map_err(|e| e.to_string()), Err("Day 17 panicked: Goal 1 unreachable".to_owned()));
+        assert_eq!(catch_panic(17, || Ok(42)).ok(), Some(42));
+    }
+
+    #[test]
+    fn timings_arguments() {
+        let config = Config::build(&args(&["aoc2023", "timings", "history", "--day", "17"]));
+        assert!(matches!(config, Ok(Config::TimingHistory { day: 17, db }) if db == "timings.sqlite"));
+        let config = Config::build(&args(&["aoc2023", "timings", "record", "--db", "t.sqlite", "--inputs", "in"]));
+        assert!(matches!(config, Ok(Config::RecordTimings { inputs, db }) if inputs == "in" && db == "t.sqlite"));
+        assert!(Config::build(&args(&["aoc2023", "timings", "record", "--day", "17"])).is_err());
+        assert!(Config::build(&args(&["aoc2023", "timings", "history"])).is_err());
+        assert!(Config::build(&args(&["aoc2023", "timings"])).is_err());
+    }
+
+    #[test]
+    fn visualizers_available() {
+        assert!(VISUALIZERS.iter().all(|(number, _)| day(*number).is_ok()));
+    }
+
+    /// Inputs that are malformed for most days, such as empty or truncated ones, ones with
+    /// unexpected characters and numbers that do not fit into machine integers
+    const ADVERSARIAL_INPUTS: &[&str] = &[
+        "", "\n", " ", "x", "#", ".", "S", "0", "1", "-1", ",", ":", "?", "é", "\u{0}",
+        "S-", "-S", "7S", "S\n|", "|\nS", "F7\nSJ", "S7\nLJ", ".S.\n...", "SS\nSS",
+        "1 2 3\n4", "1\n\n2", "99999999999999999999999999", "-9223372036854775808",
+        "Game 1: 3 blue", "Card 1: 1 | 1", "seeds: 1\n\nx-to-y map:\n1 2", "Time: 1\nDistance:",
+        "32T3K", "32T3K x", "LR\n\nAAA = (BBB, BBB)", "L\n\nAAA = (AAA, AAA)", "R\n\nZZZ = (ZZZ, ZZZ)",
+        "0 0 0\n1", "#.#\n.#", "#.\n.#\n\n#", "O#\n.O", "rn=1,cm-,=", "rn=x", "-", "=",
+        "|-\n/\\", "\\/", "19\n1", "R 6 (#70c710)", "R x (#zzzzzz)", "U 0 (#000000)",
+        "in{}\n\n{x=1}", "in{a<1:in,R}\n\n{a=0}", "in{x>0:ab,A}\n\n{x=1,m=1,a=1,s=1}",
+        "1,0,1~1,2,1", "1,1,1~0,0,0", "0,0,0~0,0,0\n0,0,0~0,0,0", "1,0~1,2,1",
+        // Numbers at the limits of the integer types of the days
+        "Card 4294967295: 1 | 1\nCard 1: 1 | 1", "in{x>-1:A,R}\n\n{x=1,m=1,a=1,s=1}",
+        "300000000000000, 300000000000000, 0 @ 9223372036854775807, 1, 0\n300000000000000, 299999999999999, 0 @ 1, 9223372036854775807, 0",
+        "-9223372036854775808, 9223372036854775807, 0 @ -9223372036854775808, 9223372036854775807, 1\n\
+         9223372036854775807, -9223372036854775808, 0 @ 9223372036854775807, -9223372036854775808, -1\n\
+         0, 0, -9223372036854775808 @ 1, -1, 9223372036854775807",
+    ];
+
+    #[test]
+    fn adversarial_inputs_do_not_panic() {
+        std::panic::set_hook(Box::new(|_| ()));
+        let mut panics = Vec::new();
+        for day in DAYS {
+            for input in ADVERSARIAL_INPUTS {
+                for (i, part) in day.parts.iter().enumerate() {
+                    if std::panic::catch_unwind(|| part(input)).is_err() {
+                        panics.push(format!("day {} part {} on {input:?}", day.number, i + 1));
+                    }
+                }
+                if std::panic::catch_unwind(|| (day.validate)(input)).is_err() {
+                    panics.push(format!("day {} validation on {input:?}", day.number));
+                }
+            }
+        }
+        let _ = std::panic::take_hook();
+        assert!(panics.is_empty(), "Panicked: {panics:#?}");
+    }
+
+    #[test]
+    fn solve_arguments() {
+        let config = Config::build(&args(&["aoc2023", "3", "a.txt", "b.txt"]));
+        assert!(matches!(config, Ok(Config::Solve { day: 3, options, .. }) if options.is_empty()));
+        let config = Config::build(&args(&["aoc2023", "7", "a.txt", "b.txt", "--serve"]));
+        assert!(matches!(config, Ok(Config::Solve { day: 7, options, .. }) if options == ["--serve"]));
+        assert!(Config::build(&args(&["aoc2023", "3", "a.txt"])).is_err());
+    }
+}
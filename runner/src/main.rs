@@ -0,0 +1,11 @@
+use std::env;
+use std::error::Error;
+
+use runner::config::Config;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().collect();
+    let config = Config::build(&args)?;
+    runner::run(config)?;
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! An HTML page with the answers of all days, how long they took and visualizations of some of
+//! them.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use aoc_core::answer::Answer;
+use aoc_core::render::{self, escape_xml, Frame};
+
+use crate::{catch_panic, Day, DAYS, VISUALIZERS};
+
+/// Outcome of solving one part
+pub(crate) struct PartRun {
+    pub(crate) answer: Result<Answer, String>,
+    pub(crate) time: Duration,
+}
+
+/// Solves both parts of a day on its input and measures how long each part takes.
+pub(crate) fn solve_parts(day: &Day, input: &str) -> Vec<PartRun> {
+    day.parts.iter().map(|part| {
+        let start = Instant::now();
+        let answer = catch_panic(day.number, || part(input)).map_err(|e| e.to_string());
+        PartRun { answer, time: start.elapsed() }
+    }).collect()
+}
+
+/// Outcome of solving one day on its input
+struct DayReport {
+    number: u32,
+    parts: Vec<PartRun>,
+    /// The last frame of the visualization, if the day has one
+    visualization: Option<Result<Frame, String>>,
+}
+
+impl DayReport {
+    fn new(day: &Day, input: &str) -> Self {
+        let parts = solve_parts(day, input);
+        let visualization = VISUALIZERS.iter()
+            .find(|(number, _)| *number == day.number)
+            .map(|(_, visualize)| catch_panic(day.number, || {
+                let mut frames = visualize(input)?;
+                render::last_frame(frames.as_mut()).ok_or_else(|| "No frames".into())
+            }).map_err(|e| e.to_string()));
+        Self { number: day.number, parts, visualization }
+    }
+}
+
+/// Finds the inputs named `day<n>.txt` in the directory for the days that are compiled in, sorted
+/// by day.
+pub(crate) fn find_inputs(dir: &Path) -> Result<Vec<(&'static Day, PathBuf)>, Box<dyn Error>> {
+    Ok(DAYS.iter()
+        .map(|day| (day, dir.join(format!("day{}.txt", day.number))))
+        .filter(|(_, path)| path.is_file())
+        .collect())
+}
+
+fn to_html(reports: &[DayReport]) -> String {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code 2023</title>\n",
+        "<style>\ntable { border-collapse: collapse; }\ntd, th { border: 1px solid #ccc; padding: 2px 8px; }\n",
+        "td.time { text-align: right; }\n.error { color: #c00; }\n</style>\n</head>\n<body>\n",
+        "<h1>Advent of Code 2023</h1>\n<table>\n",
+        "<tr><th>Day</th><th>Part 1</th><th>Time</th><th>Part 2</th><th>Time</th></tr>\n",
+    ));
+    for report in reports {
+        html.push_str(&format!("<tr><td>{}</td>", report.number));
+        for part in &report.parts {
+            match &part.answer {
+                Ok(answer) => html.push_str(&format!("<td>{}</td>", escape_xml(&answer.to_string()))),
+                Err(e) => html.push_str(&format!("<td class=\"error\">{}</td>", escape_xml(e))),
+            }
+            html.push_str(&format!("<td class=\"time\">{:.1} ms</td>", part.time.as_secs_f64() * 1000.0));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    for report in reports {
+        let Some(visualization) = &report.visualization else { continue };
+        html.push_str(&format!("<h2>Day {}</h2>\n", report.number));
+        match visualization {
+            Ok(frame) => html.push_str(&format!(
+                "<figure>\n{}<figcaption>{}</figcaption>\n</figure>\n",
+                render::frame_to_svg(frame), escape_xml(&frame.caption)
+            )),
+            Err(e) => html.push_str(&format!("<p class=\"error\">{}</p>\n", escape_xml(e))),
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Solves every day whose input `day<n>.txt` is in the directory `inputs` and writes the report to
+/// `output`.
+pub fn write_report(inputs: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let inputs = find_inputs(Path::new(inputs))?;
+    if inputs.is_empty() {
+        return Err("No inputs named day<n>.txt found".into());
+    }
+    let mut reports = Vec::new();
+    for (day, path) in inputs {
+        println!("Solving day {}", day.number);
+        reports.push(DayReport::new(day, &fs::read_to_string(path)?));
+    }
+    fs::write(output, to_html(&reports))?;
+    println!("Wrote report of {} day(s) to {output}", reports.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html() {
+        let reports = [
+            DayReport {
+                number: 3,
+                parts: vec![
+                    PartRun { answer: Ok(42.into()), time: Duration::from_millis(5) },
+                    PartRun { answer: Err("Bad <input>".to_owned()), time: Duration::ZERO },
+                ],
+                visualization: None,
+            },
+            DayReport {
+                number: 10,
+                parts: vec![],
+                visualization: Some(Ok(Frame::from_text("Inside: 1 tiles", "F7\nLJ"))),
+            },
+        ];
+        let html = to_html(&reports);
+        assert!(html.contains("<tr><td>3</td><td>42</td><td class=\"time\">5.0 ms</td><td class=\"error\">Bad &lt;input&gt;</td>"));
+        assert!(html.contains("<h2>Day 10</h2>\n<figure>\n<svg "));
+        assert!(html.contains("<figcaption>Inside: 1 tiles</figcaption>"));
+        assert!(!html.contains("<h2>Day 3</h2>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "day10")]
+    fn report_of_inputs() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("aoc2023-report-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let example = "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...\n";
+        fs::write(dir.join("day10.txt"), example)?;
+        fs::write(dir.join("day12.txt"), "")?;
+        let output = dir.join("report.html");
+        write_report(dir.to_str().ok_or("Invalid path")?, output.to_str().ok_or("Invalid path")?)?;
+        let html = fs::read_to_string(&output)?;
+        assert!(html.contains("<tr><td>10</td><td>8</td>"));
+        assert!(html.contains("<svg "));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
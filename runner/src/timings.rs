@@ -0,0 +1,206 @@
+//! A history of how long the parts of each day took, kept in an SQLite database with one row per
+//! git commit, day and part.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+
+use crate::day;
+use crate::report::{find_inputs, solve_parts};
+
+/// The time a part took when solved at a commit
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    commit: String,
+    day: u32,
+    /// 1-based
+    part: usize,
+    time: Duration,
+}
+
+/// The timings recorded so far. The rows keep the order in which they were first recorded, since
+/// replacing a time keeps the row ID.
+struct TimingDb {
+    connection: Connection,
+}
+
+impl TimingDb {
+    /// Opens the database at `path`, creating it if it does not exist yet.
+    fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::create(Connection::open(path)?)
+    }
+
+    fn create(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS timings (
+                commit_hash TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                part INTEGER NOT NULL,
+                micros INTEGER NOT NULL,
+                PRIMARY KEY (commit_hash, day, part)
+            )",
+            (),
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Records the time of a part at a commit, replacing an earlier time for the same commit, day
+    /// and part.
+    fn insert(&self, entry: &Entry) -> rusqlite::Result<()> {
+        let micros = i64::try_from(entry.time.as_micros()).unwrap_or(i64::MAX);
+        self.connection.execute(
+            "INSERT INTO timings (commit_hash, day, part, micros) VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (commit_hash, day, part) DO UPDATE SET micros = excluded.micros",
+            params![entry.commit, entry.day, entry.part, micros],
+        )?;
+        Ok(())
+    }
+
+    /// The times recorded for a day, in the order they were first recorded
+    fn entries(&self, day: u32) -> rusqlite::Result<Vec<Entry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT commit_hash, day, part, micros FROM timings WHERE day = ?1 ORDER BY rowid",
+        )?;
+        let rows = statement.query_map([day], |row| Ok(Entry {
+            commit: row.get(0)?,
+            day: row.get(1)?,
+            part: row.get(2)?,
+            time: Duration::from_micros(row.get(3)?),
+        }))?;
+        rows.collect()
+    }
+}
+
+/// Returns a table of the times of both parts of a day at each commit, given the entries of the day
+/// in the order they were recorded. The commits are in the order they were first recorded, with the
+/// change from the previous commit with a time for the part.
+fn history_table(entries: &[Entry]) -> String {
+    let mut commits: Vec<&str> = Vec::new();
+    for entry in entries {
+        if !commits.contains(&entry.commit.as_str()) {
+            commits.push(&entry.commit);
+        }
+    }
+    let mut table = format!("{:<14} {:>12} {:>8} {:>12} {:>8}\n", "Commit", "Part 1", "Change", "Part 2", "Change");
+    let mut previous: [Option<Duration>; 2] = [None, None];
+    for commit in commits {
+        let mut row = format!("{commit:<14}");
+        for (part, previous) in previous.iter_mut().enumerate() {
+            let time = entries.iter()
+                .find(|entry| entry.commit == commit && entry.part == part + 1)
+                .map(|entry| entry.time);
+            let Some(time) = time else {
+                row.push_str(&format!(" {:>12} {:>8}", "-", ""));
+                continue;
+            };
+            let change = previous
+                .filter(|previous| !previous.is_zero())
+                .map(|previous| format!("{:+.1}%", (time.as_secs_f64() / previous.as_secs_f64() - 1.0) * 100.0))
+                .unwrap_or_default();
+            row.push_str(&format!(" {:>9.1} ms {change:>8}", time.as_secs_f64() * 1000.0));
+            *previous = Some(time);
+        }
+        table.push_str(row.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Returns the abbreviated hash of the checked-out commit, with "-dirty" appended if there are
+/// uncommitted changes, so that their timings are not attributed to the commit.
+fn current_commit() -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output()?;
+    if !output.status.success() {
+        return Err(format!("Could not determine the commit: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    let clean = Command::new("git").args(["diff", "--quiet", "HEAD"]).status()?.success();
+    Ok(if clean { commit } else { format!("{commit}-dirty") })
+}
+
+/// Solves every day whose input `day<n>.txt` is in the directory `inputs` and records how long the
+/// parts took at the current commit in the database `db`. Parts that fail are not recorded.
+pub fn record(inputs: &str, db: &str) -> Result<(), Box<dyn Error>> {
+    let inputs = find_inputs(Path::new(inputs))?;
+    if inputs.is_empty() {
+        return Err("No inputs named day<n>.txt found".into());
+    }
+    let commit = current_commit()?;
+    let timings = TimingDb::open(Path::new(db))?;
+    for (day, path) in inputs {
+        let runs = solve_parts(day, &fs::read_to_string(path)?);
+        print!("Day {:>2}:", day.number);
+        for (i, run) in runs.iter().enumerate() {
+            match &run.answer {
+                Ok(_) => {
+                    print!(" part {} {:.1} ms", i + 1, run.time.as_secs_f64() * 1000.0);
+                    timings.insert(&Entry { commit: commit.clone(), day: day.number, part: i + 1, time: run.time })?;
+                },
+                Err(e) => print!(" part {} failed ({e})", i + 1),
+            }
+        }
+        println!();
+    }
+    println!("Recorded timings at commit {commit} in {db}");
+    Ok(())
+}
+
+/// Prints the times of a day recorded in the database `db` at each commit.
+pub fn history(number: u32, db: &str) -> Result<(), Box<dyn Error>> {
+    day(number)?;
+    if !Path::new(db).exists() {
+        return Err(format!("No timings recorded in {db}").into());
+    }
+    let entries = TimingDb::open(Path::new(db))?.entries(number)?;
+    if entries.is_empty() {
+        return Err(format!("No timings of day {number} recorded in {db}").into());
+    }
+    println!("Day {number}");
+    print!("{}", history_table(&entries));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(commit: &str, day: u32, part: usize, ms: u64) -> Entry {
+        Entry { commit: commit.to_owned(), day, part, time: Duration::from_millis(ms) }
+    }
+
+    #[test]
+    fn insert_and_replace() -> rusqlite::Result<()> {
+        let timings = TimingDb::create(Connection::open_in_memory()?)?;
+        timings.insert(&entry("abc1234", 17, 1, 20))?;
+        timings.insert(&entry("abc1234", 17, 2, 40))?;
+        timings.insert(&entry("abc1234", 3, 1, 5))?;
+        timings.insert(&entry("abc1234", 17, 1, 10))?;
+        // The replaced time keeps its place
+        assert_eq!(timings.entries(17)?, [entry("abc1234", 17, 1, 10), entry("abc1234", 17, 2, 40)]);
+        assert_eq!(timings.entries(3)?, [entry("abc1234", 3, 1, 5)]);
+        assert!(timings.entries(4)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn history() -> rusqlite::Result<()> {
+        let timings = TimingDb::create(Connection::open_in_memory()?)?;
+        for entry in [entry("aaa", 17, 1, 100), entry("aaa", 17, 2, 200), entry("aaa", 3, 1, 1),
+            entry("bbb", 17, 1, 50), entry("ccc", 17, 1, 50), entry("ccc", 17, 2, 300)] {
+            timings.insert(&entry)?;
+        }
+        let table = history_table(&timings.entries(17)?);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines, [
+            "Commit               Part 1   Change       Part 2   Change",
+            "aaa                100.0 ms              200.0 ms",
+            "bbb                 50.0 ms   -50.0%            -",
+            "ccc                 50.0 ms    +0.0%     300.0 ms   +50.0%",
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+//! Scrambling puzzle inputs so that they can be shared, for example in bug reports, without giving
+//! away the original input. The scrambled input has the same answers as the original.
+
+use std::collections::HashMap;
+
+use aoc_core::rng::{Rng, SeededRng};
+
+/// Days whose inputs can be anonymized
+pub const DAYS: [u32; 3] = [4, 8, 22];
+
+/// Scrambles the input of the given day, using `seed` for all random choices.
+pub fn anonymize(day: u32, input: &str, seed: u64) -> Result<String, String> {
+    let mut rng = SeededRng::new(seed);
+    match day {
+        4 => scramble_cards(input, &mut rng),
+        8 => scramble_network(input, &mut rng),
+        22 => scramble_bricks(input, &mut rng),
+        _ => Err(format!("Cannot anonymize inputs of day {day}, only of days {DAYS:?}")),
+    }
+}
+
+/// Replaces every number on the scratchcards by another one, the same for all cards, so that each
+/// card keeps its number of matches. Card IDs and their order stay the same.
+fn scramble_cards(input: &str, rng: &mut impl Rng) -> Result<String, String> {
+    let mut cards = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let error = || format!("Line {}: expected a card like \"Card 1: 41 48 | 83 86 17\"", i + 1);
+        let (head, numbers) = line.split_once(':').ok_or_else(error)?;
+        let (winning, own) = numbers.split_once('|').ok_or_else(error)?;
+        let parse = |numbers: &str| -> Result<Vec<u64>, String> {
+            numbers.split_whitespace().map(|n| n.parse().map_err(|_| error())).collect()
+        };
+        cards.push((head, parse(winning)?, parse(own)?));
+    }
+    let mut distinct: Vec<u64> = cards.iter().flat_map(|(_, winning, own)| winning.iter().chain(own)).copied().collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    // Pick distinct replacements from 1 to 99 like in real inputs, or more if there are more numbers
+    let mut replacements: Vec<u64> = (1..=distinct.len().max(99) as u64).collect();
+    rng.shuffle(&mut replacements);
+    let replace: HashMap<u64, u64> = distinct.into_iter().zip(replacements).collect();
+    let width = replace.values().max().map_or(1, |n| n.to_string().len());
+    let format = |numbers: &[u64]| {
+        numbers.iter().map(|n| format!("{:>width$}", replace[n])).collect::<Vec<_>>().join(" ")
+    };
+    Ok(cards.iter().map(|(head, winning, own)| format!("{head}: {} | {}\n", format(winning), format(own))).collect())
+}
+
+/// Gives the nodes of the network new random labels and shuffles their order. AAA and ZZZ keep
+/// their labels, and other labels keep ending in A or Z if they did. With probability 1/2, left
+/// and right are swapped both in the instructions and in the nodes.
+fn scramble_network(input: &str, rng: &mut impl Rng) -> Result<String, String> {
+    // The instructions may be wrapped over several lines up to the first blank line
+    let num_instruction_lines = input.lines().take_while(|line| !line.trim().is_empty()).count();
+    if num_instruction_lines == 0 {
+        return Err("Empty input".to_owned());
+    }
+    let instructions: String = input.lines().take(num_instruction_lines).flat_map(str::chars).filter(|c| !c.is_whitespace()).collect();
+    let mut nodes = Vec::new();
+    for (i, line) in input.lines().enumerate().skip(num_instruction_lines).filter(|(_, line)| !line.is_empty()) {
+        let error = || format!("Line {}: expected a node like \"AAA = (BBB, CCC)\"", i + 1);
+        let (label, neighbors) = line.split_once(" = (").ok_or_else(error)?;
+        let (left, right) = neighbors.strip_suffix(')').and_then(|n| n.split_once(", ")).ok_or_else(error)?;
+        nodes.push([label, left, right]);
+    }
+
+    // Unused labels for each kind of node, by the letter they end in
+    let mut pools: HashMap<char, Vec<String>> = HashMap::new();
+    for a in 'A'..='Z' {
+        for b in 'A'..='Z' {
+            for c in 'A'..='Z' {
+                let label = format!("{a}{b}{c}");
+                if label != "AAA" && label != "ZZZ" {
+                    let kind = if c == 'A' || c == 'Z' { c } else { '-' };
+                    pools.entry(kind).or_default().push(label);
+                }
+            }
+        }
+    }
+    for pool in pools.values_mut() {
+        rng.shuffle(pool);
+    }
+    let mut relabel: HashMap<&str, String> = HashMap::from([("AAA", "AAA".to_owned()), ("ZZZ", "ZZZ".to_owned())]);
+    for &label in nodes.iter().flatten() {
+        if !relabel.contains_key(label) {
+            let kind = label.chars().last().filter(|&c| c == 'A' || c == 'Z').unwrap_or('-');
+            let new_label = pools.get_mut(&kind).and_then(Vec::pop).ok_or("Too many nodes for three-letter labels")?;
+            relabel.insert(label, new_label);
+        }
+    }
+
+    let swap = rng.below(2) == 1;
+    let instructions: String = instructions.chars()
+        .map(|c| match c {
+            'L' if swap => 'R',
+            'R' if swap => 'L',
+            c => c,
+        })
+        .collect();
+    let mut lines: Vec<String> = nodes.iter()
+        .map(|[label, left, right]| {
+            let (left, right) = if swap { (right, left) } else { (left, right) };
+            format!("{} = ({}, {})\n", relabel[label], relabel[left], relabel[right])
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+    Ok(format!("{instructions}\n\n{}", lines.concat()))
+}
+
+/// Moves, mirrors and rotates the snapshot of the bricks horizontally, lifts it by a few units and
+/// shuffles the order of the bricks.
+fn scramble_bricks(input: &str, rng: &mut impl Rng) -> Result<String, String> {
+    let mut bricks = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let error = || format!("Line {}: expected a brick like \"1,0,1~1,2,1\"", i + 1);
+        let coordinates: Vec<i64> = line.split(['~', ',']).map(|c| c.parse().map_err(|_| error())).collect::<Result<_, _>>()?;
+        let brick: [i64; 6] = coordinates.try_into().map_err(|_| error())?;
+        bricks.push(brick);
+    }
+    let max = |i| bricks.iter().flat_map(|brick: &[i64; 6]| [brick[i], brick[i + 3]]).max().unwrap_or(0);
+    let (max_x, max_y) = (max(0), max(1));
+    let (mirror_x, mirror_y, rotate) = (rng.below(2) == 1, rng.below(2) == 1, rng.below(2) == 1);
+    let (dx, dy, dz) = (rng.below(10) as i64, rng.below(10) as i64, rng.below(10) as i64);
+    let mut lines: Vec<String> = bricks.iter()
+        .map(|brick| {
+            let ends = [[brick[0], brick[1], brick[2]], [brick[3], brick[4], brick[5]]].map(|[x, y, z]| {
+                let x = if mirror_x { max_x - x } else { x };
+                let y = if mirror_y { max_y - y } else { y };
+                let (x, y) = if rotate { (y, x) } else { (x, y) };
+                format!("{},{},{}", x + dx, y + dy, z + dz)
+            });
+            format!("{}~{}\n", ends[0], ends[1])
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+    Ok(lines.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    const CARDS: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+    const NETWORK: &str = "\
+LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+";
+
+    const GHOSTS: &str = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
+
+    const BRICKS: &str = "\
+1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9
+";
+
+    /// Asserts that the part solved on the input gives the same answer before and after scrambling
+    /// it with several seeds. Parts that fail on the original input are skipped.
+    fn assert_same_answer(day: u32, input: &str, part: impl Fn(&str) -> Result<String, Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        let Ok(expected) = part(input) else { return Ok(()) };
+        for seed in 0..8 {
+            let scrambled = anonymize(day, input, seed)?;
+            assert_eq!(part(&scrambled)?, expected, "day {day}, seed {seed}:\n{scrambled}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn answers_are_kept() -> Result<(), Box<dyn Error>> {
+        for part in day4::PARTS {
+            assert_same_answer(4, CARDS, |input| Ok(part(input)?.to_string()))?;
+        }
+        for input in [NETWORK, GHOSTS, "L L\nR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n"] {
+            for part in day8::PARTS {
+                assert_same_answer(8, input, |input| Ok(part(input)?.to_string()))?;
+            }
+        }
+        for part in day22::PARTS {
+            assert_same_answer(22, BRICKS, |input| Ok(part(input)?.to_string()))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn inputs_are_scrambled() -> Result<(), Box<dyn Error>> {
+        let cards = anonymize(4, CARDS, 1)?;
+        assert_ne!(cards, CARDS);
+        assert!(cards.lines().zip(CARDS.lines()).all(|(new, old)| new[..7] == old[..7] && new.len() == old.len()));
+        let network = anonymize(8, GHOSTS, 1)?;
+        assert!(!network.contains("11") && !network.contains("XXX"));
+        assert_eq!(network.lines().filter(|line| line.get(2..3) == Some("A")).count(), 2);
+        assert_ne!(anonymize(22, BRICKS, 1)?, anonymize(22, BRICKS, 2)?);
+        // Seeds make the output reproducible
+        assert_eq!(anonymize(22, BRICKS, 3)?, anonymize(22, BRICKS, 3)?);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert_eq!(anonymize(4, "Card 1: 1 2 3", 0), Err("Line 1: expected a card like \"Card 1: 41 48 | 83 86 17\"".to_owned()));
+        assert_eq!(anonymize(8, "LR\n\nAAA = BBB", 0), Err("Line 3: expected a node like \"AAA = (BBB, CCC)\"".to_owned()));
+        assert_eq!(anonymize(22, "1,0,1~1,2", 0), Err("Line 1: expected a brick like \"1,0,1~1,2,1\"".to_owned()));
+        assert!(anonymize(5, "", 0).is_err());
+    }
+}
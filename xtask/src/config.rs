@@ -0,0 +1,58 @@
+const USAGE: &str = "Usage: xtask diff-answers <old revision> <new revision> --inputs <directory> [--days <n,n,...>]
+       xtask anonymize <day> <input file> [--seed <n>]";
+
+pub enum Config {
+    /// Build the runner at two revisions, run the days on the same inputs and compare the answers
+    /// and timings
+    DiffAnswers { old_rev: String, new_rev: String, inputs: String, days: Option<Vec<u32>> },
+    /// Print a scrambled version of a puzzle input that has the same answers, using a random seed
+    /// unless one is given
+    Anonymize { day: u32, input: String, seed: Option<u64> },
+}
+
+impl Config {
+    pub fn build(args: &[String]) -> Result<Config, &'static str> {
+        match args.get(1).map(String::as_str) {
+            Some("diff-answers") => Self::build_diff_answers(&args[2..]),
+            Some("anonymize") => Self::build_anonymize(&args[2..]),
+            _ => Err(USAGE),
+        }
+    }
+
+    fn build_diff_answers(args: &[String]) -> Result<Config, &'static str> {
+        let [old_rev, new_rev, flags @ ..] = args else {
+            return Err(USAGE);
+        };
+        let mut inputs = None;
+        let mut days = None;
+        let mut iter = flags.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or(USAGE)?;
+            match flag.as_str() {
+                "--inputs" => inputs = Some(value.clone()),
+                "--days" => days = Some(
+                    value.split(',').map(|day| day.parse().map_err(|_| "Could not parse day")).collect::<Result<_, _>>()?
+                ),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config::DiffAnswers { old_rev: old_rev.clone(), new_rev: new_rev.clone(), inputs: inputs.ok_or(USAGE)?, days })
+    }
+
+    fn build_anonymize(args: &[String]) -> Result<Config, &'static str> {
+        let [day, input, flags @ ..] = args else {
+            return Err(USAGE);
+        };
+        let day = day.parse().map_err(|_| "Could not parse day")?;
+        let mut seed = None;
+        let mut iter = flags.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next().ok_or(USAGE)?;
+            match flag.as_str() {
+                "--seed" => seed = Some(value.parse().map_err(|_| "Could not parse seed")?),
+                _ => return Err(USAGE),
+            }
+        }
+        Ok(Config::Anonymize { day, input: input.clone(), seed })
+    }
+}
@@ -0,0 +1,218 @@
+//! Development tasks that work on the repository as a whole.
+
+pub mod anonymize;
+pub mod config;
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use config::Config;
+
+/// Outcome of running one day with one build of the runner
+#[derive(Debug, PartialEq)]
+struct DayRun {
+    /// Answer lines printed by the day, such as "Result of part 1: 42", or an error message
+    answers: Result<Vec<String>, String>,
+    time: Duration,
+}
+
+/// Returns the lines of the runner output that report answers. Days 1 to 3 used to print
+/// "Sum for part" instead of "Result of part", which is normalized so that old revisions can be
+/// compared with new ones.
+fn extract_answers(stdout: &str) -> Vec<String> {
+    stdout.lines()
+        .filter_map(|line| match line.strip_prefix("Sum for part") {
+            Some(rest) => Some(format!("Result of part{rest}")),
+            None => line.starts_with("Result of part").then(|| line.to_owned()),
+        })
+        .collect()
+}
+
+/// A build of the runner at some revision, checked out in a temporary git worktree
+struct Build {
+    rev: String,
+    worktree: PathBuf,
+    binary: PathBuf,
+}
+
+impl Build {
+    fn new(rev: &str, work_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let worktree = work_dir.join(rev.replace(|c: char| !c.is_ascii_alphanumeric(), "_"));
+        run_command(Command::new("git").args(["worktree", "add", "--detach"]).arg(&worktree).arg(rev))?;
+        let build = Self { rev: rev.to_owned(), binary: worktree.join("runner/target/release/aoc2023"), worktree };
+        println!("Building {rev}");
+        run_command(Command::new("cargo")
+            .args(["build", "--release", "--manifest-path"])
+            .arg(build.worktree.join("runner/Cargo.toml"))
+            .env_remove("CARGO_TARGET_DIR"))?;
+        Ok(build)
+    }
+
+    fn run_day(&self, day: u32, input: &Path) -> DayRun {
+        let start = Instant::now();
+        let output = Command::new(&self.binary).arg(day.to_string()).arg(input).arg(input).output();
+        let time = start.elapsed();
+        let answers = match output {
+            Ok(output) if output.status.success() => Ok(extract_answers(&String::from_utf8_lossy(&output.stdout))),
+            Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_owned()),
+            Err(e) => Err(e.to_string()),
+        };
+        DayRun { answers, time }
+    }
+}
+
+impl Drop for Build {
+    fn drop(&mut self) {
+        let result = run_command(Command::new("git").args(["worktree", "remove", "--force"]).arg(&self.worktree));
+        if let Err(e) = result {
+            eprintln!("Could not remove worktree of {}: {e}", self.rev);
+        }
+    }
+}
+
+fn run_command(command: &mut Command) -> Result<(), Box<dyn Error>> {
+    let status = command.status()?;
+    if !status.success() {
+        return Err(format!("Command {command:?} failed with {status}").into());
+    }
+    Ok(())
+}
+
+/// Finds the inputs named `day<n>.txt` in the directory, sorted by day.
+fn find_inputs(dir: &Path, days: Option<&[u32]>) -> Result<Vec<(u32, PathBuf)>, Box<dyn Error>> {
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let Some(day) = name.strip_prefix("day").and_then(|rest| rest.strip_suffix(".txt")) else {
+            continue;
+        };
+        let Ok(day) = day.parse() else { continue };
+        if days.is_none_or(|days| days.contains(&day)) {
+            inputs.push((day, path));
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Describes how the run of a day differs between the old and the new build.
+fn describe_diff(day: u32, old: &DayRun, new: &DayRun) -> String {
+    let verdict = match (&old.answers, &new.answers) {
+        (Ok(old), Ok(new)) if old == new => "same answers".to_owned(),
+        (Ok(old), Ok(new)) => format!("ANSWERS DIFFER\n    old: {}\n    new: {}", old.join("; "), new.join("; ")),
+        (Err(e), Ok(_)) => format!("fixed (old failed: {e})"),
+        (Ok(_), Err(e)) => format!("BROKEN (new fails: {e})"),
+        (Err(_), Err(e)) => format!("fails in both (new: {e})"),
+    };
+    let (old_ms, new_ms) = (old.time.as_secs_f64() * 1000.0, new.time.as_secs_f64() * 1000.0);
+    format!("Day {day:>2}: {old_ms:>9.1} ms -> {new_ms:>9.1} ms  {verdict}")
+}
+
+/// Builds the runner at both revisions, runs each day on its input with both builds and prints
+/// the differences. Fails if any answers differ.
+fn diff_answers(old_rev: &str, new_rev: &str, inputs: &Path, days: Option<&[u32]>) -> Result<(), Box<dyn Error>> {
+    let inputs = find_inputs(inputs, days)?;
+    if inputs.is_empty() {
+        return Err("No inputs named day<n>.txt found".into());
+    }
+    let work_dir = std::env::temp_dir().join(format!("aoc2023-diff-answers-{}", std::process::id()));
+    fs::create_dir_all(&work_dir)?;
+    let old = Build::new(old_rev, &work_dir)?;
+    let new = Build::new(new_rev, &work_dir)?;
+    let mut num_differences = 0;
+    for (day, input) in &inputs {
+        let (old_run, new_run) = (old.run_day(*day, input), new.run_day(*day, input));
+        if old_run.answers != new_run.answers {
+            num_differences += 1;
+        }
+        println!("{}", describe_diff(*day, &old_run, &new_run));
+    }
+    drop((old, new));
+    fs::remove_dir_all(&work_dir)?;
+    if num_differences > 0 {
+        return Err(format!("Answers differ for {num_differences} day(s)").into());
+    }
+    Ok(())
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    match config {
+        Config::DiffAnswers { old_rev, new_rev, inputs, days } => {
+            diff_answers(&old_rev, &new_rev, Path::new(&inputs), days.as_deref())
+        },
+        Config::Anonymize { day, input, seed } => {
+            let seed = match seed {
+                Some(seed) => seed,
+                None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as u64,
+            };
+            eprintln!("Anonymizing day {day} with seed {seed}");
+            print!("{}", anonymize::anonymize(day, &fs::read_to_string(input)?, seed)?);
+            Ok(())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn answer_lines() {
+        let stdout = "Part 1: Reading file a\nResult of part 1: 42\nCycle at i = 3\nSum for part 2: 7\n";
+        assert_eq!(extract_answers(stdout), vec!["Result of part 1: 42", "Result of part 2: 7"]);
+    }
+
+    #[test]
+    fn diff_descriptions() {
+        let run = |answers: Result<&[&str], &str>, ms| DayRun {
+            answers: answers.map(|a| a.iter().map(|s| s.to_string()).collect()).map_err(str::to_owned),
+            time: Duration::from_millis(ms),
+        };
+        let same = describe_diff(5, &run(Ok(&["a"]), 10), &run(Ok(&["a"]), 2));
+        assert_eq!(same, "Day  5:      10.0 ms ->       2.0 ms  same answers");
+        let differ = describe_diff(5, &run(Ok(&["a", "b"]), 1), &run(Ok(&["a", "c"]), 1));
+        assert!(differ.ends_with("ANSWERS DIFFER\n    old: a; b\n    new: a; c"));
+        assert!(describe_diff(5, &run(Ok(&[]), 1), &run(Err("boom"), 1)).ends_with("BROKEN (new fails: boom)"));
+    }
+
+    #[test]
+    fn inputs_by_day() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("xtask-inputs-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        for name in ["day10.txt", "day2.txt", "notes.txt", "dayx.txt"] {
+            fs::write(dir.join(name), "")?;
+        }
+        let days = |filter: Option<&[u32]>| -> Result<Vec<u32>, Box<dyn Error>> {
+            Ok(find_inputs(&dir, filter)?.into_iter().map(|(day, _)| day).collect())
+        };
+        assert_eq!(days(None)?, vec![2, 10]);
+        assert_eq!(days(Some(&[10]))?, vec![10]);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn diff_answers_arguments() {
+        let config = Config::build(&args(&["xtask", "diff-answers", "main", "HEAD", "--inputs", "in", "--days", "1,7"]));
+        assert!(matches!(config, Ok(Config::DiffAnswers { days: Some(days), .. }) if days == vec![1, 7]));
+        assert!(Config::build(&args(&["xtask", "diff-answers", "main", "HEAD"])).is_err());
+        assert!(Config::build(&args(&["xtask", "build"])).is_err());
+    }
+
+    #[test]
+    fn anonymize_arguments() {
+        let config = Config::build(&args(&["xtask", "anonymize", "8", "input.txt", "--seed", "42"]));
+        assert!(matches!(config, Ok(Config::Anonymize { day: 8, seed: Some(42), .. })));
+        assert!(matches!(Config::build(&args(&["xtask", "anonymize", "8", "input.txt"])), Ok(Config::Anonymize { seed: None, .. })));
+        assert!(Config::build(&args(&["xtask", "anonymize", "eight", "input.txt"])).is_err());
+        assert!(Config::build(&args(&["xtask", "anonymize", "8", "input.txt", "--seed"])).is_err());
+    }
+}